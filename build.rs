@@ -0,0 +1,31 @@
+//! Embeds the commit the binary is built from as `BLVM_GIT_COMMIT` (`blvm version`). Builds
+//! outside a git checkout (crates.io, Docker contexts without `.git`) can set it in the
+//! environment instead.
+
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=BLVM_GIT_COMMIT");
+    for path in [".git/HEAD", ".git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+    let commit = std::env::var("BLVM_GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.is_empty())
+        .or_else(|| {
+            let output = Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        });
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=BLVM_GIT_COMMIT={commit}");
+    }
+}
@@ -0,0 +1,56 @@
+/*
+ * blvm.h - C ABI for embedding a blvm node (build with `--features ffi`; links libblvm)
+ *
+ * Functions are blocking and thread-safe for a given node. On failure they return NULL or -1
+ * and blvm_last_error() describes the failure on the calling thread, including a panic inside
+ * the library (unless it was built with panic = "abort"). Strings returned by the library are
+ * freed with blvm_string_free().
+ */
+#ifndef BLVM_H
+#define BLVM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct BlvmNode BlvmNode;
+
+/* event_json is a JSON object such as {"type":"tip","hash":"...","height":123}, valid only
+ * during the call. Runs on a library thread; must not call blvm_node_* functions. */
+typedef void (*BlvmEventCallback)(const char *event_json, void *user_data);
+
+/* Start a node and wait until its RPC server answers. network: mainnet, testnet, signet or
+ * regtest. config_path (a blvm.toml: node settings and rpc_user / rpc_password; binary-only
+ * sections are not started) and rpc_addr ("host:port") may be NULL. */
+BlvmNode *blvm_node_start(const char *network, const char *data_dir, const char *config_path,
+                          const char *rpc_addr);
+
+/* Stop the node, wait for it to exit and free it. Returns 0 on a clean stop, 1 when the node
+ * stopped but reported an error (see blvm_last_error), -1 on failure. */
+int blvm_node_stop(BlvmNode *node);
+
+/* Call any RPC method; params_json is a JSON array or NULL. Returns the JSON result. */
+char *blvm_node_rpc(const BlvmNode *node, const char *method, const char *params_json);
+
+/* Best block hash; its height is stored in *height when height is not NULL. */
+char *blvm_node_tip(const BlvmNode *node, uint64_t *height);
+
+/* Submit a serialized block. Returns 0 when accepted, -1 when rejected or on error. */
+int blvm_node_submit_block(const BlvmNode *node, const uint8_t *data, size_t len);
+
+/* Replace the event callback; NULL unregisters. user_data must outlive the registration. */
+int blvm_node_set_event_callback(BlvmNode *node, BlvmEventCallback callback, void *user_data);
+
+/* Last failure on this thread, or NULL. Do not free. */
+const char *blvm_last_error(void);
+
+void blvm_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BLVM_H */
@@ -0,0 +1,66 @@
+//! Address relay privacy policy (`[addr_relay]`)
+//!
+//! `advertise = "never"` keeps the node's own address out of announcements even with inbound
+//! connections enabled: `blvm start` folds it into the node's `enable_self_advertisement`.
+//! Disabling addr gossip, limiting addr response sizes and randomizing advertisement timing are
+//! declined: blvm-node's address relay exposes no setting for any of them. Unknown keys are
+//! rejected, so such settings fail at config load instead of being ignored.
+
+use serde::{Deserialize, Serialize};
+
+/// Whether the node announces its own address
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Advertise {
+    /// When listening and `enable_self_advertisement` is on
+    #[default]
+    Auto,
+    Never,
+}
+
+/// `[addr_relay]` config section
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct AddrRelayConfig {
+    pub advertise: Advertise,
+}
+
+impl AddrRelayConfig {
+    /// Policy the node applies: `advertise` is `never` unless self-advertisement is also on
+    pub fn resolve(&self, enable_self_advertisement: bool) -> Self {
+        let mut policy = self.clone();
+        if !enable_self_advertisement {
+            policy.advertise = Advertise::Never;
+        }
+        policy
+    }
+
+    /// One-line summary for `start --dry-run` and the startup log
+    pub fn describe(&self) -> String {
+        match self.advertise {
+            Advertise::Never => "own address not advertised".to_string(),
+            Advertise::Auto => "own address advertised".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_resolution() {
+        let config: AddrRelayConfig = toml::from_str("").unwrap();
+        assert_eq!(config.resolve(true).advertise, Advertise::Auto);
+        // Outbound-only or `enable_self_advertisement = false` never advertises
+        assert_eq!(config.resolve(false).advertise, Advertise::Never);
+
+        let never: AddrRelayConfig = toml::from_str("advertise = \"never\"\n").unwrap();
+        assert_eq!(never.resolve(true).advertise, Advertise::Never);
+        assert!(never.describe().contains("not advertised"));
+
+        // Keys the node has no way to apply are refused rather than ignored
+        assert!(toml::from_str::<AddrRelayConfig>("gossip = false\n").is_err());
+        assert!(toml::from_str::<AddrRelayConfig>("max_addr_response = 250\n").is_err());
+    }
+}
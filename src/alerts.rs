@@ -0,0 +1,321 @@
+//! Local alert rules (`[alerts]`)
+//!
+//! Rules compare a node metric against a threshold; a rule fires once the condition has held
+//! for `for_minutes` and resolves when it clears. Active alerts are kept in `alerts.json` in
+//! the data directory so `blvm status` can show them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// State file name in the data directory
+pub const ALERTS_FILE: &str = "alerts.json";
+
+/// Value a rule is evaluated against
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// Connected peers
+    PeerCount,
+    /// Minutes since the tip block's timestamp
+    MinutesSinceBlock,
+    /// Free space on the data directory's filesystem, in GB (10^9 bytes)
+    DiskFreeGb,
+}
+
+impl Metric {
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::PeerCount => "peer_count",
+            Metric::MinutesSinceBlock => "minutes_since_block",
+            Metric::DiskFreeGb => "disk_free_gb",
+        }
+    }
+}
+
+/// One `[[alerts.rules]]` entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: Metric,
+    /// Fire when the metric is below this value
+    #[serde(default)]
+    pub below: Option<f64>,
+    /// Fire when the metric is above this value
+    #[serde(default)]
+    pub above: Option<f64>,
+    /// How long the condition must hold before firing
+    #[serde(default)]
+    pub for_minutes: u64,
+}
+
+impl AlertRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("alert rule without a name".to_string());
+        }
+        if self.below.is_none() && self.above.is_none() {
+            return Err(format!(
+                "alert rule '{}' needs `below` or `above`",
+                self.name
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn breached(&self, value: f64) -> bool {
+        self.below.is_some_and(|t| value < t) || self.above.is_some_and(|t| value > t)
+    }
+
+    fn describe(&self, value: f64) -> String {
+        let condition = match (self.below, self.above) {
+            (Some(t), _) if value < t => format!("below {t}"),
+            (_, Some(t)) if value > t => format!("above {t}"),
+            _ => "ok".to_string(),
+        };
+        let held = if self.for_minutes > 0 {
+            format!(" for {}m", self.for_minutes)
+        } else {
+            String::new()
+        };
+        format!("{} = {value} ({condition}{held})", self.metric.name())
+    }
+}
+
+/// `[alerts]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AlertsConfig {
+    pub enabled: bool,
+    /// Seconds between evaluations
+    pub check_interval_secs: u64,
+    /// URL that receives a JSON POST when an alert fires or resolves
+    pub webhook: Option<String>,
+    /// Shell command run when an alert fires or resolves (`BLVM_ALERT_*` in its environment)
+    pub exec: Option<String>,
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_secs: 30,
+            webhook: None,
+            exec: None,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Current metric values; missing ones leave their rules unchanged
+pub type Metrics = BTreeMap<Metric, f64>;
+
+/// An alert that has fired and not yet resolved
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActiveAlert {
+    pub name: String,
+    pub message: String,
+    /// Unix time the condition started holding
+    pub since: u64,
+    /// Unix time the alert fired
+    pub fired_at: u64,
+}
+
+/// Alert state change to notify about
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transition {
+    Fired(ActiveAlert),
+    Resolved(ActiveAlert),
+}
+
+impl Transition {
+    pub fn status(&self) -> &'static str {
+        match self {
+            Transition::Fired(_) => "firing",
+            Transition::Resolved(_) => "resolved",
+        }
+    }
+
+    pub fn alert(&self) -> &ActiveAlert {
+        match self {
+            Transition::Fired(alert) | Transition::Resolved(alert) => alert,
+        }
+    }
+
+    /// Webhook body
+    pub fn payload(&self, now: u64) -> Value {
+        let alert = self.alert();
+        json!({
+            "alert": alert.name,
+            "status": self.status(),
+            "message": alert.message,
+            "since": alert.since,
+            "time": now,
+        })
+    }
+}
+
+/// Persistent evaluation state
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AlertState {
+    /// Rule name -> Unix time its condition started holding (not yet fired)
+    pub pending: BTreeMap<String, u64>,
+    pub active: Vec<ActiveAlert>,
+}
+
+impl AlertState {
+    /// Load from the data directory (empty state if missing or unreadable)
+    pub fn load(data_dir: &Path) -> Self {
+        std::fs::read_to_string(data_dir.join(ALERTS_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> anyhow::Result<()> {
+        let path = data_dir.join(ALERTS_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Evaluate `rules` against `metrics` at `now`; returns alerts that fired or resolved
+    pub fn evaluate(
+        &mut self,
+        rules: &[AlertRule],
+        metrics: &Metrics,
+        now: u64,
+    ) -> Vec<Transition> {
+        // Forget rules removed from the config
+        self.pending
+            .retain(|name, _| rules.iter().any(|r| &r.name == name));
+        self.active
+            .retain(|a| rules.iter().any(|r| r.name == a.name));
+
+        let mut transitions = Vec::new();
+        for rule in rules {
+            let Some(&value) = metrics.get(&rule.metric) else {
+                continue;
+            };
+            let active = self.active.iter().position(|a| a.name == rule.name);
+            if !rule.breached(value) {
+                self.pending.remove(&rule.name);
+                if let Some(i) = active {
+                    let mut alert = self.active.remove(i);
+                    alert.message = rule.describe(value);
+                    transitions.push(Transition::Resolved(alert));
+                }
+                continue;
+            }
+            match active {
+                Some(i) => self.active[i].message = rule.describe(value),
+                None => {
+                    let since = *self.pending.entry(rule.name.clone()).or_insert(now);
+                    if now.saturating_sub(since) >= rule.for_minutes * 60 {
+                        self.pending.remove(&rule.name);
+                        let alert = ActiveAlert {
+                            name: rule.name.clone(),
+                            message: rule.describe(value),
+                            since,
+                            fired_at: now,
+                        };
+                        self.active.push(alert.clone());
+                        transitions.push(Transition::Fired(alert));
+                    }
+                }
+            }
+        }
+        transitions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn low_peers() -> AlertRule {
+        AlertRule {
+            name: "low-peers".to_string(),
+            metric: Metric::PeerCount,
+            below: Some(4.0),
+            above: None,
+            for_minutes: 10,
+        }
+    }
+
+    fn peers(n: f64) -> Metrics {
+        Metrics::from([(Metric::PeerCount, n)])
+    }
+
+    #[test]
+    fn fires_after_hold_time_and_resolves() {
+        let rules = [low_peers()];
+        let mut state = AlertState::default();
+        assert!(state.evaluate(&rules, &peers(2.0), 1000).is_empty());
+        assert!(state.evaluate(&rules, &peers(1.0), 1300).is_empty());
+        let fired = state.evaluate(&rules, &peers(1.0), 1600);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].status(), "firing");
+        assert_eq!(fired[0].alert().since, 1000);
+        assert_eq!(fired[0].alert().message, "peer_count = 1 (below 4 for 10m)");
+        // Still breached: no repeat notification
+        assert!(state.evaluate(&rules, &peers(0.0), 1700).is_empty());
+        assert_eq!(state.active.len(), 1);
+        // Missing metric leaves the alert active
+        assert!(state.evaluate(&rules, &Metrics::new(), 1800).is_empty());
+        let resolved = state.evaluate(&rules, &peers(8.0), 1900);
+        assert_eq!(resolved[0].status(), "resolved");
+        assert!(state.active.is_empty());
+    }
+
+    #[test]
+    fn recovery_before_hold_time_resets() {
+        let rules = [low_peers()];
+        let mut state = AlertState::default();
+        state.evaluate(&rules, &peers(1.0), 0);
+        state.evaluate(&rules, &peers(5.0), 300);
+        assert!(state.evaluate(&rules, &peers(1.0), 700).is_empty());
+        assert_eq!(state.pending["low-peers"], 700);
+    }
+
+    #[test]
+    fn parses_config_and_validates() {
+        let config: AlertsConfig = toml::from_str(
+            r#"
+            webhook = "http://127.0.0.1:9000/hook"
+            [[rules]]
+            name = "stale-tip"
+            metric = "minutes_since_block"
+            above = 60
+            [[rules]]
+            name = "broken"
+            metric = "disk_free_gb"
+            "#,
+        )
+        .unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.rules[0].metric, Metric::MinutesSinceBlock);
+        assert!(config.rules[0].validate().is_ok());
+        assert!(config.rules[1].validate().is_err());
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut state = AlertState::default();
+        state.evaluate(
+            &[AlertRule {
+                for_minutes: 0,
+                ..low_peers()
+            }],
+            &peers(0.0),
+            5,
+        );
+        state.save(dir.path()).unwrap();
+        assert_eq!(AlertState::load(dir.path()), state);
+    }
+}
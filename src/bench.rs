@@ -0,0 +1,92 @@
+//! Micro-benchmarks on the host CPU (`blvm bench`)
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Timing of one implementation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub name: String,
+    pub iterations: u32,
+    pub elapsed: Duration,
+    /// Input bytes processed per iteration
+    pub bytes: usize,
+}
+
+impl Measurement {
+    pub fn per_iteration(&self) -> Duration {
+        self.elapsed / self.iterations.max(1)
+    }
+
+    /// MB/s of input processed
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        (self.bytes as f64 * self.iterations as f64) / secs / 1_000_000.0
+    }
+}
+
+/// Run `f` `iterations` times after one warm-up call
+pub fn measure(name: &str, bytes: usize, iterations: u32, mut f: impl FnMut()) -> Measurement {
+    f();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    Measurement {
+        name: name.to_string(),
+        iterations,
+        elapsed: start.elapsed(),
+        bytes,
+    }
+}
+
+/// `sha2`'s SHA-256 on block-sized data, headers, sighash preimages and merkle nodes
+pub fn hash_benchmarks(iterations: u32) -> Vec<Measurement> {
+    use crate::hash::sha256d;
+
+    let block: Vec<u8> = (0..1_000_000u32).map(|i| i as u8).collect();
+    let header = [0x5au8; 80];
+    // BIP143 preimage of a P2WPKH input
+    let preimage = [0xa5u8; 182];
+    let nodes: Vec<[u8; 64]> = (0..4096u32)
+        .map(|i| std::array::from_fn(|j| (i as usize * 64 + j) as u8))
+        .collect();
+    let small_runs = iterations.saturating_mul(1000);
+
+    vec![
+        measure("1 MB sha256d", block.len(), iterations, || {
+            black_box(sha256d(black_box(&block)));
+        }),
+        measure("header sha256d", header.len(), small_runs, || {
+            black_box(sha256d(black_box(&header)));
+        }),
+        measure(
+            "sighash preimage sha256d",
+            preimage.len(),
+            small_runs,
+            || {
+                black_box(sha256d(black_box(&preimage)));
+            },
+        ),
+        measure("merkle nodes", nodes.len() * 64, iterations, || {
+            for node in black_box(&nodes) {
+                black_box(sha256d(node));
+            }
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_benchmarks_cover_every_input() {
+        let results = hash_benchmarks(1);
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|m| m.bytes > 0));
+    }
+}
@@ -0,0 +1,29 @@
+//! `blvm bench`
+
+use crate::*;
+
+pub(crate) fn handle_bench(subcommand: &BenchCommand) -> Result<()> {
+    let results = match subcommand {
+        BenchCommand::Hash { iterations } => {
+            println!(
+                "SHA-256 backend: {} (what sha2 picks on this CPU)",
+                blvm::hash::Sha256Backend::detect().name()
+            );
+            blvm::bench::hash_benchmarks(*iterations)
+        }
+    };
+    print_measurements(&results);
+    Ok(())
+}
+
+fn print_measurements(results: &[blvm::bench::Measurement]) {
+    println!("{:<32} {:>12} {:>12}", "BENCHMARK", "PER RUN", "MB/S");
+    for m in results {
+        println!(
+            "{:<32} {:>12} {:>12.1}",
+            m.name,
+            format!("{:.1?}", m.per_iteration()),
+            m.throughput()
+        );
+    }
+}
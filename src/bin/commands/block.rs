@@ -0,0 +1,249 @@
+//! `blvm block`, `decode` and `analyze-block`
+
+use super::chain::chain_params;
+use super::tx::read_hex_or_file;
+use crate::*;
+
+/// getblock with the highest verbosity the node serves: 3 (prevouts and fees, needs undo
+/// data), then 2, then the raw block decoded locally into the verbosity 2 layout
+async fn getblock_detailed(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    hash: &Value,
+) -> Result<Value> {
+    for verbosity in [3, 2] {
+        match rpc_call_with_config(rpc_addr, config, "getblock", json!([hash, verbosity])).await {
+            Ok(block) if block["tx"].get(0).is_some_and(|tx| tx.is_object()) => return Ok(block),
+            Ok(_) => {}
+            Err(e) => tracing::debug!("getblock verbosity {verbosity} failed: {e}"),
+        }
+    }
+    let raw = rpc_call_with_config(rpc_addr, config, "getblock", json!([hash, 0])).await?;
+    let bytes = hex::decode(raw.as_str().unwrap_or_default()).context("Invalid block hex")?;
+    Ok(block_json(
+        &blvm::decode::Block::decode(&bytes).context("Invalid block")?,
+    ))
+}
+
+pub(crate) async fn handle_block(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    block: Option<&str>,
+    limit: usize,
+    as_json: bool,
+) -> Result<()> {
+    use blvm::block_fees::{TxSummary, summarize};
+    let hash = match block {
+        Some(b) if b.len() != 64 && b.parse::<u64>().is_ok() => {
+            rpc_call_with_config(rpc_addr, config, "getblockhash", json!([b.parse::<u64>()?]))
+                .await?
+        }
+        Some(b) => json!(b),
+        None => rpc_call_with_config(rpc_addr, config, "getbestblockhash", json!([])).await?,
+    };
+    let block = getblock_detailed(rpc_addr, config, &hash).await?;
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&block)?);
+        return Ok(());
+    }
+
+    let txs: Vec<TxSummary> = block["tx"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(TxSummary::from_value)
+        .collect();
+    println!("=== Block {} ===", block["hash"].as_str().unwrap_or("?"));
+    if let Some(height) = block["height"].as_u64() {
+        println!("Height: {height}");
+    }
+    if let Some(time) = block["time"].as_u64() {
+        println!("Time: {}", blvm::events::format_utc(time));
+    }
+    println!(
+        "Transactions: {}  Size: {} bytes  Weight: {}",
+        txs.len(),
+        block["size"].as_u64().unwrap_or(0),
+        block["weight"].as_u64().unwrap_or(0)
+    );
+    match summarize(&txs) {
+        Some(fees) => println!(
+            "Fees: {} sat  Feerate (sat/vB): min {:.1}, median {:.1}, max {:.1}",
+            fees.total, fees.min_feerate, fees.median_feerate, fees.max_feerate
+        ),
+        None if txs.len() > 1 => println!("Fees: unknown (node has no undo data for this block)"),
+        None => {}
+    }
+
+    let shown = if limit == 0 { txs.len() } else { limit };
+    println!(
+        "\n{:<64} {:>7} {:>7} {:>10} {:>9}",
+        "TXID", "VSIZE", "IN/OUT", "FEE (SAT)", "SAT/VB"
+    );
+    for tx in txs.iter().take(shown) {
+        println!(
+            "{:<64} {:>7} {:>7} {:>10} {:>9}",
+            tx.txid,
+            tx.vsize,
+            format!("{}/{}", tx.inputs, tx.outputs),
+            tx.fee.map_or("-".to_string(), |f| f.to_string()),
+            tx.feerate().map_or("-".to_string(), |r| format!("{r:.1}"))
+        );
+    }
+    if txs.len() > shown {
+        println!("... {} more (--txs 0 lists all)", txs.len() - shown);
+    }
+    Ok(())
+}
+
+/// A decoded block in the getblock verbosity 2 layout
+fn block_json(block: &blvm::decode::Block) -> Value {
+    let mut value = header_json(&block.header);
+    value["merkle_valid"] = json!(block.computed_merkle_root() == block.header.merkle_root);
+    value["size"] = json!(block.encode(true).len());
+    value["strippedsize"] = json!(block.encode(false).len());
+    value["weight"] = json!(block.weight());
+    value["nTx"] = json!(block.transactions.len());
+    value["tx"] = block.transactions.iter().map(transaction_json).collect();
+    value
+}
+
+pub(crate) fn handle_decode(subcommand: &DecodeCommand) -> Result<()> {
+    use blvm::decode::{Block, BlockHeader};
+    let value = match subcommand {
+        DecodeCommand::Block { input } => {
+            block_json(&Block::decode(&read_hex_or_file(input)?).context("Invalid block")?)
+        }
+        DecodeCommand::Header { hex } => {
+            let bytes = hex::decode(hex.trim()).context("Invalid hex")?;
+            header_json(&BlockHeader::decode(&bytes).context("Invalid block header")?)
+        }
+        DecodeCommand::Script { hex } => {
+            let script = hex::decode(hex.trim()).context("Invalid hex")?;
+            script_json(&script)
+        }
+    };
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+fn header_json(header: &blvm::decode::BlockHeader) -> Value {
+    use blvm::hash::to_display_hex;
+    let mut value = json!({
+        "hash": to_display_hex(&header.hash()),
+        "version": header.version,
+        "versionHex": format!("{:08x}", header.version),
+        "merkleroot": to_display_hex(&header.merkle_root),
+        "time": header.time,
+        "nonce": header.nonce,
+        "bits": format!("{:08x}", header.bits),
+        "difficulty": blvm::difficulty::difficulty_from_bits(header.bits),
+    });
+    if header.prev_blockhash != [0; 32] {
+        value["previousblockhash"] = json!(to_display_hex(&header.prev_blockhash));
+    }
+    value
+}
+
+fn script_json(script: &[u8]) -> Value {
+    json!({
+        "asm": blvm::script::to_asm(script),
+        "hex": hex::encode(script),
+        "type": blvm::script::classify(script),
+    })
+}
+
+fn transaction_json(tx: &blvm::decode::Transaction) -> Value {
+    use blvm::hash::to_display_hex;
+    let coinbase = tx.is_coinbase();
+    let vin: Vec<Value> = tx
+        .inputs
+        .iter()
+        .map(|input| {
+            let mut value = if coinbase {
+                json!({ "coinbase": hex::encode(&input.script_sig) })
+            } else {
+                json!({
+                    "txid": to_display_hex(&input.prev_txid),
+                    "vout": input.prev_vout,
+                    "scriptSig": {
+                        "asm": blvm::script::to_asm(&input.script_sig),
+                        "hex": hex::encode(&input.script_sig),
+                    },
+                })
+            };
+            if !input.witness.is_empty() {
+                value["txinwitness"] = input.witness.iter().map(hex::encode).collect();
+            }
+            value["sequence"] = json!(input.sequence);
+            value
+        })
+        .collect();
+    let vout: Vec<Value> = tx
+        .outputs
+        .iter()
+        .enumerate()
+        .map(|(n, output)| {
+            json!({
+                "value": output.value as f64 / 100_000_000.0,
+                "n": n,
+                "scriptPubKey": script_json(&output.script_pubkey),
+            })
+        })
+        .collect();
+    json!({
+        "txid": to_display_hex(&tx.txid()),
+        "hash": to_display_hex(&tx.wtxid()),
+        "version": tx.version,
+        "size": tx.encode(true).len(),
+        "vsize": tx.vsize(),
+        "weight": tx.weight(),
+        "locktime": tx.lock_time,
+        "vin": vin,
+        "vout": vout,
+    })
+}
+
+pub(crate) async fn handle_analyze_block(
+    input: &str,
+    height: Option<u32>,
+    network: &Network,
+    rpc_addr: Option<SocketAddr>,
+    config: &NodeConfig,
+) -> Result<()> {
+    use blvm::block_analysis::{Outcome, analyze};
+    let bytes = read_hex_or_file(input)?;
+    let entry = blvm::quarantine::load_entry(Path::new(input));
+    if let Some(entry) = &entry {
+        println!("Quarantined block {}", entry.hash);
+        println!("Reason: {}", entry.reason);
+    }
+    let height = height.or_else(|| entry.as_ref()?.height.map(|h| h as u32));
+    let params = chain_params(network)?;
+    let steps = analyze(&bytes, Some(&params), height);
+    let mut failed = steps.iter().any(|s| s.outcome == Outcome::Fail);
+    println!("=== Block analysis ({}) ===", params.network);
+    for step in &steps {
+        println!("{step}");
+    }
+
+    if let Some(rpc_addr) = rpc_addr {
+        let proposal = json!([{ "mode": "proposal", "data": hex::encode(&bytes) }]);
+        match rpc_call_with_config(rpc_addr, config, "getblocktemplate", proposal).await {
+            Ok(Value::Null) => println!("[PASS] node: accepted as a block proposal"),
+            Ok(reason) => {
+                failed = true;
+                println!(
+                    "[FAIL] node: {}",
+                    reason.as_str().unwrap_or(&reason.to_string())
+                );
+            }
+            Err(e) => println!("[SKIP] node: {e}"),
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
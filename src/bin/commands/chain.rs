@@ -0,0 +1,317 @@
+//! `blvm chain`: chain info, precious blocks, genesis, parameters and difficulty
+
+use crate::*;
+
+pub(crate) async fn handle_chain(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    format: Option<blvm::output::Format>,
+) -> Result<()> {
+    let info = rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await?;
+
+    if let Some(format) = format {
+        let field = |key: &str| info.get(key).cloned().unwrap_or(Value::Null);
+        let record = blvm::output::Table::record(vec![
+            ("chain", field("chain")),
+            ("blocks", field("blocks")),
+            ("headers", field("headers")),
+            ("best_block", field("bestblockhash")),
+            ("difficulty", field("difficulty")),
+            ("verification_progress", field("verificationprogress")),
+        ]);
+        print!("{}", record.render(format));
+        return Ok(());
+    }
+
+    println!("=== Blockchain Information ===");
+    println!(
+        "Chain: {}",
+        info.get("chain")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+    );
+    println!(
+        "Blocks: {}",
+        info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0)
+    );
+    println!(
+        "Headers: {}",
+        info.get("headers").and_then(|v| v.as_u64()).unwrap_or(0)
+    );
+    if let Some(hash) = info.get("bestblockhash").and_then(|v| v.as_str()) {
+        println!("Best Block: {hash}");
+    }
+    if let Some(diff) = info.get("difficulty").and_then(|v| v.as_f64()) {
+        println!("Difficulty: {diff:.2}");
+    }
+    if let Some(progress) = info.get("verificationprogress").and_then(|v| v.as_f64()) {
+        println!("Verification Progress: {:.2}%", progress * 100.0);
+    }
+
+    Ok(())
+}
+
+/// Ancestors walked looking for the fork point of a precious block
+const MAX_PRECIOUS_FORK_DEPTH: u64 = 100;
+
+pub(crate) async fn handle_chain_precious(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    hash: &str,
+) -> Result<()> {
+    let call = |method: &'static str, params: Value| {
+        rpc_call_with_config(rpc_addr, config, method, params)
+    };
+    if blvm::hash::from_display_hex(hash).is_none() {
+        anyhow::bail!("Invalid block hash '{hash}' (expected 64 hex characters)");
+    }
+    let work = |header: &Value| {
+        header
+            .get("chainwork")
+            .and_then(|v| v.as_str())
+            .map(|w| format!("{w:0>64}"))
+            .unwrap_or_default()
+    };
+    let block = call("getblockheader", json!([hash])).await?;
+    let tip_hash = call("getbestblockhash", json!([])).await?;
+    let tip_hash = tip_hash.as_str().unwrap_or_default().to_string();
+    let tip = call("getblockheader", json!([tip_hash])).await?;
+    let height = block.get("height").and_then(|v| v.as_u64()).unwrap_or(0);
+    let in_active_chain = block
+        .get("confirmations")
+        .and_then(|v| v.as_i64())
+        .is_some_and(|c| c >= 0);
+    if !in_active_chain && work(&block) < work(&tip) {
+        anyhow::bail!(
+            "Block {hash} has less work than the active tip {tip_hash}; preciousblock only breaks ties between equal-work chains"
+        );
+    }
+
+    println!("Tip before: {tip_hash}");
+    match call("preciousblock", json!([hash])).await {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("-32601") && !in_active_chain => {
+            // Node without preciousblock: step off the active branch and back; with equal
+            // work the node keeps the branch it switched to
+            println!(
+                "Node has no preciousblock RPC; switching branches with invalidate/reconsider"
+            );
+            let mut ancestor = block.clone();
+            for _ in 0..MAX_PRECIOUS_FORK_DEPTH {
+                if ancestor
+                    .get("confirmations")
+                    .and_then(|v| v.as_i64())
+                    .is_some_and(|c| c >= 0)
+                {
+                    break;
+                }
+                let prev = ancestor
+                    .get("previousblockhash")
+                    .cloned()
+                    .context("Fork point not found")?;
+                ancestor = call("getblockheader", json!([prev])).await?;
+            }
+            let fork_height = ancestor.get("height").and_then(|v| v.as_u64()).unwrap_or(0);
+            if ancestor
+                .get("confirmations")
+                .and_then(|v| v.as_i64())
+                .is_none_or(|c| c < 0)
+            {
+                anyhow::bail!("Fork point is more than {MAX_PRECIOUS_FORK_DEPTH} blocks back");
+            }
+            let first_active = call("getblockhash", json!([fork_height + 1])).await?;
+            call("invalidateblock", json!([first_active])).await?;
+            call("reconsiderblock", json!([first_active])).await?;
+        }
+        Err(e) => return Err(e),
+    }
+    let new_tip = call("getbestblockhash", json!([])).await?;
+    let new_tip = new_tip.as_str().unwrap_or_default();
+    println!("Tip after:  {new_tip}");
+    if in_active_chain {
+        println!(
+            "✅ Block {hash} (height {height}) is on the active chain and now preferred in ties"
+        );
+    } else if new_tip == tip_hash {
+        println!("Tip unchanged: the active chain still has more work than block {hash}'s branch");
+    } else {
+        println!("✅ Switched to the branch containing block {hash} (height {height})");
+    }
+    Ok(())
+}
+
+pub(crate) fn chain_params(network: &Network) -> Result<blvm::chain_params::ChainParams> {
+    let name = network_from_cli_enum(network);
+    blvm::chain_params::ChainParams::for_network(name)
+        .ok_or_else(|| anyhow::anyhow!("No chain parameters for network {name}"))
+}
+
+pub(crate) fn handle_chain_genesis(network: &Network) -> Result<()> {
+    let params = chain_params(network)?;
+    let genesis = &params.genesis;
+    println!("=== Genesis Block ({}) ===", params.network);
+    println!("Hash: {}", genesis.hash);
+    println!("Version: {}", genesis.version);
+    println!("Previous Block: {}", "0".repeat(64));
+    println!("Merkle Root: {}", genesis.merkle_root);
+    println!("Time: {}", genesis.time);
+    println!("Bits: {:08x}", genesis.bits);
+    println!("Nonce: {}", genesis.nonce);
+    println!(
+        "Coinbase: \"{}\"",
+        blvm::chain_params::GENESIS_COINBASE_MESSAGE
+    );
+    println!(
+        "Subsidy: {} BTC (unspendable)",
+        blvm::chain_params::INITIAL_SUBSIDY_SATS / 100_000_000
+    );
+    Ok(())
+}
+
+pub(crate) fn handle_chain_params(network: &Network) -> Result<()> {
+    let params = chain_params(network)?;
+    println!("=== Chain Parameters ({}) ===", params.network);
+    println!("Magic: {}", hex::encode(params.magic));
+    println!("Default P2P Port: {}", params.default_p2p_port);
+    println!("Default RPC Port: {}", params.default_rpc_port);
+    println!("Genesis: {}", params.genesis.hash);
+    println!("PoW Limit: {:08x}", params.pow_limit_bits);
+    println!(
+        "Difficulty Adjustment: every {} blocks ({} s target spacing){}",
+        params.retarget_interval,
+        params.target_spacing,
+        if params.no_retargeting {
+            " — disabled"
+        } else if params.allow_min_difficulty_blocks {
+            " — min-difficulty blocks allowed"
+        } else {
+            ""
+        }
+    );
+
+    println!("\nActivation heights:");
+    let activations = &params.activations;
+    for (name, height) in [
+        ("BIP34", activations.bip34),
+        ("BIP65", activations.bip65),
+        ("BIP66", activations.bip66),
+        ("CSV", activations.csv),
+        ("SegWit", activations.segwit),
+        ("Taproot", activations.taproot),
+    ] {
+        match height {
+            Some(h) => println!("  {name:<8} {h}"),
+            None => println!("  {name:<8} version bits (no fixed height)"),
+        }
+    }
+
+    println!(
+        "\nHalving schedule (every {} blocks):",
+        params.subsidy_halving_interval
+    );
+    for (era, (height, subsidy)) in params.halving_schedule().iter().enumerate().take(10) {
+        println!(
+            "  Era {era:<2} height {height:>9}  {}.{:08} BTC",
+            subsidy / 100_000_000,
+            subsidy % 100_000_000
+        );
+    }
+    let supply = params.max_supply_sats();
+    println!(
+        "  Max supply: {}.{:08} BTC",
+        supply / 100_000_000,
+        supply % 100_000_000
+    );
+    Ok(())
+}
+
+fn parse_bits(bits: &str) -> Result<u32> {
+    u32::from_str_radix(bits.trim_start_matches("0x"), 16)
+        .with_context(|| format!("Invalid nBits '{bits}' (expected hex, e.g. 1d00ffff)"))
+}
+
+pub(crate) fn handle_next_difficulty_offline(
+    network: &Network,
+    bits: &str,
+    first_time: i64,
+    last_time: i64,
+) -> Result<()> {
+    use blvm::difficulty::{difficulty_from_bits, next_work_required};
+    let params = chain_params(network)?;
+    let bits = parse_bits(bits)?;
+    let next = next_work_required(&params, bits, first_time, last_time);
+    println!("=== Difficulty Retarget ({}) ===", params.network);
+    println!(
+        "Period: {} s (target {} s)",
+        last_time - first_time,
+        params.target_timespan
+    );
+    println!(
+        "Current: {:08x} (difficulty {:.2})",
+        bits,
+        difficulty_from_bits(bits)
+    );
+    println!(
+        "Next:    {:08x} (difficulty {:.2})",
+        next,
+        difficulty_from_bits(next)
+    );
+    println!(
+        "Change: {:+.2}%",
+        (difficulty_from_bits(next) / difficulty_from_bits(bits) - 1.0) * 100.0
+    );
+    Ok(())
+}
+
+pub(crate) async fn handle_next_difficulty(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    network: &Network,
+) -> Result<()> {
+    use blvm::difficulty::{difficulty_from_bits, estimate_retarget};
+    let params = chain_params(network)?;
+    let header =
+        |hash: Value| rpc_call_with_config(rpc_addr, config, "getblockheader", json!([hash, true]));
+
+    let tip = header(rpc_call_with_config(rpc_addr, config, "getbestblockhash", json!([])).await?)
+        .await?;
+    let tip_height = tip.get("height").and_then(|v| v.as_u64()).unwrap_or(0);
+    let tip_time = tip.get("time").and_then(|v| v.as_i64()).unwrap_or(0);
+    let tip_bits = parse_bits(tip.get("bits").and_then(|v| v.as_str()).unwrap_or(""))?;
+    let period_start = tip_height - tip_height % params.retarget_interval as u64;
+    let first_hash =
+        rpc_call_with_config(rpc_addr, config, "getblockhash", json!([period_start])).await?;
+    let first_time = header(first_hash)
+        .await?
+        .get("time")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(tip_time);
+
+    let estimate = estimate_retarget(&params, tip_height, tip_time, tip_bits, first_time);
+    println!("=== Next Difficulty Adjustment ===");
+    println!("Tip: {tip_height} (bits {tip_bits:08x})");
+    println!("Current difficulty: {:.2}", difficulty_from_bits(tip_bits));
+    if params.no_retargeting {
+        println!("Retargeting is disabled on {}", params.network);
+        return Ok(());
+    }
+    println!("Retarget at height: {}", estimate.retarget_height);
+    println!(
+        "Blocks remaining: {} (~{})",
+        estimate.blocks_remaining,
+        format_age(estimate.seconds_remaining)
+    );
+    println!(
+        "Estimated next difficulty: {:.2} ({:+.2}%, bits {:08x})",
+        difficulty_from_bits(estimate.next_bits),
+        estimate.change_percent,
+        estimate.next_bits
+    );
+    if params.allow_min_difficulty_blocks {
+        println!(
+            "Note: {} allows minimum-difficulty blocks after 20 minutes",
+            params.network
+        );
+    }
+    Ok(())
+}
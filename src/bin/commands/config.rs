@@ -0,0 +1,137 @@
+//! `blvm config`: show, validate, locate and edit the config file
+
+use crate::*;
+
+/// Rewrite the TOML config file with `edit`, checking that the node still accepts it; the
+/// previous version is kept as `<file>.bak`
+pub(crate) fn edit_config_file(
+    cli_config: &Option<PathBuf>,
+    edit: impl FnOnce(&str) -> Result<String, String>,
+) -> Result<PathBuf> {
+    let path = find_config_file(cli_config)
+        .or_else(|| cli_config.clone())
+        .unwrap_or_else(|| PathBuf::from("./blvm.toml"));
+    if path.extension().and_then(|e| e.to_str()) == Some("json") {
+        anyhow::bail!(
+            "{} is JSON; only TOML config files can be edited",
+            path.display()
+        );
+    }
+    let content = if path.exists() {
+        std::fs::read_to_string(&path).context("Failed to read config file")?
+    } else {
+        String::new()
+    };
+    let updated = edit(&content).map_err(|e| anyhow::anyhow!(e))?;
+    // Keep a .toml extension so both loaders parse the temp file as TOML
+    let tmp = path.with_extension("tmp.toml");
+    std::fs::write(&tmp, &updated).context("Failed to write config file")?;
+    let check = match NodeConfig::from_file(&tmp) {
+        Ok(_) => blvm::extra_config::ExtraConfig::from_file(&tmp).map(|_| ()),
+        Err(e) => Err(anyhow::anyhow!("{e}")),
+    };
+    if let Err(e) = check {
+        let _ = std::fs::remove_file(&tmp);
+        anyhow::bail!(
+            "Edited config would not load ({e}); {} unchanged",
+            path.display()
+        );
+    }
+    if path.exists() {
+        std::fs::copy(&path, path.with_extension("toml.bak"))
+            .context("Failed to back up config file")?;
+    }
+    std::fs::rename(&tmp, &path).context("Failed to replace config file")?;
+    Ok(path)
+}
+
+pub(crate) fn handle_config_show(config: &NodeConfig) -> Result<()> {
+    println!(
+        "{}",
+        toml::to_string_pretty(config).context("Failed to serialize config")?
+    );
+    Ok(())
+}
+
+pub(crate) fn handle_config_validate(
+    path: Option<PathBuf>,
+    cli_config: &Option<PathBuf>,
+) -> Result<()> {
+    let config_path = path
+        .or_else(|| cli_config.clone())
+        .or_else(|| find_config_file(cli_config));
+
+    match config_path {
+        Some(path) => match NodeConfig::from_file(&path) {
+            Ok(config) => match config.validate() {
+                Ok(()) => {
+                    println!("✅ Configuration file is valid: {}", path.display());
+                    Ok(())
+                }
+                Err(e) => {
+                    eprintln!("❌ Configuration validation failed: {e}");
+                    std::process::exit(1);
+                }
+            },
+            Err(e) => {
+                eprintln!("❌ Configuration file is invalid: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => {
+            eprintln!("❌ No configuration file found");
+            std::process::exit(1);
+        }
+    }
+}
+
+pub(crate) fn handle_config_path(cli_config: &Option<PathBuf>) -> Result<()> {
+    if let Some(path) = find_config_file(cli_config) {
+        println!("{}", path.display());
+        Ok(())
+    } else {
+        println!("No configuration file found");
+        Ok(())
+    }
+}
+
+/// Set config value(s) in the config file. Supports dotted keys for primary and module config.
+/// Examples: storage.data_dir=./data, modules.stratum-v2.listen_addr=0.0.0.1:3333
+pub(crate) fn handle_config_set(
+    cli_config: &Option<PathBuf>,
+    assignments: &[String],
+) -> Result<()> {
+    let config_path = find_config_file(cli_config)
+        .or_else(|| Some(PathBuf::from("./blvm.toml")))
+        .ok_or_else(|| anyhow::anyhow!("No config file path"))?;
+
+    let mut content = if config_path.exists() {
+        std::fs::read_to_string(&config_path).context("Failed to read config file")?
+    } else {
+        String::new()
+    };
+
+    let mut root: toml::Value = if content.trim().is_empty() {
+        toml::Value::Table(toml::map::Map::new())
+    } else {
+        content
+            .parse()
+            .context("Failed to parse config file as TOML")?
+    };
+
+    for assignment in assignments {
+        let (key, value_str) = assignment.split_once('=').ok_or_else(|| {
+            anyhow::anyhow!("Invalid assignment '{}': expected key=value", assignment)
+        })?;
+        let key = key.trim();
+        let value_str = value_str.trim();
+
+        let value = blvm::config_overlay::parse_value(value_str);
+        blvm::config_overlay::set_dotted(&mut root, key, value)?;
+    }
+
+    content = toml::to_string_pretty(&root).context("Failed to serialize config")?;
+    std::fs::write(&config_path, content).context("Failed to write config file")?;
+    println!("Updated {}", config_path.display());
+    Ok(())
+}
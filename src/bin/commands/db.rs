@@ -0,0 +1,387 @@
+//! `blvm db`: chainstate statistics, tip rewind and verification
+
+use crate::*;
+
+pub(crate) async fn handle_db_stats(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    data_dir: &str,
+) -> Result<()> {
+    println!("=== Database Statistics ===");
+    println!("Data Directory: {data_dir}");
+    match blvm::datadir::entry_sizes(Path::new(data_dir)) {
+        Ok(entries) => {
+            let total: u64 = entries.iter().map(|e| e.bytes).sum();
+            println!("Total Size: {}", blvm::datadir::format_bytes(total));
+            for entry in &entries {
+                let name = if entry.is_dir {
+                    format!("{}/", entry.name)
+                } else {
+                    entry.name.clone()
+                };
+                println!(
+                    "  {name:<32} {:>12}",
+                    blvm::datadir::format_bytes(entry.bytes)
+                );
+            }
+        }
+        Err(e) => println!("Unable to read data directory: {e}"),
+    }
+
+    // UTXO statistics come from the running node (the chainstate is locked while it runs).
+    match rpc_call_with_config(rpc_addr, config, "gettxoutsetinfo", json!(["none"])).await {
+        Ok(info) => {
+            let txouts = info.get("txouts").and_then(|v| v.as_u64()).unwrap_or(0);
+            let bogosize = info.get("bogosize").and_then(|v| v.as_u64()).unwrap_or(0);
+            println!("\n=== UTXO Set ===");
+            if let Some(height) = info.get("height").and_then(|v| v.as_u64()) {
+                println!("Height: {height}");
+            }
+            println!("UTXO Count: {txouts}");
+            println!("Serialized Size: {}", blvm::datadir::format_bytes(bogosize));
+            if txouts > 0 {
+                println!("Average Entry Size: {} B", bogosize / txouts);
+            }
+            if let Some(disk) = info.get("disk_size").and_then(|v| v.as_u64()) {
+                println!(
+                    "Chainstate Disk Size: {}",
+                    blvm::datadir::format_bytes(disk)
+                );
+            }
+            if let Some(height) = info.get("height").and_then(|v| v.as_u64()) {
+                print_entry_size_sample(rpc_addr, config, height).await;
+            }
+        }
+        Err(e) => println!("\nUTXO set statistics unavailable: {e}"),
+    }
+
+    // Undo data (spent outputs per block) serves reorgs, getblock verbosity 3 and fee stats
+    println!("\n=== Undo Data ===");
+    if let Ok((files, bytes)) = blvm::datadir::undo_files(Path::new(data_dir))
+        && files > 0
+    {
+        println!(
+            "Undo files: {files} ({})",
+            blvm::datadir::format_bytes(bytes)
+        );
+    }
+    match tip_has_undo_data(rpc_addr, config).await {
+        Some(true) => println!("Tip block: undo data present"),
+        Some(false) => println!(
+            "Tip block: no undo data (blocks connected before undo storage have none until reindexed)"
+        ),
+        None => println!("Tip block: unknown (node unreachable or no spending transactions)"),
+    }
+
+    Ok(())
+}
+
+/// Blocks below the tip whose unspent outputs make up the entry size sample
+const ENTRY_SAMPLE_BLOCKS: u64 = 10;
+
+/// Stop sampling once this many unspent outputs were measured
+const ENTRY_SAMPLE_MAX: usize = 5000;
+
+/// Entry size percentiles and histogram from the still-unspent outputs of recent blocks
+async fn print_entry_size_sample(rpc_addr: SocketAddr, config: &NodeConfig, tip: u64) {
+    let mut sample = blvm::utxo_sizes::SizeSample::default();
+    let mut blocks = 0;
+    for height in (tip.saturating_sub(ENTRY_SAMPLE_BLOCKS - 1)..=tip).rev() {
+        if sample.len() >= ENTRY_SAMPLE_MAX {
+            break;
+        }
+        let Ok(hash) =
+            rpc_call_with_config(rpc_addr, config, "getblockhash", json!([height])).await
+        else {
+            break;
+        };
+        let Ok(block) = rpc_call_with_config(rpc_addr, config, "getblock", json!([hash, 2])).await
+        else {
+            break;
+        };
+        let outputs: Vec<(String, u64)> = block["tx"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|tx| {
+                let txid = tx["txid"].as_str().unwrap_or_default().to_string();
+                (0..tx["vout"].as_array().map_or(0, Vec::len) as u64)
+                    .map(move |n| (txid.clone(), n))
+            })
+            .take(ENTRY_SAMPLE_MAX - sample.len())
+            .collect();
+        let calls: Vec<blvm::rpc_batch::BatchCall> = outputs
+            .iter()
+            .map(|(txid, n)| blvm::rpc_batch::BatchCall {
+                method: "gettxout".to_string(),
+                params: json!([txid, n]),
+            })
+            .collect();
+        let Ok(replies) = rpc_batch_with_config(rpc_addr, config, &calls).await else {
+            break;
+        };
+        for coin in replies.iter().flatten() {
+            if let Some(script) = coin["scriptPubKey"]["hex"].as_str() {
+                sample.add(blvm::utxo_sizes::entry_size(script));
+            }
+        }
+        blocks += 1;
+    }
+    if sample.is_empty() {
+        return;
+    }
+    let percentile = |p| sample.percentile(p).unwrap_or(0);
+    println!(
+        "Entry Size Sample: {} unspent outputs of the last {blocks} blocks",
+        sample.len()
+    );
+    println!(
+        "  p10 {} B, p50 {} B, p90 {} B, p99 {} B",
+        percentile(10),
+        percentile(50),
+        percentile(90),
+        percentile(99)
+    );
+    for row in sample.rows() {
+        let range = match row.to {
+            Some(to) => format!("{}-{} B", row.from, to - 1),
+            None => format!("{}+ B", row.from),
+        };
+        println!(
+            "  {range:<12} {:>6} ({:.1}%)",
+            row.count,
+            row.count as f64 * 100.0 / sample.len() as f64
+        );
+    }
+}
+
+/// Whether getblock verbosity 3 returns prevouts for the tip; `None` when it cannot tell
+async fn tip_has_undo_data(rpc_addr: SocketAddr, config: &NodeConfig) -> Option<bool> {
+    let tip = rpc_call_with_config(rpc_addr, config, "getbestblockhash", json!([]))
+        .await
+        .ok()?;
+    let block = rpc_call_with_config(rpc_addr, config, "getblock", json!([tip, 3]))
+        .await
+        .ok();
+    let Some(block) = block else {
+        return Some(false);
+    };
+    let spend = block["tx"].as_array()?.get(1)?;
+    Some(spend["vin"][0].get("prevout").is_some())
+}
+
+/// Deepest rewind allowed without `--allow-deep` (two days of blocks, like Core's
+/// `MIN_BLOCKS_TO_KEEP`)
+const MAX_INVALIDATE_DEPTH: u64 = 288;
+
+/// Rewind a running node's tip to `height` by invalidating the block at `height + 1`. The node
+/// disconnects the blocks itself; they stay marked invalid until `reconsiderblock`.
+pub(crate) async fn handle_db_invalidate(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    height: u64,
+    yes: bool,
+    allow_deep: bool,
+) -> Result<()> {
+    let info = rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await?;
+    let tip = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+    if height >= tip {
+        anyhow::bail!("Target height {height} is not below the current tip ({tip})");
+    }
+    let depth = tip - height;
+    if depth > MAX_INVALIDATE_DEPTH && !allow_deep {
+        anyhow::bail!(
+            "Invalidating {depth} blocks exceeds {MAX_INVALIDATE_DEPTH}; pass --allow-deep if this is intended"
+        );
+    }
+    if let Some(pruned_to) = info
+        .get("pruneheight")
+        .and_then(|v| v.as_u64())
+        .filter(|_| info.get("pruned").and_then(|v| v.as_bool()) == Some(true))
+        && height < pruned_to
+    {
+        anyhow::bail!(
+            "Block data below height {pruned_to} has been pruned; cannot rewind to {height}"
+        );
+    }
+    let network = rpc_call_with_config(rpc_addr, config, "getnetworkinfo", json!([])).await?;
+    if network
+        .get("networkactive")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+    {
+        anyhow::bail!(
+            "Networking is active; disable it first so peers cannot interfere:\n  blvm rpc setnetworkactive '[false]'"
+        );
+    }
+
+    let first_hash =
+        rpc_call_with_config(rpc_addr, config, "getblockhash", json!([height + 1])).await?;
+    let Some(first_hash) = first_hash.as_str().map(str::to_string) else {
+        anyhow::bail!(
+            "getblockhash {} returned no block hash: {first_hash}",
+            height + 1
+        );
+    };
+    println!("Current tip: {tip}");
+    println!("Target height: {height} ({depth} blocks will be disconnected)");
+    println!("First disconnected block: {first_hash}");
+    if !yes {
+        use std::io::{BufRead, IsTerminal, Write};
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("Refusing to invalidate without a terminal; pass --yes to confirm");
+        }
+        print!("Type the target height to confirm: ");
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().lock().read_line(&mut answer)?;
+        if answer.trim() != height.to_string() {
+            anyhow::bail!("Confirmation did not match; nothing was changed");
+        }
+    }
+
+    println!("Invalidating {first_hash} ({depth} blocks)…");
+    rpc_call_with_config(rpc_addr, config, "invalidateblock", json!([first_hash])).await?;
+    let new_tip = rpc_call_with_config(rpc_addr, config, "getblockcount", json!([]))
+        .await?
+        .as_u64()
+        .unwrap_or(0);
+    if new_tip != height {
+        anyhow::bail!("Node reports tip {new_tip} after invalidateblock (expected {height})");
+    }
+    println!("✅ Tip is now height {height}");
+    println!("Blocks above it stay marked invalid; to reconnect them later:");
+    println!("  blvm rpc reconsiderblock '[\"{first_hash}\"]'");
+    println!("Re-enable networking with: blvm rpc setnetworkactive '[true]'");
+    Ok(())
+}
+
+pub(crate) async fn handle_db_verify(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    checklevel: u32,
+    nblocks: u64,
+    as_json: bool,
+) -> Result<()> {
+    let auth = RpcAuth::from_config(config)?;
+    let report = verify_chain(rpc_addr, &auth, checklevel, nblocks).await?;
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "Checked {} blocks ({}..={}) at level {}{}",
+            report.blocks_checked,
+            report.from_height,
+            report.to_height,
+            report.checklevel,
+            if report.chainstate_checked {
+                ", chainstate included"
+            } else {
+                ""
+            }
+        );
+        for problem in &report.problems {
+            println!(
+                "  [level {}] {} {}: {}",
+                problem.level, problem.height, problem.hash, problem.problem
+            );
+        }
+    }
+    if !report.valid {
+        anyhow::bail!(
+            "Chain verification found {} problem(s)",
+            report.problems.len()
+        );
+    }
+    if !as_json {
+        println!("✅ No problems found");
+    }
+    Ok(())
+}
+
+/// `verifychain` over the node's RPC: re-check the last `nblocks` blocks (0 = all) up to
+/// `checklevel` (see `blvm::verifychain`)
+pub(crate) async fn verify_chain(
+    rpc_addr: SocketAddr,
+    auth: &RpcAuth,
+    checklevel: u32,
+    nblocks: u64,
+) -> Result<blvm::verifychain::Report> {
+    let call = |method: &'static str, params: Value| rpc_call_as(rpc_addr, auth, method, params);
+    let info = call("getblockchaininfo", json!([])).await?;
+    let tip = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+    let bip34 = info
+        .get("chain")
+        .and_then(|v| v.as_str())
+        .map(|chain| match chain {
+            "main" => "mainnet",
+            "test" => "testnet",
+            other => other,
+        })
+        .and_then(blvm::chain_params::ChainParams::for_network)
+        .and_then(|params| params.activations.bip34);
+    let mut report = blvm::verifychain::Report::new(checklevel, nblocks, tip);
+    let checklevel = report.checklevel;
+
+    let mut prev_hash = None;
+    let mut prev_header = None;
+    if report.from_height > 0 {
+        let hash = call("getblockhash", json!([report.from_height - 1])).await?;
+        if checklevel >= 2 && hash.is_string() {
+            prev_header = Some(call("getblockheader", json!([hash])).await?);
+        }
+        prev_hash = hash.as_str().and_then(blvm::hash::from_display_hex);
+    }
+    for height in report.from_height..=report.to_height {
+        let hash = call("getblockhash", json!([height])).await?;
+        let hash = hash.as_str().unwrap_or_default().to_string();
+        let hash_bytes = blvm::hash::from_display_hex(&hash)
+            .with_context(|| format!("Invalid block hash '{hash}' at height {height}"))?;
+        let bytes = match call("getblock", json!([hash, 0])).await {
+            Ok(Value::String(raw)) => hex::decode(raw).map_err(anyhow::Error::from),
+            Ok(other) => Err(anyhow::anyhow!("unexpected getblock result {other}")),
+            Err(e) => Err(e),
+        };
+        match bytes {
+            Ok(bytes) => {
+                if checklevel >= 1 {
+                    let problems =
+                        blvm::revalidation::check_block(&bytes, &hash_bytes, prev_hash.as_ref());
+                    for problem in problems {
+                        report.problem(height, &hash, 1, problem);
+                    }
+                }
+                if checklevel >= 2 {
+                    let header = call("getblockheader", json!([hash])).await?;
+                    let problems = blvm::verifychain::check_index(
+                        &bytes,
+                        &header,
+                        prev_header.as_ref(),
+                        bip34,
+                    );
+                    for problem in problems {
+                        report.problem(height, &hash, 2, problem);
+                    }
+                    prev_header = Some(header);
+                }
+            }
+            Err(e) => {
+                report.problem(height, &hash, 0, format!("block not readable: {e:#}"));
+                prev_header = None;
+            }
+        }
+        report.blocks_checked += 1;
+        prev_hash = Some(hash_bytes);
+    }
+
+    if checklevel >= 3 {
+        let tip_hash = call("getbestblockhash", json!([])).await?;
+        let tip_hash = tip_hash.as_str().unwrap_or_default().to_string();
+        let utxo = call("gettxoutsetinfo", json!([])).await?;
+        if let Some(problem) = blvm::verifychain::check_chainstate(&utxo, &tip_hash, tip) {
+            report.problem(tip, &tip_hash, 3, problem);
+        }
+        report.chainstate_checked = true;
+    }
+    Ok(report)
+}
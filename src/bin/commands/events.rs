@@ -0,0 +1,61 @@
+//! `blvm events tail`: the operator event log
+
+use crate::*;
+
+pub(crate) async fn handle_events_tail(
+    data_dir: &Path,
+    lines: usize,
+    follow: bool,
+    kind: Option<&str>,
+) -> Result<()> {
+    use std::io::{Read, Seek};
+    let path = data_dir.join(blvm::events::EVENTS_FILE);
+    let matches = |event: &blvm::events::Event| kind.is_none_or(|k| event.kind.starts_with(k));
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && follow => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let events: Vec<_> = blvm::events::parse_lines(&content)
+        .into_iter()
+        .filter(|e| matches(e))
+        .collect();
+    for event in &events[events.len().saturating_sub(lines)..] {
+        println!("{}", event.render());
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut offset = content.len() as u64;
+    let mut pending = String::new();
+    loop {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let Ok(len) = std::fs::metadata(&path).map(|m| m.len()) else {
+            continue;
+        };
+        if len < offset {
+            // Rotated: start over on the new file
+            offset = 0;
+            pending.clear();
+        }
+        if len == offset {
+            continue;
+        }
+        let mut file = std::fs::File::open(&path)?;
+        file.seek(std::io::SeekFrom::Start(offset))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        offset += bytes.len() as u64;
+        pending.push_str(&String::from_utf8_lossy(&bytes));
+        let Some(end) = pending.rfind('\n') else {
+            continue;
+        };
+        for event in blvm::events::parse_lines(&pending[..end]) {
+            if matches(&event) {
+                println!("{}", event.render());
+            }
+        }
+        pending.drain(..=end);
+    }
+}
@@ -0,0 +1,78 @@
+//! `blvm fleet exec`
+
+use crate::*;
+
+/// Send a signed admin command to fleet nodes and print each reply
+pub(crate) async fn handle_fleet_exec(
+    fleet: &blvm::fleet::FleetConfig,
+    command: &[String],
+    only: &[String],
+) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    blvm::fleet::AdminCommand::parse(command).map_err(|e| anyhow::anyhow!(e))?;
+    let key_id = fleet
+        .key_id
+        .as_deref()
+        .context("Set [fleet] key_id in the config file")?;
+    let secret = env::var("BLVM_FLEET_KEY")
+        .ok()
+        .or_else(|| fleet.key.clone())
+        .context("Set BLVM_FLEET_KEY or [fleet] key to the operator key (hex)")?;
+    let key = hex::decode(secret.trim()).context("Fleet key is not valid hex")?;
+    if let Some(name) = only
+        .iter()
+        .find(|name| !fleet.nodes.iter().any(|n| &&n.name == name))
+    {
+        anyhow::bail!("No node named '{}' in [fleet] nodes", name);
+    }
+    let nodes: Vec<_> = fleet
+        .nodes
+        .iter()
+        .filter(|n| only.is_empty() || only.contains(&n.name))
+        .collect();
+    if nodes.is_empty() {
+        anyhow::bail!("No [[fleet.nodes]] configured");
+    }
+
+    let mut failures = 0;
+    for node in nodes {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let request = blvm::fleet::Request {
+            key_id: key_id.to_string(),
+            time: now.as_secs(),
+            nonce: now.as_nanos() as u64,
+            command: command.to_vec(),
+        };
+        let envelope = blvm::fleet::Envelope::sign(&request, &key);
+        let exchange = async {
+            let mut stream = tokio::net::TcpStream::connect(node.addr).await?;
+            let mut line = serde_json::to_string(&envelope)?;
+            line.push('\n');
+            stream.write_all(line.as_bytes()).await?;
+            let mut reply = String::new();
+            BufReader::new(stream).read_line(&mut reply).await?;
+            Ok::<_, anyhow::Error>(serde_json::from_str::<blvm::fleet::Reply>(&reply)?)
+        };
+        match tokio::time::timeout(Duration::from_secs(15), exchange).await {
+            Ok(Ok(reply)) if reply.ok => println!("{:<16} ok: {}", node.name, reply.message),
+            Ok(Ok(reply)) => {
+                failures += 1;
+                println!("{:<16} error: {}", node.name, reply.message);
+            }
+            Ok(Err(e)) => {
+                failures += 1;
+                println!("{:<16} error: {}", node.name, e);
+            }
+            Err(_) => {
+                failures += 1;
+                println!("{:<16} error: timed out", node.name);
+            }
+        }
+    }
+    if failures > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,224 @@
+//! `blvm mining`: status, clients and payout config
+
+use super::module::module_listed;
+use crate::*;
+
+pub(crate) async fn handle_mining_status(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    mining: &blvm::mining::MiningConfig,
+    blocks: u64,
+) -> Result<()> {
+    use blvm::mining::{
+        MAX_BLOCK_WEIGHT, TemplateStats, block_intervals, estimate_hashrate, format_hashrate,
+    };
+    let info = rpc_call_with_config(rpc_addr, config, "getmininginfo", json!([])).await?;
+    let height = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+    let difficulty = info
+        .get("difficulty")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.0);
+    println!("=== Mining Status ===");
+    println!(
+        "Chain:            {}",
+        info.get("chain")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+    );
+    println!("Height:           {height}");
+    println!("Difficulty:       {difficulty:.2}");
+
+    // Block times from the headers of the last `blocks` blocks
+    let mut times = Vec::new();
+    let mut hash = rpc_call_with_config(rpc_addr, config, "getbestblockhash", json!([])).await?;
+    for _ in 0..=blocks.min(height) {
+        let header =
+            rpc_call_with_config(rpc_addr, config, "getblockheader", json!([hash, true])).await?;
+        times.push(header.get("time").and_then(|v| v.as_u64()).unwrap_or(0));
+        match header.get("previousblockhash") {
+            Some(prev) => hash = prev.clone(),
+            None => break,
+        }
+    }
+    times.reverse();
+    let intervals = block_intervals(&times);
+    match info.get("networkhashps").and_then(|v| v.as_f64()) {
+        Some(hashps) => println!("Network hashrate: {}", format_hashrate(hashps)),
+        None => {
+            if let Some(intervals) = &intervals {
+                println!(
+                    "Network hashrate: {} (estimated from the last {} blocks)",
+                    format_hashrate(estimate_hashrate(difficulty, intervals.mean)),
+                    times.len() - 1
+                );
+            }
+        }
+    }
+    if let Some(intervals) = &intervals {
+        println!(
+            "Block times:      mean {} over {} blocks (min {}s, max {}s)",
+            format_age(intervals.mean.max(0.0) as u64),
+            times.len() - 1,
+            intervals.min,
+            intervals.max
+        );
+    }
+    if let Some(last) = times.last() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        println!(
+            "Last block:       {} ago",
+            format_age(now.saturating_sub(*last))
+        );
+    }
+
+    // Prefer the node's own template summary; fall back to building a template
+    let template = match info.get("template") {
+        Some(t) => TemplateStats {
+            tx_count: t.get("tx_count").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+            weight: t.get("weight").and_then(|v| v.as_u64()).unwrap_or(0),
+            fees: t.get("fees").and_then(|v| v.as_u64()).unwrap_or(0),
+        },
+        None => {
+            let gbt = rpc_call_with_config(
+                rpc_addr,
+                config,
+                "getblocktemplate",
+                json!([{"rules": ["segwit"]}]),
+            )
+            .await;
+            match gbt {
+                Ok(gbt) => TemplateStats::from_template(&gbt),
+                Err(e) => {
+                    println!("Template:         unavailable ({e})");
+                    TemplateStats::default()
+                }
+            }
+        }
+    };
+    if template != TemplateStats::default() {
+        println!(
+            "Template:         {} txs, {:.1}% of max weight, {} BTC in fees",
+            template.tx_count,
+            template.weight as f64 * 100.0 / MAX_BLOCK_WEIGHT as f64,
+            blvm::bip21::format_btc(template.fees)
+        );
+    }
+    if let Some(pooled) = info.get("pooledtx").and_then(|v| v.as_u64()) {
+        println!("Mempool:          {pooled} txs");
+    }
+
+    let sv2 = match info.get("stratum_v2_active").and_then(|v| v.as_bool()) {
+        Some(active) => active,
+        None => rpc_call_with_config(rpc_addr, config, "listmodules", json!([]))
+            .await
+            .is_ok_and(|list| module_listed(&list, &mining.template_module)),
+    };
+    println!(
+        "Stratum V2:       {}",
+        if sv2 {
+            format!("active ({})", mining.template_module)
+        } else {
+            "inactive".to_string()
+        }
+    );
+    Ok(())
+}
+
+pub(crate) async fn handle_mining_clients(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    mining: &blvm::mining::MiningConfig,
+    only_anomalies: bool,
+) -> Result<()> {
+    use blvm::mining::{ClientStats, client_anomalies};
+    if !node_serves(rpc_addr, config, "callmodule").await? {
+        anyhow::bail!(
+            "Client stats come from {} through the node's callmodule, which this node does not \
+             serve",
+            mining.template_module
+        );
+    }
+    let stats = rpc_call_with_config(
+        rpc_addr,
+        config,
+        "callmodule",
+        json!([mining.template_module, "client_stats", null, 10]),
+    )
+    .await
+    .with_context(|| format!("client stats from {}", mining.template_module))?;
+    let clients: Vec<ClientStats> = stats
+        .get("clients")
+        .unwrap_or(&stats)
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(ClientStats::from_value)
+        .collect();
+    let difficulty = rpc_call_with_config(rpc_addr, config, "getmininginfo", json!([]))
+        .await?
+        .get("difficulty")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.0);
+    if clients.is_empty() {
+        println!("No mining clients connected");
+        return Ok(());
+    }
+    println!(
+        "{:<16} {:<20} {:>10} {:>7} {:>6} {:>9} {:>8} {:>8}",
+        "Client", "Worker", "Shares", "Stale", "Blocks", "Expected", "p50 ms", "p95 ms"
+    );
+    let mut flagged = 0;
+    for client in &clients {
+        let anomalies = client_anomalies(client, &clients, difficulty);
+        if only_anomalies && anomalies.is_empty() {
+            continue;
+        }
+        flagged += usize::from(!anomalies.is_empty());
+        println!(
+            "{:<16} {:<20} {:>10} {:>6.1}% {:>6} {:>9.2} {:>8} {:>8}",
+            client.id,
+            client.worker,
+            client.accepted_shares,
+            client.stale_rate() * 100.0,
+            client.blocks_found,
+            client.expected_blocks(difficulty),
+            client.latency_p50_ms,
+            client.latency_p95_ms
+        );
+        for anomaly in anomalies {
+            println!("  WARNING: {anomaly}");
+        }
+    }
+    if only_anomalies && flagged == 0 {
+        println!("No anomalies among {} client(s)", clients.len());
+    }
+    Ok(())
+}
+
+pub(crate) fn handle_mining_config(
+    mining: &blvm::mining::MiningConfig,
+    data_dir: &Path,
+) -> Result<()> {
+    println!("Template module:   {}", mining.template_module);
+    let Some(descriptor) = &mining.payout_descriptor else {
+        println!("Payout rotation:   off (set [mining] payout_descriptor)");
+        return Ok(());
+    };
+    let descriptor =
+        blvm::mining::validate_payout_descriptor(descriptor).map_err(|e| anyhow::anyhow!(e))?;
+    let state = blvm::mining::PayoutState::load_for(data_dir, &descriptor);
+    println!("Payout descriptor: {descriptor}");
+    println!("Payout index:      {}", state.index);
+    println!(
+        "Payout address:    {}",
+        state
+            .address
+            .as_deref()
+            .unwrap_or("(derived when the node starts)")
+    );
+    println!("Blocks found:      {}", state.blocks_found);
+    Ok(())
+}
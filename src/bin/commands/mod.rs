@@ -0,0 +1,23 @@
+//! Subcommand handlers, one module per command group
+//!
+//! `main.rs` parses the command line and dispatches here; the node start path stays in
+//! `main.rs` and the background tasks it spawns are in `tasks`.
+
+pub(crate) mod bench;
+pub(crate) mod block;
+pub(crate) mod chain;
+pub(crate) mod config;
+pub(crate) mod db;
+pub(crate) mod events;
+pub(crate) mod fleet;
+pub(crate) mod mining;
+pub(crate) mod module;
+pub(crate) mod peers;
+pub(crate) mod rpc;
+#[cfg(feature = "silent-payments")]
+pub(crate) mod silent_payments;
+pub(crate) mod start;
+pub(crate) mod stats;
+pub(crate) mod status;
+pub(crate) mod tx;
+pub(crate) mod version;
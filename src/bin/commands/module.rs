@@ -0,0 +1,259 @@
+//! `blvm module`: scaffold, check, status and module CLI commands
+
+use super::version::compiled_features;
+use crate::*;
+
+/// Print config file path for a module (works offline; uses config to resolve path)
+pub(crate) fn handle_module_config_path(
+    module: &str,
+    config: &NodeConfig,
+    data_dir: &str,
+) -> Result<()> {
+    let path = modules_data_dir(config, data_dir)
+        .join(module)
+        .join("config.toml");
+    println!("{}", path.display());
+    Ok(())
+}
+
+/// Module data directory: `[modules].data_dir`, else `<data_dir>/modules`
+pub(crate) fn modules_data_dir(config: &NodeConfig, data_dir: &str) -> PathBuf {
+    config
+        .modules
+        .as_ref()
+        .map(|m| PathBuf::from(&m.data_dir))
+        .unwrap_or_else(|| PathBuf::from(data_dir).join("modules"))
+}
+
+pub(crate) async fn handle_module(
+    rpc_addr: SocketAddr,
+    subcommand: &ModuleCommand,
+    config: &NodeConfig,
+    data_dir: &str,
+) -> Result<()> {
+    let (method, params) = match subcommand {
+        ModuleCommand::Scaffold { name, output } => {
+            return handle_module_scaffold(name, output.as_deref());
+        }
+        ModuleCommand::Check { path } => return handle_module_check(path),
+        ModuleCommand::Status { name } => {
+            return handle_module_status(rpc_addr, name.as_deref(), config, data_dir).await;
+        }
+        ModuleCommand::Load { name } => ("loadmodule", json!([name])),
+        ModuleCommand::Unload { name } => ("unloadmodule", json!([name])),
+        ModuleCommand::Reload { name } => ("reloadmodule", json!([name])),
+        ModuleCommand::List => ("listmodules", json!([])),
+    };
+    let result = rpc_call_with_config(rpc_addr, config, method, params).await?;
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
+
+/// Write a module skeleton to `output` (default `./<name>`)
+fn handle_module_scaffold(name: &str, output: Option<&Path>) -> Result<()> {
+    if !blvm::scaffold::is_valid_module_name(name) {
+        anyhow::bail!(
+            "Invalid module name '{}': use lowercase letters, digits and '-', starting with a letter",
+            name
+        );
+    }
+    let root = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(name));
+    if root.exists()
+        && std::fs::read_dir(&root)
+            .context("Failed to read output directory")?
+            .next()
+            .is_some()
+    {
+        anyhow::bail!("{} already exists and is not empty", root.display());
+    }
+
+    let files = blvm::scaffold::module_files(name);
+    for file in &files {
+        let path = root.join(&file.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, &file.contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    println!("Created module '{name}' in {}", root.display());
+    for file in &files {
+        println!("  {}", file.path.display());
+    }
+    println!(
+        "\nWrite the module against blvm-sdk (https://github.com/BTCDecoded/blvm-sdk), check the \
+         manifest with `blvm module check {}`, then copy the binary and module.toml into \
+         <modules_dir>/{name}/",
+        root.display()
+    );
+    Ok(())
+}
+
+/// Loaded state (from the node, if reachable) and data directory usage per module
+async fn handle_module_status(
+    rpc_addr: SocketAddr,
+    name: Option<&str>,
+    config: &NodeConfig,
+    data_dir: &str,
+) -> Result<()> {
+    let modules_dir = modules_data_dir(config, data_dir);
+    let names: Vec<String> = match name {
+        Some(name) => vec![name.to_string()],
+        None => {
+            let mut names: Vec<String> = std::fs::read_dir(&modules_dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(|e| e.ok())
+                        .filter(|e| e.path().is_dir())
+                        .map(|e| e.file_name().to_string_lossy().into_owned())
+                        .collect()
+                })
+                .unwrap_or_default();
+            names.sort();
+            names
+        }
+    };
+    let loaded = rpc_call_with_config(rpc_addr, config, "listmodules", json!([]))
+        .await
+        .ok();
+
+    println!("=== Module Status ===");
+    if names.is_empty() {
+        println!("No modules found in {}", modules_dir.display());
+        return Ok(());
+    }
+    for name in &names {
+        println!("\n{name}:");
+        let state = match &loaded {
+            Some(list) if module_listed(list, name) => "yes",
+            Some(_) => "no",
+            None => "unknown (node not reachable)",
+        };
+        println!("  Loaded: {state}");
+        let quota = blvm::module_manifest::ModuleManifest::from_file(
+            module_manifest_dir(config).join(name).join("module.toml"),
+        )
+        .ok()
+        .and_then(|m| m.resources.disk_quota_mb);
+        let disk = blvm::module_storage::DiskUsage::measure(&modules_dir.join(name), quota);
+        println!(
+            "  Data: {}{}",
+            disk.describe(),
+            if disk.exceeded() {
+                " — over quota"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Directory holding installed modules (`<modules_dir>/<name>/module.toml`)
+fn module_manifest_dir(config: &NodeConfig) -> PathBuf {
+    PathBuf::from(
+        config
+            .modules
+            .as_ref()
+            .map_or("modules", |m| m.modules_dir.as_str()),
+    )
+}
+
+/// Manifests of the installed modules that parse; broken ones are reported by `start --dry-run`
+pub(crate) fn installed_module_manifests(
+    config: &NodeConfig,
+) -> Vec<blvm::module_manifest::ModuleManifest> {
+    let mut manifests: Vec<_> = std::fs::read_dir(module_manifest_dir(config))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| {
+                    blvm::module_manifest::ModuleManifest::from_file(e.path().join("module.toml"))
+                        .ok()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    manifests.sort_by(|a, b| a.name.cmp(&b.name));
+    manifests
+}
+
+/// Whether a `listmodules` result contains `name` (as a string or an object `name` field)
+pub(crate) fn module_listed(list: &Value, name: &str) -> bool {
+    list.as_array().is_some_and(|modules| {
+        modules.iter().any(|m| {
+            m.as_str() == Some(name) || m.get("name").and_then(|v| v.as_str()) == Some(name)
+        })
+    })
+}
+
+/// Lint a module manifest offline against this build's features (not the node's load-time checks)
+fn handle_module_check(path: &Path) -> Result<()> {
+    let manifest_path = if path.is_dir() {
+        path.join("module.toml")
+    } else {
+        path.to_path_buf()
+    };
+    let manifest = blvm::module_manifest::ModuleManifest::from_file(&manifest_path)?;
+    let errors = manifest.compatibility_errors(&compiled_features());
+    if errors.is_empty() {
+        println!(
+            "✅ {} {} passed manifest checks",
+            manifest.name, manifest.version
+        );
+        Ok(())
+    } else {
+        eprintln!(
+            "❌ {} {} failed manifest checks:",
+            manifest.name, manifest.version
+        );
+        for error in &errors {
+            eprintln!("  - {error}");
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Handle dynamic module CLI (e.g. blvm sync-policy list)
+pub(crate) async fn handle_module_cli(
+    rpc_addr: SocketAddr,
+    args: &[String],
+    config: &NodeConfig,
+) -> Result<()> {
+    if args.len() < 2 {
+        anyhow::bail!(
+            "Usage: blvm <module_name> <subcommand> [args...]\n\
+             Example: blvm sync-policy list\n\
+             Run 'blvm' with no args to see core commands. Module commands require the node to be running."
+        );
+    }
+    let module_name = &args[0];
+    let subcommand = &args[1];
+    let sub_args: Vec<String> = args[2..].to_vec();
+    let params = {
+        let mut p = vec![json!(module_name), json!(subcommand)];
+        p.extend(sub_args.into_iter().map(Value::from));
+        Value::Array(p)
+    };
+    let result = rpc_call_with_config(rpc_addr, config, "runmodulecli", params).await?;
+    let stdout = result.get("stdout").and_then(|v| v.as_str()).unwrap_or("");
+    let stderr = result.get("stderr").and_then(|v| v.as_str()).unwrap_or("");
+    let exit_code = result
+        .get("exit_code")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(1);
+    if !stdout.is_empty() {
+        print!("{stdout}");
+    }
+    if !stderr.is_empty() {
+        eprint!("{stderr}");
+    }
+    if exit_code != 0 {
+        std::process::exit(exit_code as i32);
+    }
+    Ok(())
+}
@@ -0,0 +1,346 @@
+//! `blvm peers`, `seeds` and `network`
+
+use super::config::edit_config_file;
+use crate::*;
+
+pub(crate) async fn handle_peers(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    persistent: &[blvm::persistent_peers::PersistentPeer],
+    format: Option<blvm::output::Format>,
+) -> Result<()> {
+    let peers = rpc_call_with_config(rpc_addr, config, "getpeerinfo", json!([])).await?;
+
+    if let Some(format) = format {
+        let mut table = blvm::output::Table::new(&[
+            "id",
+            "addr",
+            "inbound",
+            "version",
+            "subver",
+            "latency_ms",
+            "persistent",
+        ]);
+        for peer in peers.as_array().into_iter().flatten() {
+            let field = |key: &str| peer.get(key).cloned().unwrap_or(Value::Null);
+            let label = peer
+                .get("addr")
+                .and_then(|v| v.as_str())
+                .and_then(|addr| persistent.iter().find(|p| p.address == addr))
+                .map(|entry| json!(entry.label.as_deref().unwrap_or("")))
+                .unwrap_or(Value::Null);
+            let latency_ms = peer
+                .get("latency")
+                .and_then(|v| v.as_f64())
+                .map(|secs| json!((secs * 1000.0 * 100.0).round() / 100.0))
+                .unwrap_or(Value::Null);
+            table.push(vec![
+                field("id"),
+                field("addr"),
+                field("inbound"),
+                field("version"),
+                field("subver"),
+                latency_ms,
+                label,
+            ]);
+        }
+        print!("{}", table.render(format));
+        return Ok(());
+    }
+
+    println!("=== Connected Peers ===");
+    if let Some(peer_array) = peers.as_array() {
+        if peer_array.is_empty() {
+            println!("No peers connected");
+        } else {
+            for (i, peer) in peer_array.iter().enumerate() {
+                println!("\nPeer {}:", i + 1);
+                if let Some(addr) = peer.get("addr").and_then(|v| v.as_str()) {
+                    println!("  Address: {addr}");
+                    if let Some(entry) = persistent.iter().find(|p| p.address == addr) {
+                        println!(
+                            "  Persistent: {}",
+                            entry.label.as_deref().unwrap_or("(no label)")
+                        );
+                    }
+                }
+                if let Some(version) = peer.get("version").and_then(|v| v.as_u64()) {
+                    println!("  Version: {version}");
+                }
+                if let Some(latency) = peer.get("latency").and_then(|v| v.as_f64()) {
+                    println!("  Latency: {:.2}ms", latency * 1000.0);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn handle_seeds_export(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    network: &str,
+    filter: &blvm::seeds::Filter,
+    out: Option<&Path>,
+) -> Result<()> {
+    let known = rpc_call_with_config(rpc_addr, config, "getnodeaddresses", json!([0])).await?;
+    let candidates: Vec<blvm::seeds::Candidate> = known
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(blvm::seeds::Candidate::from_value)
+        .collect();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let total = candidates.len();
+    let seeds = blvm::seeds::select(candidates, filter, now);
+    let header = format!(
+        "blvm {network} seeds, {}\n{} of {total} known addresses (min uptime {:.0}%, seen within {}d)",
+        blvm::events::format_utc(now),
+        seeds.len(),
+        filter.min_uptime * 100.0,
+        filter.max_age_secs / 86_400
+    );
+    let text = blvm::seeds::to_seed_file(&seeds, &header);
+    match out {
+        Some(path) => {
+            std::fs::write(path, &text).context("Failed to write seed file")?;
+            eprintln!(
+                "Wrote {} seeds ({} known addresses) to {}",
+                seeds.len(),
+                total,
+                path.display()
+            );
+        }
+        None => print!("{text}"),
+    }
+    if seeds.is_empty() {
+        eprintln!("No address met the filters; try a lower --min-uptime or larger --max-age-days");
+    }
+    Ok(())
+}
+
+pub(crate) fn handle_peers_persist(
+    cli_config: &Option<PathBuf>,
+    subcommand: &PersistCommand,
+) -> Result<()> {
+    use blvm::persistent_peers::{
+        PeerTransport, PersistentPeer, add_to_config, entries, remove_from_config,
+    };
+    match subcommand {
+        PersistCommand::Add {
+            address,
+            label,
+            transport,
+            max_retries,
+            retry_interval,
+            no_relay,
+        } => {
+            let peer = PersistentPeer {
+                label: label.clone(),
+                transport: PeerTransport::parse(transport).map_err(|e| anyhow::anyhow!(e))?,
+                max_retries: *max_retries,
+                retry_interval_secs: *retry_interval,
+                relay: !no_relay,
+                ..PersistentPeer::new(address.clone())
+            };
+            let path = edit_config_file(cli_config, |content| add_to_config(content, &peer))?;
+            println!("Added persistent peer {address} to {}", path.display());
+        }
+        PersistCommand::Remove { address } => {
+            let path =
+                edit_config_file(cli_config, |content| remove_from_config(content, address))?;
+            println!("Removed persistent peer {address} from {}", path.display());
+        }
+        PersistCommand::List => {
+            let Some(path) = find_config_file(cli_config) else {
+                println!("No config file; no persistent peers");
+                return Ok(());
+            };
+            let content = std::fs::read_to_string(&path).context("Failed to read config file")?;
+            let peers = entries(&content).map_err(|e| anyhow::anyhow!(e))?;
+            if peers.is_empty() {
+                println!("No [[persistent_peer]] entries in {}", path.display());
+            }
+            for peer in peers {
+                let retries = match peer.max_retries {
+                    0 => "unlimited retries".to_string(),
+                    n => format!("{n} retries"),
+                };
+                println!(
+                    "{:<40} {:<6} {:<12} every {}s, {retries}  {}",
+                    peer.address,
+                    peer.transport.as_str(),
+                    if peer.relay { "relay" } else { "blocks-only" },
+                    peer.retry_interval_secs,
+                    peer.label.as_deref().unwrap_or("")
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `[peer_policy]` counters from the data directory
+pub(crate) fn handle_peers_policy(
+    data_dir: &str,
+    settings: &blvm::peer_policy::PeerPolicyConfig,
+) -> Result<()> {
+    let stats = blvm::peer_policy::PolicyStats::load(Path::new(data_dir));
+    println!("=== Peer Policy ===");
+    println!(
+        "Policy: {}",
+        if settings.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    if let Some(min) = settings.min_protocol_version {
+        println!("Minimum protocol version: {min}");
+    }
+    for pattern in &settings.user_agent_patterns {
+        println!("User agent filter: {pattern}");
+    }
+    if stats.filtered.is_empty() {
+        println!("No peers filtered");
+        return Ok(());
+    }
+    println!("Since {}:", blvm::events::format_utc(stats.since));
+    for (reason, count) in &stats.filtered {
+        println!("  {reason:<32} {count}");
+    }
+    println!("Disconnected: {}", stats.disconnected);
+    println!("Tolerated (below deprioritize_above): {}", stats.tolerated);
+    Ok(())
+}
+
+pub(crate) async fn handle_peers_history(
+    front: Option<SocketAddr>,
+    config: &NodeConfig,
+    data_dir: &Path,
+    limit: usize,
+    kind: Option<&str>,
+) -> Result<()> {
+    let mut events = None;
+    if let Some(front) = front {
+        match rpc_call_with_config(front, config, "getpeereventlog", json!([limit, kind])).await {
+            Ok(result) => {
+                events =
+                    serde_json::from_value::<Vec<blvm::events::Event>>(result["events"].clone())
+                        .ok();
+            }
+            Err(e) => eprintln!("getpeereventlog on {front} failed ({e}); reading the event log"),
+        }
+    }
+    let (source, events) = match events {
+        Some(events) => ("in memory".to_string(), events),
+        None => {
+            let path = data_dir.join(blvm::events::EVENTS_FILE);
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+                }
+            };
+            let prefix = kind.map_or_else(|| "peer.".to_string(), |k| format!("peer.{k}"));
+            let events = blvm::events::parse_lines(&content)
+                .into_iter()
+                .filter(|e| e.kind.starts_with(&prefix))
+                .collect();
+            (path.display().to_string(), events)
+        }
+    };
+
+    println!("=== Peer Event History ({source}) ===");
+    if events.is_empty() {
+        println!("No peer events recorded");
+        return Ok(());
+    }
+    let now = blvm::mocktime::unix_now();
+    for event in &events[events.len().saturating_sub(limit)..] {
+        println!(
+            "  {:>6} ago  {:<10} {}",
+            format_age(now.saturating_sub(event.time)),
+            event.kind.trim_start_matches("peer."),
+            event.message
+        );
+    }
+    Ok(())
+}
+
+pub(crate) async fn handle_network(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    format: Option<blvm::output::Format>,
+) -> Result<()> {
+    let info = rpc_call_with_config(rpc_addr, config, "getnetworkinfo", json!([])).await?;
+
+    if let Some(format) = format {
+        let field = |key: &str| info.get(key).cloned().unwrap_or(Value::Null);
+        let local_addresses: Vec<Value> = info
+            .get("localaddresses")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|addr| addr.get("address").cloned())
+            .collect();
+        let totals = rpc_call_with_config(rpc_addr, config, "getnettotals", json!([]))
+            .await
+            .unwrap_or(Value::Null);
+        let record = blvm::output::Table::record(vec![
+            ("version", field("version")),
+            ("subversion", field("subversion")),
+            ("network_active", field("networkactive")),
+            ("connections", field("connections")),
+            ("local_addresses", json!(local_addresses)),
+            (
+                "bytes_received",
+                totals.get("totalbytesrecv").cloned().unwrap_or(Value::Null),
+            ),
+            (
+                "bytes_sent",
+                totals.get("totalbytessent").cloned().unwrap_or(Value::Null),
+            ),
+        ]);
+        print!("{}", record.render(format));
+        return Ok(());
+    }
+
+    println!("=== Network Information ===");
+    println!(
+        "Version: {}",
+        info.get("version").and_then(|v| v.as_u64()).unwrap_or(0)
+    );
+    println!(
+        "Subversion: {}",
+        info.get("subversion")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+    );
+    println!(
+        "Network Active: {}",
+        info.get("networkactive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    );
+    if let Some(connections) = info.get("connections").and_then(|v| v.as_u64()) {
+        println!("Connections: {connections}");
+    }
+    if let Some(local_addrs) = info.get("localaddresses").and_then(|v| v.as_array()) {
+        if !local_addrs.is_empty() {
+            println!("Local Addresses:");
+            for addr in local_addrs {
+                if let Some(addr_str) = addr.get("address").and_then(|v| v.as_str()) {
+                    println!("  {addr_str}");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+//! `blvm rpc`, `compare-rpc`, `rpc-stats` and `tasks`
+
+use crate::*;
+
+pub(crate) async fn handle_rpc_stats(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    sort: &str,
+) -> Result<()> {
+    use blvm::rpc_stats::{RpcStats, SortBy};
+    let result = rpc_call_with_config(rpc_addr, config, "getrpcstats", json!([]))
+        .await
+        .with_context(|| {
+            format!(
+                "getrpcstats is answered by the [rpc_front] listener only; is {rpc_addr} the front?"
+            )
+        })?;
+    let stats: RpcStats =
+        serde_json::from_value(result).context("Unexpected getrpcstats response")?;
+    let sort = match sort {
+        "errors" => SortBy::Errors,
+        "latency" => SortBy::Latency,
+        _ => SortBy::Calls,
+    };
+
+    println!("=== RPC Statistics ({} methods) ===", stats.methods.len());
+    if stats.since > 0 {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        println!("Since: {} ago", format_age(now.saturating_sub(stats.since)));
+    }
+    if stats.methods.is_empty() {
+        println!("No RPC calls recorded");
+        return Ok(());
+    }
+    println!(
+        "{:<28} {:>9} {:>7} {:>9} {:>9} {:>9} {:>10} {:>10}",
+        "Method", "Calls", "Errors", "p50 (ms)", "p95 (ms)", "p99 (ms)", "Avg req", "Avg resp"
+    );
+    let ms = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("{v:.1}"));
+    for (method, m) in stats.sorted(sort) {
+        let avg = |total: u64| blvm::datadir::format_bytes(total.checked_div(m.calls).unwrap_or(0));
+        println!(
+            "{:<28} {:>9} {:>6.1}% {:>9} {:>9} {:>9} {:>10} {:>10}",
+            method,
+            m.calls,
+            m.error_rate() * 100.0,
+            ms(m.latency_ms.quantile(0.5)),
+            ms(m.latency_ms.quantile(0.95)),
+            ms(m.latency_ms.quantile(0.99)),
+            avg(m.request_bytes),
+            avg(m.response_bytes)
+        );
+    }
+    Ok(())
+}
+
+pub(crate) async fn handle_tasks(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    stalled: bool,
+) -> Result<()> {
+    let result = rpc_call_with_config(rpc_addr, config, "dumptasks", json!([stalled]))
+        .await
+        .with_context(|| {
+            format!(
+                "dumptasks is answered by the [rpc_front] listener only; is {rpc_addr} the front?"
+            )
+        })?;
+    let tasks = result["tasks"].as_array().cloned().unwrap_or_default();
+    let runtime = &result["runtime"];
+    println!("=== Background Tasks ({}) ===", tasks.len());
+    if runtime.is_object() {
+        println!(
+            "Runtime: {} workers, {} alive tasks, {} queued",
+            runtime["workers"], runtime["alive_tasks"], runtime["global_queue_depth"]
+        );
+    }
+    if tasks.is_empty() {
+        println!("No tasks reported");
+        return Ok(());
+    }
+    println!(
+        "{:<20} {:<8} {:>10} {:>11} {:>11} {:>12} {:>11}",
+        "Name", "State", "Polls", "Busy (ms)", "Max (ms)", "Polling (ms)", "Idle (ms)"
+    );
+    let ms = |v: &Value| {
+        v.as_f64()
+            .map_or_else(|| "-".to_string(), |v| format!("{v:.1}"))
+    };
+    for task in &tasks {
+        println!(
+            "{:<20} {:<8} {:>10} {:>11} {:>11} {:>12} {:>11}{}",
+            task["name"].as_str().unwrap_or("?"),
+            task["state"].as_str().unwrap_or("?"),
+            task["polls"].as_u64().unwrap_or(0),
+            ms(&task["busy_ms"]),
+            ms(&task["max_poll_ms"]),
+            ms(&task["polling_ms"]),
+            ms(&task["idle_ms"]),
+            if task["stalled"] == true {
+                "  ⚠️ stalled"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Print the result, or with `raw` the whole response (exits 1 when it carries an error)
+pub(crate) async fn handle_rpc(
+    rpc_addr: SocketAddr,
+    method: &str,
+    params: Value,
+    raw: bool,
+    config: &NodeConfig,
+) -> Result<()> {
+    if raw {
+        blvm::fail_point!("rpc.client.call");
+        let auth = RpcAuth::from_config(config)?;
+        let response = rpc_post(rpc_addr, &auth, &rpc_request(method, params)).await?;
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        if response.get("error").is_some_and(|e| !e.is_null()) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let result = rpc_call_with_config(rpc_addr, config, method, params).await;
+    let result = if blvm::rpc_front::FRONT_METHODS.contains(&method) {
+        result.with_context(|| {
+            format!(
+                "{method} is answered by the [rpc_front] listener only; is {rpc_addr} the front?"
+            )
+        })?
+    } else {
+        result?
+    };
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
+
+/// Print batch results as a JSON array in call order (failed calls as `{"error": ...}`); exits
+/// 1 when any call failed
+pub(crate) async fn handle_rpc_batch(
+    rpc_addr: SocketAddr,
+    calls: &[blvm::rpc_batch::BatchCall],
+    config: &NodeConfig,
+) -> Result<()> {
+    let replies = rpc_batch_with_config(rpc_addr, config, calls).await?;
+    let failed = replies.iter().filter(|reply| reply.is_err()).count();
+    let output: Vec<Value> = replies
+        .into_iter()
+        .map(|reply| reply.unwrap_or_else(|error| json!({ "error": error })))
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    if failed > 0 {
+        eprintln!("{failed} of {} calls failed", calls.len());
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Print differences; exits 1 when the results differ (errors count as results).
+pub(crate) fn handle_compare_rpc(
+    left: Result<Value>,
+    right: Result<Value>,
+    ignore: &[String],
+) -> Result<()> {
+    let as_value =
+        |result: Result<Value>| result.unwrap_or_else(|e| json!({ "error": e.to_string() }));
+    let (left, right) = (as_value(left), as_value(right));
+    let diffs = blvm::json_diff::diff(&left, &right, ignore);
+    if diffs.is_empty() {
+        println!("Results match");
+        return Ok(());
+    }
+    for diff in &diffs {
+        println!("{diff}");
+    }
+    println!("{} difference(s)", diffs.len());
+    std::process::exit(1);
+}
@@ -0,0 +1,137 @@
+//! `blvm wallet` and `blvm silent-payments`
+
+use crate::*;
+
+async fn handle_silent_payments(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    subcommand: &SilentPaymentCommand,
+) -> Result<()> {
+    use blvm::silent_payments::Receiver;
+    let receiver = |spend_pubkey: &str| -> Result<Receiver> {
+        let scan_key = env::var("BLVM_SP_SCAN_KEY")
+            .context("Set BLVM_SP_SCAN_KEY to the scan secret key (hex)")?;
+        let scan_key: [u8; 32] = hex::decode(scan_key.trim())
+            .ok()
+            .and_then(|k| k.try_into().ok())
+            .context("BLVM_SP_SCAN_KEY must be 32 bytes of hex")?;
+        let spend_pubkey: [u8; 33] = hex::decode(spend_pubkey)
+            .ok()
+            .and_then(|k| k.try_into().ok())
+            .context("--spend-pubkey must be a 33-byte compressed public key (hex)")?;
+        Receiver::new(&scan_key, &spend_pubkey).map_err(|e| anyhow::anyhow!(e))
+    };
+    match subcommand {
+        SilentPaymentCommand::Address { spend_pubkey } => {
+            let receiver = receiver(spend_pubkey)?;
+            let info =
+                rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await?;
+            let testnet = info.get("chain").and_then(|v| v.as_str()) != Some("main");
+            println!("{}", receiver.address(testnet).encode());
+        }
+        SilentPaymentCommand::Scan {
+            spend_pubkey,
+            from,
+            to,
+        } => {
+            let receiver = receiver(spend_pubkey)?;
+            let tip = rpc_call_with_config(rpc_addr, config, "getblockcount", json!([]))
+                .await?
+                .as_u64()
+                .unwrap_or(0);
+            let to = to.unwrap_or(tip).min(tip);
+            if *from > to {
+                anyhow::bail!("--from {} is above the last block to scan ({})", from, to);
+            }
+            let mut payments = 0;
+            for height in *from..=to {
+                let hash =
+                    rpc_call_with_config(rpc_addr, config, "getblockhash", json!([height])).await?;
+                let block =
+                    rpc_call_with_config(rpc_addr, config, "getblock", json!([hash, 3])).await?;
+                // Skip the coinbase: it has no prevouts to derive a shared secret from
+                for tx in block["tx"].as_array().into_iter().flatten().skip(1) {
+                    let inputs = tx["vin"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(silent_payment_input)
+                        .collect::<Option<Vec<_>>>()
+                        .context("getblock did not return prevouts (verbosity 3)")?;
+                    let outputs: Vec<Vec<u8>> = tx["vout"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|out| {
+                            hex::decode(out["scriptPubKey"]["hex"].as_str().unwrap_or(""))
+                                .unwrap_or_default()
+                        })
+                        .collect();
+                    for found in receiver.scan(&inputs, &outputs) {
+                        let txid = tx["txid"].as_str().unwrap_or("?");
+                        let value = tx["vout"][found.vout as usize]["value"]
+                            .as_f64()
+                            .unwrap_or(0.0);
+                        let unspent = rpc_call_with_config(
+                            rpc_addr,
+                            config,
+                            "gettxout",
+                            json!([txid, found.vout]),
+                        )
+                        .await?;
+                        let status = if unspent.is_null() {
+                            "spent"
+                        } else {
+                            "unspent"
+                        };
+                        println!(
+                            "{}:{} {:.8} BTC height {} {} tweak {}",
+                            txid,
+                            found.vout,
+                            value,
+                            height,
+                            status,
+                            hex::encode(found.tweak)
+                        );
+                        payments += 1;
+                    }
+                }
+            }
+            println!("Scanned blocks {from}-{to}: {payments} payment(s) found");
+            if payments > 0 {
+                println!("Spend key for an output = spend secret key + tweak (mod n)");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// getblock verbosity 3 input with its prevout; `None` when the prevout is missing
+fn silent_payment_input(vin: &Value) -> Option<blvm::silent_payments::TxInput> {
+    let bytes = |v: &Value| v.as_str().and_then(|h| hex::decode(h).ok());
+    Some(blvm::silent_payments::TxInput {
+        txid: blvm::hash::from_display_hex(vin["txid"].as_str()?)?,
+        vout: vin["vout"].as_u64()? as u32,
+        script_sig: bytes(&vin["scriptSig"]["hex"]).unwrap_or_default(),
+        witness: vin["txinwitness"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(bytes)
+            .collect(),
+        prevout_script: bytes(&vin["prevout"]["scriptPubKey"]["hex"])?,
+    })
+}
+
+pub(crate) async fn handle_wallet(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    subcommand: &WalletCommand,
+) -> Result<()> {
+    match subcommand {
+        WalletCommand::Sp { subcommand } => {
+            handle_silent_payments(rpc_addr, config, subcommand).await?
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,163 @@
+//! `blvm start --dry-run`: startup checks without starting the node
+
+use super::version::compiled_features;
+use crate::*;
+
+/// `start --dry-run`: run every startup check that does not mutate state. Port availability is
+/// tested by binding each port and releasing it at once; chainstate stores are only checked to
+/// look intact (their files are read, never opened as a database).
+pub(crate) fn handle_start_dry_run(
+    config: &NodeConfig,
+    extra: &blvm::extra_config::ExtraConfig,
+    data_dir: &str,
+    listen_addr: SocketAddr,
+    rpc_addr: SocketAddr,
+    network: &Network,
+) -> Result<()> {
+    let mut failures = 0usize;
+    let mut check = |ok: bool, message: String| {
+        if ok {
+            println!("  ✅ {message}");
+        } else {
+            println!("  ❌ {message}");
+            failures += 1;
+        }
+    };
+
+    println!("=== Startup Dry Run ===");
+    println!("Network: {}", network_from_cli_enum(network));
+    println!("Data directory: {data_dir}");
+    println!("P2P listen address: {listen_addr}");
+    println!("RPC address: {rpc_addr}");
+    println!("\nChecks:");
+    let addr_relay = extra.addr_relay.resolve(config.enable_self_advertisement);
+    check(true, format!("Address relay: {}", addr_relay.describe()));
+
+    // Data directory: must exist (or be creatable) and be writable.
+    let data_path = Path::new(data_dir);
+    if data_path.exists() {
+        let probe = data_path.join(".blvm-dry-run");
+        let writable = std::fs::write(&probe, b"").is_ok();
+        let _ = std::fs::remove_file(&probe);
+        check(writable, format!("Data directory {data_dir} is writable"));
+        match blvm::datadir::dir_size(data_path) {
+            Ok(0) => check(true, "No existing chainstate (fresh sync)".to_string()),
+            Ok(size) => check(
+                true,
+                format!(
+                    "Existing data found ({}); node will resume from it",
+                    blvm::datadir::format_bytes(size)
+                ),
+            ),
+            Err(e) => check(false, format!("Data directory unreadable: {e}")),
+        }
+        // Chainstate: read each store's header files and check they look intact. blvm-node's
+        // storage is only reachable through prepare_node_store_from_protocol, which can migrate.
+        match blvm::datadir::find_stores(data_path) {
+            Ok(stores) if stores.is_empty() => check(
+                true,
+                "No chainstate store yet; the node will create one".to_string(),
+            ),
+            Ok(stores) => {
+                for store in stores {
+                    let label = format!(
+                        "Chainstate {} store {} ({})",
+                        store.kind.as_str(),
+                        store.path.display(),
+                        blvm::datadir::format_bytes(store.bytes)
+                    );
+                    match store.problem {
+                        None => check(true, format!("{label} looks intact")),
+                        Some(problem) => check(false, format!("{label} looks damaged: {problem}")),
+                    }
+                }
+            }
+            Err(e) => check(false, format!("Chainstate unreadable: {e}")),
+        }
+        // Saved fee estimator state makes estimatesmartfee usable right after start
+        let fee_age = std::fs::metadata(data_path.join(blvm::datadir::FEE_ESTIMATES_FILE))
+            .and_then(|meta| meta.modified())
+            .ok()
+            .map(|modified| modified.elapsed().unwrap_or_default().as_secs());
+        check(
+            true,
+            match fee_age {
+                Some(age) => format!(
+                    "Saved fee estimates from {} ago will be restored",
+                    format_age(age)
+                ),
+                None => "No saved fee estimates; estimation starts cold".to_string(),
+            },
+        );
+    } else {
+        let parent_ok = data_path
+            .parent()
+            .map(|p| p.as_os_str().is_empty() || p.exists())
+            .unwrap_or(true);
+        check(
+            parent_ok,
+            format!("Data directory {data_dir} will be created"),
+        );
+    }
+
+    // Ports: bind and release immediately. Port 0 (nolisten) is picked by the OS at start.
+    for (label, addr) in [("P2P", listen_addr), ("RPC", rpc_addr)] {
+        if addr.port() == 0 {
+            check(
+                true,
+                format!("{label} listener on an ephemeral port of {}", addr.ip()),
+            );
+            continue;
+        }
+        match std::net::TcpListener::bind(addr) {
+            Ok(_) => check(true, format!("{label} port {addr} is available")),
+            Err(e) => check(false, format!("{label} port {addr} is not available: {e}")),
+        }
+    }
+
+    // Modules: every manifest on disk must be accepted by this build.
+    match config.modules.as_ref().filter(|m| m.enabled) {
+        Some(modules) => {
+            let features = compiled_features();
+            let manifests: Vec<PathBuf> = std::fs::read_dir(&modules.modules_dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(|e| e.ok())
+                        .map(|e| e.path().join("module.toml"))
+                        .filter(|p| p.exists())
+                        .collect()
+                })
+                .unwrap_or_default();
+            if manifests.is_empty() {
+                check(
+                    true,
+                    format!("No module manifests in {}", modules.modules_dir),
+                );
+            }
+            for manifest_path in manifests {
+                match blvm::module_manifest::ModuleManifest::from_file(&manifest_path) {
+                    Ok(manifest) => {
+                        let errors = manifest.compatibility_errors(&features);
+                        let detail = if errors.is_empty() {
+                            "compatible".to_string()
+                        } else {
+                            errors.join("; ")
+                        };
+                        check(
+                            errors.is_empty(),
+                            format!("Module {} {}: {detail}", manifest.name, manifest.version),
+                        );
+                    }
+                    Err(e) => check(false, format!("{}: {e}", manifest_path.display())),
+                }
+            }
+        }
+        None => check(true, "Module system disabled".to_string()),
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{failures} check(s) failed; the node would not start cleanly");
+    }
+    println!("\n✅ All checks passed; `blvm start` would proceed with this configuration");
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//! `blvm stats`, `mempool histogram` and `opreturn scan`
+
+use crate::*;
+
+/// Output script types of a block given by hash or height; returns the block hash too
+pub(crate) async fn fetch_script_types(
+    rpc_addr: SocketAddr,
+    auth: &RpcAuth,
+    block: &Value,
+) -> Result<(String, blvm::script_stats::Breakdown)> {
+    let hash = match block {
+        Value::String(hash) => hash.clone(),
+        Value::Number(height) => {
+            let hash = rpc_call_as(rpc_addr, auth, "getblockhash", json!([height])).await?;
+            hash.as_str().unwrap_or_default().to_string()
+        }
+        other => anyhow::bail!("Expected a block hash or height, got {other}"),
+    };
+    let raw = rpc_call_as(rpc_addr, auth, "getblock", json!([hash, 0])).await?;
+    let bytes = hex::decode(raw.as_str().unwrap_or_default()).context("Invalid block hex")?;
+    let block = blvm::decode::Block::decode(&bytes)
+        .map_err(|e| anyhow::anyhow!("Undecodable block {hash}: {e}"))?;
+    Ok((hash, blvm::script_stats::tally(&block)))
+}
+
+pub(crate) async fn handle_stats_scripts(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    data_dir: &Path,
+    from: Option<u64>,
+    to: Option<u64>,
+    as_json: bool,
+) -> Result<()> {
+    use blvm::script_stats::{Breakdown, ScriptStats, shares};
+    let (label, totals, last) = match from {
+        Some(from) => {
+            let to = match to {
+                Some(to) => to,
+                None => rpc_call_with_config(rpc_addr, config, "getblockcount", json!([]))
+                    .await?
+                    .as_u64()
+                    .unwrap_or(0),
+            };
+            if from > to {
+                anyhow::bail!("--from {from} is above --to {to}");
+            }
+            let auth = RpcAuth::from_config(config)?;
+            let mut totals = Breakdown::new();
+            for height in from..=to {
+                let (_, types) = fetch_script_types(rpc_addr, &auth, &json!(height)).await?;
+                for (kind, stats) in types {
+                    let total = totals.entry(kind).or_default();
+                    total.outputs += stats.outputs;
+                    total.value += stats.value;
+                }
+            }
+            (format!("heights {from}..={to}"), totals, None)
+        }
+        None => {
+            let Some(stats) = ScriptStats::load(data_dir) else {
+                println!(
+                    "No script statistics ({} is written while a node with [script_stats] enabled runs; or pass --from)",
+                    data_dir.join(blvm::script_stats::STATE_FILE).display()
+                );
+                return Ok(());
+            };
+            let label = match (stats.from_height, stats.last()) {
+                (Some(from), Some(last)) => format!("heights {from}..={}", last.height),
+                _ => "no blocks yet".to_string(),
+            };
+            (label, stats.totals.clone(), stats.last().cloned())
+        }
+    };
+    if as_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(
+                &json!({ "range": label, "totals": totals, "last_block": last })
+            )?
+        );
+        return Ok(());
+    }
+    let print = |title: &str, types: &Breakdown| {
+        println!("=== {title} ===");
+        println!(
+            "{:<16} {:>12} {:>8} {:>20}",
+            "Type", "Outputs", "Share", "Value (BTC)"
+        );
+        for (kind, stats, share) in shares(types) {
+            println!(
+                "{:<16} {:>12} {:>7.2}% {:>20.8}",
+                kind,
+                stats.outputs,
+                share * 100.0,
+                stats.value as f64 / 100_000_000.0
+            );
+        }
+    };
+    print(&format!("Output Script Types ({label})"), &totals);
+    if let Some(last) = last {
+        println!();
+        print(
+            &format!("Last Block {} ({})", last.height, last.hash),
+            &last.types,
+        );
+    }
+    Ok(())
+}
+
+pub(crate) async fn handle_mempool_histogram(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    as_json: bool,
+) -> Result<()> {
+    use blvm::mempool_histogram::{MempoolHistogram, render, rows_from_json};
+    // Served by the RPC front only; blvm-node has no such method, so the histogram is then built
+    // here from the verbose mempool. Other errors (auth, connection) are reported as they are.
+    let histogram =
+        match rpc_call_with_config(rpc_addr, config, "getmempoolhistogram", json!([])).await {
+            Ok(histogram) => histogram,
+            Err(e) if !e.to_string().contains("-32601") => return Err(e),
+            Err(_) => {
+                let mut histogram = MempoolHistogram::default();
+                load_mempool_histogram(rpc_addr, config, &mut histogram).await?;
+                histogram.to_json()
+            }
+        };
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&histogram)?);
+        return Ok(());
+    }
+    let count = histogram.get("count").and_then(|v| v.as_u64()).unwrap_or(0);
+    let vsize = histogram.get("vsize").and_then(|v| v.as_u64()).unwrap_or(0);
+    println!("=== Mempool Fee Rates ({count} txs, {vsize} vB) ===");
+    for line in render(&rows_from_json(&histogram), 40) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Replace the histogram's contents with the verbose mempool (one RPC call)
+pub(crate) async fn load_mempool_histogram(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    histogram: &mut blvm::mempool_histogram::MempoolHistogram,
+) -> Result<()> {
+    let mempool = rpc_call_with_config(rpc_addr, config, "getrawmempool", json!([true])).await?;
+    *histogram = Default::default();
+    for (txid, entry) in mempool.as_object().into_iter().flatten() {
+        if let Some(entry) = blvm::mempool_histogram::Entry::from_rpc(entry) {
+            histogram.insert(txid.clone(), entry);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) async fn handle_opreturn_scan(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    from: u64,
+    to: Option<u64>,
+    prefixes: &[String],
+    protocols: &[String],
+) -> Result<()> {
+    use std::io::Write;
+    let known = blvm::opreturn::protocol_names();
+    if let Some(unknown) = protocols.iter().find(|p| !known.contains(&p.as_str())) {
+        anyhow::bail!("Unknown protocol '{unknown}' (known: {})", known.join(", "));
+    }
+    let filter = blvm::opreturn::Filter {
+        protocols: protocols.to_vec(),
+        prefixes: prefixes
+            .iter()
+            .map(|p| hex::decode(p).with_context(|| format!("Invalid --prefix hex '{p}'")))
+            .collect::<Result<_>>()?,
+    };
+    let to = match to {
+        Some(to) => to,
+        None => rpc_call_with_config(rpc_addr, config, "getblockcount", json!([]))
+            .await?
+            .as_u64()
+            .unwrap_or(0),
+    };
+    if from > to {
+        anyhow::bail!("--from {from} is above --to {to}");
+    }
+    let mut stdout = std::io::stdout().lock();
+    for height in from..=to {
+        let hash = rpc_call_with_config(rpc_addr, config, "getblockhash", json!([height])).await?;
+        let hash = hash.as_str().unwrap_or_default().to_string();
+        let raw = rpc_call_with_config(rpc_addr, config, "getblock", json!([hash, 0])).await?;
+        let bytes = hex::decode(raw.as_str().unwrap_or_default()).context("Invalid block hex")?;
+        let block = blvm::decode::Block::decode(&bytes)
+            .map_err(|e| anyhow::anyhow!("Undecodable block {hash}: {e}"))?;
+        for record in blvm::opreturn::scan_block(&block, height, &hash, &filter) {
+            writeln!(stdout, "{}", serde_json::to_string(&record)?)?;
+        }
+        // Stream block by block, so consumers see progress on long ranges
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+pub(crate) fn handle_stats_fees(
+    data_dir: &Path,
+    from: Option<u64>,
+    to: Option<u64>,
+    as_json: bool,
+) -> Result<()> {
+    let archive = blvm::fee_history::Archive::new(data_dir);
+    let Some((first, last)) = archive.block_range() else {
+        println!(
+            "No fee history ({} is written while a node with [fee_history] enabled runs)",
+            data_dir.join(blvm::fee_history::ARCHIVE_DIR).display()
+        );
+        return Ok(());
+    };
+    let (from, to) = (from.unwrap_or(first), to.unwrap_or(last));
+    let history = archive.query(from, to)?;
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&history)?);
+        return Ok(());
+    }
+    let blocks = history["blocks"].as_array().cloned().unwrap_or_default();
+    println!("=== Fee History (heights {from}..={to}, archive {first}..={last}) ===");
+    println!(
+        "{:>8} {:>6} {:>12} {:>6} {:>6} {:>6} {:>6}",
+        "height", "txs", "fees (sat)", "min", "median", "avg", "max"
+    );
+    for block in &blocks {
+        let field = |v: &Value| v.as_u64().unwrap_or(0);
+        println!(
+            "{:>8} {:>6} {:>12} {:>6} {:>6} {:>6} {:>6}",
+            field(&block["height"]),
+            field(&block["txs"]),
+            field(&block["totalfee"]),
+            field(&block["minfeerate"]),
+            field(&block["feerate_percentiles"][2]),
+            field(&block["avgfeerate"]),
+            field(&block["maxfeerate"])
+        );
+    }
+    let snapshots = history["mempool"].as_array().map_or(0, |s| s.len());
+    println!(
+        "{} block(s), {} mempool snapshot(s) (fee rates in sat/vB)",
+        blocks.len(),
+        snapshots
+    );
+    Ok(())
+}
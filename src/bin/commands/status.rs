@@ -0,0 +1,483 @@
+//! `blvm status`, `health`, `stop`, `wait-sync`, `sync` and `replica status`
+
+use crate::*;
+
+// Subcommand handlers
+pub(crate) async fn handle_status(
+    rpc_addr: SocketAddr,
+    front: Option<SocketAddr>,
+    config: &NodeConfig,
+    data_dir: &str,
+    format: Option<blvm::output::Format>,
+) -> Result<()> {
+    let chain_info = rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await?;
+    let network_info = rpc_call_with_config(rpc_addr, config, "getnetworkinfo", json!([])).await?;
+    let peer_info = rpc_call_with_config(rpc_addr, config, "getpeerinfo", json!([])).await?;
+    let state = node_state(front, config).await;
+    let phase = state
+        .as_ref()
+        .and_then(|state| state["phase"].as_str())
+        .map_or_else(|| node_phase(&chain_info).to_string(), str::to_string);
+    let chain_split = blvm::chain_split::ChainSplitState::load(Path::new(data_dir));
+
+    if let Some(format) = format {
+        let alerts = blvm::alerts::AlertState::load(Path::new(data_dir));
+        let record = blvm::output::Table::record(vec![
+            (
+                "blocks",
+                chain_info.get("blocks").cloned().unwrap_or(json!(0)),
+            ),
+            (
+                "chain",
+                chain_info.get("chain").cloned().unwrap_or(Value::Null),
+            ),
+            (
+                "verification_progress",
+                chain_info
+                    .get("verificationprogress")
+                    .cloned()
+                    .unwrap_or(json!(0.0)),
+            ),
+            (
+                "connected_peers",
+                json!(peer_info.as_array().map(|a| a.len()).unwrap_or(0)),
+            ),
+            (
+                "network_active",
+                network_info
+                    .get("networkactive")
+                    .cloned()
+                    .unwrap_or(json!(false)),
+            ),
+            ("phase", json!(phase)),
+            ("active_alerts", json!(alerts.active.len())),
+            (
+                "block_anomalies_24h",
+                json!(
+                    blvm::block_anomalies::AnomalyLog::load(Path::new(data_dir))
+                        .recent(blvm::mocktime::unix_now(), 86_400)
+                        .count()
+                ),
+            ),
+            (
+                "chain_split",
+                json!(chain_split.split.as_ref().map(|split| split.warning())),
+            ),
+        ]);
+        print!("{}", record.render(format));
+        return Ok(());
+    }
+
+    println!("=== Node Status ===");
+    if let Some(split) = &chain_split.split {
+        println!("WARNING: {}", split.warning());
+    }
+    println!(
+        "Block Height: {}",
+        chain_info
+            .get("blocks")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+    );
+    println!(
+        "Chain: {}",
+        chain_info
+            .get("chain")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+    );
+    println!(
+        "Verification Progress: {:.2}%",
+        chain_info
+            .get("verificationprogress")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0)
+            * 100.0
+    );
+    println!(
+        "Connected Peers: {}",
+        peer_info.as_array().map(|a| a.len()).unwrap_or(0)
+    );
+    println!(
+        "Network Active: {}",
+        network_info
+            .get("networkactive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    );
+
+    match &state {
+        Some(state) => {
+            let now = blvm::mocktime::unix_now();
+            let age = |time: &Value| format_age(now.saturating_sub(time.as_u64().unwrap_or(now)));
+            println!("Phase: {phase} (for {})", age(&state["since"]));
+            let history = state["history"].as_array().cloned().unwrap_or_default();
+            for transition in history.iter().rev().skip(1).take(5) {
+                println!(
+                    "  {} ago: {}",
+                    age(&transition["time"]),
+                    transition["phase"].as_str().unwrap_or("?")
+                );
+            }
+        }
+        None => println!("Phase: {phase}"),
+    }
+
+    let revalidation = blvm::revalidation::RevalidationReport::load(Path::new(data_dir));
+    if revalidation.blocks_checked > 0 {
+        println!(
+            "Revalidation: {} blocks re-checked, {} problem(s)",
+            revalidation.blocks_checked,
+            revalidation.findings.len()
+        );
+        for warning in revalidation.warnings().iter().take(5) {
+            println!("  WARNING: {warning}");
+        }
+    }
+
+    let anomalies = blvm::block_anomalies::AnomalyLog::load(Path::new(data_dir));
+    let recent: Vec<_> = anomalies
+        .recent(blvm::mocktime::unix_now(), 86_400)
+        .collect();
+    if !recent.is_empty() {
+        println!("Block Anomalies (last 24h): {}", recent.len());
+        for entry in recent.iter().rev().take(5) {
+            println!("  WARNING: block {}: {}", entry.height, entry.message);
+        }
+    }
+
+    let alerts = blvm::alerts::AlertState::load(Path::new(data_dir));
+    if !alerts.active.is_empty() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        println!("Active Alerts:");
+        for alert in &alerts.active {
+            println!(
+                "  ALERT {}: {} (for {})",
+                alert.name,
+                alert.message,
+                format_age(now.saturating_sub(alert.since))
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The phase history `blvm start` records, from the `[rpc_front]` listener's `getnodestate`
+async fn node_state(front: Option<SocketAddr>, config: &NodeConfig) -> Option<Value> {
+    rpc_call_with_config(front?, config, "getnodestate", json!([]))
+        .await
+        .ok()
+}
+
+/// Sync phase derived from `getblockchaininfo`
+pub(crate) fn node_phase(chain_info: &Value) -> blvm::node_state::NodePhase {
+    let blocks = chain_info
+        .get("blocks")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let headers = chain_info
+        .get("headers")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let ibd = chain_info
+        .get("initialblockdownload")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    blvm::node_state::NodePhase::from_chain_info(blocks, headers, ibd)
+}
+
+pub(crate) async fn handle_health(
+    rpc_addr: SocketAddr,
+    front: Option<SocketAddr>,
+    config: &NodeConfig,
+    ready: bool,
+) -> Result<()> {
+    match rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await {
+        Ok(chain_info) if ready => {
+            // The recorded phase when the front is reachable, else the one getblockchaininfo shows
+            let (phase, is_ready) = match node_state(front, config).await {
+                Some(state) => (
+                    state["phase"].as_str().unwrap_or("unknown").to_string(),
+                    state["ready"] == true,
+                ),
+                None => {
+                    let phase = node_phase(&chain_info);
+                    (phase.to_string(), phase.is_ready())
+                }
+            };
+            if is_ready {
+                println!("✅ Node is ready ({phase})");
+                Ok(())
+            } else {
+                eprintln!("❌ Node is not ready (phase: {phase})");
+                std::process::exit(1);
+            }
+        }
+        Ok(_) => {
+            println!("✅ Node is healthy");
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("❌ Health check failed: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `stop`: ask the node to shut down, then poll its RPC port until nothing listens there
+pub(crate) async fn handle_stop(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    timeout: u64,
+) -> Result<()> {
+    use std::time::{Duration, Instant};
+    match rpc_call_with_config(rpc_addr, config, "stop", json!([])).await {
+        Ok(reply) => println!("{}", reply.as_str().unwrap_or("Node stopping").trim_end()),
+        Err(e) => {
+            eprintln!("❌ Could not stop node at {rpc_addr}: {e:#}");
+            std::process::exit(1);
+        }
+    }
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    loop {
+        let connect = tokio::net::TcpStream::connect(rpc_addr);
+        match tokio::time::timeout(Duration::from_secs(1), connect).await {
+            Ok(Err(_)) => {
+                println!("✅ Node stopped");
+                return Ok(());
+            }
+            Ok(Ok(_)) | Err(_) => {}
+        }
+        if Instant::now() >= deadline {
+            eprintln!("❌ Node still answering on {rpc_addr} after {timeout}s");
+            std::process::exit(2);
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+pub(crate) async fn handle_wait_sync(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    height: Option<u64>,
+    timeout: Option<u64>,
+) -> Result<()> {
+    use std::time::{Duration, Instant};
+    let deadline = timeout.map(|secs| Instant::now() + Duration::from_secs(secs));
+    let mut last_blocks = None;
+    loop {
+        match rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await {
+            Ok(info) => {
+                let field = |key: &str| info.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+                let (blocks, headers) = (field("blocks"), field("headers"));
+                let ibd = info
+                    .get("initialblockdownload")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                let target = height.unwrap_or(headers);
+                let reached = match height {
+                    Some(h) => blocks >= h,
+                    None => !ibd && headers > 0 && blocks >= headers,
+                };
+                if reached {
+                    println!("✅ Node at height {blocks}");
+                    return Ok(());
+                }
+                if last_blocks != Some(blocks) {
+                    println!("⏳ Height {blocks} / {target}");
+                    last_blocks = Some(blocks);
+                }
+            }
+            Err(e) => tracing::debug!("Node not reachable yet: {e}"),
+        }
+
+        let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+        if remaining == Some(Duration::ZERO) {
+            eprintln!(
+                "❌ Timed out after {}s waiting for {}",
+                timeout.unwrap_or(0),
+                height.map_or("sync".to_string(), |h| format!("height {h}"))
+            );
+            std::process::exit(1);
+        }
+        // Let the node block until something changes; sleep instead when it lacks the
+        // wait RPCs or is not up yet
+        let wait = remaining
+            .unwrap_or(Duration::MAX)
+            .min(Duration::from_secs(30));
+        let ms = wait.as_millis() as u64;
+        let (method, params) = match height {
+            Some(h) => ("waitforblockheight", json!([h, ms])),
+            None => ("waitfornewblock", json!([ms])),
+        };
+        if rpc_call_with_config(rpc_addr, config, method, params)
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(wait.min(Duration::from_secs(2))).await;
+        }
+    }
+}
+
+pub(crate) async fn handle_sync(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    data_dir: &Path,
+) -> Result<()> {
+    let info = rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await?;
+
+    let blocks = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+    let headers = info.get("headers").and_then(|v| v.as_u64()).unwrap_or(0);
+    let progress = info
+        .get("verificationprogress")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.0);
+    let initial_block_download = info
+        .get("initialblockdownload")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    println!("=== Sync Status ===");
+    println!("Blocks: {blocks}");
+    println!("Headers: {headers}");
+    println!("Progress: {:.2}%", progress * 100.0);
+    if initial_block_download {
+        println!("Initial block download: yes (active IBD)");
+    }
+
+    if blocks == headers && progress >= 1.0 {
+        println!("Status: ✅ Fully synced");
+    } else if headers > blocks {
+        println!("Status: ⏳ Syncing ({} blocks behind)", headers - blocks);
+    } else if progress < 0.999 && blocks > 0 {
+        println!("Status: ⏳ Verifying downloaded blocks");
+        println!(
+            "Note: During active IBD, node logs (`IBD: <height> / <tip>`) are often ahead of this RPC view."
+        );
+    } else {
+        println!("Status: ⏳ Verifying");
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let samples = blvm::sync_history::load(data_dir, now.saturating_sub(SYNC_HISTORY_WINDOW));
+    if samples.len() >= 2 {
+        print_sync_sparkline(&samples, now);
+    }
+
+    Ok(())
+}
+
+/// Time span shown by `blvm sync` history output
+const SYNC_HISTORY_WINDOW: u64 = 24 * 3600;
+
+fn print_sync_sparkline(samples: &[blvm::sync_history::Sample], now: u64) {
+    use blvm::sync_history::{blocks_per_hour, height_columns, sparkline};
+    // One column per half hour
+    let columns = height_columns(samples, now.saturating_sub(SYNC_HISTORY_WINDOW), now, 48);
+    println!("Last 24h: |{}|", sparkline(&columns));
+    if let (Some(first), Some(last)) = (samples.first(), samples.last()) {
+        println!(
+            "           height {} -> {}, {:.0} blocks/hour",
+            first.blocks,
+            last.blocks,
+            blocks_per_hour(samples).unwrap_or(0.0)
+        );
+    }
+}
+
+/// Recorded sync samples for the last 24h (no node connection needed)
+pub(crate) fn handle_sync_history(data_dir: &Path, as_json: bool) -> Result<()> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let samples = blvm::sync_history::load(data_dir, now.saturating_sub(SYNC_HISTORY_WINDOW));
+    if as_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "window_secs": SYNC_HISTORY_WINDOW,
+                "blocks_per_hour": blvm::sync_history::blocks_per_hour(&samples),
+                "samples": samples,
+            }))?
+        );
+        return Ok(());
+    }
+    if samples.is_empty() {
+        println!(
+            "No sync samples in the last 24h ({} is written while the node runs)",
+            data_dir.join(blvm::sync_history::HISTORY_FILE).display()
+        );
+        return Ok(());
+    }
+    println!("=== Sync History (24h) ===");
+    print_sync_sparkline(&samples, now);
+    println!();
+    println!(
+        "{:<20} {:>10} {:>10} {:>9}",
+        "Time (UTC)", "Blocks", "Headers", "Progress"
+    );
+    // Roughly hourly rows
+    let mut last_row = 0;
+    for (i, sample) in samples.iter().enumerate() {
+        if sample.time < last_row + 3600 && i + 1 < samples.len() {
+            continue;
+        }
+        last_row = sample.time;
+        println!(
+            "{:<20} {:>10} {:>10} {:>8.2}%",
+            blvm::events::format_utc(sample.time),
+            sample.blocks,
+            sample.headers,
+            sample.progress * 100.0
+        );
+    }
+    Ok(())
+}
+
+pub(crate) fn handle_replica_status(data_dir: &Path, as_json: bool) -> Result<()> {
+    let Some(status) = blvm::replica::load(data_dir) else {
+        println!(
+            "No replica status ({} is written while a node with [replica] enabled runs)",
+            data_dir.join(blvm::replica::STATE_FILE).display()
+        );
+        return Ok(());
+    };
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let or_unknown = |v: Option<u64>| v.map_or("unknown".to_string(), |v| v.to_string());
+    println!("=== Replica Status ===");
+    println!(
+        "Primary:          {} ({})",
+        status.primary,
+        if status.primary_connected {
+            "connected"
+        } else {
+            "not connected"
+        }
+    );
+    println!("Local height:     {}", status.local_height);
+    println!("Primary height:   {}", or_unknown(status.primary_height));
+    println!("Lag (blocks):     {}", or_unknown(status.lag_blocks));
+    println!("Caught up (RPC):  {}", status.blocks_caught_up);
+    println!("Peers dropped:    {}", status.peers_dropped);
+    println!(
+        "Last check:       {} ({} ago)",
+        blvm::events::format_utc(status.time),
+        format_age(now.saturating_sub(status.time))
+    );
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! `blvm tx analyze` and `blvm proof`
+
+use crate::*;
+
+/// Hex string, or a file holding hex text or raw bytes
+pub(crate) fn read_hex_or_file(input: &str) -> Result<Vec<u8>> {
+    let path = Path::new(input);
+    if path.is_file() {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        return Ok(std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|text| hex::decode(text.trim()).ok())
+            .unwrap_or(bytes));
+    }
+    hex::decode(input.trim()).context("Input is neither a file nor valid hex")
+}
+
+/// Outputs spent by `tx`: unspent ones (confirmed or in the mempool) from gettxout, else from
+/// the funding transaction (needs txindex or a mempool parent)
+pub(crate) async fn fetch_prevouts(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    tx: &blvm::decode::Transaction,
+) -> Result<Vec<blvm::tx_analysis::Prevout>> {
+    let mut prevouts = Vec::with_capacity(tx.inputs.len());
+    for input in &tx.inputs {
+        let txid = blvm::hash::to_display_hex(&input.prev_txid);
+        let vout = input.prev_vout;
+        let mut output =
+            rpc_call_with_config(rpc_addr, config, "gettxout", json!([txid, vout, true])).await?;
+        if output.is_null() {
+            let funding =
+                rpc_call_with_config(rpc_addr, config, "getrawtransaction", json!([txid, true]))
+                    .await
+                    .with_context(|| format!("{txid}:{vout} is spent or unknown"))?;
+            output = funding["vout"][vout as usize].clone();
+        }
+        let value = output["value"]
+            .as_f64()
+            .with_context(|| format!("No output {txid}:{vout}"))?;
+        let script = output["scriptPubKey"]["hex"].as_str().unwrap_or_default();
+        prevouts.push(blvm::tx_analysis::Prevout {
+            value: blvm::block_fees::btc_to_sat(value),
+            script_pubkey: hex::decode(script).context("Invalid scriptPubKey hex")?,
+        });
+    }
+    Ok(prevouts)
+}
+
+/// `prevouts` is the reason the fee is unknown when missing
+pub(crate) fn handle_tx_analyze(
+    tx: &blvm::decode::Transaction,
+    prevouts: std::result::Result<Vec<blvm::tx_analysis::Prevout>, String>,
+    as_json: bool,
+) -> Result<()> {
+    let analysis = blvm::tx_analysis::analyze(tx, prevouts.as_deref().ok());
+    if as_json {
+        let outputs: Vec<Value> = analysis
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(n, o)| {
+                json!({
+                    "n": n,
+                    "value": o.value,
+                    "type": o.kind,
+                    "dust_threshold": o.dust_threshold,
+                    "dust": o.is_dust(),
+                })
+            })
+            .collect();
+        let value = json!({
+            "txid": analysis.txid,
+            "wtxid": analysis.wtxid,
+            "size": analysis.size,
+            "base_size": analysis.base_size,
+            "weight": analysis.weight,
+            "vsize": analysis.vsize,
+            "sigop_cost": analysis.sigop_cost,
+            "sigops_complete": analysis.sigops_complete,
+            "bip125_replaceable": analysis.rbf,
+            "inputs": analysis.inputs,
+            "outputs": outputs,
+            "fee": analysis.fee,
+            "fee_rate": analysis.fee_rate(),
+            "standard": analysis.warnings.is_empty(),
+            "warnings": analysis.warnings,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+    println!("txid:     {}", analysis.txid);
+    println!("wtxid:    {}", analysis.wtxid);
+    println!(
+        "size:     {} bytes ({} without witness), weight {}, vsize {}",
+        analysis.size, analysis.base_size, analysis.weight, analysis.vsize
+    );
+    println!(
+        "sigops:   cost {}{}",
+        analysis.sigop_cost,
+        if analysis.sigops_complete {
+            ""
+        } else {
+            " (legacy only; P2SH and witness sigops need the prevouts)"
+        }
+    );
+    println!(
+        "inputs:   {} ({})",
+        analysis.inputs,
+        if analysis.rbf {
+            "signals RBF"
+        } else {
+            "final sequence, no RBF signal"
+        }
+    );
+    println!("outputs:  {}", analysis.outputs.len());
+    for (n, output) in analysis.outputs.iter().enumerate() {
+        println!(
+            "  {:>3} {:>16} sat  {}{}",
+            n,
+            output.value,
+            output.kind,
+            if output.is_dust() { "  DUST" } else { "" }
+        );
+    }
+    match (analysis.fee, analysis.fee_rate(), &prevouts) {
+        (Some(fee), Some(rate), _) => println!("fee:      {fee} sat ({rate:.2} sat/vB)"),
+        (_, _, Err(reason)) => println!("fee:      unknown ({reason})"),
+        _ => println!("fee:      unknown"),
+    }
+    if analysis.warnings.is_empty() {
+        println!("Standard: yes");
+    } else {
+        println!("Warnings:");
+        for warning in &analysis.warnings {
+            println!("  - {warning}");
+        }
+    }
+    Ok(())
+}
+
+fn parse_txid(txid: &str) -> Result<[u8; 32]> {
+    blvm::hash::from_display_hex(txid)
+        .ok_or_else(|| anyhow::anyhow!("Invalid txid '{txid}' (expected 64 hex characters)"))
+}
+
+pub(crate) async fn handle_proof_create(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    txids: &[String],
+    blockhash: Option<&str>,
+) -> Result<()> {
+    let wanted = txids
+        .iter()
+        .map(|t| parse_txid(t))
+        .collect::<Result<Vec<_>>>()?;
+    let blockhash = match blockhash {
+        Some(hash) => hash.to_string(),
+        None => {
+            let tx = rpc_call_with_config(
+                rpc_addr,
+                config,
+                "getrawtransaction",
+                json!([txids[0], true]),
+            )
+            .await
+            .context("Transaction lookup failed (enable txindex or pass --blockhash)")?;
+            tx.get("blockhash")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Transaction {} is not in a block", txids[0]))?
+                .to_string()
+        }
+    };
+    let raw = rpc_call_with_config(rpc_addr, config, "getblock", json!([blockhash, 0])).await?;
+    let bytes = hex::decode(raw.as_str().unwrap_or_default()).context("Invalid block hex")?;
+    let block = blvm::decode::Block::decode(&bytes).context("Invalid block")?;
+    let block_txids: Vec<[u8; 32]> = block.transactions.iter().map(|tx| tx.txid()).collect();
+    for (txid, hash) in txids.iter().zip(&wanted) {
+        if !block_txids.contains(hash) {
+            anyhow::bail!("Transaction {txid} is not in block {blockhash}");
+        }
+    }
+    let proof = blvm::merkle_proof::MerkleProof::build(block.header, &block_txids, &wanted);
+    println!("{}", hex::encode(proof.encode()));
+    Ok(())
+}
+
+pub(crate) async fn handle_proof_verify(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    proof: &str,
+    offline: bool,
+) -> Result<()> {
+    use blvm::hash::to_display_hex;
+    let proof = blvm::merkle_proof::MerkleProof::decode(&read_hex_or_file(proof)?)
+        .context("Invalid proof")?;
+    let matches = proof.verify()?;
+    let blockhash = to_display_hex(&proof.header.hash());
+    if !offline {
+        let header =
+            rpc_call_with_config(rpc_addr, config, "getblockheader", json!([blockhash, true]))
+                .await
+                .with_context(|| format!("Block {blockhash} not found"))?;
+        let confirmations = header
+            .get("confirmations")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(-1);
+        if confirmations < 1 {
+            anyhow::bail!("Block {blockhash} is not in the best chain");
+        }
+        println!("Block: {blockhash} ({confirmations} confirmations)");
+    } else {
+        println!("Block: {blockhash} (not checked against the chain)");
+    }
+    for (index, txid) in matches {
+        println!("{} (index {index})", to_display_hex(&txid));
+    }
+    Ok(())
+}
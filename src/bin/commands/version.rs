@@ -0,0 +1,110 @@
+//! `blvm version`: build, feature and protocol information
+
+use crate::*;
+
+/// Commit the binary was built from (embedded by build.rs)
+const GIT_COMMIT: Option<&str> = option_env!("BLVM_GIT_COMMIT");
+
+/// Version of the JSON-RPC interface served through `[rpc_front]` and used by the CLI; bumped on
+/// incompatible changes
+const RPC_API_VERSION: u32 = 1;
+
+pub(crate) fn handle_version(json: bool) -> Result<()> {
+    if json {
+        let networks: Vec<Value> = Network::value_variants()
+            .iter()
+            .map(|network| {
+                let name = network_from_cli_enum(network);
+                let params = blvm::chain_params::ChainParams::for_network(name);
+                json!({
+                    "name": name,
+                    "magic": params.as_ref().map(|p| hex::encode(p.magic)),
+                    "default_p2p_port": params.as_ref().map(|p| p.default_p2p_port),
+                    "default_rpc_port": params.as_ref().map(|p| p.default_rpc_port),
+                })
+            })
+            .collect();
+        let info = json!({
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "semver": {
+                "major": env!("CARGO_PKG_VERSION_MAJOR").parse::<u64>().unwrap_or(0),
+                "minor": env!("CARGO_PKG_VERSION_MINOR").parse::<u64>().unwrap_or(0),
+                "patch": env!("CARGO_PKG_VERSION_PATCH").parse::<u64>().unwrap_or(0),
+                "pre": env!("CARGO_PKG_VERSION_PRE"),
+            },
+            "git_commit": GIT_COMMIT,
+            "features": compiled_features(),
+            "networks": networks,
+            "rpc_api_version": RPC_API_VERSION,
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("blvm {}", env!("CARGO_PKG_VERSION"));
+    println!("Repository: {}", env!("CARGO_PKG_REPOSITORY"));
+    if let Some(commit) = GIT_COMMIT {
+        println!("Git: {}", &commit[..commit.len().min(12)]);
+    }
+    println!("RPC API: v{RPC_API_VERSION}");
+
+    // Show enabled features
+    println!("\nFeatures:");
+    for feature in compiled_features() {
+        if feature == "bip158" {
+            println!("  ✓ bip158 (always on)");
+        } else {
+            println!("  ✓ {feature}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Node features compiled into this binary (bip158 is always built in).
+pub(crate) fn compiled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    #[cfg(feature = "utxo-commitments")]
+    features.push("utxo-commitments");
+    #[cfg(feature = "dandelion")]
+    features.push("dandelion");
+    #[cfg(feature = "ctv")]
+    features.push("ctv");
+    #[cfg(feature = "stratum-v2")]
+    features.push("stratum-v2");
+    features.push("bip158");
+    #[cfg(feature = "sigop")]
+    features.push("sigop");
+    #[cfg(feature = "governance")]
+    features.push("governance");
+    #[cfg(feature = "iroh")]
+    features.push("iroh");
+    #[cfg(feature = "quinn")]
+    features.push("quinn");
+    #[cfg(feature = "rest-api")]
+    features.push("rest-api");
+    #[cfg(feature = "bip70-http")]
+    features.push("bip70-http");
+    #[cfg(feature = "compression")]
+    features.push("compression");
+    #[cfg(feature = "rocksdb")]
+    features.push("rocksdb");
+    #[cfg(feature = "miniscript")]
+    features.push("miniscript");
+    #[cfg(feature = "silent-payments")]
+    features.push("silent-payments");
+    #[cfg(feature = "wasm-modules")]
+    features.push("wasm-modules");
+    #[cfg(feature = "module-watcher")]
+    features.push("module-watcher");
+    #[cfg(feature = "systemd")]
+    features.push("systemd");
+    #[cfg(feature = "debug-runtime")]
+    features.push("debug-runtime");
+    #[cfg(feature = "pprof")]
+    features.push("pprof");
+    #[cfg(feature = "failpoints")]
+    features.push("failpoints");
+    features
+}
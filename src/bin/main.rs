@@ -3,11 +3,14 @@
 //! Main entry point for the Bitcoin Commons BLVM node binary.
 //! This binary starts a full Bitcoin node using the blvm-node library.
 
+mod commands;
+mod tasks;
+
 use anyhow::{Context, Result};
 use blvm_node::ProtocolVersion;
 use blvm_node::config::NodeConfig;
 use blvm_node::node::Node as ReferenceNode;
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use serde_json::{Value, json};
 use std::env;
 use std::net::SocketAddr;
@@ -15,6 +18,62 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tracing::{error, info, warn};
 
+use commands::bench::handle_bench;
+use commands::block::{handle_analyze_block, handle_block, handle_decode};
+use commands::chain::{
+    handle_chain, handle_chain_genesis, handle_chain_params, handle_chain_precious,
+    handle_next_difficulty, handle_next_difficulty_offline,
+};
+use commands::config::{
+    handle_config_path, handle_config_set, handle_config_show, handle_config_validate,
+};
+use commands::db::{handle_db_invalidate, handle_db_stats, handle_db_verify};
+use commands::events::handle_events_tail;
+use commands::fleet::handle_fleet_exec;
+use commands::mining::{handle_mining_clients, handle_mining_config, handle_mining_status};
+use commands::module::{
+    handle_module, handle_module_cli, handle_module_config_path, installed_module_manifests,
+    modules_data_dir,
+};
+use commands::peers::{
+    handle_network, handle_peers, handle_peers_history, handle_peers_persist, handle_peers_policy,
+    handle_seeds_export,
+};
+use commands::rpc::{
+    handle_compare_rpc, handle_rpc, handle_rpc_batch, handle_rpc_stats, handle_tasks,
+};
+#[cfg(feature = "silent-payments")]
+use commands::silent_payments::handle_wallet;
+use commands::start::handle_start_dry_run;
+use commands::stats::{
+    handle_mempool_histogram, handle_opreturn_scan, handle_stats_fees, handle_stats_scripts,
+};
+use commands::status::{
+    handle_health, handle_replica_status, handle_status, handle_stop, handle_sync,
+    handle_sync_history, handle_wait_sync,
+};
+use commands::tx::{
+    fetch_prevouts, handle_proof_create, handle_proof_verify, handle_tx_analyze, read_hex_or_file,
+};
+use commands::version::handle_version;
+use tasks::alerts::{run_alerts, run_block_anomalies, run_chain_split, run_quarantine_watch};
+use tasks::events::run_event_watch;
+#[cfg(unix)]
+use tasks::events::run_notifications;
+use tasks::fleet::run_fleet_admin;
+use tasks::front::run_rpc_front;
+use tasks::mdns::run_mdns_discovery;
+use tasks::mining::{run_payout_rotation, run_template_refresh};
+use tasks::peers::{
+    run_peer_limits, run_peer_policy, run_peer_timeouts, run_persistent_peers, run_seed_fallback,
+};
+use tasks::replica::run_replica;
+use tasks::service::run_probes;
+#[cfg(all(feature = "systemd", unix))]
+use tasks::service::run_systemd_notify;
+use tasks::stats::{run_fee_history, run_node_phase, run_script_stats, run_sync_sampler};
+use tasks::upkeep::{run_module_quotas, run_revalidation};
+
 #[derive(Parser)]
 #[command(name = "blvm", version, about = "Bitcoin Commons BLVM — Bitcoin node", long_about = None)]
 struct Cli {
@@ -53,6 +112,54 @@ struct Cli {
     #[command(flatten)]
     advanced: AdvancedConfig,
 
+    /// Outbound-only mode: do not accept inbound P2P connections from other hosts; the listener
+    /// moves to an ephemeral loopback port (config: `listen = false`)
+    #[arg(long)]
+    nolisten: bool,
+
+    /// RPC cookie file to authenticate with when the config sets no credentials
+    /// (default: <data_dir>/.cookie, used when it exists)
+    #[arg(long, value_name = "PATH", global = true)]
+    rpc_cookie_file: Option<PathBuf>,
+
+    /// Talk to the node's RPC over HTTPS (certificates are validated)
+    #[arg(long, global = true)]
+    rpc_tls: bool,
+
+    /// PEM CA certificate trusted for the RPC server, in addition to the system roots
+    #[arg(long, value_name = "PATH", global = true, requires = "rpc_tls")]
+    rpc_ca_cert: Option<PathBuf>,
+
+    /// Skip RPC certificate and hostname validation (testing only)
+    #[arg(long, global = true, requires = "rpc_tls")]
+    rpc_insecure: bool,
+
+    /// Give up on an RPC request after this many seconds (default: no limit)
+    #[arg(long, value_name = "SECS", global = true)]
+    rpc_timeout: Option<u64>,
+
+    /// Retry RPC requests that could not connect, with backoff (timed-out requests are not
+    /// retried: the node may already have acted on them)
+    #[arg(long, value_name = "N", global = true, default_value_t = 0)]
+    rpc_retries: u32,
+
+    /// Wait for the RPC server to come up (and finish warming up) instead of failing
+    #[arg(long, global = true)]
+    rpc_wait: bool,
+
+    /// Output of `status`, `chain`, `network` and `peers` as JSON, a table or CSV instead of text
+    #[arg(long, value_enum, global = true)]
+    format: Option<OutputFormat>,
+
+    /// Serve /debug/pprof/ profiling endpoints on this address (requires `pprof` feature)
+    #[arg(long, value_name = "ADDR")]
+    pprof_addr: Option<SocketAddr>,
+
+    /// Discard saved fee estimator state (`fee_estimates.dat`) before starting; estimates are
+    /// rebuilt from newly observed blocks
+    #[arg(long)]
+    reset_fee_estimates: bool,
+
     /// Do not auto-migrate from a Bitcoin Core datadir on start
     #[arg(long)]
     no_auto_migrate: bool,
@@ -70,7 +177,12 @@ struct Cli {
 #[derive(Subcommand)]
 enum Command {
     /// Start the node (default)
-    Start,
+    Start {
+        /// Validate config, data dir, modules and ports, open the chainstate read-only, report what
+        /// would happen, and exit
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Show comprehensive node status
     Status {
         /// RPC server address (overrides config)
@@ -82,19 +194,60 @@ enum Command {
         /// RPC server address (overrides config)
         #[arg(long)]
         rpc_addr: Option<SocketAddr>,
+        /// Readiness: healthy only once the node is in the synced phase
+        #[arg(long)]
+        ready: bool,
+    },
+    /// Block until the node is synced, or reaches --height (exit code 1 on timeout)
+    WaitSync {
+        /// Wait for this block height instead of full sync
+        #[arg(long)]
+        height: Option<u64>,
+        /// Give up after this many seconds (default: wait forever)
+        #[arg(long)]
+        timeout: Option<u64>,
+        /// RPC server address (overrides config)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Shut down a running node over RPC and wait until its RPC endpoint is gone
+    /// (exit code 1 when the node could not be asked to stop, 2 on timeout)
+    Stop {
+        /// Seconds to wait for the node to finish shutting down
+        #[arg(long, default_value_t = 120)]
+        timeout: u64,
+        /// RPC server address (overrides config)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
     },
     /// Show version and build information
-    Version,
+    Version {
+        /// Print build and capability information as JSON (also `--format json`)
+        #[arg(long)]
+        json: bool,
+    },
     /// Show blockchain information
     Chain {
+        #[command(subcommand)]
+        subcommand: Option<ChainCommand>,
         /// RPC server address (overrides config)
-        #[arg(long)]
+        #[arg(long, global = true)]
         rpc_addr: Option<SocketAddr>,
     },
     /// Show connected peers
     Peers {
+        #[command(subcommand)]
+        subcommand: Option<PeersCommand>,
         /// RPC server address (overrides config)
-        #[arg(long)]
+        #[arg(long, global = true)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Fixed seed lists from the address manager
+    Seeds {
+        #[command(subcommand)]
+        subcommand: SeedsCommand,
+        /// RPC server address (overrides config)
+        #[arg(long, global = true)]
         rpc_addr: Option<SocketAddr>,
     },
     /// Show network information
@@ -103,12 +256,57 @@ enum Command {
         #[arg(long)]
         rpc_addr: Option<SocketAddr>,
     },
+    /// Operator event log (new tips, reorgs, bans, restarts)
+    Events {
+        #[command(subcommand)]
+        subcommand: EventsCommand,
+    },
+    /// Admin commands for your own nodes over the authenticated fleet channel
+    Fleet {
+        #[command(subcommand)]
+        subcommand: FleetCommand,
+    },
+    /// Per-method RPC call counts, error rates and latency, as recorded by the `[rpc_front]`
+    /// listener (point --rpc-addr at it)
+    RpcStats {
+        /// Order by call count, error rate, or p95 latency
+        #[arg(long, default_value = "calls", value_parser = ["calls", "errors", "latency"])]
+        sort: String,
+        /// RPC front address (defaults to `[rpc_front] listen`, then the node's RPC address)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Poll statistics of blvm's background tasks, longest current poll first (dumptasks,
+    /// answered by the `[rpc_front]` listener)
+    Tasks {
+        /// Only show tasks whose current poll has blocked their thread past the stall threshold
+        #[arg(long)]
+        stalled: bool,
+        /// RPC front address (defaults to `[rpc_front] listen`)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
     /// Show sync status
     Sync {
+        /// Print the last 24h of recorded sync samples instead (offline; `json` for dashboards)
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            num_args = 0..=1,
+            default_missing_value = "text",
+            value_parser = ["text", "json"]
+        )]
+        history: Option<String>,
         /// RPC server address (overrides config)
         #[arg(long)]
         rpc_addr: Option<SocketAddr>,
     },
+    /// Show read-only replica status (offline: lag and catch-up from `replica.json`)
+    Replica {
+        /// Print the status as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Configuration management
     Config {
         #[command(subcommand)]
@@ -116,15 +314,118 @@ enum Command {
     },
     /// Direct RPC call
     Rpc {
+        /// RPC method name, then its parameters as a JSON array (default `[]`; `-` reads it
+        /// from stdin). With `-named`: key=value parameters (`-` reads them from stdin, one per
+        /// line). With `--batch`: METHOD PARAMS pairs, or one JSON array of request objects
+        #[arg(required = true, value_name = "METHOD [PARAMS]")]
+        args: Vec<String>,
+        /// Pass parameters by name as key=value (also accepted as `-named`, as in bitcoin-cli)
+        #[arg(long, conflicts_with = "batch")]
+        named: bool,
+        /// Print the whole JSON-RPC response (result, error and id) instead of the result
+        #[arg(long, conflicts_with = "batch")]
+        raw: bool,
+        /// Send all calls as one JSON-RPC batch and print their results in order
+        #[arg(long)]
+        batch: bool,
+        /// RPC server address (overrides config)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Issue the same RPC call to two endpoints and diff the JSON results
+    CompareRpc {
         /// RPC method name
         method: String,
         /// RPC parameters (JSON array)
         #[arg(default_value = "[]")]
         params: String,
+        /// Left endpoint (default: this node's RPC address and credentials)
+        #[arg(long)]
+        left: Option<SocketAddr>,
+        /// Right endpoint (e.g. Bitcoin Core)
+        #[arg(long)]
+        right: SocketAddr,
+        /// Left endpoint credentials as user:password
+        #[arg(long, value_name = "USER:PASSWORD")]
+        left_auth: Option<String>,
+        /// Right endpoint credentials as user:password
+        #[arg(long, value_name = "USER:PASSWORD")]
+        right_auth: Option<String>,
+        /// Path to leave out of the comparison (repeatable; `*` = any key, `[*]` = any index)
+        #[arg(long = "ignore", value_name = "PATH")]
+        ignore: Vec<String>,
+    },
+    /// Decode serialized blocks, headers and scripts (offline)
+    Decode {
+        #[command(subcommand)]
+        subcommand: DecodeCommand,
+    },
+    /// Transaction tools
+    Tx {
+        #[command(subcommand)]
+        subcommand: TxCommand,
         /// RPC server address (overrides config)
         #[arg(long)]
         rpc_addr: Option<SocketAddr>,
     },
+    /// Micro-benchmarks on this machine (offline)
+    Bench {
+        #[command(subcommand)]
+        subcommand: BenchCommand,
+    },
+    /// Show a block with its transactions and per-transaction fees (getblock verbosity 3)
+    Block {
+        /// Block hash or height (default: chain tip)
+        block: Option<String>,
+        /// Transactions to list (0 = all)
+        #[arg(long, default_value_t = 20)]
+        txs: usize,
+        /// Print the getblock result as JSON
+        #[arg(long)]
+        json: bool,
+        /// RPC server address (overrides config)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Re-run context-free validation on a block with a step-by-step report
+    AnalyzeBlock {
+        /// Block hex, or a file with hex or raw bytes (e.g. <data-dir>/quarantine/<hash>.block)
+        input: String,
+        /// Block height for height-dependent checks (default: from the quarantine record)
+        #[arg(long)]
+        height: Option<u32>,
+        /// Also ask the running node for full validation (getblocktemplate proposal mode)
+        #[arg(long)]
+        node: bool,
+        /// RPC server address (overrides config)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Transaction inclusion proofs (gettxoutproof format)
+    Proof {
+        #[command(subcommand)]
+        subcommand: ProofCommand,
+        /// RPC server address (overrides config)
+        #[arg(long, global = true)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Wallet tools that need no node wallet (silent payments)
+    #[cfg(feature = "silent-payments")]
+    Wallet {
+        #[command(subcommand)]
+        subcommand: WalletCommand,
+        /// RPC server address (overrides config)
+        #[arg(long, global = true)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Mining: template and network summary, payout address rotation
+    Mining {
+        #[command(subcommand)]
+        subcommand: MiningCommand,
+        /// RPC server address (overrides config)
+        #[arg(long, global = true)]
+        rpc_addr: Option<SocketAddr>,
+    },
     /// Module lifecycle (load, unload, reload, list)
     Module {
         #[command(subcommand)]
@@ -133,6 +434,38 @@ enum Command {
         #[arg(long)]
         rpc_addr: Option<SocketAddr>,
     },
+    /// Chain analytics (output script types)
+    Stats {
+        #[command(subcommand)]
+        subcommand: StatsCommand,
+        /// RPC server address (overrides config)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Mempool views
+    Mempool {
+        #[command(subcommand)]
+        subcommand: MempoolCommand,
+        /// RPC server address (overrides config)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// OP_RETURN payload tools
+    Opreturn {
+        #[command(subcommand)]
+        subcommand: OpreturnCommand,
+        /// RPC server address (overrides config)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Chainstate database statistics and maintenance
+    Db {
+        #[command(subcommand)]
+        subcommand: DbCommand,
+        /// RPC server address (overrides config)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
     /// Migration and data conversion tools
     #[cfg(feature = "rocksdb")]
     Migrate {
@@ -196,6 +529,351 @@ enum MigrateCommand {
     },
 }
 
+#[derive(Subcommand)]
+enum ChainCommand {
+    /// Genesis block of the selected network (offline)
+    Genesis,
+    /// Consensus and network parameters of the selected network (offline)
+    Params,
+    /// Expected next difficulty adjustment (from the tip via RPC, or offline from explicit values)
+    NextDifficulty {
+        /// Offline: compact target (nBits, hex) of the period's last block
+        #[arg(long, requires_all = ["first_time", "last_time"])]
+        bits: Option<String>,
+        /// Offline: timestamp of the period's first block
+        #[arg(long, requires = "bits")]
+        first_time: Option<i64>,
+        /// Offline: timestamp of the period's last block
+        #[arg(long, requires = "bits")]
+        last_time: Option<i64>,
+    },
+    /// Prefer a block over equal-work alternatives (preciousblock), e.g. to settle a tie-break fork
+    Precious {
+        /// Block hash
+        hash: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DecodeCommand {
+    /// Block as getblock verbosity 2 would show it
+    Block {
+        /// Block hex, or a file containing hex or raw bytes (e.g. from getblock <hash> 0)
+        input: String,
+    },
+    /// 80-byte block header as getblockheader would show it
+    Header {
+        /// Header hex
+        hex: String,
+    },
+    /// Script as decodescript would show it
+    Script {
+        /// Script hex
+        hex: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TxCommand {
+    /// Weight, vsize, sigops, dust, RBF, fee and relay-policy warnings before broadcasting
+    Analyze {
+        /// Transaction hex, or a file containing hex or raw bytes
+        tx: String,
+        /// Spent output per input, in order, as <satoshis>:<scriptPubKey hex> (default: looked
+        /// up on the node)
+        #[arg(long = "prevout")]
+        prevouts: Vec<blvm::tx_analysis::Prevout>,
+        /// Do not ask the node for the spent outputs
+        #[arg(long)]
+        offline: bool,
+        /// Print JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProofCommand {
+    /// Build a proof that transactions are in a block (hex on stdout)
+    Create {
+        /// Transaction ids, all in the same block
+        #[arg(required = true)]
+        txids: Vec<String>,
+        /// Block containing the transactions (required unless txindex is enabled)
+        #[arg(long)]
+        blockhash: Option<String>,
+    },
+    /// Check a proof and print the transaction ids it commits to
+    Verify {
+        /// Proof hex, or a file containing it
+        proof: String,
+        /// Only check the merkle root, not that the block is in the best chain
+        #[arg(long)]
+        offline: bool,
+    },
+}
+
+#[cfg(feature = "silent-payments")]
+#[derive(Subcommand)]
+enum WalletCommand {
+    /// Silent payments (BIP352): static address and block scanning
+    Sp {
+        #[command(subcommand)]
+        subcommand: SilentPaymentCommand,
+    },
+}
+
+/// Scan secret key comes from `BLVM_SP_SCAN_KEY` so it stays out of shell history
+#[cfg(feature = "silent-payments")]
+#[derive(Subcommand)]
+enum SilentPaymentCommand {
+    /// Print the static silent payment address (sp1... / tsp1...)
+    Address {
+        /// Spend public key (33-byte compressed, hex)
+        #[arg(long)]
+        spend_pubkey: String,
+    },
+    /// Scan blocks for payments to the address (needs getblock verbosity 3 prevouts)
+    Scan {
+        /// Spend public key (33-byte compressed, hex)
+        #[arg(long)]
+        spend_pubkey: String,
+        /// First block height to scan (the address's birthday)
+        #[arg(long)]
+        from: u64,
+        /// Last block height to scan (default: tip)
+        #[arg(long)]
+        to: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MiningCommand {
+    /// Next-block template, recent block times, network hashrate and Stratum V2 state
+    /// (getmininginfo)
+    Status {
+        /// Recent blocks used for block times and the hashrate estimate
+        #[arg(long, default_value_t = 12)]
+        blocks: u64,
+    },
+    /// Per-client share latency, stale rate and blocks found, with anomalies flagged
+    /// (from the template module)
+    Clients {
+        /// Only list clients with anomalies
+        #[arg(long)]
+        anomalies: bool,
+    },
+    /// Show the `[mining]` settings and the payout derivation index in use
+    Config,
+}
+
+#[derive(Subcommand)]
+enum EventsCommand {
+    /// Print the most recent events from <data-dir>/events.jsonl
+    Tail {
+        /// Number of events to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+        /// Keep printing new events as they are written
+        #[arg(short, long)]
+        follow: bool,
+        /// Only events whose kind starts with this (e.g. chain, peer.ban)
+        #[arg(long)]
+        kind: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FleetCommand {
+    /// Send a command to every node in `[fleet] nodes` (or those named with --node):
+    /// ping | log-level <filter> | pause-relay | resume-relay
+    Exec {
+        /// Command and arguments
+        #[arg(required = true, num_args = 1..)]
+        command: Vec<String>,
+        /// Only these nodes (repeatable)
+        #[arg(long = "node", value_name = "NAME")]
+        nodes: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PeersCommand {
+    /// Recent peer events: connects, disconnects and bans seen by the event watcher, plus
+    /// blvm's own evictions, filters and timeouts with reasons. Read from the running node's
+    /// in-memory log (getpeereventlog on the `[rpc_front]` listener), or from
+    /// <data-dir>/events.jsonl when there is no front to ask (works offline)
+    History {
+        /// Maximum number of events to show (newest last)
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// Only show events of this kind
+        #[arg(long, value_parser = ["connect", "disconnect", "ban", "evicted", "filtered", "timeout"])]
+        kind: Option<String>,
+        /// RPC front address (defaults to `[rpc_front] listen`)
+        #[arg(long)]
+        rpc_addr: Option<SocketAddr>,
+    },
+    /// Peers filtered by `[peer_policy]` (version and user-agent rules), per reason (works
+    /// offline)
+    Policy,
+    /// Manage `[[persistent_peer]]` entries in the config file (applied on the next start)
+    Persist {
+        #[command(subcommand)]
+        subcommand: PersistCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum BenchCommand {
+    /// SHA-256 throughput on blocks, headers, sighash preimages and merkle nodes, through the
+    /// `sha2` backend this CPU uses
+    Hash {
+        #[arg(long, default_value_t = 20)]
+        iterations: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum SeedsCommand {
+    /// Write reliable known peers as a seed file (one `ip:port` per line)
+    Export {
+        /// Minimum connection success rate, in percent
+        #[arg(long, default_value_t = 50.0)]
+        min_uptime: f64,
+        /// Skip addresses not seen for this many days
+        #[arg(long, default_value_t = 7)]
+        max_age_days: u64,
+        /// At most this many seeds per /16 (IPv4) or /32 (IPv6)
+        #[arg(long, default_value_t = 2)]
+        per_netgroup: usize,
+        /// Maximum number of seeds
+        #[arg(long, default_value_t = 512)]
+        limit: usize,
+        /// Output file (default: stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PersistCommand {
+    /// Add a persistent peer
+    Add {
+        /// `ip:port` (an iroh node id with --transport iroh)
+        address: String,
+        /// Name shown in `blvm peers`
+        #[arg(long)]
+        label: Option<String>,
+        #[arg(long, default_value = "tcp", value_parser = ["tcp", "iroh", "quinn"])]
+        transport: String,
+        /// Reconnect attempts in a row before giving up (0 = keep trying)
+        #[arg(long, default_value_t = 0)]
+        max_retries: u32,
+        /// Seconds between reconnect attempts
+        #[arg(long, default_value_t = 60)]
+        retry_interval: u64,
+        /// Exchange blocks only, no transaction relay
+        #[arg(long)]
+        no_relay: bool,
+    },
+    /// Remove a persistent peer
+    Remove { address: String },
+    /// List persistent peers from the config file
+    List,
+}
+
+#[derive(Subcommand)]
+enum DbCommand {
+    /// Show on-disk sizes per data directory entry and UTXO set statistics (sizes work offline)
+    Stats,
+    /// Rewind the tip to a height by `invalidateblock` on the block above it (node must be running
+    /// with networking off). Not an offline rollback: the blocks stay invalid until reconsidered
+    Invalidate {
+        /// New tip height
+        #[arg(long)]
+        height: u64,
+        /// Skip the interactive confirmation prompt
+        #[arg(long)]
+        yes: bool,
+        /// Allow disconnecting more than 288 blocks
+        #[arg(long)]
+        allow_deep: bool,
+    },
+    /// Check recent blocks against the chainstate (`verifychain`)
+    Verify {
+        /// Check the running node over RPC (required; offline checks need exclusive storage access)
+        #[arg(long)]
+        online: bool,
+        /// 0 = readable, 1 = hashes/merkle/PoW, 2 = header index and BIP34, 3 = chainstate at tip (the highest level)
+        #[arg(long, default_value_t = blvm::verifychain::DEFAULT_CHECKLEVEL)]
+        checklevel: u32,
+        /// Blocks to check back from the tip (0 = all)
+        #[arg(long, default_value_t = blvm::verifychain::DEFAULT_NBLOCKS)]
+        nblocks: u64,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum StatsCommand {
+    /// Outputs by script type: collector totals (offline), or a height range scanned over RPC
+    Scripts {
+        /// First height to scan (scans over RPC instead of reading the collector's totals)
+        #[arg(long)]
+        from: Option<u64>,
+        /// Last height to scan (default: tip)
+        #[arg(long, requires = "from")]
+        to: Option<u64>,
+        /// Print JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Archived block fee statistics and mempool snapshots ([fee_history], offline)
+    Fees {
+        /// First height (default: the oldest archived block)
+        #[arg(long)]
+        from: Option<u64>,
+        /// Last height (default: the newest archived block)
+        #[arg(long)]
+        to: Option<u64>,
+        /// Print the getfeehistory result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum MempoolCommand {
+    /// Fee-rate buckets with count and vsize, drawn as bars
+    Histogram {
+        /// Print the getmempoolhistogram result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum OpreturnCommand {
+    /// Stream OP_RETURN payloads in a height range as JSON lines
+    Scan {
+        /// First height
+        #[arg(long)]
+        from: u64,
+        /// Last height (default: tip)
+        #[arg(long)]
+        to: Option<u64>,
+        /// Keep payloads starting with these bytes (hex; repeatable)
+        #[arg(long)]
+        prefix: Vec<String>,
+        /// Keep a known protocol: runes, omni, stacks (repeatable)
+        #[arg(long)]
+        protocol: Vec<String>,
+    },
+}
+
 #[derive(Subcommand)]
 enum ModuleCommand {
     /// Load a module at runtime (hot load)
@@ -215,6 +893,44 @@ enum ModuleCommand {
     },
     /// List loaded modules
     List,
+    /// Generate a new module's manifest; the code is written against blvm-sdk (works offline)
+    Scaffold {
+        /// Module name (lowercase letters, digits, '-')
+        name: String,
+        /// Output directory (default: ./<name>)
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Lint a module manifest against this build's features (works offline)
+    Check {
+        /// Path to module.toml or the module directory
+        path: PathBuf,
+    },
+    /// Show whether modules are loaded and their data directory usage against the quota
+    Status {
+        /// Only show this module
+        name: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OutputFormat {
+    /// Pretty-printed JSON
+    Json,
+    /// Aligned columns with a header row
+    Table,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+impl From<OutputFormat> for blvm::output::Format {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Json => blvm::output::Format::Json,
+            OutputFormat::Table => blvm::output::Format::Table,
+            OutputFormat::Csv => blvm::output::Format::Csv,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -343,9 +1059,104 @@ impl From<Network> for ProtocolVersion {
     }
 }
 
+/// `rpc_user` / `rpc_password` (config file or `BLVM_RPC_USER` / `BLVM_RPC_PASSWORD`) for CLI
+/// RPC calls; set once the config is resolved
+static RPC_CREDENTIALS: std::sync::OnceLock<(String, String)> = std::sync::OnceLock::new();
+
+/// Cookie file for RPC calls without configured credentials, and whether it was given
+/// explicitly (`--rpc-cookie-file`); set once the data directory is resolved
+static RPC_COOKIE_FILE: std::sync::OnceLock<(PathBuf, bool)> = std::sync::OnceLock::new();
+
+/// `--rpc-tls` options for CLI RPC calls: CA certificate and whether validation is skipped.
+/// Unset = plain HTTP.
+static RPC_TLS: std::sync::OnceLock<(Option<PathBuf>, bool)> = std::sync::OnceLock::new();
+
+/// `--rpc-timeout`, `--rpc-retries` and `--rpc-wait`, applied by [`rpc_post`] to every call
+#[derive(Debug, Clone, Copy, Default)]
+struct RpcRetry {
+    timeout: Option<Duration>,
+    retries: u32,
+    wait: bool,
+}
+
+static RPC_RETRY: std::sync::OnceLock<RpcRetry> = std::sync::OnceLock::new();
+
+/// Replaces the active log filter at runtime (unset in `debug-runtime` builds)
+static LOG_FILTER_RELOAD: std::sync::OnceLock<Box<dyn Fn(&str) -> Result<()> + Send + Sync>> =
+    std::sync::OnceLock::new();
+
+/// bitcoin-cli spells it `-named`: rewrite that to `--named` among the `rpc` subcommand's
+/// options (before the method), and nowhere else. Global options and their values are skipped
+/// to find the subcommand, so `--data-dir rpc` is not taken for it.
+fn rpc_named_arg(mut args: Vec<std::ffi::OsString>) -> Vec<std::ffi::OsString> {
+    let cli = Cli::command();
+    let Some(rpc) = cli.find_subcommand("rpc") else {
+        return args;
+    };
+    let mut i = 1;
+    loop {
+        let Some(arg) = args.get(i).and_then(|arg| arg.to_str()) else {
+            return args;
+        };
+        if arg == "rpc" {
+            break;
+        }
+        if arg == "--" || !arg.starts_with('-') || arg == "-" {
+            return args;
+        }
+        if takes_next_word(&cli, arg) {
+            i += 1;
+        }
+        i += 1;
+    }
+    i += 1;
+    while let Some(arg) = args.get(i).and_then(|arg| arg.to_str()) {
+        match arg {
+            "-named" => args[i] = "--named".into(),
+            "--" => break,
+            _ if arg.starts_with('-') && arg != "-" => {
+                if takes_next_word(rpc, arg) || takes_next_word(&cli, arg) {
+                    i += 1;
+                }
+            }
+            _ => break,
+        }
+        i += 1;
+    }
+    args
+}
+
+/// Whether `arg` (`--name` or `-x`) is an option of `command` that takes the next word as its
+/// value
+fn takes_next_word(command: &clap::Command, arg: &str) -> bool {
+    let option = match arg.strip_prefix("--") {
+        Some(long) => command
+            .get_arguments()
+            .find(|option| option.get_long() == Some(long)),
+        None => {
+            let mut shorts = arg[1..].chars();
+            match (shorts.next(), shorts.next()) {
+                (Some(short), None) => command
+                    .get_arguments()
+                    .find(|option| option.get_short() == Some(short)),
+                _ => None,
+            }
+        }
+    };
+    option.is_some_and(|option| option.get_action().takes_values())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let cli = Cli::parse_from(rpc_named_arg(env::args_os().collect()));
+    if cli.rpc_tls {
+        let _ = RPC_TLS.set((cli.rpc_ca_cert.clone(), cli.rpc_insecure));
+    }
+    let _ = RPC_RETRY.set(RpcRetry {
+        timeout: cli.rpc_timeout.map(Duration::from_secs),
+        retries: cli.rpc_retries,
+        wait: cli.rpc_wait,
+    });
 
     // Initialize tracing: RUST_LOG > BLVM_LOG_LEVEL > default (verbose ? debug : info)
     let default_filter = if cli.verbose {
@@ -368,40 +1179,219 @@ async fn main() -> Result<()> {
         }
     };
 
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    // `log_level` from the config applies (and reloads) only when the environment sets none
+    let config_log_filter = (env::var_os("RUST_LOG").is_none()
+        && env::var_os("BLVM_LOG_LEVEL").is_none())
+    .then(|| filter.to_string());
+
+    // debug-runtime: tokio-console layer alongside the filtered fmt layer
+    #[cfg(feature = "debug-runtime")]
+    {
+        use tracing_subscriber::prelude::*;
+        tracing_subscriber::registry()
+            .with(console_subscriber::spawn())
+            .with(tracing_subscriber::fmt::layer().with_filter(filter))
+            .init();
+    }
+    #[cfg(not(feature = "debug-runtime"))]
+    {
+        let builder = tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_filter_reloading();
+        let handle = builder.reload_handle();
+        builder.init();
+        let _ = LOG_FILTER_RELOAD.set(Box::new(move |directives: &str| {
+            let filter = tracing_subscriber::EnvFilter::try_new(directives)?;
+            handle.reload(filter)?;
+            Ok(())
+        }));
+    }
+
+    #[cfg(feature = "failpoints")]
+    match blvm::failpoints::init_from_env() {
+        Ok(0) => {}
+        Ok(n) => warn!("{} failpoint(s) armed from BLVM_FAILPOINTS", n),
+        Err(e) => anyhow::bail!("Invalid BLVM_FAILPOINTS: {}", e),
+    }
 
     // Handle subcommands
     match cli.command {
         Some(Command::Status { rpc_addr }) => {
-            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let (config, data_dir, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
-            handle_status(rpc_addr, &config).await
+            let front = load_extra_config(&cli.config)?.rpc_front.listen;
+            let format = cli.format.map(Into::into);
+            handle_status(rpc_addr, front, &config, &data_dir, format).await
         }
-        Some(Command::Health { rpc_addr }) => {
+        Some(Command::Health { rpc_addr, ready }) => {
             let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
-            handle_health(rpc_addr, &config).await
+            let front = load_extra_config(&cli.config)?.rpc_front.listen;
+            handle_health(rpc_addr, front, &config, ready).await
         }
-        Some(Command::Version) => handle_version(),
-        Some(Command::Chain { rpc_addr }) => {
+        Some(Command::WaitSync {
+            height,
+            timeout,
+            rpc_addr,
+        }) => {
             let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
-            handle_chain(rpc_addr, &config).await
+            handle_wait_sync(rpc_addr, &config, height, timeout).await
         }
-        Some(Command::Peers { rpc_addr }) => {
+        Some(Command::Stop { timeout, rpc_addr }) => {
             let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
-            handle_peers(rpc_addr, &config).await
+            handle_stop(rpc_addr, &config, timeout).await
         }
-        Some(Command::Network { rpc_addr }) => {
-            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+        Some(Command::Version { json }) => {
+            handle_version(json || matches!(cli.format, Some(OutputFormat::Json)))
+        }
+        Some(Command::Chain {
+            ref subcommand,
+            rpc_addr,
+        }) => {
+            let (config, _, _, resolved_rpc, network) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
-            handle_network(rpc_addr, &config).await
+            match subcommand {
+                None => handle_chain(rpc_addr, &config, cli.format.map(Into::into)).await,
+                Some(ChainCommand::Genesis) => handle_chain_genesis(&network),
+                Some(ChainCommand::Params) => handle_chain_params(&network),
+                Some(ChainCommand::NextDifficulty {
+                    bits,
+                    first_time,
+                    last_time,
+                }) => match (bits, first_time, last_time) {
+                    (Some(bits), Some(first), Some(last)) => {
+                        handle_next_difficulty_offline(&network, bits, *first, *last)
+                    }
+                    _ => handle_next_difficulty(rpc_addr, &config, &network).await,
+                },
+                Some(ChainCommand::Precious { hash }) => {
+                    handle_chain_precious(rpc_addr, &config, hash).await
+                }
+            }
         }
-        Some(Command::Sync { rpc_addr }) => {
+        Some(Command::Peers {
+            ref subcommand,
+            rpc_addr,
+        }) => match subcommand {
+            // Only edits the config file; needs no resolved node settings
+            Some(PeersCommand::Persist { subcommand }) => {
+                handle_peers_persist(&cli.config, subcommand)
+            }
+            None => {
+                let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+                let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+                let persistent = load_extra_config(&cli.config)?.persistent_peers;
+                handle_peers(rpc_addr, &config, &persistent, cli.format.map(Into::into)).await
+            }
+            Some(PeersCommand::History {
+                limit,
+                kind,
+                rpc_addr,
+            }) => {
+                let (config, data_dir, _, _, _) = build_final_config(&cli)?;
+                let front = rpc_addr.or(load_extra_config(&cli.config)?.rpc_front.listen);
+                handle_peers_history(
+                    front,
+                    &config,
+                    Path::new(&data_dir),
+                    *limit,
+                    kind.as_deref(),
+                )
+                .await
+            }
+            Some(PeersCommand::Policy) => {
+                let (_, data_dir, _, _, _) = build_final_config(&cli)?;
+                handle_peers_policy(&data_dir, &load_extra_config(&cli.config)?.peer_policy)
+            }
+        },
+        Some(Command::Seeds {
+            ref subcommand,
+            rpc_addr,
+        }) => {
+            let (config, _, _, resolved_rpc, network) = build_final_config(&cli)?;
+            let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+            match subcommand {
+                SeedsCommand::Export {
+                    min_uptime,
+                    max_age_days,
+                    per_netgroup,
+                    limit,
+                    out,
+                } => {
+                    let filter = blvm::seeds::Filter {
+                        min_uptime: min_uptime / 100.0,
+                        max_age_secs: max_age_days * 86_400,
+                        per_netgroup: *per_netgroup,
+                        limit: *limit,
+                    };
+                    handle_seeds_export(
+                        rpc_addr,
+                        &config,
+                        network_from_cli_enum(&network),
+                        &filter,
+                        out.as_deref(),
+                    )
+                    .await
+                }
+            }
+        }
+        Some(Command::Network { rpc_addr }) => {
             let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
-            handle_sync(rpc_addr, &config).await
+            handle_network(rpc_addr, &config, cli.format.map(Into::into)).await
+        }
+        Some(Command::Events {
+            subcommand:
+                EventsCommand::Tail {
+                    lines,
+                    follow,
+                    ref kind,
+                },
+        }) => {
+            let (_, data_dir, _, _, _) = build_final_config(&cli)?;
+            handle_events_tail(Path::new(&data_dir), lines, follow, kind.as_deref()).await
+        }
+        Some(Command::Fleet {
+            subcommand:
+                FleetCommand::Exec {
+                    ref command,
+                    ref nodes,
+                },
+        }) => handle_fleet_exec(&load_extra_config(&cli.config)?.fleet, command, nodes).await,
+        Some(Command::RpcStats { ref sort, rpc_addr }) => {
+            let (config, _, _, _, _) = build_final_config(&cli)?;
+            let rpc_addr = match rpc_addr {
+                Some(rpc_addr) => rpc_addr,
+                None => front_listen(&cli, "getrpcstats")?,
+            };
+            handle_rpc_stats(rpc_addr, &config, sort).await
+        }
+        Some(Command::Tasks { stalled, rpc_addr }) => {
+            let (config, _, _, _, _) = build_final_config(&cli)?;
+            let rpc_addr = match rpc_addr {
+                Some(rpc_addr) => rpc_addr,
+                None => front_listen(&cli, "dumptasks")?,
+            };
+            handle_tasks(rpc_addr, &config, stalled).await
+        }
+        Some(Command::Sync {
+            ref history,
+            rpc_addr,
+        }) => {
+            let (config, data_dir, _, resolved_rpc, _) = build_final_config(&cli)?;
+            match history.as_deref() {
+                Some(format) => handle_sync_history(Path::new(&data_dir), format == "json"),
+                None => {
+                    let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+                    handle_sync(rpc_addr, &config, Path::new(&data_dir)).await
+                }
+            }
+        }
+        Some(Command::Replica { json }) => {
+            let (_, data_dir, _, _, _) = build_final_config(&cli)?;
+            handle_replica_status(Path::new(&data_dir), json)
         }
         Some(Command::Config { ref subcommand }) => {
             let (config, _, _, _, _) = build_final_config(&cli)?;
@@ -422,6 +1412,97 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        #[cfg(feature = "silent-payments")]
+        Some(Command::Wallet {
+            ref subcommand,
+            rpc_addr,
+        }) => {
+            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+            handle_wallet(rpc_addr, &config, subcommand).await
+        }
+        Some(Command::Mining {
+            ref subcommand,
+            rpc_addr,
+        }) => {
+            let (config, data_dir, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+            let mining = load_extra_config(&cli.config)?.mining;
+            match subcommand {
+                MiningCommand::Status { blocks } => {
+                    handle_mining_status(rpc_addr, &config, &mining, *blocks).await
+                }
+                MiningCommand::Clients { anomalies } => {
+                    handle_mining_clients(rpc_addr, &config, &mining, *anomalies).await
+                }
+                MiningCommand::Config => handle_mining_config(&mining, Path::new(&data_dir)),
+            }
+        }
+        Some(Command::Stats {
+            subcommand: StatsCommand::Scripts { from, to, json },
+            rpc_addr,
+        }) => {
+            let (config, data_dir, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+            handle_stats_scripts(rpc_addr, &config, Path::new(&data_dir), from, to, json).await
+        }
+        Some(Command::Stats {
+            subcommand: StatsCommand::Fees { from, to, json },
+            ..
+        }) => {
+            let (_, data_dir, _, _, _) = build_final_config(&cli)?;
+            handle_stats_fees(Path::new(&data_dir), from, to, json)
+        }
+        Some(Command::Mempool {
+            subcommand: MempoolCommand::Histogram { json },
+            rpc_addr,
+        }) => {
+            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+            handle_mempool_histogram(rpc_addr, &config, json).await
+        }
+        Some(Command::Opreturn {
+            subcommand:
+                OpreturnCommand::Scan {
+                    from,
+                    to,
+                    ref prefix,
+                    ref protocol,
+                },
+            rpc_addr,
+        }) => {
+            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+            handle_opreturn_scan(rpc_addr, &config, from, to, prefix, protocol).await
+        }
+        Some(Command::Db {
+            ref subcommand,
+            rpc_addr,
+        }) => {
+            let (config, data_dir, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+            match subcommand {
+                DbCommand::Stats => handle_db_stats(rpc_addr, &config, &data_dir).await,
+                DbCommand::Invalidate {
+                    height,
+                    yes,
+                    allow_deep,
+                } => handle_db_invalidate(rpc_addr, &config, *height, *yes, *allow_deep).await,
+                DbCommand::Verify {
+                    online,
+                    checklevel,
+                    nblocks,
+                    json,
+                } => {
+                    if !online {
+                        anyhow::bail!(
+                            "Offline verification is not supported; pass --online to check the running node"
+                        );
+                    }
+                    handle_db_verify(rpc_addr, &config, *checklevel, *nblocks, *json).await
+                }
+            }
+        }
         #[cfg(feature = "rocksdb")]
         Some(Command::Migrate { ref subcommand }) => match subcommand {
             MigrateCommand::Core {
@@ -447,22 +1528,167 @@ async fn main() -> Result<()> {
             }
         },
         Some(Command::Rpc {
+            ref args,
+            named,
+            raw,
+            batch,
+            rpc_addr,
+        }) => {
+            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+            if batch {
+                let calls = blvm::rpc_batch::parse_args(args).map_err(|e| anyhow::anyhow!(e))?;
+                return handle_rpc_batch(rpc_addr.unwrap_or(resolved_rpc), &calls, &config).await;
+            }
+            let (method, rest) = args.split_first().context("Missing RPC method")?;
+            // blvm-node has none of the front's own methods, so they go to the front listener
+            let rpc_addr = match rpc_addr {
+                Some(rpc_addr) => rpc_addr,
+                None if blvm::rpc_front::FRONT_METHODS.contains(&method.as_str()) => {
+                    front_listen(&cli, method)?
+                }
+                None => resolved_rpc,
+            };
+            let read_stdin = || -> Result<String> {
+                use std::io::Read;
+                let mut input = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut input)
+                    .context("Failed to read parameters from stdin")?;
+                Ok(input)
+            };
+            let params: Value = if named {
+                let mut words: Vec<String> = Vec::new();
+                for word in rest {
+                    if word == "-" {
+                        let input = read_stdin()?;
+                        words.extend(
+                            blvm::rpc_args::stdin_words(&input)
+                                .into_iter()
+                                .map(String::from),
+                        );
+                    } else {
+                        words.push(word.clone());
+                    }
+                }
+                blvm::rpc_args::named_params(&words).map_err(|e| anyhow::anyhow!(e))?
+            } else {
+                let params = match rest {
+                    [] => "[]".to_string(),
+                    [params] if params == "-" => read_stdin()?,
+                    [params] => params.clone(),
+                    _ => anyhow::bail!(
+                        "Expected METHOD [PARAMS]; use -named for key=value parameters or \
+                         --batch for several calls"
+                    ),
+                };
+                serde_json::from_str(&params).context("Invalid JSON parameters")?
+            };
+            handle_rpc(rpc_addr, method, params, raw, &config).await
+        }
+        Some(Command::CompareRpc {
             ref method,
             ref params,
+            left,
+            right,
+            ref left_auth,
+            ref right_auth,
+            ref ignore,
+        }) => {
+            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let params: Value = serde_json::from_str(params).context("Invalid JSON parameters")?;
+            let left_result = match (left, left_auth) {
+                (None, None) => {
+                    rpc_call_with_config(resolved_rpc, &config, method, params.clone()).await
+                }
+                (left, auth) => {
+                    rpc_call_with_user_password(
+                        left.unwrap_or(resolved_rpc),
+                        method,
+                        params.clone(),
+                        auth.as_deref(),
+                    )
+                    .await
+                }
+            };
+            let right_result =
+                rpc_call_with_user_password(right, method, params, right_auth.as_deref()).await;
+            handle_compare_rpc(left_result, right_result, ignore)
+        }
+        Some(Command::Decode { ref subcommand }) => handle_decode(subcommand),
+        Some(Command::Tx {
+            subcommand:
+                TxCommand::Analyze {
+                    ref tx,
+                    ref prevouts,
+                    offline,
+                    json,
+                },
+            rpc_addr,
+        }) => {
+            let bytes = read_hex_or_file(tx)?;
+            let tx = blvm::decode::Transaction::decode(&bytes).context("Invalid transaction")?;
+            let prevouts = if !prevouts.is_empty() {
+                if prevouts.len() != tx.inputs.len() {
+                    anyhow::bail!(
+                        "{} --prevout given for {} input(s)",
+                        prevouts.len(),
+                        tx.inputs.len()
+                    );
+                }
+                Ok(prevouts.clone())
+            } else if offline || tx.is_coinbase() {
+                Err("no --prevout given".to_string())
+            } else {
+                let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+                fetch_prevouts(rpc_addr.unwrap_or(resolved_rpc), &config, &tx)
+                    .await
+                    .map_err(|e| format!("{e:#}"))
+            };
+            handle_tx_analyze(&tx, prevouts, json)
+        }
+        Some(Command::Bench { ref subcommand }) => handle_bench(subcommand),
+        Some(Command::Block {
+            ref block,
+            txs,
+            json,
             rpc_addr,
         }) => {
             let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
-            let params: Value = serde_json::from_str(params).context("Invalid JSON parameters")?;
-            handle_rpc(rpc_addr, method, params, &config).await
+            handle_block(rpc_addr, &config, block.as_deref(), txs, json).await
         }
-        Some(Command::Module {
+        Some(Command::AnalyzeBlock {
+            ref input,
+            height,
+            node,
+            rpc_addr,
+        }) => {
+            let (config, _, _, resolved_rpc, network) = build_final_config(&cli)?;
+            let rpc_addr = node.then(|| rpc_addr.unwrap_or(resolved_rpc));
+            handle_analyze_block(input, height, &network, rpc_addr, &config).await
+        }
+        Some(Command::Proof {
             ref subcommand,
             rpc_addr,
         }) => {
             let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
-            handle_module(rpc_addr, subcommand, &config).await
+            match subcommand {
+                ProofCommand::Create { txids, blockhash } => {
+                    handle_proof_create(rpc_addr, &config, txids, blockhash.as_deref()).await
+                }
+                ProofCommand::Verify { proof, offline } => {
+                    handle_proof_verify(rpc_addr, &config, proof, *offline).await
+                }
+            }
+        }
+        Some(Command::Module {
+            ref subcommand,
+            rpc_addr,
+        }) => {
+            let (config, data_dir, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
+            handle_module(rpc_addr, subcommand, &config, &data_dir).await
         }
         Some(Command::ConfigPath { ref module }) => {
             let (config, data_dir, _, _, _) = build_final_config(&cli)?;
@@ -472,7 +1698,7 @@ async fn main() -> Result<()> {
             ref module,
             rpc_addr,
         }) => {
-            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let (config, data_dir, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
             handle_module(
                 rpc_addr,
@@ -480,6 +1706,7 @@ async fn main() -> Result<()> {
                     name: module.clone(),
                 },
                 &config,
+                &data_dir,
             )
             .await
         }
@@ -487,7 +1714,7 @@ async fn main() -> Result<()> {
             ref module,
             rpc_addr,
         }) => {
-            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let (config, data_dir, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
             handle_module(
                 rpc_addr,
@@ -495,6 +1722,7 @@ async fn main() -> Result<()> {
                     name: module.clone(),
                 },
                 &config,
+                &data_dir,
             )
             .await
         }
@@ -502,7 +1730,7 @@ async fn main() -> Result<()> {
             ref module,
             rpc_addr,
         }) => {
-            let (config, _, _, resolved_rpc, _) = build_final_config(&cli)?;
+            let (config, data_dir, _, resolved_rpc, _) = build_final_config(&cli)?;
             let rpc_addr = rpc_addr.unwrap_or(resolved_rpc);
             handle_module(
                 rpc_addr,
@@ -510,6 +1738,7 @@ async fn main() -> Result<()> {
                     name: module.clone(),
                 },
                 &config,
+                &data_dir,
             )
             .await
         }
@@ -517,10 +1746,21 @@ async fn main() -> Result<()> {
             let (config, _, _, rpc_addr, _) = build_final_config(&cli)?;
             handle_module_cli(rpc_addr, args, &config).await
         }
-        None | Some(Command::Start) => {
+        None | Some(Command::Start { .. }) => {
             // Start node (default behavior)
             let (config, data_dir, listen_addr, rpc_addr, network) = build_final_config(&cli)?;
 
+            if matches!(cli.command, Some(Command::Start { dry_run: true })) {
+                return handle_start_dry_run(
+                    &config,
+                    &load_extra_config(&cli.config)?,
+                    &data_dir,
+                    listen_addr,
+                    rpc_addr,
+                    &network,
+                );
+            }
+
             #[cfg(feature = "rocksdb")]
             if cli.migrate_core_only {
                 let mut config = config;
@@ -556,11 +1796,353 @@ async fn main() -> Result<()> {
             info!("RPC address: {}", rpc_addr);
             info!("P2P listen address: {}", listen_addr);
             info!("Data directory: {}", data_dir);
+            blvm::node_state::enter(blvm::node_state::NodePhase::Starting);
+
+            if cli.reset_fee_estimates {
+                match blvm::datadir::reset_fee_estimates(Path::new(&data_dir)) {
+                    Ok(true) => info!(
+                        "Removed {}; fee estimates start cold",
+                        blvm::datadir::FEE_ESTIMATES_FILE
+                    ),
+                    Ok(false) => info!("No saved fee estimates to reset"),
+                    Err(e) => anyhow::bail!(
+                        "Failed to remove {}: {e}",
+                        blvm::datadir::FEE_ESTIMATES_FILE
+                    ),
+                }
+            }
 
             unsafe {
                 std::env::set_var("DATA_DIR", &data_dir);
             }
 
+            if let Some(pprof_addr) = cli.pprof_addr {
+                #[cfg(feature = "pprof")]
+                tokio::spawn(async move {
+                    if let Err(e) = blvm::profiling::serve(pprof_addr).await {
+                        warn!("Profiling server on {} stopped: {}", pprof_addr, e);
+                    }
+                });
+                #[cfg(not(feature = "pprof"))]
+                warn!(
+                    "--pprof-addr {} ignored: build with --features pprof",
+                    pprof_addr
+                );
+            }
+
+            let extra = load_extra_config(&cli.config)?;
+            let (live_tx, live) = tokio::sync::watch::channel(extra.clone());
+            // A `max_outbound_peers` lowered by a reload, enforced by `run_peer_limits`
+            let (outbound_cap_tx, outbound_cap) = tokio::sync::watch::channel(None);
+            if extra.log_level.is_some() {
+                apply_log_level(extra.log_level.as_deref(), config_log_filter.as_deref());
+            }
+            let addr_relay = extra.addr_relay.resolve(config.enable_self_advertisement);
+            info!("Address relay: {}", addr_relay.describe());
+            if extra.revalidation.enabled {
+                info!(
+                    "Background block re-validation: {} blocks/hour",
+                    extra.revalidation.blocks_per_hour
+                );
+            }
+            blvm::tasks::spawn(
+                "revalidation",
+                run_revalidation(
+                    rpc_addr,
+                    config.clone(),
+                    PathBuf::from(&data_dir),
+                    live.clone(),
+                ),
+            );
+            let event_log: Option<SharedEventLog> = extra.events.enabled.then(|| {
+                std::sync::Arc::new(std::sync::Mutex::new(blvm::events::EventLog::new(
+                    Path::new(&data_dir),
+                    extra.events.clone(),
+                )))
+            });
+            record_event(
+                event_log.as_ref(),
+                blvm::events::Event::new("node.start", "Node starting")
+                    .with("network", network_from_cli_enum(&network))
+                    .with("version", env!("CARGO_PKG_VERSION")),
+            );
+            // Runs without [events] too: peer events also feed getpeereventlog
+            blvm::tasks::spawn(
+                "event-watch",
+                run_event_watch(rpc_addr, config.clone(), event_log.clone()),
+            );
+            blvm::tasks::spawn(
+                "sync-sampler",
+                run_sync_sampler(rpc_addr, config.clone(), PathBuf::from(&data_dir)),
+            );
+            if extra.alerts.enabled && !extra.alerts.rules.is_empty() {
+                let mut alerts = extra.alerts.clone();
+                alerts.rules.retain(|rule| match rule.validate() {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!("Ignoring {}", e);
+                        false
+                    }
+                });
+                info!("Alerting: {} rule(s)", alerts.rules.len());
+                blvm::tasks::spawn(
+                    "alerts",
+                    run_alerts(
+                        rpc_addr,
+                        config.clone(),
+                        PathBuf::from(&data_dir),
+                        alerts,
+                        event_log.clone(),
+                    ),
+                );
+            }
+            // Always running: they follow `[anomalies]` / `[chain_split]` across reloads
+            blvm::tasks::spawn(
+                "block-anomalies",
+                run_block_anomalies(
+                    rpc_addr,
+                    config.clone(),
+                    PathBuf::from(&data_dir),
+                    live.clone(),
+                    event_log.clone(),
+                ),
+            );
+            blvm::tasks::spawn(
+                "chain-split",
+                run_chain_split(
+                    rpc_addr,
+                    config.clone(),
+                    PathBuf::from(&data_dir),
+                    live.clone(),
+                    extra.alerts.enabled.then(|| extra.alerts.clone()),
+                    event_log.clone(),
+                ),
+            );
+            if extra.fleet.enabled {
+                match blvm::fleet::Verifier::new(&extra.fleet.authorized_keys) {
+                    Ok(verifier) => {
+                        blvm::tasks::spawn(
+                            "fleet-admin",
+                            run_fleet_admin(
+                                extra.fleet.listen,
+                                verifier,
+                                rpc_addr,
+                                config.clone(),
+                                event_log.clone(),
+                            ),
+                        );
+                    }
+                    Err(e) => warn!("Fleet admin channel disabled: {}", e),
+                }
+            }
+            if let Some(descriptor) = &extra.mining.payout_descriptor {
+                match blvm::mining::validate_payout_descriptor(descriptor) {
+                    Ok(descriptor) => {
+                        blvm::tasks::spawn(
+                            "payout-rotation",
+                            run_payout_rotation(
+                                rpc_addr,
+                                config.clone(),
+                                PathBuf::from(&data_dir),
+                                descriptor,
+                                extra.mining.template_module.clone(),
+                            ),
+                        );
+                    }
+                    Err(e) => warn!("Payout rotation disabled: {}", e),
+                }
+            }
+            if extra.mining.template_refresh.enabled {
+                blvm::tasks::spawn(
+                    "template-refresh",
+                    run_template_refresh(
+                        rpc_addr,
+                        config.clone(),
+                        extra.mining.template_module.clone(),
+                        extra.mining.template_refresh.clone(),
+                    ),
+                );
+            }
+            blvm::tasks::spawn(
+                "peer-limits",
+                run_peer_limits(
+                    rpc_addr,
+                    config.clone(),
+                    live.clone(),
+                    outbound_cap.clone(),
+                    event_log.clone(),
+                ),
+            );
+            blvm::tasks::spawn(
+                "peer-policy",
+                run_peer_policy(
+                    rpc_addr,
+                    config.clone(),
+                    PathBuf::from(&data_dir),
+                    live.clone(),
+                    event_log.clone(),
+                ),
+            );
+            blvm::tasks::spawn(
+                "peer-timeouts",
+                run_peer_timeouts(rpc_addr, config.clone(), live.clone(), event_log.clone()),
+            );
+            if let Some(path) = &extra.seeds_file {
+                match std::fs::read_to_string(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|content| blvm::seeds::parse_seed_file(&content))
+                {
+                    Ok(seeds) if !seeds.is_empty() => {
+                        info!("Loaded {} fixed seeds from {}", seeds.len(), path.display());
+                        blvm::tasks::spawn(
+                            "seed-fallback",
+                            run_seed_fallback(rpc_addr, config.clone(), seeds),
+                        );
+                    }
+                    Ok(_) => warn!("Seed file {} lists no addresses", path.display()),
+                    Err(e) => warn!("Ignoring seed file {}: {}", path.display(), e),
+                }
+            }
+            if extra.discovery.mdns {
+                blvm::tasks::spawn(
+                    "mdns-discovery",
+                    run_mdns_discovery(
+                        rpc_addr,
+                        config.clone(),
+                        network_from_cli_enum(&network),
+                        listen_addr,
+                        extra.listen != Some(false),
+                        extra.discovery.clone(),
+                    ),
+                );
+            }
+            if !extra.persistent_peers.is_empty() {
+                blvm::tasks::spawn(
+                    "persistent-peers",
+                    run_persistent_peers(rpc_addr, config.clone(), extra.persistent_peers.clone()),
+                );
+            }
+            if extra.notifications.enabled {
+                match extra.notifications.path.clone() {
+                    #[cfg(unix)]
+                    Some(path) => {
+                        info!("Chain notifications on {}", path.display());
+                        blvm::tasks::spawn(
+                            "notifications",
+                            run_notifications(
+                                rpc_addr,
+                                config.clone(),
+                                extra.notifications.clone(),
+                                path,
+                                PathBuf::from(&data_dir),
+                            ),
+                        );
+                    }
+                    #[cfg(not(unix))]
+                    Some(_) => warn!("[notifications] needs unix sockets or named pipes; ignored"),
+                    None => warn!("[notifications] enabled without a path; ignored"),
+                }
+            }
+            // Set once a shutdown signal arrives; /readyz reports 503 from then on
+            let shutting_down = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+            // Not-ready reason from background tasks (replica lag)
+            let held: SharedHold = Default::default();
+            if let Some(probe_addr) = extra.probes.addr {
+                blvm::tasks::spawn(
+                    "probes",
+                    run_probes(
+                        probe_addr,
+                        rpc_addr,
+                        config.clone(),
+                        extra.probes.ready_during_ibd,
+                        shutting_down.clone(),
+                        held.clone(),
+                    ),
+                );
+            }
+            blvm::tasks::spawn(
+                "script-stats",
+                run_script_stats(
+                    rpc_addr,
+                    config.clone(),
+                    PathBuf::from(&data_dir),
+                    live.clone(),
+                ),
+            );
+            blvm::tasks::spawn(
+                "fee-history",
+                run_fee_history(
+                    rpc_addr,
+                    config.clone(),
+                    PathBuf::from(&data_dir),
+                    live.clone(),
+                ),
+            );
+            if let Some(listen) = extra.rpc_front.listen {
+                blvm::tasks::spawn(
+                    "rpc-front",
+                    run_rpc_front(
+                        listen,
+                        rpc_addr,
+                        config.clone(),
+                        PathBuf::from(&data_dir),
+                        extra.rpc_front.clone(),
+                    ),
+                );
+            }
+            if config.modules.as_ref().is_some_and(|m| m.enabled) {
+                blvm::tasks::spawn(
+                    "module-quotas",
+                    run_module_quotas(
+                        rpc_addr,
+                        config.clone(),
+                        modules_data_dir(&config, &data_dir),
+                        event_log.clone(),
+                    ),
+                );
+            }
+            if extra.replica.enabled {
+                match extra.replica.primary.clone() {
+                    Some(primary) => {
+                        info!("Replica of {}", primary);
+                        blvm::tasks::spawn(
+                            "replica",
+                            run_replica(
+                                rpc_addr,
+                                config.clone(),
+                                PathBuf::from(&data_dir),
+                                extra.replica.clone(),
+                                primary,
+                                held,
+                            ),
+                        );
+                    }
+                    None => warn!("[replica] enabled without a primary; ignored"),
+                }
+            }
+            let shutdown_timeout = Duration::from_secs(extra.shutdown_timeout_secs.unwrap_or(30));
+            if extra.quarantine.enabled {
+                blvm::tasks::spawn(
+                    "quarantine-watch",
+                    run_quarantine_watch(
+                        rpc_addr,
+                        config.clone(),
+                        PathBuf::from(&data_dir),
+                        network_from_cli_enum(&network),
+                        extra.quarantine,
+                    ),
+                );
+            }
+
+            #[cfg(all(feature = "systemd", unix))]
+            blvm::tasks::spawn(
+                "systemd-notify",
+                run_systemd_notify(rpc_addr, listen_addr, config.clone()),
+            );
+
+            blvm::fail_point!("node.start");
+
+            blvm::node_state::enter(blvm::node_state::NodePhase::LoadingChainstate);
             let protocol_version: ProtocolVersion = network.into();
             let mut node = match ReferenceNode::with_storage_config(
                 &data_dir,
@@ -585,6 +2167,17 @@ async fn main() -> Result<()> {
                 node = node.with_wasm_loader(std::sync::Arc::new(blvm_sdk::BlvmSdkWasmLoader));
             }
 
+            if config.modules.as_ref().is_some_and(|m| m.enabled) {
+                let root = modules_data_dir(&config, &data_dir);
+                for manifest in installed_module_manifests(&config) {
+                    if let Err(e) = std::fs::create_dir_all(&root)
+                        .and_then(|()| blvm::module_storage::provision(&root, &manifest.name))
+                    {
+                        warn!("Module {} data directory: {}", manifest.name, e);
+                    }
+                }
+            }
+
             // with_modules_from_config takes ownership, so we need to handle it carefully
             node = match node.with_modules_from_config(&config) {
                 Ok(n) => n,
@@ -603,14 +2196,23 @@ async fn main() -> Result<()> {
             // dropping it (dropping would orphan the IBD validation thread and skip the
             // final watermark flush).
             let mut node_fut = std::pin::pin!(node.start());
+            blvm::tasks::spawn("node-phase", run_node_phase(rpc_addr, config.clone()));
             let mut shutdown_rx = blvm_node::utils::create_shutdown_receiver();
             let mut shutdown_initiated = false;
+            let mut hangup = hangup_signal();
+            let startup_config = config.clone();
+            let mut running_config = config.clone();
 
             loop {
                 if shutdown_initiated {
-                    // Signal received: give the node up to 30 s to drain (IBD watermark flush
-                    // when active, otherwise run-loop exit + storage flush).
-                    match tokio::time::timeout(Duration::from_secs(30), &mut node_fut).await {
+                    // Signal received: give the node up to `shutdown_timeout_secs` (30 s) to
+                    // drain (IBD watermark flush when active, otherwise run-loop exit + storage
+                    // flush). Keep it below the pod's terminationGracePeriodSeconds.
+                    shutting_down.store(true, std::sync::atomic::Ordering::Relaxed);
+                    blvm::node_state::enter(blvm::node_state::NodePhase::Stopping);
+                    #[cfg(all(feature = "systemd", unix))]
+                    let _ = blvm::sd_notify::notify("STOPPING=1");
+                    match tokio::time::timeout(shutdown_timeout, &mut node_fut).await {
                         Ok(Ok(())) => {}
                         Ok(Err(e)) => {
                             // IBD_STOP_REQUESTED causes the validation loop to exit before
@@ -628,7 +2230,10 @@ async fn main() -> Result<()> {
                             }
                         }
                         Err(_elapsed) => {
-                            warn!("Graceful shutdown timed out after 30 s — forcing exit");
+                            warn!(
+                                "Graceful shutdown timed out after {} s — forcing exit",
+                                shutdown_timeout.as_secs()
+                            );
                             std::process::exit(0);
                         }
                     }
@@ -655,14 +2260,151 @@ async fn main() -> Result<()> {
                             shutdown_initiated = true;
                         }
                     }
+                    () = next_hangup(&mut hangup) => {
+                        reload_config(
+                            &cli,
+                            &startup_config,
+                            &mut running_config,
+                            &live_tx,
+                            &outbound_cap_tx,
+                            config_log_filter.as_deref(),
+                            event_log.as_ref(),
+                        );
+                    }
                 }
             }
 
+            record_event(
+                event_log.as_ref(),
+                blvm::events::Event::new("node.stop", "Node stopped"),
+            );
             Ok(())
         }
     }
 }
 
+#[cfg(unix)]
+type Hangup = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type Hangup = ();
+
+#[cfg(unix)]
+fn hangup_signal() -> Hangup {
+    use tokio::signal::unix::{SignalKind, signal};
+    signal(SignalKind::hangup())
+        .inspect_err(|e| warn!("SIGHUP handler not installed: {}", e))
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn hangup_signal() -> Hangup {}
+
+/// Resolves on each SIGHUP; never on platforms without it
+async fn next_hangup(hangup: &mut Hangup) {
+    #[cfg(unix)]
+    if let Some(signal) = hangup
+        && signal.recv().await.is_some()
+    {
+        return;
+    }
+    let _ = hangup;
+    std::future::pending::<()>().await
+}
+
+/// SIGHUP: build the config again, apply the sections this binary can change live
+/// ([`blvm::config_reload::RELOADABLE`]) and lowered peer caps
+/// ([`blvm::config_reload::PEER_CAPS`]), and log every other change as needing a restart
+fn reload_config(
+    cli: &Cli,
+    startup: &NodeConfig,
+    running: &mut NodeConfig,
+    live: &tokio::sync::watch::Sender<blvm::extra_config::ExtraConfig>,
+    outbound_cap: &tokio::sync::watch::Sender<Option<usize>>,
+    config_log_filter: Option<&str>,
+    event_log: Option<&SharedEventLog>,
+) {
+    info!("SIGHUP received: reloading configuration");
+    let config = match build_final_config(cli) {
+        Ok((config, ..)) => config,
+        Err(e) => {
+            warn!("Config reload failed, keeping the running config: {:#}", e);
+            return;
+        }
+    };
+    let extra = match load_extra_config(&cli.config) {
+        Ok(extra) => extra,
+        Err(e) => {
+            warn!("Config reload failed, keeping the running config: {:#}", e);
+            return;
+        }
+    };
+    let as_value = |value: Result<Value, serde_json::Error>| value.unwrap_or(Value::Null);
+    let plan = {
+        let current = live.borrow();
+        blvm::config_reload::plan(
+            &as_value(serde_json::to_value(startup)),
+            (
+                &as_value(serde_json::to_value(&*running)),
+                &as_value(serde_json::to_value(&config)),
+            ),
+            (
+                &as_value(serde_json::to_value(&*current)),
+                &as_value(serde_json::to_value(&extra)),
+            ),
+        )
+    };
+    for change in &plan.applied {
+        info!("Config reloaded: {}", change);
+    }
+    for change in &plan.restart {
+        warn!("Config change needs a restart to take effect: {}", change);
+    }
+    if live.borrow().log_level != extra.log_level {
+        apply_log_level(extra.log_level.as_deref(), config_log_filter);
+    }
+    live.send_replace(extra);
+    // At the startup value the node's own limit already holds
+    outbound_cap.send_replace(config.max_outbound_peers.filter(|&cap| {
+        startup
+            .max_outbound_peers
+            .is_some_and(|startup| cap < startup)
+    }));
+    *running = config;
+    record_event(
+        event_log,
+        blvm::events::Event::new(
+            "config.reload",
+            format!(
+                "Config reloaded: {} change(s) applied, {} need a restart",
+                plan.applied.len(),
+                plan.restart.len()
+            ),
+        )
+        .with("applied", plan.applied.len())
+        .with("restart_required", plan.restart.len()),
+    );
+}
+
+/// Set the log filter from `log_level` (`None` restores the startup filter); ignored when
+/// `RUST_LOG` / `BLVM_LOG_LEVEL` chose the filter
+fn apply_log_level(level: Option<&str>, config_log_filter: Option<&str>) {
+    let Some(startup) = config_log_filter else {
+        if level.is_some() {
+            warn!("log_level ignored: RUST_LOG or BLVM_LOG_LEVEL is set");
+        }
+        return;
+    };
+    let Some(reload) = LOG_FILTER_RELOAD.get() else {
+        warn!("log_level ignored: log filter reload is not available in this build");
+        return;
+    };
+    let directives = level.unwrap_or(startup);
+    match reload(directives) {
+        Ok(()) => info!("Log filter set to '{}'", directives),
+        Err(e) => warn!("Invalid log_level '{}': {}", directives, e),
+    }
+}
+
 /// Environment variable overrides
 #[derive(Debug, Clone, Default)]
 struct EnvOverrides {
@@ -672,6 +2414,9 @@ struct EnvOverrides {
     rpc_addr: Option<SocketAddr>,
     max_peers: Option<usize>,
     transport: Option<String>,
+    nolisten: Option<bool>,
+    rpc_user: Option<String>,
+    rpc_password: Option<String>,
     // Feature flags
     stratum_v2: Option<bool>,
     dandelion: Option<bool>,
@@ -713,6 +2458,9 @@ impl EnvOverrides {
                 .ok()
                 .and_then(|s| s.parse().ok()),
             transport: env::var("BLVM_NODE_TRANSPORT").ok(),
+            nolisten: env::var("BLVM_NOLISTEN").ok().and_then(|s| s.parse().ok()),
+            rpc_user: env::var("BLVM_RPC_USER").ok(),
+            rpc_password: env::var("BLVM_RPC_PASSWORD").ok(),
             // Feature flags
             stratum_v2: env::var("BLVM_NODE_FEATURES_STRATUM_V2")
                 .ok()
@@ -811,6 +2559,40 @@ fn find_config_file(cli_config: &Option<PathBuf>) -> Option<PathBuf> {
     None
 }
 
+/// `[rpc_front] listen`, for `method` that only the front answers; an error when it is unset
+fn front_listen(cli: &Cli, method: &str) -> Result<SocketAddr> {
+    load_extra_config(&cli.config)?
+        .rpc_front
+        .listen
+        .with_context(|| {
+            format!(
+                "{method} is answered by the blvm RPC front, not by blvm-node; set listen under \
+                 [rpc_front] in the config file or pass --rpc-addr"
+            )
+        })
+}
+
+/// blvm-specific settings from the same config file (keys blvm-node does not read)
+///
+/// Invalid values are an error, as they are for `NodeConfig`, rather than silently ignored.
+fn load_extra_config(cli_config: &Option<PathBuf>) -> Result<blvm::extra_config::ExtraConfig> {
+    let extra = match find_config_file(cli_config) {
+        Some(path) => blvm::extra_config::ExtraConfig::from_file(&path)
+            .with_context(|| format!("Invalid blvm settings in {}", path.display()))?,
+        None => Default::default(),
+    };
+    let assignments = blvm::config_overlay::env_assignments(env::vars());
+    if assignments.is_empty() {
+        return Ok(extra);
+    }
+    blvm::config_overlay::apply(&extra, &assignments).with_context(|| {
+        format!(
+            "Invalid {}* settings for blvm",
+            blvm::config_overlay::ENV_PREFIX
+        )
+    })
+}
+
 /// Build final configuration with hierarchy: CLI > ENV > Config > Defaults
 fn network_from_cli_enum(network: &Network) -> &'static str {
     match network {
@@ -862,6 +2644,17 @@ fn build_final_config(cli: &Cli) -> Result<(NodeConfig, String, SocketAddr, Sock
         warn!("Config file specified but not found. Using defaults.");
     }
 
+    // 2b. BLVM_CONFIG__SECTION__KEY variables: any config key from the environment
+    let env_assignments = blvm::config_overlay::env_assignments(env::vars());
+    if !env_assignments.is_empty() {
+        config = blvm::config_overlay::apply(&config, &env_assignments)?;
+        for (key, _) in &env_assignments {
+            info!("Config key {} set by ENV", key);
+        }
+        // Keys set this way count as config file settings (listen_addr, protocol_version)
+        config_loaded_from_file = true;
+    }
+
     // 3. Load ENV overrides
     let env_overrides = EnvOverrides::from_env();
 
@@ -944,6 +2737,12 @@ fn build_final_config(cli: &Cli) -> Result<(NodeConfig, String, SocketAddr, Sock
         .or_else(|| env_overrides.data_dir.clone())
         .or_else(|| config.storage.as_ref().map(|s| s.data_dir.clone()))
         .unwrap_or_else(|| "./data".to_string());
+    // `{hostname}` gives each StatefulSet pod its own directory from one spec
+    let hostname = blvm::datadir::hostname();
+    let data_dir = blvm::datadir::expand_hostname(&data_dir, hostname.as_deref());
+    if let Some(storage) = config.storage.as_mut() {
+        storage.data_dir = blvm::datadir::expand_hostname(&storage.data_dir, hostname.as_deref());
+    }
 
     // listen_addr: CLI → ENV → config file (if loaded) → network-aware default
     let default_listen_port = blvm::default_p2p_port_for_network(network_from_cli_enum(&network));
@@ -957,10 +2756,57 @@ fn build_final_config(cli: &Cli) -> Result<(NodeConfig, String, SocketAddr, Sock
         })
         .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], default_listen_port)));
 
+    // Outbound-only: --nolisten > BLVM_NOLISTEN > `listen = false` in config file.
+    // The node always binds a P2P listener, so it is moved to an ephemeral loopback port where
+    // no remote peer can connect, and self-advertisement is turned off so no address is announced.
+    let extra = load_extra_config(&cli.config)?;
+    if extra.replica.enabled {
+        info!("Replica mode: following primary, no inbound P2P");
+    }
+    let nolisten = cli.nolisten
+        || extra.replica.enabled
+        || env_overrides
+            .nolisten
+            .or(extra.listen.map(|listen| !listen))
+            .unwrap_or(false);
+    let listen_addr = if nolisten {
+        info!(
+            "Inbound P2P disabled (nolisten): listener on an ephemeral loopback port, \
+             self-advertisement off; outbound connections only"
+        );
+        config.enable_self_advertisement = false;
+        SocketAddr::from(([127, 0, 0, 1], 0))
+    } else {
+        listen_addr
+    };
+    // `[addr_relay] advertise = "never"` keeps the address private even with inbound enabled
+    if extra.addr_relay.advertise == blvm::addr_relay::Advertise::Never {
+        config.enable_self_advertisement = false;
+    }
+
     let rpc_addr = cli
         .rpc_addr
         .or(env_overrides.rpc_addr)
         .unwrap_or_else(|| blvm::default_rpc_addr_for_network(network_from_cli_enum(&network)));
+    // RPC client credentials: ENV > config file; a user without a password sends none
+    let rpc_user = env_overrides.rpc_user.clone().or(extra.rpc_user.clone());
+    if let Some(password) = env_overrides
+        .rpc_password
+        .clone()
+        .or(extra.rpc_password.clone())
+    {
+        if env_overrides.rpc_password.is_some() {
+            info!("RPC password set by ENV");
+        }
+        let _ = RPC_CREDENTIALS.set((rpc_user.unwrap_or_else(|| "btc".to_string()), password));
+    }
+    let _ = RPC_COOKIE_FILE.set(match &cli.rpc_cookie_file {
+        Some(path) => (path.clone(), true),
+        None => (
+            PathBuf::from(&data_dir).join(blvm::rpc_cookie::COOKIE_FILE),
+            false,
+        ),
+    });
 
     // Apply resolved values to config so downstream code reads them from one place
     config.listen_addr = Some(listen_addr);
@@ -1386,576 +3232,351 @@ fn rpc_connect_failure_hint(rpc_addr: SocketAddr) -> String {
     }
 }
 
-async fn rpc_call(rpc_addr: SocketAddr, method: &str, params: Value) -> Result<Value> {
-    rpc_call_with_auth(rpc_addr, method, params, None, None).await
+/// Credentials sent with an RPC request
+enum RpcAuth {
+    None,
+    Basic(String, String),
+    Bearer(String),
+    /// An `Authorization` header value sent as is (a caller of the RPC front)
+    Header(String),
 }
 
-/// JSON-RPC to a running node using credentials from the loaded `blvm.toml` (`[rpc_auth]`).
-async fn rpc_call_with_config(
-    rpc_addr: SocketAddr,
-    config: &NodeConfig,
-    method: &str,
-    params: Value,
-) -> Result<Value> {
-    if let Some(auth) = &config.rpc_auth {
-        if let Some(token) = auth.admin_tokens.first() {
-            return rpc_call_with_bearer(rpc_addr, method, params, token).await;
+impl RpcAuth {
+    /// `rpc_user` / `rpc_password`, else the RPC cookie file, else none
+    fn from_defaults() -> Result<Self> {
+        if let Some((user, password)) = RPC_CREDENTIALS.get() {
+            return Ok(RpcAuth::Basic(user.clone(), password.clone()));
         }
-        if let Some(token) = auth.tokens.first() {
-            return rpc_call_with_bearer(rpc_addr, method, params, token).await;
+        Ok(match rpc_cookie()? {
+            Some((user, password)) => RpcAuth::Basic(user, password),
+            None => RpcAuth::None,
+        })
+    }
+
+    /// A front caller's own credentials, passed on unchanged
+    fn from_header(authorization: Option<&str>) -> Self {
+        match authorization {
+            Some(value) => RpcAuth::Header(value.to_string()),
+            None => RpcAuth::None,
         }
-        if let Some(ref password) = auth.password {
-            let user = auth.username.as_deref().unwrap_or("btc");
-            return rpc_call_with_auth(
-                rpc_addr,
-                method,
-                params,
-                Some(user),
-                Some(password.as_str()),
-            )
-            .await;
+    }
+
+    /// `rpc_user` / `rpc_password`, else credentials from the loaded `blvm.toml`
+    /// (`[rpc_auth]`), else the RPC cookie file
+    fn from_config(config: &NodeConfig) -> Result<Self> {
+        if RPC_CREDENTIALS.get().is_some() {
+            return Self::from_defaults();
         }
-        if auth.required {
-            anyhow::bail!(
-                "RPC authentication required: set [rpc_auth].admin_tokens, tokens, or password in the same config file used with --config"
-            );
+        if let Some(auth) = &config.rpc_auth {
+            if let Some(token) = auth.admin_tokens.first() {
+                return Ok(RpcAuth::Bearer(token.clone()));
+            }
+            if let Some(token) = auth.tokens.first() {
+                return Ok(RpcAuth::Bearer(token.clone()));
+            }
+            if let Some(ref password) = auth.password {
+                let user = auth.username.as_deref().unwrap_or("btc");
+                return Ok(RpcAuth::Basic(user.to_string(), password.clone()));
+            }
+            if auth.required {
+                anyhow::bail!(
+                    "RPC authentication required: set [rpc_auth].admin_tokens, tokens, or password in the same config file used with --config"
+                );
+            }
         }
+        Self::from_defaults()
     }
-    rpc_call(rpc_addr, method, params).await
 }
 
-async fn rpc_call_with_bearer(
+async fn rpc_call(rpc_addr: SocketAddr, method: &str, params: Value) -> Result<Value> {
+    let auth = RpcAuth::from_defaults()?;
+    rpc_result(rpc_post(rpc_addr, &auth, &rpc_request(method, params)).await?)
+}
+
+/// Credentials from the RPC cookie file: `None` when the default file does not exist (the node
+/// does not use cookie auth), an error when an explicit `--rpc-cookie-file` cannot be read
+fn rpc_cookie() -> Result<Option<(String, String)>> {
+    let Some((path, explicit)) = RPC_COOKIE_FILE.get() else {
+        return Ok(None);
+    };
+    match blvm::rpc_cookie::read(path) {
+        Ok(credentials) => Ok(Some(credentials)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => Ok(None),
+        Err(e) => Err(anyhow::anyhow!(
+            "Failed to read RPC cookie {}: {}",
+            path.display(),
+            e
+        )),
+    }
+}
+
+/// JSON-RPC to a running node using `rpc_user` / `rpc_password`, else credentials from the
+/// loaded `blvm.toml` (`[rpc_auth]`), else the RPC cookie file.
+async fn rpc_call_with_config(
     rpc_addr: SocketAddr,
+    config: &NodeConfig,
     method: &str,
     params: Value,
-    token: &str,
 ) -> Result<Value> {
-    let url = format!("http://{rpc_addr}");
-    let client = reqwest::Client::new();
-    let request = json!({
-        "jsonrpc": "2.0",
-        "method": method,
-        "params": params,
-        "id": 1
-    });
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {token}"))
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| {
-            let hint = rpc_connect_failure_hint(rpc_addr);
-            anyhow::anyhow!("Failed to connect to RPC server at {rpc_addr}{hint}: {e}")
-        })?;
-    let status = response.status();
-    if !status.is_success() {
-        anyhow::bail!("RPC request failed with status: {}", status);
-    }
-    let json: Value = response
-        .json()
-        .await
-        .context("Failed to parse RPC response")?;
-    if let Some(error) = json.get("error") {
-        anyhow::bail!("RPC error: {}", error);
-    }
-    json.get("result")
-        .cloned()
-        .ok_or_else(|| anyhow::anyhow!("No result in RPC response"))
+    blvm::fail_point!("rpc.client.call");
+    let auth = RpcAuth::from_config(config)?;
+    rpc_result(rpc_post(rpc_addr, &auth, &rpc_request(method, params)).await?)
 }
 
-async fn rpc_call_with_auth(
+/// JSON-RPC to a running node with the given credentials
+async fn rpc_call_as(
     rpc_addr: SocketAddr,
+    auth: &RpcAuth,
     method: &str,
     params: Value,
-    user: Option<&str>,
-    password: Option<&str>,
 ) -> Result<Value> {
-    let url = format!("http://{rpc_addr}");
-    let client = reqwest::Client::new();
-
-    let request = json!({
-        "jsonrpc": "2.0",
-        "method": method,
-        "params": params,
-        "id": 1
-    });
-
-    let mut req = client.post(&url).json(&request);
-
-    // Only attach credentials when explicitly configured — sending default btc/"" causes 401
-    // against localhost nodes in rate-limit-only mode (auth manager present, auth not required).
-    if user.is_some() || password.is_some() {
-        let rpc_user = user.unwrap_or("btc");
-        let rpc_password = password.unwrap_or("");
-        req = req.basic_auth(rpc_user, Some(rpc_password));
-    }
-
-    let response = req.send().await.map_err(|e| {
-        let hint = rpc_connect_failure_hint(rpc_addr);
-        anyhow::anyhow!("Failed to connect to RPC server at {rpc_addr}{hint}: {e}")
-    })?;
-
-    let status = response.status();
-    if !status.is_success() {
-        anyhow::bail!("RPC request failed with status: {}", status);
-    }
-
-    let json: Value = response
-        .json()
-        .await
-        .context("Failed to parse RPC response")?;
-
-    if let Some(error) = json.get("error") {
-        anyhow::bail!("RPC error: {}", error);
-    }
-
-    json.get("result")
-        .cloned()
-        .ok_or_else(|| anyhow::anyhow!("No result in RPC response"))
+    blvm::fail_point!("rpc.client.call");
+    rpc_result(rpc_post(rpc_addr, auth, &rpc_request(method, params)).await?)
 }
 
-// Subcommand handlers
-async fn handle_status(rpc_addr: SocketAddr, config: &NodeConfig) -> Result<()> {
-    let chain_info = rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await?;
-    let network_info = rpc_call_with_config(rpc_addr, config, "getnetworkinfo", json!([])).await?;
-    let peer_info = rpc_call_with_config(rpc_addr, config, "getpeerinfo", json!([])).await?;
-
-    println!("=== Node Status ===");
-    println!(
-        "Block Height: {}",
-        chain_info
-            .get("blocks")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0)
-    );
-    println!(
-        "Chain: {}",
-        chain_info
-            .get("chain")
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown")
-    );
-    println!(
-        "Verification Progress: {:.2}%",
-        chain_info
-            .get("verificationprogress")
-            .and_then(|v| v.as_f64())
-            .unwrap_or(0.0)
-            * 100.0
-    );
-    println!(
-        "Connected Peers: {}",
-        peer_info.as_array().map(|a| a.len()).unwrap_or(0)
-    );
-    println!(
-        "Network Active: {}",
-        network_info
-            .get("networkactive")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false)
-    );
-
-    Ok(())
+/// Send `calls` as one JSON-RPC batch (credentials as for [`rpc_call_with_config`]); replies
+/// come back in call order
+async fn rpc_batch_with_config(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    calls: &[blvm::rpc_batch::BatchCall],
+) -> Result<Vec<std::result::Result<Value, Value>>> {
+    blvm::fail_point!("rpc.client.call");
+    let auth = RpcAuth::from_config(config)?;
+    let response = rpc_post(rpc_addr, &auth, &blvm::rpc_batch::request_body(calls)).await?;
+    blvm::rpc_batch::order_responses(calls.len(), &response).map_err(|e| anyhow::anyhow!(e))
 }
 
-async fn handle_health(rpc_addr: SocketAddr, config: &NodeConfig) -> Result<()> {
-    match rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await {
-        Ok(_) => {
-            println!("✅ Node is healthy");
-            Ok(())
-        }
-        Err(e) => {
-            eprintln!("❌ Health check failed: {e}");
-            std::process::exit(1);
-        }
+/// RPC endpoint URL: `https://` with `--rpc-tls`
+fn rpc_url(rpc_addr: SocketAddr) -> String {
+    match RPC_TLS.get() {
+        Some(_) => format!("https://{rpc_addr}"),
+        None => format!("http://{rpc_addr}"),
     }
 }
 
-fn handle_version() -> Result<()> {
-    println!("blvm {}", env!("CARGO_PKG_VERSION"));
-    println!("Repository: {}", env!("CARGO_PKG_REPOSITORY"));
-
-    // Try to get git info if available
-    if let Ok(sha) = std::process::Command::new("git")
-        .args(["rev-parse", "--short", "HEAD"])
-        .output()
-    {
-        if let Ok(sha_str) = String::from_utf8(sha.stdout) {
-            println!("Git: {}", sha_str.trim());
-        }
+/// HTTP client for RPC calls, built on first use and shared (with its connection pool) by
+/// every call after that
+fn rpc_client() -> Result<&'static reqwest::Client> {
+    static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
     }
-
-    // Show enabled features
-    println!("\nFeatures:");
-    #[cfg(feature = "utxo-commitments")]
-    println!("  ✓ utxo-commitments");
-    #[cfg(feature = "dandelion")]
-    println!("  ✓ dandelion");
-    #[cfg(feature = "ctv")]
-    println!("  ✓ ctv");
-    #[cfg(feature = "stratum-v2")]
-    println!("  ✓ stratum-v2");
-    println!("  ✓ bip158 (always on)");
-    #[cfg(feature = "sigop")]
-    println!("  ✓ sigop");
-
-    Ok(())
+    let client = build_rpc_client()?;
+    Ok(CLIENT.get_or_init(|| client))
 }
 
-async fn handle_chain(rpc_addr: SocketAddr, config: &NodeConfig) -> Result<()> {
-    let info = rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await?;
-
-    println!("=== Blockchain Information ===");
-    println!(
-        "Chain: {}",
-        info.get("chain")
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown")
-    );
-    println!(
-        "Blocks: {}",
-        info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0)
-    );
-    println!(
-        "Headers: {}",
-        info.get("headers").and_then(|v| v.as_u64()).unwrap_or(0)
-    );
-    if let Some(hash) = info.get("bestblockhash").and_then(|v| v.as_str()) {
-        println!("Best Block: {hash}");
-    }
-    if let Some(diff) = info.get("difficulty").and_then(|v| v.as_f64()) {
-        println!("Difficulty: {diff:.2}");
+/// HTTP client with the `--rpc-timeout` limit, trusting `--rpc-ca-cert` or (with
+/// `--rpc-insecure`) any certificate
+fn build_rpc_client() -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(timeout) = RPC_RETRY.get().and_then(|retry| retry.timeout) {
+        builder = builder.timeout(timeout);
     }
-    if let Some(progress) = info.get("verificationprogress").and_then(|v| v.as_f64()) {
-        println!("Verification Progress: {:.2}%", progress * 100.0);
-    }
-
-    Ok(())
+    let Some((ca_cert, insecure)) = RPC_TLS.get() else {
+        return builder.build().context("Failed to set up RPC client");
+    };
+    builder = builder.danger_accept_invalid_certs(*insecure);
+    if *insecure {
+        builder = builder.danger_accept_invalid_hostnames(true);
+    }
+    if let Some(path) = ca_cert {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("Failed to read RPC CA certificate {}", path.display()))?;
+        let cert = reqwest::Certificate::from_pem(&pem)
+            .with_context(|| format!("Invalid RPC CA certificate {}", path.display()))?;
+        builder = builder.add_root_certificate(cert);
+    }
+    builder.build().context("Failed to set up RPC TLS client")
 }
 
-async fn handle_peers(rpc_addr: SocketAddr, config: &NodeConfig) -> Result<()> {
-    let peers = rpc_call_with_config(rpc_addr, config, "getpeerinfo", json!([])).await?;
-
-    println!("=== Connected Peers ===");
-    if let Some(peer_array) = peers.as_array() {
-        if peer_array.is_empty() {
-            println!("No peers connected");
-        } else {
-            for (i, peer) in peer_array.iter().enumerate() {
-                println!("\nPeer {}:", i + 1);
-                if let Some(addr) = peer.get("addr").and_then(|v| v.as_str()) {
-                    println!("  Address: {addr}");
-                }
-                if let Some(version) = peer.get("version").and_then(|v| v.as_u64()) {
-                    println!("  Version: {version}");
-                }
-                if let Some(latency) = peer.get("latency").and_then(|v| v.as_f64()) {
-                    println!("  Latency: {:.2}ms", latency * 1000.0);
-                }
-            }
-        }
-    }
-
-    Ok(())
+fn rpc_request(method: &str, params: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+        "id": 1
+    })
 }
 
-async fn handle_network(rpc_addr: SocketAddr, config: &NodeConfig) -> Result<()> {
-    let info = rpc_call_with_config(rpc_addr, config, "getnetworkinfo", json!([])).await?;
-
-    println!("=== Network Information ===");
-    println!(
-        "Version: {}",
-        info.get("version").and_then(|v| v.as_u64()).unwrap_or(0)
-    );
-    println!(
-        "Subversion: {}",
-        info.get("subversion")
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown")
-    );
-    println!(
-        "Network Active: {}",
-        info.get("networkactive")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false)
-    );
-    if let Some(connections) = info.get("connections").and_then(|v| v.as_u64()) {
-        println!("Connections: {connections}");
-    }
-    if let Some(local_addrs) = info.get("localaddresses").and_then(|v| v.as_array()) {
-        if !local_addrs.is_empty() {
-            println!("Local Addresses:");
-            for addr in local_addrs {
-                if let Some(addr_str) = addr.get("address").and_then(|v| v.as_str()) {
-                    println!("  {addr_str}");
-                }
+/// POST a JSON-RPC body (one request or a batch) and return the parsed response body.
+/// Connection failures are retried `--rpc-retries` times, or until the server answers with
+/// `--rpc-wait` (which also waits out the `-28` warm-up error). Timeouts are not retried: the
+/// node may already have acted on the request, and calls like `sendrawtransaction` or
+/// `submitblock` must not be sent twice.
+async fn rpc_post(rpc_addr: SocketAddr, auth: &RpcAuth, body: &Value) -> Result<Value> {
+    let url = rpc_url(rpc_addr);
+    let client = rpc_client()?;
+    let retry = RPC_RETRY.get().copied().unwrap_or_default();
+    let mut attempt = 0u32;
+    loop {
+        let mut req = client.post(&url).json(body);
+        match auth {
+            RpcAuth::None => {}
+            RpcAuth::Basic(user, password) => req = req.basic_auth(user, Some(password)),
+            RpcAuth::Bearer(token) => req = req.header("Authorization", format!("Bearer {token}")),
+            RpcAuth::Header(value) => req = req.header("Authorization", value),
+        }
+
+        let response = match req.send().await {
+            Ok(response) => response,
+            Err(e) if e.is_connect() && (retry.wait || attempt < retry.retries) => {
+                attempt += 1;
+                tracing::debug!("RPC request to {} failed ({}), retrying", rpc_addr, e);
+                tokio::time::sleep(rpc_retry_delay(attempt)).await;
+                continue;
             }
-        }
-    }
-
-    Ok(())
-}
-
-async fn handle_sync(rpc_addr: SocketAddr, config: &NodeConfig) -> Result<()> {
-    let info = rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await?;
-
-    let blocks = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
-    let headers = info.get("headers").and_then(|v| v.as_u64()).unwrap_or(0);
-    let progress = info
-        .get("verificationprogress")
-        .and_then(|v| v.as_f64())
-        .unwrap_or(0.0);
-    let initial_block_download = info
-        .get("initialblockdownload")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false);
+            Err(e) => {
+                let hint = rpc_connect_failure_hint(rpc_addr);
+                let tries = match attempt {
+                    0 => String::new(),
+                    n => format!(" after {} attempts", n + 1),
+                };
+                anyhow::bail!("Failed to connect to RPC server at {rpc_addr}{hint}{tries}: {e}");
+            }
+        };
 
-    println!("=== Sync Status ===");
-    println!("Blocks: {blocks}");
-    println!("Headers: {headers}");
-    println!("Progress: {:.2}%", progress * 100.0);
-    if initial_block_download {
-        println!("Initial block download: yes (active IBD)");
-    }
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("RPC request failed with status: {}", status);
+        }
 
-    if blocks == headers && progress >= 1.0 {
-        println!("Status: ✅ Fully synced");
-    } else if headers > blocks {
-        println!("Status: ⏳ Syncing ({} blocks behind)", headers - blocks);
-    } else if progress < 0.999 && blocks > 0 {
-        println!("Status: ⏳ Verifying downloaded blocks");
-        println!(
-            "Note: During active IBD, node logs (`IBD: <height> / <tip>`) are often ahead of this RPC view."
-        );
-    } else {
-        println!("Status: ⏳ Verifying");
+        let json: Value = response
+            .json()
+            .await
+            .context("Failed to parse RPC response")?;
+        let warming_up = json
+            .get("error")
+            .and_then(|e| e.get("code"))
+            .and_then(|c| c.as_i64())
+            == Some(-28);
+        if retry.wait && warming_up {
+            attempt += 1;
+            tokio::time::sleep(rpc_retry_delay(attempt)).await;
+            continue;
+        }
+        return Ok(json);
     }
-
-    Ok(())
 }
 
-fn handle_config_show(config: &NodeConfig) -> Result<()> {
-    println!(
-        "{}",
-        toml::to_string_pretty(config).context("Failed to serialize config")?
-    );
-    Ok(())
+/// Backoff before retry `attempt` (1-based): 250 ms doubling up to 5 s
+fn rpc_retry_delay(attempt: u32) -> Duration {
+    Duration::from_millis(250u64.saturating_mul(1 << attempt.saturating_sub(1).min(5)))
+        .min(Duration::from_secs(5))
 }
 
-fn handle_config_validate(path: Option<PathBuf>, cli_config: &Option<PathBuf>) -> Result<()> {
-    let config_path = path
-        .or_else(|| cli_config.clone())
-        .or_else(|| find_config_file(cli_config));
-
-    match config_path {
-        Some(path) => match NodeConfig::from_file(&path) {
-            Ok(config) => match config.validate() {
-                Ok(()) => {
-                    println!("✅ Configuration file is valid: {}", path.display());
-                    Ok(())
-                }
-                Err(e) => {
-                    eprintln!("❌ Configuration validation failed: {e}");
-                    std::process::exit(1);
-                }
-            },
-            Err(e) => {
-                eprintln!("❌ Configuration file is invalid: {e}");
-                std::process::exit(1);
+/// Whether the node serves `method`, probed without parameters: any answer but "Method not
+/// found" counts. `Err` when the node could not be asked.
+async fn node_serves(rpc_addr: SocketAddr, config: &NodeConfig, method: &str) -> Result<bool> {
+    match rpc_call_with_config(rpc_addr, config, method, json!([])).await {
+        Ok(_) => Ok(true),
+        Err(e) => {
+            let message = e.to_string();
+            if message.contains("-32601") || message.contains("status: 404") {
+                Ok(false)
+            } else if message.starts_with("RPC ") {
+                Ok(true)
+            } else {
+                Err(e)
             }
-        },
-        None => {
-            eprintln!("❌ No configuration file found");
-            std::process::exit(1);
         }
     }
 }
 
-fn handle_config_path(cli_config: &Option<PathBuf>) -> Result<()> {
-    if let Some(path) = find_config_file(cli_config) {
-        println!("{}", path.display());
-        Ok(())
-    } else {
-        println!("No configuration file found");
-        Ok(())
+/// `result` of a single-call response
+fn rpc_result(json: Value) -> Result<Value> {
+    // Core (JSON-RPC 1.0 style) sends `"error": null` on success
+    if let Some(error) = json.get("error").filter(|e| !e.is_null()) {
+        anyhow::bail!("RPC error: {}", error);
     }
-}
 
-/// Set config value(s) in the config file. Supports dotted keys for primary and module config.
-/// Examples: storage.data_dir=./data, modules.stratum-v2.listen_addr=0.0.0.1:3333
-fn handle_config_set(cli_config: &Option<PathBuf>, assignments: &[String]) -> Result<()> {
-    let config_path = find_config_file(cli_config)
-        .or_else(|| Some(PathBuf::from("./blvm.toml")))
-        .ok_or_else(|| anyhow::anyhow!("No config file path"))?;
+    json.get("result")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("No result in RPC response"))
+}
 
-    let mut content = if config_path.exists() {
-        std::fs::read_to_string(&config_path).context("Failed to read config file")?
-    } else {
-        String::new()
-    };
+async fn rpc_call_with_bearer(
+    rpc_addr: SocketAddr,
+    method: &str,
+    params: Value,
+    token: &str,
+) -> Result<Value> {
+    let auth = RpcAuth::Bearer(token.to_string());
+    rpc_result(rpc_post(rpc_addr, &auth, &rpc_request(method, params)).await?)
+}
 
-    let mut root: toml::Value = if content.trim().is_empty() {
-        toml::Value::Table(toml::map::Map::new())
+async fn rpc_call_with_auth(
+    rpc_addr: SocketAddr,
+    method: &str,
+    params: Value,
+    user: Option<&str>,
+    password: Option<&str>,
+) -> Result<Value> {
+    // Only attach credentials when explicitly configured — sending default btc/"" causes 401
+    // against localhost nodes in rate-limit-only mode (auth manager present, auth not required).
+    let auth = if user.is_some() || password.is_some() {
+        RpcAuth::Basic(
+            user.unwrap_or("btc").to_string(),
+            password.unwrap_or("").to_string(),
+        )
     } else {
-        content
-            .parse()
-            .context("Failed to parse config file as TOML")?
+        RpcAuth::None
     };
-
-    for assignment in assignments {
-        let (key, value_str) = assignment.split_once('=').ok_or_else(|| {
-            anyhow::anyhow!("Invalid assignment '{}': expected key=value", assignment)
-        })?;
-        let key = key.trim();
-        let value_str = value_str.trim();
-
-        let value = parse_toml_value(value_str)?;
-        set_toml_dotted(&mut root, key, value)?;
-    }
-
-    content = toml::to_string_pretty(&root).context("Failed to serialize config")?;
-    std::fs::write(&config_path, content).context("Failed to write config file")?;
-    println!("Updated {}", config_path.display());
-    Ok(())
+    rpc_result(rpc_post(rpc_addr, &auth, &rpc_request(method, params)).await?)
 }
 
-fn parse_toml_value(s: &str) -> Result<toml::Value> {
-    let s = s.trim();
-    if s == "true" {
-        return Ok(toml::Value::Boolean(true));
-    }
-    if s == "false" {
-        return Ok(toml::Value::Boolean(false));
-    }
-    if let Ok(i) = s.parse::<i64>() {
-        return Ok(toml::Value::Integer(i));
-    }
-    if let Ok(f) = s.parse::<f64>() {
-        return Ok(toml::Value::Float(f));
+/// `user:password` credentials (from `--left-auth` / `--right-auth`); `None` sends no auth.
+async fn rpc_call_with_user_password(
+    rpc_addr: SocketAddr,
+    method: &str,
+    params: Value,
+    auth: Option<&str>,
+) -> Result<Value> {
+    match auth {
+        Some(auth) => {
+            let (user, password) = auth
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("Credentials must be user:password"))?;
+            rpc_call_with_auth(rpc_addr, method, params, Some(user), Some(password)).await
+        }
+        None => rpc_call(rpc_addr, method, params).await,
     }
-    Ok(toml::Value::String(s.to_string()))
 }
 
-fn set_toml_dotted(root: &mut toml::Value, key: &str, value: toml::Value) -> Result<()> {
-    let parts: Vec<&str> = key.split('.').collect();
-    if parts.is_empty() {
-        anyhow::bail!("Empty key");
-    }
+type SharedEventLog = std::sync::Arc<std::sync::Mutex<blvm::events::EventLog>>;
 
-    let mut current = root;
-    for (i, part) in parts.iter().enumerate() {
-        let is_last = i == parts.len() - 1;
-        if is_last {
-            if let toml::Value::Table(t) = current {
-                t.insert(part.to_string(), value);
-                return Ok(());
-            }
-            anyhow::bail!(
-                "Key '{}': expected table at '{}'",
-                key,
-                parts[..=i].join(".")
-            );
-        }
-        if let toml::Value::Table(t) = current {
-            let entry = t
-                .entry(part.to_string())
-                .or_insert_with(|| toml::Value::Table(toml::map::Map::new()));
-            if let toml::Value::Table(_) = entry {
-                current = entry;
-            } else {
-                anyhow::bail!(
-                    "Key '{}': '{}' exists but is not a section",
-                    key,
-                    parts[..=i].join(".")
-                );
-            }
-        } else {
-            anyhow::bail!(
-                "Key '{}': expected table at '{}'",
-                key,
-                parts[..=i].join(".")
-            );
-        }
+fn record_event(log: Option<&SharedEventLog>, event: blvm::events::Event) {
+    if let Ok(mut peer_events) = blvm::peer_events::log().lock() {
+        peer_events.record(&event);
+    }
+    let Some(Ok(mut log)) = log.map(|log| log.lock()) else {
+        return;
+    };
+    if let Err(e) = log.record(event) {
+        warn!("Failed to write {}: {}", log.path().display(), e);
     }
-    Ok(())
 }
 
-/// Print config file path for a module (works offline; uses config to resolve path)
-fn handle_module_config_path(module: &str, config: &NodeConfig, data_dir: &str) -> Result<()> {
-    let modules_data_dir = config
-        .modules
-        .as_ref()
-        .map(|m| PathBuf::from(&m.data_dir))
-        .unwrap_or_else(|| PathBuf::from(data_dir).join("modules"));
-    let path = modules_data_dir.join(module).join("config.toml");
-    println!("{}", path.display());
-    Ok(())
-}
+/// Reason background tasks want `/readyz` to fail (`None` = no objection)
+type SharedHold = std::sync::Arc<std::sync::Mutex<Option<String>>>;
 
-async fn handle_rpc(
-    rpc_addr: SocketAddr,
-    method: &str,
-    params: Value,
-    config: &NodeConfig,
-) -> Result<()> {
-    let result = rpc_call_with_config(rpc_addr, config, method, params).await?;
-    println!("{}", serde_json::to_string_pretty(&result)?);
-    Ok(())
-}
+/// The binary's own settings as of the last config reload (SIGHUP)
+type LiveConfig = tokio::sync::watch::Receiver<blvm::extra_config::ExtraConfig>;
 
-async fn handle_module(
-    rpc_addr: SocketAddr,
-    subcommand: &ModuleCommand,
-    config: &NodeConfig,
-) -> Result<()> {
-    let (method, params) = match subcommand {
-        ModuleCommand::Load { name } => ("loadmodule", json!([name])),
-        ModuleCommand::Unload { name } => ("unloadmodule", json!([name])),
-        ModuleCommand::Reload { name } => ("reloadmodule", json!([name])),
-        ModuleCommand::List => ("listmodules", json!([])),
-    };
-    let result = rpc_call_with_config(rpc_addr, config, method, params).await?;
-    println!("{}", serde_json::to_string_pretty(&result)?);
-    Ok(())
+/// Follow a reloaded `check_interval_secs`; the next tick is one new period away
+fn retime(ticker: &mut tokio::time::Interval, secs: u64) {
+    let period = Duration::from_secs(secs.max(1));
+    if ticker.period() != period {
+        *ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    }
 }
 
-/// Handle dynamic module CLI (e.g. blvm sync-policy list)
-async fn handle_module_cli(
-    rpc_addr: SocketAddr,
-    args: &[String],
-    config: &NodeConfig,
-) -> Result<()> {
-    if args.len() < 2 {
-        anyhow::bail!(
-            "Usage: blvm <module_name> <subcommand> [args...]\n\
-             Example: blvm sync-policy list\n\
-             Run 'blvm' with no args to see core commands. Module commands require the node to be running."
-        );
+/// Compact age like `42s`, `5m`, `3h`, `2d`
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
     }
-    let module_name = &args[0];
-    let subcommand = &args[1];
-    let sub_args: Vec<String> = args[2..].to_vec();
-    let params = {
-        let mut p = vec![json!(module_name), json!(subcommand)];
-        p.extend(sub_args.into_iter().map(Value::from));
-        Value::Array(p)
-    };
-    let result = rpc_call_with_config(rpc_addr, config, "runmodulecli", params).await?;
-    let stdout = result.get("stdout").and_then(|v| v.as_str()).unwrap_or("");
-    let stderr = result.get("stderr").and_then(|v| v.as_str()).unwrap_or("");
-    let exit_code = result
-        .get("exit_code")
-        .and_then(|v| v.as_i64())
-        .unwrap_or(1);
-    if !stdout.is_empty() {
-        print!("{stdout}");
-    }
-    if !stderr.is_empty() {
-        eprint!("{stderr}");
-    }
-    if exit_code != 0 {
-        std::process::exit(exit_code as i32);
-    }
-    Ok(())
 }
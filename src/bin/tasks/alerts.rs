@@ -0,0 +1,407 @@
+//! Alerts, block anomalies, chain split detection and invalid-tip quarantine
+
+use crate::*;
+
+/// Evaluate `[alerts]` rules on a timer and notify on every state change
+pub(crate) async fn run_alerts(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    settings: blvm::alerts::AlertsConfig,
+    event_log: Option<SharedEventLog>,
+) {
+    let mut state = blvm::alerts::AlertState::load(&data_dir);
+    let mut ticker =
+        tokio::time::interval(Duration::from_secs(settings.check_interval_secs.max(1)));
+    loop {
+        ticker.tick().await;
+        let metrics = alert_metrics(rpc_addr, &config, &data_dir).await;
+        let now = blvm::mocktime::unix_now();
+        let transitions = state.evaluate(&settings.rules, &metrics, now);
+        if let Err(e) = state.save(&data_dir) {
+            warn!("Failed to save alert state: {}", e);
+        }
+        for transition in transitions {
+            let alert = transition.alert();
+            match &transition {
+                blvm::alerts::Transition::Fired(_) => {
+                    warn!("Alert {} firing: {}", alert.name, alert.message)
+                }
+                blvm::alerts::Transition::Resolved(_) => {
+                    info!("Alert {} resolved: {}", alert.name, alert.message)
+                }
+            }
+            record_event(
+                event_log.as_ref(),
+                blvm::events::Event::new(
+                    &format!("alert.{}", transition.status()),
+                    format!("{}: {}", alert.name, alert.message),
+                )
+                .with("alert", alert.name.clone()),
+            );
+            notify_alert(&settings, &transition, now).await;
+        }
+    }
+}
+
+/// Check each new tip's timestamp and difficulty (`[anomalies]`); findings are logged, recorded
+/// as `block.anomaly` events and kept in `anomalies.json` for `blvm status`
+pub(crate) async fn run_block_anomalies(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    live: LiveConfig,
+    event_log: Option<SharedEventLog>,
+) {
+    use blvm::block_anomalies::{AnomalyLog, Header, check};
+    let mut log = AnomalyLog::load(&data_dir);
+    let mut last_tip: Option<String> = None;
+    let mut ticker = tokio::time::interval(Duration::from_secs(10));
+    loop {
+        ticker.tick().await;
+        let settings = live.borrow().anomalies.clone();
+        if !settings.enabled {
+            last_tip = None;
+            continue;
+        }
+        let info =
+            match rpc_call_with_config(rpc_addr, &config, "getblockchaininfo", json!([])).await {
+                Ok(info) => info,
+                Err(e) => {
+                    tracing::debug!("Block anomaly check skipped: {}", e);
+                    continue;
+                }
+            };
+        let Some(best) = info.get("bestblockhash").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        if last_tip.as_deref() == Some(best) {
+            continue;
+        }
+        let Ok(tip) =
+            rpc_call_with_config(rpc_addr, &config, "getblockheader", json!([best])).await
+        else {
+            continue;
+        };
+        let Some(header) = Header::from_value(&tip) else {
+            continue;
+        };
+        // The first tip seen is only a baseline: it may be hours old after a restart
+        let first = last_tip.replace(best.to_string()).is_none();
+        if first {
+            continue;
+        }
+        let parent = match tip.get("previousblockhash") {
+            Some(prev) => rpc_call_with_config(rpc_addr, &config, "getblockheader", json!([prev]))
+                .await
+                .ok()
+                .and_then(|parent| Header::from_value(&parent)),
+            None => None,
+        };
+        let chain = info.get("chain").and_then(|v| v.as_str()).unwrap_or("");
+        let synced = !info
+            .get("initialblockdownload")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let now = blvm::mocktime::unix_now();
+        let found = check(&settings, &header, parent.as_ref(), chain, synced, now);
+        for anomaly in &found {
+            warn!("Block {} ({}): {}", header.height, header.hash, anomaly);
+            log.record(&header, anomaly, now);
+            record_event(
+                event_log.as_ref(),
+                blvm::events::Event::new(
+                    "block.anomaly",
+                    format!("Block {}: {}", header.height, anomaly),
+                )
+                .with("height", header.height)
+                .with("hash", header.hash.clone())
+                .with("anomaly", anomaly.kind()),
+            );
+        }
+        if !found.is_empty()
+            && let Err(e) = log.save(&data_dir)
+        {
+            warn!(
+                "Failed to write {}: {}",
+                blvm::block_anomalies::ANOMALIES_FILE,
+                e
+            );
+        }
+    }
+}
+
+/// Watch `getchaintips` for a competing chain most peers follow; raises and clears the
+/// `chain-split` alert (webhook / exec from `[alerts]`) and keeps `chain-split.json` current
+pub(crate) async fn run_chain_split(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    live: LiveConfig,
+    alerts: Option<blvm::alerts::AlertsConfig>,
+    event_log: Option<SharedEventLog>,
+) {
+    let mut state = blvm::chain_split::ChainSplitState::load(&data_dir);
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        ticker.tick().await;
+        let settings = live.borrow().chain_split.clone();
+        retime(&mut ticker, settings.check_interval_secs);
+        if !settings.enabled {
+            continue;
+        }
+        let split = match detect_chain_split(rpc_addr, &config, &settings).await {
+            Ok(split) => split,
+            Err(e) => {
+                tracing::debug!("Chain split check skipped: {}", e);
+                continue;
+            }
+        };
+        let now = blvm::mocktime::unix_now();
+        let transition = match (&state.split, &split) {
+            (None, Some(split)) => {
+                state.since = now;
+                warn!("{}", split.warning());
+                Some(blvm::alerts::Transition::Fired(blvm::alerts::ActiveAlert {
+                    name: "chain-split".to_string(),
+                    message: split.warning(),
+                    since: now,
+                    fired_at: now,
+                }))
+            }
+            (Some(previous), None) => {
+                info!(
+                    "Chain split resolved (fork at height {})",
+                    previous.fork_height
+                );
+                Some(blvm::alerts::Transition::Resolved(
+                    blvm::alerts::ActiveAlert {
+                        name: "chain-split".to_string(),
+                        message: format!(
+                            "fork at height {} no longer followed",
+                            previous.fork_height
+                        ),
+                        since: state.since,
+                        fired_at: now,
+                    },
+                ))
+            }
+            _ => None,
+        };
+        if state.split != split {
+            state.split = split;
+            if let Err(e) = state.save(&data_dir) {
+                warn!("Failed to write {}: {}", blvm::chain_split::STATE_FILE, e);
+            }
+        }
+        let Some(transition) = transition else {
+            continue;
+        };
+        let kind = match transition {
+            blvm::alerts::Transition::Fired(_) => "chain.split",
+            blvm::alerts::Transition::Resolved(_) => "chain.split_resolved",
+        };
+        let mut event = blvm::events::Event::new(kind, transition.alert().message.clone());
+        if let Some(split) = &state.split {
+            event = event
+                .with("fork_hash", split.fork_hash.clone())
+                .with("fork_height", split.fork_height)
+                .with("peers_on_fork", split.peers_on_fork);
+        }
+        record_event(event_log.as_ref(), event);
+        if let Some(alerts) = &alerts {
+            notify_alert(alerts, &transition, now).await;
+        }
+    }
+}
+
+/// The highest competing tip that meets `[chain_split]` thresholds, if any
+async fn detect_chain_split(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    settings: &blvm::chain_split::ChainSplitConfig,
+) -> Result<Option<blvm::chain_split::Split>> {
+    use blvm::chain_split::{Split, fork_candidates, parse_chainwork, peers_on_fork, work_ratio};
+    let tips = rpc_call_with_config(rpc_addr, config, "getchaintips", json!([])).await?;
+    let Some((active_height, forks)) = fork_candidates(&tips, settings.min_branch_len) else {
+        anyhow::bail!("getchaintips reported no active tip");
+    };
+    if forks.is_empty() {
+        return Ok(None);
+    }
+    let peers = rpc_call_with_config(rpc_addr, config, "getpeerinfo", json!([])).await?;
+    let chainwork = |header: &Value| {
+        header
+            .get("chainwork")
+            .and_then(|v| v.as_str())
+            .and_then(parse_chainwork)
+            .ok_or_else(|| anyhow::anyhow!("block header without chainwork"))
+    };
+    let best = rpc_call_with_config(rpc_addr, config, "getbestblockhash", json!([])).await?;
+    let active_work =
+        chainwork(&rpc_call_with_config(rpc_addr, config, "getblockheader", json!([best])).await?)?;
+    for fork in forks {
+        let (on_fork, total) = peers_on_fork(&peers, fork.height, active_height);
+        if total == 0 || (on_fork as f64) < settings.min_peer_fraction * total as f64 {
+            continue;
+        }
+        let fork_work = chainwork(
+            &rpc_call_with_config(rpc_addr, config, "getblockheader", json!([fork.hash])).await?,
+        )?;
+        let point = rpc_call_with_config(
+            rpc_addr,
+            config,
+            "getblockhash",
+            json!([fork.fork_point_height()]),
+        )
+        .await?;
+        let point_work = chainwork(
+            &rpc_call_with_config(rpc_addr, config, "getblockheader", json!([point])).await?,
+        )?;
+        let ratio = work_ratio(fork_work, active_work, point_work);
+        if ratio < settings.min_work_ratio {
+            continue;
+        }
+        return Ok(Some(Split {
+            fork_hash: fork.hash,
+            fork_height: fork.height,
+            branch_len: fork.branch_len,
+            status: fork.status,
+            active_height,
+            peers_on_fork: on_fork,
+            peers: total,
+            work_ratio: ratio,
+        }));
+    }
+    Ok(None)
+}
+
+/// Metric values for alert rules; any that cannot be read right now are left out
+async fn alert_metrics(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    data_dir: &Path,
+) -> blvm::alerts::Metrics {
+    use blvm::alerts::Metric;
+    let mut metrics = blvm::alerts::Metrics::new();
+    if let Ok(peers) = rpc_call_with_config(rpc_addr, config, "getpeerinfo", json!([])).await
+        && let Some(peers) = peers.as_array()
+    {
+        metrics.insert(Metric::PeerCount, peers.len() as f64);
+    }
+    if let Ok(best) = rpc_call_with_config(rpc_addr, config, "getbestblockhash", json!([])).await
+        && let Ok(header) =
+            rpc_call_with_config(rpc_addr, config, "getblockheader", json!([best, true])).await
+        && let Some(time) = header.get("time").and_then(|v| v.as_u64())
+    {
+        let now = blvm::mocktime::unix_now();
+        metrics.insert(
+            Metric::MinutesSinceBlock,
+            now.saturating_sub(time) as f64 / 60.0,
+        );
+    }
+    if let Some(free) = blvm::datadir::available_space(data_dir) {
+        metrics.insert(Metric::DiskFreeGb, free as f64 / 1e9);
+    }
+    metrics
+}
+
+async fn notify_alert(
+    settings: &blvm::alerts::AlertsConfig,
+    transition: &blvm::alerts::Transition,
+    now: u64,
+) {
+    let alert = transition.alert();
+    if let Some(url) = &settings.webhook {
+        let result = reqwest::Client::new()
+            .post(url)
+            .timeout(Duration::from_secs(10))
+            .json(&transition.payload(now))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        if let Err(e) = result {
+            warn!("Alert webhook {} failed: {}", url, e);
+        }
+    }
+    if let Some(cmd) = &settings.exec {
+        let status = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .env("BLVM_ALERT_NAME", &alert.name)
+            .env("BLVM_ALERT_STATUS", transition.status())
+            .env("BLVM_ALERT_MESSAGE", &alert.message)
+            .env("BLVM_ALERT_SINCE", alert.since.to_string())
+            .status()
+            .await;
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("Alert command exited with {}", status),
+            Err(e) => warn!("Alert command failed to start: {}", e),
+        }
+    }
+}
+
+/// Poll for chain tips the node marked invalid and quarantine their blocks
+pub(crate) async fn run_quarantine_watch(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    network: &'static str,
+    settings: blvm::quarantine::QuarantineConfig,
+) {
+    let params = blvm::chain_params::ChainParams::for_network(network);
+    let mut ticker = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        ticker.tick().await;
+        let Ok(tips) = rpc_call_with_config(rpc_addr, &config, "getchaintips", json!([])).await
+        else {
+            continue;
+        };
+        let invalid = tips
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|tip| tip.get("status").and_then(|v| v.as_str()) == Some("invalid"))
+            .filter_map(|tip| Some((tip.get("hash")?.as_str()?, tip.get("height")?.as_u64())));
+        for (hash, height) in invalid {
+            if blvm::quarantine::contains(&data_dir, hash) {
+                continue;
+            }
+            // Block data may be gone if the node only saw the header
+            let Ok(raw) =
+                rpc_call_with_config(rpc_addr, &config, "getblock", json!([hash, 0])).await
+            else {
+                continue;
+            };
+            let Ok(bytes) = hex::decode(raw.as_str().unwrap_or_default()) else {
+                continue;
+            };
+            let steps =
+                blvm::block_analysis::analyze(&bytes, params.as_ref(), height.map(|h| h as u32));
+            let reason = steps
+                .iter()
+                .find(|s| s.outcome == blvm::block_analysis::Outcome::Fail)
+                .map(|s| format!("{}: {}", s.name, s.detail))
+                .unwrap_or_else(|| {
+                    "rejected by node; context-free checks pass (script or UTXO rule)".to_string()
+                });
+            let entry = blvm::quarantine::QuarantineEntry {
+                hash: hash.to_string(),
+                height,
+                reason,
+                quarantined_at: blvm::mocktime::unix_now(),
+                trace: steps.iter().map(|s| s.to_string()).collect(),
+            };
+            match blvm::quarantine::save(&data_dir, &bytes, &entry, settings.max_entries) {
+                Ok(path) => warn!(
+                    "Invalid block {} quarantined at {} ({})",
+                    hash,
+                    path.display(),
+                    entry.reason
+                ),
+                Err(e) => warn!("Failed to quarantine invalid block {}: {}", hash, e),
+            }
+        }
+    }
+}
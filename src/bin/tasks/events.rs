@@ -0,0 +1,341 @@
+//! The event watcher and block notifications
+
+use crate::*;
+
+/// Poll the node for new tips, reorgs, peer connects, disconnects and bans and record them
+pub(crate) async fn run_event_watch(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    log: Option<SharedEventLog>,
+) {
+    let mut tip: Option<(String, u64)> = None;
+    let mut peers = None;
+    let mut ticker = tokio::time::interval(Duration::from_secs(10));
+    loop {
+        ticker.tick().await;
+        match poll_events(rpc_addr, &config, &mut tip, &mut peers).await {
+            Ok(events) => {
+                for event in events {
+                    record_event(log.as_ref(), event);
+                }
+            }
+            Err(e) => tracing::debug!("Event poll skipped: {}", e),
+        }
+    }
+}
+
+/// Connected peers (id -> address, inbound) and banned subnets at the last event poll
+type PeerSnapshot = (
+    std::collections::HashMap<u64, (String, bool)>,
+    std::collections::HashSet<String>,
+);
+
+async fn poll_events(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    tip: &mut Option<(String, u64)>,
+    peers: &mut Option<PeerSnapshot>,
+) -> Result<Vec<blvm::events::Event>> {
+    use blvm::events::Event;
+    let header = |hash: String| async move {
+        rpc_call_with_config(rpc_addr, config, "getblockheader", json!([hash, true])).await
+    };
+    let mut events = Vec::new();
+
+    let best = rpc_call_with_config(rpc_addr, config, "getbestblockhash", json!([])).await?;
+    let best = best.as_str().unwrap_or_default().to_string();
+    if tip.as_ref().map(|(hash, _)| hash) != Some(&best) {
+        let height = header(best.clone())
+            .await?
+            .get("height")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        if let Some((old_hash, _)) = tip.take() {
+            // A previous tip that left the best chain means a reorg; walk back to the fork
+            let mut cursor = header(old_hash.clone()).await?;
+            let mut depth = 0u64;
+            while cursor.get("confirmations").and_then(|v| v.as_i64()) == Some(-1) && depth < 1000 {
+                depth += 1;
+                let Some(prev) = cursor.get("previousblockhash").and_then(|v| v.as_str()) else {
+                    break;
+                };
+                cursor = header(prev.to_string()).await?;
+            }
+            if depth > 0 {
+                let fork_height = cursor.get("height").and_then(|v| v.as_u64()).unwrap_or(0);
+                events.push(
+                    Event::new(
+                        "chain.reorg",
+                        format!("Reorganization replaced {depth} block(s)"),
+                    )
+                    .with("depth", depth)
+                    .with("fork_height", fork_height)
+                    .with("old_tip", old_hash)
+                    .with("new_tip", best.clone()),
+                );
+            }
+        }
+        events.push(
+            Event::new("chain.tip", format!("New tip at height {height}"))
+                .with("height", height)
+                .with("hash", best.clone()),
+        );
+        *tip = Some((best, height));
+    }
+
+    if let (Ok(info), Ok(banned)) = (
+        rpc_call_with_config(rpc_addr, config, "getpeerinfo", json!([])).await,
+        rpc_call_with_config(rpc_addr, config, "listbanned", json!([])).await,
+    ) {
+        let connected: std::collections::HashMap<u64, (String, bool)> = info
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|p| {
+                let id = p.get("id")?.as_u64()?;
+                let addr = p.get("addr")?.as_str()?.to_string();
+                let inbound = p.get("inbound").and_then(Value::as_bool).unwrap_or(false);
+                Some((id, (addr, inbound)))
+            })
+            .collect();
+        let banned: std::collections::HashSet<String> = banned
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|b| Some(b.get("address")?.as_str()?.to_string()))
+            .collect();
+        // The first poll only takes a snapshot; peers already connected are not news
+        if let Some((old_connected, old_banned)) = peers.as_ref() {
+            for (id, (addr, inbound)) in &connected {
+                if !old_connected.contains_key(id) {
+                    let direction = if *inbound { "inbound" } else { "outbound" };
+                    events.push(
+                        Event::new("peer.connect", format!("Connected {addr} ({direction})"))
+                            .with("addr", addr.clone())
+                            .with("inbound", *inbound),
+                    );
+                }
+            }
+            for (id, (addr, _)) in old_connected {
+                if !connected.contains_key(id) {
+                    let mut event = Event::new("peer.disconnect", format!("Disconnected {addr}"))
+                        .with("addr", addr.clone());
+                    if blvm::peer_events::is_banned(addr, &banned) {
+                        event.message.push_str(" (banned)");
+                        event = event.with("reason", "banned");
+                    }
+                    events.push(event);
+                }
+            }
+            for addr in banned.difference(old_banned) {
+                events.push(
+                    Event::new("peer.ban", format!("Banned {addr}")).with("addr", addr.clone()),
+                );
+            }
+        }
+        *peers = Some((connected, banned));
+    }
+    Ok(events)
+}
+
+/// Journal notification lines, then write them to every socket client (dropping slow or closed
+/// ones) or to the pipe (reopened when its reader went away)
+#[cfg(unix)]
+pub(crate) async fn run_notifications(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    settings: blvm::notifications::NotificationsConfig,
+    path: PathBuf,
+    data_dir: PathBuf,
+) {
+    use blvm::notifications::{MempoolTracker, Notification, Sink};
+    use tokio::io::AsyncWriteExt;
+
+    let journal = std::sync::Arc::new(std::sync::Mutex::new(blvm::event_journal::Journal::open(
+        &data_dir,
+        settings.journal_max_events,
+    )));
+    let clients =
+        std::sync::Arc::new(tokio::sync::Mutex::new(Vec::<tokio::net::UnixStream>::new()));
+    if settings.sink == Sink::Socket {
+        // A socket file left by a previous run would make bind fail
+        let _ = std::fs::remove_file(&path);
+        let listener = match tokio::net::UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("Notifications socket {} unavailable: {}", path.display(), e);
+                return;
+            }
+        };
+        let clients = clients.clone();
+        let journal = journal.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(accept_notification_client(
+                    stream,
+                    clients.clone(),
+                    journal.clone(),
+                ));
+            }
+        });
+    }
+
+    let mut pipe: Option<tokio::net::unix::pipe::Sender> = None;
+    let mut tip: Option<String> = None;
+    let mut mempool = MempoolTracker::default();
+    let mut ticker =
+        tokio::time::interval(Duration::from_millis(settings.poll_interval_ms.max(100)));
+    loop {
+        ticker.tick().await;
+        let mut events = Vec::new();
+        if settings.blocks {
+            match notification_tip(rpc_addr, &config, &mut tip).await {
+                Ok(tip_events) => events.extend(tip_events),
+                Err(e) => tracing::debug!("Notification tip check skipped: {}", e),
+            }
+        }
+        if settings.transactions {
+            match rpc_call_with_config(rpc_addr, &config, "getrawmempool", json!([])).await {
+                Ok(txids) => {
+                    let txids = txids
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|t| t.as_str().map(str::to_string))
+                        .collect();
+                    events.extend(
+                        mempool
+                            .update(txids)
+                            .into_iter()
+                            .map(|txid| Notification::Tx { txid }),
+                    );
+                }
+                Err(e) => tracing::debug!("Notification mempool check skipped: {}", e),
+            }
+        }
+        if events.is_empty() {
+            continue;
+        }
+        let bytes = match journal.lock().unwrap().append(&events) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Event journal write failed: {}", e);
+                continue;
+            }
+        };
+        let write_timeout = Duration::from_secs(1);
+        match settings.sink {
+            Sink::Socket => {
+                let mut clients = clients.lock().await;
+                let mut kept = Vec::with_capacity(clients.len());
+                for mut client in clients.drain(..) {
+                    if let Ok(Ok(())) =
+                        tokio::time::timeout(write_timeout, client.write_all(bytes.as_bytes()))
+                            .await
+                    {
+                        kept.push(client);
+                    }
+                }
+                *clients = kept;
+            }
+            Sink::Pipe => {
+                // Opening fails (ENXIO) while nobody has the FIFO open for reading
+                if pipe.is_none() {
+                    pipe = tokio::net::unix::pipe::OpenOptions::new()
+                        .open_sender(&path)
+                        .map_err(|e| tracing::debug!("Notification pipe not open: {}", e))
+                        .ok();
+                }
+                if let Some(sender) = pipe.as_mut() {
+                    let written =
+                        tokio::time::timeout(write_timeout, sender.write_all(bytes.as_bytes()))
+                            .await;
+                    if !matches!(written, Ok(Ok(()))) {
+                        pipe = None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Register a socket client, first replaying the journal past the sequence number in its
+/// optional `{"replay_from": N}` greeting. Holding the client list while replaying means no
+/// live line is missed in between; one may arrive twice.
+#[cfg(unix)]
+async fn accept_notification_client(
+    mut stream: tokio::net::UnixStream,
+    clients: std::sync::Arc<tokio::sync::Mutex<Vec<tokio::net::UnixStream>>>,
+    journal: std::sync::Arc<std::sync::Mutex<blvm::event_journal::Journal>>,
+) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    // Clients that only listen send nothing and start with live events
+    let mut greeting = String::new();
+    let read = BufReader::new(&mut stream).read_line(&mut greeting);
+    let acked = match tokio::time::timeout(Duration::from_millis(500), read).await {
+        Ok(Ok(_)) => blvm::event_journal::parse_replay_request(&greeting),
+        _ => None,
+    };
+    let mut clients = clients.lock().await;
+    if let Some(acked) = acked {
+        let replay = journal.lock().unwrap().replay(acked);
+        tracing::debug!(
+            "Replaying {} notification line(s) after seq {}",
+            replay.lines().count(),
+            acked
+        );
+        if stream.write_all(replay.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+    clients.push(stream);
+}
+
+/// Notifications for a tip that moved since the last call (none on the first call): a `reorg`
+/// when the previous tip is no longer in the active chain, then the new tip's `block`
+#[cfg(unix)]
+async fn notification_tip(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    tip: &mut Option<String>,
+) -> Result<Vec<blvm::notifications::Notification>> {
+    use blvm::notifications::Notification;
+
+    let hash = rpc_call_with_config(rpc_addr, config, "getbestblockhash", json!([])).await?;
+    let hash = hash.as_str().unwrap_or_default().to_string();
+    if tip.as_deref() == Some(hash.as_str()) {
+        return Ok(Vec::new());
+    }
+    let Some(old_tip) = tip.replace(hash.clone()) else {
+        return Ok(Vec::new());
+    };
+    let mut events = Vec::new();
+    // Walk back from the old tip to the first block still in the active chain
+    // (confirmations -1 marks a block off it)
+    let mut cursor = old_tip.clone();
+    for _ in 0..1000 {
+        let header =
+            rpc_call_with_config(rpc_addr, config, "getblockheader", json!([cursor])).await?;
+        if header.get("confirmations").and_then(|v| v.as_i64()) != Some(-1) {
+            if cursor != old_tip {
+                events.push(Notification::Reorg {
+                    old_tip,
+                    fork_height: header.get("height").and_then(|v| v.as_u64()).unwrap_or(0),
+                });
+            }
+            break;
+        }
+        match header.get("previousblockhash").and_then(|v| v.as_str()) {
+            Some(prev) => cursor = prev.to_string(),
+            None => break,
+        }
+    }
+    let header = rpc_call_with_config(rpc_addr, config, "getblockheader", json!([hash])).await?;
+    events.push(Notification::Block {
+        height: header.get("height").and_then(|v| v.as_u64()).unwrap_or(0),
+        time: header.get("time").and_then(|v| v.as_u64()).unwrap_or(0),
+        hash,
+    });
+    Ok(events)
+}
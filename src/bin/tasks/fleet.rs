@@ -0,0 +1,131 @@
+//! The fleet admin channel
+
+use crate::*;
+
+/// Serve the fleet admin channel: one signed JSON request line in, one reply line out
+pub(crate) async fn run_fleet_admin(
+    listen: SocketAddr,
+    verifier: blvm::fleet::Verifier,
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    event_log: Option<SharedEventLog>,
+) {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    let listener = match tokio::net::TcpListener::bind(listen).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Fleet admin channel could not bind {}: {}", listen, e);
+            return;
+        }
+    };
+    info!("Fleet admin channel on {}", listen);
+    let verifier = std::sync::Arc::new(tokio::sync::Mutex::new(verifier));
+    loop {
+        let Ok((stream, peer)) = listener.accept().await else {
+            continue;
+        };
+        let (verifier, config, event_log) = (verifier.clone(), config.clone(), event_log.clone());
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            let mut capped = (&mut reader).take(blvm::fleet::MAX_ENVELOPE as u64);
+            let read = tokio::time::timeout(Duration::from_secs(10), capped.read_line(&mut line));
+            if !matches!(read.await, Ok(Ok(n)) if n > 0) {
+                return;
+            }
+            // Cut off before parsing, so an unauthenticated peer cannot make it buffer more
+            let envelope = if !line.ends_with('\n') && line.len() >= blvm::fleet::MAX_ENVELOPE {
+                Err(format!(
+                    "envelope longer than {} bytes",
+                    blvm::fleet::MAX_ENVELOPE
+                ))
+            } else {
+                serde_json::from_str::<blvm::fleet::Envelope>(&line)
+                    .map_err(|e| format!("malformed envelope: {e}"))
+            };
+            let reply = match envelope {
+                Ok(envelope) => {
+                    let now = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0);
+                    let verified = verifier.lock().await.verify(&envelope, now);
+                    match verified {
+                        Ok(request) => {
+                            let result =
+                                run_fleet_command(&request.command, rpc_addr, &config).await;
+                            info!(
+                                "Fleet command '{}' from key {} ({}): {}",
+                                request.command.join(" "),
+                                request.key_id,
+                                peer,
+                                if result.is_ok() { "ok" } else { "failed" }
+                            );
+                            record_event(
+                                event_log.as_ref(),
+                                blvm::events::Event::new(
+                                    "fleet.exec",
+                                    format!("Admin command '{}'", request.command.join(" ")),
+                                )
+                                .with("key_id", request.key_id.clone())
+                                .with("ok", result.is_ok()),
+                            );
+                            result
+                        }
+                        Err(e) => {
+                            warn!("Rejected fleet request from {}: {}", peer, e);
+                            Err(e)
+                        }
+                    }
+                }
+                Err(e) => Err(e),
+            };
+            let reply = match reply {
+                Ok(message) => blvm::fleet::Reply { ok: true, message },
+                Err(message) => blvm::fleet::Reply { ok: false, message },
+            };
+            let mut stream = reader.into_inner();
+            let mut out = serde_json::to_string(&reply).unwrap_or_default();
+            out.push('\n');
+            let _ = stream.write_all(out.as_bytes()).await;
+        });
+    }
+}
+
+async fn run_fleet_command(
+    command: &[String],
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+) -> std::result::Result<String, String> {
+    use blvm::fleet::AdminCommand;
+    let rpc = |method: &'static str, params: Value| async move {
+        rpc_call_with_config(rpc_addr, config, method, params)
+            .await
+            .map_err(|e| e.to_string())
+    };
+    match AdminCommand::parse(command)? {
+        AdminCommand::Ping => {
+            let height = rpc("getblockcount", json!([])).await?;
+            Ok(format!(
+                "blvm {} at height {}",
+                env!("CARGO_PKG_VERSION"),
+                height
+            ))
+        }
+        AdminCommand::LogLevel(directives) => {
+            let reload = LOG_FILTER_RELOAD
+                .get()
+                .ok_or("log filter reload is not available in this build")?;
+            reload(&directives).map_err(|e| e.to_string())?;
+            Ok(format!("log filter set to '{directives}'"))
+        }
+        AdminCommand::PauseRelay => {
+            rpc("setnetworkactive", json!([false])).await?;
+            Ok("network activity paused".to_string())
+        }
+        AdminCommand::ResumeRelay => {
+            rpc("setnetworkactive", json!([true])).await?;
+            Ok("network activity resumed".to_string())
+        }
+    }
+}
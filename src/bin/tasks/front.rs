@@ -0,0 +1,543 @@
+//! The RPC front: a listener ahead of the node's RPC with a response cache, call
+//! statistics and the extra methods `blvm` answers itself
+
+use crate::commands::db::verify_chain;
+use crate::commands::stats::{fetch_script_types, load_mempool_histogram};
+use crate::*;
+
+type SharedRpcCache = std::sync::Arc<std::sync::Mutex<blvm::rpc_cache::RpcCache>>;
+
+type SharedRpcStats = std::sync::Arc<std::sync::Mutex<blvm::rpc_stats::RpcStats>>;
+
+/// Front listener for the node's RPC: requests go to the node unchanged, except that expensive
+/// reads are answered from the response cache. Every single call is timed into `getrpcstats`.
+pub(crate) async fn run_rpc_front(
+    listen: SocketAddr,
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    settings: blvm::rpc_front::RpcFrontConfig,
+) {
+    use tokio::io::{AsyncWriteExt, BufReader};
+    let listener = match tokio::net::TcpListener::bind(listen).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("RPC front could not bind {}: {}", listen, e);
+            return;
+        }
+    };
+    info!("RPC front on {} (node RPC {})", listen, rpc_addr);
+    let cache: SharedRpcCache = std::sync::Arc::new(std::sync::Mutex::new(
+        blvm::rpc_cache::RpcCache::new(settings.cache),
+    ));
+    // Authorization headers the node has accepted; a request whose header is not among them is
+    // checked with the node before its body is read
+    let accepted = std::sync::Arc::new(std::sync::Mutex::new(
+        blvm::rpc_front::AcceptedAuth::default(),
+    ));
+    let dispatcher = std::sync::Arc::new(blvm::rpc_dispatch::Dispatcher::new(settings.limits));
+    let histogram: SharedMempoolHistogram = Default::default();
+    let stats: SharedRpcStats = std::sync::Arc::new(std::sync::Mutex::new(
+        blvm::rpc_stats::RpcStats::new(blvm::mocktime::unix_now()),
+    ));
+    tokio::spawn(watch_rpc_cache_tip(rpc_addr, config.clone(), cache.clone()));
+    tokio::spawn(watch_mempool_histogram(
+        rpc_addr,
+        config.clone(),
+        histogram.clone(),
+    ));
+    let client = reqwest::Client::new();
+    let connections = std::sync::Arc::new(tokio::sync::Semaphore::new(
+        blvm::rpc_front::MAX_CONNECTIONS,
+    ));
+    loop {
+        // At the limit, stop accepting and leave new connections in the listen backlog
+        let Ok(connection) = connections.clone().acquire_owned().await else {
+            return;
+        };
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let (cache, dispatcher) = (cache.clone(), dispatcher.clone());
+        let (histogram, data_dir) = (histogram.clone(), data_dir.clone());
+        let (accepted, client, stats) = (accepted.clone(), client.clone(), stats.clone());
+        tokio::spawn(async move {
+            let _connection = connection;
+            let mut reader = BufReader::new(stream);
+            let read = async {
+                let head = blvm::rpc_front::read_head(&mut reader).await?;
+                // Credentials first: nothing past the head is read for a caller the node
+                // would turn away
+                let auth = head.authorization.as_deref();
+                if !front_authorized(&client, rpc_addr, &accepted, auth).await {
+                    return Err((401, "unauthorized".to_string()));
+                }
+                let body = blvm::rpc_front::read_body(&mut reader, head.content_length)
+                    .await
+                    .map_err(|e| (400, e.to_string()))?;
+                Ok::<_, (u16, String)>((head, body))
+            };
+            let reply = match tokio::time::timeout(Duration::from_secs(30), read).await {
+                Ok(Ok((head, body))) => {
+                    let method = serde_json::from_slice::<Value>(&body).ok().and_then(|r| {
+                        blvm::rpc_front::single_call(&r).map(|(method, _)| method.to_string())
+                    });
+                    let request_bytes = body.len() as u64;
+                    let started = std::time::Instant::now();
+                    let shared = (&cache, &dispatcher, &histogram, &stats);
+                    let reply =
+                        rpc_front_reply(&client, rpc_addr, head, body, &data_dir, shared).await;
+                    if let Some(method) = method
+                        && let Ok(mut stats) = stats.lock()
+                    {
+                        let response_bytes =
+                            reply.split_once("\r\n\r\n").map_or(0, |(_, b)| b.len());
+                        stats.record(
+                            &method,
+                            started.elapsed().as_secs_f64() * 1000.0,
+                            request_bytes,
+                            response_bytes as u64,
+                            blvm::rpc_front::is_error_reply(&reply),
+                        );
+                    }
+                    reply
+                }
+                Ok(Err((status, message))) => {
+                    blvm::rpc_front::response(status, &json!({ "error": message }).to_string())
+                }
+                Err(_) => return,
+            };
+            let _ = reader.into_inner().write_all(reply.as_bytes()).await;
+        });
+    }
+}
+
+/// Answer one request through the front: from the cache when possible, else from the node once
+/// the method's lane has a free slot
+async fn rpc_front_reply(
+    client: &reqwest::Client,
+    rpc_addr: SocketAddr,
+    head: blvm::rpc_front::RequestHead,
+    body: Vec<u8>,
+    data_dir: &Path,
+    (cache, dispatcher, histogram, stats): (
+        &SharedRpcCache,
+        &blvm::rpc_dispatch::Dispatcher,
+        &SharedMempoolHistogram,
+        &SharedRpcStats,
+    ),
+) -> String {
+    use blvm::rpc_front::{response, single_call, with_id};
+    let request: Option<Value> = serde_json::from_slice(&body).ok();
+    let call = request.as_ref().and_then(single_call);
+    // Calls the front makes to the node on the caller's behalf carry the caller's credentials
+    let caller = RpcAuth::from_header(head.authorization.as_deref());
+    if let (
+        Some(request),
+        Some((
+            method @ ("getrpcqueueinfo"
+            | "getmempoolhistogram"
+            | "getrpcstats"
+            | "dumptasks"
+            | "getnodestate"
+            | "getpeereventlog"),
+            params,
+        )),
+    ) = (&request, &call)
+    {
+        let result = match *method {
+            "getrpcqueueinfo" => dispatcher.info(),
+            "getpeereventlog" => blvm::peer_events::log()
+                .lock()
+                .map(|log| {
+                    log.to_json(
+                        params.get(0).and_then(|v| v.as_u64()).unwrap_or(50) as usize,
+                        params.get(1).and_then(|v| v.as_str()),
+                    )
+                })
+                .unwrap_or(Value::Null),
+            "getnodestate" => blvm::node_state::tracker()
+                .lock()
+                .map(|history| history.to_json())
+                .unwrap_or(Value::Null),
+            "dumptasks" => blvm::tasks::registry().dump(
+                blvm::tasks::DEFAULT_STALL,
+                params.get(0).and_then(|v| v.as_bool()).unwrap_or(false),
+            ),
+            "getrpcstats" => stats
+                .lock()
+                .ok()
+                .and_then(|s| serde_json::to_value(&*s).ok())
+                .unwrap_or(Value::Null),
+            _ => histogram.lock().map(|h| h.to_json()).unwrap_or(Value::Null),
+        };
+        let reply = json!({ "result": result, "error": null, "id": request.get("id") });
+        return response(200, &reply.to_string());
+    }
+    let mut generation = None;
+    if let (Some(request), Some((method, params))) = (&request, &call)
+        && let Ok(mut cache) = cache.lock()
+    {
+        if *method == "getrpccacheinfo" {
+            let reply = json!({ "result": cache.info(), "error": null, "id": request.get("id") });
+            return response(200, &reply.to_string());
+        }
+        if let Some(hit) = cache.get(method, params) {
+            return response(200, &with_id(hit, request).to_string());
+        }
+        generation = Some(cache.generation());
+    }
+
+    // Held until the node has answered
+    let _permit = match (&request, &call) {
+        (Some(request), Some((method, _))) => match dispatcher.acquire(method).await {
+            Ok(permit) => permit,
+            Err(message) => {
+                let error = json!({ "code": -1, "message": message });
+                let reply = json!({ "result": null, "error": error, "id": request.get("id") });
+                return response(503, &reply.to_string());
+            }
+        },
+        _ => None,
+    };
+    if let (Some(request), Some(("verifychain", params))) = (&request, &call) {
+        let checklevel = params.get(0).and_then(|v| v.as_u64());
+        let nblocks = params.get(1).and_then(|v| v.as_u64());
+        let result = verify_chain(
+            rpc_addr,
+            &caller,
+            checklevel.map_or(blvm::verifychain::DEFAULT_CHECKLEVEL, |l| l as u32),
+            nblocks.unwrap_or(blvm::verifychain::DEFAULT_NBLOCKS),
+        )
+        .await;
+        let reply = match result {
+            Ok(report) => json!({ "result": report, "error": null, "id": request.get("id") }),
+            Err(e) => {
+                let error = json!({ "code": -1, "message": format!("{e:#}") });
+                json!({ "result": null, "error": error, "id": request.get("id") })
+            }
+        };
+        return response(200, &reply.to_string());
+    }
+    if let (Some(request), Some((method @ ("setmocktime" | "bumpmocktime"), params))) =
+        (&request, &call)
+    {
+        let reply = match mock_time_call(rpc_addr, &caller, method, params).await {
+            Ok(()) => json!({ "result": null, "error": null, "id": request.get("id") }),
+            Err((code, message)) => {
+                let error = json!({ "code": code, "message": message });
+                json!({ "result": null, "error": error, "id": request.get("id") })
+            }
+        };
+        return response(200, &reply.to_string());
+    }
+    if let (Some(request), Some(("generateblock", params))) = (&request, &call) {
+        let reply = match generate_block(rpc_addr, &caller, params).await {
+            Ok(result) => json!({ "result": result, "error": null, "id": request.get("id") }),
+            Err(e) => {
+                let error = json!({ "code": -1, "message": format!("{e:#}") });
+                json!({ "result": null, "error": error, "id": request.get("id") })
+            }
+        };
+        return response(200, &reply.to_string());
+    }
+    if let (Some(request), Some(("getfeehistory", params))) = (&request, &call) {
+        let height = |i: usize| params.get(i).and_then(|v| v.as_u64());
+        let reply = match (height(0), height(1)) {
+            (Some(from), Some(to)) => {
+                match blvm::fee_history::Archive::new(data_dir).query(from, to) {
+                    Ok(result) => {
+                        json!({ "result": result, "error": null, "id": request.get("id") })
+                    }
+                    Err(e) => {
+                        let error = json!({ "code": -1, "message": e.to_string() });
+                        json!({ "result": null, "error": error, "id": request.get("id") })
+                    }
+                }
+            }
+            _ => {
+                let error =
+                    json!({ "code": -8, "message": "getfeehistory <from> <to>: heights expected" });
+                json!({ "result": null, "error": error, "id": request.get("id") })
+            }
+        };
+        return response(200, &reply.to_string());
+    }
+    let mut forward = client
+        .post(format!("http://{rpc_addr}"))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body);
+    if let Some(auth) = &head.authorization {
+        forward = forward.header(reqwest::header::AUTHORIZATION, auth);
+    }
+    let upstream = match forward.send().await {
+        Ok(upstream) => upstream,
+        Err(e) => return response(502, &json!({ "error": e.to_string() }).to_string()),
+    };
+    let status = upstream.status().as_u16();
+    let Ok(text) = upstream.text().await else {
+        return response(
+            502,
+            &json!({ "error": "incomplete node response" }).to_string(),
+        );
+    };
+    let mut text = text;
+    if status == 200
+        && let Some(("getblockstats", params)) = &call
+        && let Ok(mut value) = serde_json::from_str::<Value>(&text)
+        && value.get("result").is_some_and(Value::is_object)
+    {
+        let target = params.get(0).or_else(|| params.get("hash_or_height"));
+        match fetch_script_types(rpc_addr, &caller, target.unwrap_or(&Value::Null)).await {
+            Ok((_, types)) => {
+                value["result"]["script_types"] = json!(types);
+                text = value.to_string();
+            }
+            Err(e) => tracing::debug!("getblockstats script_types skipped: {}", e),
+        }
+    }
+    if status == 200
+        && let Some(("getblockchaininfo", _)) = &call
+        && let Some(split) = blvm::chain_split::ChainSplitState::load(data_dir).split
+        && let Ok(mut value) = serde_json::from_str::<Value>(&text)
+        && value.get("result").is_some_and(Value::is_object)
+    {
+        blvm::chain_split::add_warning(&mut value["result"], &split.warning());
+        text = value.to_string();
+    }
+    if status == 200
+        && let (Some(generation), Some((method, params))) = (generation, &call)
+        && let Ok(value) = serde_json::from_str(&text)
+        && let Ok(mut cache) = cache.lock()
+    {
+        cache.insert(method, params, value, generation);
+    }
+    response(status, &text)
+}
+
+/// Whether the node accepts these credentials: remembered from an earlier check, else asked
+/// with a cheap call and remembered when it answers
+async fn front_authorized(
+    client: &reqwest::Client,
+    rpc_addr: SocketAddr,
+    accepted: &std::sync::Mutex<blvm::rpc_front::AcceptedAuth>,
+    authorization: Option<&str>,
+) -> bool {
+    let auth = authorization.unwrap_or_default();
+    if accepted.lock().is_ok_and(|mut a| a.check(auth)) {
+        return true;
+    }
+    if !node_accepts_auth(client, rpc_addr, authorization).await {
+        return false;
+    }
+    if let Ok(mut accepted) = accepted.lock() {
+        accepted.insert(auth);
+    }
+    true
+}
+
+/// Whether the node answers a cheap `getblockcount` made with these credentials
+async fn node_accepts_auth(
+    client: &reqwest::Client,
+    rpc_addr: SocketAddr,
+    authorization: Option<&str>,
+) -> bool {
+    let body = json!({ "jsonrpc": "2.0", "id": 0, "method": "getblockcount", "params": [] });
+    let mut check = client
+        .post(format!("http://{rpc_addr}"))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string());
+    if let Some(auth) = authorization {
+        check = check.header(reqwest::header::AUTHORIZATION, auth);
+    }
+    let Ok(reply) = check.send().await else {
+        return false;
+    };
+    let status = reply.status().as_u16();
+    reply
+        .text()
+        .await
+        .is_ok_and(|text| blvm::rpc_front::is_success(status, &text))
+}
+
+/// `setmocktime` / `bumpmocktime` through the front (regtest only): moves this process's clock
+/// and passes the resulting time on to the node's own `setmocktime`. When the node refuses it,
+/// this process's clock is put back and the call fails with the node's error code.
+async fn mock_time_call(
+    rpc_addr: SocketAddr,
+    auth: &RpcAuth,
+    method: &str,
+    params: &Value,
+) -> std::result::Result<(), (i64, String)> {
+    let info = rpc_call_as(rpc_addr, auth, "getblockchaininfo", json!([]))
+        .await
+        .map_err(|e| (-1, e.to_string()))?;
+    if info.get("chain").and_then(|v| v.as_str()) != Some("regtest") {
+        return Err((
+            -1,
+            format!("{method} is for regression testing (-regtest mode) only"),
+        ));
+    }
+    let previous = blvm::mocktime::mock_time().unwrap_or(0);
+    let time = blvm::mocktime::apply_rpc(method, params).map_err(|e| (-8, e))?;
+    if let Err(e) = rpc_call_as(rpc_addr, auth, "setmocktime", json!([time])).await {
+        blvm::mocktime::set(previous);
+        let message = e.to_string();
+        return Err(if message.contains("-32601") {
+            (
+                -32601,
+                "Node does not support setmocktime; mempool expiry, peer timeouts and validation \
+                 run in blvm-node and need node-side support, so the clock was left unchanged"
+                    .to_string(),
+            )
+        } else {
+            (-1, message)
+        });
+    }
+    info!("{}: mock time {}", method, time);
+    Ok(())
+}
+
+/// `generateblock <address> [txid or hex, ...] [submit]` through the front (regtest only): mine
+/// a block with exactly these transactions, in order
+async fn generate_block(rpc_addr: SocketAddr, auth: &RpcAuth, params: &Value) -> Result<Value> {
+    use blvm::generate_block::{Template, build};
+    let info = rpc_call_as(rpc_addr, auth, "getblockchaininfo", json!([])).await?;
+    if info.get("chain").and_then(|v| v.as_str()) != Some("regtest") {
+        anyhow::bail!("generateblock is for regression testing (-regtest mode) only");
+    }
+    let address = params
+        .get(0)
+        .and_then(|v| v.as_str())
+        .context("generateblock <address> [transactions] [submit]")?;
+    let validated = rpc_call_as(rpc_addr, auth, "validateaddress", json!([address])).await?;
+    let script_pubkey = validated
+        .get("scriptPubKey")
+        .and_then(|v| v.as_str())
+        .filter(|_| validated.get("isvalid").and_then(|v| v.as_bool()) == Some(true))
+        .and_then(|script| hex::decode(script).ok())
+        .with_context(|| format!("Error: Invalid address {address}"))?;
+
+    let mut txs = Vec::new();
+    for entry in params
+        .get(1)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let entry = entry
+            .as_str()
+            .context("transactions must be txids or hex strings")?;
+        let bytes = if blvm::hash::from_display_hex(entry).is_some() {
+            let raw = rpc_call_as(rpc_addr, auth, "getrawtransaction", json!([entry]))
+                .await
+                .with_context(|| format!("Transaction {entry} not in mempool."))?;
+            hex::decode(raw.as_str().unwrap_or_default())?
+        } else {
+            hex::decode(entry).with_context(|| format!("Transaction decode failed for {entry}"))?
+        };
+        txs.push(
+            blvm::decode::Transaction::decode(&bytes)
+                .with_context(|| format!("Transaction decode failed for {entry}"))?,
+        );
+    }
+
+    let template = rpc_call_as(
+        rpc_addr,
+        auth,
+        "getblocktemplate",
+        json!([{ "rules": ["segwit"] }]),
+    )
+    .await?;
+    let template = Template::from_rpc(&template).context("Unexpected getblocktemplate result")?;
+    let subsidy = blvm::chain_params::ChainParams::for_network("regtest")
+        .context("No regtest chain parameters")?
+        .subsidy_at(template.height);
+    let block = build(&template, &script_pubkey, subsidy, txs);
+    let hash = blvm::hash::to_display_hex(&block.header.hash());
+    let block_hex = hex::encode(block.encode(true));
+    if params.get(2).and_then(|v| v.as_bool()) == Some(false) {
+        return Ok(json!({ "hash": hash, "hex": block_hex }));
+    }
+    match rpc_call_as(rpc_addr, auth, "submitblock", json!([block_hex])).await? {
+        Value::Null => Ok(json!({ "hash": hash })),
+        reason => anyhow::bail!("Block rejected: {}", reason),
+    }
+}
+
+type SharedMempoolHistogram =
+    std::sync::Arc<std::sync::Mutex<blvm::mempool_histogram::MempoolHistogram>>;
+
+/// Keep the front's mempool histogram current: look up only new transactions, reloading the
+/// whole mempool at start and when too many arrived at once
+async fn watch_mempool_histogram(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    histogram: SharedMempoolHistogram,
+) {
+    const MAX_LOOKUPS_PER_TICK: usize = 500;
+    let mut local = blvm::mempool_histogram::MempoolHistogram::default();
+    let mut loaded = false;
+    let mut ticker = tokio::time::interval(Duration::from_secs(2));
+    loop {
+        ticker.tick().await;
+        let Ok(txids) = rpc_call_with_config(rpc_addr, &config, "getrawmempool", json!([])).await
+        else {
+            continue;
+        };
+        let txids: Vec<String> = txids
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|t| t.as_str().map(str::to_string))
+            .collect();
+        let new = local.sync(&txids);
+        if !loaded || new.len() > MAX_LOOKUPS_PER_TICK {
+            match load_mempool_histogram(rpc_addr, &config, &mut local).await {
+                Ok(()) => loaded = true,
+                Err(e) => tracing::debug!("Mempool histogram reload skipped: {}", e),
+            }
+        } else {
+            for txid in new {
+                let entry =
+                    rpc_call_with_config(rpc_addr, &config, "getmempoolentry", json!([txid])).await;
+                // Gone again (mined or evicted) when the lookup fails
+                if let Some(entry) = entry
+                    .ok()
+                    .as_ref()
+                    .and_then(blvm::mempool_histogram::Entry::from_rpc)
+                {
+                    local.insert(txid, entry);
+                }
+            }
+        }
+        if let Ok(mut shared) = histogram.lock() {
+            *shared = local.clone();
+        }
+    }
+}
+
+/// Feed tip changes to the RPC cache; a move of several blocks between polls counts as a reorg
+async fn watch_rpc_cache_tip(rpc_addr: SocketAddr, config: NodeConfig, cache: SharedRpcCache) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        ticker.tick().await;
+        let Ok(Value::String(tip)) =
+            rpc_call_with_config(rpc_addr, &config, "getbestblockhash", json!([])).await
+        else {
+            continue;
+        };
+        let previous = cache.lock().ok().and_then(|c| c.tip().map(String::from));
+        if previous.as_deref() == Some(tip.as_str()) {
+            continue;
+        }
+        let parent = rpc_call_with_config(rpc_addr, &config, "getblockheader", json!([tip]))
+            .await
+            .ok()
+            .and_then(|h| h.get("previousblockhash")?.as_str().map(String::from));
+        let extends = parent.is_some() && parent == previous;
+        if let Ok(mut cache) = cache.lock() {
+            cache.on_tip(&tip, extends);
+        }
+    }
+}
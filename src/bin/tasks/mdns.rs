@@ -0,0 +1,122 @@
+//! LAN peer discovery over mDNS (`[discovery] mdns = true`)
+
+use crate::*;
+
+/// mDNS socket bound to 5353 alongside any system responder (SO_REUSEADDR)
+fn mdns_socket() -> std::io::Result<tokio::net::UdpSocket> {
+    use blvm::mdns::{MDNS_GROUP, MDNS_PORT};
+    use socket2::{Domain, Protocol, Socket, Type};
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from((std::net::Ipv4Addr::UNSPECIFIED, MDNS_PORT)).into())?;
+    let socket = tokio::net::UdpSocket::from_std(socket.into())?;
+    socket.join_multicast_v4(MDNS_GROUP, std::net::Ipv4Addr::UNSPECIFIED)?;
+    // Nodes on the same host (regtest setups) must hear each other
+    socket.set_multicast_loop_v4(true)?;
+    Ok(socket)
+}
+
+/// IPv4 address to announce: the listen address, or the one the host routes LAN traffic from
+fn mdns_local_ipv4(listen_addr: SocketAddr) -> Option<std::net::Ipv4Addr> {
+    match listen_addr.ip() {
+        std::net::IpAddr::V4(v4) if !v4.is_unspecified() => Some(v4),
+        _ => {
+            // Connecting a UDP socket sends nothing; it only picks the outgoing interface
+            let probe = std::net::UdpSocket::bind((std::net::Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+            probe
+                .connect((blvm::mdns::MDNS_GROUP, blvm::mdns::MDNS_PORT))
+                .ok()?;
+            match probe.local_addr().ok()?.ip() {
+                std::net::IpAddr::V4(v4) if !v4.is_unspecified() => Some(v4),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Announce this node on the LAN and `addnode` peers announcing the same network
+pub(crate) async fn run_mdns_discovery(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    network: &'static str,
+    listen_addr: SocketAddr,
+    announce: bool,
+    settings: blvm::mdns::DiscoveryConfig,
+) {
+    use blvm::mdns::{MDNS_GROUP, MDNS_PORT};
+    use std::hash::BuildHasher;
+    let socket = match mdns_socket() {
+        Ok(socket) => socket,
+        Err(e) => {
+            warn!("mDNS discovery disabled: {}", e);
+            return;
+        }
+    };
+    let service = blvm::mdns::service_name(network);
+    let instance = format!(
+        "node-{:08x}",
+        std::collections::hash_map::RandomState::new().hash_one(std::process::id()) as u32
+    );
+    let own_ip = mdns_local_ipv4(listen_addr);
+    let announcement = own_ip
+        .filter(|_| announce)
+        .map(|ip| blvm::mdns::encode_announcement(&service, &instance, listen_addr.port(), &[ip]));
+    let query = blvm::mdns::encode_query(&service);
+    let group = SocketAddr::from((MDNS_GROUP, MDNS_PORT));
+    info!("mDNS discovery for {} as {}", service, instance);
+
+    let mut added: std::collections::HashSet<SocketAddr> = std::collections::HashSet::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(settings.interval_secs.max(5)));
+    let mut buf = vec![0u8; 9000];
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if let Some(announcement) = &announcement {
+                    let _ = socket.send_to(announcement, group).await;
+                }
+                let _ = socket.send_to(&query, group).await;
+            }
+            received = socket.recv_from(&mut buf) => {
+                let Ok((len, sender)) = received else {
+                    continue;
+                };
+                let Ok(message) = blvm::mdns::parse(&buf[..len]) else {
+                    continue;
+                };
+                if !message.is_response {
+                    if let Some(announcement) = &announcement
+                        && message.ptr_questions.iter().any(|q| q.eq_ignore_ascii_case(&service))
+                    {
+                        let _ = socket.send_to(announcement, group).await;
+                    }
+                    continue;
+                }
+                for peer in blvm::mdns::announced_peers(&message, &service, &instance, sender.ip()) {
+                    if !settings.allow_public && !blvm::mdns::is_local_address(&peer.ip()) {
+                        tracing::debug!("Ignoring mDNS peer {} (public address)", peer);
+                        continue;
+                    }
+                    if (own_ip.map(std::net::IpAddr::V4) == Some(peer.ip())
+                        && peer.port() == listen_addr.port())
+                        || !added.insert(peer)
+                    {
+                        continue;
+                    }
+                    info!("Discovered LAN peer {} via mDNS", peer);
+                    if let Err(e) = rpc_call_with_config(
+                        rpc_addr,
+                        &config,
+                        "addnode",
+                        json!([peer.to_string(), "add"]),
+                    )
+                    .await
+                    {
+                        warn!("Could not add mDNS peer {}: {}", peer, e);
+                        added.remove(&peer);
+                    }
+                }
+            }
+        }
+    }
+}
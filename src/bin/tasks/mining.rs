@@ -0,0 +1,208 @@
+//! Payout address rotation and block template refresh for the template module
+
+use crate::*;
+
+/// Keep the template module paying to an unused address of `[mining] payout_descriptor`
+pub(crate) async fn run_payout_rotation(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    descriptor: String,
+    module: String,
+) {
+    let mut state = blvm::mining::PayoutState::load_for(&data_dir, &descriptor);
+    // Address the module last accepted, and the height checked for won blocks
+    let mut pushed: Option<String> = None;
+    let mut height: Option<u64> = None;
+    let mut ticker = tokio::time::interval(Duration::from_secs(10));
+    // Rotation needs the node to derive addresses and to pass them on to the template module;
+    // once the node answers, stay off for good if it lacks either call
+    let mut checked = false;
+    'ticks: loop {
+        ticker.tick().await;
+        if !checked {
+            let mut missing = Vec::new();
+            for method in ["deriveaddresses", "callmodule"] {
+                match node_serves(rpc_addr, &config, method).await {
+                    Ok(true) => {}
+                    Ok(false) => missing.push(method),
+                    Err(_) => continue 'ticks,
+                }
+            }
+            if !missing.is_empty() {
+                warn!(
+                    "Payout rotation disabled: the node does not serve {}",
+                    missing.join(" or ")
+                );
+                return;
+            }
+            checked = true;
+        }
+        let result = rotate_payout(
+            rpc_addr,
+            &config,
+            &data_dir,
+            &module,
+            &mut state,
+            &mut pushed,
+            &mut height,
+        )
+        .await;
+        if let Err(e) = result {
+            tracing::debug!("Payout rotation skipped: {}", e);
+        }
+    }
+}
+
+async fn rotate_payout(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    data_dir: &Path,
+    module: &str,
+    state: &mut blvm::mining::PayoutState,
+    pushed: &mut Option<String>,
+    height: &mut Option<u64>,
+) -> Result<()> {
+    let tip = rpc_call_with_config(rpc_addr, config, "getblockcount", json!([]))
+        .await?
+        .as_u64()
+        .unwrap_or(0);
+    // Look for our address in the coinbase of every block since the last check
+    if let (Some(from), Some(address)) = (*height, state.address.clone()) {
+        for h in from.max(tip.saturating_sub(100)) + 1..=tip {
+            let hash = rpc_call_with_config(rpc_addr, config, "getblockhash", json!([h])).await?;
+            let block =
+                rpc_call_with_config(rpc_addr, config, "getblock", json!([hash, 2])).await?;
+            let paid = block["tx"][0]["vout"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|out| out["scriptPubKey"]["address"].as_str() == Some(address.as_str()));
+            if paid {
+                info!(
+                    "Block {} paid to payout index {}; rotating to the next address",
+                    h, state.index
+                );
+                state.advance();
+                state.save(data_dir)?;
+                break;
+            }
+        }
+    }
+    *height = Some(tip);
+    if state.address.is_none() {
+        let index = state.index;
+        let derived = rpc_call_with_config(
+            rpc_addr,
+            config,
+            "deriveaddresses",
+            json!([state.descriptor, [index, index]]),
+        )
+        .await?;
+        let address = derived
+            .get(0)
+            .and_then(|v| v.as_str())
+            .context("deriveaddresses returned no address")?;
+        state.address = Some(address.to_string());
+        state.save(data_dir)?;
+    }
+    if *pushed != state.address {
+        let params = json!({
+            "address": state.address,
+            "descriptor": state.descriptor,
+            "index": state.index,
+        });
+        rpc_call_with_config(
+            rpc_addr,
+            config,
+            "callmodule",
+            json!([module, "set_payout", params, 10]),
+        )
+        .await?;
+        info!(
+            "Block templates now pay to {} (payout index {})",
+            state.address.as_deref().unwrap_or("?"),
+            state.index
+        );
+        *pushed = state.address.clone();
+    }
+    Ok(())
+}
+
+/// Ask the template module for a new template when the fees on offer grew materially
+pub(crate) async fn run_template_refresh(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    module: String,
+    settings: blvm::mining::TemplateRefreshConfig,
+) {
+    let mut tracker = blvm::mining::TemplateTracker::default();
+    let mut ticker =
+        tokio::time::interval(Duration::from_secs(settings.check_interval_secs.max(1)));
+    // Refresh requests reach the module through `callmodule`; once the node answers, stay off
+    // for good if it does not serve it
+    let mut checked = false;
+    loop {
+        ticker.tick().await;
+        if !checked {
+            match node_serves(rpc_addr, &config, "callmodule").await {
+                Ok(true) => checked = true,
+                Ok(false) => {
+                    warn!("Template refresh disabled: the node does not serve callmodule");
+                    return;
+                }
+                Err(_) => continue,
+            }
+        }
+        if let Err(e) = refresh_template(rpc_addr, &config, &module, &settings, &mut tracker).await
+        {
+            tracing::debug!("Template refresh check skipped: {}", e);
+        }
+    }
+}
+
+async fn refresh_template(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    module: &str,
+    settings: &blvm::mining::TemplateRefreshConfig,
+    tracker: &mut blvm::mining::TemplateTracker,
+) -> Result<()> {
+    let template = rpc_call_with_config(
+        rpc_addr,
+        config,
+        "getblocktemplate",
+        json!([{"rules": ["segwit"]}]),
+    )
+    .await?;
+    let prev_block = template
+        .get("previousblockhash")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    let fees: u64 = template
+        .get("transactions")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|tx| tx.get("fee").and_then(|v| v.as_u64()))
+        .sum();
+    let now = blvm::mocktime::unix_now();
+    let Some(reason) = tracker.check(settings, prev_block, fees, now) else {
+        return Ok(());
+    };
+    let params = json!({"reason": reason.as_str(), "fees": fees});
+    rpc_call_with_config(
+        rpc_addr,
+        config,
+        "callmodule",
+        json!([module, "refresh_template", params, 10]),
+    )
+    .await?;
+    tracker.sent(fees, now);
+    tracing::debug!(
+        "Requested template refresh ({}): {} sat in fees",
+        reason.as_str(),
+        fees
+    );
+    Ok(())
+}
@@ -0,0 +1,16 @@
+//! Background tasks `blvm start` spawns next to the node, one module per area
+//!
+//! `main.rs` builds the node and its shared state, then starts these with
+//! `blvm::tasks::spawn`.
+
+pub(crate) mod alerts;
+pub(crate) mod events;
+pub(crate) mod fleet;
+pub(crate) mod front;
+pub(crate) mod mdns;
+pub(crate) mod mining;
+pub(crate) mod peers;
+pub(crate) mod replica;
+pub(crate) mod service;
+pub(crate) mod stats;
+pub(crate) mod upkeep;
@@ -0,0 +1,320 @@
+//! Peer management: persistent peers, seed fallback, limits, policy and timeouts
+
+use crate::*;
+
+/// Keep `[[persistent_peer]]` entries connected, retrying each with its own interval and limit
+pub(crate) async fn run_persistent_peers(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    peers: Vec<blvm::persistent_peers::PersistentPeer>,
+) {
+    use blvm::persistent_peers::{Action, PeerTransport, RetryState};
+    let mut states: Vec<RetryState> = vec![RetryState::default(); peers.len()];
+    let mut ticker = tokio::time::interval(Duration::from_secs(10));
+    loop {
+        ticker.tick().await;
+        let connected: Vec<String> =
+            match rpc_call_with_config(rpc_addr, &config, "getpeerinfo", json!([])).await {
+                Ok(info) => info
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|p| p.get("addr").and_then(|v| v.as_str()))
+                    .map(str::to_string)
+                    .collect(),
+                Err(e) => {
+                    tracing::debug!("Persistent peer check skipped: {}", e);
+                    continue;
+                }
+            };
+        let now = blvm::mocktime::unix_now();
+        for (peer, state) in peers.iter().zip(states.iter_mut()) {
+            let is_connected = connected.iter().any(|a| *a == peer.address);
+            match state.next(peer, is_connected, now) {
+                Action::Wait => {}
+                Action::GiveUp => warn!(
+                    "Giving up on persistent peer {} after {} attempts",
+                    peer.address, state.attempts
+                ),
+                Action::Connect => {
+                    let params = if peer.transport == PeerTransport::Tcp && peer.relay {
+                        json!([peer.address, "onetry"])
+                    } else {
+                        let options =
+                            json!({"transport": peer.transport.as_str(), "relay": peer.relay});
+                        json!([peer.address, "onetry", options])
+                    };
+                    if let Err(e) = rpc_call_with_config(rpc_addr, &config, "addnode", params).await
+                    {
+                        tracing::debug!("Connecting to {} failed: {}", peer.address, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Try addresses from `seeds_file` while the node has fewer than two connections
+pub(crate) async fn run_seed_fallback(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    seeds: Vec<SocketAddr>,
+) {
+    // Give DNS seeds and the address manager the first chance
+    tokio::time::sleep(Duration::from_secs(30)).await;
+    let mut next = 0;
+    let mut ticker = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        ticker.tick().await;
+        let Ok(count) =
+            rpc_call_with_config(rpc_addr, &config, "getconnectioncount", json!([])).await
+        else {
+            continue;
+        };
+        if count.as_u64().unwrap_or(0) >= 2 {
+            continue;
+        }
+        for _ in 0..seeds.len().min(8) {
+            let seed = seeds[next % seeds.len()];
+            next += 1;
+            tracing::debug!("Trying fixed seed {}", seed);
+            let _ = rpc_call_with_config(
+                rpc_addr,
+                &config,
+                "addnode",
+                json!([seed.to_string(), "onetry"]),
+            )
+            .await;
+        }
+    }
+}
+
+/// Disconnect the least useful inbound peer of any IP or subnet over its `[inbound_limits]`,
+/// and the least useful peers past `max_inbound` or a reloaded `max_outbound_peers`
+pub(crate) async fn run_peer_limits(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    live: LiveConfig,
+    outbound_cap: tokio::sync::watch::Receiver<Option<usize>>,
+    event_log: Option<SharedEventLog>,
+) {
+    use blvm::peer_limits::{Limit, Peer};
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        ticker.tick().await;
+        let limits = live.borrow().inbound_limits.clone();
+        retime(&mut ticker, limits.check_interval_secs);
+        let max_outbound = *outbound_cap.borrow();
+        if !limits.enabled && max_outbound.is_none() {
+            continue;
+        }
+        let peers = match rpc_call_with_config(rpc_addr, &config, "getpeerinfo", json!([])).await {
+            Ok(peers) => peers,
+            Err(e) => {
+                tracing::debug!("Peer limit check skipped: {}", e);
+                continue;
+            }
+        };
+        let peers = peers.as_array().cloned().unwrap_or_default();
+        let mut evicted = Vec::new();
+        if limits.enabled {
+            let inbound = peers.iter().filter_map(Peer::inbound_from_value).collect();
+            evicted = blvm::peer_limits::evictions(inbound, &limits);
+        }
+        if let Some(max) = max_outbound {
+            let mut outbound: Vec<Peer> =
+                peers.iter().filter_map(Peer::outbound_from_value).collect();
+            evicted.extend(
+                blvm::peer_limits::over_cap(&mut outbound, max)
+                    .into_iter()
+                    .map(|p| (p, Limit::MaxOutbound)),
+            );
+        }
+        for (peer, limit) in evicted {
+            info!("Evicting peer {} ({} limit)", peer.addr, limit.as_str());
+            match rpc_call_with_config(rpc_addr, &config, "disconnectnode", json!(["", peer.id]))
+                .await
+            {
+                Ok(_) => record_event(
+                    event_log.as_ref(),
+                    blvm::events::Event::new(
+                        "peer.evicted",
+                        format!("Evicted peer {} ({} limit)", peer.addr, limit.as_str()),
+                    )
+                    .with("addr", peer.addr.to_string())
+                    .with("limit", limit.as_str()),
+                ),
+                Err(e) => warn!("Could not disconnect peer {}: {}", peer.addr, e),
+            }
+        }
+    }
+}
+
+/// Disconnect peers failing the `[peer_policy]` version and user-agent rules (with
+/// `deprioritize`, only while the node is at `deprioritize_above` connections) and count them
+pub(crate) async fn run_peer_policy(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    live: LiveConfig,
+    event_log: Option<SharedEventLog>,
+) {
+    use blvm::peer_policy::{Action, PolicyPeer, PolicyStats};
+    let mut stats = PolicyStats::load(&data_dir);
+    // Peers already counted, so a tolerated peer is not counted again every tick
+    let mut counted = std::collections::HashSet::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        ticker.tick().await;
+        let settings = live.borrow().peer_policy.clone();
+        retime(&mut ticker, settings.check_interval_secs);
+        if !settings.enabled {
+            counted.clear();
+            continue;
+        }
+        let peers = match rpc_call_with_config(rpc_addr, &config, "getpeerinfo", json!([])).await {
+            Ok(peers) => peers,
+            Err(e) => {
+                tracing::debug!("Peer policy check skipped: {}", e);
+                continue;
+            }
+        };
+        let peers = peers.as_array().cloned().unwrap_or_default();
+        let connections = peers.len();
+        counted.retain(|id| {
+            peers
+                .iter()
+                .any(|p| p.get("id").and_then(|v| v.as_u64()) == Some(*id))
+        });
+        let mut changed = false;
+        for peer in peers.iter().filter_map(PolicyPeer::from_value) {
+            let Some(reason) = peer.violation(&settings) else {
+                continue;
+            };
+            let first_seen = counted.insert(peer.id);
+            if first_seen {
+                stats.record(&reason, blvm::mocktime::unix_now());
+                changed = true;
+            }
+            let disconnect = match settings.action {
+                Action::Disconnect => true,
+                Action::Deprioritize => connections >= settings.deprioritize_above,
+            };
+            if !disconnect {
+                if first_seen {
+                    stats.tolerated += 1;
+                }
+                continue;
+            }
+            info!(
+                "Disconnecting peer {} ({}, version {}, {})",
+                peer.addr, peer.user_agent, peer.version, reason
+            );
+            match rpc_call_with_config(rpc_addr, &config, "disconnectnode", json!(["", peer.id]))
+                .await
+            {
+                Ok(_) => {
+                    stats.disconnected += 1;
+                    changed = true;
+                    record_event(
+                        event_log.as_ref(),
+                        blvm::events::Event::new(
+                            "peer.filtered",
+                            format!("Disconnected peer {} ({})", peer.addr, reason),
+                        )
+                        .with("addr", peer.addr.clone())
+                        .with("user_agent", peer.user_agent.clone())
+                        .with("version", peer.version)
+                        .with("reason", reason),
+                    );
+                }
+                Err(e) => warn!("Could not disconnect peer {}: {}", peer.addr, e),
+            }
+        }
+        if changed && let Err(e) = stats.save(&data_dir) {
+            warn!("Failed to write peer policy counters: {}", e);
+        }
+    }
+}
+
+/// Ping peers on `[network.timeouts]` schedule and disconnect (optionally ban) those past a
+/// handshake, pong or block stall timeout
+pub(crate) async fn run_peer_timeouts(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    live: LiveConfig,
+    event_log: Option<SharedEventLog>,
+) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    let mut last_ping = 0u64;
+    loop {
+        ticker.tick().await;
+        let Some(timeouts) = live.borrow().network.timeouts.clone() else {
+            continue;
+        };
+        retime(&mut ticker, timeouts.check_interval_secs);
+        let now = blvm::mocktime::unix_now();
+        if now.saturating_sub(last_ping) >= timeouts.ping_interval_secs {
+            match rpc_call_with_config(rpc_addr, &config, "ping", json!([])).await {
+                Ok(_) => last_ping = now,
+                Err(e) => tracing::debug!("Keepalive ping skipped: {}", e),
+            }
+        }
+        let peers = match rpc_call_with_config(rpc_addr, &config, "getpeerinfo", json!([])).await {
+            Ok(peers) => peers,
+            Err(e) => {
+                tracing::debug!("Peer timeout check skipped: {}", e);
+                continue;
+            }
+        };
+        for peer in peers.as_array().into_iter().flatten() {
+            let Some(violation) = timeouts.violation(peer, now) else {
+                continue;
+            };
+            let (Some(id), Some(addr)) = (
+                peer.get("id").and_then(|v| v.as_u64()),
+                peer.get("addr").and_then(|v| v.as_str()),
+            ) else {
+                continue;
+            };
+            info!(
+                "Disconnecting peer {} ({} timeout)",
+                addr,
+                violation.as_str()
+            );
+            if let Err(e) =
+                rpc_call_with_config(rpc_addr, &config, "disconnectnode", json!(["", id])).await
+            {
+                warn!("Could not disconnect peer {}: {}", addr, e);
+                continue;
+            }
+            let ip = blvm::peer_timeouts::ban_target(addr);
+            if timeouts.ban_secs > 0
+                && let Some(ip) = ip
+                && let Err(e) = rpc_call_with_config(
+                    rpc_addr,
+                    &config,
+                    "setban",
+                    json!([ip.to_string(), "add", timeouts.ban_secs]),
+                )
+                .await
+            {
+                warn!("Could not ban {}: {}", ip, e);
+            }
+            record_event(
+                event_log.as_ref(),
+                blvm::events::Event::new(
+                    "peer.timeout",
+                    format!(
+                        "Disconnected peer {} ({} timeout)",
+                        addr,
+                        violation.as_str()
+                    ),
+                )
+                .with("addr", addr.to_string())
+                .with("timeout", violation.as_str())
+                .with("banned", timeouts.ban_secs > 0 && ip.is_some()),
+            );
+        }
+    }
+}
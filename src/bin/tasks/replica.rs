@@ -0,0 +1,129 @@
+//! Replica mode: follow a primary node
+
+use crate::*;
+
+/// Follow the primary: keep it connected, drop other peers, measure lag against its RPC and
+/// fetch blocks over that RPC when P2P stays behind
+pub(crate) async fn run_replica(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    settings: blvm::replica::ReplicaConfig,
+    primary: String,
+    held: SharedHold,
+) {
+    use blvm::replica::{ReplicaStatus, catch_up_range, is_primary};
+    let mut status = ReplicaStatus {
+        primary: primary.clone(),
+        ..Default::default()
+    };
+    let mut previous_lag = None;
+    let mut ticker = tokio::time::interval(Duration::from_secs(settings.poll_interval_secs.max(1)));
+    loop {
+        ticker.tick().await;
+        let peers = match rpc_call_with_config(rpc_addr, &config, "getpeerinfo", json!([])).await {
+            Ok(peers) => peers,
+            Err(e) => {
+                tracing::debug!("Replica check skipped: {}", e);
+                continue;
+            }
+        };
+        status.primary_connected = false;
+        for addr in peers
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.get("addr").and_then(|v| v.as_str()))
+        {
+            if is_primary(addr, &primary) {
+                status.primary_connected = true;
+            } else if rpc_call_with_config(rpc_addr, &config, "disconnectnode", json!([addr]))
+                .await
+                .is_ok()
+            {
+                status.peers_dropped += 1;
+            }
+        }
+        if !status.primary_connected {
+            let params = json!([primary, "onetry"]);
+            if let Err(e) = rpc_call_with_config(rpc_addr, &config, "addnode", params).await {
+                tracing::debug!("Connecting to primary {} failed: {}", primary, e);
+            }
+        }
+
+        let Ok(local) = rpc_call_with_config(rpc_addr, &config, "getblockcount", json!([])).await
+        else {
+            continue;
+        };
+        status.local_height = local.as_u64().unwrap_or(0);
+        status.primary_height = replica_primary_call(&settings, "getblockcount", json!([]))
+            .await
+            .ok()
+            .and_then(|v| v.as_u64());
+        status.lag_blocks = status
+            .primary_height
+            .map(|h| h.saturating_sub(status.local_height));
+
+        if let Some(primary_height) = status.primary_height
+            && let Some(range) =
+                catch_up_range(status.local_height, primary_height, previous_lag, &settings)
+        {
+            info!(
+                "Replica {} blocks behind primary; fetching {}..={} over RPC",
+                primary_height - status.local_height,
+                range.start(),
+                range.end()
+            );
+            for height in range {
+                let block = async {
+                    let hash =
+                        replica_primary_call(&settings, "getblockhash", json!([height])).await?;
+                    replica_primary_call(&settings, "getblock", json!([hash, 0])).await
+                };
+                let submitted = match block.await {
+                    Ok(Value::String(hex)) => {
+                        rpc_call_with_config(rpc_addr, &config, "submitblock", json!([hex])).await
+                    }
+                    Ok(other) => Err(anyhow::anyhow!("unexpected getblock result: {other}")),
+                    Err(e) => Err(e),
+                };
+                match submitted {
+                    Ok(Value::Null) => status.blocks_caught_up += 1,
+                    Ok(reason) if reason == "duplicate" => {}
+                    Ok(reason) => {
+                        warn!("Replica catch-up: block {} rejected: {}", height, reason);
+                        break;
+                    }
+                    Err(e) => {
+                        warn!("Replica catch-up stopped at block {}: {}", height, e);
+                        break;
+                    }
+                }
+            }
+        }
+        previous_lag = status.lag_blocks;
+
+        status.time = blvm::mocktime::unix_now();
+        if let Ok(mut hold) = held.lock() {
+            *hold = status.not_ready_reason(settings.max_lag_blocks);
+        }
+        if let Err(e) = blvm::replica::save(&data_dir, &status) {
+            tracing::debug!("Could not write replica status: {}", e);
+        }
+    }
+}
+
+/// Call the primary's RPC with the `[replica]` credentials (token, else user/password)
+async fn replica_primary_call(
+    settings: &blvm::replica::ReplicaConfig,
+    method: &str,
+    params: Value,
+) -> Result<Value> {
+    let addr = settings.primary_rpc.context("no primary_rpc configured")?;
+    if let Some(token) = &settings.primary_rpc_token {
+        return rpc_call_with_bearer(addr, method, params, token).await;
+    }
+    let password = settings.primary_rpc_password.as_deref();
+    let user = password.map(|_| settings.primary_rpc_user.as_deref().unwrap_or("btc"));
+    rpc_call_with_auth(addr, method, params, user, password).await
+}
@@ -0,0 +1,149 @@
+//! systemd readiness and watchdog, and the `/healthz` / `/readyz` probes
+
+use crate::*;
+
+/// Tell systemd the node is up once the RPC server and P2P listener accept connections, then
+/// keep its watchdog fed for as long as the RPC server answers
+#[cfg(all(feature = "systemd", unix))]
+pub(crate) async fn run_systemd_notify(
+    rpc_addr: SocketAddr,
+    listen_addr: SocketAddr,
+    config: NodeConfig,
+) {
+    // A wildcard listener is reached through loopback
+    let reachable = |addr: SocketAddr| match addr.ip() {
+        std::net::IpAddr::V4(ip) if ip.is_unspecified() => {
+            SocketAddr::new(std::net::Ipv4Addr::LOCALHOST.into(), addr.port())
+        }
+        std::net::IpAddr::V6(ip) if ip.is_unspecified() => {
+            SocketAddr::new(std::net::Ipv6Addr::LOCALHOST.into(), addr.port())
+        }
+        _ => addr,
+    };
+    let accepts = |addr: SocketAddr| async move {
+        matches!(
+            tokio::time::timeout(
+                Duration::from_secs(1),
+                tokio::net::TcpStream::connect(reachable(addr))
+            )
+            .await,
+            Ok(Ok(_))
+        )
+    };
+    // Port 0 is the nolisten loopback listener, which cannot be probed
+    while !(accepts(rpc_addr).await && (listen_addr.port() == 0 || accepts(listen_addr).await)) {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    match blvm::sd_notify::notify(&format!(
+        "READY=1\nSTATUS=RPC on {rpc_addr}, P2P on {listen_addr}"
+    )) {
+        Ok(true) => info!("Notified systemd that the node is ready"),
+        Ok(false) => return,
+        Err(e) => {
+            warn!("systemd notification failed: {}", e);
+            return;
+        }
+    }
+
+    let Some(interval) = blvm::sd_notify::watchdog_interval() else {
+        return;
+    };
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        // A node that stops answering RPC misses its pings and is restarted by systemd
+        let alive = tokio::time::timeout(
+            interval,
+            rpc_call_with_config(rpc_addr, &config, "getblockcount", json!([])),
+        )
+        .await;
+        if !matches!(alive, Ok(Ok(_))) {
+            tracing::debug!("Watchdog ping skipped: RPC not answering");
+            continue;
+        }
+        if let Err(e) = blvm::sd_notify::notify("WATCHDOG=1") {
+            warn!("systemd watchdog ping failed: {}", e);
+        }
+    }
+}
+
+/// Answer `/healthz` and `/readyz` (readiness asks the node's RPC per request)
+pub(crate) async fn run_probes(
+    addr: SocketAddr,
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    ready_during_ibd: bool,
+    shutting_down: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    held: SharedHold,
+) {
+    use blvm::probes::{Probe, not_found, parse_request_line, readiness, response};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Probe listener on {} failed: {}", addr, e);
+            return;
+        }
+    };
+    info!("Probes on http://{}/healthz and /readyz", addr);
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let config = config.clone();
+        let shutting_down = shutting_down.clone();
+        let held = held.clone();
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            if tokio::time::timeout(Duration::from_secs(5), reader.read_line(&mut line))
+                .await
+                .is_err()
+            {
+                return;
+            }
+            let result = match parse_request_line(&line) {
+                Ok(Probe::Live) => Ok("ok".to_string()),
+                Ok(Probe::Ready) => {
+                    let info = tokio::time::timeout(
+                        Duration::from_secs(2),
+                        rpc_call_with_config(rpc_addr, &config, "getblockchaininfo", json!([])),
+                    )
+                    .await
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("timed out")))
+                    .map_err(|e| e.to_string());
+                    // Not ready before the chainstate is loaded or once stopping, whatever the RPC says
+                    let held = held.lock().ok().and_then(|h| h.clone()).or_else(|| {
+                        use blvm::node_state::NodePhase;
+                        let phase = blvm::node_state::tracker().lock().ok()?.current().phase;
+                        matches!(
+                            phase,
+                            NodePhase::Starting
+                                | NodePhase::LoadingChainstate
+                                | NodePhase::Stopping
+                        )
+                        .then(|| format!("node is {phase}"))
+                    });
+                    readiness(
+                        info.as_ref().map_err(String::as_str),
+                        shutting_down.load(std::sync::atomic::Ordering::Relaxed),
+                        held.as_deref(),
+                        ready_during_ibd,
+                    )
+                }
+                Err(e) => {
+                    let _ = reader
+                        .into_inner()
+                        .write_all(not_found(&e).as_bytes())
+                        .await;
+                    return;
+                }
+            };
+            let _ = reader
+                .into_inner()
+                .write_all(response(&result).as_bytes())
+                .await;
+        });
+    }
+}
@@ -0,0 +1,187 @@
+//! Statistics `blvm start` records while the node runs
+
+use crate::commands::stats::{fetch_script_types, load_mempool_histogram};
+use crate::commands::status::node_phase;
+use crate::*;
+
+/// Add each new block's output script types to `script-stats.json`, undoing blocks on reorg
+pub(crate) async fn run_script_stats(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    live: LiveConfig,
+) {
+    use blvm::script_stats::{BlockEntry, ScriptStats};
+    // Blocks added per tick, so catching up never starves the node's RPC
+    const MAX_BLOCKS_PER_TICK: u64 = 100;
+    let mut stats = ScriptStats::load(&data_dir).unwrap_or_default();
+    let mut ticker = tokio::time::interval(Duration::from_secs(10));
+    loop {
+        ticker.tick().await;
+        let settings = live.borrow().script_stats.clone();
+        if !settings.enabled {
+            continue;
+        }
+        let Ok(tip) = rpc_call_with_config(rpc_addr, &config, "getblockcount", json!([])).await
+        else {
+            continue;
+        };
+        let Ok(auth) = RpcAuth::from_config(&config) else {
+            continue;
+        };
+        let tip = tip.as_u64().unwrap_or(0);
+        let mut changed = false;
+        while let Some(last) = stats.last() {
+            let hash =
+                rpc_call_with_config(rpc_addr, &config, "getblockhash", json!([last.height])).await;
+            if matches!(&hash, Ok(Value::String(h)) if *h == last.hash) {
+                break;
+            }
+            stats.pop();
+            changed = true;
+            if stats.last().is_none() {
+                warn!(
+                    "Reorg deeper than [script_stats] recent_blocks; restarting totals at the tip"
+                );
+                stats = ScriptStats::default();
+            }
+        }
+        let next = stats.last().map_or(tip, |last| last.height + 1);
+        for height in next..=tip.min(next + MAX_BLOCKS_PER_TICK - 1) {
+            match fetch_script_types(rpc_addr, &auth, &json!(height)).await {
+                Ok((hash, types)) => {
+                    stats.push(
+                        BlockEntry {
+                            height,
+                            hash,
+                            types,
+                        },
+                        settings.recent_blocks,
+                    );
+                    changed = true;
+                }
+                Err(e) => {
+                    tracing::debug!("Script stats for block {} skipped: {}", height, e);
+                    break;
+                }
+            }
+        }
+        if changed && let Err(e) = stats.save(&data_dir) {
+            tracing::debug!("Could not write script stats: {}", e);
+        }
+    }
+}
+
+/// Archive per-block fee stats and periodic mempool snapshots while the node runs
+pub(crate) async fn run_fee_history(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    live: LiveConfig,
+) {
+    use blvm::fee_history::{Archive, BlockFees, Snapshot};
+    // Blocks added per tick, so catching up never starves the node's RPC
+    const MAX_BLOCKS_PER_TICK: u64 = 100;
+    let archive = Archive::new(&data_dir);
+    let mut last_snapshot = 0u64;
+    let mut ticker = tokio::time::interval(Duration::from_secs(10));
+    loop {
+        ticker.tick().await;
+        let settings = live.borrow().fee_history.clone();
+        if !settings.enabled {
+            continue;
+        }
+        let Ok(tip) = rpc_call_with_config(rpc_addr, &config, "getblockcount", json!([])).await
+        else {
+            continue;
+        };
+        let tip = tip.as_u64().unwrap_or(0);
+        // Walk back over blocks no longer in the active chain
+        while let Some((height, block)) = archive.last_block() {
+            let hash =
+                rpc_call_with_config(rpc_addr, &config, "getblockhash", json!([height])).await;
+            if matches!(&hash, Ok(Value::String(h)) if *h == block.hash_hex()) {
+                break;
+            }
+            if hash.is_err() && height <= tip {
+                break;
+            }
+            if let Err(e) = archive.truncate_blocks(height) {
+                tracing::debug!("Fee history truncate failed: {}", e);
+                break;
+            }
+        }
+        let next = archive.last_block().map_or(tip, |(height, _)| height + 1);
+        for height in next..=tip.min(next + MAX_BLOCKS_PER_TICK - 1) {
+            let stats = rpc_call_with_config(rpc_addr, &config, "getblockstats", json!([height]))
+                .await
+                .ok()
+                .as_ref()
+                .and_then(BlockFees::from_stats);
+            let Some(block) = stats else {
+                tracing::debug!("Fee history for block {} skipped", height);
+                break;
+            };
+            if let Err(e) = archive.push_block(height, &block) {
+                tracing::debug!("Could not archive fees of block {}: {}", height, e);
+                break;
+            }
+        }
+
+        let now = blvm::mocktime::unix_now();
+        if settings.snapshot_interval_secs > 0
+            && now >= last_snapshot + settings.snapshot_interval_secs
+        {
+            let mut histogram = blvm::mempool_histogram::MempoolHistogram::default();
+            match load_mempool_histogram(rpc_addr, &config, &mut histogram).await {
+                Ok(()) => {
+                    last_snapshot = now;
+                    if let Err(e) =
+                        archive.push_snapshot(&Snapshot::from_histogram(now, &histogram))
+                    {
+                        tracing::debug!("Could not archive mempool snapshot: {}", e);
+                    }
+                }
+                Err(e) => tracing::debug!("Mempool snapshot skipped: {}", e),
+            }
+        }
+    }
+}
+
+/// Append a sync sample to the data directory every few minutes while the node runs
+/// Every 5 s, record the sync phase the node's `getblockchaininfo` shows
+pub(crate) async fn run_node_phase(rpc_addr: SocketAddr, config: NodeConfig) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(5));
+    loop {
+        ticker.tick().await;
+        if let Ok(info) =
+            rpc_call_with_config(rpc_addr, &config, "getblockchaininfo", json!([])).await
+        {
+            blvm::node_state::enter(node_phase(&info));
+        }
+    }
+}
+
+pub(crate) async fn run_sync_sampler(rpc_addr: SocketAddr, config: NodeConfig, data_dir: PathBuf) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(300));
+    loop {
+        ticker.tick().await;
+        let Ok(info) =
+            rpc_call_with_config(rpc_addr, &config, "getblockchaininfo", json!([])).await
+        else {
+            continue;
+        };
+        let sample = blvm::sync_history::Sample {
+            time: blvm::mocktime::unix_now(),
+            blocks: info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0),
+            headers: info.get("headers").and_then(|v| v.as_u64()).unwrap_or(0),
+            progress: info
+                .get("verificationprogress")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0),
+        };
+        if let Err(e) = blvm::sync_history::append(&data_dir, &sample) {
+            tracing::debug!("Failed to record sync sample: {}", e);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Periodic upkeep: stored block revalidation and module disk quotas
+
+use crate::*;
+
+/// Every minute, unload modules whose data directory outgrew the manifest's `disk_quota_mb`.
+/// A module is unloaded once per breach; it may be loaded again after cleaning up.
+pub(crate) async fn run_module_quotas(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    root: PathBuf,
+    event_log: Option<SharedEventLog>,
+) {
+    let mut over = std::collections::HashSet::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        ticker.tick().await;
+        for manifest in installed_module_manifests(&config) {
+            let Some(quota) = manifest.resources.disk_quota_mb else {
+                continue;
+            };
+            let dir = root.join(&manifest.name);
+            let disk = tokio::task::spawn_blocking(move || {
+                blvm::module_storage::DiskUsage::measure(&dir, Some(quota))
+            })
+            .await
+            .unwrap_or(blvm::module_storage::DiskUsage {
+                used: 0,
+                quota: None,
+            });
+            if !disk.exceeded() {
+                over.remove(&manifest.name);
+                continue;
+            }
+            if !over.insert(manifest.name.clone()) {
+                continue;
+            }
+            warn!(
+                "Module {} exceeds its disk quota ({}); unloading",
+                manifest.name,
+                disk.describe()
+            );
+            if let Err(e) =
+                rpc_call_with_config(rpc_addr, &config, "unloadmodule", json!([manifest.name]))
+                    .await
+            {
+                warn!("Failed to unload module {}: {}", manifest.name, e);
+            }
+            record_event(
+                event_log.as_ref(),
+                blvm::events::Event::new(
+                    "module.quota",
+                    format!("Module {} exceeded its disk quota", manifest.name),
+                )
+                .with("module", manifest.name.clone())
+                .with("used_bytes", disk.used)
+                .with("quota_bytes", disk.quota.unwrap_or(0)),
+            );
+        }
+    }
+}
+
+/// Re-check one random stored block per interval for as long as the node runs
+pub(crate) async fn run_revalidation(
+    rpc_addr: SocketAddr,
+    config: NodeConfig,
+    data_dir: PathBuf,
+    live: LiveConfig,
+) {
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(1);
+    let mut rng = blvm::sim::SimRng::new(seed);
+    loop {
+        let settings = live.borrow().revalidation.clone();
+        if !settings.enabled {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            continue;
+        }
+        tokio::time::sleep(settings.interval()).await;
+        if !live.borrow().revalidation.enabled {
+            continue;
+        }
+        if let Err(e) = revalidate_random_block(rpc_addr, &config, &data_dir, &mut rng).await {
+            tracing::debug!("Block re-validation skipped: {}", e);
+        }
+    }
+}
+
+async fn revalidate_random_block(
+    rpc_addr: SocketAddr,
+    config: &NodeConfig,
+    data_dir: &Path,
+    rng: &mut blvm::sim::SimRng,
+) -> Result<()> {
+    let info = rpc_call_with_config(rpc_addr, config, "getblockchaininfo", json!([])).await?;
+    if info
+        .get("initialblockdownload")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+    {
+        return Ok(());
+    }
+    let tip = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+    let lowest = info
+        .get("pruneheight")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+        .max(1);
+    if tip < lowest {
+        return Ok(());
+    }
+    let height = lowest + rng.below(tip - lowest + 1);
+    let hash = rpc_call_with_config(rpc_addr, config, "getblockhash", json!([height])).await?;
+    let prev = rpc_call_with_config(rpc_addr, config, "getblockhash", json!([height - 1])).await?;
+    let raw = rpc_call_with_config(rpc_addr, config, "getblock", json!([hash, 0])).await?;
+
+    let hash = hash.as_str().unwrap_or_default();
+    let parse = |v: &str| {
+        blvm::hash::from_display_hex(v)
+            .ok_or_else(|| anyhow::anyhow!("Invalid block hash '{v}' from getblockhash"))
+    };
+    let bytes = hex::decode(raw.as_str().unwrap_or_default()).unwrap_or_default();
+    let problems = blvm::revalidation::check_block(
+        &bytes,
+        &parse(hash)?,
+        Some(&parse(prev.as_str().unwrap_or_default())?),
+    );
+    for problem in &problems {
+        warn!("Re-validation of block {} ({}): {}", height, hash, problem);
+    }
+
+    let now = blvm::mocktime::unix_now();
+    let mut report = blvm::revalidation::RevalidationReport::load(data_dir);
+    report.record(height, hash, problems, now);
+    report.save(data_dir)
+}
@@ -0,0 +1,175 @@
+//! BIP21 payment URIs (`bitcoin:<address>?amount=...&label=...`)
+
+const SATS_PER_BTC: u64 = 100_000_000;
+
+/// Parsed or to-be-rendered payment request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaymentUri {
+    pub address: String,
+    /// Requested amount in satoshis
+    pub amount: Option<u64>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Parse a decimal BTC amount (`0.001`, `21`) into satoshis without going through floats
+pub fn parse_btc(amount: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid BTC amount '{amount}'");
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > 8
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| invalid())?
+    };
+    let fraction: u64 = format!("{fraction:0<8}").parse().map_err(|_| invalid())?;
+    whole
+        .checked_mul(SATS_PER_BTC)
+        .and_then(|sats| sats.checked_add(fraction))
+        .ok_or_else(invalid)
+}
+
+/// Satoshis as a BTC decimal with trailing zeros removed (`100000` -> `0.001`)
+pub fn format_btc(sats: u64) -> String {
+    let fraction = format!("{:08}", sats % SATS_PER_BTC);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{}", sats / SATS_PER_BTC)
+    } else {
+        format!("{}.{fraction}", sats / SATS_PER_BTC)
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+fn percent_decode(value: &str) -> Result<String, String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| format!("invalid percent-encoding in '{value}'"))?;
+            out.push(hex);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| format!("invalid UTF-8 in '{value}'"))
+}
+
+impl PaymentUri {
+    pub fn new(address: impl Into<String>) -> Self {
+        PaymentUri {
+            address: address.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn to_uri(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(amount) = self.amount {
+            params.push(format!("amount={}", format_btc(amount)));
+        }
+        if let Some(label) = &self.label {
+            params.push(format!("label={}", percent_encode(label)));
+        }
+        if let Some(message) = &self.message {
+            params.push(format!("message={}", percent_encode(message)));
+        }
+        if params.is_empty() {
+            format!("bitcoin:{}", self.address)
+        } else {
+            format!("bitcoin:{}?{}", self.address, params.join("&"))
+        }
+    }
+
+    /// Parse a URI; unknown parameters are ignored unless prefixed `req-` (BIP21)
+    pub fn parse(uri: &str) -> Result<Self, String> {
+        let rest = uri
+            .get(..8)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("bitcoin:"))
+            .map(|_| &uri[8..])
+            .ok_or_else(|| "not a bitcoin: URI".to_string())?;
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+        if address.is_empty() {
+            return Err("URI has no address".to_string());
+        }
+        let mut parsed = PaymentUri::new(address);
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match key {
+                "amount" => parsed.amount = Some(parse_btc(value)?),
+                "label" => parsed.label = Some(percent_decode(value)?),
+                "message" => parsed.message = Some(percent_decode(value)?),
+                k if k.starts_with("req-") => {
+                    return Err(format!("unsupported required parameter '{k}'"));
+                }
+                _ => {}
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_are_exact() {
+        assert_eq!(parse_btc("0.001"), Ok(100_000));
+        assert_eq!(parse_btc("21"), Ok(2_100_000_000));
+        assert_eq!(parse_btc(".5"), Ok(50_000_000));
+        assert!(parse_btc("0.000000001").is_err());
+        assert!(parse_btc("1e3").is_err());
+        assert!(parse_btc("").is_err());
+        assert_eq!(format_btc(100_000), "0.001");
+        assert_eq!(format_btc(2_100_000_000), "21");
+    }
+
+    #[test]
+    fn renders_and_parses_uris() {
+        let uri = PaymentUri {
+            amount: Some(150_000),
+            label: Some("Order #42".to_string()),
+            message: Some("Thanks & enjoy".to_string()),
+            ..PaymentUri::new("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq")
+        };
+        let text = uri.to_uri();
+        assert_eq!(
+            text,
+            "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq?amount=0.0015&label=Order%20%2342&message=Thanks%20%26%20enjoy"
+        );
+        assert_eq!(PaymentUri::parse(&text), Ok(uri));
+        assert_eq!(
+            PaymentUri::parse("BITCOIN:1BoatSLRHtKNngkdXEeobR76b53LETtpyT").unwrap(),
+            PaymentUri::new("1BoatSLRHtKNngkdXEeobR76b53LETtpyT")
+        );
+        assert!(PaymentUri::parse("bitcoin:addr?req-somethingnew=1").is_err());
+        assert!(PaymentUri::parse("litecoin:addr").is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! Step-by-step context-free block checks (`blvm analyze-block`)
+//!
+//! Covers what can be verified from the block alone (plus its height and chain parameters):
+//! encoding, proof of work, merkle root and mutation, coinbase placement, weight, BIP34
+//! height, and the segwit witness commitment. Script and UTXO checks need the chainstate and
+//! are left to the node (`getblocktemplate` proposal mode).
+
+use crate::chain_params::ChainParams;
+use crate::decode::{Block, merkle_root};
+use crate::difficulty::{U256, check_proof_of_work};
+use crate::hash::{sha256d, to_display_hex};
+
+/// BIP141 block weight limit
+pub const MAX_BLOCK_WEIGHT: usize = 4_000_000;
+
+/// Witness commitment output prefix: `OP_RETURN` push-36 `aa21a9ed`
+const WITNESS_COMMITMENT_PREFIX: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
+/// Result of one check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// Not applicable or missing context (height, chain parameters)
+    Skipped,
+}
+
+/// One named validation step with a human-readable detail line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+impl Step {
+    fn new(name: &'static str, passed: bool, detail: impl Into<String>) -> Self {
+        Step {
+            name,
+            outcome: if passed { Outcome::Pass } else { Outcome::Fail },
+            detail: detail.into(),
+        }
+    }
+
+    fn skipped(name: &'static str, detail: impl Into<String>) -> Self {
+        Step {
+            name,
+            outcome: Outcome::Skipped,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tag = match self.outcome {
+            Outcome::Pass => "PASS",
+            Outcome::Fail => "FAIL",
+            Outcome::Skipped => "SKIP",
+        };
+        write!(f, "[{tag}] {}: {}", self.name, self.detail)
+    }
+}
+
+/// Run every check; later steps are skipped only if the block cannot be decoded
+pub fn analyze(bytes: &[u8], params: Option<&ChainParams>, height: Option<u32>) -> Vec<Step> {
+    let block = match Block::decode(bytes) {
+        Ok(block) => block,
+        Err(e) => return vec![Step::new("decode", false, e.to_string())],
+    };
+    let hash = block.header.hash();
+    let mut steps = vec![Step::new(
+        "decode",
+        true,
+        format!(
+            "{} bytes, {} transactions, hash {}",
+            bytes.len(),
+            block.transactions.len(),
+            to_display_hex(&hash)
+        ),
+    )];
+    steps.push(check_pow(&block, &hash, params));
+    steps.push(check_merkle(&block));
+    steps.push(check_coinbase(&block));
+    let weight = block.weight();
+    steps.push(Step::new(
+        "weight",
+        weight <= MAX_BLOCK_WEIGHT,
+        format!("{weight} WU (limit {MAX_BLOCK_WEIGHT})"),
+    ));
+    steps.push(check_bip34(&block, params, height));
+    steps.push(check_witness_commitment(&block));
+    steps
+}
+
+fn check_pow(block: &Block, hash: &[u8; 32], params: Option<&ChainParams>) -> Step {
+    let bits = block.header.bits;
+    if !check_proof_of_work(hash, bits) {
+        return Step::new(
+            "proof-of-work",
+            false,
+            format!("hash above target {bits:08x}"),
+        );
+    }
+    let above_limit =
+        params.is_some_and(|p| U256::from_compact(bits) > U256::from_compact(p.pow_limit_bits));
+    if above_limit {
+        return Step::new(
+            "proof-of-work",
+            false,
+            format!("target {bits:08x} easier than the network's proof-of-work limit"),
+        );
+    }
+    Step::new("proof-of-work", true, format!("meets target {bits:08x}"))
+}
+
+fn check_merkle(block: &Block) -> Step {
+    let txids: Vec<[u8; 32]> = block.transactions.iter().map(|tx| tx.txid()).collect();
+    let mut sorted = txids.clone();
+    sorted.sort_unstable();
+    if sorted.windows(2).any(|w| w[0] == w[1]) {
+        // CVE-2012-2459: duplicated transactions can produce a valid-looking root
+        return Step::new(
+            "merkle-root",
+            false,
+            "duplicate transactions (mutated block)",
+        );
+    }
+    let computed = merkle_root(txids);
+    if computed != block.header.merkle_root {
+        return Step::new(
+            "merkle-root",
+            false,
+            format!(
+                "header commits to {}, transactions hash to {}",
+                to_display_hex(&block.header.merkle_root),
+                to_display_hex(&computed)
+            ),
+        );
+    }
+    Step::new("merkle-root", true, to_display_hex(&computed))
+}
+
+fn check_coinbase(block: &Block) -> Step {
+    let Some(coinbase) = block.transactions.first() else {
+        return Step::new("coinbase", false, "block has no transactions");
+    };
+    if !coinbase.is_coinbase() {
+        return Step::new("coinbase", false, "first transaction is not a coinbase");
+    }
+    if let Some(i) = block
+        .transactions
+        .iter()
+        .skip(1)
+        .position(|tx| tx.is_coinbase())
+    {
+        return Step::new(
+            "coinbase",
+            false,
+            format!("transaction {} is a second coinbase", i + 1),
+        );
+    }
+    let len = coinbase.inputs[0].script_sig.len();
+    if !(2..=100).contains(&len) {
+        return Step::new(
+            "coinbase",
+            false,
+            format!("coinbase scriptSig is {len} bytes (must be 2-100)"),
+        );
+    }
+    let value: u64 = coinbase.outputs.iter().map(|o| o.value).sum();
+    Step::new(
+        "coinbase",
+        true,
+        format!(
+            "pays {}.{:08} BTC",
+            value / 100_000_000,
+            value % 100_000_000
+        ),
+    )
+}
+
+/// Script prefix `CScript() << height` that BIP34 requires in the coinbase scriptSig
+pub fn bip34_prefix(height: u32) -> Vec<u8> {
+    match height {
+        0 => vec![0x00],
+        1..=16 => vec![0x50 + height as u8],
+        _ => {
+            let mut num: Vec<u8> = height.to_le_bytes().to_vec();
+            while num.last() == Some(&0) {
+                num.pop();
+            }
+            if num.last().is_some_and(|b| b & 0x80 != 0) {
+                num.push(0);
+            }
+            let mut out = vec![num.len() as u8];
+            out.extend(num);
+            out
+        }
+    }
+}
+
+fn check_bip34(block: &Block, params: Option<&ChainParams>, height: Option<u32>) -> Step {
+    let (Some(params), Some(height)) = (params, height) else {
+        return Step::skipped("bip34-height", "needs --height");
+    };
+    match params.activations.bip34 {
+        Some(active) if height >= active => {}
+        _ => return Step::skipped("bip34-height", "not active at this height"),
+    }
+    let Some(coinbase) = block.transactions.first().filter(|tx| tx.is_coinbase()) else {
+        return Step::new("bip34-height", false, "no coinbase");
+    };
+    let expected = bip34_prefix(height);
+    Step::new(
+        "bip34-height",
+        coinbase.inputs[0].script_sig.starts_with(&expected),
+        format!("coinbase must start with {}", hex::encode(&expected)),
+    )
+}
+
+fn check_witness_commitment(block: &Block) -> Step {
+    let Some(coinbase) = block.transactions.first() else {
+        return Step::skipped("witness-commitment", "no coinbase");
+    };
+    let commitment = coinbase.outputs.iter().rev().find(|o| {
+        o.script_pubkey.len() >= 38 && o.script_pubkey.starts_with(&WITNESS_COMMITMENT_PREFIX)
+    });
+    let has_witness = block.transactions.iter().any(|tx| tx.has_witness());
+    let Some(commitment) = commitment else {
+        return if has_witness {
+            Step::new(
+                "witness-commitment",
+                false,
+                "witness data without a commitment in the coinbase",
+            )
+        } else {
+            Step::skipped("witness-commitment", "no witness data")
+        };
+    };
+    let reserved = match coinbase.inputs.first().map(|i| i.witness.as_slice()) {
+        Some([reserved]) if reserved.len() == 32 => reserved.clone(),
+        _ => {
+            return Step::new(
+                "witness-commitment",
+                false,
+                "coinbase witness must be a single 32-byte reserved value",
+            );
+        }
+    };
+    let mut wtxids: Vec<[u8; 32]> = block.transactions.iter().map(|tx| tx.wtxid()).collect();
+    wtxids[0] = [0; 32];
+    let mut preimage = merkle_root(wtxids).to_vec();
+    preimage.extend_from_slice(&reserved);
+    let expected = sha256d(&preimage);
+    Step::new(
+        "witness-commitment",
+        commitment.script_pubkey[6..38] == expected,
+        format!(
+            "commitment {}",
+            hex::encode(&commitment.script_pubkey[6..38])
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENESIS_BLOCK: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    fn outcome(steps: &[Step], name: &str) -> Outcome {
+        steps
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap()
+            .outcome
+            .clone()
+    }
+
+    #[test]
+    fn genesis_passes() {
+        let params = ChainParams::for_network("mainnet").unwrap();
+        let steps = analyze(&hex::decode(GENESIS_BLOCK).unwrap(), Some(&params), Some(0));
+        assert!(
+            steps.iter().all(|s| s.outcome != Outcome::Fail),
+            "{steps:?}"
+        );
+        assert_eq!(outcome(&steps, "bip34-height"), Outcome::Skipped);
+        assert_eq!(outcome(&steps, "witness-commitment"), Outcome::Skipped);
+    }
+
+    #[test]
+    fn reports_failing_step() {
+        let mut bytes = hex::decode(GENESIS_BLOCK).unwrap();
+        bytes[76] ^= 1; // nonce: header no longer meets its target
+        let steps = analyze(&bytes, None, None);
+        assert_eq!(outcome(&steps, "proof-of-work"), Outcome::Fail);
+        assert_eq!(outcome(&steps, "merkle-root"), Outcome::Pass);
+
+        let steps = analyze(&bytes[..90], None, None);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].outcome, Outcome::Fail);
+    }
+
+    #[test]
+    fn bip34_encoding() {
+        assert_eq!(bip34_prefix(1), [0x51]);
+        assert_eq!(bip34_prefix(17), [0x01, 0x11]);
+        assert_eq!(bip34_prefix(128), [0x02, 0x80, 0x00]);
+        assert_eq!(bip34_prefix(227_931), [0x03, 0x5b, 0x7a, 0x03]);
+    }
+}
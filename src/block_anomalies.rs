@@ -0,0 +1,239 @@
+//! Timestamp and difficulty anomaly warnings for new tip blocks (`[anomalies]`)
+//!
+//! Each new tip is compared with the local clock and its parent. A timestamp well ahead of the
+//! local clock, or (once synced) a fresh tip whose timestamp is far behind it, usually means
+//! the local clock is off; on mainnet a difficulty drop larger than `max_difficulty_drop_pct`
+//! at a retarget is far outside anything seen in practice (the consensus limit is 75%) and is
+//! worth a look. Findings go to the event log and to `anomalies.json`, which `blvm status`
+//! reads.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+
+/// Recent findings in the data directory
+pub const ANOMALIES_FILE: &str = "anomalies.json";
+
+/// Findings kept in [`ANOMALIES_FILE`]
+const MAX_RECORDED: usize = 50;
+
+/// `[anomalies]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    pub enabled: bool,
+    /// Block timestamp ahead of the local clock by more than this
+    pub future_secs: u64,
+    /// New tip (outside initial block download) behind the local clock by more than this
+    pub stale_secs: u64,
+    /// Mainnet difficulty drop between consecutive blocks, in percent
+    pub max_difficulty_drop_pct: f64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            future_secs: 60 * 60,
+            stale_secs: 3 * 60 * 60,
+            max_difficulty_drop_pct: 30.0,
+        }
+    }
+}
+
+/// The `getblockheader` fields the checks use
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub hash: String,
+    pub height: u64,
+    pub time: u64,
+    pub difficulty: f64,
+}
+
+impl Header {
+    pub fn from_value(header: &Value) -> Option<Self> {
+        Some(Header {
+            hash: header.get("hash")?.as_str()?.to_string(),
+            height: header.get("height")?.as_u64()?,
+            time: header.get("time")?.as_u64()?,
+            difficulty: header.get("difficulty")?.as_f64()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anomaly {
+    FutureTimestamp { ahead_secs: u64 },
+    StaleTimestamp { behind_secs: u64 },
+    DifficultyDrop { from: f64, to: f64 },
+}
+
+impl Anomaly {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Anomaly::FutureTimestamp { .. } => "future_timestamp",
+            Anomaly::StaleTimestamp { .. } => "stale_timestamp",
+            Anomaly::DifficultyDrop { .. } => "difficulty_drop",
+        }
+    }
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anomaly::FutureTimestamp { ahead_secs } => write!(
+                f,
+                "timestamp {ahead_secs}s ahead of the local clock (is the clock behind?)"
+            ),
+            Anomaly::StaleTimestamp { behind_secs } => write!(
+                f,
+                "new tip timestamp {behind_secs}s behind the local clock (is the clock ahead?)"
+            ),
+            Anomaly::DifficultyDrop { from, to } => write!(
+                f,
+                "difficulty dropped {:.1}% ({from:.2} -> {to:.2})",
+                (1.0 - to / from) * 100.0
+            ),
+        }
+    }
+}
+
+/// Anomalies of tip `header` (child of `parent`) seen at unix time `now`. `synced` is false
+/// during initial block download, when old timestamps are expected.
+pub fn check(
+    config: &AnomalyConfig,
+    header: &Header,
+    parent: Option<&Header>,
+    chain: &str,
+    synced: bool,
+    now: u64,
+) -> Vec<Anomaly> {
+    let mut found = Vec::new();
+    if header.time > now + config.future_secs {
+        found.push(Anomaly::FutureTimestamp {
+            ahead_secs: header.time - now,
+        });
+    }
+    if synced && now > header.time + config.stale_secs {
+        found.push(Anomaly::StaleTimestamp {
+            behind_secs: now - header.time,
+        });
+    }
+    if chain == "main"
+        && let Some(parent) = parent
+        && parent.difficulty > 0.0
+        && header.difficulty < parent.difficulty * (1.0 - config.max_difficulty_drop_pct / 100.0)
+    {
+        found.push(Anomaly::DifficultyDrop {
+            from: parent.difficulty,
+            to: header.difficulty,
+        });
+    }
+    found
+}
+
+/// One recorded finding
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Recorded {
+    pub height: u64,
+    pub hash: String,
+    pub kind: String,
+    pub message: String,
+    /// Unix time it was detected
+    pub detected: u64,
+}
+
+/// Most recent findings, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct AnomalyLog {
+    pub entries: Vec<Recorded>,
+}
+
+impl AnomalyLog {
+    pub fn load(data_dir: &Path) -> Self {
+        std::fs::read(data_dir.join(ANOMALIES_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> std::io::Result<()> {
+        let path = data_dir.join(ANOMALIES_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)
+    }
+
+    pub fn record(&mut self, header: &Header, anomaly: &Anomaly, now: u64) {
+        self.entries.push(Recorded {
+            height: header.height,
+            hash: header.hash.clone(),
+            kind: anomaly.kind().to_string(),
+            message: anomaly.to_string(),
+            detected: now,
+        });
+        let excess = self.entries.len().saturating_sub(MAX_RECORDED);
+        self.entries.drain(..excess);
+    }
+
+    /// Findings detected within `window_secs` of `now`
+    pub fn recent(&self, now: u64, window_secs: u64) -> impl Iterator<Item = &Recorded> {
+        self.entries
+            .iter()
+            .filter(move |entry| now.saturating_sub(entry.detected) <= window_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(height: u64, time: u64, difficulty: f64) -> Header {
+        Header {
+            hash: format!("h{height}"),
+            height,
+            time,
+            difficulty,
+        }
+    }
+
+    #[test]
+    fn flags_clock_and_difficulty_anomalies() {
+        let config = AnomalyConfig::default();
+        let now = 1_700_000_000;
+        let parent = header(100, now - 600, 1000.0);
+        let normal = header(101, now - 30, 900.0);
+        assert!(check(&config, &normal, Some(&parent), "main", true, now).is_empty());
+
+        let ahead = header(101, now + 2 * 3600, 1000.0);
+        assert_eq!(
+            check(&config, &ahead, Some(&parent), "main", true, now),
+            vec![Anomaly::FutureTimestamp { ahead_secs: 7200 }]
+        );
+        // Old timestamps are expected while syncing
+        let old = header(101, now - 86_400, 1000.0);
+        assert!(check(&config, &old, Some(&parent), "main", false, now).is_empty());
+        assert_eq!(
+            check(&config, &old, Some(&parent), "main", true, now)[0].kind(),
+            "stale_timestamp"
+        );
+
+        let drop = header(101, now, 500.0);
+        let found = check(&config, &drop, Some(&parent), "main", true, now);
+        assert_eq!(
+            found[0].to_string(),
+            "difficulty dropped 50.0% (1000.00 -> 500.00)"
+        );
+        assert!(check(&config, &drop, Some(&parent), "test", true, now).is_empty());
+
+        let mut log = AnomalyLog::default();
+        for i in 0..60 {
+            log.record(&drop, &found[0], now - 100_000 + i);
+        }
+        log.record(&ahead, &Anomaly::FutureTimestamp { ahead_secs: 7200 }, now);
+        assert_eq!(log.entries.len(), MAX_RECORDED);
+        assert_eq!(log.recent(now, 86_400).count(), 1);
+    }
+}
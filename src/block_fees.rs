@@ -0,0 +1,123 @@
+//! Per-transaction fees from `getblock` output (`blvm block`)
+//!
+//! Verbosity 3 includes each input's prevout (read from undo data) and a `fee` per
+//! transaction; verbosity 2 has decoded transactions but no input values, so fees are unknown.
+
+use serde_json::Value;
+
+/// Amount in BTC (as RPC JSON) to satoshis
+pub fn btc_to_sat(btc: f64) -> u64 {
+    (btc * 100_000_000.0).round() as u64
+}
+
+/// One transaction of a decoded block
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxSummary {
+    pub txid: String,
+    pub vsize: u64,
+    pub inputs: usize,
+    pub outputs: usize,
+    /// `None` for the coinbase and when prevouts are unavailable
+    pub fee: Option<u64>,
+}
+
+impl TxSummary {
+    /// Read a verbosity 2/3 transaction entry
+    pub fn from_value(tx: &Value) -> Option<Self> {
+        let vin = tx.get("vin")?.as_array()?;
+        let vout = tx.get("vout")?.as_array()?;
+        let vsize = tx
+            .get("vsize")
+            .and_then(|v| v.as_u64())
+            .or_else(|| Some(tx.get("weight")?.as_u64()?.div_ceil(4)))?;
+        let coinbase = vin.iter().any(|input| input.get("coinbase").is_some());
+        let fee = if coinbase {
+            None
+        } else if let Some(fee) = tx.get("fee").and_then(|v| v.as_f64()) {
+            Some(btc_to_sat(fee))
+        } else {
+            // Prevouts without a precomputed fee: inputs minus outputs
+            let value = |v: &Value| v.get("value").and_then(|v| v.as_f64()).map(btc_to_sat);
+            let spent: Option<u64> = vin.iter().map(|i| value(i.get("prevout")?)).sum();
+            let created: Option<u64> = vout.iter().map(value).sum();
+            spent.zip(created).map(|(s, c)| s.saturating_sub(c))
+        };
+        Some(TxSummary {
+            txid: tx.get("txid")?.as_str()?.to_string(),
+            vsize,
+            inputs: vin.len(),
+            outputs: vout.len(),
+            fee,
+        })
+    }
+
+    /// sat/vB
+    pub fn feerate(&self) -> Option<f64> {
+        self.fee.map(|fee| fee as f64 / self.vsize.max(1) as f64)
+    }
+}
+
+/// Fee totals over a block's non-coinbase transactions (only when every fee is known)
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeSummary {
+    pub total: u64,
+    pub min_feerate: f64,
+    pub median_feerate: f64,
+    pub max_feerate: f64,
+}
+
+pub fn summarize(txs: &[TxSummary]) -> Option<FeeSummary> {
+    let paying: Vec<&TxSummary> = txs.iter().skip(1).collect();
+    let mut rates: Vec<f64> = paying
+        .iter()
+        .map(|tx| tx.feerate())
+        .collect::<Option<_>>()?;
+    if rates.is_empty() {
+        return None;
+    }
+    rates.sort_by(f64::total_cmp);
+    Some(FeeSummary {
+        total: paying.iter().filter_map(|tx| tx.fee).sum(),
+        min_feerate: rates[0],
+        median_feerate: rates[rates.len() / 2],
+        max_feerate: rates[rates.len() - 1],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_fees_from_verbosity_3_and_prevouts() {
+        let coinbase = json!({"txid": "aa", "vsize": 120, "vin": [{"coinbase": "03"}],
+            "vout": [{"value": 3.125}]});
+        let with_fee = json!({"txid": "bb", "vsize": 141, "fee": 0.00001410,
+            "vin": [{"txid": "cc", "vout": 0}], "vout": [{"value": 0.5}]});
+        let prevouts_only = json!({"txid": "dd", "weight": 562,
+            "vin": [{"prevout": {"value": 0.3}}, {"prevout": {"value": 0.2}}],
+            "vout": [{"value": 0.4999}]});
+        let verbosity_2 = json!({"txid": "ee", "vsize": 110,
+            "vin": [{"txid": "ff", "vout": 1}], "vout": [{"value": 0.1}]});
+
+        let txs: Vec<TxSummary> = [&coinbase, &with_fee, &prevouts_only]
+            .into_iter()
+            .map(|tx| TxSummary::from_value(tx).unwrap())
+            .collect();
+        assert_eq!(txs[0].fee, None);
+        assert_eq!(txs[1].fee, Some(1410));
+        assert_eq!(txs[1].feerate(), Some(10.0));
+        assert_eq!((txs[2].vsize, txs[2].fee), (141, Some(10_000)));
+
+        let summary = summarize(&txs).unwrap();
+        assert_eq!(summary.total, 11_410);
+        assert_eq!(summary.min_feerate, 10.0);
+        assert!(summary.max_feerate > 70.0);
+
+        let no_fee = TxSummary::from_value(&verbosity_2).unwrap();
+        assert_eq!(no_fee.fee, None);
+        assert!(summarize(&[txs[0].clone(), no_fee]).is_none());
+        assert!(summarize(&txs[..1]).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+//! Static chain parameters per network (`blvm chain genesis` / `blvm chain params`)
+//!
+//! Values mirror Bitcoin Core's chainparams; consensus enforcement lives in blvm-consensus.
+
+/// Genesis block header and coinbase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Genesis {
+    pub hash: &'static str,
+    pub version: i32,
+    pub merkle_root: &'static str,
+    pub time: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+/// Soft fork activation heights (`None`: deployed via version bits, no fixed height)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activations {
+    pub bip34: Option<u32>,
+    pub bip65: Option<u32>,
+    pub bip66: Option<u32>,
+    pub csv: Option<u32>,
+    pub segwit: Option<u32>,
+    pub taproot: Option<u32>,
+}
+
+/// Consensus and network parameters for one chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainParams {
+    pub network: &'static str,
+    pub genesis: Genesis,
+    pub magic: [u8; 4],
+    pub default_p2p_port: u16,
+    pub default_rpc_port: u16,
+    pub subsidy_halving_interval: u32,
+    /// Blocks per difficulty adjustment period
+    pub retarget_interval: u32,
+    /// Target seconds per retarget period
+    pub target_timespan: u32,
+    pub target_spacing: u32,
+    /// Proof-of-work limit in compact form
+    pub pow_limit_bits: u32,
+    /// Testnet/regtest 20-minute minimum-difficulty rule
+    pub allow_min_difficulty_blocks: bool,
+    /// Regtest: difficulty never changes
+    pub no_retargeting: bool,
+    pub activations: Activations,
+}
+
+/// Genesis coinbase text shared by all four networks
+pub const GENESIS_COINBASE_MESSAGE: &str =
+    "The Times 03/Jan/2009 Chancellor on brink of second bailout for banks";
+
+/// Genesis merkle root (the same coinbase on every network)
+const GENESIS_MERKLE_ROOT: &str =
+    "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+
+/// Initial block subsidy in satoshis
+pub const INITIAL_SUBSIDY_SATS: u64 = 50 * 100_000_000;
+
+impl ChainParams {
+    /// Parameters for a network name (aliases accepted, see [`crate::canonical_network_name`])
+    pub fn for_network(network: &str) -> Option<Self> {
+        let network = crate::canonical_network_name(network)?;
+        let magic = crate::wire::network_magic(network)?;
+        let default_p2p_port = crate::default_p2p_port_for_network(network);
+        let default_rpc_port = crate::default_rpc_addr_for_network(network).port();
+        let base = ChainParams {
+            network,
+            genesis: Genesis {
+                hash: "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+                version: 1,
+                merkle_root: GENESIS_MERKLE_ROOT,
+                time: 1231006505,
+                bits: 0x1d00ffff,
+                nonce: 2083236893,
+            },
+            magic,
+            default_p2p_port,
+            default_rpc_port,
+            subsidy_halving_interval: 210_000,
+            retarget_interval: 2016,
+            target_timespan: 14 * 24 * 60 * 60,
+            target_spacing: 600,
+            pow_limit_bits: 0x1d00ffff,
+            allow_min_difficulty_blocks: false,
+            no_retargeting: false,
+            activations: Activations {
+                bip34: Some(227_931),
+                bip65: Some(388_381),
+                bip66: Some(363_725),
+                csv: Some(419_328),
+                segwit: Some(481_824),
+                taproot: Some(709_632),
+            },
+        };
+        Some(match network {
+            "mainnet" => base,
+            "testnet" => ChainParams {
+                genesis: Genesis {
+                    hash: "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943",
+                    time: 1296688602,
+                    nonce: 414098458,
+                    ..base.genesis
+                },
+                allow_min_difficulty_blocks: true,
+                activations: Activations {
+                    bip34: Some(21_111),
+                    bip65: Some(581_885),
+                    bip66: Some(330_776),
+                    csv: Some(770_112),
+                    segwit: Some(834_624),
+                    taproot: None,
+                },
+                ..base
+            },
+            "signet" => ChainParams {
+                genesis: Genesis {
+                    hash: "00000008819873e925422c1ff0f99f7cc9bbb232af63a077a480a3633bee1ef6",
+                    time: 1598918400,
+                    bits: 0x1e0377ae,
+                    nonce: 52613770,
+                    ..base.genesis
+                },
+                pow_limit_bits: 0x1e0377ae,
+                activations: Activations {
+                    bip34: Some(1),
+                    bip65: Some(1),
+                    bip66: Some(1),
+                    csv: Some(1),
+                    segwit: Some(1),
+                    taproot: Some(0),
+                },
+                ..base
+            },
+            _ => ChainParams {
+                genesis: Genesis {
+                    hash: "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206",
+                    time: 1296688602,
+                    bits: 0x207fffff,
+                    nonce: 2,
+                    ..base.genesis
+                },
+                subsidy_halving_interval: 150,
+                pow_limit_bits: 0x207fffff,
+                allow_min_difficulty_blocks: true,
+                no_retargeting: true,
+                activations: Activations {
+                    bip34: Some(1),
+                    bip65: Some(1),
+                    bip66: Some(1),
+                    csv: Some(1),
+                    segwit: Some(0),
+                    taproot: Some(0),
+                },
+                ..base
+            },
+        })
+    }
+
+    /// Block subsidy (satoshis) at `height`
+    pub fn subsidy_at(&self, height: u32) -> u64 {
+        let halvings = height / self.subsidy_halving_interval;
+        if halvings >= 64 {
+            0
+        } else {
+            INITIAL_SUBSIDY_SATS >> halvings
+        }
+    }
+
+    /// `(height, subsidy_sats)` for each era until the subsidy reaches zero
+    pub fn halving_schedule(&self) -> Vec<(u32, u64)> {
+        (0..64)
+            .map(|era| era * self.subsidy_halving_interval)
+            .map(|height| (height, self.subsidy_at(height)))
+            .take_while(|&(_, subsidy)| subsidy > 0)
+            .collect()
+    }
+
+    /// Total coins ever issued (satoshis)
+    pub fn max_supply_sats(&self) -> u64 {
+        self.halving_schedule()
+            .iter()
+            .map(|&(_, subsidy)| subsidy * self.subsidy_halving_interval as u64)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn networks_and_aliases() {
+        let main = ChainParams::for_network("mainnet").unwrap();
+        assert_eq!(main.default_p2p_port, 8333);
+        assert_eq!(main.default_rpc_port, 8332);
+        assert_eq!(main.magic, [0xf9, 0xbe, 0xb4, 0xd9]);
+        assert_eq!(
+            ChainParams::for_network("testnet3").unwrap().genesis.nonce,
+            414098458
+        );
+        assert!(ChainParams::for_network("regtest").unwrap().no_retargeting);
+        assert!(ChainParams::for_network("litecoin").is_none());
+    }
+
+    #[test]
+    fn mainnet_supply_and_schedule() {
+        let main = ChainParams::for_network("mainnet").unwrap();
+        assert_eq!(main.subsidy_at(0), 5_000_000_000);
+        assert_eq!(main.subsidy_at(840_000), 312_500_000);
+        let schedule = main.halving_schedule();
+        assert_eq!(schedule.len(), 33);
+        assert_eq!(schedule[1], (210_000, 2_500_000_000));
+        assert_eq!(main.max_supply_sats(), 2_099_999_997_690_000);
+    }
+}
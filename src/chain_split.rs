@@ -0,0 +1,243 @@
+//! Chain-split detection (`[chain_split]`)
+//!
+//! A split shows up as a competing tip in `getchaintips` that forks below the active tip, has
+//! at least `min_branch_len` blocks, and carries work comparable to the active chain since the
+//! fork point (`min_work_ratio`). It is reported once at least `min_peer_fraction` of peers
+//! follow it, judged by their best known header height (`synced_headers`): above the active tip
+//! when the fork is ahead, at the fork tip's height when it is behind. A fork level with the
+//! active tip cannot be told apart by height and is reported once it pulls ahead or falls
+//! behind. The current split is kept in `chain-split.json` so `blvm status` and
+//! `getblockchaininfo` through the RPC front can warn about it. The node's own
+//! `getblockchaininfo` is not changed: clients that need the warning there must use the front.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// State file name in the data directory
+pub const STATE_FILE: &str = "chain-split.json";
+
+/// `[chain_split]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ChainSplitConfig {
+    pub enabled: bool,
+    /// Share of connected peers that must follow the fork
+    pub min_peer_fraction: f64,
+    pub min_branch_len: u64,
+    /// Fork work since the fork point relative to the active chain's
+    pub min_work_ratio: f64,
+    pub check_interval_secs: u64,
+}
+
+impl Default for ChainSplitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_peer_fraction: 0.3,
+            min_branch_len: 2,
+            min_work_ratio: 0.9,
+            check_interval_secs: 60,
+        }
+    }
+}
+
+/// A competing tip from `getchaintips`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkTip {
+    pub hash: String,
+    pub height: u64,
+    pub branch_len: u64,
+    pub status: String,
+}
+
+impl ForkTip {
+    pub fn fork_point_height(&self) -> u64 {
+        self.height.saturating_sub(self.branch_len)
+    }
+}
+
+/// Active tip height and the competing tips worth checking, highest first. Header chains that
+/// merely extend the active tip (a node catching up) are not forks.
+pub fn fork_candidates(tips: &Value, min_branch_len: u64) -> Option<(u64, Vec<ForkTip>)> {
+    let tips = tips.as_array()?;
+    let active_height = tips
+        .iter()
+        .find(|tip| tip.get("status").and_then(|v| v.as_str()) == Some("active"))?
+        .get("height")?
+        .as_u64()?;
+    let mut forks: Vec<ForkTip> = tips
+        .iter()
+        .filter_map(|tip| {
+            let status = tip.get("status")?.as_str()?;
+            if !matches!(
+                status,
+                "headers-only" | "valid-headers" | "valid-fork" | "invalid"
+            ) {
+                return None;
+            }
+            Some(ForkTip {
+                hash: tip.get("hash")?.as_str()?.to_string(),
+                height: tip.get("height")?.as_u64()?,
+                branch_len: tip.get("branchlen")?.as_u64()?,
+                status: status.to_string(),
+            })
+        })
+        .filter(|fork| {
+            fork.branch_len >= min_branch_len && fork.fork_point_height() < active_height
+        })
+        .collect();
+    forks.sort_by_key(|fork| std::cmp::Reverse(fork.height));
+    Some((active_height, forks))
+}
+
+/// `chainwork` from `getblockheader` (hex); values beyond 128 bits saturate
+pub fn parse_chainwork(hex: &str) -> Option<u128> {
+    let hex = hex.trim_start_matches('0');
+    if hex.len() > 32 {
+        return Some(u128::MAX);
+    }
+    if hex.is_empty() {
+        return Some(0);
+    }
+    u128::from_str_radix(hex, 16).ok()
+}
+
+/// Work the fork added since the fork point, relative to what the active chain added
+pub fn work_ratio(fork_work: u128, active_work: u128, fork_point_work: u128) -> f64 {
+    let fork = fork_work.saturating_sub(fork_point_work);
+    match active_work.saturating_sub(fork_point_work) {
+        0 => 1.0,
+        active => fork as f64 / active as f64,
+    }
+}
+
+/// `(peers following the fork, peers with a known header height)` from `getpeerinfo`
+pub fn peers_on_fork(peers: &Value, fork_height: u64, active_height: u64) -> (usize, usize) {
+    let heights: Vec<u64> = peers
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|peer| peer.get("synced_headers")?.as_i64())
+        .filter_map(|height| u64::try_from(height).ok())
+        .collect();
+    let on_fork = heights
+        .iter()
+        .filter(|&&height| match fork_height.cmp(&active_height) {
+            std::cmp::Ordering::Greater => height > active_height,
+            std::cmp::Ordering::Less => height == fork_height,
+            std::cmp::Ordering::Equal => false,
+        })
+        .count();
+    (on_fork, heights.len())
+}
+
+/// A detected split
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Split {
+    pub fork_hash: String,
+    pub fork_height: u64,
+    pub branch_len: u64,
+    /// `getchaintips` status of the fork (`invalid` = the node rejects it)
+    pub status: String,
+    pub active_height: u64,
+    pub peers_on_fork: usize,
+    pub peers: usize,
+    pub work_ratio: f64,
+}
+
+impl Split {
+    pub fn warning(&self) -> String {
+        format!(
+            "Possible chain split: {} of {} peers follow a {} fork of {} blocks at height {} \
+             ({:.0}% of our work since the fork, our tip at {})",
+            self.peers_on_fork,
+            self.peers,
+            self.status,
+            self.branch_len,
+            self.fork_height,
+            self.work_ratio * 100.0,
+            self.active_height
+        )
+    }
+}
+
+/// Current split, if any
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ChainSplitState {
+    pub split: Option<Split>,
+    /// Unix time the split was first seen
+    pub since: u64,
+}
+
+impl ChainSplitState {
+    pub fn load(data_dir: &Path) -> Self {
+        std::fs::read(data_dir.join(STATE_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> std::io::Result<()> {
+        let path = data_dir.join(STATE_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)
+    }
+}
+
+/// `warnings` of a `getblockchaininfo` result with `warning` added (string or array form)
+pub fn add_warning(result: &mut Value, warning: &str) {
+    match result.get_mut("warnings") {
+        Some(Value::Array(warnings)) => warnings.push(Value::from(warning)),
+        Some(Value::String(existing)) if !existing.is_empty() => {
+            *existing = format!("{existing}; {warning}");
+        }
+        _ => result["warnings"] = Value::from(warning),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn detects_forks_and_attributes_peers() {
+        let tips = json!([
+            {"height": 105, "hash": "aa", "branchlen": 0, "status": "active"},
+            // Catching up on our own chain: not a fork
+            {"height": 110, "hash": "bb", "branchlen": 5, "status": "headers-only"},
+            {"height": 107, "hash": "cc", "branchlen": 4, "status": "invalid"},
+            {"height": 101, "hash": "dd", "branchlen": 1, "status": "valid-fork"},
+        ]);
+        let (active, forks) = fork_candidates(&tips, 2).unwrap();
+        assert_eq!(active, 105);
+        assert_eq!(forks.len(), 1);
+        assert_eq!(
+            (forks[0].hash.as_str(), forks[0].fork_point_height()),
+            ("cc", 103)
+        );
+
+        assert_eq!(
+            parse_chainwork("000000000000000000000000000000000000000000000000000000000000ff00"),
+            Some(0xff00)
+        );
+        assert_eq!(work_ratio(1_190, 1_200, 1_000), 0.95);
+
+        let peers = json!([
+            {"synced_headers": 107}, {"synced_headers": 107},
+            {"synced_headers": 105}, {"synced_headers": -1},
+        ]);
+        assert_eq!(peers_on_fork(&peers, 107, 105), (2, 3));
+        assert_eq!(peers_on_fork(&peers, 105, 105), (0, 3));
+
+        let mut info = json!({"warnings": ["old"]});
+        add_warning(&mut info, "split");
+        assert_eq!(info["warnings"], json!(["old", "split"]));
+        let mut info = json!({"warnings": ""});
+        add_warning(&mut info, "split");
+        assert_eq!(info["warnings"], "split");
+    }
+}
@@ -0,0 +1,159 @@
+//! Dotted-key assignments on config documents (`blvm config set`, `BLVM_CONFIG__*` variables)
+//!
+//! Any config key can come from the environment: `BLVM_CONFIG__RPC_AUTH__REQUIRED=true` is
+//! `[rpc_auth] required = true`. `__` separates sections and names are lowercased. Values are
+//! read as booleans or numbers when they parse as such, as TOML when quoted or bracketed
+//! (`"0011"`, `["a", "b"]`, `{ x = 1 }`), and as plain strings otherwise.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Prefix of environment variables holding config keys
+pub const ENV_PREFIX: &str = "BLVM_CONFIG__";
+
+/// Value of a `key=value` assignment
+pub fn parse_value(s: &str) -> toml::Value {
+    let s = s.trim();
+    if s.starts_with(['"', '\'', '[', '{'])
+        && let Ok(mut doc) = format!("v = {s}").parse::<toml::Table>()
+        && let Some(value) = doc.remove("v")
+    {
+        return value;
+    }
+    if s == "true" {
+        return toml::Value::Boolean(true);
+    }
+    if s == "false" {
+        return toml::Value::Boolean(false);
+    }
+    if let Ok(i) = s.parse::<i64>() {
+        return toml::Value::Integer(i);
+    }
+    if let Ok(f) = s.parse::<f64>() {
+        return toml::Value::Float(f);
+    }
+    toml::Value::String(s.to_string())
+}
+
+/// Set `a.b.c` in `root`, creating missing sections
+pub fn set_dotted(root: &mut toml::Value, key: &str, value: toml::Value) -> Result<()> {
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|p| p.is_empty()) {
+        anyhow::bail!("Invalid key '{}'", key);
+    }
+
+    let mut current = root;
+    for (i, part) in parts.iter().enumerate() {
+        let toml::Value::Table(t) = current else {
+            anyhow::bail!(
+                "Key '{}': '{}' exists but is not a section",
+                key,
+                parts[..i].join(".")
+            );
+        };
+        if i == parts.len() - 1 {
+            t.insert(part.to_string(), value);
+            return Ok(());
+        }
+        current = t
+            .entry(part.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::map::Map::new()));
+    }
+    Ok(())
+}
+
+/// `(dotted key, value)` for every `BLVM_CONFIG__*` variable, sorted by key
+pub fn env_assignments(
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Vec<(String, toml::Value)> {
+    let mut assignments: Vec<(String, toml::Value)> = vars
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let path = name.strip_prefix(ENV_PREFIX)?;
+            let key = path.split("__").collect::<Vec<_>>().join(".");
+            Some((key.to_lowercase(), parse_value(&value)))
+        })
+        .collect();
+    assignments.sort_by(|a, b| a.0.cmp(&b.0));
+    assignments
+}
+
+/// `config` with the assignments applied (round-trips through TOML, so unknown keys are
+/// ignored exactly as in a config file)
+pub fn apply<T: Serialize + DeserializeOwned>(
+    config: &T,
+    assignments: &[(String, toml::Value)],
+) -> Result<T> {
+    let mut root = toml::Value::try_from(config).context("Failed to serialize config")?;
+    for (key, value) in assignments {
+        set_dotted(&mut root, key, value.clone())?;
+    }
+    root.try_into()
+        .map_err(|e| anyhow::anyhow!("Invalid {ENV_PREFIX}* setting: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_names_map_to_dotted_keys() {
+        let vars = [
+            ("BLVM_CONFIG__PROBES__ADDR", "0.0.0.0:8080"),
+            ("BLVM_CONFIG__LISTEN", "false"),
+            ("BLVM_CONFIG__FLEET__KEY_ID", "\"0011\""),
+            ("BLVM_NETWORK", "mainnet"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let assignments = env_assignments(vars);
+        assert_eq!(
+            assignments,
+            vec![
+                (
+                    "fleet.key_id".to_string(),
+                    toml::Value::String("0011".into())
+                ),
+                ("listen".to_string(), toml::Value::Boolean(false)),
+                (
+                    "probes.addr".to_string(),
+                    toml::Value::String("0.0.0.0:8080".into())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_onto_typed_config() {
+        let config = crate::extra_config::ExtraConfig::default();
+        let assignments = env_assignments(
+            [
+                ("BLVM_CONFIG__LISTEN", "false"),
+                ("BLVM_CONFIG__REVALIDATION__ENABLED", "true"),
+                ("BLVM_CONFIG__UNKNOWN_SECTION__KEY", "1"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        let config = apply(&config, &assignments).unwrap();
+        assert_eq!(config.listen, Some(false));
+        assert!(config.revalidation.enabled);
+
+        let bad = env_assignments([("BLVM_CONFIG__LISTEN".into(), "maybe".into())]);
+        assert!(apply(&config, &bad).is_err());
+    }
+
+    #[test]
+    fn parses_values_and_rejects_scalar_sections() {
+        assert_eq!(parse_value("42"), toml::Value::Integer(42));
+        assert_eq!(
+            parse_value("[\"a\", \"b\"]"),
+            toml::Value::Array(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(parse_value("[oops"), toml::Value::String("[oops".into()));
+
+        let mut root = toml::Value::Table(Default::default());
+        set_dotted(&mut root, "a", toml::Value::Integer(1)).unwrap();
+        assert!(set_dotted(&mut root, "a.b", toml::Value::Integer(2)).is_err());
+        assert!(set_dotted(&mut root, "a..b", toml::Value::Integer(2)).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Config reload on SIGHUP (`blvm start`)
+//!
+//! The config is built again the way `start` built it and compared with the running one.
+//! Sections this binary enforces itself ([`RELOADABLE`]) take effect at once, and so does
+//! lowering a node peer cap ([`PEER_CAPS`]), which the binary enforces by disconnecting the
+//! excess. Any other change, including every other node setting (blvm-node reads its config
+//! only at startup), is reported as needing a restart.
+
+use crate::json_diff::{Difference, diff};
+use serde_json::Value;
+
+/// `blvm.toml` keys applied without a restart
+pub const RELOADABLE: &[&str] = &[
+    "log_level",
+    "inbound_limits",
+    "network.timeouts",
+    "anomalies",
+    "chain_split",
+    "peer_policy",
+    "revalidation",
+    "script_stats",
+    "fee_history",
+];
+
+/// Node peer caps applied without a restart while at or below their startup value; blvm-node
+/// sizes its connection limits at startup, so raising one past that needs a restart. Both the
+/// field name and its `max_peers` config file key are listed.
+pub const PEER_CAPS: &[&str] = &["max_outbound_peers", "max_peers"];
+
+/// Changes between the running and the reloaded config
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReloadPlan {
+    pub applied: Vec<Difference>,
+    pub restart: Vec<Difference>,
+}
+
+/// Sort the differences of the node config and of the binary's own settings (each as
+/// `(running, reloaded)`) into applied and restart-only; `startup_node` is the node config the
+/// node was started with
+pub fn plan(startup_node: &Value, node: (&Value, &Value), extra: (&Value, &Value)) -> ReloadPlan {
+    let mut plan = ReloadPlan::default();
+    for change in diff(node.0, node.1, &[]) {
+        if cap_within_startup(startup_node, node.1, change.path()) {
+            plan.applied.push(change);
+        } else {
+            plan.restart.push(change);
+        }
+    }
+    for change in diff(extra.0, extra.1, &[]) {
+        if is_reloadable(change.path()) {
+            plan.applied.push(change);
+        } else {
+            plan.restart.push(change);
+        }
+    }
+    plan
+}
+
+/// A [`PEER_CAPS`] key whose reloaded value does not exceed the one the node started with
+fn cap_within_startup(startup: &Value, reloaded: &Value, path: &str) -> bool {
+    let cap = |config: &Value| config.get(path).and_then(|v| v.as_u64());
+    PEER_CAPS.contains(&path)
+        && matches!((cap(startup), cap(reloaded)), (Some(startup), Some(reloaded)) if reloaded <= startup)
+}
+
+fn is_reloadable(path: &str) -> bool {
+    RELOADABLE.iter().any(|key| {
+        path.strip_prefix(key)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reloadable_sections_apply_the_rest_needs_restart() {
+        let node = json!({"max_outbound_peers": 8, "network": "regtest"});
+        let reloaded_node = json!({"max_outbound_peers": 16, "network": "regtest"});
+        let extra = json!({
+            "log_level": null,
+            "inbound_limits": {"enabled": false, "per_ip": 4},
+            "network": {"timeouts": null},
+            "anomalies_extra": 1,
+            "probes": {"addr": null},
+        });
+        let reloaded_extra = json!({
+            "log_level": "blvm=debug",
+            "inbound_limits": {"enabled": true, "per_ip": 2},
+            "network": {"timeouts": {"ban_secs": 60}},
+            "anomalies_extra": 2,
+            "probes": {"addr": "127.0.0.1:9000"},
+        });
+        let plan = plan(&node, (&node, &reloaded_node), (&extra, &reloaded_extra));
+        let paths = |changes: &[Difference]| -> Vec<String> {
+            let mut paths: Vec<String> = changes.iter().map(|c| c.path().to_string()).collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(
+            paths(&plan.applied),
+            [
+                "inbound_limits.enabled",
+                "inbound_limits.per_ip",
+                "log_level",
+                "network.timeouts"
+            ]
+        );
+        assert_eq!(
+            paths(&plan.restart),
+            ["anomalies_extra", "max_outbound_peers", "probes.addr"]
+        );
+    }
+
+    #[test]
+    fn peer_caps_apply_up_to_their_startup_value() {
+        let startup = json!({"max_outbound_peers": 8, "listen_addr": "0.0.0.0:8333"});
+        let lowered = json!({"max_outbound_peers": 4, "listen_addr": "0.0.0.0:8333"});
+        let extra = json!({});
+        let plan_for = |running: &Value, reloaded: &Value| {
+            plan(&startup, (running, reloaded), (&extra, &extra))
+        };
+
+        let lower = plan_for(&startup, &lowered);
+        assert_eq!(lower.applied.len(), 1);
+        assert!(lower.restart.is_empty());
+        // Back up to the startup value still applies; past it needs a restart
+        assert_eq!(plan_for(&lowered, &startup).applied.len(), 1);
+        let raised = json!({"max_outbound_peers": 16, "listen_addr": "0.0.0.0:8333"});
+        let raise = plan_for(&lowered, &raised);
+        assert!(raise.applied.is_empty());
+        assert_eq!(raise.restart[0].path(), "max_outbound_peers");
+        let removed = json!({"listen_addr": "0.0.0.0:8333"});
+        assert_eq!(plan_for(&startup, &removed).restart.len(), 1);
+    }
+}
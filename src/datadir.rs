@@ -0,0 +1,374 @@
+//! Data directory inspection helpers (offline; no node required)
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Fee estimator state the node writes on shutdown and restores on start
+pub const FEE_ESTIMATES_FILE: &str = "fee_estimates.dat";
+
+/// On-disk size of one top-level data directory entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntrySize {
+    /// File or directory name relative to the data directory
+    pub name: String,
+    /// Total bytes (recursive for directories)
+    pub bytes: u64,
+    /// Whether the entry is a directory
+    pub is_dir: bool,
+}
+
+/// Recursive size of a file or directory. Symlinks are not followed.
+pub fn dir_size(path: &Path) -> io::Result<u64> {
+    let meta = std::fs::symlink_metadata(path)?;
+    if !meta.is_dir() {
+        return Ok(meta.len());
+    }
+    let mut total = 0u64;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        total = total.saturating_add(dir_size(&entry.path())?);
+    }
+    Ok(total)
+}
+
+/// Sizes of the top-level entries in `path`, largest first.
+pub fn entry_sizes(path: &Path) -> io::Result<Vec<EntrySize>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let is_dir = entry.file_type()?.is_dir();
+        entries.push(EntrySize {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            bytes: dir_size(&entry.path())?,
+            is_dir,
+        });
+    }
+    entries.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+/// Free bytes on the filesystem holding `path` (via POSIX `df -Pk`; `None` where unavailable)
+pub fn available_space(path: &Path) -> Option<u64> {
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Undo (`blocks/rev*.dat`) files and their total size; `(0, 0)` without a `blocks/` directory
+pub fn undo_files(data_dir: &Path) -> io::Result<(usize, u64)> {
+    let blocks = data_dir.join("blocks");
+    if !blocks.is_dir() {
+        return Ok((0, 0));
+    }
+    let mut count = 0;
+    let mut bytes = 0u64;
+    for entry in std::fs::read_dir(blocks)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with("rev") && name.ends_with(".dat") {
+            count += 1;
+            bytes = bytes.saturating_add(entry.metadata()?.len());
+        }
+    }
+    Ok((count, bytes))
+}
+
+/// First bytes of every redb database file
+const REDB_MAGIC: [u8; 9] = *b"redb\x1a\x0a\xa9\x0d\x0a";
+/// `mm_magic` in an LMDB meta page, after the 16-byte page header
+const LMDB_MAGIC: u32 = 0xBEEF_C0DE;
+
+/// Storage engine that wrote a store in the data directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreKind {
+    Redb,
+    RocksDb,
+    Sled,
+    Lmdb,
+}
+
+impl StoreKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StoreKind::Redb => "redb",
+            StoreKind::RocksDb => "rocksdb",
+            StoreKind::Sled => "sled",
+            StoreKind::Lmdb => "lmdb",
+        }
+    }
+}
+
+/// A storage backend's files found in the data directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Store {
+    pub kind: StoreKind,
+    /// The redb file, or the directory of a RocksDB, sled or LMDB store
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// Why the store looks damaged; `None` when its files read back as expected
+    pub problem: Option<String>,
+}
+
+/// Stores under `data_dir` (up to two directories deep), each with its header files checked to
+/// look intact. This only sniffs bytes: a store can pass and still fail to open in blvm-node.
+/// Nothing is written and no lock is taken, so this is safe next to a running node.
+pub fn find_stores(data_dir: &Path) -> io::Result<Vec<Store>> {
+    let mut stores = Vec::new();
+    find_stores_in(data_dir, 0, &mut stores)?;
+    Ok(stores)
+}
+
+fn find_stores_in(dir: &Path, depth: usize, stores: &mut Vec<Store>) -> io::Result<()> {
+    let store_dir = |kind, problem| -> io::Result<Store> {
+        Ok(Store {
+            kind,
+            path: dir.to_path_buf(),
+            bytes: dir_size(dir)?,
+            problem,
+        })
+    };
+    if dir.join("CURRENT").is_file() {
+        stores.push(store_dir(StoreKind::RocksDb, rocksdb_problem(dir))?);
+        return Ok(());
+    }
+    if dir.join("data.mdb").is_file() {
+        let problem = read_prefix(&dir.join("data.mdb"), 20).map_or_else(
+            |e| Some(format!("data.mdb: {e}")),
+            |head| {
+                let magic = head
+                    .get(16..20)
+                    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+                (magic != Some(LMDB_MAGIC)).then(|| "data.mdb has no LMDB meta page".to_string())
+            },
+        );
+        stores.push(store_dir(StoreKind::Lmdb, problem)?);
+        return Ok(());
+    }
+    if dir.join("conf").is_file() && dir.join("db").is_file() {
+        let problem = ["conf", "db"].iter().find_map(|name| {
+            read_prefix(&dir.join(name), 1)
+                .err()
+                .map(|e| format!("{name}: {e}"))
+        });
+        stores.push(store_dir(StoreKind::Sled, problem)?);
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() && depth < 2 {
+            find_stores_in(&path, depth + 1, stores)?;
+        } else if file_type.is_file() {
+            let named_redb = path.extension().is_some_and(|ext| ext == "redb");
+            let problem = match read_prefix(&path, REDB_MAGIC.len()) {
+                Ok(head) if head == REDB_MAGIC => None,
+                Ok(_) if named_redb => Some("not a redb file (bad magic)".to_string()),
+                Err(e) if named_redb => Some(e.to_string()),
+                _ => continue,
+            };
+            stores.push(Store {
+                kind: StoreKind::Redb,
+                bytes: entry.metadata()?.len(),
+                path,
+                problem,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// A RocksDB directory opens when `CURRENT` names a readable `MANIFEST-*` file
+fn rocksdb_problem(dir: &Path) -> Option<String> {
+    let current = match std::fs::read_to_string(dir.join("CURRENT")) {
+        Ok(current) => current,
+        Err(e) => return Some(format!("CURRENT: {e}")),
+    };
+    let manifest = current.trim();
+    if !manifest.starts_with("MANIFEST-") {
+        return Some(format!("CURRENT does not name a manifest: {manifest:?}"));
+    }
+    read_prefix(&dir.join(manifest), 1)
+        .err()
+        .map(|e| format!("CURRENT names {manifest}, which cannot be read: {e}"))
+}
+
+/// Up to `len` bytes from the start of `path`, opened read-only
+fn read_prefix(path: &Path, len: usize) -> io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(len);
+    std::fs::File::open(path)?
+        .take(len as u64)
+        .read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// Delete saved fee estimator state (`--reset-fee-estimates`); `Ok(false)` when there was none
+pub fn reset_fee_estimates(data_dir: &Path) -> io::Result<bool> {
+    match std::fs::remove_file(data_dir.join(FEE_ESTIMATES_FILE)) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replace `{hostname}` in a data directory path, so one StatefulSet spec gives every pod its
+/// own stable directory on a shared volume (`/data/{hostname}` → `/data/blvm-0`)
+pub fn expand_hostname(dir: &str, hostname: Option<&str>) -> String {
+    match hostname {
+        Some(hostname) if dir.contains("{hostname}") => dir.replace("{hostname}", hostname),
+        _ => dir.to_string(),
+    }
+}
+
+/// `HOSTNAME` (set in every pod), else `/etc/hostname`
+pub fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+}
+
+/// `Available` column of `df -Pk` output, in bytes
+fn parse_df_available(output: &str) -> Option<u64> {
+    let available = output.lines().nth(1)?.split_whitespace().nth(3)?;
+    available.parse::<u64>().ok().map(|kib| kib * 1024)
+}
+
+/// Human-readable byte count using binary units (e.g. `1.50 GiB`).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.2} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_bytes_uses_binary_units() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.50 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.00 GiB");
+    }
+
+    #[test]
+    fn expands_hostname_placeholder() {
+        assert_eq!(
+            expand_hostname("/data/{hostname}/node", Some("blvm-1")),
+            "/data/blvm-1/node"
+        );
+        assert_eq!(expand_hostname("/data", Some("blvm-1")), "/data");
+        assert_eq!(
+            expand_hostname("/data/{hostname}", None),
+            "/data/{hostname}"
+        );
+    }
+
+    #[test]
+    fn parses_df_output() {
+        let out = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                   /dev/sda1         41152736  20576368  18463260      53% /\n";
+        assert_eq!(parse_df_available(out), Some(18_463_260 * 1024));
+        assert_eq!(parse_df_available("Filesystem\n"), None);
+    }
+
+    #[test]
+    fn entry_sizes_are_recursive_and_sorted() {
+        let root = std::env::temp_dir().join(format!("blvm-datadir-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("chainstate/sub")).unwrap();
+        std::fs::write(root.join("chainstate/a"), [0u8; 100]).unwrap();
+        std::fs::write(root.join("chainstate/sub/b"), [0u8; 50]).unwrap();
+        std::fs::write(root.join("peers.dat"), [0u8; 10]).unwrap();
+
+        let sizes = entry_sizes(&root).unwrap();
+        assert_eq!(sizes[0].name, "chainstate");
+        assert_eq!(sizes[0].bytes, 150);
+        assert!(sizes[0].is_dir);
+        assert_eq!(sizes[1].name, "peers.dat");
+        assert_eq!(dir_size(&root).unwrap(), 160);
+
+        assert_eq!(undo_files(&root).unwrap(), (0, 0));
+        std::fs::create_dir_all(root.join("blocks")).unwrap();
+        std::fs::write(root.join("blocks/blk00000.dat"), [0u8; 30]).unwrap();
+        std::fs::write(root.join("blocks/rev00000.dat"), [0u8; 20]).unwrap();
+        assert_eq!(undo_files(&root).unwrap(), (1, 20));
+
+        std::fs::write(root.join(FEE_ESTIMATES_FILE), [0u8; 8]).unwrap();
+        assert!(reset_fee_estimates(&root).unwrap());
+        assert!(!reset_fee_estimates(&root).unwrap());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn finds_stores_and_checks_their_headers() {
+        let root = std::env::temp_dir().join(format!("blvm-stores-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        assert!(find_stores(&root).is_err());
+        std::fs::create_dir_all(root.join("blvm/rocks")).unwrap();
+        std::fs::create_dir_all(root.join("lmdb")).unwrap();
+        let mut redb = REDB_MAGIC.to_vec();
+        redb.extend([0u8; 23]);
+        std::fs::write(root.join("blvm/chain.redb"), &redb).unwrap();
+        std::fs::write(root.join("blvm/index.redb"), b"garbage").unwrap();
+        std::fs::write(root.join("peers.dat"), b"not a store").unwrap();
+        std::fs::write(root.join("blvm/rocks/CURRENT"), "MANIFEST-000004\n").unwrap();
+        let mut meta = vec![0u8; 16];
+        meta.extend(LMDB_MAGIC.to_le_bytes());
+        std::fs::write(root.join("lmdb/data.mdb"), &meta).unwrap();
+
+        let mut stores = find_stores(&root).unwrap();
+        stores.sort_by(|a, b| a.path.cmp(&b.path));
+        let found: Vec<_> = stores
+            .iter()
+            .map(|s| (s.kind, s.path.strip_prefix(&root).unwrap().to_path_buf()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (StoreKind::Redb, PathBuf::from("blvm/chain.redb")),
+                (StoreKind::Redb, PathBuf::from("blvm/index.redb")),
+                (StoreKind::RocksDb, PathBuf::from("blvm/rocks")),
+                (StoreKind::Lmdb, PathBuf::from("lmdb")),
+            ]
+        );
+        assert_eq!(stores[0].problem, None);
+        assert_eq!(stores[0].bytes, 32);
+        assert_eq!(
+            stores[1].problem.as_deref(),
+            Some("not a redb file (bad magic)")
+        );
+        assert!(
+            stores[2]
+                .problem
+                .as_ref()
+                .unwrap()
+                .contains("MANIFEST-000004")
+        );
+        assert_eq!(stores[3].problem, None);
+
+        std::fs::write(root.join("blvm/rocks/MANIFEST-000004"), b"m").unwrap();
+        assert_eq!(rocksdb_problem(&root.join("blvm/rocks")), None);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
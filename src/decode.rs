@@ -0,0 +1,447 @@
+//! Consensus serialization of headers, transactions, and blocks (`blvm decode`)
+
+use crate::hash::sha256d;
+
+/// Serialized header size
+pub const HEADER_SIZE: usize = 80;
+
+/// Decoding failure with the byte offset where it happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn error(&self, message: impl Into<String>) -> DecodeError {
+        DecodeError {
+            offset: self.pos,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn bytes(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| self.error(format!("unexpected end of data (need {n} bytes)")))?;
+        let out = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.bytes(N)?.try_into().expect("length checked"))
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// CompactSize, rejecting non-canonical encodings and counts larger than the input
+    pub(crate) fn compact_size(&mut self) -> Result<usize, DecodeError> {
+        let start = self.pos;
+        let value = match self.u8()? {
+            0xfd => {
+                let v = u16::from_le_bytes(self.array()?) as u64;
+                (v >= 0xfd).then_some(v)
+            }
+            0xfe => {
+                let v = self.u32()? as u64;
+                (v > 0xffff).then_some(v)
+            }
+            0xff => {
+                let v = self.u64()?;
+                (v > 0xffff_ffff).then_some(v)
+            }
+            n => Some(n as u64),
+        };
+        match value {
+            Some(v) if v <= self.data.len() as u64 => Ok(v as usize),
+            Some(_) => Err(DecodeError {
+                offset: start,
+                message: "size exceeds input".to_string(),
+            }),
+            None => Err(DecodeError {
+                offset: start,
+                message: "non-canonical CompactSize".to_string(),
+            }),
+        }
+    }
+
+    pub(crate) fn var_bytes(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let len = self.compact_size()?;
+        Ok(self.bytes(len)?.to_vec())
+    }
+
+    pub(crate) fn finish(&self) -> Result<(), DecodeError> {
+        if self.pos == self.data.len() {
+            Ok(())
+        } else {
+            Err(self.error(format!("{} trailing bytes", self.data.len() - self.pos)))
+        }
+    }
+}
+
+pub(crate) fn write_compact_size(out: &mut Vec<u8>, n: usize) {
+    match n {
+        0..=0xfc => out.push(n as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend_from_slice(&(n as u64).to_le_bytes());
+        }
+    }
+}
+
+/// 80-byte block header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub version: i32,
+    pub prev_blockhash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub time: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+impl BlockHeader {
+    pub(crate) fn read(r: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        Ok(Self {
+            version: r.u32()? as i32,
+            prev_blockhash: r.array()?,
+            merkle_root: r.array()?,
+            time: r.u32()?,
+            bits: r.u32()?,
+            nonce: r.u32()?,
+        })
+    }
+
+    /// Decode exactly one header
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader::new(bytes);
+        let header = Self::read(&mut r)?;
+        r.finish()?;
+        Ok(header)
+    }
+
+    pub fn encode(&self) -> [u8; HEADER_SIZE] {
+        let mut out = [0u8; HEADER_SIZE];
+        out[0..4].copy_from_slice(&self.version.to_le_bytes());
+        out[4..36].copy_from_slice(&self.prev_blockhash);
+        out[36..68].copy_from_slice(&self.merkle_root);
+        out[68..72].copy_from_slice(&self.time.to_le_bytes());
+        out[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        out[76..80].copy_from_slice(&self.nonce.to_le_bytes());
+        out
+    }
+
+    pub fn hash(&self) -> [u8; 32] {
+        sha256d(&self.encode())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxIn {
+    pub prev_txid: [u8; 32],
+    pub prev_vout: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOut {
+    /// Satoshis
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: i32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+impl Transaction {
+    fn read(r: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let version = r.u32()? as i32;
+        let mut input_count = r.compact_size()?;
+        // BIP144: marker 0x00 + flag 0x01
+        let segwit = input_count == 0 && r.data.get(r.pos) == Some(&1);
+        if segwit {
+            r.u8()?;
+            input_count = r.compact_size()?;
+        }
+        let mut inputs = Vec::with_capacity(input_count.min(1024));
+        for _ in 0..input_count {
+            inputs.push(TxIn {
+                prev_txid: r.array()?,
+                prev_vout: r.u32()?,
+                script_sig: r.var_bytes()?,
+                sequence: r.u32()?,
+                witness: Vec::new(),
+            });
+        }
+        let output_count = r.compact_size()?;
+        let mut outputs = Vec::with_capacity(output_count.min(1024));
+        for _ in 0..output_count {
+            outputs.push(TxOut {
+                value: r.u64()?,
+                script_pubkey: r.var_bytes()?,
+            });
+        }
+        if segwit {
+            for input in &mut inputs {
+                let items = r.compact_size()?;
+                for _ in 0..items {
+                    input.witness.push(r.var_bytes()?);
+                }
+            }
+            if inputs.iter().all(|i| i.witness.is_empty()) {
+                return Err(r.error("superfluous witness flag"));
+            }
+        }
+        Ok(Self {
+            version,
+            inputs,
+            outputs,
+            lock_time: r.u32()?,
+        })
+    }
+
+    /// Decode exactly one transaction
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader::new(bytes);
+        let tx = Self::read(&mut r)?;
+        r.finish()?;
+        Ok(tx)
+    }
+
+    pub fn has_witness(&self) -> bool {
+        self.inputs.iter().any(|i| !i.witness.is_empty())
+    }
+
+    /// Serialize; `with_witness` selects the BIP144 form when any input has a witness
+    pub fn encode(&self, with_witness: bool) -> Vec<u8> {
+        let witness = with_witness && self.has_witness();
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.version.to_le_bytes());
+        if witness {
+            out.extend_from_slice(&[0x00, 0x01]);
+        }
+        write_compact_size(&mut out, self.inputs.len());
+        for input in &self.inputs {
+            out.extend_from_slice(&input.prev_txid);
+            out.extend_from_slice(&input.prev_vout.to_le_bytes());
+            write_compact_size(&mut out, input.script_sig.len());
+            out.extend_from_slice(&input.script_sig);
+            out.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        write_compact_size(&mut out, self.outputs.len());
+        for output in &self.outputs {
+            out.extend_from_slice(&output.value.to_le_bytes());
+            write_compact_size(&mut out, output.script_pubkey.len());
+            out.extend_from_slice(&output.script_pubkey);
+        }
+        if witness {
+            for input in &self.inputs {
+                write_compact_size(&mut out, input.witness.len());
+                for item in &input.witness {
+                    write_compact_size(&mut out, item.len());
+                    out.extend_from_slice(item);
+                }
+            }
+        }
+        out.extend_from_slice(&self.lock_time.to_le_bytes());
+        out
+    }
+
+    pub fn txid(&self) -> [u8; 32] {
+        sha256d(&self.encode(false))
+    }
+
+    pub fn wtxid(&self) -> [u8; 32] {
+        sha256d(&self.encode(true))
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.len() == 1
+            && self.inputs[0].prev_txid == [0; 32]
+            && self.inputs[0].prev_vout == u32::MAX
+    }
+
+    /// BIP141 weight
+    pub fn weight(&self) -> usize {
+        self.encode(false).len() * 3 + self.encode(true).len()
+    }
+
+    pub fn vsize(&self) -> usize {
+        self.weight().div_ceil(4)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub transactions: Vec<Transaction>,
+}
+
+impl Block {
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader::new(bytes);
+        let header = BlockHeader::read(&mut r)?;
+        let count = r.compact_size()?;
+        let mut transactions = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            transactions.push(Transaction::read(&mut r)?);
+        }
+        r.finish()?;
+        Ok(Self {
+            header,
+            transactions,
+        })
+    }
+
+    /// Merkle root computed from the transactions (compare with `header.merkle_root`)
+    pub fn computed_merkle_root(&self) -> [u8; 32] {
+        merkle_root(self.transactions.iter().map(Transaction::txid).collect())
+    }
+
+    /// Serialize; `with_witness: false` gives the stripped form
+    pub fn encode(&self, with_witness: bool) -> Vec<u8> {
+        let mut out = self.header.encode().to_vec();
+        write_compact_size(&mut out, self.transactions.len());
+        for tx in &self.transactions {
+            out.extend_from_slice(&tx.encode(with_witness));
+        }
+        out
+    }
+
+    /// BIP141 weight
+    pub fn weight(&self) -> usize {
+        self.encode(false).len() * 3 + self.encode(true).len()
+    }
+}
+
+/// Merkle root over leaf hashes (odd levels duplicate the last node)
+pub fn merkle_root(mut level: Vec<[u8; 32]>) -> [u8; 32] {
+    if level.is_empty() {
+        return [0; 32];
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let mut buf = [0u8; 64];
+                buf[..32].copy_from_slice(&pair[0]);
+                buf[32..].copy_from_slice(pair.get(1).unwrap_or(&pair[0]));
+                sha256d(&buf)
+            })
+            .collect();
+    }
+    level[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::to_display_hex;
+
+    const GENESIS_BLOCK: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    #[test]
+    fn decodes_genesis_block() {
+        let bytes = hex::decode(GENESIS_BLOCK).unwrap();
+        let block = Block::decode(&bytes).unwrap();
+        assert_eq!(
+            to_display_hex(&block.header.hash()),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        let coinbase = &block.transactions[0];
+        assert!(coinbase.is_coinbase());
+        assert_eq!(coinbase.outputs[0].value, 50 * 100_000_000);
+        assert_eq!(
+            to_display_hex(&coinbase.txid()),
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+        );
+        assert_eq!(block.computed_merkle_root(), block.header.merkle_root);
+        assert_eq!(block.weight(), bytes.len() * 4);
+        assert_eq!(block.encode(true), bytes);
+        assert_eq!(
+            BlockHeader::decode(&bytes[..HEADER_SIZE]).unwrap(),
+            block.header
+        );
+    }
+
+    #[test]
+    fn segwit_round_trip() {
+        let tx = Transaction {
+            version: 2,
+            inputs: vec![TxIn {
+                prev_txid: [7; 32],
+                prev_vout: 1,
+                script_sig: Vec::new(),
+                sequence: 0xffff_fffd,
+                witness: vec![vec![0x30; 71], vec![0x02; 33]],
+            }],
+            outputs: vec![TxOut {
+                value: 12_345,
+                script_pubkey: [&[0x00, 0x14][..], &[0xab; 20]].concat(),
+            }],
+            lock_time: 0,
+        };
+        let bytes = tx.encode(true);
+        assert_eq!(Transaction::decode(&bytes).unwrap(), tx);
+        assert_ne!(tx.txid(), tx.wtxid());
+        assert_eq!(tx.weight(), 82 * 3 + bytes.len());
+    }
+
+    #[test]
+    fn rejects_bad_encodings() {
+        let bytes = hex::decode(GENESIS_BLOCK).unwrap();
+        let err = Block::decode(&bytes[..100]).unwrap_err();
+        assert!(err.message.contains("unexpected end"), "{err}");
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Block::decode(&trailing).is_err());
+        // 0xfd prefix encoding a value < 0xfd
+        let mut r = Reader::new(&[0xfd, 0x10, 0x00]);
+        assert!(r.compact_size().is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! Output script descriptor helpers (BIP380 checksums, key expressions)
+
+const INPUT_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+const CHECKSUM_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u64; 5] = [
+    0xf5dee51989,
+    0xa9fdca3312,
+    0x1bab10e32d,
+    0x3706b1677a,
+    0x644d626ffd,
+];
+
+fn polymod(symbols: impl IntoIterator<Item = u64>) -> u64 {
+    let mut chk = 1u64;
+    for value in symbols {
+        let top = chk >> 35;
+        chk = ((chk & 0x7_ffff_ffff) << 5) ^ value;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+/// Eight-character BIP380 checksum of a descriptor without `#...`; `None` on characters outside
+/// the descriptor character set
+pub fn checksum(descriptor: &str) -> Option<String> {
+    let mut symbols = Vec::with_capacity(descriptor.len() * 4 / 3 + 9);
+    let mut groups = Vec::with_capacity(3);
+    for c in descriptor.chars() {
+        let v = INPUT_CHARSET.find(c)? as u64;
+        symbols.push(v & 31);
+        groups.push(v >> 5);
+        if groups.len() == 3 {
+            symbols.push(groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+    match groups.as_slice() {
+        [a] => symbols.push(*a),
+        [a, b] => symbols.push(a * 3 + b),
+        _ => {}
+    }
+    let chk = polymod(symbols.into_iter().chain([0; 8])) ^ 1;
+    Some(
+        (0..8)
+            .map(|i| CHECKSUM_CHARSET[((chk >> (5 * (7 - i))) & 31) as usize] as char)
+            .collect(),
+    )
+}
+
+/// Descriptor with its checksum: appended when missing, verified when present
+pub fn with_checksum(descriptor: &str) -> Result<String, String> {
+    let (body, given) = match descriptor.split_once('#') {
+        Some((body, given)) => (body, Some(given)),
+        None => (descriptor, None),
+    };
+    let expected =
+        checksum(body).ok_or_else(|| "descriptor contains invalid characters".to_string())?;
+    match given {
+        Some(given) if given != expected => Err(format!(
+            "descriptor checksum mismatch (given {given}, expected {expected})"
+        )),
+        _ => Ok(format!("{body}#{expected}")),
+    }
+}
+
+/// Functions whose arguments are key expressions (`multi*` after the threshold, `tr` in the
+/// internal-key position only)
+const KEY_FUNCTIONS: &[&str] = &[
+    "pk",
+    "pkh",
+    "wpkh",
+    "combo",
+    "pk_k",
+    "pk_h",
+    "multi",
+    "sortedmulti",
+    "multi_a",
+    "sortedmulti_a",
+];
+
+/// `/*` at the end of a key's derivation path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wildcard {
+    Unhardened,
+    /// `/*h` or `/*'`: needs the private key to derive
+    Hardened,
+}
+
+/// How a key expression names its key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    /// Hex public key (compressed, uncompressed or x-only)
+    Hex,
+    /// BIP32 extended key (`xpub` / `tpub`, or `xprv` / `tprv` when `private`)
+    Extended { private: bool },
+    /// WIF private key
+    Wif,
+}
+
+/// One key expression: `[origin]KEY/path/...`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyExpr {
+    /// Key origin between `[` and `]` (fingerprint and path), if given
+    pub origin: Option<String>,
+    pub key: String,
+    pub kind: KeyKind,
+    /// Derivation steps after the key, without the wildcard
+    pub path: Vec<String>,
+    pub wildcard: Option<Wildcard>,
+}
+
+impl KeyExpr {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let (origin, rest) = match expr.strip_prefix('[') {
+            Some(rest) => {
+                let (origin, rest) = rest
+                    .split_once(']')
+                    .ok_or_else(|| format!("unterminated key origin in '{expr}'"))?;
+                let mut steps = origin.split('/');
+                let fingerprint = steps.next().unwrap_or_default();
+                if fingerprint.len() != 8
+                    || !fingerprint.chars().all(|c| c.is_ascii_hexdigit())
+                    || !steps.all(is_path_step)
+                {
+                    return Err(format!("invalid key origin '[{origin}]'"));
+                }
+                (Some(origin.to_string()), rest)
+            }
+            None => (None, expr),
+        };
+        let mut steps = rest.split('/');
+        let key = steps.next().unwrap_or_default();
+        let kind = key_kind(key).ok_or_else(|| format!("unrecognised key '{key}'"))?;
+        let mut path: Vec<String> = steps.map(str::to_string).collect();
+        let wildcard = match path.last().map(String::as_str) {
+            Some("*") => Some(Wildcard::Unhardened),
+            Some("*h" | "*'") => Some(Wildcard::Hardened),
+            _ => None,
+        };
+        if wildcard.is_some() {
+            path.pop();
+        }
+        if !matches!(kind, KeyKind::Extended { .. }) && (wildcard.is_some() || !path.is_empty()) {
+            return Err(format!(
+                "only extended keys take a derivation path: '{expr}'"
+            ));
+        }
+        if path.iter().any(|step| !is_path_step(step)) {
+            return Err(format!("invalid derivation path in '{expr}'"));
+        }
+        Ok(KeyExpr {
+            origin,
+            key: key.to_string(),
+            kind,
+            path,
+            wildcard,
+        })
+    }
+
+    pub fn is_private(&self) -> bool {
+        matches!(
+            self.kind,
+            KeyKind::Wif | KeyKind::Extended { private: true }
+        )
+    }
+}
+
+fn key_kind(key: &str) -> Option<KeyKind> {
+    let base58 = |s: &str| {
+        s.chars()
+            .all(|c| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l'))
+    };
+    if matches!(key.len(), 64 | 66 | 130) && key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Some(KeyKind::Hex);
+    }
+    if key.len() == 111 && base58(key) {
+        return match &key[..4] {
+            "xpub" | "tpub" => Some(KeyKind::Extended { private: false }),
+            "xprv" | "tprv" => Some(KeyKind::Extended { private: true }),
+            _ => None,
+        };
+    }
+    // Uncompressed (5 / 9) or compressed (K, L / c) mainnet / testnet WIF
+    let wif = matches!(
+        (key.len(), key.chars().next()),
+        (51, Some('5' | '9')) | (52, Some('K' | 'L' | 'c'))
+    );
+    (wif && base58(key)).then_some(KeyKind::Wif)
+}
+
+/// A BIP32 step: an index, optionally hardened (`h` or `'`), or a `<a;b>` multipath pair
+fn is_path_step(step: &str) -> bool {
+    let index = |s: &str| {
+        let s = s.strip_suffix(['h', '\'']).unwrap_or(s);
+        !s.is_empty() && s.parse::<u32>().is_ok_and(|n| n < 1 << 31)
+    };
+    match step.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+        Some(pair) => pair.split(';').count() >= 2 && pair.split(';').all(index),
+        None => index(step),
+    }
+}
+
+/// Split at commas outside any `()`, `[]` or `{}`
+fn split_top_level(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&args[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&args[start..]);
+    parts
+}
+
+fn collect_keys(expr: &str, keys: &mut Vec<KeyExpr>) -> Result<(), String> {
+    let expr = expr.trim();
+    // Taproot script trees: {left,right}
+    if let Some(tree) = expr.strip_prefix('{').and_then(|e| e.strip_suffix('}')) {
+        for branch in split_top_level(tree) {
+            collect_keys(branch, keys)?;
+        }
+        return Ok(());
+    }
+    let (name, args) = expr
+        .strip_suffix(')')
+        .and_then(|e| e.split_once('('))
+        .ok_or_else(|| format!("expected a script expression, got '{expr}'"))?;
+    // Miniscript wrappers (`v:pk(...)`)
+    let name = name.rsplit(':').next().unwrap_or(name);
+    let args = split_top_level(args);
+    match name {
+        "raw" | "addr" => Ok(()),
+        "multi" | "sortedmulti" | "multi_a" | "sortedmulti_a" => {
+            for arg in args.iter().skip(1) {
+                keys.push(KeyExpr::parse(arg.trim())?);
+            }
+            Ok(())
+        }
+        "tr" => {
+            keys.push(KeyExpr::parse(args[0].trim())?);
+            args[1..]
+                .iter()
+                .try_for_each(|tree| collect_keys(tree, keys))
+        }
+        _ if KEY_FUNCTIONS.contains(&name) => {
+            for arg in &args {
+                keys.push(KeyExpr::parse(arg.trim())?);
+            }
+            Ok(())
+        }
+        // Other fragments take script expressions or plain values (thresholds, hashes, times)
+        _ => args
+            .iter()
+            .filter(|arg| arg.contains('('))
+            .try_for_each(|arg| collect_keys(arg, keys)),
+    }
+}
+
+/// Every key expression in a descriptor (checksum optional), in order
+pub fn key_expressions(descriptor: &str) -> Result<Vec<KeyExpr>, String> {
+    let body = descriptor
+        .split_once('#')
+        .map_or(descriptor, |(body, _)| body);
+    let mut keys = Vec::new();
+    collect_keys(body, &mut keys)?;
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XPUB_DESC: &str = "wpkh([d34db33f/84h/0h/0h]xpub6DJ2dNUysrn5Vt36jH2KLBT2i1auw1tTSSomg8PhqNiUtx8QX2SvC9nrHu81fT41fvDUnhMjEzQgXnQjKEu3oaqMSzhSrHMxyyoEAmUHQbY/0/*)";
+
+    #[test]
+    fn checksums_match_reference() {
+        assert_eq!(checksum("raw(deadbeef)").as_deref(), Some("89f8spxm"));
+        assert_eq!(checksum(XPUB_DESC).as_deref(), Some("cjjspncu"));
+        assert_eq!(checksum("raw(\u{e9})"), None);
+    }
+
+    #[test]
+    fn adds_and_verifies_checksums() {
+        assert_eq!(
+            with_checksum("raw(deadbeef)").unwrap(),
+            "raw(deadbeef)#89f8spxm"
+        );
+        assert!(with_checksum("raw(deadbeef)#89f8spxm").is_ok());
+        assert!(with_checksum("raw(deadbeef)#89f8spxx").is_err());
+    }
+
+    #[test]
+    fn parses_key_expressions() {
+        let keys = key_expressions(&with_checksum(XPUB_DESC).unwrap()).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].origin.as_deref(), Some("d34db33f/84h/0h/0h"));
+        assert_eq!(keys[0].kind, KeyKind::Extended { private: false });
+        assert_eq!(keys[0].path, ["0"]);
+        assert_eq!(keys[0].wildcard, Some(Wildcard::Unhardened));
+
+        let hardened = key_expressions(&XPUB_DESC.replace("/*", "/*h")).unwrap();
+        assert_eq!(hardened[0].wildcard, Some(Wildcard::Hardened));
+        let hardened = key_expressions(&XPUB_DESC.replace("/*", "/*'")).unwrap();
+        assert_eq!(hardened[0].wildcard, Some(Wildcard::Hardened));
+        assert_eq!(
+            key_expressions(&XPUB_DESC.replace("/*", "/5")).unwrap()[0].wildcard,
+            None
+        );
+
+        // WIF keys are private; a checksum spelling "xprv" is not a key
+        let wif =
+            key_expressions("wpkh(KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn)").unwrap();
+        assert_eq!(wif[0].kind, KeyKind::Wif);
+        assert!(wif[0].is_private());
+        let pubkey = "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5";
+        let keys = key_expressions(&format!("wpkh([d34db33f/0h]{pubkey})#xprvtprv")).unwrap();
+        assert!(!keys[0].is_private());
+        assert!(key_expressions(&format!("wpkh([xprv/0h]{pubkey})")).is_err());
+
+        let nested = format!("tr({pubkey},{{pk({pubkey}),and_v(v:pk({pubkey}),older(144))}})");
+        assert_eq!(key_expressions(&nested).unwrap().len(), 3);
+        let multi = format!(
+            "wsh(sortedmulti(1,{pubkey},{}))",
+            &XPUB_DESC[5..XPUB_DESC.len() - 1]
+        );
+        assert_eq!(key_expressions(&multi).unwrap().len(), 2);
+        assert!(key_expressions("raw(deadbeef)").unwrap().is_empty());
+        assert!(key_expressions("wpkh(notakey)").is_err());
+        assert!(key_expressions(&format!("wpkh({pubkey}/0/*)")).is_err());
+    }
+}
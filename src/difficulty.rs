@@ -0,0 +1,305 @@
+//! Difficulty retarget arithmetic (`blvm chain next-difficulty`)
+//!
+//! Mirrors Core's `CalculateNextWorkRequired` / `GetDifficulty` with an exact 256-bit target.
+
+use crate::chain_params::ChainParams;
+use std::cmp::Ordering;
+
+/// 256-bit unsigned integer, little-endian 64-bit limbs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub fn from_u64(v: u64) -> Self {
+        U256([v, 0, 0, 0])
+    }
+
+    /// From a 32-byte little-endian value (hashes as serialized)
+    pub fn from_le_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().expect("8-byte chunk"));
+        }
+        U256(limbs)
+    }
+
+    /// Number of significant bits
+    pub fn bits(&self) -> u32 {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return 64 * i as u32 + (64 - self.0[i].leading_zeros());
+            }
+        }
+        0
+    }
+
+    pub fn shl(&self, shift: u32) -> Self {
+        let mut out = [0u64; 4];
+        let (limbs, bits) = ((shift / 64) as usize, shift % 64);
+        for i in (limbs..4).rev() {
+            out[i] = self.0[i - limbs] << bits;
+            if bits > 0 && i > limbs {
+                out[i] |= self.0[i - limbs - 1] >> (64 - bits);
+            }
+        }
+        U256(out)
+    }
+
+    pub fn shr(&self, shift: u32) -> Self {
+        let mut out = [0u64; 4];
+        let (limbs, bits) = ((shift / 64) as usize, shift % 64);
+        for (i, o) in out
+            .iter_mut()
+            .enumerate()
+            .take(4usize.saturating_sub(limbs))
+        {
+            *o = self.0[i + limbs] >> bits;
+            if bits > 0 && i + limbs + 1 < 4 {
+                *o |= self.0[i + limbs + 1] << (64 - bits);
+            }
+        }
+        U256(out)
+    }
+
+    /// Multiply by a small factor (wraps like Core's `arith_uint256`)
+    pub fn mul_u64(&self, m: u64) -> Self {
+        let mut out = [0u64; 4];
+        let mut carry = 0u128;
+        for (o, &limb) in out.iter_mut().zip(&self.0) {
+            let product = limb as u128 * m as u128 + carry;
+            *o = product as u64;
+            carry = product >> 64;
+        }
+        U256(out)
+    }
+
+    pub fn div_u64(&self, d: u64) -> Self {
+        let mut out = [0u64; 4];
+        let mut rem = 0u128;
+        for i in (0..4).rev() {
+            let cur = (rem << 64) | self.0[i] as u128;
+            out[i] = (cur / d as u128) as u64;
+            rem = cur % d as u128;
+        }
+        U256(out)
+    }
+
+    /// Decode compact `nBits`; `None` for negative or overflowing encodings
+    pub fn from_compact(bits: u32) -> Option<Self> {
+        let size = bits >> 24;
+        let word = bits & 0x007f_ffff;
+        if word != 0 && bits & 0x0080_0000 != 0 {
+            return None;
+        }
+        if word != 0 && (size > 34 || (word > 0xff && size > 33) || (word > 0xffff && size > 32)) {
+            return None;
+        }
+        Some(if size <= 3 {
+            U256::from_u64((word >> (8 * (3 - size))) as u64)
+        } else {
+            U256::from_u64(word as u64).shl(8 * (size - 3))
+        })
+    }
+
+    /// Encode as compact `nBits`
+    pub fn to_compact(&self) -> u32 {
+        let mut size = self.bits().div_ceil(8);
+        let mut compact = if size <= 3 {
+            (self.0[0] << (8 * (3 - size))) as u32
+        } else {
+            self.shr(8 * (size - 3)).0[0] as u32
+        };
+        if compact & 0x0080_0000 != 0 {
+            compact >>= 8;
+            size += 1;
+        }
+        compact | (size << 24)
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+/// Difficulty relative to the minimum (`getdifficulty`)
+pub fn difficulty_from_bits(bits: u32) -> f64 {
+    let mut shift = (bits >> 24) & 0xff;
+    let mut diff = 0x0000_ffff as f64 / (bits & 0x00ff_ffff) as f64;
+    while shift < 29 {
+        diff *= 256.0;
+        shift += 1;
+    }
+    while shift > 29 {
+        diff /= 256.0;
+        shift -= 1;
+    }
+    diff
+}
+
+/// Whether `hash` meets the target encoded in `bits` (Core's `CheckProofOfWork` without the
+/// pow-limit bound)
+pub fn check_proof_of_work(hash: &[u8; 32], bits: u32) -> bool {
+    match U256::from_compact(bits) {
+        Some(target) if target != U256::default() => U256::from_le_bytes(hash) <= target,
+        _ => false,
+    }
+}
+
+/// Next `nBits` after a retarget period from `first_time` to `last_time` at `last_bits`
+pub fn next_work_required(
+    params: &ChainParams,
+    last_bits: u32,
+    first_time: i64,
+    last_time: i64,
+) -> u32 {
+    if params.no_retargeting {
+        return last_bits;
+    }
+    let timespan = params.target_timespan as i64;
+    let actual = (last_time - first_time).clamp(timespan / 4, timespan * 4);
+    let pow_limit = U256::from_compact(params.pow_limit_bits).unwrap_or_default();
+    let target = U256::from_compact(last_bits)
+        .unwrap_or(pow_limit)
+        .mul_u64(actual as u64)
+        .div_u64(timespan as u64);
+    target.min(pow_limit).to_compact()
+}
+
+/// Projection of the next adjustment from the current period so far
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetargetEstimate {
+    /// Height of the first block with the new difficulty
+    pub retarget_height: u64,
+    pub blocks_remaining: u64,
+    pub seconds_remaining: u64,
+    pub next_bits: u32,
+    /// Percentage change in difficulty (positive = harder)
+    pub change_percent: f64,
+}
+
+/// Estimate the next retarget given the period's first block time and the tip.
+///
+/// At the last block of a period this is exact; earlier it extrapolates the average
+/// block interval so far over the whole period.
+pub fn estimate_retarget(
+    params: &ChainParams,
+    tip_height: u64,
+    tip_time: i64,
+    tip_bits: u32,
+    period_first_time: i64,
+) -> RetargetEstimate {
+    let interval = params.retarget_interval as u64;
+    let period_start = tip_height - tip_height % interval;
+    let retarget_height = period_start + interval;
+    let intervals_so_far = tip_height - period_start;
+    let elapsed = (tip_time - period_first_time).max(0);
+    let avg_spacing = if intervals_so_far == 0 {
+        params.target_spacing as f64
+    } else {
+        elapsed as f64 / intervals_so_far as f64
+    };
+    let projected_last_time =
+        period_first_time + (avg_spacing * (interval - 1) as f64).round() as i64;
+    let next_bits = next_work_required(params, tip_bits, period_first_time, projected_last_time);
+    let blocks_remaining = retarget_height - tip_height;
+    RetargetEstimate {
+        retarget_height,
+        blocks_remaining,
+        seconds_remaining: (avg_spacing * blocks_remaining as f64).round() as u64,
+        next_bits,
+        change_percent: (difficulty_from_bits(next_bits) / difficulty_from_bits(tip_bits) - 1.0)
+            * 100.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mainnet() -> ChainParams {
+        ChainParams::for_network("mainnet").unwrap()
+    }
+
+    /// Vectors from Bitcoin Core's `pow_tests.cpp`
+    #[test]
+    fn matches_core_pow_tests() {
+        let p = mainnet();
+        // get_next_work: blocks 30240..32255
+        assert_eq!(
+            next_work_required(&p, 0x1d00ffff, 1261130161, 1262152739),
+            0x1d00d86a
+        );
+        // get_next_work_pow_limit
+        assert_eq!(
+            next_work_required(&p, 0x1d00ffff, 1231006505, 1233061996),
+            0x1d00ffff
+        );
+        // get_next_work_lower_limit_actual
+        assert_eq!(
+            next_work_required(&p, 0x1c05a3f4, 1279008237, 1279297671),
+            0x1c0168fd
+        );
+        // get_next_work_upper_limit_actual
+        assert_eq!(
+            next_work_required(&p, 0x1c387f6f, 1263163443, 1269211443),
+            0x1d00e1fd
+        );
+    }
+
+    #[test]
+    fn compact_round_trip() {
+        for bits in [0x1d00ffff, 0x1c05a3f4, 0x207fffff, 0x1e0377ae, 0x17053894] {
+            assert_eq!(U256::from_compact(bits).unwrap().to_compact(), bits);
+        }
+        assert!(U256::from_compact(0x04923456).is_none()); // negative
+        assert!(U256::from_compact(0xff123456).is_none()); // overflow
+        assert_eq!(difficulty_from_bits(0x1d00ffff), 1.0);
+    }
+
+    #[test]
+    fn genesis_meets_its_target() {
+        let mut hash = [0u8; 32];
+        hex::decode_to_slice(
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+            &mut hash,
+        )
+        .unwrap();
+        hash.reverse();
+        assert!(check_proof_of_work(&hash, 0x1d00ffff));
+        assert!(!check_proof_of_work(&hash, 0x1b00ffff));
+    }
+
+    #[test]
+    fn estimate_mid_period() {
+        let p = mainnet();
+        // Half way through a period at exactly 10-minute blocks: unchanged difficulty
+        let estimate = estimate_retarget(
+            &p,
+            2016 * 400 + 1008,
+            1_000_000 + 1008 * 600,
+            0x17053894,
+            1_000_000,
+        );
+        assert_eq!(estimate.retarget_height, 2016 * 401);
+        assert_eq!(estimate.blocks_remaining, 1008);
+        assert_eq!(estimate.seconds_remaining, 1008 * 600);
+        assert!(estimate.change_percent.abs() < 0.1, "{estimate:?}");
+        // Blocks twice as fast: difficulty roughly doubles
+        let fast = estimate_retarget(
+            &p,
+            2016 * 400 + 1008,
+            1_000_000 + 1008 * 300,
+            0x17053894,
+            1_000_000,
+        );
+        assert!((fast.change_percent - 100.0).abs() < 1.0, "{fast:?}");
+    }
+}
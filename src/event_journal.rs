@@ -0,0 +1,168 @@
+//! Sequenced journal of notification events (`<data_dir>/event-journal.jsonl`)
+//!
+//! Every line written to `[notifications]` clients carries a `seq` number and is appended here
+//! first, so a consumer that was down can reconnect, send `{"replay_from": <last seq it
+//! handled>}` and receive everything it missed before live events resume. Delivery is
+//! at-least-once: a line may arrive both in the replay and live, and consumers dedupe on `seq`.
+//! Only the last `journal_max_events` lines are kept; a replay reaching further back starts with
+//! a `gap` line naming the lost range.
+
+use crate::notifications::Notification;
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Journal file name in the data directory
+pub const JOURNAL_FILE: &str = "event-journal.jsonl";
+
+/// Appended, sequenced notification lines; the retained tail is also held in memory
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    max_events: usize,
+    /// Lines in the file, retained or not
+    file_lines: usize,
+    entries: VecDeque<(u64, String)>,
+    last_seq: u64,
+}
+
+impl Journal {
+    /// Open the journal in `data_dir`, continuing its sequence
+    pub fn open(data_dir: &Path, max_events: usize) -> Self {
+        let path = data_dir.join(JOURNAL_FILE);
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut journal = Self {
+            path,
+            max_events: max_events.max(1),
+            file_lines: 0,
+            entries: VecDeque::new(),
+            last_seq: 0,
+        };
+        for line in content.lines() {
+            journal.file_lines += 1;
+            let Some(seq) = serde_json::from_str::<Value>(line)
+                .ok()
+                .and_then(|v| v.get("seq")?.as_u64())
+            else {
+                continue;
+            };
+            journal.last_seq = journal.last_seq.max(seq);
+            journal.retain(seq, format!("{line}\n"));
+        }
+        journal
+    }
+
+    /// Sequence number of the newest event (0 = none yet)
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    fn retain(&mut self, seq: u64, line: String) {
+        self.entries.push_back((seq, line));
+        while self.entries.len() > self.max_events {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Number and persist `events`; returns their lines, in order
+    pub fn append(&mut self, events: &[Notification]) -> io::Result<String> {
+        let mut bytes = String::new();
+        for event in events {
+            self.last_seq += 1;
+            let mut value = event.to_value();
+            value["seq"] = json!(self.last_seq);
+            let line = format!("{value}\n");
+            bytes.push_str(&line);
+            self.retain(self.last_seq, line);
+        }
+        if self.file_lines + events.len() > 2 * self.max_events {
+            // Rewrite with only the retained tail, which already includes `events`
+            let kept: String = self.entries.iter().map(|(_, l)| l.as_str()).collect();
+            let tmp = self.path.with_extension("jsonl.tmp");
+            std::fs::write(&tmp, kept)?;
+            std::fs::rename(&tmp, &self.path)?;
+            self.file_lines = self.entries.len();
+        } else {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?
+                .write_all(bytes.as_bytes())?;
+            self.file_lines += events.len();
+        }
+        Ok(bytes)
+    }
+
+    /// Lines with a sequence number above `acked`, preceded by a `gap` line when some of them
+    /// are no longer retained
+    pub fn replay(&self, acked: u64) -> String {
+        let mut bytes = String::new();
+        let oldest = self
+            .entries
+            .front()
+            .map_or(self.last_seq + 1, |(seq, _)| *seq);
+        if acked + 1 < oldest {
+            let gap = json!({"type": "gap", "from": acked + 1, "to": oldest - 1});
+            bytes.push_str(&format!("{gap}\n"));
+        }
+        for (_, line) in self.entries.iter().filter(|(seq, _)| *seq > acked) {
+            bytes.push_str(line);
+        }
+        bytes
+    }
+}
+
+/// The acknowledged sequence number in a client's `{"replay_from": N}` line
+pub fn parse_replay_request(line: &str) -> Option<u64> {
+    serde_json::from_str::<Value>(line)
+        .ok()?
+        .get("replay_from")?
+        .as_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(height: u64) -> Notification {
+        Notification::Block {
+            hash: format!("{height:064x}"),
+            height,
+            time: 1_700_000_000 + height,
+        }
+    }
+
+    #[test]
+    fn sequences_persist_and_replay_after_ack() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut journal = Journal::open(dir.path(), 3);
+        let lines = journal.append(&[block(1), block(2)]).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        assert!(lines.contains("\"seq\":2"));
+        journal.append(&[block(3)]).unwrap();
+
+        // Reopening continues the sequence and keeps the tail
+        let mut journal = Journal::open(dir.path(), 3);
+        assert_eq!(journal.last_seq(), 3);
+        assert_eq!(journal.replay(1).lines().count(), 2);
+        assert_eq!(journal.replay(3), "");
+
+        // Past retention the replay starts with the lost range
+        for height in 4..=8 {
+            journal.append(&[block(height)]).unwrap();
+        }
+        let replay = journal.replay(2);
+        let first: Value = serde_json::from_str(replay.lines().next().unwrap()).unwrap();
+        assert_eq!(first, json!({"type": "gap", "from": 3, "to": 5}));
+        assert_eq!(replay.lines().count(), 4);
+        // Compaction bounded the file
+        let reopened = Journal::open(dir.path(), 3);
+        assert_eq!(reopened.last_seq(), 8);
+        assert!(reopened.file_lines <= 6);
+
+        assert_eq!(parse_replay_request("{\"replay_from\": 41}"), Some(41));
+        assert_eq!(parse_replay_request("hello"), None);
+    }
+}
@@ -0,0 +1,280 @@
+//! Operator event log (`<data_dir>/events.jsonl`, `blvm events tail`)
+//!
+//! One JSON object per line for significant node events — new tips, reorgs, bans, restarts.
+//! Separate from tracing output: it is meant to be read by operators and tools, so it is
+//! rate limited per event kind and rotated by size.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Log file name in the data directory
+pub const EVENTS_FILE: &str = "events.jsonl";
+
+/// `[events]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EventLogConfig {
+    pub enabled: bool,
+    /// Rotate when the file would exceed this size
+    pub max_bytes: u64,
+    /// Rotated files kept (`events.jsonl.1` is the newest)
+    pub keep: usize,
+    /// Events per kind per minute; the excess is summarized in an `events.suppressed` event
+    pub rate_per_minute: u32,
+}
+
+impl Default for EventLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_bytes: 10 * 1024 * 1024,
+            keep: 5,
+            rate_per_minute: 60,
+        }
+    }
+}
+
+/// One logged event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Event {
+    /// Unix time
+    pub time: u64,
+    /// Dotted kind, e.g. `chain.tip`, `chain.reorg`, `peer.ban`, `node.start`
+    pub kind: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, Value>,
+}
+
+impl Event {
+    pub fn new(kind: &str, message: impl Into<String>) -> Self {
+        Event {
+            time: crate::mocktime::unix_now(),
+            kind: kind.to_string(),
+            message: message.into(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.fields.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Human-readable line: `2009-01-03 18:15:05 chain.tip  message key=value`
+    pub fn render(&self) -> String {
+        let mut line = format!(
+            "{} {:<16} {}",
+            format_utc(self.time),
+            self.kind,
+            self.message
+        );
+        for (key, value) in &self.fields {
+            match value {
+                Value::String(s) => line.push_str(&format!(" {key}={s}")),
+                other => line.push_str(&format!(" {key}={other}")),
+            }
+        }
+        line
+    }
+}
+
+/// `YYYY-MM-DD HH:MM:SS` (UTC) for a Unix timestamp
+pub fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Appending writer with per-kind rate limiting and size-based rotation
+pub struct EventLog {
+    path: PathBuf,
+    config: EventLogConfig,
+    /// kind -> (minute, events written this minute, events dropped this minute)
+    windows: HashMap<String, (u64, u32, u64)>,
+}
+
+impl EventLog {
+    pub fn new(data_dir: &Path, config: EventLogConfig) -> Self {
+        EventLog {
+            path: data_dir.join(EVENTS_FILE),
+            config,
+            windows: HashMap::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `event` unless its kind is over the rate limit
+    pub fn record(&mut self, event: Event) -> io::Result<()> {
+        let minute = event.time / 60;
+        let limit = self.config.rate_per_minute;
+        let window = self
+            .windows
+            .entry(event.kind.clone())
+            .or_insert((minute, 0, 0));
+        let mut suppressed = None;
+        if window.0 != minute {
+            if window.2 > 0 {
+                suppressed = Some(window.2);
+            }
+            *window = (minute, 0, 0);
+        }
+        let admitted = window.1 < limit;
+        if admitted {
+            window.1 += 1;
+        } else {
+            window.2 += 1;
+        }
+        if let Some(dropped) = suppressed {
+            let summary = Event {
+                time: event.time,
+                kind: "events.suppressed".to_string(),
+                message: format!("{dropped} {} events dropped by rate limit", event.kind),
+                fields: BTreeMap::from([
+                    ("dropped".to_string(), Value::from(dropped)),
+                    ("for_kind".to_string(), Value::from(event.kind.clone())),
+                ]),
+            };
+            self.append(&summary)?;
+        }
+        if admitted {
+            self.append(&event)?;
+        }
+        Ok(())
+    }
+
+    fn append(&self, event: &Event) -> io::Result<()> {
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        let size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.config.max_bytes {
+            self.rotate()?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        let rotated = |n: usize| PathBuf::from(format!("{}.{n}", self.path.display()));
+        if self.config.keep == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+        let _ = std::fs::remove_file(rotated(self.config.keep));
+        for n in (1..self.config.keep).rev() {
+            if rotated(n).exists() {
+                std::fs::rename(rotated(n), rotated(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, rotated(1))
+    }
+}
+
+/// Parse log lines, skipping anything that is not an event
+pub fn parse_lines(content: &str) -> Vec<Event> {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: &str, time: u64) -> Event {
+        Event {
+            time,
+            ..Event::new(kind, "test")
+        }
+    }
+
+    #[test]
+    fn formats_utc_dates() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00");
+        assert_eq!(format_utc(1_231_006_505), "2009-01-03 18:15:05");
+        assert_eq!(format_utc(1_709_210_096), "2024-02-29 12:34:56");
+    }
+
+    #[test]
+    fn renders_fields() {
+        let e = event("chain.tip", 1_231_006_505)
+            .with("height", 0)
+            .with("hash", "000000000019d668");
+        assert_eq!(
+            e.render(),
+            "2009-01-03 18:15:05 chain.tip        test hash=000000000019d668 height=0"
+        );
+    }
+
+    #[test]
+    fn rate_limits_per_kind_and_summarizes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = EventLogConfig {
+            rate_per_minute: 2,
+            ..Default::default()
+        };
+        let mut log = EventLog::new(dir.path(), config);
+        for _ in 0..5 {
+            log.record(event("peer.ban", 60)).unwrap();
+        }
+        log.record(event("chain.tip", 60)).unwrap();
+        log.record(event("peer.ban", 120)).unwrap();
+        let events = parse_lines(&std::fs::read_to_string(log.path()).unwrap());
+        let kinds: Vec<&str> = events.iter().map(|e| e.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            [
+                "peer.ban",
+                "peer.ban",
+                "chain.tip",
+                "events.suppressed",
+                "peer.ban"
+            ]
+        );
+        assert_eq!(events[3].fields["dropped"], 3);
+    }
+
+    #[test]
+    fn rotates_by_size() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = EventLogConfig {
+            max_bytes: 200,
+            keep: 2,
+            ..Default::default()
+        };
+        let mut log = EventLog::new(dir.path(), config);
+        for i in 0..20 {
+            log.record(event("chain.tip", 60 * i)).unwrap();
+        }
+        let rotated = |n| dir.path().join(format!("{EVENTS_FILE}.{n}"));
+        assert!(rotated(1).exists());
+        assert!(rotated(2).exists());
+        assert!(!rotated(3).exists());
+        assert!(std::fs::metadata(log.path()).unwrap().len() <= 200);
+    }
+}
@@ -0,0 +1,120 @@
+//! Settings the `blvm` binary reads from the node config file in addition to `NodeConfig`
+//!
+//! blvm-node ignores these keys; they control behaviour implemented in this crate.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Binary-level settings from `blvm.toml` / `blvm.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ExtraConfig {
+    /// `listen = false`: outbound-only mode (no inbound P2P listener)
+    pub listen: Option<bool>,
+    /// `[addr_relay]`: self-advertisement policy
+    pub addr_relay: crate::addr_relay::AddrRelayConfig,
+    /// `[revalidation]`: background re-checks of stored blocks
+    pub revalidation: crate::revalidation::RevalidationConfig,
+    /// `[quarantine]`: keep blocks the node rejects for later analysis
+    pub quarantine: crate::quarantine::QuarantineConfig,
+    /// `[events]`: operator event log (`events.jsonl`)
+    pub events: crate::events::EventLogConfig,
+    /// `log_level`: log filter directives (e.g. `"blvm=debug"`), reloaded on SIGHUP
+    pub log_level: Option<String>,
+    /// `[anomalies]`: timestamp and difficulty warnings for new tip blocks
+    pub anomalies: crate::block_anomalies::AnomalyConfig,
+    /// `[alerts]`: local alert rules with webhook/exec notifications
+    pub alerts: crate::alerts::AlertsConfig,
+    /// `[chain_split]`: warn when peers follow a competing chain with comparable work
+    pub chain_split: crate::chain_split::ChainSplitConfig,
+    /// `[fee_history]`: per-block fee stats and mempool snapshots (`getfeehistory`)
+    pub fee_history: crate::fee_history::FeeHistoryConfig,
+    /// `[fleet]`: authenticated admin channel (`blvm fleet exec`)
+    pub fleet: crate::fleet::FleetConfig,
+    /// `[mining]`: payout address rotation for the template module
+    pub mining: crate::mining::MiningConfig,
+    /// `[inbound_limits]`: inbound connections per IP and per subnet
+    pub inbound_limits: crate::peer_limits::InboundLimitsConfig,
+    /// `seeds_file`: fixed seeds (`blvm seeds export` output) tried when connections are low
+    pub seeds_file: Option<std::path::PathBuf>,
+    /// `[discovery]`: LAN peer discovery over mDNS
+    pub discovery: crate::mdns::DiscoveryConfig,
+    /// `[network.timeouts]`: keepalive pings and peer timeouts (the rest of `[network]` is the
+    /// node's)
+    pub network: crate::peer_timeouts::NetworkSection,
+    /// `[notifications]`: NDJSON chain events on a unix socket or named pipe
+    pub notifications: crate::notifications::NotificationsConfig,
+    /// `[peer_policy]`: minimum peer protocol version and user-agent filters
+    pub peer_policy: crate::peer_policy::PeerPolicyConfig,
+    /// `[probes]`: `/healthz` and `/readyz` for Kubernetes
+    pub probes: crate::probes::ProbesConfig,
+    /// `[replica]`: follow a trusted primary as a read-only RPC replica
+    pub replica: crate::replica::ReplicaConfig,
+    /// `rpc_user`: user name CLI commands send to the node's RPC (`BLVM_RPC_USER` overrides it)
+    pub rpc_user: Option<String>,
+    /// `rpc_password`: password CLI commands send to the node's RPC (`BLVM_RPC_PASSWORD`
+    /// overrides it)
+    pub rpc_password: Option<String>,
+    /// `[rpc_front]`: RPC front listener with a response cache for expensive reads
+    pub rpc_front: crate::rpc_front::RpcFrontConfig,
+    /// `[script_stats]`: output script type totals per block (`blvm stats scripts`)
+    pub script_stats: crate::script_stats::ScriptStatsConfig,
+    /// `shutdown_timeout_secs`: how long a signal-initiated shutdown may drain (default 30)
+    pub shutdown_timeout_secs: Option<u64>,
+    /// `[[persistent_peer]]`: persistent peers with per-peer options
+    #[serde(rename = "persistent_peer")]
+    pub persistent_peers: Vec<crate::persistent_peers::PersistentPeer>,
+}
+
+impl ExtraConfig {
+    /// Load from a TOML or JSON config file (format chosen by extension, like `NodeConfig`)
+    pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read config file: {}", e))?;
+        Self::from_str_with_ext(&content, path.extension().and_then(|e| e.to_str()))
+    }
+
+    fn from_str_with_ext(content: &str, ext: Option<&str>) -> anyhow::Result<Self> {
+        if ext == Some("json") {
+            serde_json::from_str(content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse config file: {}", e))
+        } else {
+            toml::from_str(content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse config file: {}", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_node_config_keys() {
+        let extra = ExtraConfig::from_str_with_ext(
+            "max_peers = 50\nlisten = false\n[modules]\nenabled = true\n",
+            Some("toml"),
+        )
+        .unwrap();
+        assert_eq!(extra.listen, Some(false));
+        assert!(!extra.revalidation.enabled);
+    }
+
+    #[test]
+    fn revalidation_section() {
+        let extra = ExtraConfig::from_str_with_ext(
+            "[revalidation]\nenabled = true\nblocks_per_hour = 60\n",
+            Some("toml"),
+        )
+        .unwrap();
+        assert!(extra.revalidation.enabled);
+        assert_eq!(extra.revalidation.interval().as_secs(), 60);
+    }
+
+    #[test]
+    fn json_and_defaults() {
+        let extra = ExtraConfig::from_str_with_ext(r#"{"max_peers": 50}"#, Some("json")).unwrap();
+        assert_eq!(extra, ExtraConfig::default());
+    }
+}
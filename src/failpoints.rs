@@ -0,0 +1,189 @@
+//! Named fault-injection points for tests (feature `failpoints`)
+//!
+//! Configure with `BLVM_FAILPOINTS="name=action;name=action"`, where action is
+//! `off`, `return`, `return(msg)`, `panic`, `sleep(ms)`, optionally prefixed by a
+//! count (`2*return` fires twice, then turns off). Without the feature,
+//! [`fail_point!`](crate::fail_point) compiles to nothing.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Environment variable read by [`init_from_env`]
+pub const FAILPOINTS_ENV: &str = "BLVM_FAILPOINTS";
+
+/// What a failpoint does when reached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailAction {
+    Off,
+    /// Make the enclosing function return an error
+    Return(Option<String>),
+    Panic,
+    Sleep(u64),
+}
+
+/// An action plus how many more times it fires (`None` = always)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailConfig {
+    pub action: FailAction,
+    pub remaining: Option<u32>,
+}
+
+impl FailConfig {
+    /// Parse `[N*]action`
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let (remaining, action) = match s.split_once('*') {
+            Some((count, action)) => (
+                Some(
+                    count
+                        .trim()
+                        .parse::<u32>()
+                        .map_err(|_| format!("invalid count in '{s}'"))?,
+                ),
+                action.trim(),
+            ),
+            None => (None, s),
+        };
+        let (name, arg) = match action.split_once('(') {
+            Some((name, rest)) => (
+                name,
+                Some(
+                    rest.strip_suffix(')')
+                        .ok_or_else(|| format!("unclosed '(' in '{s}'"))?,
+                ),
+            ),
+            None => (action, None),
+        };
+        let action = match (name, arg) {
+            ("off", None) => FailAction::Off,
+            ("return", arg) => FailAction::Return(arg.map(str::to_string)),
+            ("panic", None) => FailAction::Panic,
+            ("sleep", Some(ms)) => FailAction::Sleep(
+                ms.parse()
+                    .map_err(|_| format!("invalid sleep duration in '{s}'"))?,
+            ),
+            _ => return Err(format!("unknown failpoint action '{s}'")),
+        };
+        Ok(Self { action, remaining })
+    }
+}
+
+/// Parse `name=action;name=action`
+pub fn parse_spec(spec: &str) -> Result<Vec<(String, FailConfig)>, String> {
+    spec.split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, action) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected name=action, got '{entry}'"))?;
+            Ok((name.trim().to_string(), FailConfig::parse(action)?))
+        })
+        .collect()
+}
+
+fn registry() -> &'static Mutex<HashMap<String, FailConfig>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, FailConfig>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Configure (or with [`FailAction::Off`], clear) a failpoint
+pub fn set(name: &str, config: FailConfig) {
+    let mut points = registry().lock().unwrap_or_else(|e| e.into_inner());
+    if config.action == FailAction::Off {
+        points.remove(name);
+    } else {
+        points.insert(name.to_string(), config);
+    }
+}
+
+/// Clear every failpoint
+pub fn clear_all() {
+    registry().lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Load failpoints from `BLVM_FAILPOINTS`; returns how many were configured.
+pub fn init_from_env() -> Result<usize, String> {
+    let Ok(spec) = std::env::var(FAILPOINTS_ENV) else {
+        return Ok(0);
+    };
+    let points = parse_spec(&spec)?;
+    let count = points.len();
+    for (name, config) in points {
+        set(&name, config);
+    }
+    Ok(count)
+}
+
+/// Evaluate a failpoint: sleeps or panics in place; `Some(msg)` means "return an error".
+pub fn eval(name: &str) -> Option<String> {
+    let action = {
+        let mut points = registry().lock().unwrap_or_else(|e| e.into_inner());
+        let config = points.get_mut(name)?;
+        let action = config.action.clone();
+        if let Some(remaining) = config.remaining.as_mut() {
+            *remaining -= 1;
+            if *remaining == 0 {
+                points.remove(name);
+            }
+        }
+        action
+    };
+    match action {
+        FailAction::Off => None,
+        FailAction::Return(msg) => Some(msg.unwrap_or_else(|| "injected failure".to_string())),
+        FailAction::Panic => panic!("failpoint {name} panicked"),
+        FailAction::Sleep(ms) => {
+            std::thread::sleep(Duration::from_millis(ms));
+            None
+        }
+    }
+}
+
+/// Named fault-injection point in a function returning `anyhow::Result`.
+///
+/// Expands to nothing unless the `failpoints` feature is enabled.
+#[macro_export]
+macro_rules! fail_point {
+    ($name:expr) => {
+        #[cfg(feature = "failpoints")]
+        if let Some(msg) = $crate::failpoints::eval($name) {
+            return Err(anyhow::anyhow!("failpoint {}: {}", $name, msg));
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_actions() {
+        assert_eq!(
+            FailConfig::parse("2*return(disk full)").unwrap(),
+            FailConfig {
+                action: FailAction::Return(Some("disk full".to_string())),
+                remaining: Some(2)
+            }
+        );
+        assert_eq!(
+            FailConfig::parse("sleep(250)").unwrap().action,
+            FailAction::Sleep(250)
+        );
+        assert!(FailConfig::parse("explode").is_err());
+        assert!(FailConfig::parse("sleep(abc)").is_err());
+        let spec = parse_spec("storage.write=panic; rpc.dispatch=return;").unwrap();
+        assert_eq!(spec.len(), 2);
+        assert!(parse_spec("no-equals").is_err());
+    }
+
+    #[test]
+    fn counted_failpoints_turn_off() {
+        set("test.counted", FailConfig::parse("2*return").unwrap());
+        assert!(eval("test.counted").is_some());
+        assert!(eval("test.counted").is_some());
+        assert!(eval("test.counted").is_none());
+        assert!(eval("test.unset").is_none());
+    }
+}
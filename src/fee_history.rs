@@ -0,0 +1,507 @@
+//! Historical fee market archive (`[fee_history]`, `getfeehistory`, `blvm stats fees`)
+//!
+//! While the node runs, a collector appends each block's fee statistics (from `getblockstats`)
+//! and, every `snapshot_interval_secs`, a mempool fee-rate snapshot to fixed-size binary records
+//! under `<data_dir>/fee-history/`:
+//!
+//! - `blocks.dat`: the first height (8 bytes), then one record per consecutive height; a reorg
+//!   truncates the file back to the fork
+//! - `mempool.dat`: snapshots in time order, bucketed like `mempool_histogram`
+//!
+//! Records are looked up by offset (blocks) or binary search (snapshots), so a range query reads
+//! only the records it returns. `getfeehistory` is answered by the RPC front, not by blvm-node.
+
+use crate::mempool_histogram::{FEE_RATE_BOUNDS, MempoolHistogram};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Archive directory in the data directory
+pub const ARCHIVE_DIR: &str = "fee-history";
+const BLOCKS_FILE: &str = "blocks.dat";
+const MEMPOOL_FILE: &str = "mempool.dat";
+
+/// Header of `blocks.dat`: the height of the first record
+const BLOCKS_HEADER: u64 = 8;
+const BLOCK_RECORD: u64 = 32 + 4 + 4 + 4 + 8 + 4 * FEE_RATE_POINTS as u64;
+const BUCKETS: usize = FEE_RATE_BOUNDS.len() + 1;
+const SNAPSHOT_RECORD: u64 = 8 + 4 + 8 + 8 + 8 * BUCKETS as u64;
+
+/// Fee rates kept per block: min, 10th/25th/50th/75th/90th percentile, max, average
+const FEE_RATE_POINTS: usize = 8;
+
+/// `[fee_history]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FeeHistoryConfig {
+    pub enabled: bool,
+    /// Seconds between mempool snapshots (0 = blocks only)
+    pub snapshot_interval_secs: u64,
+}
+
+impl Default for FeeHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            snapshot_interval_secs: 600,
+        }
+    }
+}
+
+/// Fee statistics of one block (fee rates in sat/vB, fees in satoshis)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockFees {
+    pub hash: [u8; 32],
+    pub time: u32,
+    pub txs: u32,
+    pub vsize: u32,
+    pub total_fee: u64,
+    /// min, p10, p25, p50, p75, p90, max, avg
+    pub fee_rates: [u32; FEE_RATE_POINTS],
+}
+
+impl BlockFees {
+    /// From a `getblockstats` result
+    pub fn from_stats(stats: &Value) -> Option<Self> {
+        let u64_of = |key: &str| stats.get(key).and_then(|v| v.as_u64());
+        let mut hash = [0u8; 32];
+        hex::decode_to_slice(stats.get("blockhash")?.as_str()?, &mut hash).ok()?;
+        let percentiles = stats.get("feerate_percentiles").and_then(|v| v.as_array());
+        let percentile = |i: usize| {
+            percentiles
+                .and_then(|p| p.get(i))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0)
+        };
+        let rates = [
+            u64_of("minfeerate").unwrap_or(0),
+            percentile(0),
+            percentile(1),
+            percentile(2),
+            percentile(3),
+            percentile(4),
+            u64_of("maxfeerate").unwrap_or(0),
+            u64_of("avgfeerate").unwrap_or(0),
+        ];
+        Some(Self {
+            hash,
+            time: u64_of("time")? as u32,
+            txs: u64_of("txs")? as u32,
+            vsize: u64_of("total_weight").unwrap_or(0).div_ceil(4) as u32,
+            total_fee: u64_of("totalfee").unwrap_or(0),
+            fee_rates: rates.map(|r| r.min(u32::MAX as u64) as u32),
+        })
+    }
+
+    pub fn hash_hex(&self) -> String {
+        hex::encode(self.hash)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(BLOCK_RECORD as usize);
+        out.extend_from_slice(&self.hash);
+        out.extend_from_slice(&self.time.to_le_bytes());
+        out.extend_from_slice(&self.txs.to_le_bytes());
+        out.extend_from_slice(&self.vsize.to_le_bytes());
+        out.extend_from_slice(&self.total_fee.to_le_bytes());
+        for rate in self.fee_rates {
+            out.extend_from_slice(&rate.to_le_bytes());
+        }
+        out
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let mut reader = Fields(bytes);
+        Self {
+            hash: reader.take(),
+            time: u32::from_le_bytes(reader.take()),
+            txs: u32::from_le_bytes(reader.take()),
+            vsize: u32::from_le_bytes(reader.take()),
+            total_fee: u64::from_le_bytes(reader.take()),
+            fee_rates: std::array::from_fn(|_| u32::from_le_bytes(reader.take())),
+        }
+    }
+
+    fn to_json(self, height: u64) -> Value {
+        let [min, p10, p25, p50, p75, p90, max, avg] = self.fee_rates;
+        json!({
+            "height": height,
+            "hash": self.hash_hex(),
+            "time": self.time,
+            "txs": self.txs,
+            "vsize": self.vsize,
+            "totalfee": self.total_fee,
+            "minfeerate": min,
+            "feerate_percentiles": [p10, p25, p50, p75, p90],
+            "maxfeerate": max,
+            "avgfeerate": avg,
+        })
+    }
+}
+
+/// Mempool fee rates at one moment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Unix time
+    pub time: u64,
+    pub count: u32,
+    pub vsize: u64,
+    pub fees: u64,
+    /// vsize per `mempool_histogram` bucket, lowest fee rate first
+    pub buckets: [u64; BUCKETS],
+}
+
+impl Snapshot {
+    pub fn from_histogram(time: u64, histogram: &MempoolHistogram) -> Self {
+        let rows = histogram.rows();
+        Self {
+            time,
+            count: histogram.len() as u32,
+            vsize: rows.iter().map(|r| r.bucket.vsize).sum(),
+            fees: rows.iter().map(|r| r.bucket.fees).sum(),
+            buckets: std::array::from_fn(|i| rows.get(i).map_or(0, |r| r.bucket.vsize)),
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(SNAPSHOT_RECORD as usize);
+        out.extend_from_slice(&self.time.to_le_bytes());
+        out.extend_from_slice(&self.count.to_le_bytes());
+        out.extend_from_slice(&self.vsize.to_le_bytes());
+        out.extend_from_slice(&self.fees.to_le_bytes());
+        for vsize in self.buckets {
+            out.extend_from_slice(&vsize.to_le_bytes());
+        }
+        out
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let mut reader = Fields(bytes);
+        Self {
+            time: u64::from_le_bytes(reader.take()),
+            count: u32::from_le_bytes(reader.take()),
+            vsize: u64::from_le_bytes(reader.take()),
+            fees: u64::from_le_bytes(reader.take()),
+            buckets: std::array::from_fn(|_| u64::from_le_bytes(reader.take())),
+        }
+    }
+
+    /// `fee_histogram` as in `getmempoolhistogram`: `[fee rate, vsize]`, highest fee rate first
+    fn to_json(self) -> Value {
+        let histogram: Vec<Value> = self
+            .buckets
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, vsize)| **vsize > 0)
+            .map(|(i, vsize)| {
+                let from = if i == 0 { 0.0 } else { FEE_RATE_BOUNDS[i - 1] };
+                json!([from, vsize])
+            })
+            .collect();
+        json!({
+            "time": self.time,
+            "count": self.count,
+            "vsize": self.vsize,
+            "fees": self.fees,
+            "fee_histogram": histogram,
+        })
+    }
+}
+
+/// Sequential fixed-width field reader over a record
+struct Fields<'a>(&'a [u8]);
+
+impl Fields<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let (field, rest) = self.0.split_at(N);
+        self.0 = rest;
+        field.try_into().expect("record length checked by caller")
+    }
+}
+
+/// The archive directory of a data directory
+pub struct Archive {
+    dir: PathBuf,
+}
+
+impl Archive {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            dir: data_dir.join(ARCHIVE_DIR),
+        }
+    }
+
+    fn open(&self, name: &str, write: bool) -> io::Result<File> {
+        if write {
+            std::fs::create_dir_all(&self.dir)?;
+        }
+        OpenOptions::new()
+            .read(true)
+            .write(write)
+            .create(write)
+            .truncate(false)
+            .open(self.dir.join(name))
+    }
+
+    /// First height and number of block records; `None` while empty
+    fn block_span(file: &mut File) -> io::Result<Option<(u64, u64)>> {
+        let len = file.metadata()?.len();
+        if len < BLOCKS_HEADER {
+            return Ok(None);
+        }
+        let mut first = [0u8; 8];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_exact(&mut first)?;
+        let records = (len - BLOCKS_HEADER) / BLOCK_RECORD;
+        Ok((records > 0).then_some((u64::from_le_bytes(first), records)))
+    }
+
+    /// Heights held, inclusive
+    pub fn block_range(&self) -> Option<(u64, u64)> {
+        let mut file = self.open(BLOCKS_FILE, false).ok()?;
+        let (first, records) = Self::block_span(&mut file).ok()??;
+        Some((first, first + records - 1))
+    }
+
+    /// Blocks from `from` to `to` (inclusive) that the archive holds
+    pub fn blocks(&self, from: u64, to: u64) -> io::Result<Vec<(u64, BlockFees)>> {
+        let mut file = match self.open(BLOCKS_FILE, false) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let Some((first, records)) = Self::block_span(&mut file)? else {
+            return Ok(Vec::new());
+        };
+        let (start, end) = (from.max(first), to.min(first + records - 1));
+        if start > end {
+            return Ok(Vec::new());
+        }
+        file.seek(SeekFrom::Start(
+            BLOCKS_HEADER + (start - first) * BLOCK_RECORD,
+        ))?;
+        let mut bytes = vec![0u8; ((end - start + 1) * BLOCK_RECORD) as usize];
+        file.read_exact(&mut bytes)?;
+        Ok(bytes
+            .chunks_exact(BLOCK_RECORD as usize)
+            .enumerate()
+            .map(|(i, record)| (start + i as u64, BlockFees::decode(record)))
+            .collect())
+    }
+
+    pub fn last_block(&self) -> Option<(u64, BlockFees)> {
+        let (_, last) = self.block_range()?;
+        self.blocks(last, last).ok()?.pop()
+    }
+
+    /// Append the block at `height`, which must follow the last one (any height starts an
+    /// empty archive)
+    pub fn push_block(&self, height: u64, block: &BlockFees) -> io::Result<()> {
+        let mut file = self.open(BLOCKS_FILE, true)?;
+        match Self::block_span(&mut file)? {
+            None => {
+                file.set_len(0)?;
+                file.seek(SeekFrom::Start(0))?;
+                file.write_all(&height.to_le_bytes())?;
+            }
+            Some((first, records)) if first + records == height => {
+                file.set_len(BLOCKS_HEADER + records * BLOCK_RECORD)?;
+                file.seek(SeekFrom::End(0))?;
+            }
+            Some((first, records)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "block {height} does not follow the archive ({first}..={})",
+                        first + records - 1
+                    ),
+                ));
+            }
+        }
+        file.write_all(&block.encode())
+    }
+
+    /// Drop blocks at `height` and above (reorg)
+    pub fn truncate_blocks(&self, height: u64) -> io::Result<()> {
+        let mut file = self.open(BLOCKS_FILE, true)?;
+        if let Some((first, _)) = Self::block_span(&mut file)? {
+            let keep = height.saturating_sub(first);
+            let len = if keep == 0 {
+                0
+            } else {
+                BLOCKS_HEADER + keep * BLOCK_RECORD
+            };
+            file.set_len(len.min(file.metadata()?.len()))?;
+        }
+        Ok(())
+    }
+
+    pub fn push_snapshot(&self, snapshot: &Snapshot) -> io::Result<()> {
+        let mut file = self.open(MEMPOOL_FILE, true)?;
+        let len = file.metadata()?.len();
+        // A torn record from an interrupted write is overwritten
+        file.set_len(len - len % SNAPSHOT_RECORD)?;
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(&snapshot.encode())
+    }
+
+    /// Snapshots taken from `from` to `to` (unix time, inclusive)
+    pub fn snapshots(&self, from: u64, to: u64) -> io::Result<Vec<Snapshot>> {
+        let mut file = match self.open(MEMPOOL_FILE, false) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let records = file.metadata()?.len() / SNAPSHOT_RECORD;
+        let mut read = |index: u64| -> io::Result<Snapshot> {
+            let mut bytes = [0u8; SNAPSHOT_RECORD as usize];
+            file.seek(SeekFrom::Start(index * SNAPSHOT_RECORD))?;
+            file.read_exact(&mut bytes)?;
+            Ok(Snapshot::decode(&bytes))
+        };
+        // First snapshot at or after `from`
+        let (mut low, mut high) = (0, records);
+        while low < high {
+            let mid = (low + high) / 2;
+            if read(mid)?.time < from {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let mut snapshots = Vec::new();
+        for index in low..records {
+            let snapshot = read(index)?;
+            if snapshot.time > to {
+                break;
+            }
+            snapshots.push(snapshot);
+        }
+        Ok(snapshots)
+    }
+
+    /// `getfeehistory <from> <to>` result: the blocks in the height range and the mempool
+    /// snapshots taken while they were mined (up to now when `to` is the last archived block)
+    pub fn query(&self, from: u64, to: u64) -> io::Result<Value> {
+        let blocks = self.blocks(from, to)?;
+        let snapshots = match (blocks.first(), blocks.last()) {
+            (Some((_, first)), Some((last_height, last))) => {
+                let end = if self
+                    .block_range()
+                    .is_some_and(|(_, tip)| tip == *last_height)
+                {
+                    u64::MAX
+                } else {
+                    last.time as u64
+                };
+                self.snapshots(first.time as u64, end)?
+            }
+            _ => Vec::new(),
+        };
+        Ok(json!({
+            "from": from,
+            "to": to,
+            "blocks": blocks
+                .into_iter()
+                .map(|(height, block)| block.to_json(height))
+                .collect::<Vec<_>>(),
+            "mempool": snapshots.into_iter().map(Snapshot::to_json).collect::<Vec<_>>(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mempool_histogram::Entry;
+
+    fn block(time: u32) -> BlockFees {
+        BlockFees {
+            hash: [time as u8; 32],
+            time,
+            txs: 2,
+            vsize: 400,
+            total_fee: 1000,
+            fee_rates: [1, 2, 3, 4, 5, 6, 7, 4],
+        }
+    }
+
+    #[test]
+    fn blocks_snapshots_and_reorgs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let archive = Archive::new(dir.path());
+        assert_eq!(archive.block_range(), None);
+        for height in 100..105 {
+            archive.push_block(height, &block(height as u32)).unwrap();
+        }
+        assert!(archive.push_block(107, &block(107)).is_err());
+        assert_eq!(archive.block_range(), Some((100, 104)));
+        assert_eq!(archive.blocks(0, 101).unwrap().len(), 2);
+        assert_eq!(archive.blocks(103, 103).unwrap()[0].1, block(103));
+
+        archive.truncate_blocks(103).unwrap();
+        assert_eq!(archive.last_block().map(|(h, _)| h), Some(102));
+        archive.push_block(103, &block(203)).unwrap();
+        assert_eq!(archive.last_block().unwrap().1.time, 203);
+
+        let mut histogram = MempoolHistogram::default();
+        histogram.insert(
+            "a".into(),
+            Entry {
+                vsize: 100,
+                fee: 2500,
+            },
+        );
+        for time in [99, 101, 150, 250] {
+            archive
+                .push_snapshot(&Snapshot::from_histogram(time, &histogram))
+                .unwrap();
+        }
+        let snapshots = archive.snapshots(100, 200).unwrap();
+        assert_eq!(
+            snapshots.iter().map(|s| s.time).collect::<Vec<_>>(),
+            [101, 150]
+        );
+        assert_eq!(snapshots[0].vsize, 100);
+
+        let result = archive.query(100, 101).unwrap();
+        assert_eq!(result["blocks"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            result["blocks"][1]["feerate_percentiles"],
+            json!([2, 3, 4, 5, 6])
+        );
+        assert_eq!(result["mempool"].as_array().unwrap().len(), 1);
+        assert_eq!(result["mempool"][0]["fee_histogram"], json!([[20.0, 100]]));
+        // Up to now when the range reaches the last archived block
+        assert_eq!(
+            archive.query(103, 110).unwrap()["mempool"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn reads_getblockstats() {
+        let stats = json!({
+            "blockhash": "00".repeat(31) + "ff",
+            "time": 1700000000,
+            "txs": 3000,
+            "total_weight": 3993000,
+            "totalfee": 12345678,
+            "minfeerate": 1,
+            "maxfeerate": 900,
+            "avgfeerate": 25,
+            "feerate_percentiles": [3, 8, 15, 30, 60],
+        });
+        let fees = BlockFees::from_stats(&stats).unwrap();
+        assert_eq!(fees.vsize, 998250);
+        assert_eq!(fees.fee_rates, [1, 3, 8, 15, 30, 60, 900, 25]);
+        assert_eq!(BlockFees::decode(&fees.encode()), fees);
+        assert!(fees.hash_hex().ends_with("ff"));
+    }
+}
@@ -0,0 +1,331 @@
+//! C ABI for embedding the node (`include/blvm.h`, `ffi` feature)
+//!
+//! Thin wrapper over `NodeBuilder` / `NodeHandle`: each `BlvmNode` owns a small tokio runtime
+//! that drives the handle, so C callers make plain blocking calls. Strings returned to C are
+//! freed with `blvm_string_free`; on failure functions return NULL or a negative value and
+//! `blvm_last_error` describes the error on the calling thread. A panic inside an entry point is
+//! reported the same way instead of unwinding into C (builds with `panic = "abort"`, like the
+//! release profile, still abort).
+
+use crate::node::{NodeBuilder, NodeHandle, Shutdown};
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int, c_void};
+
+/// Event callback: a JSON object (valid only during the call) and the registered user data
+pub type BlvmEventCallback = extern "C" fn(event_json: *const c_char, user_data: *mut c_void);
+
+/// Opaque node handle
+pub struct BlvmNode {
+    runtime: tokio::runtime::Runtime,
+    handle: NodeHandle,
+    callback: Option<tokio::task::JoinHandle<()>>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(error: &anyhow::Error) {
+    let message = CString::new(format!("{error:#}").replace('\0', " ")).expect("no NUL bytes");
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Store the error for `blvm_last_error` and return `fallback`
+fn fail<T>(result: Result<T>, fallback: T) -> T {
+    result.unwrap_or_else(|e| {
+        set_last_error(&e);
+        fallback
+    })
+}
+
+/// Body of an entry point: errors and panics go to `blvm_last_error` and return `fallback`
+fn guard<T>(fallback: T, body: impl FnOnce() -> Result<T>) -> T {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(body)) {
+        Ok(result) => fail(result, fallback),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_last_error(&anyhow::anyhow!("panic in blvm: {message}"));
+            fallback
+        }
+    }
+}
+
+/// Borrow a C string argument; NULL is `None`
+unsafe fn opt_str<'a>(ptr: *const c_char, name: &str) -> Result<Option<&'a str>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    let s = unsafe { CStr::from_ptr(ptr) };
+    s.to_str()
+        .map(Some)
+        .with_context(|| format!("{name} is not valid UTF-8"))
+}
+
+unsafe fn req_str<'a>(ptr: *const c_char, name: &str) -> Result<&'a str> {
+    unsafe { opt_str(ptr, name) }?.with_context(|| format!("{name} is NULL"))
+}
+
+unsafe fn node_ref<'a>(node: *const BlvmNode) -> Result<&'a BlvmNode> {
+    unsafe { node.as_ref() }.context("node is NULL")
+}
+
+fn into_c_string(s: String) -> Result<*mut c_char> {
+    Ok(CString::new(s)
+        .context("string contains a NUL byte")?
+        .into_raw())
+}
+
+/// Start a node and wait until its RPC server answers. `network` is mainnet, testnet, signet or
+/// regtest; `config_path` (a `blvm.toml`, loaded as by `NodeBuilder::config_file`) and `rpc_addr`
+/// (`host:port`) may be NULL. Returns NULL on failure.
+///
+/// # Safety
+/// Non-NULL arguments must be NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn blvm_node_start(
+    network: *const c_char,
+    data_dir: *const c_char,
+    config_path: *const c_char,
+    rpc_addr: *const c_char,
+) -> *mut BlvmNode {
+    guard(std::ptr::null_mut(), || {
+        let network = unsafe { req_str(network, "network") }?;
+        let data_dir = unsafe { req_str(data_dir, "data_dir") }?;
+        let mut builder = NodeBuilder::new(network, data_dir)?;
+        if let Some(path) = unsafe { opt_str(config_path, "config_path") }? {
+            builder = builder.config_file(std::path::Path::new(path))?;
+        }
+        if let Some(addr) = unsafe { opt_str(rpc_addr, "rpc_addr") }? {
+            builder = builder.rpc_addr(
+                addr.parse()
+                    .with_context(|| format!("Invalid rpc_addr '{addr}'"))?,
+            );
+        }
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("blvm-ffi")
+            .enable_all()
+            .build()?;
+        let handle = runtime.block_on(builder.start())?;
+        Ok(Box::into_raw(Box::new(BlvmNode {
+            runtime,
+            handle,
+            callback: None,
+        })))
+    })
+}
+
+/// Stop the node, wait for it to exit and free `node`. Returns 0 on a clean stop, 1 when the node
+/// stopped but its run loop ended with an error (in `blvm_last_error`), -1 on failure.
+///
+/// # Safety
+/// `node` must come from `blvm_node_start` and is invalid after this call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn blvm_node_stop(node: *mut BlvmNode) -> c_int {
+    guard(-1, || {
+        anyhow::ensure!(!node.is_null(), "node is NULL");
+        let node = unsafe { Box::from_raw(node) };
+        let BlvmNode {
+            runtime,
+            handle,
+            callback,
+        } = *node;
+        if let Some(task) = callback {
+            task.abort();
+        }
+        Ok(match runtime.block_on(handle.shutdown())? {
+            Shutdown::Clean => 0,
+            Shutdown::WithError(message) => {
+                set_last_error(&anyhow::anyhow!(message));
+                1
+            }
+        })
+    })
+}
+
+/// Call any RPC method; `params_json` is a JSON array (NULL for none). Returns the JSON
+/// `result`, or NULL on failure.
+///
+/// # Safety
+/// `node` must come from `blvm_node_start`; strings must be NUL-terminated.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn blvm_node_rpc(
+    node: *const BlvmNode,
+    method: *const c_char,
+    params_json: *const c_char,
+) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        let node = unsafe { node_ref(node) }?;
+        let method = unsafe { req_str(method, "method") }?;
+        let params: Value = match unsafe { opt_str(params_json, "params_json") }? {
+            Some(text) => serde_json::from_str(text).context("params_json is not valid JSON")?,
+            None => json!([]),
+        };
+        let result = node.runtime.block_on(node.handle.rpc(method, params))?;
+        into_c_string(result.to_string())
+    })
+}
+
+/// Height of the best block in `*height`; the hash is returned (NULL on failure).
+///
+/// # Safety
+/// `node` must come from `blvm_node_start`; `height` may be NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn blvm_node_tip(node: *const BlvmNode, height: *mut u64) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        let node = unsafe { node_ref(node) }?;
+        let tip = node.runtime.block_on(node.handle.query_tip())?;
+        if let Some(height) = unsafe { height.as_mut() } {
+            *height = tip.height;
+        }
+        into_c_string(tip.hash)
+    })
+}
+
+/// Submit a serialized block. Returns 0 when accepted, -1 when rejected or on error.
+///
+/// # Safety
+/// `node` must come from `blvm_node_start`; `data` must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn blvm_node_submit_block(
+    node: *const BlvmNode,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    guard(-1, || {
+        let node = unsafe { node_ref(node) }?;
+        anyhow::ensure!(!data.is_null(), "data is NULL");
+        let block = unsafe { std::slice::from_raw_parts(data, len) };
+        node.runtime.block_on(node.handle.submit_block(block))?;
+        Ok(0)
+    })
+}
+
+/// User data moved onto the runtime thread; the C side promises it may be used from there
+struct UserData(*mut c_void);
+unsafe impl Send for UserData {}
+
+/// Register `callback` for node events (replacing any previous one; NULL unregisters). It runs on
+/// the node's runtime thread and must not call back into `blvm_node_*` functions.
+///
+/// # Safety
+/// `node` must come from `blvm_node_start`; `user_data` must stay valid until the callback is
+/// replaced or the node is stopped.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn blvm_node_set_event_callback(
+    node: *mut BlvmNode,
+    callback: Option<BlvmEventCallback>,
+    user_data: *mut c_void,
+) -> c_int {
+    guard(-1, || {
+        let node = unsafe { node.as_mut() }.context("node is NULL")?;
+        if let Some(task) = node.callback.take() {
+            task.abort();
+        }
+        let Some(callback) = callback else {
+            return Ok(0);
+        };
+        let mut events = node.handle.subscribe_events();
+        let user_data = UserData(user_data);
+        node.callback = Some(node.runtime.spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        let line =
+                            CString::new(event.to_json().to_string()).expect("JSON has no NUL");
+                        callback(line.as_ptr(), user_data.0);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }));
+        Ok(0)
+    })
+}
+
+/// Message for the last failure on this thread, or NULL. Valid until the next failing call on
+/// the same thread; do not free.
+#[unsafe(no_mangle)]
+pub extern "C" fn blvm_last_error() -> *const c_char {
+    std::panic::catch_unwind(|| {
+        LAST_ERROR.with(|e| {
+            e.borrow()
+                .as_ref()
+                .map_or(std::ptr::null(), |message| message.as_ptr())
+        })
+    })
+    .unwrap_or(std::ptr::null())
+}
+
+/// Free a string returned by this library (NULL is ignored).
+///
+/// # Safety
+/// `s` must come from this library and not be freed twice.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn blvm_string_free(s: *mut c_char) {
+    guard((), || {
+        if !s.is_null() {
+            drop(unsafe { CString::from_raw(s) });
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_set_last_error() {
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let data_dir = CString::new(data_dir.path().to_str().unwrap()).unwrap();
+        let start = |network: &CStr| unsafe {
+            blvm_node_start(
+                network.as_ptr(),
+                data_dir.as_ptr(),
+                std::ptr::null(),
+                c"x".as_ptr(),
+            )
+        };
+        assert!(start(c"litecoin").is_null());
+        let message = unsafe { CStr::from_ptr(blvm_last_error()) }
+            .to_str()
+            .unwrap();
+        assert!(message.contains("Unknown network"));
+
+        assert!(start(c"regtest").is_null());
+        let message = unsafe { CStr::from_ptr(blvm_last_error()) }
+            .to_str()
+            .unwrap();
+        assert!(message.contains("Invalid rpc_addr"));
+
+        assert!(
+            unsafe {
+                blvm_node_rpc(
+                    std::ptr::null(),
+                    c"getblockcount".as_ptr(),
+                    std::ptr::null(),
+                )
+            }
+            .is_null()
+        );
+        assert_eq!(unsafe { blvm_node_stop(std::ptr::null_mut()) }, -1);
+        unsafe { blvm_string_free(std::ptr::null_mut()) };
+    }
+
+    #[test]
+    fn panics_become_errors() {
+        assert_eq!(guard(-1, || -> Result<c_int> { panic!("boom") }), -1);
+        let message = unsafe { CStr::from_ptr(blvm_last_error()) }
+            .to_str()
+            .unwrap();
+        assert_eq!(message, "panic in blvm: boom");
+    }
+}
@@ -0,0 +1,245 @@
+//! Fleet admin channel (`[fleet]`, `blvm fleet exec`)
+//!
+//! A node with `[fleet] enabled = true` accepts a small set of admin commands on a separate TCP
+//! port. Each request is one JSON line signed with HMAC-SHA256 under an operator key the node
+//! lists in `authorized_keys`; requests carry a timestamp and nonce so a captured request cannot
+//! be replayed. The reply is one JSON line.
+
+use crate::hash::{constant_time_eq, hmac_sha256};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+
+/// Accepted clock difference between operator and node
+pub const MAX_CLOCK_SKEW_SECS: u64 = 300;
+/// Longest envelope line read before verification, newline included
+pub const MAX_ENVELOPE: usize = 4 * 1024;
+
+/// `[fleet]` config section
+///
+/// Nodes set `enabled`, `listen` and `authorized_keys`; the operator's machine sets `key_id`,
+/// `key` and `nodes`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FleetConfig {
+    pub enabled: bool,
+    pub listen: SocketAddr,
+    /// Operator key id -> hex-encoded secret
+    pub authorized_keys: BTreeMap<String, String>,
+    /// Key used by `blvm fleet exec` (the secret may come from `BLVM_FLEET_KEY` instead)
+    pub key_id: Option<String>,
+    pub key: Option<String>,
+    pub nodes: Vec<FleetNode>,
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:8339"
+                .parse()
+                .expect("valid default fleet address"),
+            authorized_keys: BTreeMap::new(),
+            key_id: None,
+            key: None,
+            nodes: Vec::new(),
+        }
+    }
+}
+
+/// A node `blvm fleet exec` talks to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FleetNode {
+    pub name: String,
+    pub addr: SocketAddr,
+}
+
+/// Commands a node accepts over the admin channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminCommand {
+    /// Report version and height
+    Ping,
+    /// Replace the log filter (`info`, `blvm_node=debug`, ...)
+    LogLevel(String),
+    /// Stop P2P activity (`setnetworkactive false`)
+    PauseRelay,
+    ResumeRelay,
+}
+
+impl AdminCommand {
+    pub const USAGE: &'static str = "ping | log-level <filter> | pause-relay | resume-relay";
+
+    pub fn parse(words: &[String]) -> Result<Self, String> {
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        match words.as_slice() {
+            ["ping"] => Ok(AdminCommand::Ping),
+            ["log-level", filter] => Ok(AdminCommand::LogLevel(filter.to_string())),
+            ["pause-relay"] => Ok(AdminCommand::PauseRelay),
+            ["resume-relay"] => Ok(AdminCommand::ResumeRelay),
+            _ => Err(format!(
+                "unknown fleet command '{}' (expected {})",
+                words.join(" "),
+                Self::USAGE
+            )),
+        }
+    }
+}
+
+/// Signed part of a request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Request {
+    pub key_id: String,
+    /// Unix time the operator sent the request
+    pub time: u64,
+    pub nonce: u64,
+    pub command: Vec<String>,
+}
+
+/// Wire format: the request JSON exactly as signed, plus its hex HMAC
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Envelope {
+    pub request: String,
+    pub signature: String,
+}
+
+impl Envelope {
+    pub fn sign(request: &Request, key: &[u8]) -> Self {
+        let request = serde_json::to_string(request).expect("request serializes");
+        let signature = hex::encode(hmac_sha256(key, request.as_bytes()));
+        Envelope { request, signature }
+    }
+}
+
+/// Reply line
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Reply {
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Node-side authentication with replay protection
+pub struct Verifier {
+    keys: BTreeMap<String, Vec<u8>>,
+    /// (key id, nonce) -> request time, for requests inside the skew window
+    seen: HashMap<(String, u64), u64>,
+}
+
+impl Verifier {
+    /// Fails on a key that is not valid hex
+    pub fn new(authorized_keys: &BTreeMap<String, String>) -> Result<Self, String> {
+        let keys = authorized_keys
+            .iter()
+            .map(|(id, secret)| {
+                hex::decode(secret)
+                    .map(|key| (id.clone(), key))
+                    .map_err(|_| format!("fleet key '{id}' is not valid hex"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Verifier {
+            keys,
+            seen: HashMap::new(),
+        })
+    }
+
+    /// Authenticate an envelope received at `now`
+    pub fn verify(&mut self, envelope: &Envelope, now: u64) -> Result<Request, String> {
+        let request: Request = serde_json::from_str(&envelope.request)
+            .map_err(|e| format!("malformed request: {e}"))?;
+        let key = self
+            .keys
+            .get(&request.key_id)
+            .ok_or_else(|| format!("unknown key '{}'", request.key_id))?;
+        let signature = hex::decode(&envelope.signature).unwrap_or_default();
+        if !constant_time_eq(&signature, &hmac_sha256(key, envelope.request.as_bytes())) {
+            return Err("bad signature".to_string());
+        }
+        if request.time.abs_diff(now) > MAX_CLOCK_SKEW_SECS {
+            return Err("request timestamp outside the allowed clock skew".to_string());
+        }
+        self.seen
+            .retain(|_, time| time.abs_diff(now) <= MAX_CLOCK_SKEW_SECS);
+        if self
+            .seen
+            .insert((request.key_id.clone(), request.nonce), request.time)
+            .is_some()
+        {
+            return Err("replayed request".to_string());
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verifier() -> Verifier {
+        Verifier::new(&BTreeMap::from([("ops".to_string(), "00ff".repeat(16))])).unwrap()
+    }
+
+    fn request(nonce: u64) -> Request {
+        Request {
+            key_id: "ops".to_string(),
+            time: 1_000,
+            nonce,
+            command: vec!["pause-relay".to_string()],
+        }
+    }
+
+    #[test]
+    fn accepts_signed_requests_once() {
+        let key = hex::decode("00ff".repeat(16)).unwrap();
+        let mut verifier = verifier();
+        let envelope = Envelope::sign(&request(1), &key);
+        assert_eq!(verifier.verify(&envelope, 1_010), Ok(request(1)));
+        assert_eq!(
+            verifier.verify(&envelope, 1_020),
+            Err("replayed request".to_string())
+        );
+        assert!(
+            verifier
+                .verify(&Envelope::sign(&request(2), &key), 1_020)
+                .is_ok()
+        );
+        assert!(
+            verifier
+                .verify(&Envelope::sign(&request(3), &key), 2_000)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_key_and_tampering() {
+        let mut verifier = verifier();
+        let forged = Envelope::sign(&request(1), b"not the key");
+        assert_eq!(
+            verifier.verify(&forged, 1_000),
+            Err("bad signature".to_string())
+        );
+        let key = hex::decode("00ff".repeat(16)).unwrap();
+        let mut tampered = Envelope::sign(&request(1), &key);
+        tampered.request = tampered.request.replace("pause-relay", "resume-relay");
+        assert!(verifier.verify(&tampered, 1_000).is_err());
+        let unknown = Request {
+            key_id: "other".to_string(),
+            ..request(1)
+        };
+        assert!(
+            verifier
+                .verify(&Envelope::sign(&unknown, &key), 1_000)
+                .is_err()
+        );
+        assert!(Verifier::new(&BTreeMap::from([("x".to_string(), "zz".to_string())])).is_err());
+    }
+
+    #[test]
+    fn parses_commands() {
+        let words = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(AdminCommand::parse(&words("ping")), Ok(AdminCommand::Ping));
+        assert_eq!(
+            AdminCommand::parse(&words("log-level debug")),
+            Ok(AdminCommand::LogLevel("debug".to_string()))
+        );
+        assert!(AdminCommand::parse(&words("shutdown now")).is_err());
+    }
+}
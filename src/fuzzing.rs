@@ -0,0 +1,23 @@
+//! Fuzz entry points (feature `fuzzing`; targets live in `fuzz/`)
+//!
+//! Each function must not panic on any input except to report a broken invariant.
+
+/// Frame decoding: accepted frames re-encode to the exact input bytes.
+pub fn parse_network_message(data: &[u8]) {
+    if let Ok((msg, used)) = crate::wire::parse_network_message(data) {
+        assert_eq!(msg.encode(), &data[..used], "frame did not round-trip");
+    }
+}
+
+/// Script decoding and disassembly: decoded scripts re-encode to the input bytes, and
+/// disassembly never panics (including on truncated pushes).
+pub fn decode_script(data: &[u8]) {
+    if let Ok(ops) = crate::script::decode_script(data) {
+        assert_eq!(
+            crate::script::encode_script(&ops),
+            data,
+            "script did not round-trip"
+        );
+    }
+    let _ = crate::script::to_asm(data);
+}
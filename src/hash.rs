@@ -0,0 +1,135 @@
+//! Bitcoin hashing helpers
+//!
+//! SHA-256 goes through `sha2`, which picks SHA-NI (x86) or the SHA2 extensions (aarch64) at
+//! runtime; [`Sha256Backend::detect`] names the one it picks on this CPU. Hashing inside
+//! blvm-node goes through its own code and is not affected by anything here.
+
+use hmac::{Hmac, Mac};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+/// SHA-256 implementation `sha2` dispatches to on this CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sha256Backend {
+    /// x86 SHA extensions (with SSE2, SSSE3 and SSE4.1)
+    ShaNi,
+    /// ARMv8 SHA2 instructions
+    ArmSha2,
+    /// Scalar code
+    Portable,
+}
+
+impl Sha256Backend {
+    /// The backend `sha2` selects, using the same CPU feature checks
+    pub fn detect() -> Self {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        if std::arch::is_x86_feature_detected!("sha")
+            && std::arch::is_x86_feature_detected!("sse2")
+            && std::arch::is_x86_feature_detected!("ssse3")
+            && std::arch::is_x86_feature_detected!("sse4.1")
+        {
+            return Self::ShaNi;
+        }
+        #[cfg(target_arch = "aarch64")]
+        if std::arch::is_aarch64_feature_detected!("sha2") {
+            return Self::ArmSha2;
+        }
+        Self::Portable
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ShaNi => "SHA-NI",
+            Self::ArmSha2 => "ARMv8 SHA2",
+            Self::Portable => "portable",
+        }
+    }
+}
+
+/// Double SHA-256 (block hashes, txids, merkle nodes, message checksums)
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+/// Double SHA-256 of the concatenation of `parts`, without building it
+pub fn sha256d_parts(parts: &[&[u8]]) -> [u8; 32] {
+    let mut engine = Sha256::new();
+    for part in parts {
+        engine.update(part);
+    }
+    Sha256::digest(engine.finalize()).into()
+}
+
+/// HMAC-SHA256 (RFC 2104)
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// BIP340 tagged hash: `SHA256(SHA256(tag) || SHA256(tag) || message)`
+pub fn tagged_hash(tag: &str, message: &[u8]) -> [u8; 32] {
+    let tag = Sha256::digest(tag.as_bytes());
+    Sha256::new()
+        .chain_update(tag)
+        .chain_update(tag)
+        .chain_update(message)
+        .finalize()
+        .into()
+}
+
+/// RIPEMD160(SHA256(data)) (P2PKH / P2WPKH key hashes)
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+/// Equality without an early exit, for comparing MACs
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Hash in RPC display order (byte-reversed hex)
+pub fn to_display_hex(hash: &[u8; 32]) -> String {
+    let mut reversed = *hash;
+    reversed.reverse();
+    hex::encode(reversed)
+}
+
+/// Parse a display-order hex hash
+pub fn from_display_hex(s: &str) -> Option<[u8; 32]> {
+    let mut hash: [u8; 32] = hex::decode(s).ok()?.try_into().ok()?;
+    hash.reverse();
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_checksum_and_display_order() {
+        // P2P checksum of an empty payload is the first 4 bytes of sha256d("")
+        assert_eq!(sha256d(b"")[..4], [0x5d, 0xf6, 0xe0, 0xe2]);
+        let hash = sha256d(b"blvm");
+        assert_eq!(from_display_hex(&to_display_hex(&hash)), Some(hash));
+        assert!(from_display_hex("abcd").is_none());
+    }
+
+    #[test]
+    fn constant_time_eq_compares_whole_inputs() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+
+    #[test]
+    fn hash160_of_the_generator() {
+        // Compressed generator point -> HASH160 of the key for private key 1
+        let g = hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+            .unwrap();
+        assert_eq!(
+            hex::encode(hash160(&g)),
+            "751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Structural JSON diff (`blvm compare-rpc`)
+//!
+//! Paths use dots for object keys and `[i]` for array elements (`peers[0].addr`). Ignore
+//! patterns match whole paths; `*` matches one key, `[*]` any index, and a pattern also
+//! ignores everything beneath it.
+
+use serde_json::Value;
+use std::fmt;
+
+/// One difference between the left and right documents
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// Present only on the left
+    Removed { path: String, left: Value },
+    /// Present only on the right
+    Added { path: String, right: Value },
+    /// Present on both sides with different values (or types)
+    Changed {
+        path: String,
+        left: Value,
+        right: Value,
+    },
+}
+
+impl Difference {
+    pub fn path(&self) -> &str {
+        match self {
+            Difference::Removed { path, .. }
+            | Difference::Added { path, .. }
+            | Difference::Changed { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path().is_empty() {
+            "(root)"
+        } else {
+            self.path()
+        };
+        match self {
+            Difference::Removed { left, .. } => write!(f, "- {path}: {left}"),
+            Difference::Added { right, .. } => write!(f, "+ {path}: {right}"),
+            Difference::Changed { left, right, .. } => write!(f, "~ {path}: {left} -> {right}"),
+        }
+    }
+}
+
+/// Differences between `left` and `right`, skipping paths matched by `ignore`
+pub fn diff(left: &Value, right: &Value, ignore: &[String]) -> Vec<Difference> {
+    let mut out = Vec::new();
+    walk(left, right, String::new(), ignore, &mut out);
+    out
+}
+
+fn walk(left: &Value, right: &Value, path: String, ignore: &[String], out: &mut Vec<Difference>) {
+    if is_ignored(ignore, &path) {
+        return;
+    }
+    match (left, right) {
+        (Value::Object(l), Value::Object(r)) => {
+            for (key, lv) in l {
+                let child = join_key(&path, key);
+                match r.get(key) {
+                    Some(rv) => walk(lv, rv, child, ignore, out),
+                    None if !is_ignored(ignore, &child) => out.push(Difference::Removed {
+                        path: child,
+                        left: lv.clone(),
+                    }),
+                    None => {}
+                }
+            }
+            for (key, rv) in r {
+                let child = join_key(&path, key);
+                if !l.contains_key(key) && !is_ignored(ignore, &child) {
+                    out.push(Difference::Added {
+                        path: child,
+                        right: rv.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(l), Value::Array(r)) => {
+            for i in 0..l.len().max(r.len()) {
+                let child = format!("{path}[{i}]");
+                match (l.get(i), r.get(i)) {
+                    (Some(lv), Some(rv)) => walk(lv, rv, child, ignore, out),
+                    (Some(lv), None) if !is_ignored(ignore, &child) => {
+                        out.push(Difference::Removed {
+                            path: child,
+                            left: lv.clone(),
+                        })
+                    }
+                    (None, Some(rv)) if !is_ignored(ignore, &child) => {
+                        out.push(Difference::Added {
+                            path: child,
+                            right: rv.clone(),
+                        })
+                    }
+                    _ => {}
+                }
+            }
+        }
+        (l, r) if l != r => out.push(Difference::Changed {
+            path,
+            left: l.clone(),
+            right: r.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn is_ignored(ignore: &[String], path: &str) -> bool {
+    ignore.iter().any(|pattern| path_matches(pattern, path))
+}
+
+fn join_key(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Split `a.b[2].c` into `["a", "b", "[2]", "c"]`
+fn segments(path: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    for part in path.split('.').filter(|p| !p.is_empty()) {
+        let mut rest = part;
+        while let Some(open) = rest.find('[') {
+            if open > 0 {
+                out.push(&rest[..open]);
+            }
+            let close = rest[open..].find(']').map_or(rest.len(), |c| open + c + 1);
+            out.push(&rest[open..close]);
+            rest = &rest[close..];
+        }
+        if !rest.is_empty() {
+            out.push(rest);
+        }
+    }
+    out
+}
+
+/// Whether `pattern` matches `path` or one of its ancestors
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = segments(pattern);
+    let path = segments(path);
+    if pattern.is_empty() || pattern.len() > path.len() {
+        return false;
+    }
+    pattern.iter().zip(&path).all(|(p, s)| {
+        *p == *s || (*p == "*" && !s.starts_with('[')) || (*p == "[*]" && s.starts_with('['))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_added_removed_and_changed() {
+        let left = json!({"blocks": 10, "chain": "regtest", "warnings": ""});
+        let right = json!({"blocks": 11, "chain": "regtest", "pruned": false});
+        let diffs = diff(&left, &right, &[]);
+        let rendered: Vec<String> = diffs.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            rendered,
+            ["~ blocks: 10 -> 11", "- warnings: \"\"", "+ pruned: false"]
+        );
+    }
+
+    #[test]
+    fn ignore_patterns_cover_subtrees_and_wildcards() {
+        let left = json!({"time": 1, "peers": [{"id": 1, "addr": "a"}, {"id": 2, "addr": "b"}]});
+        let right = json!({"time": 2, "peers": [{"id": 9, "addr": "a"}, {"id": 8, "addr": "b"}, {"id": 7}]});
+        let ignore = vec!["time".to_string(), "peers[*].id".to_string()];
+        let diffs = diff(&left, &right, &ignore);
+        assert_eq!(diffs.len(), 1, "{diffs:?}");
+        assert_eq!(diffs[0].path(), "peers[2]");
+        assert!(diff(&left, &right, &["peers".to_string(), "time".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn path_matching() {
+        assert!(path_matches(
+            "softforks.*.active",
+            "softforks.taproot.active"
+        ));
+        assert!(path_matches("softforks", "softforks.taproot.height"));
+        assert!(!path_matches("softforks.*.active", "softforks.taproot"));
+        assert!(!path_matches("*", "[0]"));
+        assert!(path_matches("[*].fee", "[3].fee"));
+    }
+}
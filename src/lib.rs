@@ -4,7 +4,83 @@
 
 use std::net::SocketAddr;
 
+pub mod addr_relay;
+pub mod alerts;
+pub mod bench;
+pub mod bip21;
+pub mod block_analysis;
+pub mod block_anomalies;
+pub mod block_fees;
+pub mod chain_params;
+pub mod chain_split;
+pub mod config_overlay;
+pub mod config_reload;
+pub mod datadir;
+pub mod decode;
+pub mod descriptor;
+pub mod difficulty;
+pub mod event_journal;
+pub mod events;
+pub mod extra_config;
+pub mod failpoints;
+pub mod fee_history;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fleet;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+pub mod generate_block;
+pub mod hash;
+pub mod json_diff;
+pub mod mdns;
+pub mod mempool_histogram;
+pub mod merkle_proof;
+pub mod mining;
+pub mod mocktime;
+pub mod module_manifest;
+pub mod module_storage;
+pub mod node;
+pub mod node_state;
+pub mod notifications;
+pub mod opreturn;
+pub mod output;
+pub mod peer_events;
+pub mod peer_limits;
+pub mod peer_policy;
+pub mod peer_timeouts;
+pub mod persistent_peers;
+pub mod probes;
+pub mod profiling;
+#[cfg(feature = "python")]
+mod python;
+pub mod quarantine;
+pub mod replica;
+pub mod revalidation;
+pub mod rpc_args;
+pub mod rpc_batch;
+pub mod rpc_cache;
+pub mod rpc_cookie;
+pub mod rpc_dispatch;
+pub mod rpc_front;
+pub mod rpc_stats;
+pub mod scaffold;
+pub mod script;
+pub mod script_stats;
+#[cfg(all(feature = "systemd", unix))]
+pub mod sd_notify;
+pub mod seeds;
+#[cfg(feature = "silent-payments")]
+pub mod silent_payments;
+pub mod sim;
+pub mod sync_history;
+pub mod tasks;
+pub mod tx_analysis;
+pub mod utxo_sizes;
+pub mod verifychain;
 pub mod versions;
+pub mod wire;
+
+pub use node::{NodeBuilder, NodeEvent, NodeHandle, Shutdown, Tip};
 
 /// Canonical network name for config (`protocol_version` / logging).
 pub fn canonical_network_name(network: &str) -> Option<&'static str> {
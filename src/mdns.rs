@@ -0,0 +1,466 @@
+//! LAN peer discovery over mDNS (`[discovery] mdns = true`)
+//!
+//! Each node announces `<instance>._blvm-<network>._tcp.local` with its P2P port and answers
+//! PTR queries for that service. Peers found this way are handed to the node with `addnode`;
+//! only private, loopback and link-local addresses are accepted unless `allow_public` is set.
+
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// mDNS multicast group and port (RFC 6762)
+pub const MDNS_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+pub const MDNS_PORT: u16 = 5353;
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_SRV: u16 = 33;
+const CLASS_IN: u16 = 1;
+/// Cache-flush bit on unique records (SRV, A)
+const CLASS_FLUSH: u16 = 0x8000;
+const TTL_SECS: u32 = 120;
+/// Longest name on the wire (RFC 1035 section 2.3.4)
+const MAX_NAME_LEN: usize = 255;
+/// Smallest question (root name, type, class) and record (root name, type, class, TTL, length)
+const MIN_QUESTION_LEN: usize = 5;
+const MIN_RECORD_LEN: usize = 11;
+
+/// `[discovery]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DiscoveryConfig {
+    pub mdns: bool,
+    /// Also accept peers announcing public addresses
+    pub allow_public: bool,
+    /// Seconds between announcements and queries
+    pub interval_secs: u64,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            mdns: false,
+            allow_public: false,
+            interval_secs: 60,
+        }
+    }
+}
+
+/// `_blvm-<network>._tcp.local`; networks never discover each other
+pub fn service_name(network: &str) -> String {
+    format!("_blvm-{network}._tcp.local")
+}
+
+/// Addresses discovery may connect to without `allow_public`
+pub fn is_local_address(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_link_local(),
+        IpAddr::V6(v6) => {
+            v6.is_loopback()
+                // fc00::/7 unique local, fe80::/10 link-local
+                || (v6.segments()[0] & 0xfe00) == 0xfc00
+                || (v6.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// A resource record this module understands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Record {
+    Ptr {
+        name: String,
+        target: String,
+    },
+    Srv {
+        name: String,
+        port: u16,
+        target: String,
+    },
+    A {
+        name: String,
+        addr: Ipv4Addr,
+    },
+}
+
+/// The parts of an mDNS message discovery uses
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    pub is_response: bool,
+    /// Names asked for with type PTR
+    pub ptr_questions: Vec<String>,
+    /// Answer and additional records
+    pub records: Vec<Record>,
+}
+
+fn put_name(out: &mut Vec<u8>, name: &str) {
+    for label in name.split('.').filter(|l| !l.is_empty()) {
+        let label = &label.as_bytes()[..label.len().min(63)];
+        out.push(label.len() as u8);
+        out.extend_from_slice(label);
+    }
+    out.push(0);
+}
+
+fn put_record(out: &mut Vec<u8>, name: &str, rtype: u16, class: u16, rdata: &[u8]) {
+    put_name(out, name);
+    out.extend_from_slice(&rtype.to_be_bytes());
+    out.extend_from_slice(&class.to_be_bytes());
+    out.extend_from_slice(&TTL_SECS.to_be_bytes());
+    out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+    out.extend_from_slice(rdata);
+}
+
+fn header(flags: u16, questions: u16, answers: u16) -> Vec<u8> {
+    let mut out = Vec::with_capacity(512);
+    out.extend_from_slice(&0u16.to_be_bytes());
+    out.extend_from_slice(&flags.to_be_bytes());
+    out.extend_from_slice(&questions.to_be_bytes());
+    out.extend_from_slice(&answers.to_be_bytes());
+    out.extend_from_slice(&[0, 0, 0, 0]);
+    out
+}
+
+/// PTR query for `service`
+pub fn encode_query(service: &str) -> Vec<u8> {
+    let mut out = header(0, 1, 0);
+    put_name(&mut out, service);
+    out.extend_from_slice(&TYPE_PTR.to_be_bytes());
+    out.extend_from_slice(&CLASS_IN.to_be_bytes());
+    out
+}
+
+/// Unsolicited response announcing `instance` (PTR, SRV and one A record per address)
+pub fn encode_announcement(
+    service: &str,
+    instance: &str,
+    port: u16,
+    addrs: &[Ipv4Addr],
+) -> Vec<u8> {
+    let full = format!("{instance}.{service}");
+    let host = format!("{instance}.local");
+    let mut out = header(0x8400, 0, 2 + addrs.len() as u16);
+
+    let mut ptr = Vec::new();
+    put_name(&mut ptr, &full);
+    put_record(&mut out, service, TYPE_PTR, CLASS_IN, &ptr);
+
+    let mut srv = vec![0, 0, 0, 0];
+    srv.extend_from_slice(&port.to_be_bytes());
+    put_name(&mut srv, &host);
+    put_record(&mut out, &full, TYPE_SRV, CLASS_IN | CLASS_FLUSH, &srv);
+
+    for addr in addrs {
+        put_record(
+            &mut out,
+            &host,
+            TYPE_A,
+            CLASS_IN | CLASS_FLUSH,
+            &addr.octets(),
+        );
+    }
+    out
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + 2)
+            .ok_or("truncated message")?;
+        self.pos += 2;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Name at the cursor, following compression pointers
+    fn name(&mut self) -> Result<String, String> {
+        let (name, end) = read_name(self.buf, self.pos)?;
+        self.pos = end;
+        Ok(name)
+    }
+}
+
+/// Name at `pos` and the offset just after it in the record
+///
+/// Compression pointers must point strictly backwards, so every chain ends; the expanded name
+/// is limited to 255 bytes.
+fn read_name(buf: &[u8], mut pos: usize) -> Result<(String, usize), String> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut wire_len = 1;
+    let mut end = None;
+    loop {
+        let len = *buf.get(pos).ok_or("truncated name")? as usize;
+        match len {
+            0 => {
+                return Ok((labels.join("."), end.unwrap_or(pos + 1)));
+            }
+            l if l & 0xc0 == 0xc0 => {
+                let low = *buf.get(pos + 1).ok_or("truncated name")? as usize;
+                let target = ((l & 0x3f) << 8) | low;
+                if target >= pos {
+                    return Err("name pointer does not point backwards".to_string());
+                }
+                end.get_or_insert(pos + 2);
+                pos = target;
+            }
+            l if l < 64 => {
+                let label = buf.get(pos + 1..pos + 1 + l).ok_or("truncated name")?;
+                if label.contains(&b'.') {
+                    return Err("bad label".to_string());
+                }
+                wire_len += 1 + l;
+                if wire_len > MAX_NAME_LEN {
+                    return Err("name too long".to_string());
+                }
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                pos += 1 + l;
+            }
+            _ => return Err("bad label".to_string()),
+        }
+    }
+}
+
+/// Name inside the rdata `start..end` of a record; it may point elsewhere but must not run past
+/// the record
+fn read_rdata_name(buf: &[u8], start: usize, end: usize) -> Result<String, String> {
+    let (name, after) = read_name(&buf[..end], start)?;
+    if after > end {
+        return Err("name runs past its record".to_string());
+    }
+    Ok(name)
+}
+
+/// Parse a message, skipping record types discovery does not use
+pub fn parse(buf: &[u8]) -> Result<Message, String> {
+    let mut r = Reader { buf, pos: 0 };
+    let _id = r.u16()?;
+    let flags = r.u16()?;
+    let questions = r.u16()?;
+    let records = r.u16()? as usize + r.u16()? as usize + r.u16()? as usize;
+    if questions as usize * MIN_QUESTION_LEN + records * MIN_RECORD_LEN > buf.len() - r.pos {
+        return Err("record counts exceed the message".to_string());
+    }
+    let mut message = Message {
+        is_response: flags & 0x8000 != 0,
+        ..Message::default()
+    };
+    for _ in 0..questions {
+        let name = r.name()?;
+        let qtype = r.u16()?;
+        let _class = r.u16()?;
+        if qtype == TYPE_PTR {
+            message.ptr_questions.push(name);
+        }
+    }
+    for _ in 0..records {
+        let name = r.name()?;
+        let rtype = r.u16()?;
+        let _class = r.u16()?;
+        let _ttl = (r.u16()?, r.u16()?);
+        let len = r.u16()? as usize;
+        let start = r.pos;
+        let rdata = buf.get(start..start + len).ok_or("truncated record")?;
+        match rtype {
+            TYPE_PTR => message.records.push(Record::Ptr {
+                name,
+                target: read_rdata_name(buf, start, start + len)?,
+            }),
+            TYPE_SRV if len >= 7 => message.records.push(Record::Srv {
+                name,
+                port: u16::from_be_bytes([rdata[4], rdata[5]]),
+                target: read_rdata_name(buf, start + 6, start + len)?,
+            }),
+            TYPE_A if len == 4 => message.records.push(Record::A {
+                name,
+                addr: Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]),
+            }),
+            _ => {}
+        }
+        r.pos = start + len;
+    }
+    Ok(message)
+}
+
+/// Peers announced for `service` in a response, other than `own_instance`
+///
+/// A records in the message give the address; without one the sender's address is used.
+pub fn announced_peers(
+    message: &Message,
+    service: &str,
+    own_instance: &str,
+    sender: IpAddr,
+) -> Vec<SocketAddr> {
+    let own = format!("{own_instance}.{service}");
+    let mut peers = Vec::new();
+    if !message.is_response {
+        return peers;
+    }
+    for record in &message.records {
+        let Record::Ptr { name, target } = record else {
+            continue;
+        };
+        if !name.eq_ignore_ascii_case(service) || target.eq_ignore_ascii_case(&own) {
+            continue;
+        }
+        let Some((port, host)) = message.records.iter().find_map(|r| match r {
+            Record::Srv {
+                name,
+                port,
+                target: host,
+            } if name.eq_ignore_ascii_case(target) => Some((*port, host)),
+            _ => None,
+        }) else {
+            continue;
+        };
+        let ips: Vec<IpAddr> = message
+            .records
+            .iter()
+            .filter_map(|r| match r {
+                Record::A { name, addr } if name.eq_ignore_ascii_case(host) => {
+                    Some(IpAddr::V4(*addr))
+                }
+                _ => None,
+            })
+            .collect();
+        let ips = if ips.is_empty() { vec![sender] } else { ips };
+        for ip in ips {
+            let peer = SocketAddr::new(ip, port);
+            if !peers.contains(&peer) {
+                peers.push(peer);
+            }
+        }
+    }
+    peers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announcement_round_trips() {
+        let service = service_name("regtest");
+        let addrs = [Ipv4Addr::new(192, 168, 1, 20), Ipv4Addr::new(10, 0, 0, 5)];
+        let packet = encode_announcement(&service, "node-a1b2", 18444, &addrs);
+        let message = parse(&packet).unwrap();
+        assert!(message.is_response);
+
+        let sender = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20));
+        let peers = announced_peers(&message, &service, "node-ffff", sender);
+        assert_eq!(
+            peers,
+            vec![
+                "192.168.1.20:18444".parse().unwrap(),
+                "10.0.0.5:18444".parse().unwrap()
+            ]
+        );
+        // Our own announcement, and other networks, are ignored
+        assert!(announced_peers(&message, &service, "node-a1b2", sender).is_empty());
+        assert!(announced_peers(&message, &service_name("signet"), "x", sender).is_empty());
+
+        let query = parse(&encode_query(&service)).unwrap();
+        assert!(!query.is_response);
+        assert_eq!(query.ptr_questions, vec![service]);
+    }
+
+    #[test]
+    fn follows_compression_and_rejects_garbage() {
+        // The second record's name is a pointer to the first one's (offset 12, after the header)
+        let mut packet = header(0x8400, 0, 2);
+        put_record(&mut packet, "host.local", TYPE_A, CLASS_IN, &[10, 0, 0, 1]);
+        packet.extend_from_slice(&[0xc0, 12]);
+        packet.extend_from_slice(&TYPE_A.to_be_bytes());
+        packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+        packet.extend_from_slice(&TTL_SECS.to_be_bytes());
+        packet.extend_from_slice(&4u16.to_be_bytes());
+        packet.extend_from_slice(&[10, 0, 0, 2]);
+        let message = parse(&packet).unwrap();
+        assert_eq!(
+            message.records[1],
+            Record::A {
+                name: "host.local".to_string(),
+                addr: Ipv4Addr::new(10, 0, 0, 2)
+            }
+        );
+
+        // A pointer loop must not hang
+        assert!(read_name(&[0xc0, 0], 0).is_err());
+        assert!(parse(&[0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_packets() {
+        let packet = encode_announcement(
+            &service_name("regtest"),
+            "node-a1b2",
+            18444,
+            &[Ipv4Addr::new(192, 168, 1, 20)],
+        );
+        assert!(parse(&packet).is_ok());
+        for len in 0..packet.len() {
+            assert!(parse(&packet[..len]).is_err(), "prefix of {len} bytes");
+        }
+    }
+
+    #[test]
+    fn rejects_looping_and_forward_pointers() {
+        // A pointer to itself, and pointers forwards (every cycle needs one)
+        let mut looping = header(0x8400, 0, 1);
+        looping.extend_from_slice(&[0xc0, 12]);
+        looping.extend_from_slice(&[0; 10]);
+        assert!(parse(&looping).is_err());
+        assert!(read_name(&[3, b'a', b'b', b'c', 0xc0, 6, 0xc0, 4], 4).is_err());
+        assert!(read_name(&[0xc0, 2, 0], 0).is_err());
+
+        // A backward pointer to the name's own start repeats its label until the length limit
+        let mut repeating = vec![63];
+        repeating.extend_from_slice(&[b'a'; 63]);
+        repeating.extend_from_slice(&[0xc0, 0]);
+        assert_eq!(read_name(&repeating, 0).unwrap_err(), "name too long");
+
+        // A dot inside a label would read as two labels
+        assert!(read_name(&[3, b'a', b'.', b'b', 0], 0).is_err());
+    }
+
+    #[test]
+    fn rejects_oversize_counts_and_overrunning_rdata() {
+        let mut counts = header(0x8400, 0xffff, 0xffff);
+        counts.extend_from_slice(&[0; 32]);
+        assert_eq!(
+            parse(&counts).unwrap_err(),
+            "record counts exceed the message"
+        );
+
+        // A PTR whose target name continues past the record's rdata
+        let mut packet = header(0x8400, 0, 1);
+        let mut rdata = Vec::new();
+        put_name(&mut rdata, "peer._blvm-regtest._tcp.local");
+        put_name(&mut packet, "_blvm-regtest._tcp.local");
+        packet.extend_from_slice(&TYPE_PTR.to_be_bytes());
+        packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+        packet.extend_from_slice(&TTL_SECS.to_be_bytes());
+        packet.extend_from_slice(&3u16.to_be_bytes());
+        packet.extend_from_slice(&rdata);
+        assert!(parse(&packet).is_err());
+    }
+
+    #[test]
+    fn restricts_to_local_addresses() {
+        for local in [
+            "192.168.0.1",
+            "10.1.2.3",
+            "172.16.0.9",
+            "127.0.0.1",
+            "fe80::1",
+            "fd00::1",
+        ] {
+            assert!(is_local_address(&local.parse().unwrap()), "{local}");
+        }
+        for public in ["8.8.8.8", "2001:db8::1", "172.32.0.1"] {
+            assert!(!is_local_address(&public.parse().unwrap()), "{public}");
+        }
+    }
+}
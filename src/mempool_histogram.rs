@@ -0,0 +1,281 @@
+//! Mempool fee-rate histogram (`getmempoolhistogram`, `blvm mempool histogram`)
+//!
+//! Transactions are grouped into fixed fee-rate buckets (sat/vB) with their count, vsize and
+//! fees. The histogram is kept incrementally: each update only looks up transactions that are
+//! new since the last one and drops those that left.
+
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+
+/// Lower bounds (sat/vB) of the buckets after the first; the first bucket is everything below
+/// 1 sat/vB
+pub const FEE_RATE_BOUNDS: &[f64] = &[
+    1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0, 12.0, 15.0, 20.0, 30.0, 40.0, 50.0, 60.0, 80.0, 100.0,
+    125.0, 150.0, 200.0, 300.0, 500.0, 1000.0,
+];
+
+/// A mempool transaction's size and fee
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub vsize: u64,
+    /// Satoshis
+    pub fee: u64,
+}
+
+impl Entry {
+    /// From a `getmempoolentry` / verbose `getrawmempool` object (`fees.base` or `fee`, in BTC)
+    pub fn from_rpc(entry: &Value) -> Option<Self> {
+        let vsize = entry.get("vsize").or_else(|| entry.get("size"))?.as_u64()?;
+        let fee_btc = entry
+            .get("fees")
+            .and_then(|f| f.get("base"))
+            .or_else(|| entry.get("fee"))?
+            .as_f64()?;
+        Some(Self {
+            vsize,
+            fee: (fee_btc * 100_000_000.0).round() as u64,
+        })
+    }
+
+    pub fn fee_rate(&self) -> f64 {
+        self.fee as f64 / self.vsize.max(1) as f64
+    }
+}
+
+fn bucket_index(fee_rate: f64) -> usize {
+    FEE_RATE_BOUNDS
+        .iter()
+        .take_while(|&&b| fee_rate >= b)
+        .count()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Bucket {
+    pub count: u64,
+    pub vsize: u64,
+    pub fees: u64,
+}
+
+/// One histogram row: `[from, to)` sat/vB (`to` is `None` for the top bucket)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Row {
+    pub from: f64,
+    pub to: Option<f64>,
+    pub bucket: Bucket,
+}
+
+#[derive(Debug, Clone)]
+pub struct MempoolHistogram {
+    entries: HashMap<String, (usize, Entry)>,
+    buckets: Vec<Bucket>,
+}
+
+impl Default for MempoolHistogram {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            buckets: vec![Bucket::default(); FEE_RATE_BOUNDS.len() + 1],
+        }
+    }
+}
+
+impl MempoolHistogram {
+    pub fn insert(&mut self, txid: String, entry: Entry) {
+        self.remove(&txid);
+        let index = bucket_index(entry.fee_rate());
+        let bucket = &mut self.buckets[index];
+        bucket.count += 1;
+        bucket.vsize += entry.vsize;
+        bucket.fees += entry.fee;
+        self.entries.insert(txid, (index, entry));
+    }
+
+    pub fn remove(&mut self, txid: &str) {
+        if let Some((index, entry)) = self.entries.remove(txid) {
+            let bucket = &mut self.buckets[index];
+            bucket.count -= 1;
+            bucket.vsize -= entry.vsize;
+            bucket.fees -= entry.fee;
+        }
+    }
+
+    /// Drop transactions no longer in `txids`; returns those not in the histogram yet
+    pub fn sync(&mut self, txids: &[String]) -> Vec<String> {
+        let current: HashSet<&str> = txids.iter().map(String::as_str).collect();
+        let gone: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|txid| !current.contains(txid.as_str()))
+            .cloned()
+            .collect();
+        for txid in gone {
+            self.remove(&txid);
+        }
+        txids
+            .iter()
+            .filter(|txid| !self.entries.contains_key(*txid))
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Buckets from the lowest fee rate up
+    pub fn rows(&self) -> Vec<Row> {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(i, bucket)| Row {
+                from: if i == 0 { 0.0 } else { FEE_RATE_BOUNDS[i - 1] },
+                to: FEE_RATE_BOUNDS.get(i).copied(),
+                bucket: *bucket,
+            })
+            .collect()
+    }
+
+    /// `getmempoolhistogram` result; `fee_histogram` is `[fee rate, vsize]` pairs from the
+    /// highest fee rate down, as mempool.space serves it
+    pub fn to_json(&self) -> Value {
+        let rows = self.rows();
+        json!({
+            "count": self.len(),
+            "vsize": rows.iter().map(|r| r.bucket.vsize).sum::<u64>(),
+            "fees": rows.iter().map(|r| r.bucket.fees).sum::<u64>(),
+            "buckets": rows
+                .iter()
+                .map(|r| json!({
+                    "from": r.from,
+                    "to": r.to,
+                    "count": r.bucket.count,
+                    "vsize": r.bucket.vsize,
+                    "fees": r.bucket.fees,
+                }))
+                .collect::<Vec<_>>(),
+            "fee_histogram": rows
+                .iter()
+                .rev()
+                .filter(|r| r.bucket.count > 0)
+                .map(|r| json!([r.from, r.bucket.vsize]))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Rows from a `getmempoolhistogram` result
+pub fn rows_from_json(value: &Value) -> Vec<Row> {
+    value
+        .get("buckets")
+        .and_then(|b| b.as_array())
+        .into_iter()
+        .flatten()
+        .map(|b| Row {
+            from: b.get("from").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            to: b.get("to").and_then(|v| v.as_f64()),
+            bucket: Bucket {
+                count: b.get("count").and_then(|v| v.as_u64()).unwrap_or(0),
+                vsize: b.get("vsize").and_then(|v| v.as_u64()).unwrap_or(0),
+                fees: b.get("fees").and_then(|v| v.as_u64()).unwrap_or(0),
+            },
+        })
+        .collect()
+}
+
+/// Horizontal bars of vsize per non-empty bucket, highest fee rate first
+pub fn render(rows: &[Row], width: usize) -> Vec<String> {
+    let max = rows
+        .iter()
+        .map(|r| r.bucket.vsize)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    rows.iter()
+        .rev()
+        .filter(|r| r.bucket.count > 0)
+        .map(|r| {
+            let range = match r.to {
+                Some(to) => format!("{}-{}", r.from, to),
+                None => format!("{}+", r.from),
+            };
+            let bar = (r.bucket.vsize as f64 / max as f64 * width as f64).ceil() as usize;
+            format!(
+                "{range:>10} sat/vB │{:<width$} {:>9} vB {:>6} tx",
+                "█".repeat(bar),
+                r.bucket.vsize,
+                r.bucket.count,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incremental_buckets() {
+        let mut histogram = MempoolHistogram::default();
+        let txids: Vec<String> = ["a", "b", "c"].map(String::from).to_vec();
+        assert_eq!(histogram.sync(&txids), txids);
+        histogram.insert(
+            "a".into(),
+            Entry {
+                vsize: 100,
+                fee: 150,
+            },
+        );
+        histogram.insert(
+            "b".into(),
+            Entry {
+                vsize: 200,
+                fee: 300,
+            },
+        );
+        histogram.insert(
+            "c".into(),
+            Entry {
+                vsize: 100,
+                fee: 2500,
+            },
+        );
+        let rows = histogram.rows();
+        assert_eq!(
+            rows[1].bucket,
+            Bucket {
+                count: 2,
+                vsize: 300,
+                fees: 450
+            }
+        );
+        assert_eq!((rows[1].from, rows[1].to), (1.0, Some(2.0)));
+        assert_eq!(rows[bucket_index(25.0)].bucket.count, 1);
+
+        // "b" was mined, "d" arrived
+        let next: Vec<String> = ["a", "c", "d"].map(String::from).to_vec();
+        assert_eq!(histogram.sync(&next), vec!["d".to_string()]);
+        assert_eq!(histogram.rows()[1].bucket.vsize, 100);
+        assert_eq!(histogram.len(), 2);
+
+        let json = histogram.to_json();
+        assert_eq!(json["count"], 2);
+        assert_eq!(json["fee_histogram"][0], json!([20.0, 100]));
+        assert_eq!(rows_from_json(&json), histogram.rows());
+        let lines = render(&histogram.rows(), 20);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("     20-30 sat/vB"));
+
+        let entry = json!({"vsize": 141, "fees": {"base": 0.00000282}});
+        assert_eq!(
+            Entry::from_rpc(&entry),
+            Some(Entry {
+                vsize: 141,
+                fee: 282
+            })
+        );
+    }
+}
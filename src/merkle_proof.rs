@@ -0,0 +1,332 @@
+//! Transaction inclusion proofs in Core's `gettxoutproof` format (`blvm proof`)
+//!
+//! A proof is a serialized `CMerkleBlock`: the block header followed by a partial merkle
+//! tree (transaction count, the hashes needed to rebuild the root, and traversal flag bits).
+
+use crate::decode::{BlockHeader, DecodeError, Reader, write_compact_size};
+use crate::hash::sha256d;
+
+/// Largest transaction count a proof may claim (Core's bound from the block weight limit)
+const MAX_TRANSACTIONS: u32 = 4_000_000 / 60;
+
+/// Matched transaction: position in the block and txid
+pub type Match = (u32, [u8; 32]);
+
+/// Why a proof failed to verify
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofError {
+    Decode(DecodeError),
+    /// Inconsistent tree shape, unused hashes/flags, or a duplicated subtree (CVE-2012-2459)
+    Malformed(&'static str),
+    /// The rebuilt root does not match the header's merkle root
+    RootMismatch,
+}
+
+impl std::fmt::Display for ProofError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProofError::Decode(e) => write!(f, "invalid proof encoding: {e}"),
+            ProofError::Malformed(reason) => write!(f, "malformed partial merkle tree: {reason}"),
+            ProofError::RootMismatch => write!(f, "merkle root does not match block header"),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+impl From<DecodeError> for ProofError {
+    fn from(e: DecodeError) -> Self {
+        ProofError::Decode(e)
+    }
+}
+
+/// Pruned merkle tree committing to a subset of a block's transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialMerkleTree {
+    pub total_transactions: u32,
+    pub hashes: Vec<[u8; 32]>,
+    pub flags: Vec<bool>,
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(left);
+    buf[32..].copy_from_slice(right);
+    sha256d(&buf)
+}
+
+impl PartialMerkleTree {
+    fn width(&self, height: u32) -> usize {
+        (self.total_transactions as usize + (1 << height) - 1) >> height
+    }
+
+    fn height(&self) -> u32 {
+        let mut height = 0;
+        while self.width(height) > 1 {
+            height += 1;
+        }
+        height
+    }
+
+    /// Build a tree over `txids` (block order) revealing those where `matches` is true
+    pub fn build(txids: &[[u8; 32]], matches: &[bool]) -> Self {
+        let mut tree = PartialMerkleTree {
+            total_transactions: txids.len() as u32,
+            hashes: Vec::new(),
+            flags: Vec::new(),
+        };
+        let height = tree.height();
+        tree.traverse_and_build(height, 0, txids, matches);
+        tree
+    }
+
+    fn calc_hash(&self, height: u32, pos: usize, txids: &[[u8; 32]]) -> [u8; 32] {
+        if height == 0 {
+            return txids[pos];
+        }
+        let left = self.calc_hash(height - 1, pos * 2, txids);
+        let right = if pos * 2 + 1 < self.width(height - 1) {
+            self.calc_hash(height - 1, pos * 2 + 1, txids)
+        } else {
+            left
+        };
+        hash_pair(&left, &right)
+    }
+
+    fn traverse_and_build(
+        &mut self,
+        height: u32,
+        pos: usize,
+        txids: &[[u8; 32]],
+        matches: &[bool],
+    ) {
+        let start = pos << height;
+        let end = ((pos + 1) << height).min(txids.len());
+        let parent_of_match = matches[start..end].iter().any(|&m| m);
+        self.flags.push(parent_of_match);
+        if height == 0 || !parent_of_match {
+            let hash = self.calc_hash(height, pos, txids);
+            self.hashes.push(hash);
+        } else {
+            self.traverse_and_build(height - 1, pos * 2, txids, matches);
+            if pos * 2 + 1 < self.width(height - 1) {
+                self.traverse_and_build(height - 1, pos * 2 + 1, txids, matches);
+            }
+        }
+    }
+
+    /// Rebuild the merkle root; returns it with the matched `(index, txid)` pairs
+    pub fn extract_matches(&self) -> Result<([u8; 32], Vec<Match>), ProofError> {
+        if self.total_transactions == 0 {
+            return Err(ProofError::Malformed("no transactions"));
+        }
+        if self.total_transactions > MAX_TRANSACTIONS {
+            return Err(ProofError::Malformed("too many transactions"));
+        }
+        if self.hashes.len() > self.total_transactions as usize {
+            return Err(ProofError::Malformed("more hashes than transactions"));
+        }
+        if self.flags.len() < self.hashes.len() {
+            return Err(ProofError::Malformed("fewer flag bits than hashes"));
+        }
+        let mut cursor = (0, 0);
+        let mut matches = Vec::new();
+        let root = self.traverse_and_extract(self.height(), 0, &mut cursor, &mut matches)?;
+        let (bits_used, hashes_used) = cursor;
+        if bits_used.div_ceil(8) != self.flags.len().div_ceil(8) {
+            return Err(ProofError::Malformed("unused flag bits"));
+        }
+        if hashes_used != self.hashes.len() {
+            return Err(ProofError::Malformed("unused hashes"));
+        }
+        Ok((root, matches))
+    }
+
+    fn traverse_and_extract(
+        &self,
+        height: u32,
+        pos: usize,
+        cursor: &mut (usize, usize),
+        matches: &mut Vec<Match>,
+    ) -> Result<[u8; 32], ProofError> {
+        let flag = *self
+            .flags
+            .get(cursor.0)
+            .ok_or(ProofError::Malformed("ran out of flag bits"))?;
+        cursor.0 += 1;
+        if height == 0 || !flag {
+            let hash = *self
+                .hashes
+                .get(cursor.1)
+                .ok_or(ProofError::Malformed("ran out of hashes"))?;
+            cursor.1 += 1;
+            if height == 0 && flag {
+                matches.push((pos as u32, hash));
+            }
+            return Ok(hash);
+        }
+        let left = self.traverse_and_extract(height - 1, pos * 2, cursor, matches)?;
+        let right = if pos * 2 + 1 < self.width(height - 1) {
+            let right = self.traverse_and_extract(height - 1, pos * 2 + 1, cursor, matches)?;
+            if right == left {
+                return Err(ProofError::Malformed("duplicate subtree"));
+            }
+            right
+        } else {
+            left
+        };
+        Ok(hash_pair(&left, &right))
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let total_transactions = r.u32()?;
+        let count = r.compact_size()?;
+        let mut hashes = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            hashes.push(r.array()?);
+        }
+        let flag_bytes = r.var_bytes()?;
+        let flags = (0..flag_bytes.len() * 8)
+            .map(|i| flag_bytes[i / 8] & (1 << (i % 8)) != 0)
+            .collect();
+        Ok(Self {
+            total_transactions,
+            hashes,
+            flags,
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.total_transactions.to_le_bytes());
+        write_compact_size(out, self.hashes.len());
+        for hash in &self.hashes {
+            out.extend_from_slice(hash);
+        }
+        let mut flag_bytes = vec![0u8; self.flags.len().div_ceil(8)];
+        for (i, _) in self.flags.iter().enumerate().filter(|(_, f)| **f) {
+            flag_bytes[i / 8] |= 1 << (i % 8);
+        }
+        write_compact_size(out, flag_bytes.len());
+        out.extend_from_slice(&flag_bytes);
+    }
+}
+
+/// Block header plus partial merkle tree (`gettxoutproof` output)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    pub header: BlockHeader,
+    pub tree: PartialMerkleTree,
+}
+
+impl MerkleProof {
+    /// Proof that `wanted` txids are in a block with `header` and transactions `txids`
+    pub fn build(header: BlockHeader, txids: &[[u8; 32]], wanted: &[[u8; 32]]) -> Self {
+        let matches: Vec<bool> = txids.iter().map(|txid| wanted.contains(txid)).collect();
+        Self {
+            header,
+            tree: PartialMerkleTree::build(txids, &matches),
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader::new(bytes);
+        let header = BlockHeader::read(&mut r)?;
+        let tree = PartialMerkleTree::read(&mut r)?;
+        r.finish()?;
+        Ok(Self { header, tree })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = self.header.encode().to_vec();
+        self.tree.write(&mut out);
+        out
+    }
+
+    /// Matched `(index, txid)` pairs, checked against the header's merkle root
+    ///
+    /// This does not check that the header is in the best chain or has valid proof of work.
+    pub fn verify(&self) -> Result<Vec<Match>, ProofError> {
+        let (root, matches) = self.tree.extract_matches()?;
+        if root != self.header.merkle_root {
+            return Err(ProofError::RootMismatch);
+        }
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::merkle_root;
+
+    fn header(merkle_root: [u8; 32]) -> BlockHeader {
+        BlockHeader {
+            version: 0x2000_0000,
+            prev_blockhash: [1; 32],
+            merkle_root,
+            time: 1_700_000_000,
+            bits: 0x207fffff,
+            nonce: 7,
+        }
+    }
+
+    fn txids(n: u8) -> Vec<[u8; 32]> {
+        (0..n).map(|i| sha256d(&[i])).collect()
+    }
+
+    #[test]
+    fn single_transaction_block() {
+        let ids = txids(1);
+        let proof = MerkleProof::build(header(ids[0]), &ids, &ids);
+        assert_eq!(proof.tree.flags, [true]);
+        let bytes = proof.encode();
+        // header + count + 1 hash + 1 flag byte
+        assert_eq!(bytes.len(), 80 + 4 + 1 + 32 + 2);
+        // Flags decode padded to a whole byte; the encoding is unchanged
+        let decoded = MerkleProof::decode(&bytes).unwrap();
+        assert_eq!(decoded.encode(), bytes);
+        assert_eq!(decoded.verify().unwrap(), [(0, ids[0])]);
+    }
+
+    #[test]
+    fn proves_subsets_of_odd_sized_blocks() {
+        for n in [2u8, 3, 7, 10] {
+            let ids = txids(n);
+            let root = merkle_root(ids.clone());
+            for wanted in [
+                vec![ids[0]],
+                vec![ids[n as usize - 1]],
+                vec![ids[1], ids[n as usize - 1]],
+            ] {
+                let proof = MerkleProof::build(header(root), &ids, &wanted);
+                let decoded = MerkleProof::decode(&proof.encode()).unwrap();
+                let matched: Vec<[u8; 32]> = decoded
+                    .verify()
+                    .unwrap()
+                    .into_iter()
+                    .map(|(_, t)| t)
+                    .collect();
+                let mut expected = wanted.clone();
+                expected.dedup();
+                assert_eq!(matched, expected, "n={n}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_tampering() {
+        let ids = txids(5);
+        let proof = MerkleProof::build(header(merkle_root(ids.clone())), &ids, &[ids[2]]);
+        let mut wrong_root = proof.clone();
+        wrong_root.header.merkle_root = [0; 32];
+        assert_eq!(wrong_root.verify(), Err(ProofError::RootMismatch));
+
+        let mut extra_hash = proof.clone();
+        extra_hash.tree.hashes.push([9; 32]);
+        assert!(matches!(extra_hash.verify(), Err(ProofError::Malformed(_))));
+
+        let mut swapped = proof;
+        swapped.tree.hashes.swap(0, 1);
+        assert!(swapped.verify().is_err());
+    }
+}
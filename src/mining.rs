@@ -0,0 +1,556 @@
+//! Mining settings handled by the binary (`[mining]`, `blvm mining config`)
+//!
+//! Payout rotation: with `payout_descriptor` set to a ranged descriptor, the node hands the
+//! Stratum V2 module one derived address at a time and moves on to the next index once a block
+//! paying the current address is mined, so won blocks never share an address. The index lives
+//! in `<data_dir>/mining_payout.json` so restarts never hand out a used address again. It runs
+//! only against a node serving `deriveaddresses` and `callmodule`; blvm-node has no
+//! `callmodule` yet, so `blvm start` leaves rotation off with a warning.
+//!
+//! Template refresh: the module rebuilds templates on new blocks by itself; the node also asks
+//! it to (`refresh_template`) when the fees a fresh template would collect grew materially, so
+//! miners are not left hashing on a stale, cheaper block. Like rotation, this needs the node
+//! to serve `callmodule`.
+//!
+//! Status (`blvm mining status`): template and recent-block summaries from `getmininginfo`,
+//! `getblocktemplate` and block headers.
+//!
+//! Client telemetry (`blvm mining clients`): the template module measures, per connected
+//! miner, the time from job dispatch to share submission; this side flags clients whose
+//! latency, stale rate or missing blocks stand out.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Payout state file in the data directory
+pub const PAYOUT_FILE: &str = "mining_payout.json";
+/// Consensus block weight limit
+pub const MAX_BLOCK_WEIGHT: u64 = 4_000_000;
+/// Weight `getblocktemplate` leaves for the header and coinbase
+const COINBASE_RESERVED_WEIGHT: u64 = 4_000;
+
+/// `[mining]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MiningConfig {
+    /// Ranged watch-only descriptor for coinbase payouts, e.g. `wpkh(xpub.../0/*)`
+    pub payout_descriptor: Option<String>,
+    /// Module serving block templates to miners; receives `set_payout` and `refresh_template`
+    pub template_module: String,
+    /// `[mining.template_refresh]`: refresh templates when mempool fees change
+    pub template_refresh: TemplateRefreshConfig,
+}
+
+impl Default for MiningConfig {
+    fn default() -> Self {
+        Self {
+            payout_descriptor: None,
+            template_module: "blvm-stratum-v2".to_string(),
+            template_refresh: TemplateRefreshConfig::default(),
+        }
+    }
+}
+
+/// `[mining.template_refresh]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TemplateRefreshConfig {
+    pub enabled: bool,
+    /// How often the candidate template's fees are checked
+    pub check_interval_secs: u64,
+    /// Refresh when fees grew by this many percent since the miners' template
+    pub fee_delta_percent: f64,
+    /// ...and by at least this many satoshis (ignores noise while the mempool is nearly empty)
+    pub min_fee_delta_sats: u64,
+    /// Never refresh more often than this
+    pub min_interval_secs: u64,
+    /// Refresh at least this often while fees changed at all (0 = only on fee growth)
+    pub max_interval_secs: u64,
+}
+
+impl Default for TemplateRefreshConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_secs: 5,
+            fee_delta_percent: 5.0,
+            min_fee_delta_sats: 10_000,
+            min_interval_secs: 10,
+            max_interval_secs: 0,
+        }
+    }
+}
+
+/// Why a template refresh was requested
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshReason {
+    FeeIncrease,
+    Interval,
+}
+
+impl RefreshReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefreshReason::FeeIncrease => "fee_increase",
+            RefreshReason::Interval => "interval",
+        }
+    }
+}
+
+/// Fees of the template miners work on, for deciding when a new one is worth pushing
+#[derive(Debug, Clone, Default)]
+pub struct TemplateTracker {
+    /// Tip the template builds on; a new tip resets the baseline (the module refreshes then)
+    prev_block: String,
+    /// Fees of the last template sent to miners
+    fees: u64,
+    /// Unix time the last template was sent
+    sent_at: u64,
+}
+
+impl TemplateTracker {
+    /// Feed a candidate template's tip and total fees; returns whether to push it now.
+    /// The caller reports a successful push with [`TemplateTracker::sent`].
+    pub fn check(
+        &mut self,
+        settings: &TemplateRefreshConfig,
+        prev_block: &str,
+        fees: u64,
+        now: u64,
+    ) -> Option<RefreshReason> {
+        if prev_block != self.prev_block {
+            self.prev_block = prev_block.to_string();
+            self.sent(fees, now);
+            return None;
+        }
+        let elapsed = now.saturating_sub(self.sent_at);
+        if elapsed < settings.min_interval_secs || fees == self.fees {
+            return None;
+        }
+        let delta = fees.saturating_sub(self.fees);
+        if delta >= settings.min_fee_delta_sats
+            && delta as f64 >= self.fees as f64 * settings.fee_delta_percent / 100.0
+        {
+            return Some(RefreshReason::FeeIncrease);
+        }
+        if settings.max_interval_secs > 0 && elapsed >= settings.max_interval_secs {
+            return Some(RefreshReason::Interval);
+        }
+        None
+    }
+
+    pub fn sent(&mut self, fees: u64, now: u64) {
+        self.fees = fees;
+        self.sent_at = now;
+    }
+}
+
+/// Check a payout descriptor and return it with its checksum
+pub fn validate_payout_descriptor(descriptor: &str) -> Result<String, String> {
+    use crate::descriptor::{Wildcard, key_expressions, with_checksum};
+    let descriptor = with_checksum(descriptor)?;
+    let keys = key_expressions(&descriptor)?;
+    if keys.iter().any(|key| key.is_private()) {
+        return Err("payout_descriptor must not contain private keys".to_string());
+    }
+    if keys
+        .iter()
+        .any(|key| key.wildcard == Some(Wildcard::Hardened))
+    {
+        return Err(
+            "payout_descriptor ranges must be unhardened (/*): hardened children cannot be \
+             derived from public keys"
+                .to_string(),
+        );
+    }
+    if !keys.iter().any(|key| key.wildcard.is_some()) {
+        return Err("payout_descriptor must be ranged (end in /*) to rotate addresses".to_string());
+    }
+    Ok(descriptor)
+}
+
+/// Derivation index in use for the configured descriptor
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PayoutState {
+    /// Descriptor the index belongs to (with checksum)
+    pub descriptor: String,
+    /// Index of the address templates currently pay to
+    pub index: u32,
+    /// Address derived at `index`, once known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Blocks paid to addresses of this descriptor
+    #[serde(default)]
+    pub blocks_found: u32,
+}
+
+impl PayoutState {
+    pub fn load(data_dir: &Path) -> Option<Self> {
+        std::fs::read_to_string(data_dir.join(PAYOUT_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    /// State for `descriptor`, starting at index 0 when the descriptor changed
+    pub fn load_for(data_dir: &Path, descriptor: &str) -> Self {
+        match Self::load(data_dir) {
+            Some(state) if state.descriptor == descriptor => state,
+            _ => PayoutState {
+                descriptor: descriptor.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn save(&self, data_dir: &Path) -> anyhow::Result<()> {
+        let path = data_dir.join(PAYOUT_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// The current address received a block reward: move on to the next index
+    pub fn advance(&mut self) {
+        self.index += 1;
+        self.address = None;
+        self.blocks_found += 1;
+    }
+}
+
+/// What the next block would contain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TemplateStats {
+    pub tx_count: usize,
+    /// Including the reserve for header and coinbase
+    pub weight: u64,
+    /// Satoshis
+    pub fees: u64,
+}
+
+impl TemplateStats {
+    /// Summarize a `getblocktemplate` result
+    pub fn from_template(template: &Value) -> Self {
+        let txs = template["transactions"].as_array().map_or(&[][..], |t| t);
+        TemplateStats {
+            tx_count: txs.len(),
+            weight: COINBASE_RESERVED_WEIGHT
+                + txs
+                    .iter()
+                    .filter_map(|tx| tx["weight"].as_u64())
+                    .sum::<u64>(),
+            fees: txs.iter().filter_map(|tx| tx["fee"].as_u64()).sum(),
+        }
+    }
+}
+
+/// Spacing of recent blocks in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockIntervals {
+    pub mean: f64,
+    pub min: i64,
+    pub max: i64,
+}
+
+/// Intervals between consecutive block timestamps (oldest first); `None` with fewer than two.
+/// Timestamps may run backwards by consensus, so single intervals can be negative.
+pub fn block_intervals(times: &[u64]) -> Option<BlockIntervals> {
+    let deltas: Vec<i64> = times
+        .windows(2)
+        .map(|w| w[1] as i64 - w[0] as i64)
+        .collect();
+    if deltas.is_empty() {
+        return None;
+    }
+    Some(BlockIntervals {
+        mean: (times[times.len() - 1] as f64 - times[0] as f64) / deltas.len() as f64,
+        min: *deltas.iter().min()?,
+        max: *deltas.iter().max()?,
+    })
+}
+
+/// Hashes per second that find blocks of `difficulty` every `interval_secs` on average
+pub fn estimate_hashrate(difficulty: f64, interval_secs: f64) -> f64 {
+    if interval_secs <= 0.0 {
+        return 0.0;
+    }
+    difficulty * 4_294_967_296.0 / interval_secs
+}
+
+/// `612.35 EH/s`
+pub fn format_hashrate(hashes_per_sec: f64) -> String {
+    const UNITS: [&str; 7] = ["H/s", "kH/s", "MH/s", "GH/s", "TH/s", "PH/s", "EH/s"];
+    let mut value = hashes_per_sec;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{value:.2} {}", UNITS[unit])
+}
+
+/// Stale-share share above which a client is flagged
+const STALE_RATE_LIMIT: f64 = 0.05;
+/// p95 latency above this multiple of the median client's p50 is flagged
+const LATENCY_FACTOR: f64 = 4.0;
+/// Expected blocks at which finding none has a probability below 1% (e^-4.6)
+const WITHHOLDING_EXPECTED_BLOCKS: f64 = 4.6;
+
+/// Per-client counters reported by the template module's `client_stats` endpoint
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientStats {
+    pub id: String,
+    /// Worker / user name the client authorized with
+    pub worker: String,
+    pub accepted_shares: u64,
+    /// Shares for jobs that were already replaced
+    pub stale_shares: u64,
+    /// Sum of the difficulties of accepted shares
+    pub work: f64,
+    pub blocks_found: u64,
+    /// Job dispatch to share submission
+    pub latency_p50_ms: u64,
+    pub latency_p95_ms: u64,
+}
+
+impl ClientStats {
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let u = |key: &str| value[key].as_u64().unwrap_or(0);
+        Some(ClientStats {
+            id: value["id"].as_str()?.to_string(),
+            worker: value["worker"].as_str().unwrap_or_default().to_string(),
+            accepted_shares: u("accepted_shares"),
+            stale_shares: u("stale_shares"),
+            work: value["work"].as_f64().unwrap_or(0.0),
+            blocks_found: u("blocks_found"),
+            latency_p50_ms: u("latency_p50_ms"),
+            latency_p95_ms: u("latency_p95_ms"),
+        })
+    }
+
+    /// Blocks the submitted work should have found at `network_difficulty`
+    pub fn expected_blocks(&self, network_difficulty: f64) -> f64 {
+        if network_difficulty <= 0.0 {
+            return 0.0;
+        }
+        self.work / network_difficulty
+    }
+
+    pub fn stale_rate(&self) -> f64 {
+        let total = self.accepted_shares + self.stale_shares;
+        if total == 0 {
+            return 0.0;
+        }
+        self.stale_shares as f64 / total as f64
+    }
+}
+
+/// Why a client looks suspicious or unhealthy
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientAnomaly {
+    /// Enough work for several blocks but none submitted
+    PossibleWithholding {
+        expected_blocks: f64,
+    },
+    HighLatency {
+        p95_ms: u64,
+        typical_ms: u64,
+    },
+    HighStaleRate {
+        rate: f64,
+    },
+}
+
+impl std::fmt::Display for ClientAnomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientAnomaly::PossibleWithholding { expected_blocks } => write!(
+                f,
+                "possible block withholding: {expected_blocks:.1} blocks expected, none found"
+            ),
+            ClientAnomaly::HighLatency { p95_ms, typical_ms } => write!(
+                f,
+                "high latency: p95 {p95_ms} ms vs {typical_ms} ms typical"
+            ),
+            ClientAnomaly::HighStaleRate { rate } => {
+                write!(f, "high stale rate: {:.1}%", rate * 100.0)
+            }
+        }
+    }
+}
+
+/// Anomalies of `client` compared with all `clients` of the pool
+pub fn client_anomalies(
+    client: &ClientStats,
+    clients: &[ClientStats],
+    network_difficulty: f64,
+) -> Vec<ClientAnomaly> {
+    let mut anomalies = Vec::new();
+    let expected_blocks = client.expected_blocks(network_difficulty);
+    if client.blocks_found == 0 && expected_blocks >= WITHHOLDING_EXPECTED_BLOCKS {
+        anomalies.push(ClientAnomaly::PossibleWithholding { expected_blocks });
+    }
+    let mut p50s: Vec<u64> = clients
+        .iter()
+        .filter(|c| c.accepted_shares > 0)
+        .map(|c| c.latency_p50_ms)
+        .collect();
+    p50s.sort_unstable();
+    // A fleet median needs a few clients to compare against
+    if p50s.len() >= 3 {
+        let typical_ms = p50s[p50s.len() / 2];
+        if client.latency_p95_ms as f64 > typical_ms.max(1) as f64 * LATENCY_FACTOR {
+            anomalies.push(ClientAnomaly::HighLatency {
+                p95_ms: client.latency_p95_ms,
+                typical_ms,
+            });
+        }
+    }
+    let rate = client.stale_rate();
+    if rate > STALE_RATE_LIMIT {
+        anomalies.push(ClientAnomaly::HighStaleRate { rate });
+    }
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XPUB: &str = "wpkh(xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8/0/*)";
+
+    #[test]
+    fn validates_descriptors() {
+        let descriptor = validate_payout_descriptor(XPUB).unwrap();
+        assert!(descriptor.starts_with(XPUB) && descriptor.contains('#'));
+        assert!(validate_payout_descriptor(&XPUB.replace("/*", "/5")).is_err());
+        assert!(validate_payout_descriptor(&XPUB.replace("/*", "/*h")).is_err());
+        assert!(validate_payout_descriptor(&XPUB.replace("/*", "/*'")).is_err());
+        assert!(validate_payout_descriptor(&XPUB.replace("xpub", "xprv")).is_err());
+    }
+
+    #[test]
+    fn summarizes_templates_and_blocks() {
+        let template = serde_json::json!({"transactions": [
+            {"fee": 1_000, "weight": 561},
+            {"fee": 25_000, "weight": 1_000},
+        ]});
+        assert_eq!(
+            TemplateStats::from_template(&template),
+            TemplateStats {
+                tx_count: 2,
+                weight: 5_561,
+                fees: 26_000
+            }
+        );
+        let intervals = block_intervals(&[1_000, 1_600, 1_500, 2_800]).unwrap();
+        assert_eq!(
+            (intervals.mean, intervals.min, intervals.max),
+            (600.0, -100, 1_300)
+        );
+        assert!(block_intervals(&[1_000]).is_none());
+        // Difficulty 1 at 600 s per block is ~7.16 MH/s
+        assert_eq!(format_hashrate(estimate_hashrate(1.0, 600.0)), "7.16 MH/s");
+        assert_eq!(format_hashrate(6.1235e20), "612.35 EH/s");
+    }
+
+    #[test]
+    fn flags_client_anomalies() {
+        let client = |id: &str, p50: u64, p95: u64| ClientStats {
+            id: id.to_string(),
+            accepted_shares: 1_000,
+            latency_p50_ms: p50,
+            latency_p95_ms: p95,
+            ..Default::default()
+        };
+        let mut pool = vec![
+            client("a", 40, 90),
+            client("b", 50, 120),
+            client("c", 45, 400),
+        ];
+        pool[0].work = 5.0e12;
+        pool[1].work = 5.0e12;
+        pool[1].blocks_found = 1;
+        pool[2].stale_shares = 100;
+        let difficulty = 1.0e12;
+
+        // "a" did 5 blocks' worth of work without finding one
+        assert_eq!(
+            client_anomalies(&pool[0], &pool, difficulty),
+            vec![ClientAnomaly::PossibleWithholding {
+                expected_blocks: 5.0
+            }]
+        );
+        assert!(client_anomalies(&pool[1], &pool, difficulty).is_empty());
+        let flagged = client_anomalies(&pool[2], &pool, difficulty);
+        assert_eq!(
+            flagged[0],
+            ClientAnomaly::HighLatency {
+                p95_ms: 400,
+                typical_ms: 45
+            }
+        );
+        assert!(matches!(flagged[1], ClientAnomaly::HighStaleRate { rate } if rate > 0.09));
+        // Too few clients to judge latency
+        assert_eq!(client_anomalies(&pool[2], &pool[..2], difficulty).len(), 1);
+
+        let parsed = ClientStats::from_value(&serde_json::json!({
+            "id": "c1", "worker": "rig1", "accepted_shares": 10, "latency_p95_ms": 80
+        }))
+        .unwrap();
+        assert_eq!(
+            (parsed.worker.as_str(), parsed.accepted_shares),
+            ("rig1", 10)
+        );
+        assert!(ClientStats::from_value(&serde_json::json!({"worker": "x"})).is_none());
+    }
+
+    #[test]
+    fn refreshes_on_material_fee_growth() {
+        let settings = TemplateRefreshConfig {
+            max_interval_secs: 60,
+            ..Default::default()
+        };
+        let mut tracker = TemplateTracker::default();
+        // A new tip sets the baseline without a refresh
+        assert_eq!(tracker.check(&settings, "tip1", 1_000_000, 100), None);
+        // +10% but within min_interval
+        assert_eq!(tracker.check(&settings, "tip1", 1_100_000, 105), None);
+        assert_eq!(
+            tracker.check(&settings, "tip1", 1_100_000, 110),
+            Some(RefreshReason::FeeIncrease)
+        );
+        tracker.sent(1_100_000, 110);
+        // +1%: not material until max_interval passes
+        assert_eq!(tracker.check(&settings, "tip1", 1_111_000, 130), None);
+        assert_eq!(
+            tracker.check(&settings, "tip1", 1_111_000, 170),
+            Some(RefreshReason::Interval)
+        );
+        // Unchanged fees never trigger; a new tip resets
+        tracker.sent(1_111_000, 170);
+        assert_eq!(tracker.check(&settings, "tip1", 1_111_000, 400), None);
+        assert_eq!(tracker.check(&settings, "tip2", 5_000, 401), None);
+        // Small absolute growth on a nearly empty mempool is ignored
+        assert_eq!(tracker.check(&settings, "tip2", 9_000, 420), None);
+    }
+
+    #[test]
+    fn index_persists_per_descriptor() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut state = PayoutState::load_for(dir.path(), "wpkh(A/0/*)#x");
+        assert_eq!(state.index, 0);
+        state.address = Some("bc1qfirst".to_string());
+        state.advance();
+        state.save(dir.path()).unwrap();
+
+        let state = PayoutState::load_for(dir.path(), "wpkh(A/0/*)#x");
+        assert_eq!(
+            (state.index, state.blocks_found, state.address),
+            (1, 1, None)
+        );
+        // A different descriptor starts over
+        assert_eq!(PayoutState::load_for(dir.path(), "wpkh(B/0/*)#y").index, 0);
+    }
+}
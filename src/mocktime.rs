@@ -0,0 +1,105 @@
+//! Mockable wall clock (`setmocktime`, `bumpmocktime`; regtest only)
+//!
+//! Background tasks of `blvm start` (persistent peer backoff, alerts, replica checks, event and
+//! archive timestamps, ...) read the time through [`unix_now`]. While a mock time is set it
+//! returns that instead of the system clock and stands still until set or bumped again.
+//!
+//! Mempool expiry, peer timeouts and validation run inside blvm-node and read its clock, which
+//! only the node's own `setmocktime` can move. The RPC front therefore forwards every change to
+//! the node and fails the call, leaving this clock alone, when the node has no `setmocktime`.
+
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Largest `bumpmocktime` step (one year), as Core allows
+pub const MAX_BUMP_SECS: u64 = 3600 * 24 * 365;
+
+/// Mock time in seconds; 0 = use the system clock
+static MOCK_TIME: AtomicU64 = AtomicU64::new(0);
+
+/// Current Unix time in seconds: the mock time if set, else the system clock
+pub fn unix_now() -> u64 {
+    match MOCK_TIME.load(Ordering::Relaxed) {
+        0 => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        mock => mock,
+    }
+}
+
+/// The mock time, if one is set
+pub fn mock_time() -> Option<u64> {
+    Some(MOCK_TIME.load(Ordering::Relaxed)).filter(|&t| t != 0)
+}
+
+/// Set the mock time; 0 goes back to the system clock
+pub fn set(time: u64) {
+    MOCK_TIME.store(time, Ordering::Relaxed);
+}
+
+/// Advance the mock time by `secs`; returns the new time
+pub fn bump(secs: u64) -> Result<u64, String> {
+    if secs > MAX_BUMP_SECS {
+        return Err(format!(
+            "delta_time must not be negative or greater than {MAX_BUMP_SECS}"
+        ));
+    }
+    MOCK_TIME
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |t| {
+            (t != 0).then(|| t + secs)
+        })
+        .map(|t| t + secs)
+        .map_err(|_| {
+            "No mocktime set, cannot be bumped. Please call setmocktime first.".to_string()
+        })
+}
+
+/// Apply a `setmocktime [timestamp]` or `bumpmocktime [delta_time]` call; returns the resulting
+/// mock time (0 = cleared)
+pub fn apply_rpc(method: &str, params: &Value) -> Result<u64, String> {
+    let arg = params.get(0).and_then(|v| v.as_i64());
+    match (method, arg) {
+        (_, Some(n)) if n < 0 => Err(match method {
+            "setmocktime" => format!("Mocktime cannot be negative: {n}"),
+            _ => format!("delta_time must not be negative or greater than {MAX_BUMP_SECS}"),
+        }),
+        ("setmocktime", Some(time)) => {
+            set(time as u64);
+            Ok(time as u64)
+        }
+        ("bumpmocktime", Some(secs)) => bump(secs as u64),
+        (_, None) => Err(format!("{method} expects one integer argument")),
+        _ => Err(format!("{method} is not a mock time call")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // One test: the clock is process-wide
+    #[test]
+    fn set_bump_and_clear() {
+        set(0);
+        assert!(bump(10).is_err());
+        assert_eq!(mock_time(), None);
+        assert!(unix_now() > 1_600_000_000);
+
+        assert_eq!(
+            apply_rpc("setmocktime", &json!([1_700_000_000])),
+            Ok(1_700_000_000)
+        );
+        assert_eq!(unix_now(), 1_700_000_000);
+        assert_eq!(apply_rpc("bumpmocktime", &json!([600])), Ok(1_700_000_600));
+        assert_eq!(mock_time(), Some(1_700_000_600));
+        assert!(apply_rpc("bumpmocktime", &json!([-1])).is_err());
+        assert!(bump(MAX_BUMP_SECS + 1).is_err());
+        assert!(apply_rpc("setmocktime", &json!([-5])).is_err());
+        assert!(apply_rpc("setmocktime", &json!([])).is_err());
+
+        assert_eq!(apply_rpc("setmocktime", &json!([0])), Ok(0));
+        assert_eq!(mock_time(), None);
+    }
+}
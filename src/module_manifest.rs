@@ -0,0 +1,133 @@
+//! Module manifest (`module.toml`) parsing and offline checks
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Event types a manifest may list under `[capabilities].events`
+pub const MODULE_MESSAGE_TYPES: &[&str] = &[
+    "block_connected",
+    "block_disconnected",
+    "mempool_tx_added",
+    "mempool_tx_removed",
+    "shutdown",
+];
+
+/// Module manifest
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModuleManifest {
+    /// Module name (directory, binary and config section name)
+    pub name: String,
+    /// Module semantic version
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Binary executed by the node
+    pub entry_point: String,
+    #[serde(default)]
+    pub capabilities: ModuleCapabilities,
+    #[serde(default)]
+    pub resources: ModuleResources,
+}
+
+/// Capabilities declared in `[capabilities]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModuleCapabilities {
+    /// Event message types the module subscribes to
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Node cargo features the module needs (e.g. `stratum-v2`)
+    #[serde(default)]
+    pub required_features: Vec<String>,
+}
+
+/// Limits declared in `[resources]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModuleResources {
+    /// Size cap of the module's data directory; the node unloads the module past it
+    #[serde(default)]
+    pub disk_quota_mb: Option<u64>,
+}
+
+impl ModuleManifest {
+    /// Load a manifest from `module.toml`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| anyhow::anyhow!("Failed to read module manifest: {}", e))?;
+        toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse module manifest: {}", e))
+    }
+
+    /// Manifest problems found without the node: name, event and feature checks, and resource
+    /// limits. Empty when none are found.
+    pub fn compatibility_errors(&self, node_features: &[&str]) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !crate::scaffold::is_valid_module_name(&self.name) {
+            errors.push(format!("Invalid module name '{}'", self.name));
+        }
+        for event in &self.capabilities.events {
+            if !MODULE_MESSAGE_TYPES.contains(&event.as_str()) {
+                errors.push(format!("Unknown event type '{event}'"));
+            }
+        }
+        for feature in &self.capabilities.required_features {
+            if !node_features.contains(&feature.as_str()) {
+                errors.push(format!(
+                    "Requires node feature '{feature}', which this build does not include"
+                ));
+            }
+        }
+        if self.resources.disk_quota_mb == Some(0) {
+            errors.push("disk_quota_mb must be at least 1".to_string());
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(toml: &str) -> ModuleManifest {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn minimal_manifest_passes() {
+        let m = manifest("name = \"idx\"\nversion = \"0.1.0\"\nentry_point = \"idx\"\n");
+        assert!(m.capabilities.events.is_empty());
+        assert!(m.compatibility_errors(&[]).is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_events_and_missing_features() {
+        let m = manifest(
+            "name = \"idx\"\nversion = \"0.1.0\"\nentry_point = \"idx\"\n\
+             [capabilities]\nevents = [\"block_connected\", \"bogus\"]\nrequired_features = [\"stratum-v2\"]\n",
+        );
+        let errors = m.compatibility_errors(&["sigop"]);
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors[0].contains("'bogus'"));
+        assert!(errors[1].contains("stratum-v2"));
+    }
+
+    #[test]
+    fn disk_quota_is_optional_and_positive() {
+        let m = manifest("name = \"idx\"\nversion = \"0.1.0\"\nentry_point = \"idx\"\n");
+        assert_eq!(m.resources.disk_quota_mb, None);
+        let m = manifest(
+            "name = \"idx\"\nversion = \"0.1.0\"\nentry_point = \"idx\"\n[resources]\ndisk_quota_mb = 0\n",
+        );
+        assert_eq!(m.compatibility_errors(&[]).len(), 1);
+    }
+
+    #[test]
+    fn scaffold_manifest_is_compatible() {
+        let files = crate::scaffold::module_files("my-indexer");
+        let toml = files
+            .iter()
+            .find(|f| f.path.ends_with("module.toml"))
+            .unwrap();
+        let m = manifest(&toml.contents);
+        assert!(m.compatibility_errors(&[]).is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+//! Per-module data directories (`<modules data_dir>/<name>/`) and disk quotas
+//!
+//! `blvm start` provisions a directory for every installed module before the node loads them:
+//! created if missing, owned by the same user as the modules data directory and closed to
+//! other users (0700). Running it again only repairs drift. A module whose manifest sets
+//! `[resources] disk_quota_mb` is unloaded once its directory grows past the quota.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Create or repair `name`'s data directory under `root`; returns its path
+pub fn provision(root: &Path, name: &str) -> io::Result<PathBuf> {
+    if !crate::scaffold::is_valid_module_name(name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid module name '{name}'"),
+        ));
+    }
+    let dir = root.join(name);
+    std::fs::create_dir_all(&dir)?;
+    let meta = std::fs::symlink_metadata(&dir)?;
+    if !meta.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} is not a directory", dir.display()),
+        ));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+        let owner = std::fs::metadata(root)?;
+        if (meta.uid(), meta.gid()) != (owner.uid(), owner.gid()) {
+            std::os::unix::fs::chown(&dir, Some(owner.uid()), Some(owner.gid()))?;
+        }
+        if meta.mode() & 0o777 != 0o700 {
+            std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+    }
+    Ok(dir)
+}
+
+/// Bytes used by the files under `dir` (symlinks are not followed); 0 when it does not exist
+pub fn usage(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Some((e.path(), e.metadata().ok()?)))
+        .map(|(path, meta)| {
+            if meta.is_dir() {
+                usage(&path)
+            } else {
+                meta.len()
+            }
+        })
+        .sum()
+}
+
+/// Usage against a module's quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub used: u64,
+    /// From the manifest's `disk_quota_mb`; `None` = unlimited
+    pub quota: Option<u64>,
+}
+
+impl DiskUsage {
+    pub fn measure(dir: &Path, quota_mb: Option<u64>) -> Self {
+        Self {
+            used: usage(dir),
+            quota: quota_mb.map(|mb| mb * 1024 * 1024),
+        }
+    }
+
+    pub fn exceeded(&self) -> bool {
+        self.quota.is_some_and(|quota| self.used > quota)
+    }
+
+    /// `12.0 MiB of 100.0 MiB (12%)`, or just the usage without a quota
+    pub fn describe(&self) -> String {
+        let used = crate::datadir::format_bytes(self.used);
+        match self.quota {
+            Some(quota) => format!(
+                "{used} of {} ({}%)",
+                crate::datadir::format_bytes(quota),
+                self.used * 100 / quota.max(1)
+            ),
+            None => format!("{used} (no quota)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provisioning_is_idempotent_and_private() {
+        let root = tempfile::TempDir::new().unwrap();
+        let dir = provision(root.path(), "indexer").unwrap();
+        std::fs::create_dir(dir.join("db")).unwrap();
+        std::fs::write(dir.join("db/index"), vec![0u8; 3000]).unwrap();
+        std::fs::write(dir.join("state"), vec![0u8; 1000]).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        assert_eq!(provision(root.path(), "indexer").unwrap(), dir);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert_eq!(std::fs::metadata(&dir).unwrap().mode() & 0o777, 0o700);
+        }
+        assert!(provision(root.path(), "../escape").is_err());
+
+        let disk = DiskUsage::measure(&dir, Some(1));
+        assert_eq!(disk.used, 4000);
+        assert!(!disk.exceeded());
+        assert!(
+            DiskUsage {
+                used: 2 * 1024 * 1024,
+                quota: Some(1024 * 1024)
+            }
+            .exceeded()
+        );
+        assert!(!DiskUsage::measure(&root.path().join("missing"), None).exceeded());
+    }
+}
@@ -0,0 +1,540 @@
+//! Running a full node inside the host process (`NodeBuilder`, `NodeHandle`)
+//!
+//! The builder starts the same `blvm_node` node as `blvm start`, on a dedicated thread with its
+//! own tokio runtime so node work never competes with the host application's tasks. This is
+//! not an in-process API: `NodeHandle` is a JSON-RPC client of that node's RPC server
+//! (loopback by default), so every call is an HTTP round trip and everything the node serves is
+//! reachable with `NodeHandle::rpc`. Binary-side features (re-validation, alerts,
+//! notifications, ...) are not started.
+
+use anyhow::{Context, Result};
+use blvm_node::ProtocolVersion;
+use blvm_node::config::NodeConfig;
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// How long `start` waits for the RPC server to come up
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long `shutdown` waits for the node to flush and exit after `stop`
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+const TIP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Best block of the active chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tip {
+    pub hash: String,
+    pub height: u64,
+}
+
+/// Events delivered to `NodeHandle::subscribe_events` receivers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeEvent {
+    /// The tip moved (a new block, or the new tip after a reorg)
+    TipChanged(Tip),
+}
+
+impl NodeEvent {
+    /// JSON form used by the C and Python bindings
+    pub fn to_json(&self) -> Value {
+        match self {
+            NodeEvent::TipChanged(tip) => {
+                json!({"type": "tip", "hash": tip.hash, "height": tip.height})
+            }
+        }
+    }
+}
+
+/// How the node ended after `NodeHandle::shutdown`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shutdown {
+    /// The node's run loop returned `Ok`
+    Clean,
+    /// The run loop returned this error after `stop` was accepted. blvm-node ends some stops
+    /// this way, so it is reported rather than treated as a failed shutdown.
+    WithError(String),
+}
+
+/// Typed configuration for an embedded node
+#[derive(Clone)]
+pub struct NodeBuilder {
+    network: &'static str,
+    data_dir: PathBuf,
+    listen_addr: Option<SocketAddr>,
+    rpc_addr: Option<SocketAddr>,
+    credentials: Option<(String, String)>,
+    config: NodeConfig,
+}
+
+fn protocol_version(network: &str) -> ProtocolVersion {
+    match network {
+        "mainnet" => ProtocolVersion::BitcoinV1,
+        "testnet" => ProtocolVersion::Testnet3,
+        "signet" => ProtocolVersion::Signet,
+        _ => ProtocolVersion::Regtest,
+    }
+}
+
+impl NodeBuilder {
+    /// `network` is mainnet, testnet, signet or regtest (aliases as in `canonical_network_name`)
+    pub fn new(network: &str, data_dir: impl Into<PathBuf>) -> Result<Self> {
+        let network = crate::canonical_network_name(network)
+            .with_context(|| format!("Unknown network '{network}'"))?;
+        Ok(NodeBuilder {
+            network,
+            data_dir: data_dir.into(),
+            listen_addr: None,
+            rpc_addr: None,
+            credentials: None,
+            config: NodeConfig::default(),
+        })
+    }
+
+    /// Node settings, e.g. from `NodeConfig::from_file`; listen address and network set on the
+    /// builder take precedence
+    pub fn config(mut self, config: NodeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Settings from a `blvm.toml`, as `blvm start --config` reads it: the node's settings
+    /// (see [`NodeBuilder::config`]) and `rpc_user` / `rpc_password` for the handle. Sections only
+    /// the binary acts on (alerts, the RPC front, ...) are validated but not started, and
+    /// `BLVM_*` environment overrides are not applied.
+    pub fn config_file(mut self, path: &Path) -> Result<Self> {
+        let (config, credentials) = load_config_file(path)?;
+        self.config = config;
+        if credentials.is_some() {
+            self.credentials = credentials;
+        }
+        Ok(self)
+    }
+
+    /// P2P listen address (default: the config's, else loopback on the network's port; set one
+    /// on a public interface to accept inbound peers)
+    pub fn listen_addr(mut self, addr: SocketAddr) -> Self {
+        self.listen_addr = Some(addr);
+        self
+    }
+
+    /// RPC address the handle uses (default: the network's loopback RPC port)
+    pub fn rpc_addr(mut self, addr: SocketAddr) -> Self {
+        self.rpc_addr = Some(addr);
+        self
+    }
+
+    /// `rpc_user` / `rpc_password` the handle sends, taking precedence over `[rpc_auth]` and the
+    /// cookie file, as for the `blvm` CLI
+    pub fn rpc_credentials(mut self, user: impl Into<String>, password: impl Into<String>) -> Self {
+        self.credentials = Some((user.into(), password.into()));
+        self
+    }
+
+    /// Start the node and wait until its RPC server answers. `rpc_addr` must be free: an answer
+    /// only counts while the node thread is still running.
+    pub async fn start(self) -> Result<NodeHandle> {
+        let listen_addr = self
+            .listen_addr
+            .or(self.config.listen_addr)
+            .unwrap_or_else(|| {
+                SocketAddr::from((
+                    [127, 0, 0, 1],
+                    crate::default_p2p_port_for_network(self.network),
+                ))
+            });
+        let rpc_addr = self
+            .rpc_addr
+            .unwrap_or_else(|| crate::default_rpc_addr_for_network(self.network));
+        let mut config = self.config;
+        config.listen_addr = Some(listen_addr);
+        config.protocol_version = Some(self.network.to_string());
+        std::fs::create_dir_all(&self.data_dir).with_context(|| {
+            format!(
+                "Failed to create data directory {}",
+                self.data_dir.display()
+            )
+        })?;
+
+        anyhow::ensure!(
+            rpc_addr.port() != 0,
+            "rpc_addr needs a fixed port: the handle connects to it"
+        );
+        // Whatever already listens there would pass the readiness check below
+        drop(
+            std::net::TcpListener::bind(rpc_addr)
+                .with_context(|| format!("RPC address {rpc_addr} is not available"))?,
+        );
+
+        // Same precedence as the CLI: rpc_user / rpc_password, [rpc_auth], the cookie file
+        let auth = match self.credentials {
+            Some((user, password)) => RpcAuth::Basic(user, password),
+            None => match RpcAuth::from_config(&config)? {
+                Some(auth) => auth,
+                None => RpcAuth::Cookie(self.data_dir.join(crate::rpc_cookie::COOKIE_FILE)),
+            },
+        };
+        let client = RpcClient::with_auth(rpc_addr, auth);
+        let data_dir = self.data_dir.to_string_lossy().into_owned();
+        let protocol = protocol_version(self.network);
+        let (built_tx, built_rx) = tokio::sync::oneshot::channel::<Result<()>>();
+        let thread = std::thread::Builder::new()
+            .name("blvm-node".to_string())
+            .spawn(move || -> Result<()> {
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()?;
+                runtime.block_on(async move {
+                    let node = match build_node(&data_dir, listen_addr, rpc_addr, protocol, &config)
+                    {
+                        Ok(node) => node,
+                        Err(e) => {
+                            let _ = built_tx.send(Err(anyhow::anyhow!("{e:#}")));
+                            return Err(e);
+                        }
+                    };
+                    let _ = built_tx.send(Ok(()));
+                    node.start()
+                        .await
+                        .map_err(|e| anyhow::anyhow!("Node error: {e}"))
+                })
+            })
+            .context("Failed to spawn node thread")?;
+        built_rx
+            .await
+            .context("Node thread exited during setup")??;
+
+        let started = std::time::Instant::now();
+        loop {
+            if thread.is_finished() {
+                return Err(match thread.join() {
+                    Ok(Err(e)) => e,
+                    Ok(Ok(())) => anyhow::anyhow!("Node stopped during startup"),
+                    Err(_) => anyhow::anyhow!("Node thread panicked"),
+                });
+            }
+            if client.call("getblockchaininfo", json!([])).await.is_ok() {
+                break;
+            }
+            if started.elapsed() > STARTUP_TIMEOUT {
+                anyhow::bail!("Node RPC on {rpc_addr} did not come up within {STARTUP_TIMEOUT:?}");
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+
+        let (events, _) = broadcast::channel(64);
+        let poller = tokio::spawn(poll_tip(client.clone(), events.clone()));
+        Ok(NodeHandle {
+            client,
+            thread: Some(thread),
+            events,
+            poller,
+        })
+    }
+}
+
+/// Node settings and `rpc_user` / `rpc_password` credentials from a `blvm.toml`; a user
+/// without a password sends none, as for the CLI
+pub(crate) fn load_config_file(path: &Path) -> Result<(NodeConfig, Option<(String, String)>)> {
+    let config = NodeConfig::from_file(path)
+        .map_err(|e| anyhow::anyhow!("Failed to load {}: {}", path.display(), e))?;
+    let extra = crate::extra_config::ExtraConfig::from_file(path)
+        .with_context(|| format!("Invalid blvm settings in {}", path.display()))?;
+    let credentials = extra.rpc_password.map(|password| {
+        let user = extra.rpc_user.unwrap_or_else(|| "btc".to_string());
+        (user, password)
+    });
+    Ok((config, credentials))
+}
+
+/// Same construction as `blvm start`
+fn build_node(
+    data_dir: &str,
+    listen_addr: SocketAddr,
+    rpc_addr: SocketAddr,
+    protocol: ProtocolVersion,
+    config: &NodeConfig,
+) -> Result<blvm_node::node::Node> {
+    let node = blvm_node::node::Node::with_storage_config(
+        data_dir,
+        listen_addr,
+        rpc_addr,
+        Some(protocol),
+        config.storage.as_ref(),
+    )?
+    .with_config(config.clone())
+    .map_err(|e| anyhow::anyhow!("Failed to apply config: {}", e))?;
+    #[cfg(feature = "wasm-modules")]
+    let node = node.with_wasm_loader(std::sync::Arc::new(blvm_sdk::BlvmSdkWasmLoader));
+    node.with_modules_from_config(config)
+        .map_err(|e| anyhow::anyhow!("Failed to configure modules: {}", e))
+}
+
+/// RPC client for a node running on a thread of this process; dropping it without `shutdown`
+/// leaves the node running
+pub struct NodeHandle {
+    client: RpcClient,
+    thread: Option<std::thread::JoinHandle<Result<()>>>,
+    events: broadcast::Sender<NodeEvent>,
+    poller: tokio::task::JoinHandle<()>,
+}
+
+impl NodeHandle {
+    /// Address of the node's RPC server
+    pub fn rpc_addr(&self) -> SocketAddr {
+        self.client.addr
+    }
+
+    /// Any RPC the node serves; returns the `result` member
+    pub async fn rpc(&self, method: &str, params: Value) -> Result<Value> {
+        self.client.call(method, params).await
+    }
+
+    pub async fn query_tip(&self) -> Result<Tip> {
+        tip(&self.client).await
+    }
+
+    /// Submit a serialized block; `Err` carries the node's rejection reason
+    pub async fn submit_block(&self, block: &[u8]) -> Result<()> {
+        match self.rpc("submitblock", json!([hex::encode(block)])).await? {
+            Value::Null => Ok(()),
+            Value::String(reason) => anyhow::bail!("Block rejected: {reason}"),
+            other => anyhow::bail!("Block rejected: {other}"),
+        }
+    }
+
+    /// Tip changes from now on. The only event so far: the handle polls `getblockchaininfo`
+    /// every 500 ms, so several blocks between polls arrive as one `TipChanged` and a tip that
+    /// moves and returns within a poll is missed. Slow receivers see `Lagged`.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<NodeEvent> {
+        self.events.subscribe()
+    }
+
+    /// Ask the node to stop and wait for it to flush and exit. `Err` when `stop` was refused,
+    /// the node did not exit in time or its thread panicked.
+    pub async fn shutdown(mut self) -> Result<Shutdown> {
+        self.poller.abort();
+        self.rpc("stop", json!([])).await?;
+        let thread = self.thread.take().expect("thread is only taken here");
+        let joined = tokio::time::timeout(
+            SHUTDOWN_TIMEOUT,
+            tokio::task::spawn_blocking(move || thread.join()),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Node did not stop within {SHUTDOWN_TIMEOUT:?}"))?
+        .context("Node thread join failed")?;
+        match joined {
+            Ok(Ok(())) => Ok(Shutdown::Clean),
+            Ok(Err(e)) => Ok(Shutdown::WithError(format!("{e:#}"))),
+            Err(_) => anyhow::bail!("Node thread panicked"),
+        }
+    }
+}
+
+impl Drop for NodeHandle {
+    fn drop(&mut self) {
+        self.poller.abort();
+    }
+}
+
+async fn tip(client: &RpcClient) -> Result<Tip> {
+    let info = client.call("getblockchaininfo", json!([])).await?;
+    Ok(Tip {
+        hash: info
+            .get("bestblockhash")
+            .and_then(|v| v.as_str())
+            .context("getblockchaininfo has no bestblockhash")?
+            .to_string(),
+        height: info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0),
+    })
+}
+
+async fn poll_tip(client: RpcClient, events: broadcast::Sender<NodeEvent>) {
+    let mut last: Option<String> = None;
+    let mut ticker = tokio::time::interval(TIP_POLL_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let Ok(tip) = tip(&client).await else {
+            continue;
+        };
+        if last.as_deref() != Some(tip.hash.as_str()) {
+            let first = last.replace(tip.hash.clone()).is_none();
+            if !first {
+                // No receivers is not an error: nobody subscribed yet
+                let _ = events.send(NodeEvent::TipChanged(tip));
+            }
+        }
+    }
+}
+
+/// Credentials sent with each call
+#[derive(Debug, Clone)]
+enum RpcAuth {
+    None,
+    Bearer(String),
+    Basic(String, String),
+    /// Basic auth from a cookie file, read per call: the node writes a new one on every start.
+    /// No credentials while the file does not exist.
+    Cookie(PathBuf),
+}
+
+impl RpcAuth {
+    /// `[rpc_auth]` credentials; `None` when the config has none to send
+    fn from_config(config: &NodeConfig) -> Result<Option<Self>> {
+        let Some(auth) = &config.rpc_auth else {
+            return Ok(None);
+        };
+        if let Some(token) = auth.admin_tokens.first().or(auth.tokens.first()) {
+            Ok(Some(RpcAuth::Bearer(token.clone())))
+        } else if let Some(password) = &auth.password {
+            let user = auth.username.clone().unwrap_or_else(|| "btc".to_string());
+            Ok(Some(RpcAuth::Basic(user, password.clone())))
+        } else if auth.required {
+            anyhow::bail!(
+                "RPC authentication required: set [rpc_auth].admin_tokens, tokens, or password so the handle can reach the node"
+            );
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// JSON-RPC client for a running node (embedded or not)
+#[derive(Clone)]
+pub struct RpcClient {
+    addr: SocketAddr,
+    auth: RpcAuth,
+    http: reqwest::Client,
+}
+
+impl RpcClient {
+    /// Credentials come from `config.rpc_auth`
+    pub fn new(addr: SocketAddr, config: &NodeConfig) -> Result<Self> {
+        let auth = RpcAuth::from_config(config)?.unwrap_or(RpcAuth::None);
+        Ok(Self::with_auth(addr, auth))
+    }
+
+    /// Basic auth with `user` / `password` (the CLI's `rpc_user` / `rpc_password`)
+    pub fn with_credentials(addr: SocketAddr, user: &str, password: &str) -> Self {
+        Self::with_auth(addr, RpcAuth::Basic(user.to_string(), password.to_string()))
+    }
+
+    /// Basic auth from a cookie file such as `<data_dir>/.cookie`
+    pub fn with_cookie(addr: SocketAddr, path: &Path) -> Self {
+        Self::with_auth(addr, RpcAuth::Cookie(path.to_path_buf()))
+    }
+
+    fn with_auth(addr: SocketAddr, auth: RpcAuth) -> Self {
+        RpcClient {
+            addr,
+            auth,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the `result` member
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({"jsonrpc": "2.0", "method": method, "params": params, "id": 1});
+        let mut req = self
+            .http
+            .post(format!("http://{}", self.addr))
+            .json(&request);
+        req = match &self.auth {
+            RpcAuth::None => req,
+            RpcAuth::Bearer(token) => req.bearer_auth(token),
+            RpcAuth::Basic(user, password) => req.basic_auth(user, Some(password)),
+            RpcAuth::Cookie(path) => match crate::rpc_cookie::read(path) {
+                Ok((user, password)) => req.basic_auth(user, Some(password)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => req,
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to read RPC cookie {}", path.display()));
+                }
+            },
+        };
+        let response = req.send().await.context("Node RPC unreachable")?;
+        if !response.status().is_success() {
+            anyhow::bail!("RPC request failed with status: {}", response.status());
+        }
+        let json: Value = response
+            .json()
+            .await
+            .context("Failed to parse RPC response")?;
+        if let Some(error) = json.get("error").filter(|e| !e.is_null()) {
+            anyhow::bail!("RPC error: {}", error);
+        }
+        json.get("result")
+            .cloned()
+            .context("No result in RPC response")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_resolves_network_aliases() {
+        let builder = NodeBuilder::new("testnet3", "/tmp/blvm-embed").unwrap();
+        assert_eq!(builder.network, "testnet");
+        assert!(matches!(
+            protocol_version(builder.network),
+            ProtocolVersion::Testnet3
+        ));
+        assert!(NodeBuilder::new("litecoin", "/tmp/blvm-embed").is_err());
+    }
+
+    #[tokio::test]
+    async fn starts_and_stops_a_regtest_node() {
+        let free = || {
+            std::net::TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+        };
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let node = NodeBuilder::new("regtest", data_dir.path())
+            .unwrap()
+            .listen_addr(free())
+            .rpc_addr(free())
+            .start()
+            .await
+            .unwrap();
+        assert_eq!(node.query_tip().await.unwrap().height, 0);
+
+        // A second node cannot pass off the first one's RPC server as its own
+        let other_dir = tempfile::TempDir::new().unwrap();
+        let taken = NodeBuilder::new("regtest", other_dir.path())
+            .unwrap()
+            .listen_addr(free())
+            .rpc_addr(node.rpc_addr())
+            .start()
+            .await;
+        assert!(
+            taken
+                .err()
+                .unwrap()
+                .to_string()
+                .contains("is not available")
+        );
+
+        assert!(node.shutdown().await.is_ok());
+    }
+
+    #[test]
+    fn events_serialize_as_json_objects() {
+        let event = NodeEvent::TipChanged(Tip {
+            hash: "00ff".into(),
+            height: 12,
+        });
+        assert_eq!(
+            event.to_json(),
+            json!({"type": "tip", "hash": "00ff", "height": 12})
+        );
+    }
+}
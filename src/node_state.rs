@@ -0,0 +1,196 @@
+//! Node lifecycle phases and their history (`getnodestate` on the RPC front)
+//!
+//! `blvm start` records the phases it drives itself (starting, loading the chainstate,
+//! stopping) and, once the node's RPC answers, the sync phase derived from `getblockchaininfo`.
+//! `status`, `health --ready` and `/readyz` key off the recorded phase when they can reach it.
+//! blvm-node has no maintenance mode to report, so there is no maintenance phase.
+
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+/// Transitions kept in the history (oldest dropped first)
+pub const MAX_TRANSITIONS: usize = 64;
+
+/// Lifecycle phase of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NodePhase {
+    Starting,
+    LoadingChainstate,
+    Connecting,
+    HeaderSync,
+    Ibd,
+    Synced,
+    Stopping,
+}
+
+impl NodePhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodePhase::Starting => "starting",
+            NodePhase::LoadingChainstate => "loading-chainstate",
+            NodePhase::Connecting => "connecting",
+            NodePhase::HeaderSync => "header-sync",
+            NodePhase::Ibd => "ibd",
+            NodePhase::Synced => "synced",
+            NodePhase::Stopping => "stopping",
+        }
+    }
+
+    /// Readiness: only a synced node should receive traffic
+    pub fn is_ready(&self) -> bool {
+        matches!(self, NodePhase::Synced)
+    }
+
+    /// Phase from `getblockchaininfo`'s block and header counts and IBD flag
+    pub fn from_chain_info(blocks: u64, headers: u64, initial_block_download: bool) -> Self {
+        if blocks == 0 && headers == 0 {
+            NodePhase::Connecting
+        } else if initial_block_download && headers <= blocks {
+            NodePhase::HeaderSync
+        } else if initial_block_download || headers > blocks + 1 {
+            NodePhase::Ibd
+        } else {
+            NodePhase::Synced
+        }
+    }
+}
+
+impl fmt::Display for NodePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One entry of the phase history
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Transition {
+    pub phase: NodePhase,
+    /// Unix time the phase was entered
+    pub time: u64,
+}
+
+/// Current phase and the bounded list of transitions that led to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseHistory {
+    transitions: VecDeque<Transition>,
+}
+
+impl PhaseHistory {
+    pub fn new(phase: NodePhase, now: u64) -> Self {
+        PhaseHistory {
+            transitions: VecDeque::from([Transition { phase, time: now }]),
+        }
+    }
+
+    pub fn current(&self) -> Transition {
+        *self.transitions.back().expect("history is never empty")
+    }
+
+    /// Enter `phase` unless already in it; once stopping, the sync poller cannot move the node
+    /// back. Returns whether a transition was recorded.
+    pub fn record(&mut self, phase: NodePhase, now: u64) -> bool {
+        let current = self.current().phase;
+        if current == phase || current == NodePhase::Stopping {
+            return false;
+        }
+        if self.transitions.len() == MAX_TRANSITIONS {
+            self.transitions.pop_front();
+        }
+        self.transitions.push_back(Transition { phase, time: now });
+        true
+    }
+
+    /// `getnodestate` result
+    pub fn to_json(&self) -> Value {
+        let current = self.current();
+        json!({
+            "phase": current.phase,
+            "since": current.time,
+            "ready": current.phase.is_ready(),
+            "history": self.transitions,
+        })
+    }
+}
+
+/// Phase history of the node this process runs
+pub fn tracker() -> &'static Mutex<PhaseHistory> {
+    static TRACKER: OnceLock<Mutex<PhaseHistory>> = OnceLock::new();
+    TRACKER.get_or_init(|| {
+        Mutex::new(PhaseHistory::new(
+            NodePhase::Starting,
+            crate::mocktime::unix_now(),
+        ))
+    })
+}
+
+/// Record `phase` for the node this process runs
+pub fn enter(phase: NodePhase) {
+    if let Ok(mut history) = tracker().lock()
+        && history.record(phase, crate::mocktime::unix_now())
+    {
+        tracing::info!("Node phase: {}", phase);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_derived_from_chain_info() {
+        assert_eq!(
+            NodePhase::from_chain_info(0, 0, true),
+            NodePhase::Connecting
+        );
+        assert_eq!(
+            NodePhase::from_chain_info(10, 10, true),
+            NodePhase::HeaderSync
+        );
+        assert_eq!(NodePhase::from_chain_info(10, 5000, true), NodePhase::Ibd);
+        assert_eq!(
+            NodePhase::from_chain_info(5000, 5001, false),
+            NodePhase::Synced
+        );
+        assert!(NodePhase::from_chain_info(5000, 5000, false).is_ready());
+        assert_eq!(NodePhase::Ibd.to_string(), "ibd");
+    }
+
+    #[test]
+    fn history_records_changes_and_stays_stopping() {
+        let mut history = PhaseHistory::new(NodePhase::Starting, 100);
+        assert!(history.record(NodePhase::LoadingChainstate, 101));
+        assert!(!history.record(NodePhase::LoadingChainstate, 102));
+        assert!(history.record(NodePhase::Ibd, 110));
+        assert!(history.record(NodePhase::Stopping, 200));
+        assert!(!history.record(NodePhase::Synced, 201));
+
+        let state = history.to_json();
+        assert_eq!(state["phase"], "stopping");
+        assert_eq!(state["since"], 200);
+        assert_eq!(state["ready"], false);
+        assert_eq!(
+            state["history"][1],
+            json!({"phase": "loading-chainstate", "time": 101})
+        );
+        assert_eq!(state["history"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history = PhaseHistory::new(NodePhase::Starting, 0);
+        for i in 0..MAX_TRANSITIONS as u64 {
+            let phase = if i % 2 == 0 {
+                NodePhase::Ibd
+            } else {
+                NodePhase::Synced
+            };
+            history.record(phase, i + 1);
+        }
+        assert_eq!(history.transitions.len(), MAX_TRANSITIONS);
+        assert_ne!(history.transitions[0].phase, NodePhase::Starting);
+    }
+}
@@ -0,0 +1,147 @@
+//! Chain notifications as newline-delimited JSON on a unix socket or named pipe (`[notifications]`)
+//!
+//! A lighter alternative to ZMQ for integrators on the same host. With `sink = "socket"` the
+//! binary listens on `path` and every connected client receives each line; with `sink = "pipe"`
+//! lines are written to an existing FIFO (`mkfifo`) and dropped while nobody reads it. Events
+//! come from polling the node: a `block` line per new tip, and a `tx` line per transaction
+//! entering the mempool when `transactions` is set. When the old tip left the active chain, a
+//! `reorg` line naming it and the fork height precedes the new tip's `block` line. Lines are
+//! numbered and journaled for replay, see [`crate::event_journal`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Where lines are written
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Sink {
+    /// Unix domain socket the binary listens on; any number of readers
+    #[default]
+    Socket,
+    /// Existing named pipe; a single reader
+    Pipe,
+}
+
+/// `[notifications]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub enabled: bool,
+    pub path: Option<PathBuf>,
+    pub sink: Sink,
+    pub blocks: bool,
+    pub transactions: bool,
+    pub poll_interval_ms: u64,
+    /// Events kept in the replay journal
+    pub journal_max_events: usize,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            sink: Sink::Socket,
+            blocks: true,
+            transactions: false,
+            poll_interval_ms: 1000,
+            journal_max_events: 10_000,
+        }
+    }
+}
+
+/// One event line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    Block {
+        hash: String,
+        height: u64,
+        time: u64,
+    },
+    Tx {
+        txid: String,
+    },
+    /// `old_tip` was disconnected; blocks above `fork_height` were replaced
+    Reorg {
+        old_tip: String,
+        fork_height: u64,
+    },
+}
+
+impl Notification {
+    /// The line's JSON object
+    pub fn to_value(&self) -> Value {
+        match self {
+            Notification::Block { hash, height, time } => {
+                json!({"type": "block", "hash": hash, "height": height, "time": time})
+            }
+            Notification::Tx { txid } => json!({"type": "tx", "txid": txid}),
+            Notification::Reorg {
+                old_tip,
+                fork_height,
+            } => json!({"type": "reorg", "old_tip": old_tip, "fork_height": fork_height}),
+        }
+    }
+
+    /// JSON object plus the trailing newline
+    pub fn to_line(&self) -> String {
+        format!("{}\n", self.to_value())
+    }
+}
+
+/// Mempool txids seen at the previous poll
+#[derive(Debug, Default)]
+pub struct MempoolTracker {
+    seen: Option<HashSet<String>>,
+}
+
+impl MempoolTracker {
+    /// Transactions new since the last call; the first call only records the mempool, so a
+    /// restart does not replay it
+    pub fn update(&mut self, txids: Vec<String>) -> Vec<String> {
+        let current: HashSet<String> = txids.iter().cloned().collect();
+        let new = match &self.seen {
+            Some(seen) => txids.into_iter().filter(|t| !seen.contains(t)).collect(),
+            None => Vec::new(),
+        };
+        self.seen = Some(current);
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_only_new_mempool_entries() {
+        let mut tracker = MempoolTracker::default();
+        assert!(tracker.update(vec!["a".into(), "b".into()]).is_empty());
+        assert_eq!(tracker.update(vec!["b".into(), "c".into()]), vec!["c"]);
+        // Evicted and re-added transactions are announced again
+        assert_eq!(tracker.update(vec!["a".into(), "c".into()]), vec!["a"]);
+    }
+
+    #[test]
+    fn lines_are_single_json_objects() {
+        let line = Notification::Block {
+            hash: "00ab".into(),
+            height: 7,
+            time: 1_700_000_000,
+        }
+        .to_line();
+        assert!(line.ends_with("}\n") && line.matches('\n').count() == 1);
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(
+            (value["type"].as_str(), value["height"].as_u64()),
+            (Some("block"), Some(7))
+        );
+
+        let config: NotificationsConfig =
+            toml::from_str("enabled = true\npath = \"/run/blvm.sock\"\nsink = \"pipe\"").unwrap();
+        assert_eq!(config.sink, Sink::Pipe);
+        assert!(config.blocks && !config.transactions);
+    }
+}
@@ -0,0 +1,146 @@
+//! OP_RETURN payload extraction (`blvm opreturn scan`)
+//!
+//! The payload of an `OP_RETURN` output is its data pushes concatenated. Runes-style outputs
+//! put `OP_13` right after `OP_RETURN`; other protocols are recognised by a leading byte
+//! prefix in the payload.
+
+use crate::decode::Block;
+use crate::hash::to_display_hex;
+use crate::script::{ScriptOp, decode_script};
+use serde::Serialize;
+
+/// `OP_13`, the Runes protocol marker
+const RUNES_MARKER: u8 = 0x5d;
+
+/// Protocols recognised by payload prefix, as `--protocol` names them
+pub const PREFIX_PROTOCOLS: &[(&str, &[u8])] = &[("omni", b"omni"), ("stacks", b"X2")];
+
+/// Every `--protocol` name
+pub fn protocol_names() -> Vec<&'static str> {
+    let mut names = vec!["runes"];
+    names.extend(PREFIX_PROTOCOLS.iter().map(|(name, _)| *name));
+    names
+}
+
+/// Payload of an `OP_RETURN` output and the protocol it belongs to (if known); `None` for other
+/// scripts. Non-push opcodes after the first (other than the runes marker) end the payload.
+pub fn extract(script: &[u8]) -> Option<(Vec<u8>, Option<&'static str>)> {
+    let [0x6a, rest @ ..] = script else {
+        return None;
+    };
+    let ops = decode_script(rest).unwrap_or_default();
+    let (runes, ops) = match ops.split_first() {
+        Some((ScriptOp::Op(RUNES_MARKER), tail)) => (true, tail),
+        _ => (false, ops.as_slice()),
+    };
+    let payload: Vec<u8> = ops
+        .iter()
+        .map_while(|op| match op {
+            ScriptOp::Push { data, .. } => Some(*data),
+            ScriptOp::Op(_) => None,
+        })
+        .flatten()
+        .copied()
+        .collect();
+    let protocol = if runes {
+        Some("runes")
+    } else {
+        PREFIX_PROTOCOLS
+            .iter()
+            .find(|(_, prefix)| payload.starts_with(prefix))
+            .map(|(name, _)| *name)
+    };
+    Some((payload, protocol))
+}
+
+/// What to keep: any of the protocols or payload prefixes; everything when both are empty
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    pub protocols: Vec<String>,
+    pub prefixes: Vec<Vec<u8>>,
+}
+
+impl Filter {
+    pub fn matches(&self, payload: &[u8], protocol: Option<&str>) -> bool {
+        if self.protocols.is_empty() && self.prefixes.is_empty() {
+            return true;
+        }
+        protocol.is_some_and(|p| self.protocols.iter().any(|want| want == p))
+            || self
+                .prefixes
+                .iter()
+                .any(|prefix| payload.starts_with(prefix))
+    }
+}
+
+/// One JSONL record
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Record {
+    pub height: u64,
+    pub block: String,
+    pub txid: String,
+    pub vout: usize,
+    /// Satoshis (burned)
+    pub value: u64,
+    pub protocol: Option<&'static str>,
+    pub size: usize,
+    pub payload: String,
+}
+
+/// Matching OP_RETURN outputs of a block, in block order
+pub fn scan_block(block: &Block, height: u64, hash: &str, filter: &Filter) -> Vec<Record> {
+    let mut records = Vec::new();
+    for tx in &block.transactions {
+        let mut txid = None;
+        for (vout, output) in tx.outputs.iter().enumerate() {
+            let Some((payload, protocol)) = extract(&output.script_pubkey) else {
+                continue;
+            };
+            if !filter.matches(&payload, protocol) {
+                continue;
+            }
+            let txid = txid.get_or_insert_with(|| to_display_hex(&tx.txid()));
+            records.push(Record {
+                height,
+                block: hash.to_string(),
+                txid: txid.clone(),
+                vout,
+                value: output.value,
+                protocol,
+                size: payload.len(),
+                payload: hex::encode(&payload),
+            });
+        }
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_payloads_and_protocols() {
+        let omni = [&[0x6a, 0x14][..], b"omni", &[0; 16]].concat();
+        let (payload, protocol) = extract(&omni).unwrap();
+        assert_eq!((payload.len(), protocol), (20, Some("omni")));
+
+        // OP_RETURN OP_13 <push> <push>
+        let runes = [0x6a, 0x5d, 0x02, 0xaa, 0xbb, 0x01, 0xcc];
+        assert_eq!(
+            extract(&runes),
+            Some((vec![0xaa, 0xbb, 0xcc], Some("runes")))
+        );
+        assert_eq!(extract(&[0x6a]), Some((vec![], None)));
+        assert_eq!(extract(&[0x51]), None);
+
+        let filter = Filter {
+            protocols: vec!["runes".into()],
+            prefixes: vec![vec![0xde, 0xad]],
+        };
+        assert!(filter.matches(&[0xaa], Some("runes")));
+        assert!(filter.matches(&[0xde, 0xad, 0x01], None));
+        assert!(!filter.matches(b"omni", Some("omni")));
+        assert!(Filter::default().matches(&[], None));
+    }
+}
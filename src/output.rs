@@ -0,0 +1,175 @@
+//! Machine-readable output for read-only subcommands (`--format json|table|csv`)
+//!
+//! A handler collects what it would print into a [`Table`] (named columns, one row per record)
+//! and renders it in the requested format instead of its usual text. Single-record commands
+//! such as `status` render JSON as one object, list commands (`peers`) as an array.
+
+use serde_json::{Map, Value};
+
+/// Output format selected with `--format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Table,
+    Csv,
+}
+
+/// Rows of values under named columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    /// One record: JSON renders an object rather than an array
+    pub single: bool,
+}
+
+impl Table {
+    pub fn new(columns: &[&str]) -> Self {
+        Table {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: Vec::new(),
+            single: false,
+        }
+    }
+
+    /// A single record from `(column, value)` pairs
+    pub fn record(fields: Vec<(&str, Value)>) -> Self {
+        let (columns, values): (Vec<&str>, Vec<Value>) = fields.into_iter().unzip();
+        let mut table = Table::new(&columns);
+        table.rows.push(values);
+        table.single = true;
+        table
+    }
+
+    pub fn push(&mut self, row: Vec<Value>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Json => {
+                let records: Vec<Value> = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let object: Map<String, Value> = self
+                            .columns
+                            .iter()
+                            .cloned()
+                            .zip(row.iter().cloned())
+                            .collect();
+                        Value::Object(object)
+                    })
+                    .collect();
+                let value = match (self.single, records.first()) {
+                    (true, Some(record)) => record.clone(),
+                    _ => Value::Array(records),
+                };
+                serde_json::to_string_pretty(&value).unwrap_or_default()
+            }
+            Format::Table => self.to_text_table(),
+            Format::Csv => self.to_csv(),
+        }
+    }
+
+    fn to_text_table(&self) -> String {
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(cell_text).collect())
+            .collect();
+        let widths: Vec<usize> = (0..self.columns.len())
+            .map(|i| {
+                cells
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([self.columns[i].len()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let line = |values: Vec<&str>| {
+            let padded: Vec<String> = values
+                .iter()
+                .zip(&widths)
+                .map(|(value, width)| format!("{value:<width$}"))
+                .collect();
+            padded.join("  ").trim_end().to_string() + "\n"
+        };
+        let mut out = line(self.columns.iter().map(String::as_str).collect());
+        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        out += &line(rule.iter().map(String::as_str).collect());
+        for row in &cells {
+            out += &line(row.iter().map(String::as_str).collect());
+        }
+        out
+    }
+
+    fn to_csv(&self) -> String {
+        let line = |values: Vec<String>| {
+            values
+                .iter()
+                .map(|value| csv_field(value))
+                .collect::<Vec<_>>()
+                .join(",")
+                + "\n"
+        };
+        let mut out = line(self.columns.clone());
+        for row in &self.rows {
+            out += &line(row.iter().map(cell_text).collect());
+        }
+        out
+    }
+}
+
+/// Strings unquoted, null empty, anything else as JSON
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// RFC 4180 quoting for fields containing separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_json_table_and_csv() {
+        let mut peers = Table::new(&["addr", "version", "subver"]);
+        peers.push(vec![
+            json!("1.2.3.4:8333"),
+            json!(70016),
+            json!("/Satoshi:27.0.0/"),
+        ]);
+        peers.push(vec![json!("[::1]:8333"), Value::Null, json!("/a,b\"c/")]);
+
+        assert_eq!(
+            peers.render(Format::Csv),
+            "addr,version,subver\n1.2.3.4:8333,70016,/Satoshi:27.0.0/\n\
+             [::1]:8333,,\"/a,b\"\"c/\"\n"
+        );
+        let table = peers.render(Format::Table);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "addr          version  subver");
+        assert_eq!(lines[2], "1.2.3.4:8333  70016    /Satoshi:27.0.0/");
+        let json: Value = serde_json::from_str(&peers.render(Format::Json)).unwrap();
+        assert_eq!(json[1]["addr"], "[::1]:8333");
+
+        let status = Table::record(vec![("blocks", json!(120)), ("chain", json!("regtest"))]);
+        let json: Value = serde_json::from_str(&status.render(Format::Json)).unwrap();
+        assert_eq!(json, json!({"blocks": 120, "chain": "regtest"}));
+    }
+}
@@ -0,0 +1,119 @@
+//! Recent peer events in memory (`getpeereventlog` on the RPC front, `blvm peers history`)
+//!
+//! Every `peer.*` event `blvm start` records (connects, disconnects and bans seen by the event
+//! watcher, plus its own evictions, filters and timeouts) also goes into a bounded ring buffer,
+//! whether or not `[events]` writes them to disk. The buffer is lost on restart; the on-disk log
+//! is what `peers history` falls back to when the front cannot be reached.
+
+use crate::events::Event;
+use serde_json::{Value, json};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// Events kept in memory (oldest dropped first)
+pub const MAX_PEER_EVENTS: usize = 1000;
+
+/// Bounded buffer of recent `peer.*` events, oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerEventLog {
+    events: VecDeque<Event>,
+    capacity: usize,
+    /// Events dropped to stay within `capacity`
+    dropped: u64,
+}
+
+impl PeerEventLog {
+    pub fn new(capacity: usize) -> Self {
+        PeerEventLog {
+            events: VecDeque::with_capacity(capacity.min(MAX_PEER_EVENTS)),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// Keep `event` if it is a peer event. Returns whether it was kept.
+    pub fn record(&mut self, event: &Event) -> bool {
+        if !event.kind.starts_with("peer.") || self.capacity == 0 {
+            return false;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event.clone());
+        true
+    }
+
+    /// The newest `limit` events of `kind` (e.g. `ban` for `peer.ban`; all when `None`), oldest
+    /// first
+    pub fn recent(&self, limit: usize, kind: Option<&str>) -> Vec<&Event> {
+        let prefix = kind.map_or_else(|| "peer.".to_string(), |k| format!("peer.{k}"));
+        let mut events: Vec<&Event> = self
+            .events
+            .iter()
+            .rev()
+            .filter(|event| event.kind.starts_with(&prefix))
+            .take(limit)
+            .collect();
+        events.reverse();
+        events
+    }
+
+    /// `getpeereventlog [count] [kind]` result
+    pub fn to_json(&self, limit: usize, kind: Option<&str>) -> Value {
+        json!({
+            "capacity": self.capacity,
+            "dropped": self.dropped,
+            "events": self.recent(limit, kind),
+        })
+    }
+}
+
+/// Peer events of the node this process runs
+pub fn log() -> &'static Mutex<PeerEventLog> {
+    static LOG: OnceLock<Mutex<PeerEventLog>> = OnceLock::new();
+    LOG.get_or_init(|| Mutex::new(PeerEventLog::new(MAX_PEER_EVENTS)))
+}
+
+/// Whether `addr` (`host:port`, as in `getpeerinfo`) falls under a single-host ban from
+/// `listbanned` (`host/32` or `host/128`)
+pub fn is_banned(addr: &str, banned: &HashSet<String>) -> bool {
+    let host = match addr.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => host,
+        _ => addr,
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    banned.iter().any(|ban| {
+        let (ban_host, prefix) = ban.split_once('/').unwrap_or((ban, ""));
+        ban_host == host && matches!(prefix, "" | "32" | "128")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_peer_events() {
+        let mut log = PeerEventLog::new(3);
+        assert!(!log.record(&Event::new("chain.tip", "tip")));
+        for n in 0..4 {
+            assert!(log.record(&Event::new("peer.connect", format!("c{n}"))));
+        }
+        assert!(log.record(&Event::new("peer.ban", "b")));
+        let all = log.to_json(10, None);
+        assert_eq!(all["dropped"], 2);
+        let messages: Vec<_> = log.recent(10, None).iter().map(|e| &e.message).collect();
+        assert_eq!(messages, ["c2", "c3", "b"]);
+        assert_eq!(log.recent(1, Some("connect"))[0].message, "c3");
+        assert_eq!(log.recent(10, Some("ban")).len(), 1);
+    }
+
+    #[test]
+    fn matches_single_host_bans() {
+        let banned = HashSet::from(["10.0.0.1/32".to_string(), "2001:db8::1/128".to_string()]);
+        assert!(is_banned("10.0.0.1:8333", &banned));
+        assert!(is_banned("[2001:db8::1]:8333", &banned));
+        assert!(!is_banned("10.0.0.2:8333", &banned));
+    }
+}
@@ -0,0 +1,273 @@
+//! Inbound connection limits per IP and per subnet (`[inbound_limits]`), and peer caps lowered
+//! by a config reload
+//!
+//! Cheap sybil floods come from few addresses or few subnets. When a group holds more inbound
+//! peers than allowed, the binary disconnects the group's least useful peer (`disconnectnode`)
+//! rather than refusing newcomers, so a long-serving peer is not displaced by a fresh one. Total
+//! caps (`max_inbound`, a reloaded `max_outbound_peers`) evict the same way.
+//!
+//! Limits are enforced after admission, not at it: blvm-node accepts the connection and the
+//! binary sees it on its next `getpeerinfo` poll (`check_interval_secs`). Until then a group can
+//! exceed its limit, and each poll evicts at most the surplus it sees, so a flood arriving
+//! faster than the interval holds extra slots for up to one interval.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+/// `[inbound_limits]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct InboundLimitsConfig {
+    pub enabled: bool,
+    pub max_per_ip: usize,
+    /// Per /16 (IPv4) or /32 (IPv6)
+    pub max_per_subnet: usize,
+    /// Cap on all inbound peers, for going below the node's own limit without a restart
+    pub max_inbound: Option<usize>,
+    pub check_interval_secs: u64,
+}
+
+impl Default for InboundLimitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_per_ip: 2,
+            max_per_subnet: 8,
+            max_inbound: None,
+            check_interval_secs: 10,
+        }
+    }
+}
+
+/// /16 for IPv4 (and IPv4-mapped IPv6), /32 for IPv6
+pub fn subnet(ip: &IpAddr) -> Vec<u8> {
+    match ip {
+        IpAddr::V4(v4) => v4.octets()[..2].to_vec(),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => v4.octets()[..2].to_vec(),
+            None => v6.octets()[..4].to_vec(),
+        },
+    }
+}
+
+/// A peer from `getpeerinfo` that limits may evict
+#[derive(Debug, Clone, PartialEq)]
+pub struct Peer {
+    pub id: u64,
+    pub addr: SocketAddr,
+    pub connected_at: u64,
+    /// Unix time of the last new block / transaction from this peer (0 = never)
+    pub last_block: u64,
+    pub last_tx: u64,
+    pub min_ping: Option<f64>,
+}
+
+impl Peer {
+    /// Inbound entries only; peers with `noban` permission are never evicted
+    pub fn inbound_from_value(entry: &Value) -> Option<Self> {
+        if !entry.get("inbound")?.as_bool()? {
+            return None;
+        }
+        Self::from_value(entry)
+    }
+
+    /// Outbound entries only; manual connections (`addnode`, persistent peers) are never evicted
+    pub fn outbound_from_value(entry: &Value) -> Option<Self> {
+        if entry.get("inbound")?.as_bool()?
+            || entry.get("connection_type").and_then(|v| v.as_str()) == Some("manual")
+        {
+            return None;
+        }
+        Self::from_value(entry)
+    }
+
+    fn from_value(entry: &Value) -> Option<Self> {
+        let noban = entry
+            .get("permissions")
+            .and_then(|v| v.as_array())
+            .is_some_and(|p| p.iter().any(|p| p.as_str() == Some("noban")));
+        if noban {
+            return None;
+        }
+        let u64_field = |key: &str| entry.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        Some(Peer {
+            id: entry.get("id")?.as_u64()?,
+            addr: entry.get("addr")?.as_str()?.parse().ok()?,
+            connected_at: u64_field("conntime"),
+            last_block: u64_field("last_block"),
+            last_tx: u64_field("last_transaction"),
+            min_ping: entry.get("minping").and_then(|v| v.as_f64()),
+        })
+    }
+
+    /// `Less` when `self` is the better peer to keep: relayed a block more recently, then a
+    /// transaction, then lower ping, then connected longer
+    fn keep_order(&self, other: &Peer) -> Ordering {
+        other
+            .last_block
+            .cmp(&self.last_block)
+            .then(other.last_tx.cmp(&self.last_tx))
+            .then(
+                self.min_ping
+                    .unwrap_or(f64::MAX)
+                    .total_cmp(&other.min_ping.unwrap_or(f64::MAX)),
+            )
+            .then(self.connected_at.cmp(&other.connected_at))
+    }
+}
+
+/// Which limit a peer was evicted for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    PerIp,
+    PerSubnet,
+    MaxInbound,
+    MaxOutbound,
+}
+
+impl Limit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Limit::PerIp => "per-ip",
+            Limit::PerSubnet => "per-subnet",
+            Limit::MaxInbound => "max-inbound",
+            Limit::MaxOutbound => "max-outbound",
+        }
+    }
+}
+
+fn over_limit<K: std::hash::Hash + Eq>(
+    peers: &mut Vec<Peer>,
+    max: usize,
+    key: impl Fn(&Peer) -> K,
+) -> Vec<Peer> {
+    let mut groups: HashMap<K, Vec<Peer>> = HashMap::new();
+    for peer in peers.drain(..) {
+        groups.entry(key(&peer)).or_default().push(peer);
+    }
+    let mut evicted = Vec::new();
+    for (_, mut group) in groups {
+        group.sort_by(|a, b| a.keep_order(b));
+        evicted.extend(group.drain(max.max(1).min(group.len())..));
+        peers.extend(group);
+    }
+    evicted
+}
+
+/// The least useful peers beyond the first `cap`
+pub fn over_cap(peers: &mut Vec<Peer>, cap: usize) -> Vec<Peer> {
+    peers.sort_by(|a, b| a.keep_order(b));
+    peers.split_off(cap.min(peers.len()))
+}
+
+/// Inbound peers to disconnect so that every IP and subnet is within its limit, and all of them
+/// within `max_inbound`; worst first in each group
+pub fn evictions(peers: Vec<Peer>, limits: &InboundLimitsConfig) -> Vec<(Peer, Limit)> {
+    let mut kept = peers;
+    let mut evicted: Vec<(Peer, Limit)> = over_limit(&mut kept, limits.max_per_ip, |p| p.addr.ip())
+        .into_iter()
+        .map(|p| (p, Limit::PerIp))
+        .collect();
+    evicted.extend(
+        over_limit(&mut kept, limits.max_per_subnet, |p| subnet(&p.addr.ip()))
+            .into_iter()
+            .map(|p| (p, Limit::PerSubnet)),
+    );
+    if let Some(max) = limits.max_inbound {
+        evicted.extend(
+            over_cap(&mut kept, max)
+                .into_iter()
+                .map(|p| (p, Limit::MaxInbound)),
+        );
+    }
+    evicted.sort_by_key(|(p, _)| p.id);
+    evicted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn peer(id: u64, addr: &str, last_block: u64, connected_at: u64) -> Peer {
+        Peer {
+            id,
+            addr: addr.parse().unwrap(),
+            connected_at,
+            last_block,
+            last_tx: 0,
+            min_ping: Some(0.05),
+        }
+    }
+
+    #[test]
+    fn evicts_the_worst_peer_of_crowded_groups() {
+        let limits = InboundLimitsConfig {
+            enabled: true,
+            max_per_ip: 2,
+            max_per_subnet: 3,
+            max_inbound: None,
+            check_interval_secs: 10,
+        };
+        let peers = vec![
+            // Three from one IP: the newest one that never sent a block goes
+            peer(1, "1.2.3.4:50001", 900, 100),
+            peer(2, "1.2.3.4:50002", 0, 200),
+            peer(3, "1.2.3.4:50003", 0, 300),
+            // Same /16, other IPs: four left in 1.2.0.0/16 after the per-IP pass
+            peer(4, "1.2.7.7:50000", 800, 100),
+            peer(5, "1.2.8.8:50000", 0, 50),
+            // Unrelated
+            peer(6, "5.6.7.8:50000", 0, 400),
+        ];
+        let evicted = evictions(peers, &limits);
+        let ids: Vec<(u64, Limit)> = evicted.iter().map(|(p, l)| (p.id, *l)).collect();
+        assert_eq!(ids, vec![(2, Limit::PerSubnet), (3, Limit::PerIp)]);
+
+        assert!(evictions(vec![peer(7, "9.9.9.9:1", 0, 0)], &limits).is_empty());
+    }
+
+    #[test]
+    fn total_caps_keep_the_best_peers() {
+        let limits = InboundLimitsConfig {
+            enabled: true,
+            max_inbound: Some(2),
+            ..Default::default()
+        };
+        let peers = vec![
+            peer(1, "1.1.1.1:1", 0, 100),
+            peer(2, "2.2.2.2:1", 900, 300),
+            peer(3, "3.3.3.3:1", 0, 50),
+        ];
+        let evicted = evictions(peers.clone(), &limits);
+        let ids: Vec<(u64, Limit)> = evicted.iter().map(|(p, l)| (p.id, *l)).collect();
+        assert_eq!(ids, vec![(1, Limit::MaxInbound)]);
+
+        let mut outbound = peers;
+        let dropped: Vec<u64> = over_cap(&mut outbound, 1).iter().map(|p| p.id).collect();
+        assert_eq!(dropped, vec![3, 1]);
+        assert_eq!(outbound.len(), 1);
+    }
+
+    #[test]
+    fn reads_inbound_and_outbound_peers() {
+        let entry = json!({"id": 7, "addr": "1.2.3.4:50001", "inbound": true, "conntime": 100,
+            "last_block": 90, "last_transaction": 95, "minping": 0.02});
+        let p = Peer::inbound_from_value(&entry).unwrap();
+        assert_eq!((p.id, p.last_tx, p.min_ping), (7, 95, Some(0.02)));
+        assert!(Peer::outbound_from_value(&entry).is_none());
+        let outbound = json!({"id": 8, "addr": "1.2.3.4:8333", "inbound": false});
+        assert!(Peer::inbound_from_value(&outbound).is_none());
+        assert!(Peer::outbound_from_value(&outbound).is_some());
+        let manual = json!({"id": 10, "addr": "1.2.3.4:8333", "inbound": false,
+            "connection_type": "manual"});
+        assert!(Peer::outbound_from_value(&manual).is_none());
+        let noban =
+            json!({"id": 9, "addr": "1.2.3.4:1", "inbound": true, "permissions": ["noban"]});
+        assert!(Peer::inbound_from_value(&noban).is_none());
+        assert_eq!(subnet(&"::ffff:1.2.3.4".parse().unwrap()), vec![1, 2]);
+    }
+}
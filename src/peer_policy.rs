@@ -0,0 +1,212 @@
+//! Peer version policy (`[peer_policy]`): minimum protocol version and user-agent filters
+//!
+//! Peers below `min_protocol_version` or whose user agent matches one of
+//! `user_agent_patterns` (`*` wildcards, case-insensitive, e.g. `"/Broken Fork:*"`) are
+//! disconnected, or with `action = "deprioritize"` only disconnected while the node holds at
+//! least `deprioritize_above` connections, so they still fill otherwise empty slots. Peers with
+//! the `noban` permission are exempt. Counts per reason are kept in
+//! `<data_dir>/peer-policy.json` for `blvm peers policy`.
+//!
+//! The handshake itself happens in blvm-node, which has no hook for this policy, so filtered
+//! peers are found on the binary's `getpeerinfo` poll and dropped with `disconnectnode`. A peer
+//! that matches stays connected, and may relay, for up to `check_interval_secs`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Counter file name in the data directory
+pub const STATS_FILE: &str = "peer-policy.json";
+
+/// What happens to a peer that fails the policy
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    #[default]
+    Disconnect,
+    Deprioritize,
+}
+
+/// `[peer_policy]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PeerPolicyConfig {
+    pub enabled: bool,
+    pub min_protocol_version: Option<u32>,
+    pub user_agent_patterns: Vec<String>,
+    pub action: Action,
+    /// With `action = "deprioritize"`: connection count from which filtered peers are dropped
+    pub deprioritize_above: usize,
+    pub check_interval_secs: u64,
+}
+
+impl Default for PeerPolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_protocol_version: None,
+            user_agent_patterns: Vec::new(),
+            action: Action::Disconnect,
+            deprioritize_above: 8,
+            check_interval_secs: 10,
+        }
+    }
+}
+
+/// Case-insensitive match with `*` matching any run of characters
+pub fn matches_pattern(pattern: &str, user_agent: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = user_agent.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if parts.len() == 1 {
+        return text == first;
+    }
+    if !text.starts_with(first) || text.len() < first.len() + last.len() {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    if !text.ends_with(last) {
+        return false;
+    }
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// A connected peer from `getpeerinfo`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyPeer {
+    pub id: u64,
+    pub addr: String,
+    pub version: u32,
+    pub user_agent: String,
+}
+
+impl PolicyPeer {
+    /// Peers whose handshake completed (`version` > 0), except `noban` ones
+    pub fn from_value(entry: &Value) -> Option<Self> {
+        let noban = entry
+            .get("permissions")
+            .and_then(|v| v.as_array())
+            .is_some_and(|p| p.iter().any(|p| p.as_str() == Some("noban")));
+        let version = entry.get("version")?.as_u64()? as u32;
+        if noban || version == 0 {
+            return None;
+        }
+        Some(PolicyPeer {
+            id: entry.get("id")?.as_u64()?,
+            addr: entry.get("addr")?.as_str()?.to_string(),
+            version,
+            user_agent: entry
+                .get("subver")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+
+    /// Counter key of the first rule the peer fails (`min_version` or `user_agent:<pattern>`)
+    pub fn violation(&self, config: &PeerPolicyConfig) -> Option<String> {
+        if config
+            .min_protocol_version
+            .is_some_and(|min| self.version < min)
+        {
+            return Some("min_version".to_string());
+        }
+        config
+            .user_agent_patterns
+            .iter()
+            .find(|pattern| matches_pattern(pattern, &self.user_agent))
+            .map(|pattern| format!("user_agent:{pattern}"))
+    }
+}
+
+/// Filtered peers since the counters were created
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PolicyStats {
+    /// Unix time of the first count
+    pub since: u64,
+    /// Peers found failing the policy, per reason
+    pub filtered: BTreeMap<String, u64>,
+    pub disconnected: u64,
+    /// Filtered peers kept because the node was below `deprioritize_above`
+    pub tolerated: u64,
+}
+
+impl PolicyStats {
+    pub fn load(data_dir: &Path) -> Self {
+        std::fs::read(data_dir.join(STATS_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> std::io::Result<()> {
+        let path = data_dir.join(STATS_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)
+    }
+
+    pub fn record(&mut self, reason: &str, now: u64) {
+        if self.since == 0 {
+            self.since = now;
+        }
+        *self.filtered.entry(reason.to_string()).or_default() += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn wildcard_patterns() {
+        assert!(matches_pattern("/Broken:*", "/broken:1.2/"));
+        assert!(matches_pattern("*fork*", "/Satoshi:25.0(ForkCoin)/"));
+        assert!(matches_pattern("/a*b*c/", "/a-x-b-y-c/"));
+        assert!(!matches_pattern("/a*b*c/", "/a-c-b/"));
+        assert!(!matches_pattern("/Satoshi:0.1*", "/Satoshi:27.0/"));
+        assert!(matches_pattern("exact", "EXACT"));
+        assert!(!matches_pattern("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn version_and_user_agent_rules() {
+        let config: PeerPolicyConfig = toml::from_str(
+            "enabled = true\nmin_protocol_version = 70015\nuser_agent_patterns = [\"*bcash*\"]\n\
+             action = \"deprioritize\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.action, Action::Deprioritize);
+        let peer = |version: u64, subver: &str, permissions: Value| {
+            PolicyPeer::from_value(&json!({"id": 1, "addr": "1.2.3.4:8333",
+                "version": version, "subver": subver, "permissions": permissions}))
+        };
+        let old = peer(70012, "/Satoshi:0.14.0/", json!([])).unwrap();
+        assert_eq!(old.violation(&config).as_deref(), Some("min_version"));
+        let fork = peer(70016, "/Bcash:1.0/", json!([])).unwrap();
+        assert_eq!(
+            fork.violation(&config).as_deref(),
+            Some("user_agent:*bcash*")
+        );
+        let good = peer(70016, "/Satoshi:27.0.0/", json!([])).unwrap();
+        assert_eq!(good.violation(&config), None);
+        // Exempt or not yet handshaken
+        assert!(peer(70012, "/old/", json!(["noban"])).is_none());
+        assert!(peer(0, "", json!([])).is_none());
+
+        let mut stats = PolicyStats::default();
+        stats.record("min_version", 100);
+        stats.record("min_version", 200);
+        assert_eq!((stats.since, stats.filtered["min_version"]), (100, 2));
+    }
+}
@@ -0,0 +1,146 @@
+//! Peer keepalive and stall timeouts (`[network.timeouts]`)
+//!
+//! With the section present, the binary pings every peer each `ping_interval_secs` and
+//! disconnects peers that have not finished the version handshake within
+//! `handshake_timeout_secs`, leave a ping unanswered for `pong_timeout_secs`, or hold requested
+//! blocks without delivering one for `stalled_block_timeout_secs`. `ban_secs > 0` also bans the
+//! peer's address for that long. Keys left out keep the defaults below (Bitcoin Core's values,
+//! except the shorter block stall window).
+//!
+//! Timeouts are checked every `check_interval_secs` from `getpeerinfo`, not by blvm-node's
+//! connection handling, so a peer is disconnected up to one interval after its deadline.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::net::{IpAddr, SocketAddr};
+
+/// The part of `[network]` this crate reads; the rest belongs to blvm-node
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NetworkSection {
+    /// `None` when the config has no `[network.timeouts]` table (nothing enforced)
+    pub timeouts: Option<TimeoutsConfig>,
+}
+
+/// `[network.timeouts]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TimeoutsConfig {
+    pub ping_interval_secs: u64,
+    pub pong_timeout_secs: u64,
+    pub handshake_timeout_secs: u64,
+    pub stalled_block_timeout_secs: u64,
+    /// Ban duration for violating peers (0 = disconnect only)
+    pub ban_secs: u64,
+    pub check_interval_secs: u64,
+}
+
+impl Default for TimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval_secs: 120,
+            pong_timeout_secs: 20 * 60,
+            handshake_timeout_secs: 60,
+            stalled_block_timeout_secs: 5 * 60,
+            ban_secs: 0,
+            check_interval_secs: 10,
+        }
+    }
+}
+
+/// Which timeout a peer ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    Handshake,
+    Pong,
+    StalledBlocks,
+}
+
+impl Violation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Violation::Handshake => "handshake",
+            Violation::Pong => "pong",
+            Violation::StalledBlocks => "stalled_blocks",
+        }
+    }
+}
+
+impl TimeoutsConfig {
+    /// First timeout a `getpeerinfo` entry exceeds at unix time `now`
+    pub fn violation(&self, peer: &Value, now: u64) -> Option<Violation> {
+        let conntime = peer.get("conntime").and_then(|v| v.as_u64()).unwrap_or(now);
+        let version = peer.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+        if version == 0 && now.saturating_sub(conntime) > self.handshake_timeout_secs {
+            return Some(Violation::Handshake);
+        }
+        if peer
+            .get("pingwait")
+            .and_then(|v| v.as_f64())
+            .is_some_and(|wait| wait > self.pong_timeout_secs as f64)
+        {
+            return Some(Violation::Pong);
+        }
+        let inflight = peer
+            .get("inflight")
+            .and_then(|v| v.as_array())
+            .is_some_and(|blocks| !blocks.is_empty());
+        // Progress: the last block the peer delivered, or the connection time if none yet
+        let last_block = peer.get("last_block").and_then(|v| v.as_u64()).unwrap_or(0);
+        if inflight
+            && now.saturating_sub(last_block.max(conntime)) > self.stalled_block_timeout_secs
+        {
+            return Some(Violation::StalledBlocks);
+        }
+        None
+    }
+}
+
+/// IP to ban for a `getpeerinfo` address (`None` for onion and other non-IP addresses)
+pub fn ban_target(addr: &str) -> Option<IpAddr> {
+    addr.parse::<SocketAddr>()
+        .map(|a| a.ip())
+        .or_else(|_| addr.parse::<IpAddr>())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn timeouts_from_peer_info() {
+        let section: NetworkSection =
+            toml::from_str("max_peers = 8\n[timeouts]\npong_timeout_secs = 30\n").unwrap();
+        let timeouts = section.timeouts.unwrap();
+        assert_eq!(timeouts.handshake_timeout_secs, 60);
+        assert!(
+            toml::from_str::<NetworkSection>("[shaping]\n")
+                .unwrap()
+                .timeouts
+                .is_none()
+        );
+
+        let now = 10_000;
+        let healthy = json!({"version": 70016, "conntime": 1_000, "pingwait": 2.5,
+            "inflight": [101], "last_block": 9_990});
+        assert_eq!(timeouts.violation(&healthy, now), None);
+        let no_version = json!({"version": 0, "conntime": now - 61});
+        assert_eq!(
+            timeouts.violation(&no_version, now),
+            Some(Violation::Handshake)
+        );
+        let silent = json!({"version": 70016, "conntime": 1_000, "pingwait": 31.0});
+        assert_eq!(timeouts.violation(&silent, now), Some(Violation::Pong));
+        let staller = json!({"version": 70016, "conntime": 1_000, "inflight": [101, 102],
+            "last_block": now - 301});
+        assert_eq!(
+            timeouts.violation(&staller, now),
+            Some(Violation::StalledBlocks)
+        );
+
+        assert_eq!(ban_target("[::1]:8333"), Some("::1".parse().unwrap()));
+        assert_eq!(ban_target("abc.onion:8333"), None);
+    }
+}
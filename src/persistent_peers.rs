@@ -0,0 +1,337 @@
+//! Structured persistent peers (`[[persistent_peer]]`, `blvm peers persist add|remove|list`)
+//!
+//! blvm-node's `persistent_peers` holds bare addresses. `[[persistent_peer]]` entries carry
+//! per-peer options instead; the binary keeps those peers connected itself, retrying with the
+//! entry's limits. `add`/`remove` edit the config file as text so comments and layout survive.
+//!
+//! The binary cannot hook the node's connection manager: it checks `getpeerinfo` every 10
+//! seconds and reconnects with `addnode ... onetry`, so a dropped peer stays disconnected for up
+//! to one check plus its `retry_interval_secs`. `transport` and `relay = false` are passed as
+//! `addnode` options and take effect only if the node supports them.
+
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// TOML table name of an entry
+pub const SECTION: &str = "persistent_peer";
+
+fn default_retry_interval() -> u64 {
+    60
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Transport used to reach a peer
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerTransport {
+    #[default]
+    Tcp,
+    Iroh,
+    Quinn,
+}
+
+impl PeerTransport {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PeerTransport::Tcp => "tcp",
+            PeerTransport::Iroh => "iroh",
+            PeerTransport::Quinn => "quinn",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "tcp" => Ok(PeerTransport::Tcp),
+            "iroh" => Ok(PeerTransport::Iroh),
+            "quinn" => Ok(PeerTransport::Quinn),
+            _ => Err(format!("unknown transport '{s}' (tcp, iroh, quinn)")),
+        }
+    }
+}
+
+/// One `[[persistent_peer]]` entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PersistentPeer {
+    /// `ip:port` (an iroh node id for `transport = "iroh"`)
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub transport: PeerTransport,
+    /// Reconnect attempts in a row before giving up (0 = keep trying)
+    #[serde(default)]
+    pub max_retries: u32,
+    #[serde(default = "default_retry_interval")]
+    pub retry_interval_secs: u64,
+    /// Relay transactions to and from this peer (false = blocks only)
+    #[serde(default = "default_true")]
+    pub relay: bool,
+}
+
+impl PersistentPeer {
+    pub fn new(address: impl Into<String>) -> Self {
+        PersistentPeer {
+            address: address.into(),
+            label: None,
+            transport: PeerTransport::Tcp,
+            max_retries: 0,
+            retry_interval_secs: default_retry_interval(),
+            relay: true,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.transport != PeerTransport::Iroh && self.address.parse::<SocketAddr>().is_err() {
+            return Err(format!(
+                "invalid peer address '{}' (expected ip:port)",
+                self.address
+            ));
+        }
+        if self.address.is_empty() || self.address.contains(['"', '\n']) {
+            return Err(format!("invalid peer address '{}'", self.address));
+        }
+        if self.retry_interval_secs == 0 {
+            return Err("retry_interval_secs must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// The entry as a TOML block (`[[persistent_peer]]` header first)
+    pub fn to_toml(&self) -> String {
+        let mut block = format!("[[{SECTION}]]\naddress = {}\n", quote(&self.address));
+        if let Some(label) = &self.label {
+            block.push_str(&format!("label = {}\n", quote(label)));
+        }
+        if self.transport != PeerTransport::Tcp {
+            block.push_str(&format!("transport = \"{}\"\n", self.transport.as_str()));
+        }
+        if self.max_retries != 0 {
+            block.push_str(&format!("max_retries = {}\n", self.max_retries));
+        }
+        if self.retry_interval_secs != default_retry_interval() {
+            block.push_str(&format!(
+                "retry_interval_secs = {}\n",
+                self.retry_interval_secs
+            ));
+        }
+        if !self.relay {
+            block.push_str("relay = false\n");
+        }
+        block
+    }
+}
+
+/// TOML basic string
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[derive(Deserialize)]
+struct PeerSections {
+    #[serde(default)]
+    persistent_peers: Vec<String>,
+    #[serde(default, rename = "persistent_peer")]
+    entries: Vec<PersistentPeer>,
+}
+
+fn sections(content: &str) -> Result<PeerSections, String> {
+    toml::from_str(content).map_err(|e| format!("config file is not valid TOML: {e}"))
+}
+
+/// Structured entries of a config file
+pub fn entries(content: &str) -> Result<Vec<PersistentPeer>, String> {
+    Ok(sections(content)?.entries)
+}
+
+/// `content` with `peer` appended; fails on duplicates (structured or bare)
+pub fn add_to_config(content: &str, peer: &PersistentPeer) -> Result<String, String> {
+    peer.validate()?;
+    let existing = sections(content)?;
+    if existing.persistent_peers.contains(&peer.address)
+        || existing.entries.iter().any(|e| e.address == peer.address)
+    {
+        return Err(format!("{} is already a persistent peer", peer.address));
+    }
+    let mut updated = content.to_string();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    updated.push_str(&peer.to_toml());
+    // The block is appended after any open table, which only array-of-tables syntax allows;
+    // re-parse to be sure the file still reads the same otherwise
+    if entries(&updated)?.len() != existing.entries.len() + 1 {
+        return Err("could not add the entry to the config file".to_string());
+    }
+    Ok(updated)
+}
+
+/// `content` without the `[[persistent_peer]]` block for `address`
+pub fn remove_from_config(content: &str, address: &str) -> Result<String, String> {
+    let existing = sections(content)?;
+    if !existing.entries.iter().any(|e| e.address == address) {
+        if existing.persistent_peers.iter().any(|a| a == address) {
+            return Err(format!(
+                "{address} is listed in persistent_peers; remove it from that array by hand"
+            ));
+        }
+        return Err(format!("{address} is not a persistent peer"));
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    let header = format!("[[{SECTION}]]");
+    let mut start = 0;
+    while let Some(offset) = lines[start..].iter().position(|l| l.trim() == header) {
+        let block_start = start + offset;
+        let block_end = lines[block_start + 1..]
+            .iter()
+            .position(|l| l.trim_start().starts_with('['))
+            .map_or(lines.len(), |i| block_start + 1 + i);
+        let block = lines[block_start..block_end].join("\n");
+        let matches =
+            toml::from_str::<PersistentPeer>(&lines[block_start + 1..block_end].join("\n"))
+                .is_ok_and(|entry| entry.address == address);
+        if matches {
+            // Take the blank line that separated the block along with it
+            let cut_start = if block_start > 0 && lines[block_start - 1].trim().is_empty() {
+                block_start - 1
+            } else {
+                block_start
+            };
+            let mut kept: Vec<&str> = lines[..cut_start].to_vec();
+            kept.extend_from_slice(&lines[block_end..]);
+            let mut updated = kept.join("\n");
+            if content.ends_with('\n') && !updated.is_empty() {
+                updated.push('\n');
+            }
+            if entries(&updated)?.len() + 1 != existing.entries.len() {
+                return Err(format!("could not remove the block:\n{block}"));
+            }
+            return Ok(updated);
+        }
+        start = block_end.max(block_start + 1);
+    }
+    Err(format!(
+        "could not find the [[{SECTION}]] block for {address} (written inline?)"
+    ))
+}
+
+/// What the supervisor should do about a peer on this check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Wait,
+    Connect,
+    /// `max_retries` attempts in a row failed; stop until the next start
+    GiveUp,
+}
+
+/// Reconnect bookkeeping for one peer
+#[derive(Debug, Clone, Default)]
+pub struct RetryState {
+    pub attempts: u32,
+    pub last_attempt: u64,
+    pub gave_up: bool,
+}
+
+impl RetryState {
+    pub fn next(&mut self, peer: &PersistentPeer, connected: bool, now: u64) -> Action {
+        if connected {
+            *self = RetryState::default();
+            return Action::Wait;
+        }
+        if self.gave_up
+            || (self.attempts > 0
+                && now < self.last_attempt.saturating_add(peer.retry_interval_secs))
+        {
+            return Action::Wait;
+        }
+        if peer.max_retries != 0 && self.attempts >= peer.max_retries {
+            self.gave_up = true;
+            return Action::GiveUp;
+        }
+        self.attempts += 1;
+        self.last_attempt = now;
+        Action::Connect
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "# node settings\nmax_peers = 50\npersistent_peers = [\"1.2.3.4:8333\"]\n\n[storage]\ndatabase_backend = \"auto\" # keep\n";
+
+    #[test]
+    fn adds_entries_without_touching_the_rest() {
+        let mut peer = PersistentPeer::new("10.0.0.2:8333");
+        peer.label = Some("backup \"rack 2\"".to_string());
+        peer.relay = false;
+        peer.max_retries = 5;
+        let updated = add_to_config(CONFIG, &peer).unwrap();
+        assert!(updated.starts_with(CONFIG));
+        assert_eq!(entries(&updated).unwrap(), vec![peer.clone()]);
+        assert!(add_to_config(&updated, &peer).is_err());
+        assert!(add_to_config(CONFIG, &PersistentPeer::new("1.2.3.4:8333")).is_err());
+        assert!(add_to_config(CONFIG, &PersistentPeer::new("not-an-address")).is_err());
+
+        let two = add_to_config(&updated, &PersistentPeer::new("10.0.0.3:8333")).unwrap();
+        assert_eq!(entries(&two).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn removes_only_the_matching_block() {
+        let with_a = add_to_config(CONFIG, &PersistentPeer::new("10.0.0.2:8333")).unwrap();
+        let mut b = PersistentPeer::new("10.0.0.3:8333");
+        b.transport = PeerTransport::Quinn;
+        let with_both = add_to_config(&with_a, &b).unwrap();
+
+        let removed = remove_from_config(&with_both, "10.0.0.2:8333").unwrap();
+        assert_eq!(entries(&removed).unwrap(), vec![b]);
+        assert!(removed.contains("# node settings") && removed.contains("# keep"));
+        assert_eq!(
+            remove_from_config(&with_a, "10.0.0.2:8333").unwrap(),
+            CONFIG
+        );
+        assert!(
+            remove_from_config(CONFIG, "1.2.3.4:8333")
+                .unwrap_err()
+                .contains("by hand")
+        );
+        assert!(remove_from_config(CONFIG, "9.9.9.9:8333").is_err());
+    }
+
+    #[test]
+    fn retries_up_to_the_limit() {
+        let mut peer = PersistentPeer::new("10.0.0.2:8333");
+        peer.max_retries = 2;
+        peer.retry_interval_secs = 30;
+        let mut state = RetryState::default();
+        assert_eq!(state.next(&peer, false, 100), Action::Connect);
+        assert_eq!(state.next(&peer, false, 110), Action::Wait);
+        assert_eq!(state.next(&peer, false, 130), Action::Connect);
+        assert_eq!(state.next(&peer, false, 160), Action::GiveUp);
+        assert_eq!(state.next(&peer, false, 500), Action::Wait);
+
+        // A connection resets the count
+        let mut state = RetryState::default();
+        state.next(&peer, false, 100);
+        assert_eq!(state.next(&peer, true, 200), Action::Wait);
+        assert_eq!(state.next(&peer, false, 201), Action::Connect);
+    }
+}
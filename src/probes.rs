@@ -0,0 +1,139 @@
+//! Kubernetes-style HTTP probes (`[probes]`)
+//!
+//! - `GET /healthz` (liveness): 200 while the process serves requests, including during a
+//!   graceful shutdown, so a long flush is not cut short by a restart
+//! - `GET /readyz` (readiness): 200 once the node's RPC answers and initial block download is
+//!   over (or `ready_during_ibd`); 503 as soon as shutdown starts, so the pod leaves Service
+//!   endpoints before the node stops
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::net::SocketAddr;
+
+/// `[probes]` config section
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProbesConfig {
+    /// Probe listener, e.g. `0.0.0.0:8080`; no listener when unset
+    pub addr: Option<SocketAddr>,
+    /// Report ready while still in initial block download
+    pub ready_during_ibd: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Probe {
+    Live,
+    Ready,
+}
+
+/// Parse an HTTP request line (`GET /readyz HTTP/1.1`); HEAD is accepted too
+pub fn parse_request_line(line: &str) -> Result<Probe, String> {
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err("malformed request line".to_string());
+    };
+    if method != "GET" && method != "HEAD" {
+        return Err(format!("method {method} not allowed"));
+    }
+    match target.split_once('?').map_or(target, |(path, _)| path) {
+        "/healthz" | "/livez" => Ok(Probe::Live),
+        "/readyz" => Ok(Probe::Ready),
+        path => Err(format!("unknown path {path}")),
+    }
+}
+
+/// Readiness from `getblockchaininfo` (or why the RPC failed); `Err` is the 503 reason.
+/// `held` is a reason a background task reports (e.g. a lagging replica).
+pub fn readiness(
+    info: Result<&Value, &str>,
+    shutting_down: bool,
+    held: Option<&str>,
+    ready_during_ibd: bool,
+) -> Result<String, String> {
+    if shutting_down {
+        return Err("shutting down".to_string());
+    }
+    if let Some(reason) = held {
+        return Err(reason.to_string());
+    }
+    let info = info.map_err(|e| format!("node RPC unavailable: {e}"))?;
+    let height = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+    let ibd = info
+        .get("initialblockdownload")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if ibd && !ready_during_ibd {
+        let headers = info.get("headers").and_then(|v| v.as_u64()).unwrap_or(0);
+        return Err(format!("initial block download ({height}/{headers})"));
+    }
+    Ok(format!("ready at height {height}"))
+}
+
+/// HTTP response for a probe result
+pub fn response(result: &Result<String, String>) -> String {
+    match result {
+        Ok(body) => http("200 OK", body),
+        Err(reason) => http("503 Service Unavailable", reason),
+    }
+}
+
+/// HTTP response for a request that is not a probe
+pub fn not_found(reason: &str) -> String {
+    http("404 Not Found", reason)
+}
+
+/// Complete HTTP response with a one-line plain text body
+fn http(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}\n",
+        body.len() + 1
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_probe_paths() {
+        assert_eq!(
+            parse_request_line("GET /healthz HTTP/1.1\r\n"),
+            Ok(Probe::Live)
+        );
+        assert_eq!(
+            parse_request_line("HEAD /readyz?verbose=1 HTTP/1.1"),
+            Ok(Probe::Ready)
+        );
+        assert!(parse_request_line("POST /readyz HTTP/1.1").is_err());
+        assert!(parse_request_line("GET /metrics HTTP/1.1").is_err());
+        assert!(not_found("unknown path /metrics").starts_with("HTTP/1.1 404 "));
+        assert!(parse_request_line("").is_err());
+    }
+
+    #[test]
+    fn readiness_follows_ibd_and_shutdown() {
+        let syncing = json!({"blocks": 100, "headers": 800_000, "initialblockdownload": true});
+        let synced = json!({"blocks": 800_000, "initialblockdownload": false});
+        assert!(readiness(Ok(&syncing), false, None, false).is_err());
+        assert!(readiness(Ok(&syncing), false, None, true).is_ok());
+        assert_eq!(
+            readiness(Ok(&synced), false, None, false),
+            Ok("ready at height 800000".to_string())
+        );
+        assert_eq!(
+            readiness(Ok(&synced), true, None, false),
+            Err("shutting down".to_string())
+        );
+        assert!(readiness(Err("connection refused"), false, None, false).is_err());
+        assert_eq!(
+            readiness(Ok(&synced), false, Some("replica 9 blocks behind"), false),
+            Err("replica 9 blocks behind".to_string())
+        );
+
+        let http = response(&readiness(Ok(&synced), false, None, false));
+        assert!(http.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(http.ends_with("\r\n\r\nready at height 800000\n"));
+        assert!(http.contains("Content-Length: 23\r\n"));
+    }
+}
@@ -0,0 +1,205 @@
+//! On-demand profiling endpoints (`--pprof-addr`, feature `pprof`)
+//!
+//! - `GET /debug/pprof/profile?seconds=N` — CPU profile (pprof protobuf; `&format=svg` for a flamegraph)
+//! - `GET /debug/pprof/heap` — heap profile (requires a jemalloc build of blvm-node)
+
+/// Default CPU profile duration
+pub const DEFAULT_PROFILE_SECONDS: u64 = 30;
+
+/// Upper bound so a typo cannot pin the sampler for hours
+pub const MAX_PROFILE_SECONDS: u64 = 600;
+
+/// Longest request line read; longer ones are refused
+pub const MAX_REQUEST_LINE: u64 = 8 * 1024;
+
+/// How long a client may take to send its request line
+pub const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Parsed profiling request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileRequest {
+    Cpu { seconds: u64, svg: bool },
+    Heap,
+}
+
+/// Parse an HTTP request line (`GET /debug/pprof/profile?seconds=10 HTTP/1.1`).
+pub fn parse_request_line(line: &str) -> Result<ProfileRequest, String> {
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err("malformed request line".to_string());
+    };
+    if method != "GET" {
+        return Err(format!("method {method} not allowed"));
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|kv| kv.split_once('='))
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v)
+    };
+    match path {
+        "/debug/pprof/profile" => {
+            let seconds = match param("seconds") {
+                Some(s) => s
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid seconds '{s}'"))?,
+                None => DEFAULT_PROFILE_SECONDS,
+            };
+            if seconds == 0 || seconds > MAX_PROFILE_SECONDS {
+                return Err(format!("seconds must be 1-{MAX_PROFILE_SECONDS}"));
+            }
+            Ok(ProfileRequest::Cpu {
+                seconds,
+                svg: param("format") == Some("svg"),
+            })
+        }
+        "/debug/pprof/heap" => Ok(ProfileRequest::Heap),
+        _ => Err(format!("unknown path {path}")),
+    }
+}
+
+#[cfg(feature = "pprof")]
+mod server {
+    use super::{MAX_REQUEST_LINE, ProfileRequest, REQUEST_TIMEOUT_SECS, parse_request_line};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::Mutex;
+    use tracing::{info, warn};
+
+    /// Serve profiling endpoints until the process exits. Profiles are taken one at a time.
+    pub async fn serve(addr: SocketAddr) -> anyhow::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("Profiling endpoints on http://{}/debug/pprof/", addr);
+        let lock = Arc::new(Mutex::new(()));
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let lock = lock.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle(stream, &lock).await {
+                    warn!("Profiling request failed: {}", e);
+                }
+            });
+        }
+    }
+
+    /// Read the request line (bounded in time and length), then wait for the profiler lock only
+    /// for a valid CPU profile request
+    async fn handle(stream: TcpStream, lock: &Mutex<()>) -> anyhow::Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        let mut capped = (&mut reader).take(MAX_REQUEST_LINE);
+        tokio::time::timeout(
+            Duration::from_secs(REQUEST_TIMEOUT_SECS),
+            capped.read_line(&mut line),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("no request line within {REQUEST_TIMEOUT_SECS}s"))??;
+        let request = if line.len() as u64 >= MAX_REQUEST_LINE && !line.ends_with('\n') {
+            Err(format!("request line longer than {MAX_REQUEST_LINE} bytes"))
+        } else {
+            parse_request_line(&line)
+        };
+        let mut stream = reader.into_inner();
+        let (status, content_type, body) = match request {
+            Ok(ProfileRequest::Cpu { seconds, svg }) => {
+                let profile = {
+                    let _guard = lock.lock().await;
+                    cpu_profile(seconds, svg).await
+                };
+                match profile {
+                    Ok(body) => {
+                        let content_type = if svg {
+                            "image/svg+xml"
+                        } else {
+                            "application/octet-stream"
+                        };
+                        ("200 OK", content_type, body)
+                    }
+                    Err(e) => (
+                        "500 Internal Server Error",
+                        "text/plain",
+                        e.to_string().into(),
+                    ),
+                }
+            }
+            Ok(ProfileRequest::Heap) => (
+                "501 Not Implemented",
+                "text/plain",
+                b"heap profiling requires blvm-node built with jemalloc profiling\n".to_vec(),
+            ),
+            Err(e) => (
+                "400 Bad Request",
+                "text/plain",
+                format!("{e}\n").into_bytes(),
+            ),
+        };
+        let header = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(header.as_bytes()).await?;
+        stream.write_all(&body).await?;
+        Ok(())
+    }
+
+    async fn cpu_profile(seconds: u64, svg: bool) -> anyhow::Result<Vec<u8>> {
+        let guard = pprof::ProfilerGuardBuilder::default()
+            .frequency(99)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build()?;
+        tokio::time::sleep(Duration::from_secs(seconds)).await;
+        let report = guard.report().build()?;
+        let mut body = Vec::new();
+        if svg {
+            report.flamegraph(&mut body)?;
+        } else {
+            use pprof::protos::Message;
+            report.pprof()?.encode(&mut body)?;
+        }
+        Ok(body)
+    }
+}
+
+#[cfg(feature = "pprof")]
+pub use server::serve;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_profile_requests() {
+        assert_eq!(
+            parse_request_line("GET /debug/pprof/profile HTTP/1.1\r\n"),
+            Ok(ProfileRequest::Cpu {
+                seconds: DEFAULT_PROFILE_SECONDS,
+                svg: false
+            })
+        );
+        assert_eq!(
+            parse_request_line("GET /debug/pprof/profile?seconds=5&format=svg HTTP/1.1"),
+            Ok(ProfileRequest::Cpu {
+                seconds: 5,
+                svg: true
+            })
+        );
+        assert_eq!(
+            parse_request_line("GET /debug/pprof/heap HTTP/1.1"),
+            Ok(ProfileRequest::Heap)
+        );
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        assert!(parse_request_line("POST /debug/pprof/heap HTTP/1.1").is_err());
+        assert!(parse_request_line("GET /debug/pprof/profile?seconds=0 HTTP/1.1").is_err());
+        assert!(parse_request_line("GET /debug/pprof/profile?seconds=abc HTTP/1.1").is_err());
+        assert!(parse_request_line("GET /metrics HTTP/1.1").is_err());
+        assert!(parse_request_line("").is_err());
+    }
+}
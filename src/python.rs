@@ -0,0 +1,275 @@
+//! Python bindings (`python` feature, built with maturin from `python/pyproject.toml`)
+//!
+//! `blvm.Node` embeds a node through `NodeBuilder` / `NodeHandle`; `blvm.Client` talks to a node
+//! that is already running. Both block the calling Python thread (with the GIL released) on one
+//! small tokio runtime shared by the whole process. RPC params and results cross as plain
+//! Python values via `json`.
+
+use crate::node::{NodeBuilder, NodeEvent, NodeHandle, RpcClient, Shutdown, load_config_file};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+fn py_err(e: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{e:#}"))
+}
+
+/// The process's runtime for every `Node`, `Events` and `Client`
+fn runtime() -> PyResult<&'static tokio::runtime::Runtime> {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("blvm-py")
+        .enable_all()
+        .build()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+fn to_json(py: Python<'_>, obj: Option<&Bound<'_, PyAny>>) -> PyResult<Value> {
+    let Some(obj) = obj else {
+        return Ok(json!([]));
+    };
+    let text: String = py
+        .import_bound("json")?
+        .call_method1("dumps", (obj,))?
+        .extract()?;
+    serde_json::from_str(&text).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+fn to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(py
+        .import_bound("json")?
+        .call_method1("loads", (value.to_string(),))?
+        .unbind())
+}
+
+fn parse_addr(addr: &str) -> PyResult<std::net::SocketAddr> {
+    addr.parse()
+        .map_err(|_| PyValueError::new_err(format!("Invalid address '{addr}'")))
+}
+
+/// An embedded node; use as a context manager (or call `shutdown`) to stop it
+#[pyclass(name = "Node", module = "blvm")]
+struct PyNode {
+    runtime: &'static tokio::runtime::Runtime,
+    handle: Option<NodeHandle>,
+}
+
+impl PyNode {
+    fn handle(&self) -> PyResult<&NodeHandle> {
+        self.handle
+            .as_ref()
+            .ok_or_else(|| PyRuntimeError::new_err("node has been shut down"))
+    }
+}
+
+#[pymethods]
+impl PyNode {
+    /// Start a node and wait until its RPC server answers. `config_path` is a `blvm.toml`: the
+    /// node's settings plus `rpc_user` / `rpc_password`; binary-only sections are not started.
+    #[staticmethod]
+    #[pyo3(signature = (network, data_dir, rpc_addr=None, listen_addr=None, config_path=None))]
+    fn start(
+        py: Python<'_>,
+        network: &str,
+        data_dir: PathBuf,
+        rpc_addr: Option<&str>,
+        listen_addr: Option<&str>,
+        config_path: Option<PathBuf>,
+    ) -> PyResult<Self> {
+        let mut builder = NodeBuilder::new(network, data_dir)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        if let Some(path) = config_path {
+            builder = builder
+                .config_file(&path)
+                .map_err(|e| PyValueError::new_err(format!("{e:#}")))?;
+        }
+        if let Some(addr) = rpc_addr {
+            builder = builder.rpc_addr(parse_addr(addr)?);
+        }
+        if let Some(addr) = listen_addr {
+            builder = builder.listen_addr(parse_addr(addr)?);
+        }
+        let runtime = runtime()?;
+        let handle = py
+            .allow_threads(|| runtime.block_on(builder.start()))
+            .map_err(py_err)?;
+        Ok(PyNode {
+            runtime,
+            handle: Some(handle),
+        })
+    }
+
+    #[getter]
+    fn rpc_addr(&self) -> PyResult<String> {
+        Ok(self.handle()?.rpc_addr().to_string())
+    }
+
+    /// Call any RPC method; `params` is a list (or dict for named params)
+    #[pyo3(signature = (method, params=None))]
+    fn rpc(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let params = to_json(py, params)?;
+        let handle = self.handle()?;
+        let result = py
+            .allow_threads(|| self.runtime.block_on(handle.rpc(method, params)))
+            .map_err(py_err)?;
+        to_py(py, &result)
+    }
+
+    /// `(hash, height)` of the best block
+    fn tip(&self, py: Python<'_>) -> PyResult<(String, u64)> {
+        let handle = self.handle()?;
+        let tip = py
+            .allow_threads(|| self.runtime.block_on(handle.query_tip()))
+            .map_err(py_err)?;
+        Ok((tip.hash, tip.height))
+    }
+
+    /// Submit a serialized block; raises with the node's reason when it is rejected
+    fn submit_block(&self, py: Python<'_>, block: &Bound<'_, PyBytes>) -> PyResult<()> {
+        let block = block.as_bytes().to_vec();
+        let handle = self.handle()?;
+        py.allow_threads(|| self.runtime.block_on(handle.submit_block(&block)))
+            .map_err(py_err)
+    }
+
+    /// Event stream from now on
+    fn events(&self) -> PyResult<PyEvents> {
+        Ok(PyEvents {
+            runtime: self.runtime,
+            receiver: self.handle()?.subscribe_events(),
+        })
+    }
+
+    /// Stop the node and wait for it to exit; further calls raise. Returns `None` on a clean
+    /// stop, or the error the node's run loop ended with after accepting `stop`.
+    fn shutdown(&mut self, py: Python<'_>) -> PyResult<Option<String>> {
+        let Some(handle) = self.handle.take() else {
+            return Ok(None);
+        };
+        let shutdown = py
+            .allow_threads(|| self.runtime.block_on(handle.shutdown()))
+            .map_err(py_err)?;
+        Ok(match shutdown {
+            Shutdown::Clean => None,
+            Shutdown::WithError(message) => Some(message),
+        })
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        _args: &Bound<'_, pyo3::types::PyTuple>,
+    ) -> PyResult<bool> {
+        self.shutdown(py)?;
+        Ok(false)
+    }
+}
+
+/// Events from `Node.events()`, as dicts such as `{"type": "tip", "hash": ..., "height": ...}`
+#[pyclass(name = "Events", module = "blvm")]
+struct PyEvents {
+    runtime: &'static tokio::runtime::Runtime,
+    receiver: broadcast::Receiver<NodeEvent>,
+}
+
+#[pymethods]
+impl PyEvents {
+    /// Next event, or `None` after `timeout` seconds
+    #[pyo3(signature = (timeout=None))]
+    fn recv(&mut self, py: Python<'_>, timeout: Option<f64>) -> PyResult<Option<PyObject>> {
+        let receiver = &mut self.receiver;
+        let runtime = self.runtime;
+        let event = py.allow_threads(|| {
+            runtime.block_on(async {
+                let next = async {
+                    loop {
+                        match receiver.recv().await {
+                            Ok(event) => return Some(event),
+                            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                            Err(broadcast::error::RecvError::Closed) => return None,
+                        }
+                    }
+                };
+                match timeout {
+                    Some(secs) => tokio::time::timeout(Duration::from_secs_f64(secs), next)
+                        .await
+                        .ok()
+                        .flatten(),
+                    None => next.await,
+                }
+            })
+        });
+        event.map(|event| to_py(py, &event.to_json())).transpose()
+    }
+}
+
+/// JSON-RPC client for a running node
+#[pyclass(name = "Client", module = "blvm")]
+struct PyClient {
+    runtime: &'static tokio::runtime::Runtime,
+    client: RpcClient,
+}
+
+#[pymethods]
+impl PyClient {
+    /// `config_path` is a `blvm.toml` supplying `rpc_user` / `rpc_password`, else `[rpc_auth]`
+    /// credentials
+    #[new]
+    #[pyo3(signature = (rpc_addr, config_path=None))]
+    fn new(rpc_addr: &str, config_path: Option<PathBuf>) -> PyResult<Self> {
+        let addr = parse_addr(rpc_addr)?;
+        let config_error = |e: anyhow::Error| PyValueError::new_err(format!("{e:#}"));
+        let client = match config_path {
+            Some(path) => match load_config_file(&path).map_err(config_error)? {
+                (_, Some((user, password))) => RpcClient::with_credentials(addr, &user, &password),
+                (config, None) => RpcClient::new(addr, &config).map_err(config_error)?,
+            },
+            None => RpcClient::new(addr, &Default::default()).map_err(config_error)?,
+        };
+        Ok(PyClient {
+            runtime: runtime()?,
+            client,
+        })
+    }
+
+    #[pyo3(signature = (method, params=None))]
+    fn rpc(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let params = to_json(py, params)?;
+        let result = py
+            .allow_threads(|| self.runtime.block_on(self.client.call(method, params)))
+            .map_err(py_err)?;
+        to_py(py, &result)
+    }
+}
+
+#[pymodule]
+fn blvm(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyNode>()?;
+    m.add_class::<PyEvents>()?;
+    m.add_class::<PyClient>()?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+//! Quarantine of blocks the node rejected
+//!
+//! Each entry is `<hash>.block` (raw bytes) plus `<hash>.json` (reason and analysis trace) in
+//! `<data_dir>/quarantine`, so a rejection can be re-examined later with `blvm analyze-block`.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Subdirectory of the data directory
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// `[quarantine]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct QuarantineConfig {
+    pub enabled: bool,
+    /// Oldest entries are removed beyond this count
+    pub max_entries: usize,
+}
+
+impl Default for QuarantineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 50,
+        }
+    }
+}
+
+/// Metadata stored next to a quarantined block
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuarantineEntry {
+    pub hash: String,
+    pub height: Option<u64>,
+    pub reason: String,
+    /// Unix time the block was quarantined
+    pub quarantined_at: u64,
+    /// Validation steps as run at quarantine time
+    pub trace: Vec<String>,
+}
+
+pub fn quarantine_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(QUARANTINE_DIR)
+}
+
+pub fn contains(data_dir: &Path, hash: &str) -> bool {
+    quarantine_dir(data_dir)
+        .join(format!("{hash}.json"))
+        .exists()
+}
+
+/// Write a block and its metadata, then trim to `max_entries`; returns the block path
+pub fn save(
+    data_dir: &Path,
+    bytes: &[u8],
+    entry: &QuarantineEntry,
+    max_entries: usize,
+) -> anyhow::Result<PathBuf> {
+    let dir = quarantine_dir(data_dir);
+    std::fs::create_dir_all(&dir)?;
+    let block_path = dir.join(format!("{}.block", entry.hash));
+    std::fs::write(&block_path, bytes)?;
+    std::fs::write(
+        block_path.with_extension("json"),
+        serde_json::to_vec_pretty(entry)?,
+    )?;
+    let entries = list(data_dir);
+    for (path, _) in entries
+        .iter()
+        .take(entries.len().saturating_sub(max_entries))
+    {
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(path.with_extension("json"));
+    }
+    Ok(block_path)
+}
+
+/// Metadata for a `.block` file, if its `.json` sidecar exists
+pub fn load_entry(block_path: &Path) -> Option<QuarantineEntry> {
+    let content = std::fs::read_to_string(block_path.with_extension("json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Quarantined blocks, oldest first
+pub fn list(data_dir: &Path) -> Vec<(PathBuf, QuarantineEntry)> {
+    let Ok(dir) = std::fs::read_dir(quarantine_dir(data_dir)) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<(PathBuf, QuarantineEntry)> = dir
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "block"))
+        .filter_map(|p| load_entry(&p).map(|entry| (p, entry)))
+        .collect();
+    entries.sort_by_key(|(_, entry)| entry.quarantined_at);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: &str, at: u64) -> QuarantineEntry {
+        QuarantineEntry {
+            hash: hash.to_string(),
+            height: Some(10),
+            reason: "bad-txnmrklroot".to_string(),
+            quarantined_at: at,
+            trace: vec!["[FAIL] merkle-root: mismatch".to_string()],
+        }
+    }
+
+    #[test]
+    fn saves_and_trims() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for (i, hash) in ["aa", "bb", "cc"].iter().enumerate() {
+            save(dir.path(), &[i as u8], &entry(hash, i as u64), 2).unwrap();
+        }
+        let entries = list(dir.path());
+        let hashes: Vec<&str> = entries.iter().map(|(_, e)| e.hash.as_str()).collect();
+        assert_eq!(hashes, ["bb", "cc"]);
+        assert!(!contains(dir.path(), "aa"));
+        assert_eq!(std::fs::read(&entries[1].0).unwrap(), [2]);
+        assert_eq!(load_entry(&entries[1].0).unwrap(), entry("cc", 2));
+    }
+}
@@ -0,0 +1,176 @@
+//! Read-only replica following a primary node (`[replica]`, `blvm replica status`)
+//!
+//! A replica takes blocks only from `primary`: inbound P2P is off, the primary is kept
+//! connected and any other peer is dropped. When the primary's RPC is configured the replica
+//! also measures its lag against the primary's tip, and once P2P falls more than
+//! `max_lag_blocks` behind for two checks in a row it fetches the missing blocks over that RPC
+//! and submits them locally. The latest status is kept in `<data_dir>/replica.json`; with
+//! `[probes]` a lagging replica reports not ready, so load balancers skip it.
+
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// Status file name in the data directory
+pub const STATE_FILE: &str = "replica.json";
+
+/// `[replica]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ReplicaConfig {
+    pub enabled: bool,
+    /// Primary's P2P address (`host:port`)
+    pub primary: Option<String>,
+    /// Primary's RPC address, for lag measurement and catch-up
+    pub primary_rpc: Option<SocketAddr>,
+    /// Bearer token for the primary's RPC (takes precedence over the password)
+    pub primary_rpc_token: Option<String>,
+    pub primary_rpc_user: Option<String>,
+    pub primary_rpc_password: Option<String>,
+    /// Lag beyond which the replica is not ready and catches up over RPC
+    pub max_lag_blocks: u64,
+    /// Blocks fetched over RPC per check while catching up
+    pub catch_up_batch: u64,
+    pub poll_interval_secs: u64,
+}
+
+impl Default for ReplicaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            primary: None,
+            primary_rpc: None,
+            primary_rpc_token: None,
+            primary_rpc_user: None,
+            primary_rpc_password: None,
+            max_lag_blocks: 2,
+            catch_up_batch: 50,
+            poll_interval_secs: 5,
+        }
+    }
+}
+
+/// Replica status as of the last check
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ReplicaStatus {
+    /// Unix time of the check
+    pub time: u64,
+    pub primary: String,
+    pub primary_connected: bool,
+    pub local_height: u64,
+    /// `None` without `primary_rpc` or when it did not answer
+    pub primary_height: Option<u64>,
+    pub lag_blocks: Option<u64>,
+    /// Blocks fetched over the primary's RPC since start
+    pub blocks_caught_up: u64,
+    /// Other peers disconnected since start
+    pub peers_dropped: u64,
+}
+
+impl ReplicaStatus {
+    /// Reason to report not ready, if any
+    pub fn not_ready_reason(&self, max_lag_blocks: u64) -> Option<String> {
+        match self.lag_blocks {
+            Some(lag) if lag > max_lag_blocks => {
+                Some(format!("replica {lag} blocks behind primary"))
+            }
+            _ if !self.primary_connected && self.lag_blocks.is_none() => {
+                Some("replica not connected to primary".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Heights to fetch over RPC: after a second check in a row above `max_lag_blocks`, the next
+/// `batch` blocks
+pub fn catch_up_range(
+    local: u64,
+    primary: u64,
+    previous_lag: Option<u64>,
+    config: &ReplicaConfig,
+) -> Option<RangeInclusive<u64>> {
+    let lag = primary.saturating_sub(local);
+    let lagging = |lag: u64| lag > config.max_lag_blocks;
+    if !lagging(lag) || !previous_lag.is_some_and(lagging) {
+        return None;
+    }
+    Some(local + 1..=primary.min(local + config.catch_up_batch.max(1)))
+}
+
+/// Whether a `getpeerinfo` address is the primary (IPv4-mapped IPv6 matches plain IPv4)
+pub fn is_primary(peer_addr: &str, primary: &str) -> bool {
+    if peer_addr == primary {
+        return true;
+    }
+    match (
+        peer_addr.parse::<SocketAddr>(),
+        primary.parse::<SocketAddr>(),
+    ) {
+        (Ok(a), Ok(b)) => a.ip().to_canonical() == b.ip().to_canonical() && a.port() == b.port(),
+        _ => false,
+    }
+}
+
+pub fn save(data_dir: &Path, status: &ReplicaStatus) -> std::io::Result<()> {
+    let tmp = data_dir.join(format!("{STATE_FILE}.tmp"));
+    std::fs::write(&tmp, serde_json::to_vec_pretty(status)?)?;
+    std::fs::rename(tmp, data_dir.join(STATE_FILE))
+}
+
+pub fn load(data_dir: &Path) -> Option<ReplicaStatus> {
+    let content = std::fs::read_to_string(data_dir.join(STATE_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catches_up_only_when_lag_persists() {
+        let config = ReplicaConfig {
+            max_lag_blocks: 2,
+            catch_up_batch: 50,
+            ..Default::default()
+        };
+        assert_eq!(catch_up_range(100, 102, Some(5), &config), None);
+        assert_eq!(catch_up_range(100, 110, None, &config), None);
+        assert_eq!(catch_up_range(100, 110, Some(1), &config), None);
+        assert_eq!(catch_up_range(100, 110, Some(8), &config), Some(101..=110));
+        assert_eq!(
+            catch_up_range(100, 1000, Some(900), &config),
+            Some(101..=150)
+        );
+    }
+
+    #[test]
+    fn readiness_and_state_file() {
+        let mut status = ReplicaStatus {
+            primary: "10.0.0.5:8333".into(),
+            primary_connected: true,
+            lag_blocks: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(status.not_ready_reason(2), None);
+        status.lag_blocks = Some(9);
+        assert_eq!(
+            status.not_ready_reason(2).as_deref(),
+            Some("replica 9 blocks behind primary")
+        );
+        status.lag_blocks = None;
+        status.primary_connected = false;
+        assert!(status.not_ready_reason(2).is_some());
+
+        assert!(is_primary("10.0.0.5:8333", "10.0.0.5:8333"));
+        assert!(is_primary("[::ffff:10.0.0.5]:8333", "10.0.0.5:8333"));
+        assert!(!is_primary("10.0.0.6:8333", "10.0.0.5:8333"));
+
+        let dir = std::env::temp_dir().join(format!("blvm-replica-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        save(&dir, &status).unwrap();
+        assert_eq!(load(&dir), Some(status));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
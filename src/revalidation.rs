@@ -0,0 +1,180 @@
+//! Background re-validation of stored blocks
+//!
+//! While the node runs, `blvm` periodically fetches a random historical block from it and
+//! re-checks the stored bytes against the header index: block hash, link to the previous
+//! block, merkle root, and proof of work. Problems are kept in `revalidation.json` in the data
+//! directory and shown as warnings by `blvm status`.
+
+use crate::decode::Block;
+use crate::difficulty::check_proof_of_work;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// Report file name in the data directory
+pub const REPORT_FILE: &str = "revalidation.json";
+
+/// Findings kept in the report (oldest dropped first)
+const MAX_FINDINGS: usize = 100;
+
+/// `[revalidation]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RevalidationConfig {
+    pub enabled: bool,
+    /// Blocks re-checked per hour (spread evenly)
+    pub blocks_per_hour: u32,
+}
+
+impl Default for RevalidationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            blocks_per_hour: 6,
+        }
+    }
+}
+
+impl RevalidationConfig {
+    /// Delay between samples
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(3600 / self.blocks_per_hour.max(1) as u64)
+    }
+}
+
+/// Problems with a stored block; empty when it checks out
+pub fn check_block(bytes: &[u8], hash: &[u8; 32], prev_hash: Option<&[u8; 32]>) -> Vec<String> {
+    let block = match Block::decode(bytes) {
+        Ok(block) => block,
+        Err(e) => return vec![format!("undecodable block data: {e}")],
+    };
+    let mut problems = Vec::new();
+    let header_hash = block.header.hash();
+    if &header_hash != hash {
+        problems.push("stored header does not hash to the indexed block hash".to_string());
+    }
+    if prev_hash.is_some_and(|prev| prev != &block.header.prev_blockhash) {
+        problems.push("previous block hash does not match the header index".to_string());
+    }
+    if block.computed_merkle_root() != block.header.merkle_root {
+        problems.push("merkle root does not match the stored transactions".to_string());
+    }
+    if !check_proof_of_work(&header_hash, block.header.bits) {
+        problems.push("header does not meet its proof-of-work target".to_string());
+    }
+    problems
+}
+
+/// One problem found by a re-check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Finding {
+    pub height: u64,
+    pub hash: String,
+    pub problem: String,
+    /// Unix time of detection
+    pub detected_at: u64,
+}
+
+/// Persistent summary of re-validation runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RevalidationReport {
+    pub blocks_checked: u64,
+    pub last_height: Option<u64>,
+    pub findings: Vec<Finding>,
+}
+
+impl RevalidationReport {
+    /// Load from the data directory (empty report if missing or unreadable)
+    pub fn load(data_dir: &Path) -> Self {
+        std::fs::read_to_string(data_dir.join(REPORT_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> anyhow::Result<()> {
+        let path = data_dir.join(REPORT_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Record the outcome of checking one block
+    pub fn record(&mut self, height: u64, hash: &str, problems: Vec<String>, now: u64) {
+        self.blocks_checked += 1;
+        self.last_height = Some(height);
+        self.findings
+            .extend(problems.into_iter().map(|problem| Finding {
+                height,
+                hash: hash.to_string(),
+                problem,
+                detected_at: now,
+            }));
+        let excess = self.findings.len().saturating_sub(MAX_FINDINGS);
+        self.findings.drain(..excess);
+    }
+
+    /// One warning line per finding, newest first
+    pub fn warnings(&self) -> Vec<String> {
+        self.findings
+            .iter()
+            .rev()
+            .map(|f| format!("block {} ({}): {}", f.height, f.hash, f.problem))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENESIS_BLOCK: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    fn genesis() -> (Vec<u8>, [u8; 32]) {
+        let bytes = hex::decode(GENESIS_BLOCK).unwrap();
+        let hash = crate::hash::sha256d(&bytes[..80]);
+        (bytes, hash)
+    }
+
+    #[test]
+    fn intact_block_passes() {
+        let (bytes, hash) = genesis();
+        assert!(check_block(&bytes, &hash, Some(&[0; 32])).is_empty());
+    }
+
+    #[test]
+    fn detects_corruption() {
+        let (mut bytes, hash) = genesis();
+        // Flip a byte inside the coinbase output value
+        let len = bytes.len();
+        bytes[len - 80] ^= 1;
+        let problems = check_block(&bytes, &hash, None);
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].contains("merkle root"));
+
+        let problems = check_block(&bytes[..100], &hash, None);
+        assert!(problems[0].contains("undecodable"));
+
+        let (bytes, _) = genesis();
+        let problems = check_block(&bytes, &[1; 32], Some(&[2; 32]));
+        assert_eq!(problems.len(), 2, "{problems:?}");
+    }
+
+    #[test]
+    fn report_keeps_recent_findings() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut report = RevalidationReport::load(dir.path());
+        assert_eq!(report, RevalidationReport::default());
+        for height in 0..(MAX_FINDINGS as u64 + 5) {
+            report.record(height, "ab", vec!["bad".to_string()], 1);
+        }
+        report.record(500, "cd", Vec::new(), 2);
+        report.save(dir.path()).unwrap();
+        let loaded = RevalidationReport::load(dir.path());
+        assert_eq!(loaded.blocks_checked, MAX_FINDINGS as u64 + 6);
+        assert_eq!(loaded.findings.len(), MAX_FINDINGS);
+        assert!(loaded.warnings()[0].starts_with("block 104 (ab)"));
+    }
+}
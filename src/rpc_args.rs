@@ -0,0 +1,61 @@
+//! Parameters for `blvm rpc` given bitcoin-cli style
+//!
+//! With `-named`, parameters are `key=value` words sent as one JSON object. A value is used as
+//! JSON when it parses as JSON (`count=3`, `verbose=true`, `outputs=[...]`) and as a string
+//! otherwise (`address=bc1q...`); quote it (`label="123"`) to force a string.
+
+use serde_json::{Map, Value};
+
+/// JSON object from `key=value` words
+pub fn named_params<S: AsRef<str>>(words: &[S]) -> Result<Value, String> {
+    let mut params = Map::new();
+    for word in words {
+        let word = word.as_ref();
+        let Some((key, value)) = word.split_once('=') else {
+            return Err(format!("Expected key=value, got \"{word}\""));
+        };
+        if key.is_empty() {
+            return Err(format!("Missing parameter name in \"{word}\""));
+        }
+        let value = serde_json::from_str(value).unwrap_or_else(|_| Value::from(value));
+        if params.insert(key.to_string(), value).is_some() {
+            return Err(format!("Parameter {key} given more than once"));
+        }
+    }
+    Ok(Value::Object(params))
+}
+
+/// Non-empty lines of stdin input, for `key=value` words read with `-`
+pub fn stdin_words(input: &str) -> Vec<&str> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn named_values_as_json_or_string() {
+        let params = named_params(&[
+            "height=100",
+            "verbose=true",
+            "address=bc1qexample",
+            "label=\"123\"",
+            "outputs=[{\"a\":1}]",
+        ])
+        .unwrap();
+        assert_eq!(
+            params,
+            json!({"height": 100, "verbose": true, "address": "bc1qexample",
+                "label": "123", "outputs": [{"a": 1}]})
+        );
+        assert!(named_params(&["height"]).is_err());
+        assert!(named_params(&["a=1", "a=2"]).is_err());
+        assert_eq!(stdin_words("a=1\n\n  b=x \n"), vec!["a=1", "b=x"]);
+    }
+}
@@ -0,0 +1,134 @@
+//! JSON-RPC batches for `blvm rpc --batch`
+//!
+//! Calls are given as `METHOD PARAMS` pairs or as one JSON array of request objects
+//! (`[{"method": "getblockhash", "params": [0]}, ...]`) and go to the node as a single batch
+//! request. Servers may answer a batch in any order, so replies are matched back to their calls
+//! by `id` before printing.
+
+use serde_json::{Value, json};
+
+/// One call of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchCall {
+    pub method: String,
+    pub params: Value,
+}
+
+/// Calls from the command line: one JSON array of request objects, or `METHOD PARAMS` pairs
+pub fn parse_args(args: &[String]) -> Result<Vec<BatchCall>, String> {
+    if let [single] = args
+        && single.trim_start().starts_with('[')
+    {
+        let requests: Vec<Value> =
+            serde_json::from_str(single).map_err(|e| format!("Invalid JSON batch: {e}"))?;
+        return requests
+            .iter()
+            .enumerate()
+            .map(|(i, request)| {
+                let method = request
+                    .get("method")
+                    .and_then(|m| m.as_str())
+                    .ok_or_else(|| format!("Batch entry {i} has no \"method\""))?;
+                let params = request.get("params").cloned().unwrap_or_else(|| json!([]));
+                Ok(BatchCall {
+                    method: method.to_string(),
+                    params,
+                })
+            })
+            .collect();
+    }
+    if !args.len().is_multiple_of(2) {
+        return Err(format!(
+            "Expected METHOD PARAMS pairs, got {} arguments (use [] for no parameters)",
+            args.len()
+        ));
+    }
+    args.chunks(2)
+        .map(|pair| {
+            let params = serde_json::from_str(&pair[1])
+                .map_err(|e| format!("Invalid JSON parameters for {}: {e}", pair[0]))?;
+            Ok(BatchCall {
+                method: pair[0].clone(),
+                params,
+            })
+        })
+        .collect()
+}
+
+/// Batch request body; each call's `id` is its position
+pub fn request_body(calls: &[BatchCall]) -> Value {
+    Value::Array(
+        calls
+            .iter()
+            .enumerate()
+            .map(|(id, call)| {
+                json!({"jsonrpc": "2.0", "method": call.method, "params": call.params, "id": id})
+            })
+            .collect(),
+    )
+}
+
+/// Replies in call order: `Ok(result)` or `Err(error)`; a call the server did not answer gets
+/// an error
+pub fn order_responses(
+    calls: usize,
+    response: &Value,
+) -> Result<Vec<Result<Value, Value>>, String> {
+    let Some(replies) = response.as_array() else {
+        // A server without batch support answers with a single error object
+        let error = response.get("error").cloned().unwrap_or(Value::Null);
+        return Err(format!("Server did not accept the batch: {error}"));
+    };
+    let mut ordered: Vec<Option<Result<Value, Value>>> = vec![None; calls];
+    for reply in replies {
+        let Some(slot) = reply
+            .get("id")
+            .and_then(|id| id.as_u64())
+            .and_then(|id| ordered.get_mut(id as usize))
+        else {
+            continue;
+        };
+        *slot = Some(match reply.get("error").filter(|e| !e.is_null()) {
+            Some(error) => Err(error.clone()),
+            None => Ok(reply.get("result").cloned().unwrap_or(Value::Null)),
+        });
+    }
+    Ok(ordered
+        .into_iter()
+        .map(|reply| reply.unwrap_or_else(|| Err(json!("no reply for this call"))))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_objects_and_reordered_replies() {
+        let args: Vec<String> = ["getblockcount", "[]", "getblockhash", "[0]"]
+            .map(String::from)
+            .into();
+        let calls = parse_args(&args).unwrap();
+        assert_eq!(calls[1].method, "getblockhash");
+        assert_eq!(calls[1].params, json!([0]));
+        assert!(parse_args(&args[..3]).is_err());
+
+        let objects = parse_args(&[
+            r#"[{"method": "getblockcount"}, {"method": "getblockhash", "params": [0]}]"#
+                .to_string(),
+        ])
+        .unwrap();
+        assert_eq!(objects, calls);
+        assert_eq!(request_body(&calls)[1]["id"], 1);
+
+        let response = json!([
+            {"id": 1, "result": null, "error": {"code": -8, "message": "Block height out of range"}},
+            {"id": 0, "result": 120, "error": null},
+        ]);
+        let ordered = order_responses(2, &response).unwrap();
+        assert_eq!(ordered[0], Ok(json!(120)));
+        assert_eq!(ordered[1].as_ref().unwrap_err()["code"], -8);
+        assert!(order_responses(3, &response).unwrap()[2].is_err());
+        assert!(order_responses(2, &json!({"error": {"code": -32700}})).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! Response cache for expensive read-only RPCs (`[rpc_front.cache]`, `getrpccacheinfo`)
+//!
+//! Results are keyed by method and params. Results that only depend on a block
+//! (`getblockstats`, `getblock` with verbosity 0) stay valid until a reorg; results that also
+//! depend on the tip (`gettxoutsetinfo`, verbose `getblock` with its `confirmations`) are dropped
+//! whenever the tip moves.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// `[rpc_front.cache]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RpcCacheConfig {
+    pub enabled: bool,
+    /// Oldest entries are evicted beyond this
+    pub max_entries: usize,
+    /// Methods to cache (any of `getblock`, `getblockstats`, `gettxoutsetinfo`)
+    pub methods: Vec<String>,
+}
+
+impl Default for RpcCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 1024,
+            methods: ["getblock", "getblockstats", "gettxoutsetinfo"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+/// How long a cached result stays valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Until a reorg
+    Block,
+    /// Until the tip changes
+    Tip,
+}
+
+/// Scope of a cacheable call, `None` for anything else
+pub fn scope(method: &str, params: &Value) -> Option<Scope> {
+    match method {
+        "getblockstats" => Some(Scope::Block),
+        "gettxoutsetinfo" => Some(Scope::Tip),
+        "getblock" => match params.get(1).or_else(|| params.get("verbosity")) {
+            Some(Value::Number(n)) if n.as_u64() == Some(0) => Some(Scope::Block),
+            Some(Value::Bool(false)) => Some(Scope::Block),
+            _ => Some(Scope::Tip),
+        },
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct MethodStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+struct Entry {
+    scope: Scope,
+    response: Value,
+}
+
+pub struct RpcCache {
+    config: RpcCacheConfig,
+    entries: HashMap<String, Entry>,
+    order: VecDeque<String>,
+    tip: Option<String>,
+    generation: u64,
+    methods: BTreeMap<String, MethodStats>,
+    evictions: u64,
+    invalidations: u64,
+}
+
+impl RpcCache {
+    pub fn new(config: RpcCacheConfig) -> Self {
+        Self {
+            config,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            tip: None,
+            generation: 0,
+            methods: BTreeMap::new(),
+            evictions: 0,
+            invalidations: 0,
+        }
+    }
+
+    fn cacheable(&self, method: &str, params: &Value) -> Option<Scope> {
+        if !self.config.enabled || !self.config.methods.iter().any(|m| m == method) {
+            return None;
+        }
+        scope(method, params)
+    }
+
+    fn key(method: &str, params: &Value) -> String {
+        format!("{method}:{params}")
+    }
+
+    /// Changes whenever entries are invalidated; pass it back to `insert` so a result computed
+    /// under an older tip is not stored
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Cached response for a cacheable call (hits and misses are counted per method)
+    pub fn get(&mut self, method: &str, params: &Value) -> Option<Value> {
+        self.cacheable(method, params)?;
+        let response = self
+            .entries
+            .get(&Self::key(method, params))
+            .map(|e| e.response.clone());
+        let stats = self.methods.entry(method.to_string()).or_default();
+        match response {
+            Some(_) => stats.hits += 1,
+            None => stats.misses += 1,
+        }
+        response
+    }
+
+    /// Store a successful response obtained while `generation` was current
+    pub fn insert(&mut self, method: &str, params: &Value, response: Value, generation: u64) {
+        let Some(scope) = self.cacheable(method, params) else {
+            return;
+        };
+        if generation != self.generation || !response.get("error").is_none_or(Value::is_null) {
+            return;
+        }
+        let key = Self::key(method, params);
+        if self
+            .entries
+            .insert(key.clone(), Entry { scope, response })
+            .is_none()
+        {
+            self.order.push_back(key);
+        }
+        while self.entries.len() > self.config.max_entries {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if self.entries.remove(&oldest).is_some() {
+                self.evictions += 1;
+            }
+        }
+    }
+
+    /// New best block; `extends` when its parent is the previous tip (otherwise a reorg)
+    pub fn on_tip(&mut self, tip: &str, extends: bool) {
+        let previous = self.tip.replace(tip.to_string());
+        if previous.as_deref() == Some(tip) || previous.is_none() {
+            return;
+        }
+        let before = self.entries.len();
+        if extends {
+            self.entries.retain(|_, e| e.scope == Scope::Block);
+        } else {
+            self.entries.clear();
+        }
+        self.order.retain(|key| self.entries.contains_key(key));
+        self.invalidations += (before - self.entries.len()) as u64;
+        self.generation += 1;
+    }
+
+    pub fn tip(&self) -> Option<&str> {
+        self.tip.as_deref()
+    }
+
+    /// `getrpccacheinfo` result
+    pub fn info(&self) -> Value {
+        let (hits, misses) = self
+            .methods
+            .values()
+            .fold((0, 0), |(h, m), s| (h + s.hits, m + s.misses));
+        json!({
+            "enabled": self.config.enabled,
+            "entries": self.entries.len(),
+            "max_entries": self.config.max_entries,
+            "hits": hits,
+            "misses": misses,
+            "hit_rate": if hits + misses > 0 { hits as f64 / (hits + misses) as f64 } else { 0.0 },
+            "evictions": self.evictions,
+            "invalidations": self.invalidations,
+            "tip": self.tip,
+            "methods": self.methods,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(result: Value) -> Value {
+        json!({"result": result, "error": null, "id": 1})
+    }
+
+    #[test]
+    fn hits_misses_and_eviction() {
+        let mut cache = RpcCache::new(RpcCacheConfig {
+            max_entries: 2,
+            ..Default::default()
+        });
+        let g = cache.generation();
+        assert_eq!(cache.get("getblockstats", &json!([1])), None);
+        cache.insert("getblockstats", &json!([1]), ok(json!({"height": 1})), g);
+        cache.insert("getblockstats", &json!([2]), ok(json!({"height": 2})), g);
+        assert!(cache.get("getblockstats", &json!([1])).is_some());
+        // Errors and uncached methods are never stored
+        cache.insert(
+            "getblockstats",
+            &json!([9]),
+            json!({"error": {"code": -8}}),
+            g,
+        );
+        cache.insert("getblockcount", &json!([]), ok(json!(5)), g);
+        assert_eq!(cache.get("getblockcount", &json!([])), None);
+
+        cache.insert("getblockstats", &json!([3]), ok(json!({"height": 3})), g);
+        assert_eq!(cache.get("getblockstats", &json!([1])), None);
+        let info = cache.info();
+        assert_eq!(info["entries"], 2);
+        assert_eq!(info["evictions"], 1);
+        assert_eq!(info["methods"]["getblockstats"]["hits"], 1);
+        assert_eq!(info["methods"]["getblockstats"]["misses"], 2);
+    }
+
+    #[test]
+    fn tip_changes_invalidate_by_scope() {
+        let mut cache = RpcCache::new(RpcCacheConfig::default());
+        cache.on_tip("a", false);
+        let g = cache.generation();
+        cache.insert("getblock", &json!(["h", 0]), ok(json!("00")), g);
+        cache.insert("getblock", &json!(["h", 1]), ok(json!({})), g);
+        cache.insert("gettxoutsetinfo", &json!([]), ok(json!({})), g);
+
+        cache.on_tip("b", true);
+        assert!(cache.get("getblock", &json!(["h", 0])).is_some());
+        assert_eq!(cache.get("getblock", &json!(["h", 1])), None);
+        assert_eq!(cache.get("gettxoutsetinfo", &json!([])), None);
+        // A result computed before the tip moved is discarded
+        cache.insert("gettxoutsetinfo", &json!([]), ok(json!({})), g);
+        assert_eq!(cache.get("gettxoutsetinfo", &json!([])), None);
+
+        cache.on_tip("c2", false);
+        assert_eq!(cache.get("getblock", &json!(["h", 0])), None);
+        assert_eq!(cache.info()["invalidations"], 3);
+    }
+}
@@ -0,0 +1,41 @@
+//! RPC cookie authentication (`<data_dir>/.cookie`, as Bitcoin Core)
+//!
+//! A node that generates ephemeral credentials writes `__cookie__:<random hex>` to this file on
+//! startup and removes it on shutdown; clients on the same host read it for HTTP basic auth.
+
+use std::io;
+use std::path::Path;
+
+/// Cookie file name in the data directory
+pub const COOKIE_FILE: &str = ".cookie";
+
+/// `(user, password)` from a cookie file
+pub fn read(path: &Path) -> io::Result<(String, String)> {
+    let content = std::fs::read_to_string(path)?;
+    let (user, password) = content.trim_end().split_once(':').ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a user:password cookie", path.display()),
+        )
+    })?;
+    Ok((user.to_string(), password.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_core_style_cookie() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(COOKIE_FILE);
+        std::fs::write(&path, "__cookie__:ab12:cd\n").unwrap();
+        assert_eq!(
+            read(&path).unwrap(),
+            ("__cookie__".to_string(), "ab12:cd".to_string())
+        );
+        std::fs::write(&path, "garbage").unwrap();
+        assert_eq!(read(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(read(&dir.path().join("missing")).is_err());
+    }
+}
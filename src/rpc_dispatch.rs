@@ -0,0 +1,188 @@
+//! Per-method concurrency limits for the RPC front (`[rpc_front.limits]`, `getrpcqueueinfo`)
+//!
+//! Each connection to the front runs as its own task on the multi-threaded runtime, so a slow
+//! call never holds up unrelated ones. Methods with a limit get a lane: at most `limit` calls run
+//! at once, further calls wait in the lane and fail once they have waited `queue_timeout_secs`.
+//! Only calls through the front are dispatched here; the node's own RPC port is not limited.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// `[rpc_front.limits]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RpcLimitsConfig {
+    /// How long a call may wait for its lane before failing
+    pub queue_timeout_secs: u64,
+    /// Limit for methods not listed in `methods` (`None` = unlimited)
+    pub default_limit: Option<usize>,
+    /// Concurrent calls allowed per method
+    pub methods: BTreeMap<String, usize>,
+}
+
+impl Default for RpcLimitsConfig {
+    fn default() -> Self {
+        Self {
+            queue_timeout_secs: 30,
+            default_limit: None,
+            methods: [
+                ("verifychain", 1),
+                ("rescanblockchain", 1),
+                ("scantxoutset", 1),
+                ("gettxoutsetinfo", 2),
+            ]
+            .into_iter()
+            .map(|(method, limit)| (method.to_string(), limit))
+            .collect(),
+        }
+    }
+}
+
+struct Lane {
+    limit: usize,
+    semaphore: Arc<Semaphore>,
+    queued: AtomicU64,
+    active: AtomicU64,
+    completed: AtomicU64,
+    timed_out: AtomicU64,
+}
+
+/// Held while a limited call runs; frees its slot when dropped
+pub struct Permit {
+    lane: Arc<Lane>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.lane.active.fetch_sub(1, Ordering::Relaxed);
+        self.lane.completed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub struct Dispatcher {
+    config: RpcLimitsConfig,
+    lanes: Mutex<HashMap<String, Arc<Lane>>>,
+}
+
+impl Dispatcher {
+    pub fn new(config: RpcLimitsConfig) -> Self {
+        Self {
+            config,
+            lanes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lane(&self, method: &str) -> Option<Arc<Lane>> {
+        let limit = self
+            .config
+            .methods
+            .get(method)
+            .copied()
+            .or(self.config.default_limit)?
+            .max(1);
+        let mut lanes = self.lanes.lock().unwrap_or_else(|e| e.into_inner());
+        let lane = lanes.entry(method.to_string()).or_insert_with(|| {
+            Arc::new(Lane {
+                limit,
+                semaphore: Arc::new(Semaphore::new(limit)),
+                queued: AtomicU64::new(0),
+                active: AtomicU64::new(0),
+                completed: AtomicU64::new(0),
+                timed_out: AtomicU64::new(0),
+            })
+        });
+        Some(lane.clone())
+    }
+
+    /// Wait for a slot in `method`'s lane; `Ok(None)` for unlimited methods, `Err` with the
+    /// message for the caller after the queue timeout
+    pub async fn acquire(&self, method: &str) -> Result<Option<Permit>, String> {
+        let Some(lane) = self.lane(method) else {
+            return Ok(None);
+        };
+        lane.queued.fetch_add(1, Ordering::Relaxed);
+        let wait = Duration::from_secs(self.config.queue_timeout_secs);
+        let acquired = tokio::time::timeout(wait, lane.semaphore.clone().acquire_owned()).await;
+        lane.queued.fetch_sub(1, Ordering::Relaxed);
+        match acquired {
+            Ok(Ok(permit)) => {
+                lane.active.fetch_add(1, Ordering::Relaxed);
+                Ok(Some(Permit {
+                    lane,
+                    _permit: permit,
+                }))
+            }
+            _ => {
+                lane.timed_out.fetch_add(1, Ordering::Relaxed);
+                Err(format!(
+                    "{method} queue timeout: {} call(s) already running (limit {}), waited {}s",
+                    lane.active.load(Ordering::Relaxed),
+                    lane.limit,
+                    self.config.queue_timeout_secs
+                ))
+            }
+        }
+    }
+
+    /// `getrpcqueueinfo` result
+    pub fn info(&self) -> Value {
+        let lanes = self.lanes.lock().unwrap_or_else(|e| e.into_inner());
+        let mut methods = BTreeMap::new();
+        for (method, limit) in &self.config.methods {
+            methods.insert(
+                method.clone(),
+                json!({ "limit": limit, "active": 0, "queued": 0, "completed": 0, "timed_out": 0 }),
+            );
+        }
+        for (method, lane) in lanes.iter() {
+            methods.insert(
+                method.clone(),
+                json!({
+                    "limit": lane.limit,
+                    "active": lane.active.load(Ordering::Relaxed),
+                    "queued": lane.queued.load(Ordering::Relaxed),
+                    "completed": lane.completed.load(Ordering::Relaxed),
+                    "timed_out": lane.timed_out.load(Ordering::Relaxed),
+                }),
+            );
+        }
+        json!({
+            "queue_timeout_secs": self.config.queue_timeout_secs,
+            "default_limit": self.config.default_limit,
+            "methods": methods,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lanes_limit_and_time_out() {
+        let dispatcher = Dispatcher::new(RpcLimitsConfig {
+            queue_timeout_secs: 0,
+            ..Default::default()
+        });
+        assert!(dispatcher.acquire("getblockcount").await.unwrap().is_none());
+
+        let running = dispatcher.acquire("verifychain").await.unwrap();
+        assert!(running.is_some());
+        let err = dispatcher.acquire("verifychain").await.err().unwrap();
+        assert!(err.contains("verifychain queue timeout"));
+        let info = dispatcher.info();
+        assert_eq!(info["methods"]["verifychain"]["active"], 1);
+        assert_eq!(info["methods"]["verifychain"]["timed_out"], 1);
+        assert_eq!(info["methods"]["scantxoutset"]["limit"], 1);
+
+        drop(running);
+        assert!(dispatcher.acquire("verifychain").await.unwrap().is_some());
+        assert_eq!(dispatcher.info()["methods"]["verifychain"]["completed"], 2);
+    }
+}
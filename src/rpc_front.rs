@@ -0,0 +1,331 @@
+//! JSON-RPC front listener (`[rpc_front]`)
+//!
+//! Clients point at `listen` instead of the node's RPC port. Requests are passed to the node
+//! unchanged (including their `Authorization` header), except that repeated expensive reads are
+//! answered from `rpc_cache`, calls to slow methods wait for a slot in `rpc_dispatch`, and
+//! `getrpccacheinfo` / `getrpcqueueinfo` / `getrpcstats` / `dumptasks` / `getnodestate` /
+//! `getpeereventlog` are answered here.
+//! Request heads are size-capped, and a request's credentials are checked with the node before
+//! its body is read; the body buffer then grows with the bytes that actually arrive.
+
+use crate::rpc_cache::RpcCacheConfig;
+use crate::rpc_dispatch::RpcLimitsConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt};
+
+/// Largest request body accepted
+pub const MAX_BODY: usize = 16 * 1024 * 1024;
+/// Longest request or header line accepted, terminator included
+pub const MAX_HEADER_LINE: usize = 8 * 1024;
+/// Most header lines accepted after the request line
+pub const MAX_HEADERS: usize = 100;
+/// Most distinct Authorization headers remembered as accepted by the node
+pub const MAX_ACCEPTED_AUTH: usize = 64;
+/// Connections served at once; further ones wait in the listen backlog
+pub const MAX_CONNECTIONS: usize = 256;
+/// Body bytes read (and allocated) at a time
+pub const BODY_CHUNK: usize = 64 * 1024;
+
+/// Methods answered by the front itself
+pub const FRONT_METHODS: &[&str] = &[
+    "getrpcqueueinfo",
+    "getmempoolhistogram",
+    "getrpccacheinfo",
+    "getrpcstats",
+    "verifychain",
+    "setmocktime",
+    "bumpmocktime",
+    "generateblock",
+    "getfeehistory",
+    "dumptasks",
+    "getnodestate",
+    "getpeereventlog",
+];
+
+/// `[rpc_front]` config section
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RpcFrontConfig {
+    /// Front listener, e.g. `127.0.0.1:8342`; no listener when unset
+    pub listen: Option<SocketAddr>,
+    pub cache: RpcCacheConfig,
+    pub limits: RpcLimitsConfig,
+}
+
+/// The parts of an HTTP request head the front needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestHead {
+    pub method: String,
+    pub content_length: usize,
+    pub authorization: Option<String>,
+}
+
+/// Parse a request line plus header lines (without the blank line)
+pub fn parse_head(lines: &[String]) -> Result<RequestHead, String> {
+    let method = lines
+        .first()
+        .and_then(|line| line.split_whitespace().next())
+        .ok_or("malformed request line")?
+        .to_string();
+    let mut head = RequestHead {
+        method,
+        content_length: 0,
+        authorization: None,
+    };
+    for line in &lines[1..] {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            head.content_length = value
+                .parse()
+                .map_err(|_| format!("bad Content-Length '{value}'"))?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            head.authorization = Some(value.to_string());
+        }
+    }
+    if head.content_length > MAX_BODY {
+        return Err(format!(
+            "body of {} bytes is too large",
+            head.content_length
+        ));
+    }
+    Ok(head)
+}
+
+/// Read a request head up to the blank line, refusing oversized ones with 431 and malformed
+/// ones with 400 (as HTTP status and message)
+pub async fn read_head<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> Result<RequestHead, (u16, String)> {
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .take(MAX_HEADER_LINE as u64)
+            .read_line(&mut line)
+            .await
+            .map_err(|e| (400, e.to_string()))?;
+        if read == 0 {
+            return Err((400, "connection closed".to_string()));
+        }
+        if !line.ends_with('\n') && line.len() >= MAX_HEADER_LINE {
+            return Err((
+                431,
+                format!("header line longer than {MAX_HEADER_LINE} bytes"),
+            ));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if lines.len() > MAX_HEADERS {
+            return Err((431, format!("more than {MAX_HEADERS} header lines")));
+        }
+        lines.push(line.to_string());
+    }
+    parse_head(&lines).map_err(|e| (400, e))
+}
+
+/// Read a `len`-byte body (already checked against `MAX_BODY`) in `BODY_CHUNK` steps, so memory
+/// follows what the client sends rather than what its Content-Length claims
+pub async fn read_body<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    len: usize,
+) -> std::io::Result<Vec<u8>> {
+    let mut body = Vec::with_capacity(len.min(BODY_CHUNK));
+    while body.len() < len {
+        let step = (len - body.len()).min(BODY_CHUNK);
+        let read = reader.take(step as u64).read_to_end(&mut body).await?;
+        if read == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+    }
+    Ok(body)
+}
+
+/// Authorization headers the node has accepted; past `MAX_ACCEPTED_AUTH` the least
+/// recently used one is dropped (and checked with the node again when it comes back)
+#[derive(Debug, Default)]
+pub struct AcceptedAuth {
+    /// Most recently used last
+    recent: VecDeque<String>,
+}
+
+impl AcceptedAuth {
+    /// Whether `auth` is remembered; a hit becomes the most recently used
+    pub fn check(&mut self, auth: &str) -> bool {
+        let Some(pos) = self.recent.iter().position(|known| known == auth) else {
+            return false;
+        };
+        if let Some(hit) = self.recent.remove(pos) {
+            self.recent.push_back(hit);
+        }
+        true
+    }
+
+    /// Remember `auth` once a call made with it succeeded
+    pub fn insert(&mut self, auth: &str) {
+        if self.check(auth) {
+            return;
+        }
+        if self.recent.len() >= MAX_ACCEPTED_AUTH {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(auth.to_string());
+    }
+}
+
+/// Method and params of a single (non-batch) JSON-RPC request
+pub fn single_call(request: &Value) -> Option<(&str, Value)> {
+    let method = request.get("method")?.as_str()?;
+    let params = request
+        .get("params")
+        .cloned()
+        .unwrap_or(Value::Array(vec![]));
+    Some((method, params))
+}
+
+/// A cached response re-addressed to the request's `id`
+pub fn with_id(mut response: Value, request: &Value) -> Value {
+    if let Some(obj) = response.as_object_mut() {
+        obj.insert(
+            "id".to_string(),
+            request.get("id").cloned().unwrap_or(Value::Null),
+        );
+    }
+    response
+}
+
+/// HTTP/1.1 response with a JSON body; the connection is closed afterwards
+pub fn response(status: u16, body: &str) -> String {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "",
+    };
+    format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Whether a reply built by [`response`] (or relayed from the node) reports a failure: a
+/// non-200 status or a non-null JSON-RPC `error`
+pub fn is_error_reply(reply: &str) -> bool {
+    let status = reply.split(' ').nth(1).and_then(|s| s.parse().ok());
+    let body = reply.split_once("\r\n\r\n").map_or("", |(_, body)| body);
+    !is_success(status.unwrap_or(0), body)
+}
+
+/// Whether a node reply (status and body) answered the call: status 200 with a JSON body and
+/// no JSON-RPC `error`
+pub fn is_success(status: u16, body: &str) -> bool {
+    status == 200
+        && serde_json::from_str::<Value>(body)
+            .is_ok_and(|v| v.get("error").is_none_or(Value::is_null))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_head_and_calls() {
+        let lines = [
+            "POST / HTTP/1.1",
+            "Host: 127.0.0.1",
+            "content-length: 42",
+            "Authorization: Basic YnRjOnB3",
+        ]
+        .map(String::from);
+        assert_eq!(
+            parse_head(&lines),
+            Ok(RequestHead {
+                method: "POST".into(),
+                content_length: 42,
+                authorization: Some("Basic YnRjOnB3".into()),
+            })
+        );
+        let too_big = [
+            "POST / HTTP/1.1".into(),
+            format!("Content-Length: {}", MAX_BODY + 1),
+        ];
+        assert!(parse_head(&too_big).is_err());
+
+        let request = json!({"jsonrpc": "2.0", "id": 7, "method": "getblockstats", "params": [5]});
+        assert_eq!(single_call(&request), Some(("getblockstats", json!([5]))));
+        assert_eq!(single_call(&json!([request])), None);
+        let cached = json!({"result": {}, "error": null, "id": 1});
+        assert_eq!(with_id(cached, &request)["id"], 7);
+
+        assert!(!is_error_reply(&response(
+            200,
+            r#"{"result":1,"error":null}"#
+        )));
+        assert!(is_error_reply(&response(
+            200,
+            r#"{"result":null,"error":{"code":-5}}"#
+        )));
+        assert!(is_error_reply(&response(503, "{}")));
+        assert!(!is_success(200, "<html>"));
+    }
+
+    #[test]
+    fn accepted_auth_evicts_least_recently_used() {
+        let mut accepted = AcceptedAuth::default();
+        for i in 0..MAX_ACCEPTED_AUTH {
+            accepted.insert(&format!("Basic {i}"));
+        }
+        assert!(accepted.check("Basic 0"));
+        accepted.insert("Bearer new");
+        assert!(accepted.check("Basic 0"));
+        assert!(!accepted.check("Basic 1"));
+        assert!(accepted.check("Bearer new"));
+        assert!(accepted.check(&format!("Basic {}", MAX_ACCEPTED_AUTH - 1)));
+    }
+    #[tokio::test]
+    async fn caps_request_heads() {
+        let mut ok: &[u8] = b"POST / HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}";
+        assert_eq!(read_head(&mut ok).await.unwrap().content_length, 2);
+        assert_eq!(ok, b"{}");
+
+        let long = format!(
+            "POST / HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_HEADER_LINE)
+        );
+        assert_eq!(read_head(&mut long.as_bytes()).await.unwrap_err().0, 431);
+        let many = format!(
+            "POST / HTTP/1.1\r\n{}\r\n",
+            "X: a\r\n".repeat(MAX_HEADERS + 2)
+        );
+        assert_eq!(read_head(&mut many.as_bytes()).await.unwrap_err().0, 431);
+        let mut truncated: &[u8] = b"POST / HTTP/1.1\r\n";
+        assert_eq!(read_head(&mut truncated).await.unwrap_err().0, 400);
+        let mut bad: &[u8] = b"POST / HTTP/1.1\r\nContent-Length: x\r\n\r\n";
+        assert_eq!(read_head(&mut bad).await.unwrap_err().0, 400);
+    }
+
+    #[tokio::test]
+    async fn reads_bodies_in_chunks() {
+        let data = vec![7u8; BODY_CHUNK * 2 + 5];
+        let mut reader: &[u8] = &data;
+        assert_eq!(read_body(&mut reader, data.len()).await.unwrap(), data);
+        // A client that claims more than it sends runs out instead of reserving the claim
+        let mut short: &[u8] = b"{}";
+        let err = read_body(&mut short, MAX_BODY).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(read_body(&mut &b""[..], 0).await.unwrap(), b"");
+    }
+}
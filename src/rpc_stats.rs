@@ -0,0 +1,224 @@
+//! Per-method RPC statistics (`getrpcstats`, `blvm rpc-stats`)
+//!
+//! Recorded by the `[rpc_front]` listener for every single (non-batch) call it answers or
+//! forwards, and served from there as `getrpcstats`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Latency bucket bounds (ms) used by the front
+pub const LATENCY_BOUNDS_MS: &[f64] = &[
+    1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 10000.0,
+];
+
+/// Fixed-bucket histogram: `counts[i]` observations `<= bounds[i]`, the last count above all
+/// bounds
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Histogram {
+    pub bounds: Vec<f64>,
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    pub fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        let i = self.bounds.iter().take_while(|&&b| value > b).count();
+        if let Some(count) = self.counts.get_mut(i) {
+            *count += 1;
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Estimated quantile (`q` in 0..=1), interpolating linearly inside the bucket.
+    /// Observations above the last bound are reported as the last bound.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let total = self.total();
+        if total == 0 || self.bounds.is_empty() {
+            return None;
+        }
+        let rank = q.clamp(0.0, 1.0) * total as f64;
+        let mut seen = 0u64;
+        for (i, &count) in self.counts.iter().enumerate() {
+            if count > 0 && (seen + count) as f64 >= rank {
+                let Some(&upper) = self.bounds.get(i) else {
+                    break;
+                };
+                let lower = if i == 0 { 0.0 } else { self.bounds[i - 1] };
+                let fraction = (rank - seen as f64) / count as f64;
+                return Some(lower + (upper - lower) * fraction.clamp(0.0, 1.0));
+            }
+            seen += count;
+        }
+        self.bounds.last().copied()
+    }
+}
+
+/// Counters for one RPC method
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MethodStats {
+    pub calls: u64,
+    pub errors: u64,
+    pub latency_ms: Histogram,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+}
+
+impl MethodStats {
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.calls as f64
+        }
+    }
+}
+
+/// `getrpcstats` result
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RpcStats {
+    /// Unix time the counters were last reset (node start)
+    pub since: u64,
+    pub methods: BTreeMap<String, MethodStats>,
+}
+
+/// Sort key for `blvm rpc-stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    Calls,
+    Errors,
+    Latency,
+}
+
+impl RpcStats {
+    /// Empty counters starting at `since` (unix time)
+    pub fn new(since: u64) -> Self {
+        Self {
+            since,
+            methods: BTreeMap::new(),
+        }
+    }
+
+    /// Count one call of `method`
+    pub fn record(
+        &mut self,
+        method: &str,
+        latency_ms: f64,
+        request_bytes: u64,
+        response_bytes: u64,
+        error: bool,
+    ) {
+        let stats = self
+            .methods
+            .entry(method.to_string())
+            .or_insert_with(|| MethodStats {
+                latency_ms: Histogram::new(LATENCY_BOUNDS_MS),
+                ..Default::default()
+            });
+        stats.calls += 1;
+        stats.errors += u64::from(error);
+        stats.latency_ms.observe(latency_ms);
+        stats.request_bytes += request_bytes;
+        stats.response_bytes += response_bytes;
+    }
+
+    /// Methods ordered by `sort`, highest first (ties by name)
+    pub fn sorted(&self, sort: SortBy) -> Vec<(&str, &MethodStats)> {
+        let mut rows: Vec<(&str, &MethodStats)> = self
+            .methods
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+            .collect();
+        let key = |stats: &MethodStats| match sort {
+            SortBy::Calls => stats.calls as f64,
+            SortBy::Errors => stats.error_rate(),
+            SortBy::Latency => stats.latency_ms.quantile(0.95).unwrap_or(0.0),
+        };
+        rows.sort_by(|a, b| key(b.1).total_cmp(&key(a.1)).then(a.0.cmp(b.0)));
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram() -> Histogram {
+        Histogram {
+            bounds: vec![1.0, 10.0, 100.0],
+            counts: vec![50, 40, 10, 0],
+        }
+    }
+
+    #[test]
+    fn quantiles_interpolate_within_buckets() {
+        let h = histogram();
+        assert_eq!(h.total(), 100);
+        assert_eq!(h.quantile(0.5), Some(1.0));
+        assert_eq!(h.quantile(0.7), Some(5.5));
+        assert_eq!(h.quantile(0.95), Some(55.0));
+        assert_eq!(Histogram::default().quantile(0.5), None);
+        let overflow = Histogram {
+            bounds: vec![1.0],
+            counts: vec![0, 3],
+        };
+        assert_eq!(overflow.quantile(0.99), Some(1.0));
+    }
+
+    #[test]
+    fn records_calls() {
+        let mut stats = RpcStats::new(1700000000);
+        stats.record("getblock", 3.0, 100, 2000, false);
+        stats.record("getblock", 40.0, 100, 2000, true);
+        stats.record("getblock", 20000.0, 100, 0, false);
+        let getblock = &stats.methods["getblock"];
+        assert_eq!((getblock.calls, getblock.errors), (3, 1));
+        assert_eq!(
+            (getblock.request_bytes, getblock.response_bytes),
+            (300, 4000)
+        );
+        assert_eq!(getblock.latency_ms.counts[2], 1);
+        assert_eq!(getblock.latency_ms.counts[5], 1);
+        assert_eq!(getblock.latency_ms.counts[LATENCY_BOUNDS_MS.len()], 1);
+        let round_trip: RpcStats =
+            serde_json::from_value(serde_json::to_value(&stats).unwrap()).unwrap();
+        assert_eq!(round_trip, stats);
+    }
+
+    #[test]
+    fn parses_and_sorts() {
+        let stats: RpcStats = serde_json::from_value(serde_json::json!({
+            "since": 1700000000,
+            "methods": {
+                "getblock": {"calls": 10, "errors": 0, "latency_ms": {"bounds": [1.0, 10.0], "counts": [0, 10, 0]}},
+                "sendrawtransaction": {"calls": 4, "errors": 2},
+                "getblockcount": {"calls": 100}
+            }
+        }))
+        .unwrap();
+        let names = |sort| {
+            stats
+                .sorted(sort)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(SortBy::Calls),
+            ["getblockcount", "getblock", "sendrawtransaction"]
+        );
+        assert_eq!(names(SortBy::Errors)[0], "sendrawtransaction");
+        assert_eq!(names(SortBy::Latency)[0], "getblock");
+    }
+}
@@ -0,0 +1,60 @@
+//! Module skeleton generation (`blvm module scaffold`)
+//!
+//! Only the manifest is generated: the module's code is written against blvm-sdk, which owns the
+//! module IPC protocol and the environment the node's module loader passes to a module.
+
+use std::path::PathBuf;
+
+const MODULE_TOML: &str = include_str!("../templates/module/module.toml.tmpl");
+
+/// A generated file, relative to the module root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaffoldFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Module names become crate, binary, and manifest names: lowercase ASCII, digits, `-`.
+pub fn is_valid_module_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && !name.ends_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Files for a module skeleton: the `module.toml` manifest
+pub fn module_files(name: &str) -> Vec<ScaffoldFile> {
+    vec![ScaffoldFile {
+        path: PathBuf::from("module.toml"),
+        contents: MODULE_TOML.replace("{{name}}", name),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_names_are_restricted() {
+        assert!(is_valid_module_name("my-indexer"));
+        assert!(is_valid_module_name("zmq2"));
+        assert!(!is_valid_module_name(""));
+        assert!(!is_valid_module_name("2fast"));
+        assert!(!is_valid_module_name("My-Module"));
+        assert!(!is_valid_module_name("trailing-"));
+        assert!(!is_valid_module_name("../escape"));
+    }
+
+    #[test]
+    fn manifest_substitutes_name() {
+        let files = module_files("my-indexer");
+        assert_eq!(files.len(), 1);
+        assert!(!files[0].contents.contains("{{name}}"));
+        let manifest: toml::Value = toml::from_str(&files[0].contents).unwrap();
+        assert_eq!(manifest["name"].as_str(), Some("my-indexer"));
+        assert_eq!(manifest["entry_point"].as_str(), Some("my-indexer"));
+    }
+}
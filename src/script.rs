@@ -0,0 +1,361 @@
+//! Script decoding and disassembly (Core-style `asm`)
+//!
+//! Structural only: pushes are bounds-checked and opcodes named, nothing is executed.
+
+/// One decoded script element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptOp<'a> {
+    /// Data push (`OP_0`, direct pushes, `OP_PUSHDATA1/2/4`); `opcode` is the push opcode
+    Push { opcode: u8, data: &'a [u8] },
+    /// Any other opcode
+    Op(u8),
+}
+
+/// Why decoding stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    /// A push declared more bytes than remain (byte offset of the push opcode)
+    PushPastEnd { offset: usize },
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptError::PushPastEnd { offset } => {
+                write!(f, "push at offset {offset} runs past end of script")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Decode a script into pushes and opcodes
+pub fn decode_script(script: &[u8]) -> Result<Vec<ScriptOp<'_>>, ScriptError> {
+    let mut ops = Vec::new();
+    let mut i = 0;
+    while i < script.len() {
+        let offset = i;
+        let opcode = script[i];
+        i += 1;
+        let len = match opcode {
+            0x00..=0x4b => opcode as usize,
+            0x4c..=0x4e => {
+                let width = 1 << (opcode - 0x4c);
+                let bytes = script
+                    .get(i..i + width)
+                    .ok_or(ScriptError::PushPastEnd { offset })?;
+                i += width;
+                bytes
+                    .iter()
+                    .rev()
+                    .fold(0usize, |acc, &b| (acc << 8) | b as usize)
+            }
+            _ => {
+                ops.push(ScriptOp::Op(opcode));
+                continue;
+            }
+        };
+        let data = script
+            .get(
+                i..i.checked_add(len)
+                    .ok_or(ScriptError::PushPastEnd { offset })?,
+            )
+            .ok_or(ScriptError::PushPastEnd { offset })?;
+        i += len;
+        ops.push(ScriptOp::Push { opcode, data });
+    }
+    Ok(ops)
+}
+
+/// Re-encode decoded ops (inverse of [`decode_script`])
+pub fn encode_script(ops: &[ScriptOp<'_>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for op in ops {
+        match op {
+            ScriptOp::Op(opcode) => out.push(*opcode),
+            ScriptOp::Push { opcode, data } => {
+                out.push(*opcode);
+                match opcode {
+                    0x4c => out.push(data.len() as u8),
+                    0x4d => out.extend_from_slice(&(data.len() as u16).to_le_bytes()),
+                    0x4e => out.extend_from_slice(&(data.len() as u32).to_le_bytes()),
+                    _ => {}
+                }
+                out.extend_from_slice(data);
+            }
+        }
+    }
+    out
+}
+
+/// Core-style disassembly; an undecodable tail is rendered as `[error]`.
+pub fn to_asm(script: &[u8]) -> String {
+    let (ops, error) = match decode_script(script) {
+        Ok(ops) => (ops, false),
+        Err(ScriptError::PushPastEnd { offset }) => {
+            (decode_script(&script[..offset]).unwrap_or_default(), true)
+        }
+    };
+    let mut parts: Vec<String> = ops
+        .iter()
+        .map(|op| match op {
+            ScriptOp::Push { opcode: 0, .. } => "0".to_string(),
+            ScriptOp::Push { data, .. } if data.len() <= 4 => script_num(data).to_string(),
+            ScriptOp::Push { data, .. } => hex::encode(data),
+            ScriptOp::Op(opcode) => opcode_name(*opcode).to_string(),
+        })
+        .collect();
+    if error {
+        parts.push("[error]".to_string());
+    }
+    parts.join(" ")
+}
+
+/// Output script type as Core names it in `scriptPubKey.type`
+pub fn classify(script: &[u8]) -> &'static str {
+    match script {
+        [0x76, 0xa9, 0x14, .., 0x88, 0xac] if script.len() == 25 => "pubkeyhash",
+        [0xa9, 0x14, .., 0x87] if script.len() == 23 => "scripthash",
+        [0x00, 0x14, ..] if script.len() == 22 => "witness_v0_keyhash",
+        [0x00, 0x20, ..] if script.len() == 34 => "witness_v0_scripthash",
+        [0x51, 0x20, ..] if script.len() == 34 => "witness_v1_taproot",
+        [0x51..=0x60, len, ..] if (2..=40).contains(len) && script.len() == 2 + *len as usize => {
+            "witness_unknown"
+        }
+        [0x6a, ..] => "nulldata",
+        [0x21, .., 0xac] if script.len() == 35 => "pubkey",
+        [0x41, .., 0xac] if script.len() == 67 => "pubkey",
+        _ if is_multisig(script) => "multisig",
+        _ => "nonstandard",
+    }
+}
+
+/// Bare `m <pubkeys...> n OP_CHECKMULTISIG` with 1 <= m <= n <= 16
+fn is_multisig(script: &[u8]) -> bool {
+    let Ok(ops) = decode_script(script) else {
+        return false;
+    };
+    let [
+        ScriptOp::Op(m @ 0x51..=0x60),
+        keys @ ..,
+        ScriptOp::Op(n @ 0x51..=0x60),
+        ScriptOp::Op(0xae),
+    ] = ops.as_slice()
+    else {
+        return false;
+    };
+    m <= n
+        && keys.len() == (n - 0x50) as usize
+        && keys.iter().all(
+            |op| matches!(op, ScriptOp::Push { data, .. } if data.len() == 33 || data.len() == 65),
+        )
+}
+
+/// Little-endian sign-magnitude number, as Core prints short pushes
+fn script_num(data: &[u8]) -> i64 {
+    let Some((&last, _)) = data.split_last() else {
+        return 0;
+    };
+    let mut value = data
+        .iter()
+        .rev()
+        .fold(0i64, |acc, &b| (acc << 8) | b as i64);
+    if last & 0x80 != 0 {
+        value &= !(0x80i64 << (8 * (data.len() - 1)));
+        value = -value;
+    }
+    value
+}
+
+/// Opcode mnemonic (`OP_UNKNOWN` for undefined values)
+pub fn opcode_name(opcode: u8) -> &'static str {
+    match opcode {
+        0x00 => "0",
+        0x4c => "OP_PUSHDATA1",
+        0x4d => "OP_PUSHDATA2",
+        0x4e => "OP_PUSHDATA4",
+        0x4f => "-1",
+        0x50 => "OP_RESERVED",
+        0x51 => "1",
+        0x52 => "2",
+        0x53 => "3",
+        0x54 => "4",
+        0x55 => "5",
+        0x56 => "6",
+        0x57 => "7",
+        0x58 => "8",
+        0x59 => "9",
+        0x5a => "10",
+        0x5b => "11",
+        0x5c => "12",
+        0x5d => "13",
+        0x5e => "14",
+        0x5f => "15",
+        0x60 => "16",
+        0x61 => "OP_NOP",
+        0x62 => "OP_VER",
+        0x63 => "OP_IF",
+        0x64 => "OP_NOTIF",
+        0x65 => "OP_VERIF",
+        0x66 => "OP_VERNOTIF",
+        0x67 => "OP_ELSE",
+        0x68 => "OP_ENDIF",
+        0x69 => "OP_VERIFY",
+        0x6a => "OP_RETURN",
+        0x6b => "OP_TOALTSTACK",
+        0x6c => "OP_FROMALTSTACK",
+        0x6d => "OP_2DROP",
+        0x6e => "OP_2DUP",
+        0x6f => "OP_3DUP",
+        0x70 => "OP_2OVER",
+        0x71 => "OP_2ROT",
+        0x72 => "OP_2SWAP",
+        0x73 => "OP_IFDUP",
+        0x74 => "OP_DEPTH",
+        0x75 => "OP_DROP",
+        0x76 => "OP_DUP",
+        0x77 => "OP_NIP",
+        0x78 => "OP_OVER",
+        0x79 => "OP_PICK",
+        0x7a => "OP_ROLL",
+        0x7b => "OP_ROT",
+        0x7c => "OP_SWAP",
+        0x7d => "OP_TUCK",
+        0x7e => "OP_CAT",
+        0x7f => "OP_SUBSTR",
+        0x80 => "OP_LEFT",
+        0x81 => "OP_RIGHT",
+        0x82 => "OP_SIZE",
+        0x83 => "OP_INVERT",
+        0x84 => "OP_AND",
+        0x85 => "OP_OR",
+        0x86 => "OP_XOR",
+        0x87 => "OP_EQUAL",
+        0x88 => "OP_EQUALVERIFY",
+        0x89 => "OP_RESERVED1",
+        0x8a => "OP_RESERVED2",
+        0x8b => "OP_1ADD",
+        0x8c => "OP_1SUB",
+        0x8d => "OP_2MUL",
+        0x8e => "OP_2DIV",
+        0x8f => "OP_NEGATE",
+        0x90 => "OP_ABS",
+        0x91 => "OP_NOT",
+        0x92 => "OP_0NOTEQUAL",
+        0x93 => "OP_ADD",
+        0x94 => "OP_SUB",
+        0x95 => "OP_MUL",
+        0x96 => "OP_DIV",
+        0x97 => "OP_MOD",
+        0x98 => "OP_LSHIFT",
+        0x99 => "OP_RSHIFT",
+        0x9a => "OP_BOOLAND",
+        0x9b => "OP_BOOLOR",
+        0x9c => "OP_NUMEQUAL",
+        0x9d => "OP_NUMEQUALVERIFY",
+        0x9e => "OP_NUMNOTEQUAL",
+        0x9f => "OP_LESSTHAN",
+        0xa0 => "OP_GREATERTHAN",
+        0xa1 => "OP_LESSTHANOREQUAL",
+        0xa2 => "OP_GREATERTHANOREQUAL",
+        0xa3 => "OP_MIN",
+        0xa4 => "OP_MAX",
+        0xa5 => "OP_WITHIN",
+        0xa6 => "OP_RIPEMD160",
+        0xa7 => "OP_SHA1",
+        0xa8 => "OP_SHA256",
+        0xa9 => "OP_HASH160",
+        0xaa => "OP_HASH256",
+        0xab => "OP_CODESEPARATOR",
+        0xac => "OP_CHECKSIG",
+        0xad => "OP_CHECKSIGVERIFY",
+        0xae => "OP_CHECKMULTISIG",
+        0xaf => "OP_CHECKMULTISIGVERIFY",
+        0xb0 => "OP_NOP1",
+        0xb1 => "OP_CHECKLOCKTIMEVERIFY",
+        0xb2 => "OP_CHECKSEQUENCEVERIFY",
+        0xb3 => "OP_NOP4",
+        0xb4 => "OP_NOP5",
+        0xb5 => "OP_NOP6",
+        0xb6 => "OP_NOP7",
+        0xb7 => "OP_NOP8",
+        0xb8 => "OP_NOP9",
+        0xb9 => "OP_NOP10",
+        0xba => "OP_CHECKSIGADD",
+        _ => "OP_UNKNOWN",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassembles_p2pkh() {
+        let script = hex::decode("76a91489abcdefabbaabbaabbaabbaabbaabbaabbaabba88ac").unwrap();
+        assert_eq!(
+            to_asm(&script),
+            "OP_DUP OP_HASH160 89abcdefabbaabbaabbaabbaabbaabbaabbaabba OP_EQUALVERIFY OP_CHECKSIG"
+        );
+        let ops = decode_script(&script).unwrap();
+        assert_eq!(encode_script(&ops), script);
+    }
+
+    #[test]
+    fn short_pushes_print_as_numbers() {
+        // OP_RETURN, push 0x81 (-1), OP_0, OP_16, push 0xe803 (1000)
+        assert_eq!(
+            to_asm(&[0x6a, 0x01, 0x81, 0x00, 0x60, 0x02, 0xe8, 0x03]),
+            "OP_RETURN -1 0 16 1000"
+        );
+    }
+
+    #[test]
+    fn truncated_push_is_an_error() {
+        assert_eq!(
+            decode_script(&[0x76, 0x4d, 0xff]),
+            Err(ScriptError::PushPastEnd { offset: 1 })
+        );
+        assert_eq!(
+            decode_script(&[0x05, 0x01]),
+            Err(ScriptError::PushPastEnd { offset: 0 })
+        );
+        assert_eq!(to_asm(&[0x76, 0x4c, 0x09, 0x00]), "OP_DUP [error]");
+    }
+
+    #[test]
+    fn classifies_standard_outputs() {
+        let cases = [
+            (
+                "76a91489abcdefabbaabbaabbaabbaabbaabbaabbaabba88ac",
+                "pubkeyhash",
+            ),
+            (
+                "a91489abcdefabbaabbaabbaabbaabbaabbaabbaabba87",
+                "scripthash",
+            ),
+            (
+                "001489abcdefabbaabbaabbaabbaabbaabbaabbaabba",
+                "witness_v0_keyhash",
+            ),
+            (&format!("0020{}", "ab".repeat(32)), "witness_v0_scripthash"),
+            (&format!("5120{}", "ab".repeat(32)), "witness_v1_taproot"),
+            ("52024e73", "witness_unknown"),
+            ("6a0b68656c6c6f20776f726c64", "nulldata"),
+            (&format!("21{}ac", "02".repeat(33)), "pubkey"),
+            (
+                &format!("5121{}21{}52ae", "02".repeat(33), "03".repeat(33)),
+                "multisig",
+            ),
+            ("51", "nonstandard"),
+        ];
+        for (script, expected) in cases {
+            assert_eq!(
+                classify(&hex::decode(script).unwrap()),
+                expected,
+                "{script}"
+            );
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Output script type statistics (`[script_stats]`, `blvm stats scripts`)
+//!
+//! Outputs are counted per block by type (`p2pkh`, `p2sh`, `p2wpkh`, `p2wsh`, `p2tr`,
+//! `op_return`, ... and `nonstandard`). While the node runs, a collector adds every new block to
+//! cumulative totals in `<data_dir>/script-stats.json` and keeps the per-block breakdown of
+//! recent blocks so a reorg can be undone; through the RPC front, `getblockstats` results gain a
+//! `script_types` field.
+
+use crate::decode::Block;
+use crate::script::classify;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+
+/// State file name in the data directory
+pub const STATE_FILE: &str = "script-stats.json";
+
+/// `[script_stats]` config section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ScriptStatsConfig {
+    pub enabled: bool,
+    /// Per-block breakdowns kept (and the deepest reorg the totals can follow)
+    pub recent_blocks: usize,
+}
+
+impl Default for ScriptStatsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            recent_blocks: 1008,
+        }
+    }
+}
+
+/// Short name for a `script::classify` type
+pub fn category(script: &[u8]) -> &'static str {
+    match classify(script) {
+        "pubkeyhash" => "p2pkh",
+        "scripthash" => "p2sh",
+        "witness_v0_keyhash" => "p2wpkh",
+        "witness_v0_scripthash" => "p2wsh",
+        "witness_v1_taproot" => "p2tr",
+        "nulldata" => "op_return",
+        "pubkey" => "p2pk",
+        "multisig" => "multisig",
+        "witness_unknown" => "witness_unknown",
+        _ => "nonstandard",
+    }
+}
+
+/// Outputs and value of one script type
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct TypeStats {
+    pub outputs: u64,
+    /// Satoshis
+    pub value: u64,
+}
+
+pub type Breakdown = BTreeMap<String, TypeStats>;
+
+/// Per-type breakdown of a block's outputs
+pub fn tally(block: &Block) -> Breakdown {
+    let mut counts = Breakdown::new();
+    for output in block.transactions.iter().flat_map(|tx| &tx.outputs) {
+        let entry = counts
+            .entry(category(&output.script_pubkey).to_string())
+            .or_default();
+        entry.outputs += 1;
+        entry.value += output.value;
+    }
+    counts
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockEntry {
+    pub height: u64,
+    pub hash: String,
+    pub types: Breakdown,
+}
+
+/// Collector state: totals since `from_height` plus recent per-block breakdowns
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ScriptStats {
+    /// First block in the totals
+    pub from_height: Option<u64>,
+    pub totals: Breakdown,
+    pub recent: VecDeque<BlockEntry>,
+}
+
+impl ScriptStats {
+    pub fn last(&self) -> Option<&BlockEntry> {
+        self.recent.back()
+    }
+
+    /// Add the next block, keeping at most `keep` breakdowns
+    pub fn push(&mut self, entry: BlockEntry, keep: usize) {
+        self.from_height.get_or_insert(entry.height);
+        for (kind, stats) in &entry.types {
+            let total = self.totals.entry(kind.clone()).or_default();
+            total.outputs += stats.outputs;
+            total.value += stats.value;
+        }
+        self.recent.push_back(entry);
+        while self.recent.len() > keep.max(1) {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Undo the most recent block (reorg); `None` once no breakdowns are left
+    pub fn pop(&mut self) -> Option<BlockEntry> {
+        let entry = self.recent.pop_back()?;
+        for (kind, stats) in &entry.types {
+            if let Some(total) = self.totals.get_mut(kind) {
+                total.outputs = total.outputs.saturating_sub(stats.outputs);
+                total.value = total.value.saturating_sub(stats.value);
+            }
+        }
+        self.totals.retain(|_, total| total.outputs > 0);
+        Some(entry)
+    }
+
+    pub fn load(data_dir: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(data_dir.join(STATE_FILE)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> std::io::Result<()> {
+        let tmp = data_dir.join(format!("{STATE_FILE}.tmp"));
+        std::fs::write(&tmp, serde_json::to_vec(self)?)?;
+        std::fs::rename(tmp, data_dir.join(STATE_FILE))
+    }
+}
+
+/// Share of outputs per type, largest first
+pub fn shares(types: &Breakdown) -> Vec<(&str, TypeStats, f64)> {
+    let total: u64 = types.values().map(|t| t.outputs).sum();
+    let mut rows: Vec<_> = types
+        .iter()
+        .map(|(kind, stats)| {
+            let share = if total > 0 {
+                stats.outputs as f64 / total as f64
+            } else {
+                0.0
+            };
+            (kind.as_str(), *stats, share)
+        })
+        .collect();
+    rows.sort_by(|a, b| b.1.outputs.cmp(&a.1.outputs).then(a.0.cmp(b.0)));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENESIS_BLOCK: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    #[test]
+    fn categories() {
+        let mut p2tr = vec![0x51, 0x20];
+        p2tr.extend([0; 32]);
+        assert_eq!(category(&p2tr), "p2tr");
+        let mut p2wpkh = vec![0x00, 0x14];
+        p2wpkh.extend([0; 20]);
+        assert_eq!(category(&p2wpkh), "p2wpkh");
+        assert_eq!(category(&[0x6a, 0x01, 0xff]), "op_return");
+        assert_eq!(category(&[0x01]), "nonstandard");
+    }
+
+    #[test]
+    fn totals_follow_pushes_and_reorgs() {
+        let bytes = hex::decode(GENESIS_BLOCK).unwrap();
+        let types = tally(&Block::decode(&bytes).unwrap());
+        assert_eq!(
+            types.get("p2pk"),
+            Some(&TypeStats {
+                outputs: 1,
+                value: 50 * 100_000_000
+            })
+        );
+
+        let mut stats = ScriptStats::default();
+        let entry = |height: u64, types: &Breakdown| BlockEntry {
+            height,
+            hash: format!("{height:064x}"),
+            types: types.clone(),
+        };
+        stats.push(entry(0, &types), 2);
+        stats.push(entry(1, &types), 2);
+        stats.push(entry(2, &types), 2);
+        assert_eq!(stats.totals["p2pk"].outputs, 3);
+        assert_eq!(stats.recent.len(), 2);
+        assert_eq!(stats.from_height, Some(0));
+
+        assert_eq!(stats.pop().map(|e| e.height), Some(2));
+        assert_eq!(stats.totals["p2pk"].outputs, 2);
+        assert_eq!(
+            shares(&stats.totals),
+            vec![("p2pk", stats.totals["p2pk"], 1.0)]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! systemd service notifications (`systemd` feature)
+//!
+//! Under `Type=notify` systemd passes a datagram socket in `NOTIFY_SOCKET`; `blvm start` sends
+//! `READY=1` once the RPC server and P2P listener accept connections, `WATCHDOG=1` at half of
+//! `WatchdogSec=` while the RPC server answers, and `STOPPING=1` on shutdown. Without
+//! `NOTIFY_SOCKET` every call is a no-op, so the same binary runs fine outside systemd.
+
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+
+/// Send `state` (newline-separated `KEY=VALUE` assignments); `Ok(false)` when not started by
+/// systemd
+pub fn notify(state: &str) -> io::Result<bool> {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(false);
+    };
+    let socket = UnixDatagram::unbound()?;
+    let path = path.to_string_lossy();
+    match path.strip_prefix('@') {
+        // Abstract namespace socket
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+            socket.send_to_addr(state.as_bytes(), &addr)?;
+        }
+        _ => {
+            socket.send_to(state.as_bytes(), path.as_ref())?;
+        }
+    }
+    Ok(true)
+}
+
+/// How often to send `WATCHDOG=1`: half of `WATCHDOG_USEC`, when the watchdog is meant for
+/// this process
+pub fn watchdog_interval() -> Option<Duration> {
+    watchdog_interval_from(
+        std::env::var("WATCHDOG_USEC").ok().as_deref(),
+        std::env::var("WATCHDOG_PID").ok().as_deref(),
+        std::process::id(),
+    )
+}
+
+fn watchdog_interval_from(usec: Option<&str>, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    if let Some(pid) = pid
+        && pid.trim().parse::<u32>().ok() != Some(own_pid)
+    {
+        return None;
+    }
+    let usec: u64 = usec?.trim().parse().ok()?;
+    (usec > 0).then(|| Duration::from_micros(usec / 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchdog_interval_is_half_of_usec_for_our_pid() {
+        assert_eq!(
+            watchdog_interval_from(Some("30000000"), None, 42),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            watchdog_interval_from(Some("30000000"), Some("42"), 42),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            watchdog_interval_from(Some("30000000"), Some("7"), 42),
+            None
+        );
+        assert_eq!(watchdog_interval_from(Some("0"), None, 42), None);
+        assert_eq!(watchdog_interval_from(None, None, 42), None);
+    }
+}
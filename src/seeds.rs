@@ -0,0 +1,193 @@
+//! Fixed seed lists (`blvm seeds export`, `seeds_file = "..."`)
+//!
+//! `export` picks reliable peers from the node's address manager (`getnodeaddresses`) and
+//! writes one `ip:port` per line, the format release tooling turns into compiled-in fixed
+//! seeds. The same file can be given to a running node as `seeds_file`; it is tried when the
+//! node has too few connections, as a fallback to DNS seeds.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+/// Service bits a seed must advertise (NODE_NETWORK | NODE_WITNESS)
+pub const REQUIRED_SERVICES: u64 = 0x1 | 0x8;
+
+/// One address manager entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub addr: SocketAddr,
+    pub services: u64,
+    /// Unix time the address was last seen
+    pub last_seen: u64,
+    /// Fraction of connection attempts that succeeded, when the node tracks it
+    pub uptime: Option<f64>,
+}
+
+impl Candidate {
+    /// Read a `getnodeaddresses` entry; non-IP networks (onion, i2p) are skipped
+    pub fn from_value(entry: &Value) -> Option<Self> {
+        let ip: IpAddr = entry.get("address")?.as_str()?.parse().ok()?;
+        let port = entry.get("port")?.as_u64()? as u16;
+        let uptime = entry.get("uptime").and_then(|v| v.as_f64()).or_else(|| {
+            let attempts = entry.get("attempts")?.as_u64()?;
+            let successes = entry.get("successes")?.as_u64()?;
+            (attempts > 0).then(|| successes as f64 / attempts as f64)
+        });
+        Some(Candidate {
+            addr: SocketAddr::new(ip, port),
+            services: entry.get("services").and_then(|v| v.as_u64()).unwrap_or(0),
+            last_seen: entry.get("time").and_then(|v| v.as_u64()).unwrap_or(0),
+            uptime,
+        })
+    }
+}
+
+/// Selection limits for `export`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    /// 0.0..=1.0; entries without uptime data only pass a zero minimum
+    pub min_uptime: f64,
+    pub max_age_secs: u64,
+    /// At most this many seeds from one /16 (IPv4) or /32 (IPv6)
+    pub per_netgroup: usize,
+    pub limit: usize,
+}
+
+fn is_routable(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_documentation()
+                || v4.is_broadcast())
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || first == 0x2001 && v6.segments()[1] == 0x0db8)
+        }
+    }
+}
+
+/// Best candidates first (uptime, then recency), filtered and spread across netgroups
+pub fn select(mut candidates: Vec<Candidate>, filter: &Filter, now: u64) -> Vec<Candidate> {
+    candidates.retain(|c| {
+        is_routable(&c.addr.ip())
+            && c.services & REQUIRED_SERVICES == REQUIRED_SERVICES
+            && now.saturating_sub(c.last_seen) <= filter.max_age_secs
+            && c.uptime.unwrap_or(0.0) >= filter.min_uptime
+    });
+    candidates.sort_by(|a, b| {
+        b.uptime
+            .unwrap_or(0.0)
+            .total_cmp(&a.uptime.unwrap_or(0.0))
+            .then(b.last_seen.cmp(&a.last_seen))
+    });
+    let mut per_group: HashMap<Vec<u8>, usize> = HashMap::new();
+    let mut selected: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        if selected.len() >= filter.limit {
+            break;
+        }
+        if selected.iter().any(|s| s.addr == candidate.addr) {
+            continue;
+        }
+        let count = per_group
+            .entry(crate::peer_limits::subnet(&candidate.addr.ip()))
+            .or_default();
+        if *count >= filter.per_netgroup {
+            continue;
+        }
+        *count += 1;
+        selected.push(candidate);
+    }
+    selected
+}
+
+/// Seed file text: a comment header, then one address per line
+pub fn to_seed_file(seeds: &[Candidate], header: &str) -> String {
+    let mut out = String::new();
+    for line in header.lines() {
+        out.push_str(&format!("# {line}\n"));
+    }
+    for seed in seeds {
+        out.push_str(&format!("{}\n", seed.addr));
+    }
+    out
+}
+
+/// Addresses in a seed file; `#` comments and blank lines are ignored
+pub fn parse_seed_file(content: &str) -> Result<Vec<SocketAddr>, String> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.split('#').next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            line.parse::<SocketAddr>()
+                .map_err(|_| format!("line {}: '{line}' is not ip:port", i + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NOW: u64 = 1_800_000_000;
+
+    fn candidate(addr: &str, uptime: f64, age: u64) -> Candidate {
+        Candidate {
+            addr: addr.parse().unwrap(),
+            services: 0x409,
+            last_seen: NOW - age,
+            uptime: Some(uptime),
+        }
+    }
+
+    #[test]
+    fn selects_reliable_diverse_peers() {
+        let filter = Filter {
+            min_uptime: 0.5,
+            max_age_secs: 86_400,
+            per_netgroup: 1,
+            limit: 10,
+        };
+        let candidates = vec![
+            candidate("1.2.3.4:8333", 0.9, 60),
+            candidate("1.2.9.9:8333", 0.95, 60), // same /16, better uptime
+            candidate("5.6.7.8:8333", 0.4, 60),  // unreliable
+            candidate("9.9.9.9:8333", 0.99, 200_000), // stale
+            candidate("192.168.1.5:8333", 1.0, 60), // private
+            candidate("[2a01:4f8::1]:8333", 0.7, 60),
+        ];
+        let seeds = select(candidates, &filter, NOW);
+        let addrs: Vec<String> = seeds.iter().map(|s| s.addr.to_string()).collect();
+        assert_eq!(addrs, vec!["1.2.9.9:8333", "[2a01:4f8::1]:8333"]);
+
+        let mut pruned = candidate("8.8.4.4:8333", 1.0, 60);
+        pruned.services = 0x408;
+        assert!(select(vec![pruned], &filter, NOW).is_empty());
+    }
+
+    #[test]
+    fn reads_node_addresses_and_round_trips_files() {
+        let entry = json!({"time": NOW, "services": 1033, "address": "1.2.3.4", "port": 8333,
+            "network": "ipv4", "attempts": 4, "successes": 3});
+        let c = Candidate::from_value(&entry).unwrap();
+        assert_eq!(c.uptime, Some(0.75));
+        let onion = json!({"address": "abc.onion", "port": 8333, "network": "onion"});
+        assert!(Candidate::from_value(&onion).is_none());
+
+        let text = to_seed_file(std::slice::from_ref(&c), "mainnet seeds\ngenerated");
+        assert!(text.starts_with("# mainnet seeds\n# generated\n"));
+        assert_eq!(parse_seed_file(&text).unwrap(), vec![c.addr]);
+        assert!(parse_seed_file("1.2.3.4\n").unwrap_err().contains("line 1"));
+    }
+}
@@ -0,0 +1,513 @@
+//! Silent payments (BIP352) receiving: `sp1...` addresses and per-transaction output detection
+//!
+//! Behind the `silent-payments` feature. Detection needs an elliptic-curve multiplication for
+//! every transaction with a taproot output (libsecp256k1 through the `secp256k1` crate).
+//! Labels (BIP352 `m` tweaks) are not supported.
+
+use crate::hash::{hash160, tagged_hash};
+use bech32::{FromBase32, ToBase32, Variant, u5};
+use secp256k1::{All, Parity, PublicKey, Scalar, Secp256k1, SecretKey, XOnlyPublicKey};
+
+/// BIP341 NUMS point x coordinate; inputs with this internal key are script-path only
+const NUMS_H: [u8; 32] = [
+    0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9, 0x7a, 0x5e,
+    0x07, 0x8a, 0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a, 0xce, 0x80, 0x3a, 0xc0,
+];
+
+/// Compressed public key for a 32-byte secret key
+pub fn public_key(secret: &[u8; 32]) -> Result<[u8; 33], String> {
+    let secret = SecretKey::from_slice(secret).map_err(|_| "secret key out of range")?;
+    Ok(PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret).serialize())
+}
+
+/// Static silent payment address: scan and spend public keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SilentPaymentAddress {
+    pub scan_pubkey: [u8; 33],
+    pub spend_pubkey: [u8; 33],
+    /// `tsp` (test networks) instead of `sp`
+    pub testnet: bool,
+}
+
+impl SilentPaymentAddress {
+    pub fn encode(&self) -> String {
+        let mut payload = self.scan_pubkey.to_vec();
+        payload.extend_from_slice(&self.spend_pubkey);
+        let mut data = vec![u5::try_from_u8(0).expect("version 0")];
+        data.extend(payload.to_base32());
+        // bech32 does not apply the 90-character limit, which these addresses exceed
+        let hrp = if self.testnet { "tsp" } else { "sp" };
+        bech32::encode(hrp, data, Variant::Bech32m).expect("valid prefix")
+    }
+
+    pub fn decode(address: &str) -> Result<Self, String> {
+        let (hrp, data, variant) = bech32::decode(address).map_err(|e| e.to_string())?;
+        if variant != Variant::Bech32m {
+            return Err("not a bech32m string".to_string());
+        }
+        let testnet = match hrp.as_str() {
+            "sp" => false,
+            "tsp" => true,
+            _ => return Err(format!("not a silent payment address (prefix '{hrp}')")),
+        };
+        let (version, data) = data.split_first().ok_or("missing version")?;
+        let version = version.to_u8();
+        let payload = Vec::<u8>::from_base32(data).map_err(|_| "invalid address padding")?;
+        // Future versions may append data; version 31 is reserved for incompatible changes
+        let payload = match version {
+            0 if payload.len() == 66 => &payload[..],
+            1..=30 if payload.len() >= 66 => &payload[..66],
+            _ => {
+                return Err(format!(
+                    "unsupported silent payment address version {version}"
+                ));
+            }
+        };
+        let key = |bytes: &[u8]| -> Result<[u8; 33], String> {
+            PublicKey::from_slice(bytes)
+                .map(|p| p.serialize())
+                .map_err(|_| "address contains an invalid public key".to_string())
+        };
+        Ok(SilentPaymentAddress {
+            scan_pubkey: key(&payload[..33])?,
+            spend_pubkey: key(&payload[33..])?,
+            testnet,
+        })
+    }
+}
+
+/// Transaction input with the data BIP352 reads (getblock verbosity 3 provides the prevout)
+#[derive(Debug, Clone, Default)]
+pub struct TxInput {
+    /// Previous txid in internal (little-endian) byte order
+    pub txid: [u8; 32],
+    pub vout: u32,
+    pub script_sig: Vec<u8>,
+    pub witness: Vec<Vec<u8>>,
+    pub prevout_script: Vec<u8>,
+}
+
+/// A detected payment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundOutput {
+    pub vout: u32,
+    /// Add to the spend secret key to get the output's secret key
+    pub tweak: [u8; 32],
+}
+
+fn is_p2tr(script: &[u8]) -> bool {
+    script.len() == 34 && script[0] == 0x51 && script[1] == 0x20
+}
+
+/// Segwit version above 1 (BIP352 skips transactions spending these)
+fn is_future_segwit(script: &[u8]) -> bool {
+    (4..=42).contains(&script.len())
+        && (0x52..=0x60).contains(&script[0])
+        && script[1] as usize == script.len() - 2
+}
+
+/// A 33-byte compressed key; BIP352 skips inputs revealing uncompressed keys
+fn compressed_key(bytes: &[u8]) -> Option<PublicKey> {
+    if bytes.len() != 33 {
+        return None;
+    }
+    PublicKey::from_slice(bytes).ok()
+}
+
+/// Public key an eligible input contributes, per BIP352 input rules
+fn input_public_key(input: &TxInput) -> Option<PublicKey> {
+    let spk = &input.prevout_script;
+    match spk.as_slice() {
+        // P2PKH: last 33-byte window of scriptSig matching the key hash
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+            let sig = &input.script_sig;
+            (33..=sig.len())
+                .rev()
+                .map(|end| &sig[end - 33..end])
+                .find(|key| hash160(key) == hash)
+                .and_then(|key| PublicKey::from_slice(key).ok())
+        }
+        // P2SH-P2WPKH
+        [0xa9, 0x14, _hash @ .., 0x87] if spk.len() == 23 => {
+            let redeem = input.script_sig.get(1..)?;
+            let is_p2wpkh = input.script_sig.len() == 23
+                && input.script_sig[0] == 22
+                && redeem[0] == 0x00
+                && redeem[1] == 0x14;
+            if is_p2wpkh {
+                compressed_key(input.witness.last()?)
+            } else {
+                None
+            }
+        }
+        // P2WPKH
+        [0x00, 0x14, ..] if spk.len() == 22 => compressed_key(input.witness.last()?),
+        // P2TR, unless the script path reveals the NUMS internal key
+        _ if is_p2tr(spk) => {
+            let mut stack: &[Vec<u8>] = &input.witness;
+            if stack.len() > 1 && stack.last()?.first() == Some(&0x50) {
+                stack = &stack[..stack.len() - 1]; // annex
+            }
+            if stack.len() > 1 && stack.last()?.get(1..33) == Some(&NUMS_H[..]) {
+                return None;
+            }
+            let x = XOnlyPublicKey::from_slice(&spk[2..34]).ok()?;
+            Some(PublicKey::from_x_only_public_key(x, Parity::Even))
+        }
+        _ => None,
+    }
+}
+
+/// Scans transactions for outputs paying a silent payment address
+pub struct Receiver {
+    secp: Secp256k1<All>,
+    scan_secret: SecretKey,
+    spend_pubkey: PublicKey,
+}
+
+impl Receiver {
+    pub fn new(scan_secret: &[u8; 32], spend_pubkey: &[u8; 33]) -> Result<Self, String> {
+        Ok(Receiver {
+            secp: Secp256k1::new(),
+            scan_secret: SecretKey::from_slice(scan_secret).map_err(|_| "scan key out of range")?,
+            spend_pubkey: PublicKey::from_slice(spend_pubkey)
+                .map_err(|_| "invalid spend public key")?,
+        })
+    }
+
+    pub fn address(&self, testnet: bool) -> SilentPaymentAddress {
+        SilentPaymentAddress {
+            scan_pubkey: PublicKey::from_secret_key(&self.secp, &self.scan_secret).serialize(),
+            spend_pubkey: self.spend_pubkey.serialize(),
+            testnet,
+        }
+    }
+
+    /// Outputs of one transaction (by scriptPubKey, in order) that pay this receiver
+    pub fn scan(&self, inputs: &[TxInput], outputs: &[Vec<u8>]) -> Vec<FoundOutput> {
+        if !outputs.iter().any(|o| is_p2tr(o))
+            || inputs.iter().any(|i| is_future_segwit(&i.prevout_script))
+        {
+            return Vec::new();
+        }
+        let keys: Vec<PublicKey> = inputs.iter().filter_map(input_public_key).collect();
+        // Fails for no eligible inputs and for keys summing to infinity
+        let Ok(sum) = PublicKey::combine_keys(&keys.iter().collect::<Vec<_>>()) else {
+            return Vec::new();
+        };
+        let Some(smallest_outpoint) = inputs
+            .iter()
+            .map(|i| {
+                let mut outpoint = i.txid.to_vec();
+                outpoint.extend_from_slice(&i.vout.to_le_bytes());
+                outpoint
+            })
+            .min()
+        else {
+            return Vec::new();
+        };
+        let mut preimage = smallest_outpoint;
+        preimage.extend_from_slice(&sum.serialize());
+        let Ok(input_hash) = Scalar::from_be_bytes(tagged_hash("BIP0352/Inputs", &preimage)) else {
+            return Vec::new();
+        };
+        let Ok(shared) = self
+            .scan_secret
+            .mul_tweak(&input_hash)
+            .and_then(|secret| sum.mul_tweak(&self.secp, &Scalar::from(secret)))
+        else {
+            return Vec::new();
+        };
+
+        let mut found = Vec::new();
+        let mut remaining: Vec<(u32, &[u8])> = outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| is_p2tr(o))
+            .map(|(i, o)| (i as u32, &o[2..34]))
+            .collect();
+        for k in 0u32.. {
+            let mut message = shared.serialize().to_vec();
+            message.extend_from_slice(&k.to_be_bytes());
+            let tweak = tagged_hash("BIP0352/SharedSecret", &message);
+            let Ok(output_key) = Scalar::from_be_bytes(tweak)
+                .map_err(|_| secp256k1::Error::InvalidTweak)
+                .and_then(|t| self.spend_pubkey.add_exp_tweak(&self.secp, &t))
+            else {
+                break;
+            };
+            let x_only = output_key.x_only_public_key().0.serialize();
+            let Some(pos) = remaining.iter().position(|(_, x)| *x == x_only) else {
+                break;
+            };
+            let (vout, _) = remaining.remove(pos);
+            found.push(FoundOutput { vout, tweak });
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(byte: u8) -> [u8; 32] {
+        let mut s = [0u8; 32];
+        s[31] = byte;
+        s
+    }
+
+    #[test]
+    fn public_keys() {
+        assert_eq!(
+            hex::encode(public_key(&secret(1)).unwrap()),
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        assert_eq!(
+            hex::encode(public_key(&secret(2)).unwrap()),
+            "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5"
+        );
+        assert!(public_key(&[0; 32]).is_err());
+        assert!(public_key(&[0xff; 32]).is_err());
+    }
+
+    #[test]
+    fn address_round_trip() {
+        let receiver = Receiver::new(&secret(7), &public_key(&secret(9)).unwrap()).unwrap();
+        let address = receiver.address(false);
+        let text = address.encode();
+        assert!(text.starts_with("sp1q"));
+        assert_eq!(SilentPaymentAddress::decode(&text), Ok(address.clone()));
+        assert_eq!(
+            SilentPaymentAddress::decode(&text.to_uppercase()),
+            Ok(address)
+        );
+        assert!(receiver.address(true).encode().starts_with("tsp1q"));
+        assert!(
+            SilentPaymentAddress::decode("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").is_err()
+        );
+    }
+
+    fn hex32(s: &str) -> [u8; 32] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    /// Input spending `key`'s output at `txid:vout`; `txid` is in display order as in the BIP
+    fn vector_input(key: &str, txid: &str, vout: u32, taproot: bool) -> TxInput {
+        let public = public_key(&hex32(key)).unwrap();
+        let mut txid = hex32(txid);
+        txid.reverse();
+        if taproot {
+            let mut prevout_script = vec![0x51, 0x20];
+            prevout_script.extend_from_slice(&public[1..]);
+            TxInput {
+                txid,
+                vout,
+                witness: vec![vec![0x01; 64]],
+                prevout_script,
+                ..Default::default()
+            }
+        } else {
+            let mut prevout_script = vec![0x76, 0xa9, 0x14];
+            prevout_script.extend_from_slice(&hash160(&public));
+            prevout_script.extend_from_slice(&[0x88, 0xac]);
+            let mut script_sig = vec![71];
+            script_sig.extend_from_slice(&[0x30; 71]);
+            script_sig.push(33);
+            script_sig.extend_from_slice(&public);
+            TxInput {
+                txid,
+                vout,
+                script_sig,
+                prevout_script,
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Receiving side of the official BIP352 test vectors (signatures replaced by placeholders,
+    /// which detection never looks at)
+    #[test]
+    fn bip352_vectors() {
+        let receiver = Receiver::new(
+            &hex32("0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c"),
+            &public_key(&hex32(
+                "9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3",
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            receiver.address(false).encode(),
+            "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv"
+        );
+
+        let key_a = "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1";
+        let key_b = "93f5ed907ad5b2bdbbdcb5d9116ebc0a4e1f92f910d5260237fa45a9408aad16";
+        let key_c = "fc8716a97a48ba9a05a98ae47b5cd201a25a7fd5d8b73c203c5f7b6b6b3b6ad7";
+        let tx_1 = "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16";
+        let tx_2 = "a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d";
+        let cases = [
+            (
+                "Simple send: two inputs",
+                vec![
+                    vector_input(key_a, tx_1, 0, false),
+                    vector_input(key_b, tx_2, 0, false),
+                ],
+                "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+            ),
+            (
+                "Simple send: two inputs, order reversed",
+                vec![
+                    vector_input(key_b, tx_2, 0, false),
+                    vector_input(key_a, tx_1, 0, false),
+                ],
+                "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+            ),
+            (
+                "Simple send: two inputs from the same transaction",
+                vec![
+                    vector_input(key_a, tx_1, 3, false),
+                    vector_input(key_b, tx_1, 7, false),
+                ],
+                "79e71baa2ba3fc66396de3a04f168c7bf24d6870ec88ca877754790c1db357b6",
+            ),
+            (
+                "Single recipient: multiple UTXOs from the same public key",
+                vec![
+                    vector_input(key_a, tx_1, 0, false),
+                    vector_input(key_a, tx_2, 0, false),
+                ],
+                "548ae55c8eec1e736e8d3e520f011f1f42a56d166116ad210b3937599f87f566",
+            ),
+            (
+                "Single recipient: taproot only inputs with even y-values",
+                vec![
+                    vector_input(key_a, tx_1, 0, true),
+                    vector_input(key_c, tx_2, 0, true),
+                ],
+                "de88bea8e7ffc9ce1af30d1132f910323c505185aec8eae361670421e749a1fb",
+            ),
+        ];
+        for (name, inputs, output) in cases {
+            let mut script = vec![0x51, 0x20];
+            script.extend_from_slice(&hex::decode(output).unwrap());
+            let found = receiver.scan(&inputs, &[script]);
+            assert_eq!(found.len(), 1, "{name}");
+            assert_eq!(found[0].vout, 0, "{name}");
+        }
+        let found = receiver.scan(
+            &[
+                vector_input(key_a, tx_1, 0, false),
+                vector_input(key_b, tx_2, 0, false),
+            ],
+            &[{
+                let mut script = vec![0x51, 0x20];
+                script.extend_from_slice(&hex32(
+                    "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+                ));
+                script
+            }],
+        );
+        assert_eq!(
+            hex::encode(found[0].tweak),
+            "f438b40179a3c4262de12986c0e6cce0634007cdc79c1dcd3e20b9ebc2e7eef6"
+        );
+    }
+
+    /// Sender side of BIP352 for one P2WPKH input, to check the receiver finds the outputs
+    fn send(
+        input_secret: u8,
+        outpoint: &TxInput,
+        to: &SilentPaymentAddress,
+        count: u32,
+    ) -> Vec<Vec<u8>> {
+        let secp = Secp256k1::new();
+        let a = SecretKey::from_slice(&secret(input_secret)).unwrap();
+        let mut preimage = outpoint.txid.to_vec();
+        preimage.extend_from_slice(&outpoint.vout.to_le_bytes());
+        preimage.extend_from_slice(&PublicKey::from_secret_key(&secp, &a).serialize());
+        let input_hash = Scalar::from_be_bytes(tagged_hash("BIP0352/Inputs", &preimage)).unwrap();
+        let shared = PublicKey::from_slice(&to.scan_pubkey)
+            .unwrap()
+            .mul_tweak(&secp, &Scalar::from(a.mul_tweak(&input_hash).unwrap()))
+            .unwrap();
+        let spend = PublicKey::from_slice(&to.spend_pubkey).unwrap();
+        (0..count)
+            .map(|k| {
+                let mut message = shared.serialize().to_vec();
+                message.extend_from_slice(&k.to_be_bytes());
+                let t = Scalar::from_be_bytes(tagged_hash("BIP0352/SharedSecret", &message));
+                let p = spend.add_exp_tweak(&secp, &t.unwrap()).unwrap();
+                let mut script = vec![0x51, 0x20];
+                script.extend_from_slice(&p.x_only_public_key().0.serialize());
+                script
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_payments() {
+        let receiver = Receiver::new(&secret(7), &public_key(&secret(9)).unwrap()).unwrap();
+        let input_key = public_key(&secret(5)).unwrap();
+        let mut prevout_script = vec![0x00, 0x14];
+        prevout_script.extend_from_slice(&hash160(&input_key));
+        let input = TxInput {
+            txid: [0xab; 32],
+            vout: 1,
+            witness: vec![vec![0x30; 71], input_key.to_vec()],
+            prevout_script,
+            ..Default::default()
+        };
+        let paid = send(5, &input, &receiver.address(false), 2);
+        let change = {
+            let mut s = vec![0x51, 0x20];
+            s.extend_from_slice(&public_key(&secret(1)).unwrap()[1..]);
+            s
+        };
+        let outputs = vec![change.clone(), paid[1].clone(), paid[0].clone()];
+        let found = receiver.scan(std::slice::from_ref(&input), &outputs);
+        assert_eq!(found.iter().map(|f| f.vout).collect::<Vec<_>>(), [2, 1]);
+
+        // Output key = spend key + tweak * G
+        let tweak = Scalar::from_be_bytes(found[0].tweak).unwrap();
+        let output_secret = SecretKey::from_slice(&secret(9))
+            .unwrap()
+            .add_tweak(&tweak)
+            .unwrap();
+        assert_eq!(
+            public_key(&output_secret.secret_bytes()).unwrap()[1..],
+            paid[0][2..]
+        );
+
+        // Another receiver sees nothing; neither does a transaction without taproot outputs
+        let other = Receiver::new(&secret(8), &public_key(&secret(9)).unwrap()).unwrap();
+        assert!(
+            other
+                .scan(std::slice::from_ref(&input), &outputs)
+                .is_empty()
+        );
+        assert!(
+            receiver
+                .scan(std::slice::from_ref(&input), &[vec![0x00, 0x14, 0, 0]])
+                .is_empty()
+        );
+
+        // An uncompressed key in a P2WPKH witness is skipped, not summed with the eligible input
+        let uncompressed = PublicKey::from_slice(&public_key(&secret(6)).unwrap())
+            .unwrap()
+            .serialize_uncompressed();
+        let mut prevout_script = vec![0x00, 0x14];
+        prevout_script.extend_from_slice(&hash160(&uncompressed));
+        let skipped = TxInput {
+            txid: [0xcd; 32],
+            vout: 0,
+            witness: vec![vec![0x30; 71], uncompressed.to_vec()],
+            prevout_script,
+            ..Default::default()
+        };
+        let found = receiver.scan(&[input, skipped.clone()], &outputs);
+        assert_eq!(found.iter().map(|f| f.vout).collect::<Vec<_>>(), [2, 1]);
+        assert!(receiver.scan(&[skipped], &outputs).is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! Sync progress history (`<data_dir>/sync-history.jsonl`, `blvm sync --history`)
+//!
+//! The running node appends a sample every few minutes; `blvm sync` reads them back to draw
+//! catch-up progress over the last day without querying the node.
+
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// History file name in the data directory
+pub const HISTORY_FILE: &str = "sync-history.jsonl";
+
+/// Samples older than this are dropped when the file is compacted
+pub const RETENTION_SECS: u64 = 48 * 3600;
+
+/// Compact the file once it holds this many lines
+const COMPACT_LINES: usize = 2000;
+
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One sync snapshot
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Sample {
+    /// Unix time
+    pub time: u64,
+    pub blocks: u64,
+    pub headers: u64,
+    pub progress: f64,
+}
+
+fn parse(content: &str) -> Vec<Sample> {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Samples taken at or after `since`, oldest first
+pub fn load(data_dir: &Path, since: u64) -> Vec<Sample> {
+    let content = std::fs::read_to_string(data_dir.join(HISTORY_FILE)).unwrap_or_default();
+    parse(&content)
+        .into_iter()
+        .filter(|s| s.time >= since)
+        .collect()
+}
+
+/// Append a sample, dropping samples past the retention window when the file grows large
+pub fn append(data_dir: &Path, sample: &Sample) -> io::Result<()> {
+    let path = data_dir.join(HISTORY_FILE);
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    if content.lines().count() >= COMPACT_LINES {
+        let cutoff = sample.time.saturating_sub(RETENTION_SECS);
+        let mut kept = String::new();
+        for s in parse(&content).into_iter().filter(|s| s.time >= cutoff) {
+            kept.push_str(&serde_json::to_string(&s)?);
+            kept.push('\n');
+        }
+        let tmp = path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, kept)?;
+        std::fs::rename(&tmp, &path)?;
+    }
+    let mut line = serde_json::to_string(sample)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?
+        .write_all(line.as_bytes())
+}
+
+/// Average blocks per hour between the first and last sample
+pub fn blocks_per_hour(samples: &[Sample]) -> Option<f64> {
+    let (first, last) = (samples.first()?, samples.last()?);
+    let secs = last.time.checked_sub(first.time).filter(|&s| s > 0)?;
+    Some(last.blocks.saturating_sub(first.blocks) as f64 * 3600.0 / secs as f64)
+}
+
+/// Block height over `[start, end)` split into `width` equal columns: the highest height seen
+/// in each column, carried forward through columns without samples
+pub fn height_columns(samples: &[Sample], start: u64, end: u64, width: usize) -> Vec<Option<u64>> {
+    let mut columns = vec![None; width];
+    if width == 0 || end <= start {
+        return columns;
+    }
+    let span = end - start;
+    for s in samples.iter().filter(|s| s.time >= start && s.time < end) {
+        let i = ((s.time - start) as u128 * width as u128 / span as u128) as usize;
+        columns[i] = Some(columns[i].map_or(s.blocks, |h: u64| h.max(s.blocks)));
+    }
+    let mut last = None;
+    for column in &mut columns {
+        match column {
+            Some(h) => last = Some(*h),
+            None => *column = last,
+        }
+    }
+    columns
+}
+
+/// Sparkline scaled between the lowest and highest value; blank where there is no data
+pub fn sparkline(values: &[Option<u64>]) -> String {
+    let present = values.iter().flatten();
+    let (Some(&min), Some(&max)) = (present.clone().min(), present.max()) else {
+        return " ".repeat(values.len());
+    };
+    values
+        .iter()
+        .map(|v| match v {
+            None => ' ',
+            Some(_) if max == min => SPARK_CHARS[SPARK_CHARS.len() - 1],
+            Some(v) => {
+                let level =
+                    (v - min) as u128 * (SPARK_CHARS.len() - 1) as u128 / (max - min) as u128;
+                SPARK_CHARS[level as usize]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: u64, blocks: u64) -> Sample {
+        Sample {
+            time,
+            blocks,
+            headers: 1000,
+            progress: blocks as f64 / 1000.0,
+        }
+    }
+
+    #[test]
+    fn columns_and_sparkline() {
+        let samples = [
+            sample(0, 0),
+            sample(10, 100),
+            sample(35, 700),
+            sample(39, 800),
+        ];
+        let columns = height_columns(&samples, 0, 40, 4);
+        assert_eq!(columns, [Some(0), Some(100), Some(100), Some(800)]);
+        assert_eq!(sparkline(&columns), "▁▁▁█");
+        assert_eq!(sparkline(&[None, Some(5), Some(5)]), " ██");
+        assert_eq!(sparkline(&[None, None]), "  ");
+    }
+
+    #[test]
+    fn rate_over_window() {
+        assert_eq!(
+            blocks_per_hour(&[sample(0, 100), sample(1800, 400)]),
+            Some(600.0)
+        );
+        assert_eq!(blocks_per_hour(&[sample(0, 100)]), None);
+    }
+
+    #[test]
+    fn appends_and_filters_by_time() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for (time, blocks) in [(100, 1), (200, 2), (300, 3)] {
+            append(dir.path(), &sample(time, blocks)).unwrap();
+        }
+        let recent = load(dir.path(), 200);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].blocks, 2);
+    }
+}
@@ -0,0 +1,249 @@
+//! Poll statistics for the binary's background tasks (`dumptasks`, `blvm tasks`)
+//!
+//! Tasks started with [`spawn`] are registered under a name and timed on every poll. A task
+//! whose current poll has run longer than the stall threshold is blocking its worker thread,
+//! which is what an event-loop stall looks like from the inside. Tasks blvm-node spawns itself
+//! are not registered; the runtime totals cover them. Per-task traces are what tokio-console
+//! (`debug-runtime` feature) is for.
+
+use serde_json::{Value, json};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// A poll running longer than this is reported as a stall
+pub const DEFAULT_STALL: Duration = Duration::from_millis(500);
+
+/// Counters for one named task, updated by the task's own polls
+#[derive(Debug)]
+pub struct TaskStats {
+    pub name: String,
+    spawned: Instant,
+    polls: AtomicU64,
+    busy_nanos: AtomicU64,
+    max_poll_nanos: AtomicU64,
+    /// Nanoseconds after `spawned` at which the current poll began; 0 between polls
+    poll_started: AtomicU64,
+    /// Nanoseconds after `spawned` at which the last poll ended
+    last_poll_end: AtomicU64,
+    done: AtomicBool,
+}
+
+impl TaskStats {
+    fn new(name: &str) -> Self {
+        TaskStats {
+            name: name.to_string(),
+            spawned: Instant::now(),
+            polls: AtomicU64::new(0),
+            busy_nanos: AtomicU64::new(0),
+            max_poll_nanos: AtomicU64::new(0),
+            poll_started: AtomicU64::new(0),
+            last_poll_end: AtomicU64::new(0),
+            done: AtomicBool::new(false),
+        }
+    }
+
+    fn elapsed_nanos(&self) -> u64 {
+        // Never 0, so 0 can mean "not in a poll"
+        (self.spawned.elapsed().as_nanos() as u64).max(1)
+    }
+
+    /// Snapshot as reported by `dumptasks`
+    pub fn to_json(&self, stall: Duration) -> Value {
+        let now = self.elapsed_nanos();
+        let started = self.poll_started.load(Ordering::Relaxed);
+        let polling_for = (started != 0).then(|| now.saturating_sub(started));
+        let idle_for = match polling_for {
+            Some(_) => 0,
+            None => now.saturating_sub(self.last_poll_end.load(Ordering::Relaxed)),
+        };
+        let ms = |nanos: u64| nanos as f64 / 1e6;
+        let polls = self.polls.load(Ordering::Relaxed);
+        let busy = self.busy_nanos.load(Ordering::Relaxed);
+        json!({
+            "name": self.name,
+            "state": if self.done.load(Ordering::Relaxed) {
+                "done"
+            } else if polling_for.is_some() {
+                "polling"
+            } else {
+                "idle"
+            },
+            "age_secs": now / 1_000_000_000,
+            "polls": polls,
+            "busy_ms": ms(busy),
+            "mean_poll_ms": ms(busy.checked_div(polls).unwrap_or(0)),
+            "max_poll_ms": ms(self.max_poll_nanos.load(Ordering::Relaxed)),
+            "polling_ms": polling_for.map(ms),
+            "idle_ms": ms(idle_for),
+            "stalled": polling_for.is_some_and(|nanos| nanos >= stall.as_nanos() as u64),
+        })
+    }
+}
+
+/// Future wrapper that times each poll into its task's [`TaskStats`]
+pub struct Instrumented<F> {
+    inner: Pin<Box<F>>,
+    stats: Arc<TaskStats>,
+}
+
+impl<F: Future> Future for Instrumented<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let stats = self.stats.clone();
+        let started = stats.elapsed_nanos();
+        stats.poll_started.store(started, Ordering::Relaxed);
+        let result = self.inner.as_mut().poll(cx);
+        let ended = stats.elapsed_nanos();
+        let took = ended.saturating_sub(started);
+        stats.poll_started.store(0, Ordering::Relaxed);
+        stats.last_poll_end.store(ended, Ordering::Relaxed);
+        stats.polls.fetch_add(1, Ordering::Relaxed);
+        stats.busy_nanos.fetch_add(took, Ordering::Relaxed);
+        stats.max_poll_nanos.fetch_max(took, Ordering::Relaxed);
+        if result.is_ready() {
+            stats.done.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+/// Named tasks, in spawn order
+#[derive(Debug, Default)]
+pub struct TaskRegistry {
+    tasks: Mutex<Vec<Arc<TaskStats>>>,
+}
+
+impl TaskRegistry {
+    /// Wrap `future` so its polls are recorded under `name`
+    pub fn instrument<F: Future>(&self, name: &str, future: F) -> Instrumented<F> {
+        let stats = Arc::new(TaskStats::new(name));
+        if let Ok(mut tasks) = self.tasks.lock() {
+            // Finished tasks are kept only until the next spawn
+            tasks.retain(|task| !task.done.load(Ordering::Relaxed));
+            tasks.push(stats.clone());
+        }
+        Instrumented {
+            inner: Box::pin(future),
+            stats,
+        }
+    }
+
+    /// `dumptasks` result: registered tasks, longest current poll first, then by busy time.
+    /// `stalled_only` keeps the tasks whose current poll is over `stall`.
+    pub fn dump(&self, stall: Duration, stalled_only: bool) -> Value {
+        let mut tasks: Vec<Value> = self
+            .tasks
+            .lock()
+            .map(|tasks| tasks.iter().map(|task| task.to_json(stall)).collect())
+            .unwrap_or_default();
+        tasks.retain(|task| !stalled_only || task["stalled"] == true);
+        let key = |task: &Value, field: &str| task[field].as_f64().unwrap_or(0.0);
+        tasks.sort_by(|a, b| {
+            key(b, "polling_ms")
+                .total_cmp(&key(a, "polling_ms"))
+                .then(key(b, "busy_ms").total_cmp(&key(a, "busy_ms")))
+        });
+        json!({
+            "stall_threshold_ms": stall.as_millis() as u64,
+            "runtime": runtime_metrics(),
+            "tasks": tasks,
+        })
+    }
+}
+
+/// Worker count and queue depth of the current tokio runtime, if any
+fn runtime_metrics() -> Value {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            let metrics = handle.metrics();
+            json!({
+                "workers": metrics.num_workers(),
+                "alive_tasks": metrics.num_alive_tasks(),
+                "global_queue_depth": metrics.global_queue_depth(),
+            })
+        }
+        Err(_) => Value::Null,
+    }
+}
+
+/// The process-wide registry `spawn` records into
+pub fn registry() -> &'static TaskRegistry {
+    static REGISTRY: OnceLock<TaskRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(TaskRegistry::default)
+}
+
+/// `tokio::spawn` with the task registered under `name` for `dumptasks`
+pub fn spawn<F>(name: &str, future: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(registry().instrument(name, future))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn records_polls_and_finished_tasks() {
+        let registry = TaskRegistry::default();
+        let task = registry.instrument("sleeper", async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            7
+        });
+        assert_eq!(task.await, 7);
+        let dump = registry.dump(DEFAULT_STALL, false);
+        let tasks = dump["tasks"].as_array().unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0]["name"], "sleeper");
+        assert_eq!(tasks[0]["state"], "done");
+        assert!(tasks[0]["polls"].as_u64().unwrap() >= 2);
+        assert_eq!(tasks[0]["stalled"], false);
+        assert!(dump["runtime"]["workers"].as_u64().unwrap() >= 1);
+
+        // A new spawn drops the finished task
+        let _pending = registry.instrument("next", std::future::pending::<()>());
+        assert_eq!(
+            registry.dump(DEFAULT_STALL, false)["tasks"][0]["name"],
+            "next"
+        );
+    }
+
+    #[test]
+    fn a_blocking_poll_is_reported_as_stalled() {
+        let registry = Arc::new(TaskRegistry::default());
+        let (entered_tx, entered) = std::sync::mpsc::channel();
+        let (release, release_rx) = std::sync::mpsc::channel::<()>();
+        let blocking = registry.instrument("blocker", async move {
+            entered_tx.send(()).unwrap();
+            // Blocks its thread inside a poll, as a stalled task does
+            release_rx.recv().unwrap();
+        });
+        let worker = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(blocking)
+        });
+        entered.recv().unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        let dump = registry.dump(Duration::from_millis(10), true);
+        assert_eq!(dump["tasks"][0]["name"], "blocker");
+        assert_eq!(dump["tasks"][0]["state"], "polling");
+        assert!(dump["tasks"][0]["polling_ms"].as_f64().unwrap() >= 10.0);
+        release.send(()).unwrap();
+        worker.join().unwrap();
+        assert!(
+            registry.dump(Duration::from_millis(10), true)["tasks"]
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
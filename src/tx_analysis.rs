@@ -0,0 +1,453 @@
+//! Pre-broadcast transaction checks (`blvm tx analyze`)
+//!
+//! Size, weight and sigop cost as consensus counts them, dust and RBF signaling per output and
+//! input, and the node's default relay policy (`IsStandardTx` and friends) as warnings. Fees and
+//! the P2SH/witness part of the sigop cost need the spent outputs.
+
+use crate::decode::Transaction;
+use crate::hash::to_display_hex;
+use crate::script::{ScriptOp, classify, decode_script};
+
+/// Largest standard transaction weight
+pub const MAX_STANDARD_TX_WEIGHT: usize = 400_000;
+/// Largest standard sigop cost per transaction
+pub const MAX_STANDARD_TX_SIGOPS_COST: u64 = 16_000;
+/// Default dust relay fee (sat/kvB)
+pub const DUST_RELAY_FEE: u64 = 3_000;
+/// Default minimum relay fee rate (sat/vB)
+pub const MIN_RELAY_FEE_RATE: f64 = 1.0;
+/// Default `sendrawtransaction` maxfeerate (0.10 BTC/kvB, in sat/vB)
+pub const MAX_FEE_RATE: f64 = 10_000.0;
+
+const MAX_STANDARD_SCRIPTSIG_SIZE: usize = 1650;
+const MAX_STANDARD_VERSION: i32 = 3;
+const MIN_STANDARD_TX_NONWITNESS_SIZE: usize = 65;
+const MAX_P2SH_SIGOPS: u64 = 15;
+const MAX_STANDARD_P2WSH_SCRIPT_SIZE: usize = 3600;
+const MAX_STANDARD_P2WSH_STACK_ITEMS: usize = 100;
+const WITNESS_SCALE_FACTOR: u64 = 4;
+
+/// A spent output (value in satoshis)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prevout {
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+impl std::str::FromStr for Prevout {
+    type Err = String;
+
+    /// `<satoshis>:<scriptPubKey hex>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, script) = s
+            .split_once(':')
+            .ok_or_else(|| format!("prevout '{s}' is not <satoshis>:<scriptPubKey hex>"))?;
+        Ok(Self {
+            value: value
+                .parse()
+                .map_err(|_| format!("prevout value '{value}' is not a number of satoshis"))?,
+            script_pubkey: hex::decode(script)
+                .map_err(|_| format!("prevout script '{script}' is not hex"))?,
+        })
+    }
+}
+
+/// One output with the value below which it is dust
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputInfo {
+    pub value: u64,
+    /// `script::classify` type
+    pub kind: &'static str,
+    /// 0 for unspendable outputs, which are never dust
+    pub dust_threshold: u64,
+}
+
+impl OutputInfo {
+    pub fn is_dust(&self) -> bool {
+        self.value < self.dust_threshold
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    pub txid: String,
+    pub wtxid: String,
+    /// Serialized size with witness
+    pub size: usize,
+    /// Serialized size without witness
+    pub base_size: usize,
+    pub weight: usize,
+    pub vsize: usize,
+    pub sigop_cost: u64,
+    /// Whether `sigop_cost` includes P2SH and witness sigops (needs the prevouts)
+    pub sigops_complete: bool,
+    /// Some input signals BIP125 replaceability
+    pub rbf: bool,
+    pub inputs: usize,
+    pub outputs: Vec<OutputInfo>,
+    /// Satoshis; `None` without prevouts
+    pub fee: Option<u64>,
+    /// Relay policy and sanity problems
+    pub warnings: Vec<String>,
+}
+
+impl Analysis {
+    /// sat/vB
+    pub fn fee_rate(&self) -> Option<f64> {
+        self.fee.map(|fee| fee as f64 / self.vsize.max(1) as f64)
+    }
+}
+
+/// Sigops in a script: `OP_CHECKSIG(VERIFY)` count 1; `OP_CHECKMULTISIG(VERIFY)` counts the
+/// preceding `OP_1..OP_16` key count when `accurate`, else 20
+pub fn count_sigops(script: &[u8], accurate: bool) -> u64 {
+    let ops = decode_script(script).unwrap_or_default();
+    let mut count = 0;
+    let mut last: Option<&ScriptOp<'_>> = None;
+    for op in &ops {
+        if let ScriptOp::Op(opcode) = op {
+            match opcode {
+                0xac | 0xad => count += 1,
+                0xae | 0xaf => {
+                    count += match last {
+                        Some(ScriptOp::Op(n @ 0x51..=0x60)) if accurate => (*n - 0x50) as u64,
+                        _ => 20,
+                    }
+                }
+                _ => {}
+            }
+        }
+        last = Some(op);
+    }
+    count
+}
+
+/// Witness version and program of a segwit output script
+fn witness_program(script: &[u8]) -> Option<(u8, &[u8])> {
+    match script {
+        [version @ (0x00 | 0x51..=0x60), len, program @ ..]
+            if (2..=40).contains(len) && program.len() == *len as usize =>
+        {
+            Some((if *version == 0 { 0 } else { version - 0x50 }, program))
+        }
+        _ => None,
+    }
+}
+
+/// Last push of a push-only script (the P2SH redeem script)
+fn last_push(script: &[u8]) -> Option<&[u8]> {
+    match decode_script(script).ok()?.last()? {
+        ScriptOp::Push { data, .. } => Some(data),
+        ScriptOp::Op(_) => None,
+    }
+}
+
+fn is_push_only(script: &[u8]) -> bool {
+    decode_script(script).is_ok_and(|ops| {
+        ops.iter().all(|op| match op {
+            ScriptOp::Push { .. } => true,
+            // OP_1NEGATE, OP_1..OP_16
+            ScriptOp::Op(opcode) => *opcode == 0x4f || (0x51..=0x60).contains(opcode),
+        })
+    })
+}
+
+/// Core's dust threshold at [`DUST_RELAY_FEE`]: the fee to create and later spend the output
+pub fn dust_threshold(script: &[u8]) -> u64 {
+    if script.first() == Some(&0x6a) || script.len() > 10_000 {
+        return 0;
+    }
+    let compact_size = match script.len() {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        _ => 5,
+    };
+    let output_size = 8 + compact_size + script.len() as u64;
+    // Outpoint, sequence and an estimated signature: discounted when spent through a witness
+    let spend_size = if witness_program(script).is_some() {
+        32 + 4 + 1 + 107 / 4 + 4
+    } else {
+        32 + 4 + 1 + 107 + 4
+    };
+    (output_size + spend_size) * DUST_RELAY_FEE / 1000
+}
+
+/// Sigop cost of spending `prevout` with input `index`, and any policy problems with the spend
+fn input_sigops(tx: &Transaction, index: usize, prevout: &Prevout) -> (u64, Vec<String>) {
+    let input = &tx.inputs[index];
+    let mut cost = 0;
+    let mut warnings = Vec::new();
+    let mut program = witness_program(&prevout.script_pubkey);
+    if classify(&prevout.script_pubkey) == "scripthash"
+        && let Some(redeem) = last_push(&input.script_sig)
+    {
+        let sigops = count_sigops(redeem, true);
+        cost += sigops * WITNESS_SCALE_FACTOR;
+        if sigops > MAX_P2SH_SIGOPS {
+            warnings.push(format!(
+                "input {index}: P2SH redeem script has {sigops} sigops (standard limit {MAX_P2SH_SIGOPS})"
+            ));
+        }
+        // P2SH-wrapped segwit
+        program = program.or(witness_program(redeem));
+    }
+    match program {
+        Some((0, key_hash)) if key_hash.len() == 20 => cost += 1,
+        Some((0, script_hash)) if script_hash.len() == 32 => {
+            if let Some((script, stack)) = input.witness.split_last() {
+                cost += count_sigops(script, true);
+                if script.len() > MAX_STANDARD_P2WSH_SCRIPT_SIZE {
+                    warnings.push(format!(
+                        "input {index}: witness script is {} bytes (standard limit {MAX_STANDARD_P2WSH_SCRIPT_SIZE})",
+                        script.len()
+                    ));
+                }
+                if stack.len() > MAX_STANDARD_P2WSH_STACK_ITEMS {
+                    warnings.push(format!(
+                        "input {index}: {} witness stack items (standard limit {MAX_STANDARD_P2WSH_STACK_ITEMS})",
+                        stack.len()
+                    ));
+                }
+            }
+        }
+        _ => {}
+    }
+    (cost, warnings)
+}
+
+/// Analyze `tx`; `prevouts` (one per input, in order) add the fee, the full sigop cost and the
+/// checks that depend on the spent outputs
+pub fn analyze(tx: &Transaction, prevouts: Option<&[Prevout]>) -> Analysis {
+    let base_size = tx.encode(false).len();
+    let size = tx.encode(true).len();
+    let weight = tx.weight();
+    let mut warnings = Vec::new();
+
+    let legacy: u64 = tx
+        .inputs
+        .iter()
+        .map(|i| count_sigops(&i.script_sig, false))
+        .chain(
+            tx.outputs
+                .iter()
+                .map(|o| count_sigops(&o.script_pubkey, false)),
+        )
+        .sum();
+    let mut sigop_cost = legacy * WITNESS_SCALE_FACTOR;
+    let prevouts = prevouts.filter(|p| p.len() == tx.inputs.len() && !tx.is_coinbase());
+    let mut fee = None;
+    if let Some(prevouts) = prevouts {
+        for (index, prevout) in prevouts.iter().enumerate() {
+            let (cost, problems) = input_sigops(tx, index, prevout);
+            sigop_cost += cost;
+            warnings.extend(problems);
+        }
+        let spent: u64 = prevouts.iter().map(|p| p.value).sum();
+        let created: u64 = tx.outputs.iter().map(|o| o.value).sum();
+        match spent.checked_sub(created) {
+            Some(paid) => fee = Some(paid),
+            None => warnings.push(format!(
+                "outputs ({created} sat) exceed the inputs ({spent} sat)"
+            )),
+        }
+    }
+
+    let outputs: Vec<OutputInfo> = tx
+        .outputs
+        .iter()
+        .map(|o| OutputInfo {
+            value: o.value,
+            kind: classify(&o.script_pubkey),
+            dust_threshold: dust_threshold(&o.script_pubkey),
+        })
+        .collect();
+
+    if tx.is_coinbase() {
+        warnings.push("coinbase transactions cannot be relayed".to_string());
+    }
+    if !(1..=MAX_STANDARD_VERSION).contains(&tx.version) {
+        warnings.push(format!("version {} is non-standard", tx.version));
+    }
+    if weight > MAX_STANDARD_TX_WEIGHT {
+        warnings.push(format!(
+            "weight {weight} exceeds the standard limit {MAX_STANDARD_TX_WEIGHT}"
+        ));
+    }
+    if base_size < MIN_STANDARD_TX_NONWITNESS_SIZE {
+        warnings.push(format!(
+            "non-witness size {base_size} is below {MIN_STANDARD_TX_NONWITNESS_SIZE} bytes (tx-size-small)"
+        ));
+    }
+    if sigop_cost > MAX_STANDARD_TX_SIGOPS_COST {
+        warnings.push(format!(
+            "sigop cost {sigop_cost} exceeds the standard limit {MAX_STANDARD_TX_SIGOPS_COST}"
+        ));
+    }
+    for (index, input) in tx.inputs.iter().enumerate() {
+        if input.script_sig.len() > MAX_STANDARD_SCRIPTSIG_SIZE {
+            warnings.push(format!(
+                "input {index}: scriptSig is {} bytes (standard limit {MAX_STANDARD_SCRIPTSIG_SIZE})",
+                input.script_sig.len()
+            ));
+        }
+        if !tx.is_coinbase() && !is_push_only(&input.script_sig) {
+            warnings.push(format!("input {index}: scriptSig is not push-only"));
+        }
+    }
+    for (index, output) in outputs.iter().enumerate() {
+        if output.kind == "nonstandard" {
+            warnings.push(format!("output {index}: non-standard script"));
+        }
+        if output.is_dust() {
+            warnings.push(format!(
+                "output {index}: {} sat is dust (threshold {} sat)",
+                output.value, output.dust_threshold
+            ));
+        }
+    }
+
+    let vsize = weight.div_ceil(4);
+    if let Some(fee) = fee {
+        let rate = fee as f64 / vsize.max(1) as f64;
+        if rate < MIN_RELAY_FEE_RATE {
+            warnings.push(format!(
+                "fee rate {rate:.2} sat/vB is below the minimum relay fee rate ({MIN_RELAY_FEE_RATE} sat/vB)"
+            ));
+        } else if rate > MAX_FEE_RATE {
+            warnings.push(format!(
+                "fee rate {rate:.0} sat/vB is above sendrawtransaction's default maxfeerate ({MAX_FEE_RATE} sat/vB)"
+            ));
+        }
+    }
+
+    Analysis {
+        txid: to_display_hex(&tx.txid()),
+        wtxid: to_display_hex(&tx.wtxid()),
+        size,
+        base_size,
+        weight,
+        vsize,
+        sigop_cost,
+        sigops_complete: prevouts.is_some() || tx.is_coinbase(),
+        // BIP125: any input sequence below 0xfffffffe
+        rbf: tx.inputs.iter().any(|i| i.sequence < 0xffff_fffe),
+        inputs: tx.inputs.len(),
+        outputs,
+        fee,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::{TxIn, TxOut};
+
+    fn p2wpkh() -> Vec<u8> {
+        let mut script = vec![0x00, 0x14];
+        script.extend([7; 20]);
+        script
+    }
+
+    fn spend(outputs: Vec<TxOut>) -> Transaction {
+        Transaction {
+            version: 2,
+            inputs: vec![TxIn {
+                prev_txid: [1; 32],
+                prev_vout: 0,
+                script_sig: Vec::new(),
+                sequence: 0xffff_fffd,
+                witness: vec![vec![0x30; 72], vec![0x02; 33]],
+            }],
+            outputs,
+            lock_time: 0,
+        }
+    }
+
+    #[test]
+    fn segwit_spend_with_dust_and_fee() {
+        let tx = spend(vec![
+            TxOut {
+                value: 50_000,
+                script_pubkey: p2wpkh(),
+            },
+            TxOut {
+                value: 200,
+                script_pubkey: p2wpkh(),
+            },
+        ]);
+        let prevout = Prevout {
+            value: 51_000,
+            script_pubkey: p2wpkh(),
+        };
+        let analysis = analyze(&tx, Some(&[prevout]));
+        assert_eq!(analysis.base_size, 113);
+        assert_eq!(analysis.vsize, tx.vsize());
+        assert!(analysis.rbf);
+        assert_eq!(analysis.sigop_cost, 1);
+        assert!(analysis.sigops_complete);
+        assert_eq!(analysis.fee, Some(800));
+        assert_eq!(analysis.outputs[1].dust_threshold, 294);
+        assert!(analysis.outputs[1].is_dust());
+        assert_eq!(analysis.warnings.len(), 1);
+        assert!(analysis.warnings[0].starts_with("output 1: 200 sat is dust"));
+
+        // Without prevouts only the legacy sigops are known and the fee is not
+        let offline = analyze(&tx, None);
+        assert_eq!((offline.fee, offline.sigops_complete), (None, false));
+        assert_eq!(offline.sigop_cost, 0);
+    }
+
+    #[test]
+    fn sigops_and_policy() {
+        // 2-of-3 multisig: 3 accurate, 20 otherwise
+        let mut multisig = vec![0x52];
+        for _ in 0..3 {
+            multisig.push(0x21);
+            multisig.extend([2; 33]);
+        }
+        multisig.extend([0x53, 0xae]);
+        assert_eq!(count_sigops(&multisig, true), 3);
+        assert_eq!(count_sigops(&multisig, false), 20);
+        assert_eq!(dust_threshold(&[0x6a, 0x01, 0x00]), 0);
+        // P2PKH: 34-byte output + 148-byte spend at 3 sat/vB
+        let mut p2pkh = vec![0x76, 0xa9, 0x14];
+        p2pkh.extend([0; 20]);
+        p2pkh.extend([0x88, 0xac]);
+        assert_eq!(dust_threshold(&p2pkh), 546);
+
+        let mut tx = spend(vec![TxOut {
+            value: 1000,
+            script_pubkey: vec![0x01],
+        }]);
+        tx.version = 4;
+        tx.inputs[0].script_sig = vec![0xac];
+        tx.inputs[0].sequence = 0xffff_ffff;
+        let prevout = Prevout {
+            value: 900,
+            script_pubkey: p2wpkh(),
+        };
+        let analysis = analyze(&tx, Some(&[prevout]));
+        assert!(!analysis.rbf);
+        assert_eq!(analysis.fee, None);
+        let warnings = analysis.warnings.join("\n");
+        for expected in [
+            "exceed the inputs",
+            "version 4",
+            "tx-size-small",
+            "not push-only",
+            "output 0: non-standard",
+        ] {
+            assert!(warnings.contains(expected), "{expected}: {warnings}");
+        }
+
+        assert_eq!(
+            "546:0014".parse::<Prevout>(),
+            Ok(Prevout {
+                value: 546,
+                script_pubkey: vec![0x00, 0x14]
+            })
+        );
+        assert!("abc".parse::<Prevout>().is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! UTXO entry size distribution (`blvm db stats`)
+//!
+//! `gettxoutsetinfo` only reports totals, so the distribution comes from a sample: outputs of
+//! recent blocks that `gettxout` still reports unspent. Sizes follow the `bogosize` formula
+//! (outpoint, height, amount and script length, plus the scriptPubKey) so they line up with the
+//! node's total.
+
+/// Bytes of a `bogosize` entry besides the scriptPubKey
+pub const ENTRY_OVERHEAD: u64 = 50;
+
+/// Lower bounds (bytes) of the buckets after the first; P2WPKH entries are 72 bytes, P2SH 73,
+/// P2PKH 75, P2WSH and P2TR 84, compressed P2PK 85
+pub const SIZE_BOUNDS: &[u64] = &[72, 74, 76, 84, 86, 128, 256];
+
+/// `bogosize` of an unspent output with this scriptPubKey (hex)
+pub fn entry_size(script_pubkey_hex: &str) -> u64 {
+    ENTRY_OVERHEAD + script_pubkey_hex.len() as u64 / 2
+}
+
+/// One histogram row: `[from, to)` bytes (`to` is `None` for the top bucket)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Row {
+    pub from: u64,
+    pub to: Option<u64>,
+    pub count: u64,
+}
+
+/// Sampled entry sizes
+#[derive(Debug, Clone, Default)]
+pub struct SizeSample {
+    sizes: Vec<u64>,
+}
+
+impl SizeSample {
+    pub fn add(&mut self, size: u64) {
+        self.sizes.push(size);
+    }
+
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// Nearest-rank percentile (`p` in 1-100); `None` for an empty sample
+    pub fn percentile(&self, p: u64) -> Option<u64> {
+        let mut sorted = self.sizes.clone();
+        sorted.sort_unstable();
+        let rank = (p.clamp(1, 100) as usize * sorted.len()).div_ceil(100);
+        sorted.get(rank.max(1) - 1).copied()
+    }
+
+    /// Count per [`SIZE_BOUNDS`] bucket, skipping empty ones
+    pub fn rows(&self) -> Vec<Row> {
+        let mut counts = vec![0u64; SIZE_BOUNDS.len() + 1];
+        for size in &self.sizes {
+            counts[SIZE_BOUNDS.iter().take_while(|&&b| *size >= b).count()] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .filter(|(_, count)| *count > 0)
+            .map(|(i, count)| Row {
+                from: if i == 0 { 0 } else { SIZE_BOUNDS[i - 1] },
+                to: SIZE_BOUNDS.get(i).copied(),
+                count,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_and_percentiles_of_common_script_types() {
+        // P2WPKH, P2PKH, P2TR
+        let p2wpkh = entry_size(&format!("0014{}", "00".repeat(20)));
+        let p2pkh = entry_size(&format!("76a914{}88ac", "00".repeat(20)));
+        let p2tr = entry_size(&format!("5120{}", "00".repeat(32)));
+        assert_eq!((p2wpkh, p2pkh, p2tr), (72, 75, 84));
+
+        let mut sample = SizeSample::default();
+        assert_eq!(sample.percentile(50), None);
+        for size in [p2wpkh; 6].into_iter().chain([p2pkh, p2pkh, p2tr, 300]) {
+            sample.add(size);
+        }
+        assert_eq!(sample.len(), 10);
+        assert_eq!(sample.percentile(50), Some(72));
+        assert_eq!(sample.percentile(80), Some(75));
+        assert_eq!(sample.percentile(90), Some(84));
+        assert_eq!(sample.percentile(100), Some(300));
+        assert_eq!(
+            sample.rows(),
+            vec![
+                Row {
+                    from: 72,
+                    to: Some(74),
+                    count: 6
+                },
+                Row {
+                    from: 74,
+                    to: Some(76),
+                    count: 2
+                },
+                Row {
+                    from: 84,
+                    to: Some(86),
+                    count: 1
+                },
+                Row {
+                    from: 256,
+                    to: None,
+                    count: 1
+                },
+            ]
+        );
+    }
+}
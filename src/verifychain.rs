@@ -0,0 +1,228 @@
+//! Online chain verification (`verifychain`, `blvm db verify --online`)
+//!
+//! blvm-node has no `verifychain` of its own: the RPC front answers it and `blvm db verify
+//! --online` calls this directly. Both re-check the most recent blocks through the node's
+//! ordinary RPCs, level by level:
+//!
+//! - 0: every block in the range can be read back
+//! - 1: block hash, link to the previous block, merkle root and proof of work
+//!   (`revalidation::check_block`)
+//! - 2: the header index (`getblockheader`) agrees with the stored block, and the coinbase
+//!   commits to the height once BIP34 is active
+//! - 3: the chainstate (`gettxoutsetinfo`) is at the chain tip
+//!
+//! There is no level 4: the node exposes no way to replay blocks against the UTXO set over RPC,
+//! so higher levels are clamped to 3.
+
+use crate::block_analysis::bip34_prefix;
+use crate::decode::Block;
+use crate::hash::to_display_hex;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Check level when none is given
+pub const DEFAULT_CHECKLEVEL: u32 = 3;
+/// Blocks checked when none is given (0 = all)
+pub const DEFAULT_NBLOCKS: u64 = 6;
+/// Highest level with checks of its own; higher levels are clamped to it
+pub const MAX_CHECKLEVEL: u32 = 3;
+
+/// One problem, with the level of the check that found it
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Problem {
+    pub height: u64,
+    pub hash: String,
+    pub level: u32,
+    pub problem: String,
+}
+
+/// `verifychain` result
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Report {
+    pub valid: bool,
+    pub checklevel: u32,
+    pub nblocks: u64,
+    pub from_height: u64,
+    pub to_height: u64,
+    pub blocks_checked: u64,
+    pub chainstate_checked: bool,
+    pub problems: Vec<Problem>,
+}
+
+impl Report {
+    /// Empty report for `nblocks` (0 = all) below and including `tip`
+    pub fn new(checklevel: u32, nblocks: u64, tip: u64) -> Self {
+        let from_height = match nblocks {
+            0 => 0,
+            n => tip.saturating_sub(n - 1),
+        };
+        Self {
+            valid: true,
+            checklevel: checklevel.min(MAX_CHECKLEVEL),
+            nblocks,
+            from_height,
+            to_height: tip,
+            blocks_checked: 0,
+            chainstate_checked: false,
+            problems: Vec::new(),
+        }
+    }
+
+    pub fn problem(&mut self, height: u64, hash: &str, level: u32, problem: String) {
+        self.valid = false;
+        self.problems.push(Problem {
+            height,
+            hash: hash.to_string(),
+            level,
+            problem,
+        });
+    }
+}
+
+/// Level 2: disagreements between the header index entry and the stored block
+///
+/// `parent` is the index entry at the height below, when known: the entry's height must follow
+/// it and the block must link to it. The BIP34 height commitment is checked against the height
+/// the index records for the block.
+pub fn check_index(
+    bytes: &[u8],
+    header: &Value,
+    parent: Option<&Value>,
+    bip34: Option<u32>,
+) -> Vec<String> {
+    let Ok(block) = Block::decode(bytes) else {
+        // Already reported at level 1
+        return Vec::new();
+    };
+    let mut problems = Vec::new();
+    let mut expect = |field: &str, stored: Value| {
+        if let Some(indexed) = header.get(field)
+            && indexed != &stored
+        {
+            problems.push(format!(
+                "header index {field} {indexed} does not match the stored block ({stored})"
+            ));
+        }
+    };
+    let prev_hash = to_display_hex(&block.header.prev_blockhash);
+    expect("hash", to_display_hex(&block.header.hash()).into());
+    expect("previousblockhash", prev_hash.clone().into());
+    expect(
+        "merkleroot",
+        to_display_hex(&block.header.merkle_root).into(),
+    );
+    expect("bits", format!("{:08x}", block.header.bits).into());
+    expect("time", block.header.time.into());
+    expect("nTx", block.transactions.len().into());
+
+    let height = header.get("height").and_then(|v| v.as_u64());
+    if let Some(parent) = parent {
+        let parent_height = parent.get("height").and_then(|v| v.as_u64());
+        if let (Some(height), Some(parent_height)) = (height, parent_height)
+            && height != parent_height + 1
+        {
+            problems.push(format!(
+                "header index height {height} does not follow its parent's ({parent_height})"
+            ));
+        }
+        if let Some(parent_hash) = parent.get("hash").and_then(|v| v.as_str())
+            && parent_hash != prev_hash
+        {
+            problems.push(format!(
+                "stored block links to {prev_hash}, header index parent is {parent_hash}"
+            ));
+        }
+    }
+
+    if let Some(height) = height
+        && bip34.is_some_and(|activation| height >= activation as u64)
+    {
+        let script_sig = block
+            .transactions
+            .first()
+            .and_then(|tx| tx.inputs.first())
+            .map(|input| &input.script_sig);
+        if !script_sig.is_some_and(|s| s.starts_with(&bip34_prefix(height as u32))) {
+            problems.push(format!(
+                "coinbase does not commit to the indexed height {height} (BIP34)"
+            ));
+        }
+    }
+    problems
+}
+
+/// Level 3: why the chainstate is not at the tip, if it is not
+pub fn check_chainstate(txoutset_info: &Value, tip_hash: &str, tip_height: u64) -> Option<String> {
+    let best = txoutset_info.get("bestblock").and_then(|v| v.as_str());
+    let height = txoutset_info.get("height").and_then(|v| v.as_u64());
+    match (best, height) {
+        (Some(best), _) if best != tip_hash => Some(format!(
+            "chainstate is at block {best}, chain tip is {tip_hash}"
+        )),
+        (_, Some(height)) if height != tip_height => Some(format!(
+            "chainstate is at height {height}, chain tip is at {tip_height}"
+        )),
+        (None, None) => Some("gettxoutsetinfo reports no best block".to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_params::ChainParams;
+    use serde_json::json;
+
+    const GENESIS_BLOCK: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    #[test]
+    fn index_and_chainstate_checks() {
+        let params = ChainParams::for_network("mainnet").unwrap();
+        let bytes = hex::decode(GENESIS_BLOCK).unwrap();
+        let header = json!({
+            "height": 0,
+            "merkleroot": params.genesis.merkle_root,
+            "bits": format!("{:08x}", params.genesis.bits),
+            "time": params.genesis.time,
+            "nTx": 1,
+        });
+        assert!(check_index(&bytes, &header, None, None).is_empty());
+
+        // Index entries that disagree with the block's own hash and link
+        let mut wrong = header.clone();
+        wrong["hash"] = json!("00".repeat(32));
+        wrong["previousblockhash"] = json!("11".repeat(32));
+        let problems = check_index(&bytes, &wrong, None, None);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("hash"));
+        assert!(problems[1].contains("previousblockhash"));
+
+        // A parent entry the block does not link to, at a height the entry does not follow
+        let parent = json!({"height": 5, "hash": "22".repeat(32)});
+        let problems = check_index(&bytes, &header, Some(&parent), None);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("height 0 does not follow its parent's (5)"));
+        assert!(problems[1].contains("links to"));
+        let parent = json!({"height": 0, "hash": "00".repeat(32)});
+        let mut child = header.clone();
+        child["height"] = json!(1);
+        assert!(check_index(&bytes, &child, Some(&parent), None).is_empty());
+
+        // The genesis coinbase predates BIP34, and does not commit to height 1 either
+        assert_eq!(check_index(&bytes, &header, None, Some(0)).len(), 1);
+        let problems = check_index(&bytes, &child, None, Some(0));
+        assert!(problems[0].contains("indexed height 1"));
+
+        let info = json!({"bestblock": "aa", "height": 10});
+        assert_eq!(check_chainstate(&info, "aa", 10), None);
+        assert!(check_chainstate(&info, "bb", 10).is_some());
+        assert!(check_chainstate(&info, "aa", 11).is_some());
+
+        let mut report = Report::new(9, 6, 100);
+        assert_eq!((report.checklevel, report.from_height), (3, 95));
+        assert!(report.valid);
+        report.problem(97, "cc", 1, "merkle".into());
+        assert!(!report.valid);
+        assert_eq!(Report::new(3, 0, 100).from_height, 0);
+    }
+}
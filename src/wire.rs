@@ -0,0 +1,164 @@
+//! P2P message envelope decoding (24-byte header + payload)
+//!
+//! Payload decoding and checksum verification belong to blvm-node; this is the framing layer
+//! used by tooling and the fuzz targets.
+
+/// Header size: magic (4) + command (12) + length (4) + checksum (4)
+pub const HEADER_LEN: usize = 24;
+
+/// Largest payload a peer may send (matches Core's MAX_PROTOCOL_MESSAGE_LENGTH)
+pub const MAX_PAYLOAD_LEN: u32 = 4_000_000;
+
+/// Network magic bytes, as they appear on the wire
+pub fn network_magic(network: &str) -> Option<[u8; 4]> {
+    match crate::canonical_network_name(network)? {
+        "mainnet" => Some([0xf9, 0xbe, 0xb4, 0xd9]),
+        "testnet" => Some([0x0b, 0x11, 0x09, 0x07]),
+        "signet" => Some([0x0a, 0x03, 0xcf, 0x40]),
+        "regtest" => Some([0xfa, 0xbf, 0xb5, 0xda]),
+        _ => None,
+    }
+}
+
+/// A framed message; `payload` borrows from the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkMessage<'a> {
+    pub magic: [u8; 4],
+    pub command: String,
+    pub checksum: [u8; 4],
+    pub payload: &'a [u8],
+}
+
+/// Why a frame was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireError {
+    /// Fewer bytes than the header or declared payload need
+    Truncated { needed: usize, available: usize },
+    /// Command is not NUL-padded printable ASCII
+    InvalidCommand,
+    /// Declared payload exceeds [`MAX_PAYLOAD_LEN`]
+    Oversized(u32),
+}
+
+impl std::fmt::Display for WireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WireError::Truncated { needed, available } => {
+                write!(
+                    f,
+                    "truncated message: need {needed} bytes, have {available}"
+                )
+            }
+            WireError::InvalidCommand => f.write_str("invalid command name"),
+            WireError::Oversized(len) => {
+                write!(f, "payload length {len} exceeds {MAX_PAYLOAD_LEN}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+/// Decode one framed message from the start of `bytes`. Returns the message and the
+/// number of bytes consumed.
+pub fn parse_network_message(bytes: &[u8]) -> Result<(NetworkMessage<'_>, usize), WireError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(WireError::Truncated {
+            needed: HEADER_LEN,
+            available: bytes.len(),
+        });
+    }
+    let command_bytes = &bytes[4..16];
+    let name_len = command_bytes
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(command_bytes.len());
+    let (name, padding) = command_bytes.split_at(name_len);
+    if name.is_empty()
+        || !name.iter().all(|b| b.is_ascii_graphic())
+        || padding.iter().any(|&b| b != 0)
+    {
+        return Err(WireError::InvalidCommand);
+    }
+    let len = u32::from_le_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+    if len > MAX_PAYLOAD_LEN {
+        return Err(WireError::Oversized(len));
+    }
+    let total = HEADER_LEN + len as usize;
+    if bytes.len() < total {
+        return Err(WireError::Truncated {
+            needed: total,
+            available: bytes.len(),
+        });
+    }
+    Ok((
+        NetworkMessage {
+            magic: [bytes[0], bytes[1], bytes[2], bytes[3]],
+            command: String::from_utf8_lossy(name).into_owned(),
+            checksum: [bytes[20], bytes[21], bytes[22], bytes[23]],
+            payload: &bytes[HEADER_LEN..total],
+        },
+        total,
+    ))
+}
+
+impl NetworkMessage<'_> {
+    /// Re-encode the frame (inverse of [`parse_network_message`])
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.payload.len());
+        out.extend_from_slice(&self.magic);
+        let mut command = [0u8; 12];
+        command[..self.command.len()].copy_from_slice(self.command.as_bytes());
+        out.extend_from_slice(&command);
+        out.extend_from_slice(&(self.payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(&self.checksum);
+        out.extend_from_slice(self.payload);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mainnet `verack` (empty payload, checksum of sha256d(""))
+    const VERACK: [u8; 24] = [
+        0xf9, 0xbe, 0xb4, 0xd9, b'v', b'e', b'r', b'a', b'c', b'k', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0x5d, 0xf6, 0xe0, 0xe2,
+    ];
+
+    #[test]
+    fn parses_and_reencodes_verack() {
+        let mut stream = VERACK.to_vec();
+        stream.extend_from_slice(&VERACK);
+        let (msg, used) = parse_network_message(&stream).unwrap();
+        assert_eq!(used, HEADER_LEN);
+        assert_eq!(msg.command, "verack");
+        assert_eq!(Some(msg.magic), network_magic("mainnet"));
+        assert!(msg.payload.is_empty());
+        assert_eq!(msg.encode(), VERACK);
+    }
+
+    #[test]
+    fn rejects_malformed_frames() {
+        assert!(matches!(
+            parse_network_message(&VERACK[..10]),
+            Err(WireError::Truncated { .. })
+        ));
+        let mut bad = VERACK;
+        bad[11] = b'x'; // data after the NUL padding
+        assert_eq!(parse_network_message(&bad), Err(WireError::InvalidCommand));
+        let mut big = VERACK;
+        big[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            parse_network_message(&big),
+            Err(WireError::Oversized(u32::MAX))
+        );
+        let mut short = VERACK;
+        short[16] = 8;
+        assert!(matches!(
+            parse_network_message(&short),
+            Err(WireError::Truncated { needed: 32, .. })
+        ));
+    }
+}
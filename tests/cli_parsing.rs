@@ -128,3 +128,21 @@ fn test_advanced_config_options() {
     // Should parse successfully
     let _ = cmd.assert();
 }
+
+/// Test --nolisten moves the P2P listener off public interfaces
+#[test]
+fn test_nolisten_flag() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--nolisten")
+        .arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("--rpc-addr")
+        .arg("127.0.0.1:0")
+        .arg("start")
+        .arg("--dry-run");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("P2P listen address: 127.0.0.1:0"));
+}
@@ -0,0 +1,229 @@
+//! Consensus differential test against Bitcoin Core
+//!
+//! Runs only when `BITCOIND_PATH` points at a `bitcoind` binary (v25+ for `generateblock`
+//! with `submit=false`). Both nodes run on regtest; Core mines the chain, blvm receives the
+//! same blocks, then both get identical seeded mutations of blocks and transactions and must
+//! return identical accept/reject verdicts.
+//!
+//! `DIFF_SEED` (default 1) and `DIFF_CASES` (default 64) control the generated cases.
+
+use blvm::sim::SimRng;
+use serde_json::{Value, json};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+struct Node {
+    child: Child,
+    url: String,
+    auth: Option<(&'static str, &'static str)>,
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Node {
+    /// Full JSON-RPC response (`result` / `error`)
+    async fn call(&self, method: &str, params: Value) -> Value {
+        let client = reqwest::Client::new();
+        let mut request = client
+            .post(&self.url)
+            .json(&json!({"jsonrpc": "1.0", "id": 1, "method": method, "params": params}));
+        if let Some((user, password)) = self.auth {
+            request = request.basic_auth(user, Some(password));
+        }
+        match request.send().await {
+            Ok(response) => response.json().await.unwrap_or(Value::Null),
+            Err(e) => json!({"error": {"message": e.to_string()}}),
+        }
+    }
+
+    async fn result(&self, method: &str, params: Value) -> Value {
+        let response = self.call(method, params.clone()).await;
+        match response.get("error") {
+            Some(error) if !error.is_null() => panic!("{method} {params}: {error}"),
+            _ => response.get("result").cloned().unwrap_or(Value::Null),
+        }
+    }
+
+    async fn wait_ready(&self) {
+        for _ in 0..120 {
+            let response = self.call("getblockchaininfo", json!([])).await;
+            if response.get("result").is_some_and(|r| !r.is_null()) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        panic!("{} did not become ready", self.url);
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn start_core(bitcoind: &Path, dir: &Path) -> Node {
+    let rpc_port = free_port();
+    let child = Command::new(bitcoind)
+        .args([
+            "-regtest",
+            "-listen=0",
+            "-server",
+            "-rpcuser=diff",
+            "-rpcpassword=diff",
+            "-fallbackfee=0.0001",
+        ])
+        .arg(format!("-datadir={}", dir.display()))
+        .arg(format!("-rpcport={rpc_port}"))
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("failed to start bitcoind");
+    Node {
+        child,
+        url: format!("http://127.0.0.1:{rpc_port}"),
+        auth: Some(("diff", "diff")),
+    }
+}
+
+fn start_blvm(dir: &Path) -> Node {
+    let rpc_port = free_port();
+    let child = Command::new(env!("CARGO_BIN_EXE_blvm"))
+        .args(["--network", "regtest", "--nolisten"])
+        .arg("--data-dir")
+        .arg(dir)
+        .arg("--rpc-addr")
+        .arg(format!("127.0.0.1:{rpc_port}"))
+        .arg("start")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to start blvm");
+    Node {
+        child,
+        url: format!("http://127.0.0.1:{rpc_port}"),
+        auth: None,
+    }
+}
+
+/// `submitblock` verdict: `None` = accepted, `Some(reason)` = rejected
+async fn submit_verdict(node: &Node, block_hex: &str) -> Option<String> {
+    let response = node.call("submitblock", json!([block_hex])).await;
+    if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+        return Some(error.to_string());
+    }
+    match response.get("result") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) if s == "duplicate" => None,
+        Some(other) => Some(other.to_string()),
+    }
+}
+
+async fn mempool_verdict(node: &Node, tx_hex: &str) -> bool {
+    let response = node.call("testmempoolaccept", json!([[tx_hex]])).await;
+    response["result"][0]["allowed"].as_bool().unwrap_or(false)
+}
+
+/// Flip `count` random bits, skipping the first `skip` bytes
+fn mutate(rng: &mut SimRng, bytes: &[u8], skip: usize, count: usize) -> Vec<u8> {
+    let mut out = bytes.to_vec();
+    if out.len() <= skip {
+        return out;
+    }
+    for _ in 0..count {
+        let i = skip + rng.below((out.len() - skip) as u64) as usize;
+        out[i] ^= 1 << rng.below(8);
+    }
+    out
+}
+
+#[tokio::test]
+async fn core_and_blvm_agree_on_blocks_and_transactions() {
+    let Some(bitcoind) = std::env::var_os("BITCOIND_PATH").map(PathBuf::from) else {
+        eprintln!("BITCOIND_PATH not set; skipping differential test against Bitcoin Core");
+        return;
+    };
+    let seed = env_u64("DIFF_SEED", 1);
+    let cases = env_u64("DIFF_CASES", 64);
+    let mut rng = SimRng::new(seed);
+
+    let core_dir = tempfile::TempDir::new().unwrap();
+    let blvm_dir = tempfile::TempDir::new().unwrap();
+    let core = start_core(&bitcoind, core_dir.path());
+    let blvm = start_blvm(blvm_dir.path());
+    core.wait_ready().await;
+    blvm.wait_ready().await;
+
+    // Core mines past coinbase maturity; blvm must accept every block Core accepted.
+    core.result("createwallet", json!(["diff"])).await;
+    let address = core.result("getnewaddress", json!([])).await;
+    core.result("generatetoaddress", json!([110, address]))
+        .await;
+    for height in 1..=110 {
+        let hash = core.result("getblockhash", json!([height])).await;
+        let block = core.result("getblock", json!([hash, 0])).await;
+        let verdict = submit_verdict(&blvm, block.as_str().unwrap()).await;
+        assert_eq!(verdict, None, "blvm rejected Core block at height {height}");
+    }
+
+    // Block mutations: same verdict from both nodes for every mutated candidate.
+    for case in 0..cases {
+        let candidate = core
+            .result("generateblock", json!([address, [], false]))
+            .await;
+        let hex = candidate["hex"].as_str().unwrap();
+        let bytes = hex::decode(hex).unwrap();
+        let flips = 1 + rng.below(4) as usize;
+        let mutated = hex::encode(mutate(&mut rng, &bytes, 0, flips));
+        let core_verdict = submit_verdict(&core, &mutated).await;
+        let blvm_verdict = submit_verdict(&blvm, &mutated).await;
+        assert_eq!(
+            core_verdict.is_none(),
+            blvm_verdict.is_none(),
+            "block divergence (seed {seed}, case {case}): core={core_verdict:?} blvm={blvm_verdict:?} block={mutated}"
+        );
+        // Keep both tips in step with a valid block
+        if core_verdict.is_some() {
+            assert_eq!(submit_verdict(&core, hex).await, None);
+            assert_eq!(submit_verdict(&blvm, hex).await, None);
+        }
+    }
+
+    // Transaction mutations: testmempoolaccept verdicts must match.
+    for case in 0..cases {
+        let to = core.result("getnewaddress", json!([])).await;
+        let to = to.as_str().unwrap();
+        let raw = core
+            .result("createrawtransaction", json!([[], { to: 0.1 }]))
+            .await;
+        let funded = core.result("fundrawtransaction", json!([raw])).await;
+        let signed = core
+            .result("signrawtransactionwithwallet", json!([funded["hex"]]))
+            .await;
+        let bytes = hex::decode(signed["hex"].as_str().unwrap()).unwrap();
+        // Leave the version field intact so most cases reach script/amount checks
+        let flips = 1 + rng.below(2) as usize;
+        let mutated = hex::encode(mutate(&mut rng, &bytes, 4, flips));
+        let core_ok = mempool_verdict(&core, &mutated).await;
+        let blvm_ok = mempool_verdict(&blvm, &mutated).await;
+        assert_eq!(
+            core_ok, blvm_ok,
+            "transaction divergence (seed {seed}, case {case}): core={core_ok} blvm={blvm_ok} tx={mutated}"
+        );
+    }
+}
@@ -91,6 +91,18 @@ fn test_config_show_subcommand() {
     cmd.assert().success().stdout(predicate::str::contains("["));
 }
 
+/// Test BLVM_CONFIG__* variables reach the resolved config
+#[test]
+fn test_config_show_env_keys() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.env("BLVM_CONFIG__MAX_OUTBOUND_PEERS", "7")
+        .arg("config")
+        .arg("show");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("max_outbound_peers = 7"));
+}
+
 /// Test config validate subcommand (no file)
 #[test]
 fn test_config_validate_no_file() {
@@ -206,3 +218,729 @@ fn test_start_subcommand() {
     // Should try to start node (will fail, but parsing should work)
     let _ = cmd.assert();
 }
+
+/// Test db stats reports data directory sizes without a running node
+#[test]
+fn test_db_stats_offline() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(data_dir.path().join("blocks")).unwrap();
+    std::fs::write(data_dir.path().join("blocks/blk00000.dat"), [0u8; 2048]).unwrap();
+    std::fs::write(data_dir.path().join("blocks/rev00000.dat"), [0u8; 512]).unwrap();
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("db")
+        .arg("stats")
+        .arg("--rpc-addr")
+        .arg("127.0.0.1:1");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Database Statistics"))
+        .stdout(predicate::str::contains("blocks/"))
+        .stdout(predicate::str::contains("Undo files: 1"));
+}
+
+/// Test module scaffold writes a manifest offline
+#[test]
+fn test_module_scaffold() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = dir.path().join("my-indexer");
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("module")
+        .arg("scaffold")
+        .arg("my-indexer")
+        .arg("--output")
+        .arg(&output);
+    cmd.assert().success();
+
+    assert!(output.join("module.toml").exists());
+    assert!(!output.join("src").exists());
+
+    // Refuses to overwrite an existing project
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("module")
+        .arg("scaffold")
+        .arg("my-indexer")
+        .arg("--output")
+        .arg(&output);
+    cmd.assert().failure();
+}
+
+/// Test module status shows data directory usage without a reachable node
+#[test]
+fn test_module_status_offline() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let modules = data_dir.path().join("modules");
+    let module_dir = modules.join("my-indexer");
+    std::fs::create_dir_all(&module_dir).unwrap();
+    std::fs::write(module_dir.join("index.db"), vec![0u8; 4096]).unwrap();
+    let config = data_dir.path().join("blvm.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[modules]\nenabled = false\nmodules_dir = {:?}\ndata_dir = {:?}\nsocket_dir = {:?}\n",
+            modules.join("installed"),
+            modules,
+            modules.join("sockets")
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--config")
+        .arg(&config)
+        .arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("module")
+        .arg("--rpc-addr")
+        .arg(free_local_addr().to_string())
+        .arg("status");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("my-indexer:"))
+        .stdout(predicate::str::contains("Loaded: unknown"))
+        .stdout(predicate::str::contains("Data: "));
+}
+
+/// A loopback address with a port that was free a moment ago
+fn free_local_addr() -> std::net::SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+/// Test start --dry-run reports checks and exits without starting the node
+#[test]
+fn test_start_dry_run() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("--listen-addr")
+        .arg(free_local_addr().to_string())
+        .arg("--rpc-addr")
+        .arg(free_local_addr().to_string())
+        .arg("start")
+        .arg("--dry-run");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Startup Dry Run"))
+        .stdout(predicate::str::contains("is writable"))
+        .stdout(predicate::str::contains("No saved fee estimates"))
+        .stdout(predicate::str::contains("No chainstate store yet"))
+        .stdout(predicate::str::contains("P2P port 127.0.0.1:"))
+        .stdout(predicate::str::contains("is available"));
+}
+
+/// Test start --dry-run reads the chainstate headers and fails on a damaged store
+#[test]
+fn test_start_dry_run_damaged_chainstate() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let store = data_dir.path().join("chainstate");
+    std::fs::create_dir(&store).unwrap();
+    std::fs::write(store.join("CURRENT"), "MANIFEST-000002\n").unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("--listen-addr")
+        .arg(free_local_addr().to_string())
+        .arg("--rpc-addr")
+        .arg(free_local_addr().to_string())
+        .arg("start")
+        .arg("--dry-run");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Chainstate rocksdb store"))
+        .stdout(predicate::str::contains(
+            "looks damaged: CURRENT names MANIFEST-000002",
+        ));
+    // The check only reads: nothing was created or repaired
+    assert!(!store.join("MANIFEST-000002").exists());
+}
+
+/// Test start --dry-run fails when a port is already taken
+#[test]
+fn test_start_dry_run_port_in_use() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let rpc_addr = taken.local_addr().unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("--listen-addr")
+        .arg(free_local_addr().to_string())
+        .arg("--rpc-addr")
+        .arg(rpc_addr.to_string())
+        .arg("start")
+        .arg("--dry-run");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains(format!(
+            "RPC port {rpc_addr} is not available"
+        )))
+        .stderr(predicate::str::contains("check(s) failed"));
+    drop(taken);
+}
+
+/// Test compare-rpc diffs two endpoints (identical connection errors compare equal)
+#[test]
+fn test_compare_rpc_same_endpoint() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("compare-rpc")
+        .arg("getblockcount")
+        .arg("--left")
+        .arg("127.0.0.1:1")
+        .arg("--right")
+        .arg("127.0.0.1:1")
+        .arg("--ignore")
+        .arg("time");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Results match"));
+}
+
+/// Test chain params / genesis work offline for the selected network
+#[test]
+fn test_chain_params_offline() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--network")
+        .arg("regtest")
+        .arg("chain")
+        .arg("params");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Magic: fabfb5da"))
+        .stdout(predicate::str::contains("every 150 blocks"));
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--network")
+        .arg("mainnet")
+        .arg("chain")
+        .arg("genesis");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert().success().stdout(predicate::str::contains(
+        "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+    ));
+}
+
+/// Test chain next-difficulty offline mode (Core pow_tests vector: 0x1d00ffff -> 0x1d00d86a)
+#[test]
+fn test_chain_next_difficulty_offline() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--network")
+        .arg("mainnet")
+        .arg("chain")
+        .arg("next-difficulty")
+        .arg("--bits")
+        .arg("1d00ffff")
+        .arg("--first-time")
+        .arg("1261130161")
+        .arg("--last-time")
+        .arg("1262152739");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Next:    1d00d86a"));
+}
+
+#[test]
+fn test_decode_header_and_script() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("decode").arg("header").arg(
+        "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+    );
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert().success().stdout(predicate::str::contains(
+        "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+    ));
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("decode")
+        .arg("script")
+        .arg("76a91489abcdefabbaabbaabbaabbaabbaabbaabbaabba88ac");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"type\": \"pubkeyhash\""));
+}
+
+#[test]
+fn test_proof_verify_offline() {
+    // Genesis header + partial merkle tree revealing its only transaction
+    let proof = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c01000000013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0101";
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("proof").arg("verify").arg("--offline").arg(proof);
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert().success().stdout(predicate::str::contains(
+        "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b (index 0)",
+    ));
+}
+
+#[test]
+fn test_db_invalidate_requires_height() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("db").arg("invalidate").arg("--yes");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--height"));
+}
+
+#[test]
+fn test_analyze_block_genesis() {
+    let genesis = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("genesis.block");
+    std::fs::write(&file, hex::decode(genesis).unwrap()).unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--network")
+        .arg("mainnet")
+        .arg("analyze-block")
+        .arg(&file);
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("[PASS] merkle-root"));
+}
+
+#[test]
+fn test_events_tail_offline() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        data_dir.path().join("events.jsonl"),
+        concat!(
+            r#"{"time":1231006505,"kind":"node.start","message":"Node starting"}"#,
+            "\n",
+            r#"{"time":1231006565,"kind":"chain.tip","message":"New tip at height 1","fields":{"height":1}}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("events")
+        .arg("tail")
+        .arg("--kind")
+        .arg("chain");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2009-01-03 18:16:05 chain.tip        New tip at height 1 height=1",
+        ))
+        .stdout(predicate::str::contains("node.start").not());
+}
+
+#[test]
+fn test_peers_history_offline() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        data_dir.path().join("events.jsonl"),
+        concat!(
+            r#"{"time":1231006505,"kind":"peer.connect","message":"Connected 1.2.3.4:8333 (outbound)"}"#,
+            "\n",
+            r#"{"time":1231006565,"kind":"chain.tip","message":"New tip at height 1"}"#,
+            "\n",
+            r#"{"time":1231006625,"kind":"peer.ban","message":"Banned 5.6.7.8/32"}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("peers")
+        .arg("history")
+        .arg("--kind")
+        .arg("ban");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("ban        Banned 5.6.7.8/32"))
+        .stdout(predicate::str::contains("Connected").not());
+}
+
+#[test]
+fn test_sync_history_json_offline() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    std::fs::write(
+        data_dir.path().join("sync-history.jsonl"),
+        format!(
+            "{{\"time\":{},\"blocks\":100,\"headers\":900,\"progress\":0.1}}\n{{\"time\":{},\"blocks\":123,\"headers\":900,\"progress\":0.12}}\n",
+            now - 3600,
+            now - 60
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("sync")
+        .arg("--history")
+        .arg("json");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"blocks\": 123"))
+        .stdout(predicate::str::contains("\"window_secs\": 86400"));
+}
+
+#[test]
+fn test_fleet_exec_rejects_unknown_command() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("fleet").arg("exec").arg("shutdown");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown fleet command 'shutdown'"));
+}
+
+#[test]
+fn test_peers_persist_add_list_remove() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let config = dir.path().join("blvm.toml");
+    std::fs::write(&config, "# test node\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--config")
+        .arg(&config)
+        .arg("peers")
+        .arg("persist")
+        .arg("add")
+        .arg("10.0.0.2:8333")
+        .arg("--label")
+        .arg("backup");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert().success().stdout(predicate::str::contains(
+        "Added persistent peer 10.0.0.2:8333",
+    ));
+    let content = std::fs::read_to_string(&config).unwrap();
+    assert!(content.starts_with("# test node\n"));
+    assert!(content.contains("[[persistent_peer]]"));
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--config")
+        .arg(&config)
+        .arg("peers")
+        .arg("persist")
+        .arg("list");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("10.0.0.2:8333"))
+        .stdout(predicate::str::contains("backup"));
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--config")
+        .arg(&config)
+        .arg("peers")
+        .arg("persist")
+        .arg("remove")
+        .arg("10.0.0.2:8333");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert().success();
+    assert_eq!(std::fs::read_to_string(&config).unwrap(), "# test node\n");
+}
+
+#[test]
+fn test_bench_hash_reports_cpu_features() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("bench").arg("hash").arg("--iterations").arg("1");
+    cmd.timeout(std::time::Duration::from_secs(30));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("SHA-256 backend:"))
+        .stdout(predicate::str::contains("merkle nodes"));
+}
+
+/// Test wait-sync gives up with exit code 1 once its timeout passes
+#[test]
+fn test_wait_sync_times_out() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("wait-sync")
+        .arg("--height")
+        .arg("100")
+        .arg("--timeout")
+        .arg("1")
+        .arg("--rpc-addr")
+        .arg("127.0.0.1:1");
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Timed out after 1s waiting for height 100",
+    ));
+}
+
+/// Test replica status without a status file explains where it comes from
+#[test]
+fn test_replica_status_without_state() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir").arg(data_dir.path()).arg("replica");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No replica status"));
+}
+
+/// Test db verify refuses to run without --online
+#[test]
+fn test_db_verify_requires_online() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("db").arg("verify");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --online"));
+}
+
+/// Test chain precious rejects a malformed hash before contacting the node
+#[test]
+fn test_chain_precious_invalid_hash() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("chain").arg("precious").arg("xyz");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid block hash"));
+}
+
+/// Test stats scripts without collector state points at the config and --from
+#[test]
+fn test_stats_scripts_without_state() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("stats")
+        .arg("scripts");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No script statistics"));
+}
+
+/// Test opreturn scan rejects unknown protocol names before contacting the node
+#[test]
+fn test_opreturn_scan_unknown_protocol() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("opreturn")
+        .arg("scan")
+        .arg("--from")
+        .arg("0")
+        .arg("--protocol")
+        .arg("dogecoin");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown protocol 'dogecoin'"));
+}
+
+/// Test mempool histogram fails cleanly when the node is unreachable
+#[test]
+fn test_mempool_histogram_unreachable() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("mempool")
+        .arg("histogram")
+        .arg("--rpc-addr")
+        .arg("127.0.0.1:1");
+    cmd.assert().failure();
+}
+
+/// Test stats fees without an archive points at the config section
+#[test]
+fn test_stats_fees_without_archive() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("stats")
+        .arg("fees");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No fee history"));
+}
+
+/// Test tx analyze with explicit prevouts reports the fee and flags a dust output
+#[test]
+fn test_tx_analyze_with_prevouts() {
+    let tx = "0200000000010101010101010101010101010101010101010101010101010101010101010101010000000000fdffffff0250c30000000000001600140707070707070707070707070707070707070707c800000000000000160014070707070707070707070707070707070707070702483030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030303030302102020202020202020202020202020202020202020202020202020202020202020200000000";
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("tx")
+        .arg("analyze")
+        .arg(tx)
+        .arg("--prevout")
+        .arg("51000:00140707070707070707070707070707070707070707");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("fee:      800 sat"))
+        .stdout(predicate::str::contains("output 1: 200 sat is dust"));
+}
+
+/// Test peers policy works offline and reports when nothing was filtered
+#[test]
+fn test_peers_policy_without_counters() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("peers")
+        .arg("policy");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No peers filtered"));
+}
+
+/// Test an invalid blvm-only setting fails the command instead of being ignored
+#[test]
+fn test_invalid_extra_config_fails() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let config = data_dir.path().join("blvm.toml");
+    std::fs::write(&config, "[peer_policy]\nmin_protocol_version = \"x\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("--config")
+        .arg(&config)
+        .arg("peers")
+        .arg("policy");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid blvm settings"));
+}
+
+/// Test front-only methods fail clearly when no [rpc_front] listener is configured
+#[test]
+fn test_front_method_without_front() {
+    let data_dir = tempfile::TempDir::new().unwrap();
+    let config = data_dir.path().join("blvm.toml");
+    std::fs::write(&config, "").unwrap();
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--config")
+        .arg(&config)
+        .args(["rpc", "getrpcstats"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("not by blvm-node"));
+
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.arg("--config").arg(&config).arg("tasks");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "dumptasks is answered by the blvm RPC front",
+    ));
+}
+
+/// Test that --rpc-insecure is rejected without --rpc-tls
+#[test]
+fn test_rpc_insecure_requires_tls() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.args(["--rpc-insecure", "status"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--rpc-tls"));
+}
+
+/// Test rpc --batch rejects an unpaired method before contacting the node
+#[test]
+fn test_rpc_batch_requires_pairs() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.args(["rpc", "--batch", "getblockcount", "[]", "getblockhash"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("METHOD PARAMS pairs"));
+}
+
+/// Test that --format only accepts json, table or csv
+#[test]
+fn test_format_rejects_unknown_value() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.args(["status", "--format", "yaml"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("json"))
+        .stderr(predicate::str::contains("csv"));
+}
+
+/// Test that stop exits 1 when no node is listening
+#[test]
+fn test_stop_without_node() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.args(["stop", "--rpc-addr", "127.0.0.1:1", "--timeout", "1"]);
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains("Could not stop node"));
+}
+
+/// Test that `rpc -named` rejects a parameter without a value before contacting the node
+#[test]
+fn test_rpc_named_requires_key_value() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.args(["rpc", "-named", "getblockhash", "height"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Expected key=value"));
+}
+
+/// Test that `-named` is still recognized after global options that take a value, including a
+/// value that reads `rpc`
+#[test]
+fn test_rpc_named_after_global_options() {
+    let dir = tempfile::TempDir::new().unwrap();
+    for global in [["--rpc-addr", "127.0.0.1:1"], ["--data-dir", "rpc"]] {
+        let mut cmd = Command::cargo_bin("blvm").unwrap();
+        cmd.current_dir(dir.path())
+            .args(global)
+            .args(["rpc", "-named", "getblockhash", "height"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Expected key=value"));
+    }
+}
+
+/// Test that `--rpc-retries` retries a refused connection before giving up
+#[test]
+fn test_rpc_retries_reported() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    cmd.args([
+        "--rpc-retries",
+        "2",
+        "rpc",
+        "getblockcount",
+        "--rpc-addr",
+        "127.0.0.1:1",
+    ]);
+    cmd.timeout(std::time::Duration::from_secs(10));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("after 3 attempts"));
+}
+
+/// Test that `version --json` reports build and capability information
+#[test]
+fn test_version_json() {
+    let mut cmd = Command::cargo_bin("blvm").unwrap();
+    let output = cmd.args(["version", "--json"]).output().unwrap();
+    assert!(output.status.success());
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+    assert!(
+        info["features"]
+            .as_array()
+            .unwrap()
+            .contains(&"bip158".into())
+    );
+    assert!(info["rpc_api_version"].is_u64());
+    assert_eq!(info["networks"].as_array().unwrap().len(), 4);
+}